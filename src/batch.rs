@@ -0,0 +1,200 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Batch rendering: views described by a TOML job file are rendered to image files (in a hidden window), without
+// starting the GUI. Run with:
+//
+//     $ projections --batch <job file>
+//
+// Limitation: there is no headless (e.g. EGL surfaceless) rendering. The OpenGL context is created for a hidden window
+// by winit, which needs a display server (X11 or Wayland) also in the batch mode, and all rendering code draws through
+// `glium::Display`. On a headless machine, run the program under a virtual display server:
+//
+//     $ xvfb-run -a projections --batch <job file>
+//
+// Job file format:
+//
+//     body = "earth"                     # optional; or "moon", "mars", "mercury"
+//
+//     [[job]]
+//     projection = "orthographic"        # as in session files (see src/session.rs) or the name of a plugin projection
+//     center = [20.0, 50.0]              # optional; longitude and latitude (degrees)
+//     zoom = 1.5                         # optional
+//     roll = 0.0                         # optional; degrees
+//     view_mode = "vector_map"           # optional; or "globe_texture" (default)
+//     params = { ellipsoidal = 1.0 }     # optional; keyed by the names of the projection's parameters, as in sessions
+//     width = 2000                       # pixels (at most `views::MAX_OFFSCREEN_SIZE`)
+//     height = 2000
+//     output = "maps/europe.png"         # PNG or JPEG (chosen by the extension)
+//
+// Images exceeding the max. texture size are rendered in tiles (see `ViewBase::render_offscreen_tiled`). The program
+// exits with code 0 if all jobs succeeded, 1 if any of them failed, and 2 if the job file could not be loaded.
+//
+
+use crate::config::Config;
+use crate::data;
+use crate::export;
+use crate::runner;
+use crate::session;
+use crate::views;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+#[derive(Copy, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JobViewMode { GlobeTexture, VectorMap }
+
+impl Default for JobViewMode {
+    fn default() -> JobViewMode { JobViewMode::GlobeTexture }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Job {
+    projection: String,
+    center: Option<[f64; 2]>,
+    zoom: Option<f64>,
+    #[serde(default)]
+    roll: f64,
+    #[serde(default)]
+    view_mode: JobViewMode,
+    #[serde(default)]
+    params: BTreeMap<String, f64>,
+    width: u32,
+    height: u32,
+    output: String
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobFile {
+    body: Option<String>,
+    #[serde(rename = "job", default)]
+    jobs: Vec<Job>
+}
+
+/// Renders all jobs of the job file at `path`; returns the program's exit code.
+pub fn run(path: &str, config: &Config) -> i32 {
+    let job_file: JobFile = match std::fs::read_to_string(path).map_err(|e| e.to_string())
+        .and_then(|contents| toml::from_str(&contents).map_err(|e| e.to_string()))
+    {
+        Ok(job_file) => job_file,
+        Err(e) => {
+            eprintln!("Failed to load batch job file {}: {}", path, e);
+            return 2;
+        }
+    };
+
+    let hidden_display = match runner::create_hidden_display() {
+        Ok(hidden_display) => hidden_display,
+        Err(e) => {
+            eprintln!("Failed to initialize display: {}", e);
+            eprintln!("Batch rendering needs a display server (headless rendering is not supported); \
+                on a headless machine run it under Xvfb, e.g.:");
+            eprintln!("    xvfb-run -a projections --batch {}", path);
+            return 2;
+        }
+    };
+    let display = &hidden_display.display;
+
+    let mut program_data = data::ProgramData::new(config, display);
+    if let Some(key) = &job_file.body {
+        let result = data::BODIES.iter().position(|body| body.key == key)
+            .ok_or_else(|| format!("unknown body: {}", key))
+            .and_then(|index| program_data.set_body(index, display));
+        if let Err(e) = result {
+            eprintln!("Failed to set body: {}", e);
+            return 2;
+        }
+    }
+
+    let mut num_failed = 0;
+    for (idx, job) in job_file.jobs.iter().enumerate() {
+        match render(job, &mut program_data, &hidden_display.renderer, display) {
+            Ok(()) => println!("Rendered {}.", job.output),
+            Err(e) => {
+                eprintln!("Job {} ({}) failed: {}", idx + 1, job.output, e);
+                num_failed += 1;
+            }
+        }
+    }
+    println!("{} of {} job(s) succeeded.", job_file.jobs.len() - num_failed, job_file.jobs.len());
+
+    if num_failed == 0 { 0 } else { 1 }
+}
+
+/// Creates the job's view, saves its image and removes the view.
+fn render(
+    job: &Job,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) -> Result<(), String> {
+    if job.width == 0 || job.height == 0 {
+        return Err("image size has to be positive".into());
+    }
+    if job.width > views::MAX_OFFSCREEN_SIZE || job.height > views::MAX_OFFSCREEN_SIZE {
+        let max_size = views::MAX_OFFSCREEN_SIZE;
+        return Err(format!("image size is limited to {}×{} pixels", max_size, max_size));
+    }
+
+    let unique_id = session::create_default_view(&job.projection, program_data, renderer, display)?;
+    let result = match program_data.all_views_mut().find(|view| view.unique_id() == unique_id) {
+        Some(view) => configure(job, view)
+            .and_then(|_| export::export_view_image(&job.output, view, job.width, job.height)),
+        None => Err("failed to create view".into())
+    };
+    program_data.remove_view(unique_id);
+
+    result
+}
+
+/// Applies the job's settings to `view`; fails (before changing it) if any of them is invalid.
+fn configure(job: &Job, view: &mut views::ViewBase) -> Result<(), String> {
+    if let Some([lon, lat]) = job.center {
+        if !(lon.is_finite() && lat.is_finite() && lat.abs() <= 90.0) {
+            return Err("invalid center".into());
+        }
+    }
+    if let Some(zoom) = job.zoom {
+        if !(zoom.is_finite() && zoom > 0.0) { return Err("zoom has to be positive".into()); }
+    }
+    if !job.roll.is_finite() {
+        return Err("invalid roll".into());
+    }
+    let mut params = vec![];
+    for (name, value) in &job.params {
+        let idx = view.projection_params().iter().position(|param| param.uniform_name == name.as_str())
+            .ok_or_else(|| format!("unknown parameter: {}", name))?;
+        if !value.is_finite() {
+            return Err(format!("invalid value of parameter {}", name));
+        }
+        params.push((idx, *value));
+    }
+
+    if let Some([lon, lat]) = job.center {
+        view.center_on(cgmath::Deg(lon), cgmath::Deg(lat));
+    }
+    if let Some(zoom) = job.zoom {
+        view.zoom_by(zoom / view.zoom());
+    }
+    view.set_roll(cgmath::Deg(job.roll).into());
+    view.set_view_mode(match job.view_mode {
+        JobViewMode::GlobeTexture => views::ViewMode::GlobeTexture,
+        JobViewMode::VectorMap => views::ViewMode::VectorMap
+    });
+
+    for (idx, value) in params {
+        view.set_projection_param_value(idx, value);
+    }
+
+    Ok(())
+}
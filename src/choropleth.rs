@@ -0,0 +1,178 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Choropleth maps: polygons of a shapefile filled with colors of classes of a numeric DBF attribute.
+//
+// Values are divided into classes with equal intervals or quantiles (classes with equal numbers of features);
+// each class gets an equally spaced color of a colormap. Polygons are rasterized into an equirectangular image
+// (transparent outside of them and for features without a value), drawn by views over the map.
+//
+
+use crate::geometry;
+use crate::geojson::Polyline;
+use crate::scalar_field::Colormap;
+use shapefile::dbase::FieldValue;
+
+/// Width of the rasterized image; its height is half of that.
+const IMAGE_WIDTH: u32 = 4096;
+
+pub const MAX_CLASSES: usize = 10;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Classification {
+    EqualInterval,
+    Quantile
+}
+
+impl Classification {
+    pub const ALL: [Classification; 2] = [Classification::EqualInterval, Classification::Quantile];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Classification::EqualInterval => "equal interval",
+            Classification::Quantile => "quantile"
+        }
+    }
+}
+
+/// Polygon features of a shapefile with their numeric attributes.
+pub struct PolygonLayer {
+    /// Names of numeric attributes (sorted).
+    pub fields: Vec<String>,
+    /// Rings of each feature and its values of `fields` (`None`: missing).
+    features: Vec<(Vec<Polyline>, Vec<Option<f64>>)>
+}
+
+/// Classes of a choropleth map.
+pub struct Legend {
+    /// Name of the attribute.
+    pub field: String,
+    /// Value range and color of each class.
+    pub classes: Vec<([f64; 2], [u8; 3])>
+}
+
+/// Loads polygons and their numeric attributes from a shapefile.
+pub fn load(path: &str) -> Result<PolygonLayer, String> {
+    let mut reader = shapefile::Reader::from_path(path).map_err(|e| e.to_string())?;
+
+    let mut records = vec![];
+    for shape_record in reader.iter_shapes_and_records() {
+        let (shape, record) = shape_record.map_err(|e| e.to_string())?;
+        let rings: Vec<Polyline> = match shape {
+            shapefile::Shape::Polygon(polygon) => polygon.rings().iter()
+                .map(|ring| ring.points().iter().map(|point| [point.x, point.y]).collect())
+                .collect(),
+            _ => continue
+        };
+        let values: Vec<(String, f64)> = record.into_iter()
+            .filter_map(|(name, value)| numeric_value(&value).map(|value| (name, value)))
+            .collect();
+        records.push((rings, values));
+    }
+    if records.is_empty() {
+        return Err("no polygons found".into());
+    }
+
+    let mut fields: Vec<String> = records.iter().flat_map(|(_, values)| values.iter().map(|(name, _)| name.clone()))
+        .collect();
+    fields.sort();
+    fields.dedup();
+    if fields.is_empty() {
+        return Err("no numeric attributes found".into());
+    }
+
+    let features = records.into_iter().map(|(rings, values)| {
+        let field_values = fields.iter()
+            .map(|field| values.iter().find(|(name, _)| name == field).map(|(_, value)| *value))
+            .collect();
+        (rings, field_values)
+    }).collect();
+
+    Ok(PolygonLayer{ fields, features })
+}
+
+fn numeric_value(value: &FieldValue) -> Option<f64> {
+    match value {
+        FieldValue::Numeric(value) => *value,
+        FieldValue::Float(value) => value.map(|value| value as f64),
+        FieldValue::Integer(value) => Some(*value as f64),
+        FieldValue::Double(value) | FieldValue::Currency(value) => Some(*value),
+        _ => None
+    }
+}
+
+impl PolygonLayer {
+    /// Returns the equirectangular image of polygons colored by classes of the `field_idx`-th attribute,
+    /// and the legend.
+    pub fn render(
+        &self,
+        field_idx: usize,
+        classification: Classification,
+        num_classes: usize,
+        colormap: Colormap,
+        opacity: f32
+    ) -> Result<(image::RgbaImage, Legend), String> {
+        let values: Vec<f64> = self.features.iter()
+            .filter_map(|(_, values)| values[field_idx])
+            .filter(|value| value.is_finite())
+            .collect();
+        if values.is_empty() {
+            return Err(format!("no values of {}", self.fields[field_idx]));
+        }
+        let breaks = class_breaks(&values, classification, num_classes.max(1).min(MAX_CLASSES));
+        let num_classes = breaks.len() - 1;
+        let colors: Vec<[u8; 3]> = (0..num_classes)
+            .map(|idx| colormap.color(if num_classes > 1 { idx as f32 / (num_classes - 1) as f32 } else { 0.5 }))
+            .collect();
+
+        let (width, height) = (IMAGE_WIDTH, IMAGE_WIDTH / 2);
+        let alpha = (opacity.max(0.0).min(1.0) * 255.0).round() as u8;
+        let mut image = image::RgbaImage::new(width, height);
+        for (rings, values) in &self.features {
+            let value = match values[field_idx] {
+                Some(value) if value.is_finite() => value,
+                _ => continue
+            };
+            let class = breaks[1..].iter().position(|upper| value <= *upper).unwrap_or(num_classes - 1);
+            let [r, g, b] = colors[class];
+            geometry::fill_rings(rings, width, height, |column, row| {
+                image.put_pixel(column, row, image::Rgba([r, g, b, alpha]));
+            });
+        }
+
+        let legend = Legend{
+            field: self.fields[field_idx].clone(),
+            classes: breaks.windows(2).zip(colors).map(|(range, color)| ([range[0], range[1]], color)).collect()
+        };
+
+        Ok((image, legend))
+    }
+}
+
+/// Returns the bounds of classes of `values` (the first is the min. value, the last the max. one); there may be
+/// fewer than `num_classes` classes if quantiles coincide.
+fn class_breaks(values: &[f64], classification: Classification, num_classes: usize) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+
+    let mut breaks: Vec<f64> = match classification {
+        Classification::EqualInterval => (0..=num_classes)
+            .map(|idx| min + (max - min) * idx as f64 / num_classes as f64)
+            .collect(),
+
+        Classification::Quantile => (0..=num_classes)
+            .map(|idx| sorted[(idx * (sorted.len() - 1) + num_classes / 2) / num_classes])
+            .collect()
+    };
+    breaks.dedup();
+    if breaks.len() < 2 { breaks.push(max); }
+
+    breaks
+}
@@ -0,0 +1,408 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Program configuration, loaded at startup from "projections/config.toml" in the user's configuration directory
+// (`$XDG_CONFIG_HOME` or `~/.config`; `%APPDATA%` on Windows). All entries are optional:
+//
+//     globe_texture = "data/world.topo.bathy.200412.3x8192x4096.jpg"   # equirectangular image
+//     night_lights = "data/BlackMarble_2016_3km.jpg"                    # equirectangular image; not used if missing
+//     coastline = "data/ne_10m_coastline/ne_10m_coastline.shp"
+//     rivers = "data/ne_10m_rivers_lake_centerlines/ne_10m_rivers_lake_centerlines.shp"  # not used if missing
+//     lakes = "data/ne_10m_lakes/ne_10m_lakes.shp"                     # not used if missing
+//     places = "data/ne_10m_populated_places/ne_10m_populated_places.shp"  # not used if missing
+//     geonames = "data/cities15000.txt"                                # GeoNames table for the place search;
+//                                                                      # not used if missing
+//     plate_boundaries = "data/PB2002_steps.json"                      # GeoJSON; not used if missing
+//     time_zones = "data/timezones.geojson.zip"                        # GeoJSON (may be zipped); not used if missing
+//     msaa_samples = 8                                                 # 1: no multisampling
+//     units = "km"                                                     # distances: "km", "nmi", "mi"
+//     tile_url = "https://tile.openstreetmap.org/{z}/{x}/{y}.png"      # XYZ web tile source (see "View/Web tiles")
+//
+//     [window]                                                         # initial size in logical pixels
+//     width = 1280
+//     height = 768
+//
+//     [graticule]
+//     visible = true
+//     step = 10.0                                                      # degrees
+//     labels = "off"                                                   # "off", "horizontal", "along_lines"
+//
+//     [[base_maps]]                                                    # may be repeated; selectable per view instead
+//     name = "bathymetry"                                              # of globe_texture; not used if missing
+//     path = "data/base_maps/bathymetry.jpg"
+//
+//     [bodies.moon]                                                    # also "mars", "mercury"; see "View/Body"
+//     texture = "data/moon/texture.jpg"                                # equirectangular image
+//     outlines = "data/moon/outlines.shp"                              # drawn like the coastline; not used if missing
+//
+//     [vector_map]
+//     land = "data/ne_10m_land/ne_10m_land.shp"                        # polygons filled in the vector map mode
+//     land_color = [0.94, 0.91, 0.82]                                  # RGB, 0-1
+//     ocean_color = [0.67, 0.8, 0.9]
+//
+// Relative data paths which do not exist in the current directory are also looked up in the user's data directory
+// (see `resolve_data_path`). The data paths can be overridden with command-line arguments (see `main.rs`). The units can also be changed
+// at runtime ("View/Units"); the choice is then stored in the configuration file (only the `units` entry is changed).
+//
+
+use crate::{data, views};
+use serde::{Deserialize, Serialize};
+
+/// Directory (in the user's configuration and data directories) with the program's files.
+const CONFIG_SUBDIR: &str = "projections";
+
+const CONFIG_FILE: &str = "config.toml";
+
+const MAX_MSAA_SAMPLES: u32 = 16;
+
+#[derive(Copy, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraticuleLabelsConfig { Off, Horizontal, AlongLines }
+
+impl GraticuleLabelsConfig {
+    pub fn graticule_labels(&self) -> views::GraticuleLabels {
+        match self {
+            GraticuleLabelsConfig::Off => views::GraticuleLabels::Off,
+            GraticuleLabelsConfig::Horizontal => views::GraticuleLabels::Horizontal,
+            GraticuleLabelsConfig::AlongLines => views::GraticuleLabels::AlongLines
+        }
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitsConfig { Km, Nmi, Mi }
+
+impl UnitsConfig {
+    pub fn distance_unit(&self) -> data::DistanceUnit {
+        match self {
+            UnitsConfig::Km => data::DistanceUnit::Kilometer,
+            UnitsConfig::Nmi => data::DistanceUnit::NauticalMile,
+            UnitsConfig::Mi => data::DistanceUnit::Mile
+        }
+    }
+
+    pub fn from_distance_unit(unit: data::DistanceUnit) -> UnitsConfig {
+        match unit {
+            data::DistanceUnit::Kilometer => UnitsConfig::Km,
+            data::DistanceUnit::NauticalMile => UnitsConfig::Nmi,
+            data::DistanceUnit::Mile => UnitsConfig::Mi
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub width: f64,
+    pub height: f64
+}
+
+impl Default for WindowConfig {
+    fn default() -> WindowConfig {
+        WindowConfig{ width: 1280.0, height: 768.0 }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GraticuleConfig {
+    pub visible: bool,
+    /// Spacing of graticule lines (degrees).
+    pub step: f64,
+    /// Labels of new views.
+    pub labels: GraticuleLabelsConfig
+}
+
+impl Default for GraticuleConfig {
+    fn default() -> GraticuleConfig {
+        GraticuleConfig{ visible: true, step: data::DEFAULT_GRATICULE_STEP.0, labels: GraticuleLabelsConfig::Off }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VectorMapConfig {
+    /// Shapefile with land polygons; not used if missing.
+    pub land: String,
+    pub land_color: [f32; 3],
+    pub ocean_color: [f32; 3]
+}
+
+impl Default for VectorMapConfig {
+    fn default() -> VectorMapConfig {
+        VectorMapConfig{
+            land: data::DEFAULT_LAND_FILE.to_string(),
+            land_color: data::DEFAULT_LAND_COLOR,
+            ocean_color: data::DEFAULT_OCEAN_COLOR
+        }
+    }
+}
+
+/// Equirectangular image selectable as a view's base map.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BaseMapConfig {
+    pub name: String,
+    pub path: String
+}
+
+/// Data of a body other than the Earth (whose data are given by `Config::globe_texture` and `Config::coastline`).
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BodyConfig {
+    /// Default: "data/<body>/texture.jpg".
+    pub texture: Option<String>,
+    /// Shapefile with outlines (e.g. of named features); default: "data/<body>/outlines.shp".
+    pub outlines: Option<String>
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BodiesConfig {
+    pub moon: BodyConfig,
+    pub mars: BodyConfig,
+    pub mercury: BodyConfig
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub globe_texture: String,
+    pub night_lights: String,
+    pub coastline: String,
+    pub rivers: String,
+    pub lakes: String,
+    pub places: String,
+    /// GeoNames table of places added to the place search (see `gazetteer`).
+    pub geonames: String,
+    /// Tectonic plate boundaries (see `plate_boundaries`).
+    pub plate_boundaries: String,
+    /// Time-zone boundaries (see `time_zones`).
+    pub time_zones: String,
+    /// Number of samples per pixel of views' draw buffers.
+    pub msaa_samples: u32,
+    /// Unit of shown distances.
+    pub units: UnitsConfig,
+    /// URL template of the XYZ web tile source, with `{z}`, `{x}`, `{y}` replaced by the tile's zoom level,
+    /// column and row.
+    pub tile_url: String,
+    pub window: WindowConfig,
+    pub graticule: GraticuleConfig,
+    pub vector_map: VectorMapConfig,
+    pub base_maps: Vec<BaseMapConfig>,
+    pub bodies: BodiesConfig
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config{
+            globe_texture: data::DEFAULT_GLOBE_TEXTURE_FILE.to_string(),
+            night_lights: data::DEFAULT_NIGHT_LIGHTS_FILE.to_string(),
+            coastline: data::DEFAULT_COASTLINE_FILE.to_string(),
+            rivers: data::DEFAULT_RIVERS_FILE.to_string(),
+            lakes: data::DEFAULT_LAKES_FILE.to_string(),
+            places: data::DEFAULT_PLACES_FILE.to_string(),
+            geonames: data::DEFAULT_GEONAMES_FILE.to_string(),
+            plate_boundaries: data::DEFAULT_PLATE_BOUNDARIES_FILE.to_string(),
+            time_zones: data::DEFAULT_TIME_ZONES_FILE.to_string(),
+            msaa_samples: crate::draw_buffer::DEFAULT_NUM_SAMPLES,
+            units: UnitsConfig::Km,
+            tile_url: crate::web_tiles::DEFAULT_TILE_URL.to_string(),
+            window: Default::default(),
+            graticule: Default::default(),
+            vector_map: Default::default(),
+            base_maps: data::DEFAULT_BASE_MAPS.iter().map(|(name, path)| BaseMapConfig{
+                name: name.to_string(),
+                path: path.to_string()
+            }).collect(),
+            bodies: Default::default()
+        }
+    }
+}
+
+impl Config {
+    /// Returns paths of the globe texture and outlines of `body`.
+    pub fn body_files(&self, body: &data::Body) -> (String, String) {
+        let body_config = match body.key {
+            "moon" => &self.bodies.moon,
+            "mars" => &self.bodies.mars,
+            "mercury" => &self.bodies.mercury,
+            _ => return (self.globe_texture.clone(), self.coastline.clone())
+        };
+
+        (
+            body_config.texture.clone().unwrap_or_else(|| format!("data/{}/texture.jpg", body.key)),
+            body_config.outlines.clone().unwrap_or_else(|| format!("data/{}/outlines.shp", body.key))
+        )
+    }
+
+    /// Replaces the data paths with those given by `resolve_data_path`.
+    pub fn resolve_data_paths(&mut self) {
+        for path in [
+            &mut self.globe_texture,
+            &mut self.night_lights,
+            &mut self.coastline,
+            &mut self.rivers,
+            &mut self.lakes,
+            &mut self.places,
+            &mut self.geonames,
+            &mut self.plate_boundaries,
+            &mut self.time_zones,
+            &mut self.vector_map.land
+        ] {
+            *path = resolve_data_path(path);
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.msaa_samples < 1 || self.msaa_samples > MAX_MSAA_SAMPLES {
+            return Err(format!("msaa_samples has to be between 1 and {}", MAX_MSAA_SAMPLES));
+        }
+        if self.window.width < 1.0 || self.window.height < 1.0 {
+            return Err("invalid window size".into());
+        }
+        if !(self.graticule.step >= 1.0 && self.graticule.step <= 90.0) {
+            return Err("graticule step has to be between 1° and 90°".into());
+        }
+        let vm = &self.vector_map;
+        if vm.land_color.iter().chain(vm.ocean_color.iter()).any(|c| !(*c >= 0.0 && *c <= 1.0)) {
+            return Err("land and ocean color components have to be between 0 and 1".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the directory with the program's files in the user's configuration directory (if known).
+pub fn config_dir() -> Option<std::path::PathBuf> {
+    let config_dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(std::path::PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME").map(std::path::PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config")))
+    };
+
+    config_dir.map(|dir| dir.join(CONFIG_SUBDIR))
+}
+
+/// Returns the directory with the program's files in the user's data directory (`$XDG_DATA_HOME`
+/// or `~/.local/share`; `%APPDATA%` on Windows), if known. Missing default data files are downloaded there
+/// (see `data_download`).
+pub fn data_dir() -> Option<std::path::PathBuf> {
+    let data_dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(std::path::PathBuf::from)
+    } else {
+        std::env::var_os("XDG_DATA_HOME").map(std::path::PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".local").join("share")))
+    };
+
+    data_dir.map(|dir| dir.join(CONFIG_SUBDIR))
+}
+
+/// Returns `path` if it is absolute or exists; otherwise, the same relative path in the user's data directory
+/// (see `data_dir`) if it exists there (e.g. a default data file downloaded by `data_download` when the program
+/// was not started from its source directory).
+pub fn resolve_data_path(path: &str) -> String {
+    let relative = std::path::Path::new(path);
+    if relative.is_absolute() || relative.exists() {
+        return path.to_string();
+    }
+    match data_dir().map(|dir| dir.join(relative)).filter(|path| path.exists()) {
+        Some(path) => path.to_string_lossy().into_owned(),
+        None => path.to_string()
+    }
+}
+
+/// Returns the path of the configuration file in the user's configuration directory (or in the current directory
+/// if the configuration directory is unknown).
+pub fn config_path() -> std::path::PathBuf {
+    match config_dir() {
+        Some(dir) => dir.join(CONFIG_FILE),
+        None => std::path::PathBuf::from(CONFIG_FILE)
+    }
+}
+
+/// Loads the configuration from `path`; a nonexistent file gives the default configuration.
+pub fn load(path: &std::path::Path) -> Result<Config, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Default::default()),
+        Err(e) => return Err(e.to_string())
+    };
+    let config: Config = toml::from_str(&contents).map_err(|e| e.to_string())?;
+    config.validate()?;
+
+    Ok(config)
+}
+
+/// Stores `unit` in the configuration file at `path`; the rest of the file (other entries, their order, comments
+/// and formatting) is kept as is.
+pub fn store_distance_unit(path: &std::path::Path, unit: data::DistanceUnit) -> Result<(), String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.to_string())
+    };
+    let contents = with_units(&contents, UnitsConfig::from_distance_unit(unit))?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+/// Returns the configuration file `contents` with its `units` entry set to (or added as) `units`.
+fn with_units(contents: &str, units: UnitsConfig) -> Result<String, String> {
+    let mut document = contents.parse::<toml_edit::Document>().map_err(|e| e.to_string())?;
+    let name = match units {
+        UnitsConfig::Km => "km",
+        UnitsConfig::Nmi => "nmi",
+        UnitsConfig::Mi => "mi"
+    };
+    match document.get_mut("units").and_then(|item| item.as_value_mut()) {
+        // keeps the comment following the value
+        Some(value) => *value = toml_edit::Value::from(name).decorated(" ", value.decor().suffix().unwrap_or("")),
+        None => { document["units"] = toml_edit::value(name); }
+    }
+
+    Ok(document.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storing_units_keeps_rest_of_file() {
+        let contents = concat!(
+            "# paths\n",
+            "coastline = \"coast.shp\"  # custom\n",
+            "units = \"km\"   # distances\n",
+            "\n",
+            "[window]\n",
+            "height = 600\n",
+            "width = 800\n"
+        );
+        assert_eq!(with_units(contents, UnitsConfig::Nmi).unwrap(), contents.replace("\"km\"", "\"nmi\""));
+    }
+
+    #[test]
+    fn storing_units_adds_entry() {
+        let contents = "msaa_samples = 4\n\n[window]\nwidth = 800.0\nheight = 600.0\n";
+        let stored = with_units(contents, UnitsConfig::Mi).unwrap();
+        let config: Config = toml::from_str(&stored).unwrap();
+        assert!(config.units.distance_unit() == data::DistanceUnit::Mile);
+        assert_eq!(config.msaa_samples, 4);
+        assert_eq!(config.window.width, 800.0);
+
+        let stored = with_units("", UnitsConfig::Nmi).unwrap();
+        assert!(toml::from_str::<Config>(&stored).unwrap().units.distance_unit() == data::DistanceUnit::NauticalMile);
+    }
+}
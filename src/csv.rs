@@ -0,0 +1,149 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Loading of points from CSV files, shown by views as point layers (and quoting of fields of written CSV files).
+//
+// The first row contains column names. Fields are separated by commas (or by semicolons, if the first row contains
+// semicolons and no commas) and may be enclosed in double quotes (with `""` denoting a quote inside); quoted fields
+// spanning several lines are not supported.
+//
+
+/// Names (lower case) of columns recognized as longitude.
+const LONGITUDE_COLUMNS: &[&str] = &["lon", "long", "longitude", "lng", "x"];
+
+/// Names (lower case) of columns recognized as latitude.
+const LATITUDE_COLUMNS: &[&str] = &["lat", "latitude", "y"];
+
+/// Names (lower case) of columns recognized as labels.
+const LABEL_COLUMNS: &[&str] = &["label", "name", "title"];
+
+/// Names (lower case) of columns recognized as values.
+const VALUE_COLUMNS: &[&str] = &["value", "val", "population", "magnitude"];
+
+/// Contents of a CSV file.
+pub struct Table {
+    pub columns: Vec<String>,
+    /// Fields of each row (as many as there are columns; missing fields are empty).
+    pub rows: Vec<Vec<String>>
+}
+
+/// Point of a point layer.
+#[derive(Clone)]
+pub struct Point {
+    /// Degrees.
+    pub longitude: f64,
+    /// Degrees.
+    pub latitude: f64,
+    pub label: Option<String>,
+    pub value: Option<f64>
+}
+
+/// Columns of a table holding the points' data.
+#[derive(Copy, Clone, Default)]
+pub struct PointColumns {
+    pub longitude: usize,
+    pub latitude: usize,
+    pub label: Option<usize>,
+    pub value: Option<usize>
+}
+
+impl Table {
+    /// Returns the index of the first column whose name (ignoring case and surrounding whitespace)
+    /// is one of `names`.
+    fn find_column(&self, names: &[&str]) -> Option<usize> {
+        self.columns.iter().position(|column| names.contains(&column.trim().to_lowercase().as_str()))
+    }
+
+    /// Returns the columns recognized by their names; longitude and latitude default to the first two columns.
+    pub fn guess_columns(&self) -> PointColumns {
+        PointColumns{
+            longitude: self.find_column(LONGITUDE_COLUMNS).unwrap_or(0),
+            latitude: self.find_column(LATITUDE_COLUMNS).unwrap_or(1.min(self.columns.len() - 1)),
+            label: self.find_column(LABEL_COLUMNS),
+            value: self.find_column(VALUE_COLUMNS)
+        }
+    }
+
+    /// Returns points of rows with valid coordinates and the number of skipped rows. An invalid value is treated
+    /// as no value.
+    pub fn points(&self, columns: PointColumns) -> Result<(Vec<Point>, usize), String> {
+        let number = |row: &[String], column: usize| row[column].trim().parse::<f64>().ok();
+        let points: Vec<Point> = self.rows.iter().filter_map(|row| {
+            let longitude = number(row, columns.longitude).filter(|lon| lon.abs() <= 180.0)?;
+            let latitude = number(row, columns.latitude).filter(|lat| lat.abs() <= 90.0)?;
+            Some(Point{
+                longitude,
+                latitude,
+                label: columns.label.map(|column| row[column].trim().to_string()).filter(|label| !label.is_empty()),
+                value: columns.value.and_then(|column| number(row, column))
+            })
+        }).collect();
+
+        if points.is_empty() {
+            return Err("no rows with valid coordinates".into());
+        }
+        let num_skipped = self.rows.len() - points.len();
+
+        Ok((points, num_skipped))
+    }
+}
+
+pub fn load(path: &str) -> Result<Table, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+
+    let header = lines.next().ok_or_else(|| "the file is empty".to_string())?;
+    let header = header.strip_prefix('\u{feff}').unwrap_or(header);
+    let separator = if header.contains(';') && !header.contains(',') { ';' } else { ',' };
+    let columns = parse_line(header, separator);
+
+    let rows = lines.map(|line| {
+        let mut fields = parse_line(line, separator);
+        fields.resize(columns.len(), String::new());
+        fields
+    }).collect();
+
+    Ok(Table{ columns, rows })
+}
+
+/// Returns `field` enclosed in double quotes (with quotes inside doubled) if it contains a separator, a quote
+/// or a line break; otherwise unchanged.
+pub fn quote(field: &str) -> std::borrow::Cow<str> {
+    if field.contains(&[',', ';', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+fn parse_line(line: &str, separator: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => if chars.peek() == Some(&'"') {
+                field.push('"');
+                chars.next();
+            } else {
+                quoted = false;
+            },
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            },
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c)
+        }
+    }
+    fields.push(field);
+
+    fields
+}
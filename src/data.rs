@@ -6,27 +6,273 @@
 // (see the LICENSE file for details).
 //
 
+use crate::choropleth;
+use crate::config::Config;
+use crate::csv;
+use crate::draw_buffer::Sampling;
+use crate::earthquakes;
+use crate::export;
+use crate::gazetteer::Gazetteer;
+use crate::geojson;
+use crate::geomagnetism;
+use crate::georeferencing;
+use crate::geometry;
+use crate::geotiff;
+use crate::kml;
+use crate::plate_boundaries;
+use crate::plugins::{self, Plugin};
+use crate::projection;
+use crate::scalar_field;
+use crate::scripting::OverlayScript;
+use crate::shape_cache;
+use crate::shape_filter;
+use crate::solar;
+use crate::time_zones;
+use crate::vector_field;
+use crate::web_tiles;
 use crate::views::{
+    AlbersEqualAreaView,
+    ArmadilloView,
+    ConformalSquareView,
     CylindricalLambertView,
+    EckertView,
+    EllipticalView,
+    GeneralPerspectiveView,
     GnomonicView,
+    GraticuleLabels,
+    LambertAzimuthalView,
+    LambertConformalConicView,
+    Layer,
+    LayerVisibility,
+    LittrowView,
+    LoximuthalView,
+    MercatorView,
+    NicolosiView,
     OrthographicView,
-    StereographicView
+    PluginView,
+    QscView,
+    StereographicView,
+    TransverseMercatorView,
+    ViewBase
 };
 use glium::CapabilitiesSource;
 use image::{GenericImageView};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
-#[derive(Copy, Clone)]
+/// Equirectangular texture of the globe loaded at startup by default.
+pub const DEFAULT_GLOBE_TEXTURE_FILE: &str = "data/world.topo.bathy.200412.3x8192x4096.jpg";
+
+pub const DEFAULT_COASTLINE_FILE: &str = "data/ne_10m_coastline/ne_10m_coastline.shp";
+
+/// Names and paths of base maps selectable per view besides the globe texture (not used if the files are missing).
+pub const DEFAULT_BASE_MAPS: [(&str, &str); 3] = [
+    ("bathymetry", "data/base_maps/bathymetry.jpg"),
+    ("political", "data/base_maps/political.jpg"),
+    ("grayscale relief", "data/base_maps/relief_gray.jpg")
+];
+
+/// Rivers and lakes shown as the "rivers and lakes" layer (not used if the files are missing).
+pub const DEFAULT_RIVERS_FILE: &str = "data/ne_10m_rivers_lake_centerlines/ne_10m_rivers_lake_centerlines.shp";
+
+pub const DEFAULT_LAKES_FILE: &str = "data/ne_10m_lakes/ne_10m_lakes.shp";
+
+/// Populated places shown as the "places" layer (not used if the file is missing).
+pub const DEFAULT_PLACES_FILE: &str = "data/ne_10m_populated_places/ne_10m_populated_places.shp";
+
+/// GeoNames table of places added to the place search (not used if the file is missing).
+pub const DEFAULT_GEONAMES_FILE: &str = "data/cities15000.txt";
+
+/// Tectonic plate boundaries shown as the "plate boundaries" layer (not used if the file is missing).
+pub const DEFAULT_PLATE_BOUNDARIES_FILE: &str = "data/PB2002_steps.json";
+
+/// Time-zone boundaries shown as the "time zones" layer (not used if the file is missing).
+pub const DEFAULT_TIME_ZONES_FILE: &str = "data/timezones.geojson.zip";
+
+/// Style of the time zones layer.
+pub const TIME_ZONES_STYLE: LineStyle = LineStyle{ color: [0.6, 0.2, 0.6, 0.8], width: 1.0 };
+
+/// Coefficients of the World Magnetic Model, suggested in "View/Magnetic declination...".
+pub const DEFAULT_WMM_FILE: &str = "data/WMM.COF";
+
+/// Styles of isogonic lines of east and west declination, and of the agonic line (zero declination).
+const DECLINATION_EAST_STYLE: LineStyle = LineStyle{ color: [0.85, 0.15, 0.1, 1.0], width: 1.0 };
+const DECLINATION_WEST_STYLE: LineStyle = LineStyle{ color: [0.1, 0.3, 0.85, 1.0], width: 1.0 };
+const AGONIC_LINE_STYLE: LineStyle = LineStyle{ color: [0.1, 0.6, 0.1, 1.0], width: 2.0 };
+
+/// Step (degrees) of the grid on which declination is contoured.
+const DECLINATION_GRID_STEP: f64 = 1.0;
+
+/// Name of the vector layer of isogonic lines.
+const DECLINATION_LAYER_NAME: &str = "magnetic declination";
+
+/// Name of the point layer of magnetic dip poles.
+const DIP_POLES_LAYER_NAME: &str = "magnetic dip poles";
+
+/// Land polygons filled in the vector map mode (not used if the file is missing).
+pub const DEFAULT_LAND_FILE: &str = "data/ne_10m_land/ne_10m_land.shp";
+
+pub const DEFAULT_LAND_COLOR: [f32; 3] = [0.94, 0.91, 0.82];
+
+pub const DEFAULT_OCEAN_COLOR: [f32; 3] = [0.67, 0.8, 0.9];
+
+/// Width of the night shading texture (see `ProgramData::set_night_time`); its height is half of that.
+const NIGHT_TEXTURE_WIDTH: u32 = 1024;
+
+/// Opacity of the night shading where the Sun is far below the horizon.
+const NIGHT_OPACITY: f32 = 0.6;
+
+/// Altitude of the Sun below which the night shading is fully opaque (end of nautical twilight); night lights
+/// (see `NightShading::night_lights`) are fully shown there.
+pub const NIGHT_SUN_ALTITUDE: cgmath::Deg<f64> = cgmath::Deg(-12.0);
+
+/// Width of the land mask (see `LandFill`); its height is half of that.
+const LAND_MASK_WIDTH: u32 = 8192;
+
+/// Style of the coastline layer (loaded at startup from `Config::coastline`).
+pub const COASTLINE_STYLE: LineStyle = LineStyle{ color: [0.0, 0.0, 0.0, 1.0], width: 1.0 };
+
+/// Style of the rivers and lakes layers (loaded at startup from `Config::rivers`, `Config::lakes`).
+pub const HYDROGRAPHY_STYLE: LineStyle = LineStyle{ color: [0.1, 0.4, 0.85, 1.0], width: 1.0 };
+
+/// Default style of vector layers loaded from files.
+pub const VECTOR_LAYER_STYLE: LineStyle = LineStyle{ color: [0.1, 0.55, 0.2, 1.0], width: 1.0 };
+
+/// Default style of point layers loaded from files.
+pub const POINT_LAYER_STYLE: PointStyle = PointStyle{
+    color: [0.2, 0.5, 1.0, 1.0],
+    radius: 3.0,
+    size_by_value: false,
+    max_radius: 12.0,
+    color_by_value: false,
+    max_color: [1.0, 0.1, 0.1, 1.0],
+    labels: false
+};
+
+/// Style of earthquakes: symbols grow and turn red with magnitude.
+const EARTHQUAKES_STYLE: PointStyle = PointStyle{
+    color: [1.0, 0.85, 0.1, 0.8],
+    radius: 2.0,
+    size_by_value: true,
+    max_radius: 16.0,
+    color_by_value: true,
+    max_color: [0.9, 0.05, 0.05, 0.8],
+    labels: false
+};
+
+/// Style of magnetic dip poles.
+const DIP_POLES_STYLE: PointStyle = PointStyle{
+    color: [0.7, 0.1, 0.7, 1.0],
+    radius: 5.0,
+    labels: true,
+    ..POINT_LAYER_STYLE
+};
+
+/// Max. size of the texture of a web tiles region.
+const MAX_WEB_TILES_TEXTURE_SIZE: u32 = 8192;
+
+/// Max. size of the texture of a georeferenced image's region.
+const MAX_IMAGE_OVERLAY_TEXTURE_SIZE: u32 = 8192;
+
+/// Default opacity of a georeferenced image (see `ImageOverlay`).
+pub const DEFAULT_IMAGE_OVERLAY_OPACITY: f32 = 0.8;
+
+/// Max. width of the globe texture enlarged to match the resolution of a GeoTIFF raster.
+const MAX_GEOTIFF_TEXTURE_WIDTH: u32 = 16384;
+
+/// Default spacing of graticule lines.
+pub const DEFAULT_GRATICULE_STEP: cgmath::Deg<f64> = cgmath::Deg(10.0);
+
+/// Spacings of graticule lines selectable in views (degrees).
+pub const GRATICULE_STEPS: [f64; 5] = [1.0, 5.0, 10.0, 15.0, 30.0];
+
+/// Default length of segments of graticule lines.
+pub const DEFAULT_GRATICULE_SUBSTEP: cgmath::Deg<f64> = cgmath::Deg(1.0);
+
+/// Lengths of segments of graticule lines selectable in views (degrees); shorter segments give smoother lines.
+pub const GRATICULE_SUBSTEPS: [f64; 4] = [2.0, 1.0, 0.5, 0.25];
+
+/// Unit of distances shown in the GUI (distances are stored in kilometers).
+#[derive(Copy, Clone, PartialEq)]
+pub enum DistanceUnit { Kilometer, NauticalMile, Mile }
+
+impl DistanceUnit {
+    pub const ALL: [DistanceUnit; 3] = [DistanceUnit::Kilometer, DistanceUnit::NauticalMile, DistanceUnit::Mile];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DistanceUnit::Kilometer => "kilometers",
+            DistanceUnit::NauticalMile => "nautical miles",
+            DistanceUnit::Mile => "miles"
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            DistanceUnit::Kilometer => "km",
+            DistanceUnit::NauticalMile => "nmi",
+            DistanceUnit::Mile => "mi"
+        }
+    }
+
+    /// Returns the length of the unit in kilometers.
+    fn km(&self) -> f64 {
+        match self {
+            DistanceUnit::Kilometer => 1.0,
+            DistanceUnit::NauticalMile => 1.852,
+            DistanceUnit::Mile => 1.609344
+        }
+    }
+
+    pub fn from_km(&self, distance: f64) -> f64 { distance / self.km() }
+
+    pub fn to_km(&self, distance: f64) -> f64 { distance * self.km() }
+}
+
+impl Default for DistanceUnit {
+    fn default() -> DistanceUnit { DistanceUnit::Kilometer }
+}
+
+/// Celestial body whose surface is mapped.
+#[derive(Copy, Clone, PartialEq)]
+pub struct Body {
+    pub name: &'static str,
+    /// Name used in the configuration file and in sessions.
+    pub key: &'static str,
+    /// Mean radius (km); used for distances on the (spherical) surface.
+    pub mean_radius: f64,
+    /// Equatorial radius of the reference ellipsoid (meters); grid coordinates are expressed in its multiples.
+    pub semi_major_axis: f64,
+    pub flattening: f64
+}
+
+/// Selectable bodies (IAU reference radii); the first one is the Earth.
+pub static BODIES: [Body; 4] = [
+    Body{ name: "Earth", key: "earth", mean_radius: 6371.0, semi_major_axis: 6_378_137.0, flattening: 0.003352811 },
+    Body{ name: "Moon", key: "moon", mean_radius: 1737.4, semi_major_axis: 1_738_100.0, flattening: 0.0012 },
+    Body{ name: "Mars", key: "mars", mean_radius: 3389.5, semi_major_axis: 3_396_190.0, flattening: 0.005886 },
+    Body{ name: "Mercury", key: "mercury", mean_radius: 2439.7, semi_major_axis: 2_440_530.0, flattening: 0.00093 }
+];
+
+// `repr(C)`: vertices are read in place from memory-mapped files (see `shape_cache`)
+#[derive(Copy, Clone, PartialEq)]
+#[repr(C)]
 pub struct LonLatVertex {
     // values in degrees; -180° ⩽ longitude ⩽ 180°, -90° ⩽ latitude ⩽ 90°
-    lonlat_position: [f32; 2]
+    pub lonlat_position: [f32; 2]
 }
 glium::implement_vertex!(LonLatVertex, lonlat_position);
 
+impl LonLatVertex {
+    pub fn new(longitude: cgmath::Deg<f64>, latitude: cgmath::Deg<f64>) -> LonLatVertex {
+        LonLatVertex{ lonlat_position: [longitude.0 as f32, latitude.0 as f32] }
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct XyVertex {
-    position: [f32; 2]
+    pub position: [f32; 2]
 }
 glium::implement_vertex!(XyVertex, position);
 
@@ -65,12 +311,42 @@ pub struct GlProgramPair {
 }
 
 pub struct OpenGlPrograms {
+    pub adams_hemisphere: GlProgramPair,
+    pub albers_equal_area: GlProgramPair,
+    pub armadillo: GlProgramPair,
     pub cylindrical_lambert: GlProgramPair,
+    pub eckert_iv: GlProgramPair,
+    pub eckert_vi: GlProgramPair,
+    pub gall_stereographic: GlProgramPair,
+    pub general_perspective: GlProgramPair,
     pub gnomonic: GlProgramPair,
+    pub guyou: GlProgramPair,
+    pub hammer: GlProgramPair,
+    pub lambert_azimuthal: GlProgramPair,
+    pub lambert_conformal_conic: GlProgramPair,
+    pub littrow: GlProgramPair,
+    pub loximuthal: GlProgramPair,
+    pub mercator: GlProgramPair,
+    pub miller: GlProgramPair,
+    pub mollweide: GlProgramPair,
+    pub nicolosi: GlProgramPair,
     pub orthographic: GlProgramPair,
+    pub peirce_quincuncial: GlProgramPair,
+    pub qsc: GlProgramPair,
     pub stereographic: GlProgramPair,
+    pub transverse_mercator: GlProgramPair,
     pub texture_copy_single: Rc<glium::Program>,
-    pub texture_copy_multi: Rc<glium::Program>
+    pub texture_copy_multi: Rc<glium::Program>,
+    /// Renders lines specified directly in map coordinates.
+    pub outline: Rc<glium::Program>
+}
+
+pub struct PluginData {
+    pub plugin: &'static Plugin,
+    /// Present if the plugin provides a vertex shader which compiled successfully.
+    pub gl_programs: Option<GlProgramPair>,
+    /// Error which prevented creation of `gl_programs` (if any).
+    pub error: Option<String>
 }
 
 #[derive(Clone)]
@@ -79,6 +355,436 @@ pub struct LonLatGlBuffers {
     pub indices: Rc<glium::IndexBuffer<u32>>,
 }
 
+#[derive(Copy, Clone, PartialEq)]
+pub struct LineStyle {
+    pub color: [f32; 4],
+    /// Width in pixels.
+    pub width: f32
+}
+
+/// Lines of a vector layer drawn with the same style.
+#[derive(Clone)]
+pub struct StyledLines {
+    /// If `None`, the layer's style is used.
+    pub style: Option<LineStyle>,
+    /// Source lines (not simplified).
+    pub polylines: Vec<geojson::Polyline>,
+    pub gl_buf: LonLatGlBuffers
+}
+
+impl StyledLines {
+    /// Creates lines whose GL buffers contain `polylines` simplified with `tolerance` (degrees; 0: no simplification).
+    fn new(
+        style: Option<LineStyle>,
+        polylines: Vec<geojson::Polyline>,
+        tolerance: f64,
+        display: &glium::Display
+    ) -> StyledLines {
+        let gl_buf = create_simplified_polylines(&polylines, tolerance, display);
+        StyledLines{ style, polylines, gl_buf }
+    }
+
+    /// Creates lines of a shape file loaded with `load_shape_lines`; its `gl_buf` (not simplified) is used
+    /// if `tolerance` is 0.
+    fn from_shape_lines(
+        polylines: Vec<geojson::Polyline>,
+        gl_buf: LonLatGlBuffers,
+        tolerance: f64,
+        display: &glium::Display
+    ) -> StyledLines {
+        if tolerance > 0.0 {
+            StyledLines::new(None, polylines, tolerance, display)
+        } else {
+            StyledLines{ style: None, polylines, gl_buf }
+        }
+    }
+}
+
+/// Lines of a visible vector layer drawn with the same style, as passed to views.
+#[derive(Clone)]
+pub struct DrawnLines {
+    pub style: LineStyle,
+    pub gl_buf: LonLatGlBuffers,
+    /// See `VectorLayer::base_map`.
+    pub base_map: bool,
+    /// See `VectorLayer::layer`.
+    pub layer: Layer
+}
+
+/// Lines loaded from a GeoJSON, KML/KMZ or shape file, or derived from other layers (e.g. a buffer).
+pub struct VectorLayer {
+    /// File path or description of a derived layer.
+    pub name: String,
+    /// Source lines (in the order of the file).
+    pub polylines: Vec<geojson::Polyline>,
+    pub lines: Vec<StyledLines>,
+    /// Style of lines which do not specify their own.
+    pub style: LineStyle,
+    pub visible: bool,
+    /// If true, the layer is a part of the base map (e.g. the coastline): it is drawn only in the vector map mode
+    /// and below the graticule, regardless of the "vector layers" layer.
+    pub base_map: bool,
+    /// Layer of views controlling the visibility of a layer which is not a part of the base map.
+    pub layer: Layer,
+    /// Shapefile the layer was loaded from; its features can be filtered by attributes.
+    pub shapefile: Option<String>,
+    /// Filter of the shapefile's features (if any).
+    pub filter: Option<shape_filter::AttributeFilter>
+}
+
+/// Populated place (city) shown by the "places" layer.
+pub struct Place {
+    pub name: String,
+    /// Country (Natural Earth's `ADM0NAME`); may be empty.
+    pub country: String,
+    pub longitude: cgmath::Deg<f64>,
+    pub latitude: cgmath::Deg<f64>,
+    /// Population rank (Natural Earth's `RANK_MAX`; from 0 to 14, larger for larger places).
+    pub rank: u8
+}
+
+/// Label of a time zone's offset from UTC shown by the "time zones" layer.
+pub struct TimeZoneLabel {
+    pub text: String,
+    pub longitude: cgmath::Deg<f64>,
+    pub latitude: cgmath::Deg<f64>
+}
+
+/// Symbols of a point layer (circles); their size and color can vary with the points' values.
+#[derive(Copy, Clone, PartialEq)]
+pub struct PointStyle {
+    pub color: [f32; 4],
+    /// Radius (logical pixels); of the smallest value if `size_by_value` is set.
+    pub radius: f32,
+    /// If true, symbols' area grows linearly with the value from `radius` to `max_radius`.
+    pub size_by_value: bool,
+    pub max_radius: f32,
+    /// If true, symbols' color changes linearly with the value from `color` to `max_color`.
+    pub color_by_value: bool,
+    pub max_color: [f32; 4],
+    /// If true, points' labels are shown.
+    pub labels: bool
+}
+
+/// Points loaded from a CSV file or an earthquake feed, shown by views as symbols (with the "vector layers" layer).
+#[derive(Clone)]
+pub struct PointLayer {
+    /// File path or name of the earthquake feed.
+    pub name: String,
+    pub points: Vec<csv::Point>,
+    /// Range of the points' values (`None` if no point has a value).
+    pub value_range: Option<[f64; 2]>,
+    pub style: PointStyle,
+    pub visible: bool
+}
+
+impl PointLayer {
+    fn new(name: String, points: Vec<csv::Point>, style: PointStyle) -> PointLayer {
+        let values = || points.iter().filter_map(|point| point.value);
+        let value_range = if values().next().is_some() {
+            Some([values().fold(f64::INFINITY, f64::min), values().fold(f64::NEG_INFINITY, f64::max)])
+        } else {
+            None
+        };
+
+        PointLayer{ name, points, value_range, style, visible: true }
+    }
+
+    /// Returns the radius and color of the symbol of `point`.
+    pub fn symbol(&self, point: &csv::Point) -> (f32, [f32; 4]) {
+        let style = &self.style;
+        // position of the value in the layer's range; points without a value get the smallest symbol
+        let t = match (point.value, self.value_range) {
+            (Some(value), Some([min, max])) if max > min => ((value - min) / (max - min)) as f32,
+            (Some(_), Some(_)) => 1.0,
+            _ => 0.0
+        };
+        let radius = if style.size_by_value {
+            (style.radius.powi(2) + t * (style.max_radius.powi(2) - style.radius.powi(2))).sqrt()
+        } else {
+            style.radius
+        };
+        let color = if style.color_by_value {
+            let mut color = style.color;
+            for (c, max_c) in color.iter_mut().zip(style.max_color.iter()) { *c += t * (max_c - *c); }
+            color
+        } else {
+            style.color
+        };
+
+        (radius, color)
+    }
+}
+
+/// Named point shown in all views (with the "markers" layer); stored in the user's configuration directory.
+#[derive(Clone)]
+pub struct UserMarker {
+    pub name: String,
+    pub longitude: cgmath::Deg<f64>,
+    pub latitude: cgmath::Deg<f64>
+}
+
+/// Equirectangular image of night lights loaded at startup if present (e.g. NASA's Black Marble,
+/// https://earthobservatory.nasa.gov/features/NightLights).
+pub const DEFAULT_NIGHT_LIGHTS_FILE: &str = "data/BlackMarble_2016_3km.jpg";
+
+/// Equirectangular texture selectable as a view's base map (loaded when first selected).
+pub struct BaseMap {
+    pub name: String,
+    path: String,
+    texture: Option<Rc<glium::Texture2d>>
+}
+
+/// Default opacity of the overlay texture (see `Overlay`).
+pub const DEFAULT_OVERLAY_OPACITY: f32 = 0.8;
+
+/// Equirectangular texture (e.g. global cloud cover) blended over the globe texture in the globe texture mode.
+/// It is treated as premultiplied over black (as cloud cover images are): the brightest channel of a pixel
+/// is its coverage, so dark areas are transparent.
+#[derive(Clone)]
+pub struct Overlay {
+    pub texture: Rc<glium::Texture2d>,
+    /// From 0 to 1.
+    pub opacity: f32
+}
+
+/// Default opacity of a scalar field (see `ScalarField`).
+pub const DEFAULT_SCALAR_FIELD_OPACITY: f32 = 0.7;
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct ScalarFieldParams {
+    pub colormap: scalar_field::Colormap,
+    /// Values mapped to the ends of the colormap.
+    pub value_range: [f32; 2],
+    /// From 0 to 1.
+    pub opacity: f32
+}
+
+/// NetCDF variable(s) of a layer, at an index ("step") of their first leading dimension (e.g. time).
+#[derive(Clone)]
+pub struct NetCdfSource {
+    pub path: String,
+    pub variables: Vec<String>,
+    pub step: usize,
+    /// Labels of all steps (see `scalar_field::step_labels`).
+    pub step_labels: Rc<Vec<String>>
+}
+
+/// Gridded scalar field (see `scalar_field`) shown as a color-mapped overlay in the globe texture mode.
+#[derive(Clone)]
+pub struct ScalarField {
+    pub grid: Rc<scalar_field::Grid>,
+    pub source: NetCdfSource,
+    pub params: ScalarFieldParams,
+    /// Equirectangular image of the field (see `scalar_field::Grid::to_image`); missing values are transparent.
+    pub texture: Rc<glium::Texture2d>
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct ChoroplethParams {
+    /// Index of the attribute in `choropleth::PolygonLayer::fields`.
+    pub field: usize,
+    pub classification: choropleth::Classification,
+    pub num_classes: usize,
+    pub colormap: scalar_field::Colormap,
+    /// From 0 to 1.
+    pub opacity: f32
+}
+
+impl Default for ChoroplethParams {
+    fn default() -> ChoroplethParams {
+        ChoroplethParams{
+            field: 0,
+            classification: choropleth::Classification::Quantile,
+            num_classes: 5,
+            colormap: scalar_field::Colormap::Viridis,
+            opacity: 0.8
+        }
+    }
+}
+
+/// Polygons colored by classes of a numeric attribute (see `choropleth`), drawn over the map in both view modes.
+#[derive(Clone)]
+pub struct Choropleth {
+    pub params: ChoroplethParams,
+    pub legend: Rc<choropleth::Legend>,
+    /// Equirectangular image of the polygons (with alpha).
+    pub texture: Rc<glium::Texture2d>
+}
+
+/// Length (logical pixels) of an arrow of the max. magnitude of a newly loaded vector field.
+const DEFAULT_MAX_ARROW_LENGTH: f32 = 30.0;
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct VectorFieldParams {
+    /// Spacing (degrees) of arrows.
+    pub spacing: f32,
+    /// Length (logical pixels) of an arrow of unit magnitude.
+    pub scale: f32,
+    pub color: [f32; 4]
+}
+
+impl Default for VectorFieldParams {
+    fn default() -> VectorFieldParams {
+        VectorFieldParams{ spacing: 5.0, scale: 1.0, color: [1.0, 1.0, 1.0, 0.9] }
+    }
+}
+
+/// Vector field (see `vector_field`) drawn by views as arrows.
+#[derive(Clone)]
+pub struct VectorFieldLayer {
+    pub field: Rc<vector_field::VectorField>,
+    pub source: NetCdfSource,
+    pub params: VectorFieldParams
+}
+
+/// Georeferenced image (see `georeferencing`), shown over the base map in the globe texture mode.
+#[derive(Clone)]
+pub struct ImageOverlay {
+    /// Equirectangular image of the region covered by the image; transparent outside of the image.
+    pub texture: Rc<glium::Texture2d>,
+    /// Range of longitude (degrees).
+    pub lon_range: [f64; 2],
+    /// Range of latitude (degrees).
+    pub lat_range: [f64; 2],
+    /// From 0 to 1.
+    pub opacity: f32
+}
+
+/// Region of a base map from web tiles (see `web_tiles`), shown over the base map in the globe texture mode.
+#[derive(Clone)]
+pub struct WebTiles {
+    /// Equirectangular image of the region; transparent where tiles are missing.
+    pub texture: Rc<glium::Texture2d>,
+    /// Range of longitude (degrees).
+    pub lon_range: [f64; 2],
+    /// Range of latitude (degrees).
+    pub lat_range: [f64; 2]
+}
+
+/// Use of hillshading (see `Hillshading`).
+#[derive(Copy, Clone, PartialEq)]
+pub enum HillshadingMode {
+    /// Modulates the map's texture (leaving flat areas unchanged).
+    Modulate,
+    /// Replaces the map's texture.
+    Standalone
+}
+
+impl HillshadingMode {
+    pub const ALL: [HillshadingMode; 2] = [HillshadingMode::Modulate, HillshadingMode::Standalone];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HillshadingMode::Modulate => "modulate texture",
+            HillshadingMode::Standalone => "standalone"
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct HillshadingParams {
+    /// Elevations (meters) corresponding to black and white of the elevation raster.
+    pub elevation_range: [f32; 2],
+    /// Vertical exaggeration.
+    pub exaggeration: f32,
+    /// Azimuth of the light source (clockwise from north).
+    pub sun_azimuth: cgmath::Deg<f32>,
+    /// Elevation of the light source above the horizon.
+    pub sun_elevation: cgmath::Deg<f32>,
+    pub mode: HillshadingMode
+}
+
+impl Default for HillshadingParams {
+    fn default() -> HillshadingParams {
+        HillshadingParams{
+            elevation_range: [0.0, 8848.0],
+            exaggeration: 5.0,
+            sun_azimuth: cgmath::Deg(315.0),
+            sun_elevation: cgmath::Deg(45.0),
+            mode: HillshadingMode::Modulate
+        }
+    }
+}
+
+/// Shading of relief computed (in "globe_texturing.frag") from an equirectangular elevation raster.
+#[derive(Clone)]
+pub struct Hillshading {
+    /// Single-channel texture with elevations mapped to 0-1 (see `HillshadingParams::elevation_range`).
+    pub elevation: Rc<glium::Texture2d>,
+    pub params: HillshadingParams
+}
+
+/// Shading of the night hemisphere drawn by views.
+#[derive(Clone)]
+pub struct NightShading {
+    /// Darkens the night hemisphere (see `create_night_texture`).
+    pub texture: Rc<glium::Texture2d>,
+    /// Unit vector pointing towards the subsolar point (see `projection::lonlat_to_xyz`).
+    pub sun_direction: [f32; 3],
+    /// Equirectangular texture of night lights; if present, it is shown on the night side instead of
+    /// the globe texture (blended across the terminator), instead of darkening.
+    pub night_lights: Option<Rc<glium::Texture2d>>
+}
+
+/// Land and ocean filling of the vector map mode. Land polygons are rasterized into an equirectangular mask,
+/// which is colored into a texture drawn on the globe mesh (like the globe texture), so that filled areas
+/// are subject to the same projection and clipping as the textured globe.
+pub struct LandFill {
+    mask: image::GrayImage,
+    land_color: [f32; 3],
+    ocean_color: [f32; 3],
+    texture: Rc<glium::Texture2d>
+}
+
+impl LandFill {
+    fn new(
+        path: &str,
+        land_color: [f32; 3],
+        ocean_color: [f32; 3],
+        display: &glium::Display
+    ) -> Result<LandFill, String> {
+        let rings = load_shape_file(path)?;
+        if rings.is_empty() {
+            return Err("no polygons found".into());
+        }
+        let mask = geometry::rasterize_rings(&rings, LAND_MASK_WIDTH, LAND_MASK_WIDTH / 2);
+        let texture = Rc::new(create_land_texture(&mask, land_color, ocean_color, display)?);
+
+        Ok(LandFill{ mask, land_color, ocean_color, texture })
+    }
+
+    pub fn land_color(&self) -> [f32; 3] { self.land_color }
+
+    pub fn ocean_color(&self) -> [f32; 3] { self.ocean_color }
+}
+
+/// Time (in seconds) driving animations (projection tour, overlay scripts). It normally follows the wall clock;
+/// during frame export it is advanced by a fixed step per frame, so that exported animations do not depend
+/// on the machine's speed.
+#[derive(Default)]
+pub struct AnimationClock {
+    time: f64,
+    /// Time step of the current frame.
+    dt: f64,
+    fixed_step: Option<f64>
+}
+
+impl AnimationClock {
+    /// Advances the clock at the start of a frame; `wall_dt` is the wall-clock time elapsed since the previous one.
+    pub fn tick(&mut self, wall_dt: f64) {
+        self.dt = self.fixed_step.unwrap_or(wall_dt);
+        self.time += self.dt;
+    }
+
+    pub fn time(&self) -> f64 { self.time }
+
+    pub fn dt(&self) -> f64 { self.dt }
+
+    /// Makes the clock advance by `step` per frame (`None`: follow the wall clock).
+    pub fn set_fixed_step(&mut self, step: Option<f64>) { self.fixed_step = step; }
+}
+
 pub struct ProgramData {
     id_counter: Rc<RefCell<u32>>,
 
@@ -88,166 +794,1839 @@ pub struct ProgramData {
 
     pub globe_texture: Rc<glium::Texture2d>,
 
+    /// Base maps selectable per view besides the globe texture (which is base map 0).
+    base_maps: Vec<BaseMap>,
+
     pub globe_gl_buf: LonLatGlBuffers,
 
     pub graticule_gl_buf: LonLatGlBuffers,
 
-    pub map_gl_buf: LonLatGlBuffers,
+    /// Spacing of graticule lines of new views.
+    pub graticule_step: cgmath::Deg<f64>,
+
+    /// Graticule labels of new views.
+    pub default_graticule_labels: GraticuleLabels,
+
+    /// Sampling of new views' draw buffers.
+    pub sampling: Sampling,
+
+    /// Unit of distances shown in the GUI.
+    pub distance_unit: DistanceUnit,
+
+    /// URL template of the web tile source (see `web_tiles`).
+    pub tile_url: String,
+
+    pub plugins: Vec<PluginData>,
+
+    pub overlay_script: Option<OverlayScript>,
+
+    pub animation_clock: AnimationClock,
+
+    vector_layers: Vec<VectorLayer>,
+
+    point_layers: Rc<Vec<PointLayer>>,
+
+    /// Date and time for which the night hemisphere is shaded (`None`: not shaded).
+    night_time: Option<solar::UtcDateTime>,
+
+    night_shading: Option<NightShading>,
+
+    night_lights: Option<Rc<glium::Texture2d>>,
+
+    overlay: Option<Overlay>,
+
+    /// Paths of images shown one at a time as the overlay (see `load_overlay_sequence`); empty if the overlay
+    /// is a single image.
+    overlay_frames: Vec<String>,
+
+    /// Index of the element of `overlay_frames` shown as the overlay.
+    overlay_frame: usize,
+
+    hillshading: Option<Hillshading>,
+
+    web_tiles: Option<WebTiles>,
+
+    image_overlay: Option<ImageOverlay>,
+
+    scalar_field: Option<ScalarField>,
+
+    vector_field: Option<VectorFieldLayer>,
+
+    /// Source polygons of `choropleth`.
+    choropleth_layer: Option<choropleth::PolygonLayer>,
+
+    choropleth: Option<Choropleth>,
+
+    /// Features of shapefiles of vector layers (loaded when the layers are first filtered), by file path.
+    shape_features: HashMap<String, Rc<shape_filter::Features>>,
+
+    /// Tolerance (degrees) of simplification of lines of vector layers (0: not simplified).
+    line_simplification: f64,
+
+    /// The most recent fetching of web tiles.
+    tile_fetch: Option<web_tiles::TileFetch>,
+
+    /// The most recent fetching of an earthquake feed.
+    earthquake_fetch: Option<earthquakes::FeedFetch>,
+
+    /// Populated places shown in views (none for bodies other than the Earth), in the order of decreasing rank.
+    pub places: Rc<Vec<Place>>,
+
+    earth_places: Rc<Vec<Place>>,
+
+    /// Places searched by name (of the Earth).
+    gazetteer: Gazetteer,
+
+    /// Labels of UTC offsets of time zones shown in views (none for bodies other than the Earth), in the order
+    /// of decreasing zone size.
+    pub time_zone_labels: Rc<Vec<TimeZoneLabel>>,
+
+    earth_time_zone_labels: Rc<Vec<TimeZoneLabel>>,
+
+    /// Named markers shown in all views.
+    user_markers: Rc<Vec<UserMarker>>,
+
+    /// Index of the mapped body in `BODIES`.
+    body: usize,
+
+    /// Paths of the globe texture and outlines (drawn like the coastline) of each of `BODIES`.
+    body_files: Vec<(String, String)>,
+
+    /// Filled land and ocean of the vector map mode (`None` if land polygons are not available).
+    land_fill: Option<LandFill>,
+
+    /// Layer visibility used by views without overrides.
+    layer_visibility: LayerVisibility,
+
+    pub albers_equal_area_views: Vec<AlbersEqualAreaView>,
+
+    pub armadillo_views: Vec<ArmadilloView>,
+
+    pub conformal_square_views: Vec<ConformalSquareView>,
+
+    pub cylindrical_lambert_views: Vec<CylindricalLambertView>,
+
+    pub eckert_views: Vec<EckertView>,
+
+    pub elliptical_views: Vec<EllipticalView>,
+
+    pub general_perspective_views: Vec<GeneralPerspectiveView>,
+
+    pub gnomonic_views: Vec<GnomonicView>,
+
+    pub lambert_azimuthal_views: Vec<LambertAzimuthalView>,
+
+    pub lambert_conformal_conic_views: Vec<LambertConformalConicView>,
+
+    pub littrow_views: Vec<LittrowView>,
+
+    pub loximuthal_views: Vec<LoximuthalView>,
+
+    pub mercator_views: Vec<MercatorView>,
+
+    pub nicolosi_views: Vec<NicolosiView>,
+
+    pub orthographic_views: Vec<OrthographicView>,
+
+    pub plugin_views: Vec<PluginView>,
+
+    pub qsc_views: Vec<QscView>,
+
+    pub stereographic_views: Vec<StereographicView>,
+
+    pub transverse_mercator_views: Vec<TransverseMercatorView>
+}
+
+fn create_gl_program_pair(vertex_shader_source: &str, display: &glium::Display) -> GlProgramPair {
+    try_create_gl_program_pair(
+        vertex_shader_source,
+        include_str!("resources/shaders/lines.geom"),
+        include_str!("resources/shaders/tris.geom"),
+        display
+    ).unwrap()
+}
+
+/// Creates programs for a pseudocylindrical projection, whose map can be interrupted (see `views::Lobe`).
+fn create_pseudocylindrical_gl_program_pair(vertex_shader_source: &str, display: &glium::Display) -> GlProgramPair {
+    try_create_gl_program_pair(
+        &with_define(vertex_shader_source, "LOBES"),
+        &with_define(include_str!("resources/shaders/lines.geom"), "LOBES"),
+        &with_define(include_str!("resources/shaders/tris.geom"), "LOBES"),
+        display
+    ).unwrap()
+}
+
+/// Creates programs for a projection whose map is interrupted (consists of disjoint parts);
+/// triangles spanning an interruption are discarded regardless of zoom.
+fn create_interrupted_gl_program_pair(vertex_shader_source: &str, display: &glium::Display) -> GlProgramPair {
+    try_create_gl_program_pair(
+        vertex_shader_source,
+        include_str!("resources/shaders/lines.geom"),
+        &with_define(include_str!("resources/shaders/tris.geom"), "INTERRUPTED"),
+        display
+    ).unwrap()
+}
+
+/// Creates programs for a projection whose map (interrupted like with `create_interrupted_gl_program_pair`)
+/// can be repeated as tiles; see the `tile_size` uniform in "tris.geom".
+fn create_tiled_gl_program_pair(vertex_shader_source: &str, display: &glium::Display) -> GlProgramPair {
+    try_create_gl_program_pair(
+        vertex_shader_source,
+        &with_define(include_str!("resources/shaders/lines.geom"), "TILES"),
+        &with_define(&with_define(include_str!("resources/shaders/tris.geom"), "INTERRUPTED"), "TILES"),
+        display
+    ).unwrap()
+}
+
+fn try_create_gl_program_pair(
+    vertex_shader_source: &str,
+    lines_geometry_shader_source: &str,
+    triangles_geometry_shader_source: &str,
+    display: &glium::Display
+) -> Result<GlProgramPair, glium::program::ProgramChooserCreationError> {
+    Ok(GlProgramPair{
+        lines: Rc::new(program!(display,
+            330 => {
+                vertex: vertex_shader_source,
+                geometry: lines_geometry_shader_source,
+                fragment: include_str!("resources/shaders/uniform_color.frag")
+            }
+        )?),
+
+        triangles: Rc::new(program!(display,
+                330 => {
+                    vertex: vertex_shader_source,
+                    geometry: triangles_geometry_shader_source,
+                    fragment: include_str!("resources/shaders/globe_texturing.frag")
+                }
+        )?)
+    })
+}
+
+/// Returns `source` with `#define <name>` inserted after the `#version` directive.
+fn with_define(source: &str, name: &str) -> String {
+    let mut result = String::new();
+    let mut defined = false;
+    for line in source.lines() {
+        result.push_str(line);
+        result.push('\n');
+        if !defined && line.trim_start().starts_with("#version") {
+            result.push_str(&format!("#define {}\n", name));
+            defined = true;
+        }
+    }
+
+    result
+}
+
+fn create_plugin_data(plugin: &'static Plugin, display: &glium::Display) -> PluginData {
+    let (gl_programs, error) = match &plugin.vertex_shader {
+        None => (None, None),
+        Some(vertex_shader_source) => match try_create_gl_program_pair(
+            vertex_shader_source,
+            include_str!("resources/shaders/lines.geom"),
+            include_str!("resources/shaders/tris.geom"),
+            display
+        ) {
+            Ok(gl_programs) => (Some(gl_programs), None),
+            Err(e) => {
+                eprintln!("Failed to create OpenGL programs for plugin \"{}\": {}", plugin.name, e);
+                (None, Some(e.to_string()))
+            }
+        }
+    };
+
+    PluginData{ plugin, gl_programs, error }
+}
+
+impl ProgramData {
+    pub fn new(config: &Config, display: &glium::Display) -> ProgramData {
+        let globe_texture = Rc::new(
+            create_texture_from_image(&config.globe_texture, display).unwrap_or_else(|e| {
+                eprintln!("Failed to load globe texture {}: {}", config.globe_texture, e);
+                create_placeholder_texture(display)
+            })
+        );
+
+        let globe_gl_buf = create_globe_mesh(cgmath::Deg(2.0), display);
+
+        let graticule_step = cgmath::Deg(config.graticule.step);
+        let graticule_gl_buf = create_graticule(graticule_step, DEFAULT_GRATICULE_SUBSTEP, display);
+
+        let (coastline, coastline_gl_buf) = load_shape_lines(&config.coastline, display).unwrap_or_else(|e| {
+            eprintln!("Failed to load coastline {}: {}", config.coastline, e);
+            (vec![], create_polylines(&[], display))
+        });
+        let coastline_layer = VectorLayer{
+            name: "coastline".into(),
+            lines: vec![StyledLines::from_shape_lines(coastline.clone(), coastline_gl_buf, 0.0, display)],
+            polylines: coastline,
+            style: COASTLINE_STYLE,
+            visible: true,
+            base_map: true,
+            layer: Layer::VectorLayers,
+            shapefile: Some(config.coastline.clone()),
+            filter: None
+        };
+
+        // rivers, lakes and plate boundaries are optional; missing files are skipped silently
+        let mut optional_layers = vec![];
+        for (name, path) in [("rivers", &config.rivers), ("lakes", &config.lakes)] {
+            if !std::path::Path::new(path).is_file() { continue; }
+            match load_shape_lines(path, display) {
+                Ok((polylines, gl_buf)) => optional_layers.push(VectorLayer{
+                    name: name.into(),
+                    lines: vec![StyledLines::from_shape_lines(polylines.clone(), gl_buf, 0.0, display)],
+                    polylines,
+                    style: HYDROGRAPHY_STYLE,
+                    visible: true,
+                    base_map: false,
+                    layer: Layer::RiversAndLakes,
+                    shapefile: Some(path.clone()),
+                    filter: None
+                }),
+                Err(e) => eprintln!("Failed to load {} {}: {}", name, path, e)
+            }
+        }
+
+        if std::path::Path::new(&config.plate_boundaries).is_file() {
+            match plate_boundaries_layer(&config.plate_boundaries, 0.0, display) {
+                Ok(layer) => optional_layers.push(layer),
+                Err(e) => eprintln!("Failed to load plate boundaries {}: {}", config.plate_boundaries, e)
+            }
+        }
+
+        let mut time_zone_labels = vec![];
+        if std::path::Path::new(&config.time_zones).is_file() {
+            match time_zones_layer(&config.time_zones, 0.0, display) {
+                Ok((layer, labels)) => {
+                    optional_layers.push(layer);
+                    time_zone_labels = labels;
+                },
+                Err(e) => eprintln!("Failed to load time zones {}: {}", config.time_zones, e)
+            }
+        }
+        let time_zone_labels = Rc::new(time_zone_labels);
+
+        let places = if std::path::Path::new(&config.places).is_file() {
+            load_places(&config.places).unwrap_or_else(|e| {
+                eprintln!("Failed to load places {}: {}", config.places, e);
+                vec![]
+            })
+        } else {
+            vec![]
+        };
+
+        let mut gazetteer = Gazetteer::new(&places);
+        if std::path::Path::new(&config.geonames).is_file() {
+            if let Err(e) = gazetteer.add_geonames(&config.geonames) {
+                eprintln!("Failed to load GeoNames places {}: {}", config.geonames, e);
+            }
+        }
+
+        let places = Rc::new(places);
+
+        let night_lights = if std::path::Path::new(&config.night_lights).is_file() {
+            create_texture_from_image(&config.night_lights, display).map_err(|e| {
+                eprintln!("Failed to load night lights {}: {}", config.night_lights, e);
+            }).ok().map(Rc::new)
+        } else {
+            None
+        };
+
+        let land_fill = if std::path::Path::new(&config.vector_map.land).is_file() {
+            let vm = &config.vector_map;
+            LandFill::new(&vm.land, vm.land_color, vm.ocean_color, display).map_err(|e| {
+                eprintln!("Failed to load land polygons {}: {}", vm.land, e);
+            }).ok()
+        } else {
+            None
+        };
+
+        let texture_copy_single = Rc::new(program!(display,
+            330 => {
+                vertex: include_str!("resources/shaders/pass-through.vert"),
+                fragment: include_str!("resources/shaders/texturing.frag"),
+            }
+        ).unwrap());
+
+        let texture_copy_multi = Rc::new(program!(display,
+            330 => {
+                vertex: include_str!("resources/shaders/pass-through.vert"),
+                fragment: include_str!("resources/shaders/texturing_multi-sample.frag"),
+            }
+        ).unwrap());
+
+        let outline = Rc::new(program!(display,
+            330 => {
+                vertex: include_str!("resources/shaders/outline.vert"),
+                fragment: include_str!("resources/shaders/uniform_color.frag"),
+            }
+        ).unwrap());
+
+        let adams_hemisphere = create_gl_program_pair(
+            &with_define(include_str!("resources/shaders/conformal_square.vert"), "ADAMS_HEMISPHERE"),
+            display
+        );
+        let albers_equal_area = create_gl_program_pair(
+            include_str!("resources/shaders/albers_equal_area.vert"),
+            display
+        );
+        let armadillo = create_gl_program_pair(
+            include_str!("resources/shaders/armadillo.vert"),
+            display
+        );
+        let cylindrical_lambert = create_pseudocylindrical_gl_program_pair(
+            include_str!("resources/shaders/cylindrical_lambert.vert"),
+            display
+        );
+        let eckert_iv = create_pseudocylindrical_gl_program_pair(
+            include_str!("resources/shaders/eckert.vert"),
+            display
+        );
+        let eckert_vi = create_pseudocylindrical_gl_program_pair(
+            &with_define(include_str!("resources/shaders/eckert.vert"), "ECKERT_VI"),
+            display
+        );
+        let mollweide = create_pseudocylindrical_gl_program_pair(
+            include_str!("resources/shaders/elliptical.vert"),
+            display
+        );
+        let hammer = create_gl_program_pair(
+            &with_define(include_str!("resources/shaders/elliptical.vert"), "HAMMER"),
+            display
+        );
+        let general_perspective = create_gl_program_pair(
+            include_str!("resources/shaders/general_perspective.vert"),
+            display
+        );
+        let gnomonic = create_gl_program_pair(
+            include_str!("resources/shaders/gnomonic.vert"),
+            display
+        );
+        let lambert_azimuthal = create_gl_program_pair(
+            include_str!("resources/shaders/lambert_azimuthal.vert"),
+            display
+        );
+        let lambert_conformal_conic = create_gl_program_pair(
+            include_str!("resources/shaders/lambert_conformal_conic.vert"),
+            display
+        );
+        let littrow = create_gl_program_pair(
+            include_str!("resources/shaders/littrow.vert"),
+            display
+        );
+        let loximuthal = create_pseudocylindrical_gl_program_pair(
+            include_str!("resources/shaders/loximuthal.vert"),
+            display
+        );
+        let mercator = create_pseudocylindrical_gl_program_pair(
+            include_str!("resources/shaders/mercator.vert"),
+            display
+        );
+        let miller = create_pseudocylindrical_gl_program_pair(
+            &with_define(include_str!("resources/shaders/mercator.vert"), "MILLER"),
+            display
+        );
+        let gall_stereographic = create_pseudocylindrical_gl_program_pair(
+            &with_define(include_str!("resources/shaders/mercator.vert"), "GALL_STEREOGRAPHIC"),
+            display
+        );
+        let qsc = create_interrupted_gl_program_pair(
+            include_str!("resources/shaders/qsc.vert"),
+            display
+        );
+        let guyou = create_gl_program_pair(
+            &with_define(include_str!("resources/shaders/conformal_square.vert"), "GUYOU"),
+            display
+        );
+        let peirce_quincuncial = create_tiled_gl_program_pair(
+            &with_define(include_str!("resources/shaders/conformal_square.vert"), "PEIRCE_QUINCUNCIAL"),
+            display
+        );
+        let nicolosi = create_gl_program_pair(
+            include_str!("resources/shaders/nicolosi.vert"),
+            display
+        );
+        let orthographic = create_gl_program_pair(
+            include_str!("resources/shaders/orthographic.vert"),
+            display
+        );
+        let stereographic = create_gl_program_pair(
+            include_str!("resources/shaders/stereographic.vert"),
+            display
+        );
+        let transverse_mercator = create_gl_program_pair(
+            include_str!("resources/shaders/transverse_mercator.vert"),
+            display
+        );
+
+        let plugins = plugins::load_plugins().into_iter().map(|p| create_plugin_data(p, display)).collect();
+
+        let mut layer_visibility = LayerVisibility::default();
+        layer_visibility.set_visible(Layer::Graticule, config.graticule.visible);
+
+        let unit_quad_data = [
+            XyVertex{ position: [-1.0, -1.0] },
+            XyVertex{ position: [ 1.0, -1.0] },
+            XyVertex{ position: [ 1.0,  1.0] },
+            XyVertex{ position: [-1.0,  1.0] }
+        ];
+        let unit_quad = Rc::new(glium::VertexBuffer::new(display, &unit_quad_data).unwrap());
+
+        ProgramData{
+            id_counter: Rc::new(RefCell::new(0)),
+
+            globe_texture,
+
+            base_maps: config.base_maps.iter()
+                .filter(|base_map| std::path::Path::new(&base_map.path).is_file())
+                .map(|base_map| BaseMap{ name: base_map.name.clone(), path: base_map.path.clone(), texture: None })
+                .collect(),
+
+            globe_gl_buf,
+
+            graticule_gl_buf,
+
+            graticule_step,
+
+            default_graticule_labels: config.graticule.labels.graticule_labels(),
+
+            sampling: Sampling::with_num_samples(config.msaa_samples),
+
+            distance_unit: config.units.distance_unit(),
+
+            tile_url: config.tile_url.clone(),
+
+            plugins,
+
+            overlay_script: None,
+
+            animation_clock: AnimationClock::default(),
+
+            vector_layers: std::iter::once(coastline_layer).chain(optional_layers).collect(),
+
+            night_time: None,
+
+            night_shading: None,
+
+            night_lights,
+
+            overlay: None,
+            overlay_frames: vec![],
+            overlay_frame: 0,
+
+            hillshading: None,
+
+            web_tiles: None,
+
+            image_overlay: None,
+
+            scalar_field: None,
+
+            vector_field: None,
+
+            choropleth_layer: None,
+
+            choropleth: None,
+
+            shape_features: HashMap::new(),
+
+            line_simplification: 0.0,
+
+            tile_fetch: None,
+
+            earthquake_fetch: None,
+
+            places: Rc::clone(&places),
+
+            earth_places: places,
+
+            gazetteer,
+
+            time_zone_labels: Rc::clone(&time_zone_labels),
+
+            earth_time_zone_labels: time_zone_labels,
+
+            user_markers: Rc::new(vec![]),
+
+            point_layers: Rc::new(vec![]),
+
+            body: 0,
+
+            body_files: BODIES.iter().map(|body| config.body_files(body)).collect(),
+
+            land_fill,
+
+            layer_visibility,
+
+            albers_equal_area_views: vec![],
+
+            armadillo_views: vec![],
+
+            conformal_square_views: vec![],
+
+            cylindrical_lambert_views: vec![],
+
+            eckert_views: vec![],
+
+            elliptical_views: vec![],
+
+            general_perspective_views: vec![],
+
+            gnomonic_views: vec![],
+
+            lambert_azimuthal_views: vec![],
+
+            lambert_conformal_conic_views: vec![],
+
+            littrow_views: vec![],
+
+            loximuthal_views: vec![],
+
+            mercator_views: vec![],
+
+            nicolosi_views: vec![],
+
+            orthographic_views: vec![],
+
+            plugin_views: vec![],
+
+            qsc_views: vec![],
+
+            stereographic_views: vec![],
+
+            transverse_mercator_views: vec![],
+
+            gl_programs: OpenGlPrograms {
+                texture_copy_single,
+                texture_copy_multi,
+                adams_hemisphere,
+                albers_equal_area,
+                armadillo,
+                cylindrical_lambert,
+                eckert_iv,
+                eckert_vi,
+                gall_stereographic,
+                general_perspective,
+                gnomonic,
+                guyou,
+                hammer,
+                lambert_azimuthal,
+                lambert_conformal_conic,
+                littrow,
+                loximuthal,
+                mercator,
+                miller,
+                mollweide,
+                nicolosi,
+                orthographic,
+                outline,
+                peirce_quincuncial,
+                qsc,
+                stereographic,
+                transverse_mercator
+            },
+
+            unit_quad,
+        }
+    }
+
+    pub fn new_unique_id(&self) -> u32 {
+        let new_id = *self.id_counter.borrow();
+        *self.id_counter.borrow_mut() += 1;
+
+        new_id
+    }
+
+    pub fn next_unique_id(&self) -> u32 { *self.id_counter.borrow() }
+
+    /// Makes `id` the next unique id to be issued; it must not be the id of an existing object.
+    pub fn set_next_unique_id(&self, id: u32) {
+        *self.id_counter.borrow_mut() = id;
+    }
+
+    pub fn layer_visibility(&self) -> LayerVisibility { self.layer_visibility }
+
+    /// Replaces the globe texture (in all views) with an equirectangular image loaded from `path`.
+    pub fn load_globe_texture(&mut self, path: &str, display: &glium::Display) -> Result<(), String> {
+        self.set_globe_texture(create_texture_from_image(path, display)?);
+
+        Ok(())
+    }
+
+    /// Draws a GeoTIFF raster loaded from `path` (reprojected to equirectangular) onto the globe texture.
+    /// The texture is enlarged (if possible) to match the raster's resolution.
+    pub fn load_geotiff(&mut self, path: &str, display: &glium::Display) -> Result<(), String> {
+        let raster = geotiff::load(path)?;
+
+        let raw: glium::texture::RawImage2d<u8> = self.globe_texture.read();
+        let mut image = image::RgbaImage::from_raw(raw.width, raw.height, raw.data.into_owned()).unwrap();
+
+        let max_width = (display.get_capabilities().max_texture_size as u32).min(MAX_GEOTIFF_TEXTURE_WIDTH);
+        let width = ((2.0 * std::f64::consts::PI / raster.pixel_size()).ceil() as u32).min(max_width);
+        if width > image.width() {
+            image = image::imageops::resize(&image, width, width / 2, image::imageops::FilterType::Triangle);
+        }
+
+        if raster.reproject(&mut image) == 0 {
+            return Err("the raster contains no valid pixels".into());
+        }
+        self.set_globe_texture(create_texture(image::DynamicImage::ImageRgba8(image), display)?);
+
+        Ok(())
+    }
+
+    fn set_globe_texture(&mut self, texture: glium::texture::texture2d::Texture2d) {
+        self.globe_texture = Rc::new(texture);
+        let globe_texture = Rc::clone(&self.globe_texture);
+        for view in self.all_views_mut().filter(|view| view.base_map() == 0) {
+            view.set_base_map(0, Rc::clone(&globe_texture));
+        }
+    }
+
+    /// Returns names of the selectable base maps; the first one is the globe texture.
+    pub fn base_map_names(&self) -> Vec<String> {
+        std::iter::once("default".to_string()).chain(self.base_maps.iter().map(|b| b.name.clone())).collect()
+    }
+
+    /// Shows the `index`-th base map (see `base_map_names`) in the view with the specified unique id.
+    pub fn set_view_base_map(
+        &mut self,
+        unique_id: u32,
+        index: usize,
+        display: &glium::Display
+    ) -> Result<(), String> {
+        let texture = if index == 0 {
+            Rc::clone(&self.globe_texture)
+        } else {
+            let base_map = &mut self.base_maps[index - 1];
+            match &base_map.texture {
+                Some(texture) => Rc::clone(texture),
+                None => {
+                    let texture = Rc::new(create_texture_from_image(&base_map.path, display)
+                        .map_err(|e| format!("{}: {}", base_map.path, e))?);
+                    base_map.texture = Some(Rc::clone(&texture));
+                    texture
+                }
+            }
+        };
+        if let Some(view) = self.all_views_mut().find(|view| view.unique_id() == unique_id) {
+            view.set_base_map(index, texture);
+        }
+
+        Ok(())
+    }
+
+    pub fn body(&self) -> &'static Body { &BODIES[self.body] }
+
+    pub fn is_earth(&self) -> bool { self.body == 0 }
+
+    /// Maps the `index`-th of `BODIES` in all views: replaces the globe texture and the coastline with the body's
+    /// texture and outlines (missing files give a placeholder texture and no outlines). The Earth's other data
+    /// (rivers and lakes, places, land fill, night shading) are not shown for other bodies.
+    pub fn set_body(&mut self, index: usize, display: &glium::Display) -> Result<(), String> {
+        let (texture_path, outlines_path) = &self.body_files[index];
+        let texture = if std::path::Path::new(texture_path).is_file() {
+            create_texture_from_image(texture_path, display)?
+        } else {
+            create_placeholder_texture(display)
+        };
+        let (outlines, outlines_gl_buf, shapefile) = if std::path::Path::new(outlines_path).is_file() {
+            let (outlines, gl_buf) = load_shape_lines(outlines_path, display)?;
+            (outlines, gl_buf, Some(outlines_path.to_string()))
+        } else {
+            (vec![], create_polylines(&[], display), None)
+        };
+
+        self.body = index;
+        // other base maps show the Earth
+        let globe_texture = Rc::new(texture);
+        self.globe_texture = Rc::clone(&globe_texture);
+        for view in self.all_views_mut() {
+            view.set_base_map(0, Rc::clone(&globe_texture));
+        }
+
+        let mut base_map = VectorLayer{
+            name: if self.is_earth() { "coastline".into() } else { "outlines".into() },
+            lines: vec![
+                StyledLines::from_shape_lines(outlines.clone(), outlines_gl_buf, self.line_simplification, display)
+            ],
+            polylines: outlines,
+            style: COASTLINE_STYLE,
+            visible: true,
+            base_map: true,
+            layer: Layer::VectorLayers,
+            shapefile,
+            filter: None
+        };
+        match self.vector_layers.iter().position(|layer| layer.base_map) {
+            Some(idx) => {
+                base_map.style = self.vector_layers[idx].style;
+                base_map.visible = self.vector_layers[idx].visible;
+                self.vector_layers[idx] = base_map;
+            },
+            None => self.vector_layers.insert(0, base_map)
+        }
+        self.update_views_vector_layers();
+
+        if !self.is_earth() { self.set_night_time(None, display); }
+        // tiles are of the previous body
+        self.remove_web_tiles();
+        self.places = if self.is_earth() { Rc::clone(&self.earth_places) } else { Rc::new(vec![]) };
+        self.time_zone_labels =
+            if self.is_earth() { Rc::clone(&self.earth_time_zone_labels) } else { Rc::new(vec![]) };
+
+        let body = *self.body();
+        let places = Rc::clone(&self.places);
+        let time_zone_labels = Rc::clone(&self.time_zone_labels);
+        let land_texture = self.land_texture();
+        for view in self.all_views_mut() {
+            view.set_body(body);
+            view.set_places(Rc::clone(&places));
+            view.set_time_zone_labels(Rc::clone(&time_zone_labels));
+            view.set_land_texture(land_texture.clone());
+        }
+
+        Ok(())
+    }
+
+    pub fn land_fill(&self) -> Option<&LandFill> { self.land_fill.as_ref() }
+
+    pub fn night_time(&self) -> Option<solar::UtcDateTime> { self.night_time }
+
+    pub fn night_shading(&self) -> Option<NightShading> { self.night_shading.clone() }
+
+    pub fn has_night_lights(&self) -> bool { self.night_lights.is_some() }
+
+    /// Shades the night hemisphere at `time` in all views (`None`: removes the shading).
+    pub fn set_night_time(&mut self, time: Option<solar::UtcDateTime>, display: &glium::Display) {
+        if time == self.night_time { return; }
+
+        self.night_time = time;
+        self.night_shading = time.map(|time| {
+            let (longitude, latitude) = solar::subsolar_point(&time);
+            let sun_direction = projection::lonlat_to_xyz(longitude.into(), latitude.into()).cast::<f32>().unwrap();
+            NightShading{
+                texture: Rc::new(create_night_texture(&time, display)),
+                sun_direction: sun_direction.into(),
+                night_lights: self.night_lights.clone()
+            }
+        });
+        self.update_views_night_shading();
+    }
+
+    /// Loads an equirectangular image of night lights shown on the night side in the globe texture mode.
+    pub fn load_night_lights(&mut self, path: &str, display: &glium::Display) -> Result<(), String> {
+        self.night_lights = Some(Rc::new(create_texture_from_image(path, display)?));
+        if let Some(night_shading) = &mut self.night_shading {
+            night_shading.night_lights = self.night_lights.clone();
+        }
+        self.update_views_night_shading();
+
+        Ok(())
+    }
+
+    fn update_views_night_shading(&mut self) {
+        let night_shading = self.night_shading.clone();
+        for view in self.all_views_mut() {
+            view.set_night_shading(night_shading.clone());
+        }
+    }
+
+    pub fn overlay(&self) -> Option<&Overlay> { self.overlay.as_ref() }
+
+    /// Loads an equirectangular image blended over the globe texture in all views (replacing the previous one).
+    pub fn load_overlay(&mut self, path: &str, display: &glium::Display) -> Result<(), String> {
+        self.show_overlay_image(path, display)?;
+        self.overlay_frames.clear();
+
+        Ok(())
+    }
+
+    /// Loads a sequence of equirectangular images (e.g. hourly cloud cover) shown as the overlay one at a time,
+    /// starting at the current time step (see `set_time_step`).
+    pub fn load_overlay_sequence(&mut self, paths: Vec<String>, display: &glium::Display) -> Result<(), String> {
+        if paths.is_empty() { return Err("no images selected".into()); }
+        let frame = self.time_step().min(paths.len() - 1);
+        self.show_overlay_image(&paths[frame], display)?;
+        self.overlay_frames = paths;
+        self.overlay_frame = frame;
+
+        Ok(())
+    }
+
+    fn show_overlay_image(&mut self, path: &str, display: &glium::Display) -> Result<(), String> {
+        let opacity = self.overlay.as_ref().map_or(DEFAULT_OVERLAY_OPACITY, |overlay| overlay.opacity);
+        self.overlay = Some(Overlay{ texture: Rc::new(create_texture_from_image(path, display)?), opacity });
+        self.update_views_overlay();
+
+        Ok(())
+    }
+
+    pub fn set_overlay_opacity(&mut self, opacity: f32) {
+        if let Some(overlay) = &mut self.overlay {
+            overlay.opacity = opacity.max(0.0).min(1.0);
+        }
+        self.update_views_overlay();
+    }
+
+    pub fn remove_overlay(&mut self) {
+        self.overlay = None;
+        self.overlay_frames.clear();
+        self.update_views_overlay();
+    }
+
+    fn update_views_overlay(&mut self) {
+        let overlay = self.overlay.clone();
+        for view in self.all_views_mut() {
+            view.set_overlay(overlay.clone());
+        }
+    }
+
+    pub fn web_tiles(&self) -> Option<&WebTiles> { self.web_tiles.as_ref() }
+
+    pub fn tile_fetch(&self) -> Option<&web_tiles::TileFetch> { self.tile_fetch.as_ref() }
+
+    /// Starts fetching tiles from `source` covering the region (degrees; see `web_tiles::TileRange::covering`)
+    /// at a zoom level of at most `max_zoom`; they are shown (replacing the previous ones) once all have been received
+    /// (see `poll_web_tiles`). Does nothing if the same tiles are being fetched or have been fetched without errors.
+    pub fn fetch_web_tiles(
+        &mut self,
+        source: &web_tiles::TileSource,
+        lon_range: [f64; 2],
+        lat_range: [f64; 2],
+        max_zoom: u32
+    ) -> Result<(), String> {
+        let range = web_tiles::TileRange::covering(lon_range, lat_range, max_zoom.min(source.max_zoom()?));
+        if let Some(fetch) = &self.tile_fetch {
+            if fetch.source == *source && fetch.range == range && (!fetch.is_complete() || fetch.num_failed == 0) {
+                return Ok(());
+            }
+        }
+        self.tile_fetch = Some(web_tiles::TileFetch::start(source, range)?);
+
+        Ok(())
+    }
+
+    /// Receives fetched web tiles; once all have been received, shows them in all views. Has to be called
+    /// regularly (e.g. every frame).
+    pub fn poll_web_tiles(&mut self, display: &glium::Display) {
+        let fetch = match &mut self.tile_fetch {
+            Some(fetch) => fetch,
+            None => return
+        };
+        if !fetch.poll() { return; }
+
+        let max_size = (display.get_capabilities().max_texture_size as u32).min(MAX_WEB_TILES_TEXTURE_SIZE);
+        let (lon_range, lat_range) = fetch.range.bounds();
+        match create_rgba_texture(fetch.resample(max_size), display) {
+            Ok(texture) => {
+                self.web_tiles = Some(WebTiles{ texture: Rc::new(texture), lon_range, lat_range });
+                self.update_views_web_tiles();
+            },
+            Err(e) => eprintln!("Failed to create texture of web tiles: {}", e)
+        }
+    }
+
+    pub fn remove_web_tiles(&mut self) {
+        self.tile_fetch = None;
+        self.web_tiles = None;
+        self.update_views_web_tiles();
+    }
+
+    fn update_views_web_tiles(&mut self) {
+        let web_tiles = self.web_tiles.clone();
+        for view in self.all_views_mut() {
+            view.set_web_tiles(web_tiles.clone());
+        }
+    }
+
+    pub fn image_overlay(&self) -> Option<&ImageOverlay> { self.image_overlay.as_ref() }
+
+    /// Loads an image, warps it into the region given by `georeference` and shows it in all views (replacing
+    /// the previous one, keeping its opacity).
+    pub fn load_image_overlay(
+        &mut self,
+        path: &str,
+        georeference: &georeferencing::Georeference,
+        display: &glium::Display
+    ) -> Result<(), String> {
+        let image = image::open(path).map_err(|e| e.to_string())?.into_rgba8();
+        let max_size = (display.get_capabilities().max_texture_size as u32).min(MAX_IMAGE_OVERLAY_TEXTURE_SIZE);
+        let warped = georeferencing::warp(&image, georeference, max_size)?;
+        self.image_overlay = Some(ImageOverlay{
+            texture: Rc::new(create_rgba_texture(warped.image, display)?),
+            lon_range: warped.lon_range,
+            lat_range: warped.lat_range,
+            opacity: self.image_overlay.as_ref().map_or(DEFAULT_IMAGE_OVERLAY_OPACITY, |overlay| overlay.opacity)
+        });
+        self.update_views_image_overlay();
+
+        Ok(())
+    }
+
+    pub fn set_image_overlay_opacity(&mut self, opacity: f32) {
+        if let Some(overlay) = &mut self.image_overlay {
+            overlay.opacity = opacity.max(0.0).min(1.0);
+        }
+        self.update_views_image_overlay();
+    }
+
+    pub fn remove_image_overlay(&mut self) {
+        self.image_overlay = None;
+        self.update_views_image_overlay();
+    }
+
+    /// Returns the places searched by name (empty for bodies other than the Earth).
+    pub fn gazetteer(&self) -> Option<&Gazetteer> {
+        if self.is_earth() { Some(&self.gazetteer) } else { None }
+    }
+
+    fn update_views_image_overlay(&mut self) {
+        let image_overlay = self.image_overlay.clone();
+        for view in self.all_views_mut() {
+            view.set_image_overlay(image_overlay.clone());
+        }
+    }
+
+    pub fn scalar_field(&self) -> Option<&ScalarField> { self.scalar_field.as_ref() }
+
+    /// Loads the field `variable` (at `index` of its first leading dimension) from a NetCDF file and shows it
+    /// in all views, with the value range set to the field's.
+    pub fn load_scalar_field(
+        &mut self,
+        path: &str,
+        variable: &str,
+        index: usize,
+        display: &glium::Display
+    ) -> Result<(), String> {
+        let grid = scalar_field::load(path, variable, index)?;
+        let (colormap, opacity) = self.scalar_field.as_ref().map_or(
+            (scalar_field::Colormap::Viridis, DEFAULT_SCALAR_FIELD_OPACITY),
+            |field| (field.params.colormap, field.params.opacity)
+        );
+        let params = ScalarFieldParams{ colormap, value_range: grid.value_range, opacity };
+        let texture = Rc::new(create_rgba_texture(grid.to_image(params.colormap, params.value_range), display)?);
+        let source = NetCdfSource{
+            path: path.to_string(),
+            variables: vec![variable.to_string()],
+            step: index,
+            step_labels: Rc::new(scalar_field::step_labels(path, variable)?)
+        };
+        self.scalar_field = Some(ScalarField{ grid: Rc::new(grid), source, params, texture });
+        self.update_views_scalar_field();
+
+        Ok(())
+    }
+
+    pub fn set_scalar_field_params(&mut self, params: ScalarFieldParams, display: &glium::Display) {
+        if let Some(field) = &mut self.scalar_field {
+            if params.colormap != field.params.colormap || params.value_range != field.params.value_range {
+                match create_rgba_texture(field.grid.to_image(params.colormap, params.value_range), display) {
+                    Ok(texture) => field.texture = Rc::new(texture),
+                    Err(e) => eprintln!("Failed to create texture of scalar field: {}", e)
+                }
+            }
+            field.params = params;
+        }
+        self.update_views_scalar_field();
+    }
+
+    pub fn remove_scalar_field(&mut self) {
+        self.scalar_field = None;
+        self.update_views_scalar_field();
+    }
+
+    fn update_views_scalar_field(&mut self) {
+        let scalar_field = self.scalar_field.clone();
+        for view in self.all_views_mut() {
+            view.set_scalar_field(scalar_field.clone());
+        }
+    }
+
+    pub fn vector_field(&self) -> Option<&VectorFieldLayer> { self.vector_field.as_ref() }
+
+    /// Loads a vector field with components `u_variable` (eastward) and `v_variable` (northward) from a NetCDF file
+    /// and shows it in all views.
+    pub fn load_vector_field(
+        &mut self,
+        path: &str,
+        u_variable: &str,
+        v_variable: &str,
+        index: usize
+    ) -> Result<(), String> {
+        let field = vector_field::load(path, u_variable, v_variable, index)?;
+        let params = VectorFieldParams{
+            scale: DEFAULT_MAX_ARROW_LENGTH / field.max_speed.max(f32::EPSILON),
+            ..self.vector_field.as_ref().map_or(Default::default(), |layer| layer.params)
+        };
+        let source = NetCdfSource{
+            path: path.to_string(),
+            variables: vec![u_variable.to_string(), v_variable.to_string()],
+            step: index,
+            step_labels: Rc::new(scalar_field::step_labels(path, u_variable)?)
+        };
+        self.vector_field = Some(VectorFieldLayer{ field: Rc::new(field), source, params });
+        self.update_views_vector_field();
+
+        Ok(())
+    }
+
+    pub fn set_vector_field_params(&mut self, params: VectorFieldParams) {
+        if let Some(layer) = &mut self.vector_field {
+            layer.params = params;
+        }
+        self.update_views_vector_field();
+    }
+
+    pub fn remove_vector_field(&mut self) {
+        self.vector_field = None;
+        self.update_views_vector_field();
+    }
+
+    fn update_views_vector_field(&mut self) {
+        let vector_field = self.vector_field.clone();
+        for view in self.all_views_mut() {
+            view.set_vector_field(vector_field.clone());
+        }
+    }
+
+    pub fn choropleth(&self) -> Option<&Choropleth> { self.choropleth.as_ref() }
+
+    /// Returns the names of numeric attributes of the choropleth's polygons.
+    pub fn choropleth_fields(&self) -> &[String] {
+        self.choropleth_layer.as_ref().map_or(&[][..], |layer| layer.fields.as_slice())
+    }
+
+    /// Loads polygons from a shapefile and shows them in all views colored by classes of their first numeric
+    /// attribute (keeping the other parameters of the previous choropleth).
+    pub fn load_choropleth(&mut self, path: &str, display: &glium::Display) -> Result<(), String> {
+        let layer = choropleth::load(path)?;
+        let params = ChoroplethParams{
+            field: 0,
+            ..self.choropleth.as_ref().map_or(Default::default(), |choropleth| choropleth.params)
+        };
+        self.choropleth = Some(create_choropleth(&layer, params, display)?);
+        self.choropleth_layer = Some(layer);
+        self.update_views_choropleth();
+
+        Ok(())
+    }
+
+    pub fn set_choropleth_params(&mut self, params: ChoroplethParams, display: &glium::Display) -> Result<(), String> {
+        if let Some(layer) = &self.choropleth_layer {
+            self.choropleth = Some(create_choropleth(layer, params, display)?);
+            self.update_views_choropleth();
+        }
+
+        Ok(())
+    }
+
+    pub fn remove_choropleth(&mut self) {
+        self.choropleth_layer = None;
+        self.choropleth = None;
+        self.update_views_choropleth();
+    }
+
+    fn update_views_choropleth(&mut self) {
+        let choropleth = self.choropleth.clone();
+        for view in self.all_views_mut() {
+            view.set_choropleth(choropleth.clone());
+        }
+    }
+
+    /// Returns the number of steps of time-animated layers (overlay sequence, NetCDF fields with a leading
+    /// dimension such as time); 1 if there are none.
+    pub fn num_time_steps(&self) -> usize {
+        self.time_step_counts().max().unwrap_or(1)
+    }
+
+    fn time_step_counts(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::once(self.overlay_frames.len())
+            .chain(self.scalar_field.as_ref().map(|field| field.source.step_labels.len()))
+            .chain(self.vector_field.as_ref().map(|layer| layer.source.step_labels.len()))
+            .filter(|&count| count > 1)
+    }
+
+    /// Returns the current step of time-animated layers (the greatest of their steps).
+    pub fn time_step(&self) -> usize {
+        let field_step = |source: &NetCdfSource| if source.step_labels.len() > 1 { source.step } else { 0 };
+        let overlay_step = if self.overlay_frames.len() > 1 { self.overlay_frame } else { 0 };
+
+        overlay_step
+            .max(self.scalar_field.as_ref().map_or(0, |field| field_step(&field.source)))
+            .max(self.vector_field.as_ref().map_or(0, |layer| field_step(&layer.source)))
+    }
+
+    /// Returns the label of the current time step: the date (or coordinate) of a NetCDF field's step or the file
+    /// name of the overlay's frame.
+    pub fn time_step_label(&self) -> String {
+        let sources = self.scalar_field.iter().map(|field| &field.source)
+            .chain(self.vector_field.iter().map(|layer| &layer.source));
+        for source in sources {
+            if source.step_labels.len() > 1 { return source.step_labels[source.step].clone(); }
+        }
+        match self.overlay_frames.get(self.overlay_frame) {
+            Some(path) => std::path::Path::new(path).file_name().unwrap_or_default().to_string_lossy().into_owned(),
+            None => String::new()
+        }
+    }
+
+    /// Shows all time-animated layers at `step` (layers with fewer steps show their last one).
+    pub fn set_time_step(&mut self, step: usize, display: &glium::Display) -> Result<(), String> {
+        let overlay_frame = step.min(self.overlay_frames.len().max(1) - 1);
+        if self.overlay_frames.len() > 1 && overlay_frame != self.overlay_frame {
+            let path = self.overlay_frames[overlay_frame].clone();
+            self.show_overlay_image(&path, display)?;
+            self.overlay_frame = overlay_frame;
+        }
+
+        if let Some(field) = &self.scalar_field {
+            let index = step.min(field.source.step_labels.len() - 1);
+            if index != field.source.step {
+                let grid = scalar_field::load(&field.source.path, &field.source.variables[0], index)?;
+                let params = field.params;
+                let texture = create_rgba_texture(grid.to_image(params.colormap, params.value_range), display)?;
+                let field = self.scalar_field.as_mut().unwrap();
+                field.grid = Rc::new(grid);
+                field.texture = Rc::new(texture);
+                field.source.step = index;
+                self.update_views_scalar_field();
+            }
+        }
+
+        if let Some(layer) = &self.vector_field {
+            let index = step.min(layer.source.step_labels.len() - 1);
+            if index != layer.source.step {
+                let variables = &layer.source.variables;
+                let field = vector_field::load(&layer.source.path, &variables[0], &variables[1], index)?;
+                let layer = self.vector_field.as_mut().unwrap();
+                layer.field = Rc::new(field);
+                layer.source.step = index;
+                self.update_views_vector_field();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn hillshading(&self) -> Option<&Hillshading> { self.hillshading.as_ref() }
+
+    /// Loads an equirectangular elevation raster (a grayscale image, 8 or 16 bits per pixel) and shades relief
+    /// in all views.
+    pub fn load_elevation(
+        &mut self,
+        path: &str,
+        params: HillshadingParams,
+        display: &glium::Display
+    ) -> Result<(), String> {
+        let elevation = Rc::new(create_elevation_texture(image::open(path).map_err(|e| e.to_string())?, display)?);
+        self.hillshading = Some(Hillshading{ elevation, params });
+        self.update_views_hillshading();
+
+        Ok(())
+    }
+
+    pub fn set_hillshading_params(&mut self, params: HillshadingParams) {
+        if let Some(hillshading) = &mut self.hillshading {
+            hillshading.params = params;
+        }
+        self.update_views_hillshading();
+    }
+
+    pub fn remove_hillshading(&mut self) {
+        self.hillshading = None;
+        self.update_views_hillshading();
+    }
+
+    fn update_views_hillshading(&mut self) {
+        let hillshading = self.hillshading.clone();
+        for view in self.all_views_mut() {
+            view.set_hillshading(hillshading.clone());
+        }
+    }
+
+    /// Returns the texture with filled land and ocean (if available) to be drawn by views in the vector map mode.
+    pub fn land_texture(&self) -> Option<Rc<glium::Texture2d>> {
+        self.land_fill.as_ref().filter(|_| self.is_earth()).map(|land_fill| Rc::clone(&land_fill.texture))
+    }
+
+    /// Changes the colors of filled land and ocean (if available) in all views.
+    pub fn set_land_colors(
+        &mut self,
+        land_color: [f32; 3],
+        ocean_color: [f32; 3],
+        display: &glium::Display
+    ) -> Result<(), String> {
+        let land_fill = match &mut self.land_fill {
+            Some(land_fill) => land_fill,
+            None => return Err("land polygons are not loaded".into())
+        };
+        land_fill.texture = Rc::new(create_land_texture(&land_fill.mask, land_color, ocean_color, display)?);
+        land_fill.land_color = land_color;
+        land_fill.ocean_color = ocean_color;
+
+        let texture = self.land_texture();
+        for view in self.all_views_mut() {
+            view.set_land_texture(texture.clone());
+        }
+
+        Ok(())
+    }
+
+    pub fn user_markers(&self) -> &Rc<Vec<UserMarker>> { &self.user_markers }
+
+    /// Replaces the named markers (in all views).
+    pub fn set_user_markers(&mut self, user_markers: Vec<UserMarker>) {
+        self.user_markers = Rc::new(user_markers);
+        let user_markers = Rc::clone(&self.user_markers);
+        for view in self.all_views_mut() {
+            view.set_user_markers(Rc::clone(&user_markers));
+        }
+    }
+
+    pub fn vector_layers(&self) -> &[VectorLayer] { &self.vector_layers }
+
+    /// Returns lines of all visible vector layers.
+    pub fn vector_layer_lines(&self) -> Vec<DrawnLines> {
+        // rivers, lakes, plate boundaries and time zones are the Earth's
+        let is_earth_layer = |layer: &VectorLayer|
+            [Layer::RiversAndLakes, Layer::PlateBoundaries, Layer::TimeZones].contains(&layer.layer);
+        let is_shown = |layer: &VectorLayer| layer.visible && (self.is_earth() || !is_earth_layer(layer));
+        self.vector_layers.iter().filter(|layer| is_shown(*layer)).flat_map(|layer| {
+            layer.lines.iter().map(move |lines| DrawnLines{
+                style: lines.style.unwrap_or(layer.style),
+                gl_buf: lines.gl_buf.clone(),
+                base_map: layer.base_map,
+                layer: layer.layer
+            })
+        }).collect()
+    }
+
+    /// Loads a vector layer from a GeoJSON or KML/KMZ file (chosen by extension) and shows it in all views.
+    pub fn load_vector_layer(&mut self, path: &str, display: &glium::Display) -> Result<(), String> {
+        let lower_case_path = path.to_lowercase();
+        let placemarks = if lower_case_path.ends_with(".kml") || lower_case_path.ends_with(".kmz") {
+            kml::load(path)?
+        } else {
+            vec![kml::Placemark{ style: None, lines: geojson::load(path)? }]
+        };
+        self.add_vector_layer(path.to_string(), placemarks, VECTOR_LAYER_STYLE, display);
+
+        Ok(())
+    }
+
+    /// Adds a vector layer and shows it in all views; `style` is used for placemarks which do not specify their own.
+    pub fn add_vector_layer(
+        &mut self,
+        name: String,
+        placemarks: Vec<kml::Placemark>,
+        style: LineStyle,
+        display: &glium::Display
+    ) {
+        let polylines = placemarks.iter().flat_map(|placemark| placemark.lines.iter().cloned()).collect();
+
+        // lines of the same style are drawn together
+        let mut styles: Vec<(Option<LineStyle>, Vec<geojson::Polyline>)> = vec![];
+        for placemark in placemarks {
+            match styles.iter_mut().find(|(style, _)| *style == placemark.style) {
+                Some((_, lines)) => lines.extend(placemark.lines),
+                None => styles.push((placemark.style, placemark.lines))
+            }
+        }
+
+        self.vector_layers.push(VectorLayer{
+            name,
+            polylines,
+            lines: styles.into_iter()
+                .map(|(style, lines)| StyledLines::new(style, lines, self.line_simplification, display))
+                .collect(),
+            style,
+            visible: true,
+            base_map: false,
+            layer: Layer::VectorLayers,
+            shapefile: None,
+            filter: None
+        });
+        self.update_views_vector_layers();
+    }
+
+    /// Loads tectonic plate boundaries from a GeoJSON file (replacing the previous ones) and shows them in all views.
+    pub fn load_plate_boundaries(&mut self, path: &str, display: &glium::Display) -> Result<(), String> {
+        let layer = plate_boundaries_layer(path, self.line_simplification, display)?;
+        match self.vector_layers.iter().position(|layer| layer.layer == Layer::PlateBoundaries) {
+            Some(idx) => self.vector_layers[idx] = layer,
+            None => self.vector_layers.push(layer)
+        }
+        self.update_views_vector_layers();
+
+        Ok(())
+    }
+
+    pub fn has_plate_boundaries(&self) -> bool {
+        self.vector_layers.iter().any(|layer| layer.layer == Layer::PlateBoundaries)
+    }
+
+    /// Loads time-zone boundaries (replacing the previous ones) and shows them in all views.
+    pub fn load_time_zones(&mut self, path: &str, display: &glium::Display) -> Result<(), String> {
+        let (layer, labels) = time_zones_layer(path, self.line_simplification, display)?;
+        match self.vector_layers.iter().position(|layer| layer.layer == Layer::TimeZones) {
+            Some(idx) => self.vector_layers[idx] = layer,
+            None => self.vector_layers.push(layer)
+        }
+        self.update_views_vector_layers();
+
+        self.earth_time_zone_labels = Rc::new(labels);
+        if self.is_earth() {
+            self.time_zone_labels = Rc::clone(&self.earth_time_zone_labels);
+            let labels = Rc::clone(&self.time_zone_labels);
+            for view in self.all_views_mut() {
+                view.set_time_zone_labels(Rc::clone(&labels));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn has_time_zones(&self) -> bool {
+        self.vector_layers.iter().any(|layer| layer.layer == Layer::TimeZones)
+    }
+
+    pub fn set_vector_layer_visible(&mut self, index: usize, visible: bool) {
+        self.vector_layers[index].visible = visible;
+        self.update_views_vector_layers();
+    }
+
+    pub fn set_vector_layer_style(&mut self, index: usize, style: LineStyle) {
+        self.vector_layers[index].style = style;
+        self.update_views_vector_layers();
+    }
+
+    pub fn remove_vector_layer(&mut self, index: usize) {
+        self.vector_layers.remove(index);
+        self.update_views_vector_layers();
+    }
+
+    pub fn line_simplification(&self) -> f64 { self.line_simplification }
+
+    /// Simplifies lines of all vector layers with `tolerance` (degrees; 0: no simplification) for drawing; the source
+    /// lines are kept.
+    pub fn set_line_simplification(&mut self, tolerance: f64, display: &glium::Display) {
+        self.line_simplification = tolerance;
+        for layer in &mut self.vector_layers {
+            for lines in &mut layer.lines {
+                lines.gl_buf = create_simplified_polylines(&lines.polylines, tolerance, display);
+            }
+        }
+        self.update_views_vector_layers();
+    }
+
+    /// Returns the numbers of drawn (simplified) and source vertices of all vector layers.
+    pub fn vector_layer_vertices(&self) -> (usize, usize) {
+        let all_lines = || self.vector_layers.iter().flat_map(|layer| layer.lines.iter());
+        (
+            all_lines().map(|lines| lines.gl_buf.vertices.len()).sum(),
+            all_lines().map(|lines| lines.polylines.iter().map(|line| line.len()).sum::<usize>()).sum()
+        )
+    }
 
-    pub cylindrical_lambert_views: Vec<CylindricalLambertView>,
+    /// Returns the features of the shapefile of the `index`-th vector layer (loading them on first use).
+    fn vector_layer_features(&mut self, index: usize) -> Result<Rc<shape_filter::Features>, String> {
+        let path = self.vector_layers[index].shapefile.clone()
+            .ok_or_else(|| format!("{} was not loaded from a shapefile", self.vector_layers[index].name))?;
+        if let Some(features) = self.shape_features.get(&path) {
+            return Ok(Rc::clone(features));
+        }
+        let features = Rc::new(shape_filter::load(&path)?);
+        self.shape_features.insert(path, Rc::clone(&features));
 
-    pub gnomonic_views: Vec<GnomonicView>,
+        Ok(features)
+    }
 
-    pub orthographic_views: Vec<OrthographicView>,
+    /// Returns the names of attributes of features of the `index`-th vector layer's shapefile.
+    pub fn vector_layer_attributes(&mut self, index: usize) -> Result<Vec<String>, String> {
+        Ok(self.vector_layer_features(index)?.attributes.clone())
+    }
 
-    pub stereographic_views: Vec<StereographicView>
-}
+    /// Shows only the features of the `index`-th vector layer's shapefile which match `filter` (all if `None`);
+    /// returns the numbers of shown and all features.
+    pub fn set_vector_layer_filter(
+        &mut self,
+        index: usize,
+        filter: Option<shape_filter::AttributeFilter>,
+        display: &glium::Display
+    ) -> Result<(usize, usize), String> {
+        let features = self.vector_layer_features(index)?;
+        let (polylines, num_shown) = features.lines(filter.as_ref());
+
+        let layer = &mut self.vector_layers[index];
+        layer.lines = vec![StyledLines::new(None, polylines.clone(), self.line_simplification, display)];
+        layer.polylines = polylines;
+        layer.filter = filter;
+        self.update_views_vector_layers();
+
+        Ok((num_shown, features.len()))
+    }
 
-fn create_gl_program_pair(vertex_shader_source: &str, display: &glium::Display) -> GlProgramPair {
-    GlProgramPair{
-        lines: Rc::new(program!(display,
-            330 => {
-                vertex: vertex_shader_source,
-                geometry: include_str!("resources/shaders/lines.geom"),
-                fragment: include_str!("resources/shaders/uniform_color.frag")
-            }
-        ).unwrap()),
+    /// Exports the vector layers at `indices` projected by the view with the specified unique id
+    /// (see `export::export_vector_layers`).
+    pub fn export_vector_layers(&mut self, view_id: u32, indices: &[usize], path: &str) -> Result<(), String> {
+        // moved out temporarily, as views are only accessible mutably
+        let vector_layers = std::mem::take(&mut self.vector_layers);
+        let layers: Vec<&VectorLayer> = indices.iter().filter_map(|idx| vector_layers.get(*idx)).collect();
+        let result = match self.all_views_mut().find(|view| view.unique_id() == view_id) {
+            Some(view) => export::export_vector_layers(path, view, &layers),
+            None => Err("view not found".into())
+        };
+        self.vector_layers = vector_layers;
+
+        result
+    }
 
-        triangles: Rc::new(program!(display,
-                330 => {
-                    vertex: vertex_shader_source,
-                    geometry: include_str!("resources/shaders/tris.geom"),
-                    fragment: include_str!("resources/shaders/globe_texturing.frag")
-                }
-        ).unwrap())
+    fn update_views_vector_layers(&mut self) {
+        let lines = self.vector_layer_lines();
+        for view in self.all_views_mut() {
+            view.set_vector_layers(lines.clone());
+        }
     }
-}
 
-impl ProgramData {
-    pub fn new(display: &glium::Display) -> ProgramData {
-        let globe_texture = Rc::new(create_texture_from_image(
-            "data/world.topo.bathy.200412.3x8192x4096.jpg",
-            display
-        ));
+    pub fn point_layers(&self) -> &Rc<Vec<PointLayer>> { &self.point_layers }
 
-        let globe_gl_buf = create_globe_mesh(cgmath::Deg(2.0), display);
+    /// Adds a point layer and shows it in all views.
+    pub fn add_point_layer(&mut self, name: String, points: Vec<csv::Point>) {
+        Rc::make_mut(&mut self.point_layers).push(PointLayer::new(name, points, POINT_LAYER_STYLE));
+        self.update_views_point_layers();
+    }
 
-        let graticule_gl_buf = create_graticule(cgmath::Deg(10.0), 10, display);
+    /// Shows isogonic lines (every `spacing` degrees of declination) of the geomagnetic field of `model` at
+    /// the decimal year `year` as a vector layer, and the field's dip poles as a point layer (replacing
+    /// the previous ones). Returns the positions of the north and south dip poles.
+    pub fn show_magnetic_declination(
+        &mut self,
+        model: &geomagnetism::Model,
+        year: f64,
+        spacing: f64,
+        display: &glium::Display
+    ) -> Result<[[f64; 2]; 2], String> {
+        if spacing.is_nan() || spacing <= 0.0 { return Err("spacing must be positive".into()); }
+        let max_idx = (180.0 / spacing).ceil() as i32;
+        let levels: Vec<f64> = (-max_idx..=max_idx)
+            .map(|idx| idx as f64 * spacing)
+            .filter(|level| level.abs() < 180.0)
+            .collect();
+        let isolines = geometry::isolines(
+            |[lon, lat]| model.declination(lon, lat, year), &levels, DECLINATION_GRID_STEP, Some(360.0)
+        );
 
-        let map_gl_buf = create_map_from_shape_file(
-            "data/ne_10m_coastline/ne_10m_coastline.shp",
+        let mut placemarks: Vec<kml::Placemark> = [DECLINATION_WEST_STYLE, AGONIC_LINE_STYLE, DECLINATION_EAST_STYLE]
+            .iter()
+            .map(|style| kml::Placemark{ style: Some(*style), lines: vec![] })
+            .collect();
+        for (level, lines) in levels.iter().zip(isolines) {
+            let idx = if *level < 0.0 { 0 } else if *level == 0.0 { 1 } else { 2 };
+            placemarks[idx].lines.extend(lines);
+        }
+        self.remove_magnetic_declination();
+        self.add_vector_layer(
+            format!("{} ({} {:.1}, every {}°)", DECLINATION_LAYER_NAME, model.name, year, spacing),
+            placemarks,
+            DECLINATION_EAST_STYLE,
             display
         );
 
-        let texture_copy_single = Rc::new(program!(display,
-            330 => {
-                vertex: include_str!("resources/shaders/pass-through.vert"),
-                fragment: include_str!("resources/shaders/texturing.frag"),
-            }
-        ).unwrap());
-
-        let texture_copy_multi = Rc::new(program!(display,
-            330 => {
-                vertex: include_str!("resources/shaders/pass-through.vert"),
-                fragment: include_str!("resources/shaders/texturing_multi-sample.frag"),
-            }
-        ).unwrap());
+        let poles = model.dip_poles(year);
+        let points = poles.iter().zip(["north dip pole", "south dip pole"]).map(|([lon, lat], label)| csv::Point{
+            longitude: *lon,
+            latitude: *lat,
+            label: Some(label.to_string()),
+            value: None
+        }).collect();
+        let layer = PointLayer::new(DIP_POLES_LAYER_NAME.into(), points, DIP_POLES_STYLE);
+        Rc::make_mut(&mut self.point_layers).push(layer);
+        self.update_views_point_layers();
+
+        Ok(poles)
+    }
 
-        let cylindrical_lambert = create_gl_program_pair(
-            include_str!("resources/shaders/cylindrical_lambert.vert"),
-            display
-        );
-        let gnomonic = create_gl_program_pair(
-            include_str!("resources/shaders/gnomonic.vert"),
-            display
-        );
-        let orthographic = create_gl_program_pair(
-            include_str!("resources/shaders/orthographic.vert"),
-            display
-        );
-        let stereographic = create_gl_program_pair(
-            include_str!("resources/shaders/stereographic.vert"),
-            display
-        );
+    /// Removes the layers created by `show_magnetic_declination`.
+    pub fn remove_magnetic_declination(&mut self) {
+        self.vector_layers.retain(|layer| !layer.name.starts_with(DECLINATION_LAYER_NAME));
+        self.update_views_vector_layers();
+        Rc::make_mut(&mut self.point_layers).retain(|layer| layer.name != DIP_POLES_LAYER_NAME);
+        self.update_views_point_layers();
+    }
 
-        let unit_quad_data = [
-            XyVertex{ position: [-1.0, -1.0] },
-            XyVertex{ position: [ 1.0, -1.0] },
-            XyVertex{ position: [ 1.0,  1.0] },
-            XyVertex{ position: [-1.0,  1.0] }
-        ];
-        let unit_quad = Rc::new(glium::VertexBuffer::new(display, &unit_quad_data).unwrap());
+    pub fn earthquake_fetch(&self) -> Option<&earthquakes::FeedFetch> { self.earthquake_fetch.as_ref() }
 
-        ProgramData{
-            id_counter: Rc::new(RefCell::new(0)),
+    /// Starts fetching earthquakes of `feed`; they are shown once received (see `poll_earthquakes`).
+    pub fn fetch_earthquakes(&mut self, feed: earthquakes::Feed) {
+        self.earthquake_fetch = Some(earthquakes::FeedFetch::start(feed));
+    }
 
-            globe_texture,
+    /// Receives fetched earthquakes and shows them as a point layer, replacing the previous earthquakes (keeping
+    /// their style and visibility). Once fetching has finished, returns the number of earthquakes. Has to be called
+    /// regularly (e.g. every frame).
+    pub fn poll_earthquakes(&mut self) -> Option<Result<usize, String>> {
+        let result = self.earthquake_fetch.as_ref()?.poll()?;
+        let feed = self.earthquake_fetch.take().unwrap().feed;
+        let points = match result {
+            Ok(points) => points,
+            Err(e) => return Some(Err(e))
+        };
+        let num_earthquakes = points.len();
+
+        let mut layer = PointLayer::new(feed.layer_name(), points, EARTHQUAKES_STYLE);
+        let layers = Rc::make_mut(&mut self.point_layers);
+        match layers.iter().position(|layer| layer.name.starts_with(earthquakes::LAYER_NAME)) {
+            Some(idx) => {
+                layer.style = layers[idx].style;
+                layer.visible = layers[idx].visible;
+                layers[idx] = layer;
+            },
+            None => layers.push(layer)
+        }
+        self.update_views_point_layers();
 
-            globe_gl_buf,
+        Some(Ok(num_earthquakes))
+    }
 
-            graticule_gl_buf,
+    pub fn set_point_layer_visible(&mut self, index: usize, visible: bool) {
+        Rc::make_mut(&mut self.point_layers)[index].visible = visible;
+        self.update_views_point_layers();
+    }
 
-            map_gl_buf,
+    pub fn set_point_layer_style(&mut self, index: usize, style: PointStyle) {
+        Rc::make_mut(&mut self.point_layers)[index].style = style;
+        self.update_views_point_layers();
+    }
 
-            cylindrical_lambert_views: vec![],
+    pub fn remove_point_layer(&mut self, index: usize) {
+        Rc::make_mut(&mut self.point_layers).remove(index);
+        self.update_views_point_layers();
+    }
 
-            gnomonic_views: vec![],
+    fn update_views_point_layers(&mut self) {
+        let point_layers = Rc::clone(&self.point_layers);
+        for view in self.all_views_mut() {
+            view.set_point_layers(Rc::clone(&point_layers));
+        }
+    }
 
-            orthographic_views: vec![],
+    pub fn set_layer_visibility(&mut self, layer_visibility: LayerVisibility) {
+        self.layer_visibility = layer_visibility;
+        for view in self.all_views_mut() {
+            view.set_global_layers(layer_visibility);
+        }
+    }
 
-            stereographic_views: vec![],
+    /// Returns bases of all views.
+    pub fn all_views_mut(&mut self) -> impl Iterator<Item = &mut ViewBase> {
+        self.albers_equal_area_views.iter_mut().map(|v| v.base_mut())
+            .chain(self.armadillo_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.conformal_square_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.cylindrical_lambert_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.eckert_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.elliptical_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.general_perspective_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.gnomonic_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.lambert_azimuthal_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.lambert_conformal_conic_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.littrow_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.loximuthal_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.mercator_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.nicolosi_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.orthographic_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.plugin_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.qsc_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.stereographic_views.iter_mut().map(|v| v.base_mut()))
+            .chain(self.transverse_mercator_views.iter_mut().map(|v| v.base_mut()))
+    }
 
-            gl_programs: OpenGlPrograms {
-                texture_copy_single,
-                texture_copy_multi,
-                cylindrical_lambert,
-                gnomonic,
-                orthographic,
-                stereographic
-            },
+    /// Removes the view with the specified unique id (if any).
+    pub fn remove_view(&mut self, unique_id: u32) {
+        self.albers_equal_area_views.retain(|v| v.unique_id() != unique_id);
+        self.armadillo_views.retain(|v| v.unique_id() != unique_id);
+        self.conformal_square_views.retain(|v| v.unique_id() != unique_id);
+        self.cylindrical_lambert_views.retain(|v| v.unique_id() != unique_id);
+        self.eckert_views.retain(|v| v.unique_id() != unique_id);
+        self.elliptical_views.retain(|v| v.unique_id() != unique_id);
+        self.general_perspective_views.retain(|v| v.unique_id() != unique_id);
+        self.gnomonic_views.retain(|v| v.unique_id() != unique_id);
+        self.lambert_azimuthal_views.retain(|v| v.unique_id() != unique_id);
+        self.lambert_conformal_conic_views.retain(|v| v.unique_id() != unique_id);
+        self.littrow_views.retain(|v| v.unique_id() != unique_id);
+        self.loximuthal_views.retain(|v| v.unique_id() != unique_id);
+        self.mercator_views.retain(|v| v.unique_id() != unique_id);
+        self.nicolosi_views.retain(|v| v.unique_id() != unique_id);
+        self.orthographic_views.retain(|v| v.unique_id() != unique_id);
+        self.plugin_views.retain(|v| v.unique_id() != unique_id);
+        self.qsc_views.retain(|v| v.unique_id() != unique_id);
+        self.stereographic_views.retain(|v| v.unique_id() != unique_id);
+        self.transverse_mercator_views.retain(|v| v.unique_id() != unique_id);
+    }
 
-            unit_quad,
-        }
+    pub fn albers_equal_area_views(&mut self) -> &mut Vec<AlbersEqualAreaView> {
+        &mut self.albers_equal_area_views
     }
 
-    pub fn new_unique_id(&self) -> u32 {
-        let new_id = *self.id_counter.borrow();
-        *self.id_counter.borrow_mut() += 1;
+    pub fn armadillo_views(&mut self) -> &mut Vec<ArmadilloView> {
+        &mut self.armadillo_views
+    }
 
-        new_id
+    pub fn conformal_square_views(&mut self) -> &mut Vec<ConformalSquareView> {
+        &mut self.conformal_square_views
     }
 
     pub fn cylindrical_lambert_views(&mut self) -> &mut Vec<CylindricalLambertView> {
         &mut self.cylindrical_lambert_views
     }
 
+    pub fn eckert_views(&mut self) -> &mut Vec<EckertView> {
+        &mut self.eckert_views
+    }
+
+    pub fn elliptical_views(&mut self) -> &mut Vec<EllipticalView> {
+        &mut self.elliptical_views
+    }
+
+    pub fn general_perspective_views(&mut self) -> &mut Vec<GeneralPerspectiveView> {
+        &mut self.general_perspective_views
+    }
+
     pub fn gnomonic_views(&mut self) -> &mut Vec<GnomonicView> {
         &mut self.gnomonic_views
     }
 
+    pub fn lambert_azimuthal_views(&mut self) -> &mut Vec<LambertAzimuthalView> {
+        &mut self.lambert_azimuthal_views
+    }
+
+    pub fn lambert_conformal_conic_views(&mut self) -> &mut Vec<LambertConformalConicView> {
+        &mut self.lambert_conformal_conic_views
+    }
+
+    pub fn littrow_views(&mut self) -> &mut Vec<LittrowView> {
+        &mut self.littrow_views
+    }
+
+    pub fn loximuthal_views(&mut self) -> &mut Vec<LoximuthalView> {
+        &mut self.loximuthal_views
+    }
+
+    pub fn mercator_views(&mut self) -> &mut Vec<MercatorView> {
+        &mut self.mercator_views
+    }
+
+    pub fn nicolosi_views(&mut self) -> &mut Vec<NicolosiView> {
+        &mut self.nicolosi_views
+    }
+
     pub fn orthographic_views(&mut self) -> &mut Vec<OrthographicView> {
         &mut self.orthographic_views
     }
 
+    pub fn plugin_views(&mut self) -> &mut Vec<PluginView> {
+        &mut self.plugin_views
+    }
+
+    pub fn qsc_views(&mut self) -> &mut Vec<QscView> {
+        &mut self.qsc_views
+    }
+
     pub fn stereographic_views(&mut self) -> &mut Vec<StereographicView> {
         &mut self.stereographic_views
     }
 
+    pub fn transverse_mercator_views(&mut self) -> &mut Vec<TransverseMercatorView> {
+        &mut self.transverse_mercator_views
+    }
+
+    pub fn add_albers_equal_area_view(&mut self, view: AlbersEqualAreaView) {
+        self.albers_equal_area_views.push(view);
+    }
+
+    pub fn add_armadillo_view(&mut self, view: ArmadilloView) {
+        self.armadillo_views.push(view);
+    }
+
+    pub fn add_conformal_square_view(&mut self, view: ConformalSquareView) {
+        self.conformal_square_views.push(view);
+    }
+
     pub fn add_cylindrical_lambert_view(&mut self, view: CylindricalLambertView) {
         self.cylindrical_lambert_views.push(view);
     }
 
+    pub fn add_eckert_view(&mut self, view: EckertView) {
+        self.eckert_views.push(view);
+    }
+
+    pub fn add_elliptical_view(&mut self, view: EllipticalView) {
+        self.elliptical_views.push(view);
+    }
+
+    pub fn add_general_perspective_view(&mut self, view: GeneralPerspectiveView) {
+        self.general_perspective_views.push(view);
+    }
+
     pub fn add_gnomonic_view(&mut self, view: GnomonicView) {
         self.gnomonic_views.push(view);
     }
 
+    pub fn add_lambert_azimuthal_view(&mut self, view: LambertAzimuthalView) {
+        self.lambert_azimuthal_views.push(view);
+    }
+
+    pub fn add_lambert_conformal_conic_view(&mut self, view: LambertConformalConicView) {
+        self.lambert_conformal_conic_views.push(view);
+    }
+
+    pub fn add_littrow_view(&mut self, view: LittrowView) {
+        self.littrow_views.push(view);
+    }
+
+    pub fn add_loximuthal_view(&mut self, view: LoximuthalView) {
+        self.loximuthal_views.push(view);
+    }
+
+    pub fn add_mercator_view(&mut self, view: MercatorView) {
+        self.mercator_views.push(view);
+    }
+
+    pub fn add_nicolosi_view(&mut self, view: NicolosiView) {
+        self.nicolosi_views.push(view);
+    }
+
     pub fn add_orthographic_view(&mut self, view: OrthographicView) {
         self.orthographic_views.push(view);
     }
 
+    pub fn add_plugin_view(&mut self, view: PluginView) {
+        self.plugin_views.push(view);
+    }
+
+    pub fn add_qsc_view(&mut self, view: QscView) {
+        self.qsc_views.push(view);
+    }
+
     pub fn add_stereographic_view(&mut self, view: StereographicView) {
         self.stereographic_views.push(view);
     }
+
+    pub fn add_transverse_mercator_view(&mut self, view: TransverseMercatorView) {
+        self.transverse_mercator_views.push(view);
+    }
+}
+
+/// Creates buffers for a list of line segments, each specified by a pair of consecutive vertices.
+pub fn create_line_list(vertices: &[LonLatVertex], display: &glium::Display) -> LonLatGlBuffers {
+    let index_data: Vec<u32> = (0..vertices.len() as u32).collect();
+
+    let vertices = Rc::new(glium::VertexBuffer::new(display, vertices).unwrap());
+    let indices = Rc::new(glium::IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, &index_data).unwrap());
+
+    LonLatGlBuffers{ vertices, indices }
 }
 
 fn create_globe_mesh(
@@ -312,46 +2691,43 @@ fn create_globe_mesh(
     LonLatGlBuffers{ vertices, indices }
 }
 
-fn create_graticule(
+/// Creates graticule lines spaced by `step`, each consisting of segments of approx. `substep` (at most `step`),
+/// so that they are smoothly curved when projected.
+pub fn create_graticule(
     step: cgmath::Deg<f64>,
-    num_substeps: usize,
+    substep: cgmath::Deg<f64>,
     display: &glium::Display
 ) -> LonLatGlBuffers {
+    let num_substeps = (step.0 / substep.0).round().max(1.0) as usize;
+    let substep = step.0 / num_substeps as f64;
+    // tolerance of the lines' ends (so that accumulated rounding errors do not remove the last segment)
+    let eps = 1.0e-9;
+
     let mut vertex_data: Vec<LonLatVertex> = vec![];
     let mut index_data: Vec<u32> = vec![];
 
-    let mut longitude = cgmath::Deg(-180.0);
-    while longitude <= cgmath::Deg(180.0) {
-        let mut latitude = cgmath::Deg(-90.0);
-        let mut parallel_starts = true;
-        while latitude <= cgmath::Deg(90.0) {
-            vertex_data.push(LonLatVertex{ lonlat_position: [longitude.0 as f32, latitude.0 as f32] });
-            if !parallel_starts {
+    let mut add_line = |points: &mut dyn Iterator<Item = [f64; 2]>| {
+        for (idx, [lon, lat]) in points.enumerate() {
+            vertex_data.push(LonLatVertex{ lonlat_position: [lon as f32, lat as f32] });
+            if idx > 0 {
                 index_data.push((vertex_data.len() - 2) as u32);
                 index_data.push((vertex_data.len() - 1) as u32);
             }
-            latitude += step / num_substeps as f64;
-            parallel_starts = false;
         }
+    };
 
-        longitude += step;
+    let num_meridians = (360.0 / step.0 + eps).floor() as usize;
+    let num_meridian_points = (180.0 / substep + eps).floor() as usize + 1;
+    for i in 0..=num_meridians {
+        let longitude = -180.0 + i as f64 * step.0;
+        add_line(&mut (0..num_meridian_points).map(|j| [longitude, -90.0 + j as f64 * substep]));
     }
 
-    let mut latitude = cgmath::Deg(-90.0);
-    while latitude <= cgmath::Deg(90.0) {
-        let mut longitude = cgmath::Deg(-180.0);
-        let mut meridian_starts = true;
-        while longitude <= cgmath::Deg(180.0) {
-            vertex_data.push(LonLatVertex{ lonlat_position: [longitude.0 as f32, latitude.0 as f32] });
-            if !meridian_starts {
-                index_data.push((vertex_data.len() - 2) as u32);
-                index_data.push((vertex_data.len() - 1) as u32);
-            }
-            longitude += step / num_substeps as f64;
-            meridian_starts = false;
-        }
-
-        latitude += step;
+    let num_parallels = (180.0 / step.0 + eps).floor() as usize;
+    let num_parallel_points = (360.0 / substep + eps).floor() as usize + 1;
+    for i in 0..=num_parallels {
+        let latitude = -90.0 + i as f64 * step.0;
+        add_line(&mut (0..num_parallel_points).map(|j| [-180.0 + j as f64 * substep, latitude]));
     }
 
     let vertices = Rc::new(glium::VertexBuffer::new(display, &vertex_data).unwrap());
@@ -361,10 +2737,13 @@ fn create_graticule(
 }
 
 fn create_texture_from_image(path: &str, display: &glium::Display)
--> glium::texture::texture2d::Texture2d {
-    let max_texture_size = display.get_capabilities().max_texture_size as u32;
+-> Result<glium::texture::texture2d::Texture2d, String> {
+    create_texture(image::open(path).map_err(|e| e.to_string())?, display)
+}
 
-    let mut map_image = image::open(path).unwrap();
+fn create_texture(mut map_image: image::DynamicImage, display: &glium::Display)
+-> Result<glium::texture::texture2d::Texture2d, String> {
+    let max_texture_size = display.get_capabilities().max_texture_size as u32;
 
     let dims = map_image.dimensions();
     if dims.0 > max_texture_size || dims.1 > max_texture_size {
@@ -376,10 +2755,7 @@ fn create_texture_from_image(path: &str, display: &glium::Display)
     }
     let dims = map_image.dimensions();
 
-    let img_buffer = match map_image {
-        image::DynamicImage::ImageRgb8(image) => image,
-        _ => panic!("expected an RGB8 image")
-    };
+    let img_buffer = map_image.into_rgb8();
 
     let layout = img_buffer.as_flat_samples().layout;
     //TODO: handle line padding
@@ -395,46 +2771,298 @@ fn create_texture_from_image(path: &str, display: &glium::Display)
         },
         glium::texture::UncompressedFloatFormat::U8U8U8,
         glium::texture::MipmapsOption::AutoGeneratedMipmaps
-    ).unwrap();
+    ).map_err(|e| e.to_string())?;
+
+    Ok(texture)
+}
+
+fn create_rgba_texture(image: image::RgbaImage, display: &glium::Display)
+-> Result<glium::texture::texture2d::Texture2d, String> {
+    glium::texture::texture2d::Texture2d::with_format(
+        display,
+        glium::texture::RawImage2d{
+            data: std::borrow::Cow::<[u8]>::from(image.as_raw().as_slice()),
+            width: image.width(),
+            height: image.height(),
+            format: glium::texture::ClientFormat::U8U8U8U8
+        },
+        glium::texture::UncompressedFloatFormat::U8U8U8U8,
+        glium::texture::MipmapsOption::AutoGeneratedMipmaps
+    ).map_err(|e| e.to_string())
+}
+
+/// Creates a single-channel texture (16 bits per pixel) of elevations from the brightness of `image`.
+fn create_elevation_texture(mut image: image::DynamicImage, display: &glium::Display)
+-> Result<glium::texture::texture2d::Texture2d, String> {
+    let max_texture_size = display.get_capabilities().max_texture_size as u32;
+
+    let dims = image.dimensions();
+    if dims.0 > max_texture_size || dims.1 > max_texture_size {
+        image = image.resize(
+            max_texture_size.min(dims.0),
+            max_texture_size.min(dims.1),
+            image::imageops::FilterType::Triangle
+        );
+    }
+    let image = image.into_luma16();
+
+    glium::texture::texture2d::Texture2d::with_format(
+        display,
+        glium::texture::RawImage2d{
+            data: std::borrow::Cow::<[u16]>::from(image.as_raw().as_slice()),
+            width: image.width(),
+            height: image.height(),
+            format: glium::texture::ClientFormat::U16
+        },
+        glium::texture::UncompressedFloatFormat::U16,
+        glium::texture::MipmapsOption::NoMipmap
+    ).map_err(|e| e.to_string())
+}
+
+/// Creates a texture with `mask`'s inside (see `geometry::rasterize_rings`) and outside colored with `land_color`
+/// and `ocean_color`, respectively.
+fn create_land_texture(
+    mask: &image::GrayImage,
+    land_color: [f32; 3],
+    ocean_color: [f32; 3],
+    display: &glium::Display
+) -> Result<glium::texture::texture2d::Texture2d, String> {
+    let to_u8 = |c: f32| (c.max(0.0).min(1.0) * 255.0).round() as u8;
+    let to_rgb = |color: [f32; 3]| image::Rgb([to_u8(color[0]), to_u8(color[1]), to_u8(color[2])]);
+    let (land, ocean) = (to_rgb(land_color), to_rgb(ocean_color));
+    let image = image::RgbImage::from_fn(mask.width(), mask.height(), |x, y| {
+        if mask.get_pixel(x, y).0[0] != 0 { land } else { ocean }
+    });
+
+    create_texture(image::DynamicImage::ImageRgb8(image), display)
+}
+
+/// Creates an equirectangular texture shading (with transparent black) the night hemisphere at `time`;
+/// the shading increases gradually through twilight.
+fn create_night_texture(time: &solar::UtcDateTime, display: &glium::Display) -> glium::texture::texture2d::Texture2d {
+    let (subsolar_lon, subsolar_lat) = solar::subsolar_point(time);
+    let (subsolar_lon, subsolar_lat) = (subsolar_lon.0.to_radians(), subsolar_lat.0.to_radians());
+    let (width, height) = (NIGHT_TEXTURE_WIDTH, NIGHT_TEXTURE_WIDTH / 2);
+
+    let image = image::RgbaImage::from_fn(width, height, |x, y| {
+        let longitude = ((x as f64 + 0.5) / width as f64 * 360.0 - 180.0).to_radians();
+        let latitude = (90.0 - (y as f64 + 0.5) / height as f64 * 180.0).to_radians();
+        let altitude = solar::sun_altitude(subsolar_lon, subsolar_lat, longitude, latitude);
+        let darkness = (altitude / NIGHT_SUN_ALTITUDE.0.to_radians()).max(0.0).min(1.0) as f32;
+        image::Rgba([0, 0, 0, (darkness * NIGHT_OPACITY * 255.0).round() as u8])
+    });
+
+    glium::texture::texture2d::Texture2d::with_format(
+        display,
+        glium::texture::RawImage2d::from_raw_rgba(image.into_raw(), (width, height)),
+        glium::texture::UncompressedFloatFormat::U8U8U8U8,
+        glium::texture::MipmapsOption::NoMipmap
+    ).unwrap()
+}
+
+/// Creates a uniformly gray texture, used if the globe texture cannot be loaded.
+fn create_placeholder_texture(display: &glium::Display) -> glium::texture::texture2d::Texture2d {
+    glium::texture::texture2d::Texture2d::with_format(
+        display,
+        glium::texture::RawImage2d{
+            data: std::borrow::Cow::<[u8]>::from(&[128u8, 128, 128][..]),
+            width: 1,
+            height: 1,
+            format: glium::texture::ClientFormat::U8U8U8
+        },
+        glium::texture::UncompressedFloatFormat::U8U8U8,
+        glium::texture::MipmapsOption::NoMipmap
+    ).unwrap()
+}
+
+fn create_choropleth(
+    layer: &choropleth::PolygonLayer,
+    params: ChoroplethParams,
+    display: &glium::Display
+) -> Result<Choropleth, String> {
+    let (image, legend) =
+        layer.render(params.field, params.classification, params.num_classes, params.colormap, params.opacity)?;
+
+    Ok(Choropleth{ params, legend: Rc::new(legend), texture: Rc::new(create_rgba_texture(image, display)?) })
+}
+
+/// Creates a vector layer of plate boundaries, with lines of each type of boundary drawn in its style.
+fn plate_boundaries_layer(path: &str, tolerance: f64, display: &glium::Display) -> Result<VectorLayer, String> {
+    let boundaries = plate_boundaries::load(path)?;
+
+    Ok(VectorLayer{
+        name: "plate boundaries".into(),
+        lines: boundaries.iter().map(|(boundary_type, lines)| {
+            StyledLines::new(Some(boundary_type.style()), lines.clone(), tolerance, display)
+        }).collect(),
+        polylines: boundaries.into_iter().flat_map(|(_, lines)| lines).collect(),
+        style: plate_boundaries::BoundaryType::Other.style(),
+        visible: true,
+        base_map: false,
+        layer: Layer::PlateBoundaries,
+        shapefile: None,
+        filter: None
+    })
+}
+
+/// Creates a vector layer of time-zone boundaries and labels of the zones' current UTC offsets.
+fn time_zones_layer(
+    path: &str,
+    tolerance: f64,
+    display: &glium::Display
+) -> Result<(VectorLayer, Vec<TimeZoneLabel>), String> {
+    let zones = time_zones::load(path)?;
+    let now = solar::UtcDateTime::now().unix_time();
+    let labels = zones.iter().filter_map(|zone| zone.utc_offset(now).map(|offset| TimeZoneLabel{
+        text: time_zones::offset_label(offset),
+        longitude: cgmath::Deg(zone.label_position[0]),
+        latitude: cgmath::Deg(zone.label_position[1])
+    })).collect();
+    let polylines: Vec<geojson::Polyline> = zones.into_iter().flat_map(|zone| zone.boundary).collect();
+
+    Ok((
+        VectorLayer{
+            name: "time zones".into(),
+            lines: vec![StyledLines::new(None, polylines.clone(), tolerance, display)],
+            polylines,
+            style: TIME_ZONES_STYLE,
+            visible: true,
+            base_map: false,
+            layer: Layer::TimeZones,
+            shapefile: None,
+            filter: None
+        },
+        labels
+    ))
+}
 
-    texture
+/// Creates a list of line segments joining consecutive vertices of each polyline, simplified with `tolerance`
+/// (degrees; 0: no simplification).
+fn create_simplified_polylines(
+    polylines: &[geojson::Polyline],
+    tolerance: f64,
+    display: &glium::Display
+) -> LonLatGlBuffers {
+    if tolerance > 0.0 {
+        let simplified: Vec<geojson::Polyline> =
+            polylines.iter().map(|line| geometry::simplify(line, tolerance)).collect();
+        create_polylines(&simplified, display)
+    } else {
+        create_polylines(polylines, display)
+    }
 }
 
-fn create_map_from_shape_file(path: &str, display: &glium::Display)
--> LonLatGlBuffers {
-    let mut reader = shapefile::Reader::from_path(path).unwrap();
+/// Creates a list of line segments joining consecutive vertices of each polyline.
+fn create_polylines(polylines: &[geojson::Polyline], display: &glium::Display) -> LonLatGlBuffers {
+    let (vertex_data, index_data) = polyline_segments(polylines);
+    create_line_segments(&vertex_data, &index_data, display)
+}
 
+/// Returns vertices and indices (pairs per segment) of line segments joining consecutive vertices of each polyline.
+pub fn polyline_segments(polylines: &[geojson::Polyline]) -> (Vec<LonLatVertex>, Vec<u32>) {
     let mut vertex_data: Vec<LonLatVertex> = vec![];
     let mut index_data: Vec<u32> = vec![];
 
-    for shape_record in reader.iter_shapes_and_records() {
-        let (shape, _record) = shape_record.unwrap();
-
-        // let scalerank: f64 = match record.get("scalerank").unwrap() {
-        //     shapefile::dbase::FieldValue::Numeric(value) => value.unwrap(),
-        //     _ => panic!("unexpected field type")
-        // };
-
-        /*if scalerank == 6.0*/ {
-            match shape {
-                shapefile::Shape::Polyline(polyline) => {
-                    for part in polyline.parts() {
-                        for (idx, point) in part.iter().enumerate() {
-                            vertex_data.push(LonLatVertex{ lonlat_position: [point.x as f32, point.y as f32] });
-                            if idx > 0 {
-                                index_data.push((vertex_data.len() - 2) as u32);
-                                index_data.push((vertex_data.len() - 1) as u32);
-                            }
-                        }
-                    }
-                },
-                _ => ()
+    for polyline in polylines {
+        for (idx, [lon, lat]) in polyline.iter().enumerate() {
+            vertex_data.push(LonLatVertex{ lonlat_position: [*lon as f32, *lat as f32] });
+            if idx > 0 {
+                index_data.push((vertex_data.len() - 2) as u32);
+                index_data.push((vertex_data.len() - 1) as u32);
             }
         }
     }
 
-    let vertices = Rc::new(glium::VertexBuffer::new(display, &vertex_data).unwrap());
-    let indices = Rc::new(glium::IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, &index_data).unwrap());
+    (vertex_data, index_data)
+}
+
+fn create_line_segments(vertex_data: &[LonLatVertex], index_data: &[u32], display: &glium::Display) -> LonLatGlBuffers {
+    let vertices = Rc::new(glium::VertexBuffer::new(display, vertex_data).unwrap());
+    let indices = Rc::new(
+        glium::IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, index_data).unwrap()
+    );
 
     LonLatGlBuffers{ vertices, indices }
+}
+
+/// Loads all polylines and polygon rings (as closed polylines) from a shape file (or from its cache,
+/// see `shape_cache`).
+fn load_shape_file(path: &str) -> Result<Vec<geojson::Polyline>, String> {
+    shape_cache::cached(path, read_shape_file).map(|shapes| shapes.polylines)
+}
+
+/// Loads polylines as `load_shape_file` does, together with the GL buffers of their line segments (not simplified;
+/// their contents are also cached).
+fn load_shape_lines(path: &str, display: &glium::Display) -> Result<(Vec<geojson::Polyline>, LonLatGlBuffers), String> {
+    let shapes = shape_cache::cached(path, read_shape_file)?;
+    let gl_buf = create_line_segments(shapes.vertices(), shapes.indices(), display);
+
+    Ok((shapes.polylines, gl_buf))
+}
+
+/// Returns the 64-bit FNV-1a hash of `data` (unlike `DefaultHasher`'s, it is the same in every build).
+pub fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+fn read_shape_file(path: &str) -> Result<Vec<geojson::Polyline>, String> {
+    let mut reader = shapefile::Reader::from_path(path).map_err(|e| e.to_string())?;
+
+    let mut polylines = vec![];
+
+    for shape_record in reader.iter_shapes_and_records() {
+        let (shape, _) = shape_record.map_err(|e| e.to_string())?;
+        match shape {
+            shapefile::Shape::Polyline(polyline) => {
+                for part in polyline.parts() {
+                    polylines.push(part.iter().map(|point| [point.x, point.y]).collect());
+                }
+            },
+            shapefile::Shape::Polygon(polygon) => {
+                for ring in polygon.rings() {
+                    polylines.push(ring.points().iter().map(|point| [point.x, point.y]).collect());
+                }
+            },
+            _ => ()
+        }
+    }
+
+    Ok(polylines)
+}
+
+/// Loads populated places (points with Natural Earth's `NAME`, `ADM0NAME` and `RANK_MAX` attributes) from a shape file.
+fn load_places(path: &str) -> Result<Vec<Place>, String> {
+    use shapefile::dbase::FieldValue;
+
+    let mut reader = shapefile::Reader::from_path(path).map_err(|e| e.to_string())?;
+
+    let mut places = vec![];
+    for shape_record in reader.iter_shapes_and_records() {
+        let (shape, record) = shape_record.map_err(|e| e.to_string())?;
+        let field = |name: &str| record.get(name).or_else(|| record.get(&name.to_lowercase()));
+
+        let point = match shape {
+            shapefile::Shape::Point(point) => point,
+            _ => continue
+        };
+        let name = match field("NAME") {
+            Some(FieldValue::Character(Some(name))) => name.clone(),
+            _ => continue
+        };
+        let country = match field("ADM0NAME") {
+            Some(FieldValue::Character(Some(country))) => country.clone(),
+            _ => String::new()
+        };
+        let rank = match field("RANK_MAX") {
+            Some(FieldValue::Numeric(Some(rank))) => *rank as u8,
+            _ => 0
+        };
+
+        places.push(Place{ name, country, longitude: cgmath::Deg(point.x), latitude: cgmath::Deg(point.y), rank });
+    }
+
+    // larger places first, so that their labels take precedence
+    places.sort_by(|a, b| b.rank.cmp(&a.rank));
+
+    Ok(places)
 }
\ No newline at end of file
@@ -0,0 +1,248 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Download of missing data files (the globe texture and the coastline) on the first run.
+//
+// Files are downloaded in a background thread to a temporary file next to the destination, verified and only then
+// moved into place, so an interrupted download never leaves a broken data file. The SHA-256 digest of each download
+// must match the one pinned here, so a file changed at the source is rejected. Its length must also match
+// the Content-Length sent by the server, and images must decode completely.
+//
+// The default globe texture and coastline are the ones distributed with the program's source code, and are
+// downloaded from its repository. If their default paths (relative to the current directory) are missing, they are
+// downloaded under the same paths into the user's data directory, where they are also looked up at startup
+// (see `config::resolve_data_path`).
+//
+
+use crate::config::{self, Config};
+use crate::data;
+use crate::web_tiles;
+use sha2::Digest;
+use std::io::{Read, Write};
+use std::sync::mpsc;
+
+/// The program's repository; the default data files are at this URL with their paths (`data/...`) appended.
+const REPOSITORY_URL: &str = "https://raw.githubusercontent.com/GreatAttractor/projections/master";
+
+/// SHA-256 digest of `data::DEFAULT_GLOBE_TEXTURE_FILE` (NASA Blue Marble: Next Generation, topography
+/// and bathymetry, December 2004; 8192×4096).
+const GLOBE_TEXTURE_SHA256: &str = "21b476e6ad93235a0d2859ec5b49f8c6a99c37906af345cb5d0f8f085c56586f";
+
+/// Extensions and SHA-256 digests of the files of the shapefile `data::DEFAULT_COASTLINE_FILE` (Natural Earth
+/// 1:10m coastline, version 5.0.0-pre9). The `.shp` file is the last one, as its presence marks the coastline
+/// as downloaded.
+const COASTLINE_FILES: [(&str, &str); 3] = [
+    ("shx", "f873afee7f56779ce52253f740ec251c2f12244aea911dc40f0a85d75de8d5f2"),
+    ("dbf", "9ccc214342fe400bf8c7d91d7a5b276b0457b0ada03e8d4be16ac5ba13037f3b"),
+    ("shp", "459a4a97c09db19aadf5244026612de9d43748be27f83a360242b99f7fabb3c1")
+];
+
+/// Size of chunks in which progress is reported.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Kind {
+    /// Saved as is.
+    Image,
+    /// One of a shapefile's files; saved as is.
+    Shapefile
+}
+
+/// Missing data file to be downloaded.
+pub struct DataFile {
+    pub description: &'static str,
+    pub url: String,
+    pub destination: String,
+    kind: Kind,
+    /// Expected SHA-256 digest (lowercase hexadecimal).
+    sha256: &'static str
+}
+
+enum Message {
+    /// Index of the file, bytes received, total bytes (if known).
+    Progress(usize, u64, Option<u64>),
+    Finished(Result<(), String>)
+}
+
+/// Download of missing data files (in a background thread).
+pub struct DataDownload {
+    pub files: Vec<DataFile>,
+    receiver: Option<mpsc::Receiver<Message>>,
+    /// Index of the file being downloaded, bytes received, total bytes (if known).
+    pub progress: Option<(usize, u64, Option<u64>)>,
+    /// Result of the last download (if finished).
+    pub result: Option<Result<(), String>>
+}
+
+/// Returns the default data files which are missing (with paths resolved by `config::resolve_data_path`)
+/// and can be downloaded; files configured in place of the default ones are not downloaded.
+pub fn missing_files(config: &Config) -> Vec<DataFile> {
+    let mut files = vec![];
+    if config.globe_texture == data::DEFAULT_GLOBE_TEXTURE_FILE && !is_file(&config.globe_texture) {
+        files.push(DataFile{
+            description: "globe texture (NASA Blue Marble, 8192×4096)",
+            url: format!("{}/{}", REPOSITORY_URL, data::DEFAULT_GLOBE_TEXTURE_FILE),
+            destination: destination(data::DEFAULT_GLOBE_TEXTURE_FILE),
+            kind: Kind::Image,
+            sha256: GLOBE_TEXTURE_SHA256
+        });
+    }
+    if config.coastline == data::DEFAULT_COASTLINE_FILE && !is_file(&config.coastline) {
+        for (extension, sha256) in COASTLINE_FILES {
+            let path = format!("{}.{}", data::DEFAULT_COASTLINE_FILE.trim_end_matches(".shp"), extension);
+            files.push(DataFile{
+                description: "coastline (Natural Earth, 1:10m)",
+                url: format!("{}/{}", REPOSITORY_URL, path),
+                destination: destination(&path),
+                kind: Kind::Shapefile,
+                sha256
+            });
+        }
+    }
+
+    files
+}
+
+fn is_file(path: &str) -> bool { std::path::Path::new(path).is_file() }
+
+/// Returns the path under which the default data file `path` is to be downloaded: the same relative path
+/// in the user's data directory (or in the current directory if the data directory is unknown).
+fn destination(path: &str) -> String {
+    match config::data_dir() {
+        Some(dir) => dir.join(path).to_string_lossy().into_owned(),
+        None => path.to_string()
+    }
+}
+
+impl DataDownload {
+    pub fn new(files: Vec<DataFile>) -> DataDownload {
+        DataDownload{ files, receiver: None, progress: None, result: None }
+    }
+
+    pub fn is_running(&self) -> bool { self.receiver.is_some() }
+
+    /// Starts downloading the files which are still missing.
+    pub fn start(&mut self) {
+        self.files.retain(|file| !std::path::Path::new(&file.destination).is_file());
+        let (sender, receiver) = mpsc::channel();
+        let files: Vec<(String, String, Kind, &'static str)> = self.files.iter()
+            .map(|file| (file.url.clone(), file.destination.clone(), file.kind, file.sha256))
+            .collect();
+        std::thread::spawn(move || {
+            let mut result = Ok(());
+            for (idx, (url, destination, kind, sha256)) in files.iter().enumerate() {
+                let on_progress = |received, total| {
+                    let _ = sender.send(Message::Progress(idx, received, total));
+                };
+                result = download_file(url, destination, *kind, *sha256, on_progress)
+                    .map_err(|e| format!("{}: {}", url, e));
+                if result.is_err() { break; }
+            }
+            let _ = sender.send(Message::Finished(result));
+        });
+
+        self.receiver = Some(receiver);
+        self.progress = None;
+        self.result = None;
+    }
+
+    /// Receives progress of the download; returns true if it has finished successfully.
+    pub fn poll(&mut self) -> bool {
+        let receiver = match &self.receiver {
+            Some(receiver) => receiver,
+            None => return false
+        };
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                Message::Progress(idx, received, total) => self.progress = Some((idx, received, total)),
+                Message::Finished(result) => {
+                    self.result = Some(result);
+                    self.receiver = None;
+                    break;
+                }
+            }
+        }
+
+        matches!(self.result, Some(Ok(())))
+    }
+}
+
+fn download_file(
+    url: &str,
+    destination: &str,
+    kind: Kind,
+    sha256: &str,
+    on_progress: impl Fn(u64, Option<u64>)
+) -> Result<(), String> {
+    let destination = std::path::Path::new(destination);
+    let dir = destination.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+    std::fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+
+    let response = ureq::get(url).set("User-Agent", web_tiles::USER_AGENT).call().map_err(|e| e.to_string())?;
+    // a compressed response is decompressed while reading, so its length is not known
+    let total: Option<u64> = if response.header("Content-Encoding").is_none() {
+        response.header("Content-Length").and_then(|length| length.parse().ok())
+    } else {
+        None
+    };
+
+    let temp_path = destination.with_extension("download");
+    let mut temp_file = std::fs::File::create(&temp_path)
+        .map_err(|e| format!("cannot create {}: {}", temp_path.display(), e))?;
+    let mut reader = response.into_reader();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut received = 0u64;
+    let mut hasher = sha2::Sha256::new();
+    loop {
+        let num_read = reader.read(&mut buffer).map_err(|e| e.to_string())?;
+        if num_read == 0 { break; }
+        temp_file.write_all(&buffer[..num_read]).map_err(|e| e.to_string())?;
+        hasher.update(&buffer[..num_read]);
+        received += num_read as u64;
+        on_progress(received, total);
+    }
+    drop(temp_file);
+
+    let digest = format!("{:x}", hasher.finalize());
+    let result = if digest != sha256 {
+        Err(format!("SHA-256 mismatch (expected {}, got {})", sha256, digest))
+    } else {
+        verify_and_install(&temp_path, destination, kind, received, total)
+    };
+    let _ = std::fs::remove_file(&temp_path);
+
+    result
+}
+
+fn verify_and_install(
+    temp_path: &std::path::Path,
+    destination: &std::path::Path,
+    kind: Kind,
+    received: u64,
+    total: Option<u64>
+) -> Result<(), String> {
+    if let Some(total) = total {
+        if received != total {
+            return Err(format!("incomplete download ({} of {} bytes)", received, total));
+        }
+    }
+
+    match kind {
+        Kind::Image => {
+            image::io::Reader::open(temp_path)
+                .and_then(|reader| reader.with_guessed_format())
+                .map_err(|e| e.to_string())?
+                .decode()
+                .map_err(|e| format!("invalid image: {}", e))?;
+            std::fs::rename(temp_path, destination).map_err(|e| e.to_string())
+        },
+
+        Kind::Shapefile => std::fs::rename(temp_path, destination).map_err(|e| e.to_string())
+    }
+}
@@ -22,22 +22,34 @@ const COLOR_FORMAT: glium::texture::UncompressedFloatFormat = glium::texture::Un
 
 const DEPTH_FORMAT: glium::texture::DepthFormat = glium::texture::DepthFormat::I24;
 
-const NUM_SAMPLES: u32 = 8;
+/// Default number of samples per pixel with multi-sampling.
+pub const DEFAULT_NUM_SAMPLES: u32 = 8;
 
 #[derive(Copy, Clone, PartialEq)]
-pub enum Sampling { Single, Multi }
+pub enum Sampling {
+    Single,
+    /// Contains the number of samples per pixel.
+    Multi(u32)
+}
+
+impl Sampling {
+    /// Returns sampling with the specified number of samples per pixel.
+    pub fn with_num_samples(num_samples: u32) -> Sampling {
+        if num_samples > 1 { Sampling::Multi(num_samples) } else { Sampling::Single }
+    }
+}
 
-/// Contains (draw buffer, depth buffer).
+/// Contains (draw buffer, depth buffer) and, for multi-sampling, the number of samples.
 enum Buffers {
     SingleSampling(Texture2d, DepthTexture2d),
-    MultiSampling(Texture2dMultisample, DepthTexture2dMultisample)
+    MultiSampling(Texture2dMultisample, DepthTexture2dMultisample, u32)
 }
 
 impl Buffers {
     fn sampling(&self) -> Sampling {
         match self {
             Buffers::SingleSampling(_, _) => Sampling::Single,
-            Buffers::MultiSampling(_, _) => Sampling::Multi
+            Buffers::MultiSampling(_, _, num_samples) => Sampling::Multi(*num_samples)
         }
     }
 }
@@ -100,9 +112,10 @@ impl DrawBuffer {
                 ).unwrap();
             },
 
-            Buffers::MultiSampling(draw_buf, _) => {
+            Buffers::MultiSampling(draw_buf, _, num_samples) => {
                 let uniforms = uniform! {
-                    source_texture: draw_buf.sampled()
+                    source_texture: draw_buf.sampled(),
+                    num_samples: *num_samples as i32
                 };
 
                 fbo.draw(
@@ -116,6 +129,10 @@ impl DrawBuffer {
         };
     }
 
+    pub fn sampling(&self) -> Sampling {
+        self.draw_bufs.sampling()
+    }
+
     pub fn storage_buf(&self) -> &Rc<Texture2d> {
         &self.storage_buf
     }
@@ -126,7 +143,7 @@ impl DrawBuffer {
                 &self.display, draw_buf, depth_buf
             ).unwrap(),
 
-            Buffers::MultiSampling(draw_buf, depth_buf) => glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(
+            Buffers::MultiSampling(draw_buf, depth_buf, _) => glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(
                 &self.display, draw_buf, depth_buf
             ).unwrap()
         }
@@ -198,6 +215,29 @@ impl DrawBuffer {
         }
     }
 
+    /// Creates a draw buffer of the specified size, sharing OpenGL resources with `self`.
+    pub fn new_similar(&self, sampling: Sampling, width: u32, height: u32) -> DrawBuffer {
+        DrawBuffer::new_with_size(
+            sampling,
+            &self.texture_copy_single_gl_prog,
+            &self.texture_copy_multi_gl_prog,
+            &self.unit_quad,
+            &self.display,
+            &self.renderer,
+            width,
+            height
+        )
+    }
+
+    /// Returns contents of the storage buffer (top row first).
+    pub fn read_image(&self) -> image::RgbaImage {
+        let raw: glium::texture::RawImage2d<u8> = self.storage_buf.read();
+
+        // rows in the storage buffer are already flipped w.r.t. the draw buffer (see "pass-through.vert"),
+        // so that the first row is the top of the image
+        image::RgbaImage::from_raw(raw.width, raw.height, raw.data.into_owned()).unwrap()
+    }
+
     pub fn id(&self) -> imgui::TextureId {
         self.id
     }
@@ -229,14 +269,14 @@ impl DrawBuffer {
                 ).unwrap()
             ),
 
-            Sampling::Multi => Buffers::MultiSampling(
+            Sampling::Multi(num_samples) => Buffers::MultiSampling(
                 Texture2dMultisample::empty_with_format(
                     display,
                     format,
                     glium::texture::MipmapsOption::NoMipmap,
                     width,
                     height,
-                    NUM_SAMPLES
+                    num_samples
                 ).unwrap(),
                 DepthTexture2dMultisample::empty_with_format(
                     display,
@@ -244,8 +284,9 @@ impl DrawBuffer {
                     glium::texture::MipmapsOption::NoMipmap,
                     width,
                     height,
-                    NUM_SAMPLES
-                ).unwrap()
+                    num_samples
+                ).unwrap(),
+                num_samples
             )
         };
 
@@ -304,3 +345,9 @@ impl DrawBuffer {
         }
     }
 }
+
+impl Drop for DrawBuffer {
+    fn drop(&mut self) {
+        self.renderer.borrow_mut().textures().remove(self.id);
+    }
+}
@@ -0,0 +1,165 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Earthquakes from the USGS real-time GeoJSON feeds (https://earthquake.usgs.gov/earthquakes/feed/v1.0/geojson.php),
+// fetched in a background thread and shown as a point layer with symbols scaled by magnitude.
+//
+
+use crate::csv;
+use crate::web_tiles;
+use serde_json::Value;
+use std::io::Read;
+use std::sync::mpsc;
+
+/// Beginning of the names of point layers of earthquakes.
+pub const LAYER_NAME: &str = "USGS earthquakes";
+
+/// Max. size of a feed (the "all earthquakes, past week" feed is a few MB).
+const MAX_FEED_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Period {
+    Day,
+    Week
+}
+
+impl Period {
+    pub const ALL: [Period; 2] = [Period::Day, Period::Week];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Period::Day => "past day",
+            Period::Week => "past week"
+        }
+    }
+
+    fn url_name(&self) -> &'static str {
+        match self {
+            Period::Day => "day",
+            Period::Week => "week"
+        }
+    }
+}
+
+/// Min. magnitude of earthquakes of a feed.
+#[derive(Copy, Clone, PartialEq)]
+pub enum MinMagnitude {
+    All,
+    M1_0,
+    M2_5,
+    M4_5,
+    /// Significant earthquakes (as determined by USGS).
+    Significant
+}
+
+impl MinMagnitude {
+    pub const ALL: [MinMagnitude; 5] = [
+        MinMagnitude::All,
+        MinMagnitude::M1_0,
+        MinMagnitude::M2_5,
+        MinMagnitude::M4_5,
+        MinMagnitude::Significant
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MinMagnitude::All => "all",
+            MinMagnitude::M1_0 => "M1.0+",
+            MinMagnitude::M2_5 => "M2.5+",
+            MinMagnitude::M4_5 => "M4.5+",
+            MinMagnitude::Significant => "significant"
+        }
+    }
+
+    fn url_name(&self) -> &'static str {
+        match self {
+            MinMagnitude::All => "all",
+            MinMagnitude::M1_0 => "1.0",
+            MinMagnitude::M2_5 => "2.5",
+            MinMagnitude::M4_5 => "4.5",
+            MinMagnitude::Significant => "significant"
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct Feed {
+    pub period: Period,
+    pub min_magnitude: MinMagnitude
+}
+
+impl Feed {
+    pub fn url(&self) -> String {
+        format!(
+            "https://earthquake.usgs.gov/earthquakes/feed/v1.0/summary/{}_{}.geojson",
+            self.min_magnitude.url_name(),
+            self.period.url_name()
+        )
+    }
+
+    /// Returns the name of the feed's point layer.
+    pub fn layer_name(&self) -> String {
+        format!("{} ({}, {})", LAYER_NAME, self.period.name(), self.min_magnitude.name())
+    }
+}
+
+/// Returns epicenters of earthquakes of a feed: points labeled with the earthquakes' titles (e.g. "M 4.6 - 20 km
+/// SW of ...") and valued with their magnitudes.
+pub fn parse(contents: &str) -> Result<Vec<csv::Point>, String> {
+    let value: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let features = value.get("features").and_then(Value::as_array)
+        .ok_or_else(|| "expected a FeatureCollection".to_string())?;
+
+    Ok(features.iter().filter_map(|feature| {
+        let coordinates = feature.get("geometry")?.get("coordinates")?.as_array()?;
+        let properties = feature.get("properties");
+        Some(csv::Point{
+            longitude: coordinates.get(0)?.as_f64()?,
+            latitude: coordinates.get(1)?.as_f64()?,
+            label: properties.and_then(|p| p.get("title")?.as_str()).map(|title| title.to_string()),
+            value: properties.and_then(|p| p.get("mag")?.as_f64())
+        })
+    }).collect())
+}
+
+/// Fetching of a feed (in a background thread).
+pub struct FeedFetch {
+    pub feed: Feed,
+    receiver: mpsc::Receiver<Result<Vec<csv::Point>, String>>
+}
+
+impl FeedFetch {
+    pub fn start(feed: Feed) -> FeedFetch {
+        let (sender, receiver) = mpsc::channel();
+        let url = feed.url();
+        std::thread::spawn(move || {
+            // fails if the fetch has been dropped
+            let _ = sender.send(fetch(&url));
+        });
+
+        FeedFetch{ feed, receiver }
+    }
+
+    /// Returns the earthquakes once they have been received.
+    pub fn poll(&self) -> Option<Result<Vec<csv::Point>, String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err("fetching stopped unexpectedly".into()))
+        }
+    }
+}
+
+fn fetch(url: &str) -> Result<Vec<csv::Point>, String> {
+    let response = ureq::get(url).set("User-Agent", web_tiles::USER_AGENT).call().map_err(|e| e.to_string())?;
+    let mut contents = String::new();
+    response.into_reader().take(MAX_FEED_BYTES).read_to_string(&mut contents).map_err(|e| e.to_string())?;
+
+    parse(&contents)
+}
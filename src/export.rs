@@ -0,0 +1,530 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use crate::csv;
+use crate::data::VectorLayer;
+use crate::geotiff;
+use crate::pdf;
+use crate::printing::{self, PrintSettings};
+use crate::projection::{self, Projection};
+use crate::views::{Grid, Layers, StereoMode, ViewBase};
+use crate::wkt;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::sync::mpsc;
+
+/// Width of lines in exported PDF documents (in points).
+const PDF_LINE_WIDTH: f64 = 0.4;
+
+/// Width of lines in exported SVG documents (in pixels).
+const SVG_LINE_WIDTH: f64 = 0.75;
+
+/// Max. distance (in normalized device coordinates) between the end of a segment and the start of the next one
+/// for them to be joined into a single SVG path.
+const SVG_JOIN_TOLERANCE: f64 = 1.0e-9;
+
+/// Name of the attribute of exported shapefile records holding the name of the vector layer.
+const SHAPEFILE_LAYER_FIELD: &str = "layer";
+
+/// Max. length of character attributes of shapefiles.
+const SHAPEFILE_MAX_FIELD_LENGTH: u8 = 254;
+
+/// Speed of color quantization of animated GIF frames (1-30); higher is faster, with slightly lower quality.
+const GIF_SPEED: i32 = 10;
+
+/// Max. number of samples written by `export_projection_samples` (limits the file size to a few hundred MB).
+const MAX_PROJECTION_SAMPLES: usize = 5_000_000;
+
+/// Max. total number of samples written by `export_distortion_metrics` (limits the time and the file size;
+/// e.g. a 1° grid for 60 projections).
+const MAX_DISTORTION_SAMPLES: usize = 4_000_000;
+
+/// Samples distortion of `projections` on a lon./lat. grid and writes the results as CSV.
+///
+/// Samples are taken at the centers of grid cells of size `grid_step`, so that the poles
+/// and the antimeridian (where some projections are singular) are avoided. The total number of samples
+/// must not exceed `MAX_DISTORTION_SAMPLES`. `on_progress` receives the fraction of the samples written
+/// after each row of the grid.
+///
+fn export_distortion_metrics(
+    path: &str,
+    projections: &[Projection],
+    grid_step: cgmath::Deg<f64>,
+    on_progress: impl Fn(f32)
+) -> Result<(), std::io::Error> {
+    let invalid_input = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+    if grid_step.0 <= 0.0 {
+        return Err(invalid_input("grid step must be positive".into()));
+    }
+    let num_samples = (360.0 / grid_step.0).ceil() * (180.0 / grid_step.0).ceil() * projections.len() as f64;
+    if num_samples > MAX_DISTORTION_SAMPLES as f64 {
+        return Err(invalid_input(format!(
+            "grid step too small: {:.0} samples exceed the limit of {}", num_samples, MAX_DISTORTION_SAMPLES
+        )));
+    }
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    writeln!(file, "projection,longitude,latitude,h,k,area_scale,angular_deformation")?;
+
+    let num_rows = (180.0 / grid_step.0).ceil() as usize;
+    for (idx, projection) in projections.iter().enumerate() {
+        let mut latitude = cgmath::Deg(-90.0) + grid_step / 2.0;
+        let mut row = 0;
+        while latitude < cgmath::Deg(90.0) {
+            on_progress((idx * num_rows + row) as f32 / (projections.len() * num_rows) as f32);
+            let mut longitude = cgmath::Deg(-180.0) + grid_step / 2.0;
+            while longitude < cgmath::Deg(180.0) {
+                if let Some(d) = projection::distortion(*projection, longitude.into(), latitude.into()) {
+                    writeln!(
+                        file,
+                        "{},{:.4},{:.4},{:.6},{:.6},{:.6},{:.4}",
+                        csv::quote(projection.name()),
+                        longitude.0,
+                        latitude.0,
+                        d.h,
+                        d.k,
+                        d.area_scale,
+                        cgmath::Deg::from(d.angular_deformation).0
+                    )?;
+                }
+                longitude += grid_step;
+            }
+            latitude += grid_step;
+            row += 1;
+        }
+    }
+
+    file.flush()
+}
+
+enum ExportMessage {
+    /// Fraction of the samples written.
+    Progress(f32),
+    Finished(Result<(), String>)
+}
+
+/// Export of samples of projections running in a background thread (see `export_projection_samples`
+/// and `export_distortion_metrics`).
+pub struct SamplesExport {
+    receiver: mpsc::Receiver<ExportMessage>,
+    /// Fraction of the samples written.
+    pub progress: f32
+}
+
+impl SamplesExport {
+    /// Starts the export of samples of `projection` (see `export_projection_samples`).
+    pub fn start(
+        path: String,
+        projection: Projection,
+        lon_range: [f64; 2],
+        lat_range: [f64; 2],
+        grid_step: cgmath::Deg<f64>
+    ) -> SamplesExport {
+        SamplesExport::spawn(move |on_progress| {
+            export_projection_samples(&path, projection, lon_range, lat_range, grid_step, on_progress)
+        })
+    }
+
+    /// Starts the export of distortion metrics of `projections` (see `export_distortion_metrics`).
+    pub fn start_distortion_metrics(
+        path: String,
+        projections: Vec<Projection>,
+        grid_step: cgmath::Deg<f64>
+    ) -> SamplesExport {
+        SamplesExport::spawn(move |on_progress| {
+            export_distortion_metrics(&path, &projections, grid_step, on_progress)
+        })
+    }
+
+    fn spawn<F>(export: F) -> SamplesExport
+    where F: FnOnce(&dyn Fn(f32)) -> Result<(), std::io::Error> + Send + 'static
+    {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let on_progress = |fraction| { let _ = sender.send(ExportMessage::Progress(fraction)); };
+            let result = export(&on_progress).map_err(|e| e.to_string());
+            // fails if the export has been dropped
+            let _ = sender.send(ExportMessage::Finished(result));
+        });
+
+        SamplesExport{ receiver, progress: 0.0 }
+    }
+
+    /// Receives progress of the export; returns its result once finished.
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(ExportMessage::Progress(fraction)) => self.progress = fraction,
+                Ok(ExportMessage::Finished(result)) => return Some(result),
+                Err(mpsc::TryRecvError::Empty) => return None,
+                Err(mpsc::TryRecvError::Disconnected) => return Some(Err("the export stopped unexpectedly".into()))
+            }
+        }
+    }
+}
+
+/// Samples the normal aspect of `projection` on a lon./lat. grid and writes the projected points (map coordinates,
+/// i.e. in multiples of the globe's radius or semi-major axis) and the results of their inverse projection as CSV.
+///
+/// Samples are taken at the ranges' ends and every `grid_step` in between (at most `MAX_PROJECTION_SAMPLES`);
+/// points not shown by the projection are skipped, and failed inverse projections are written as empty values.
+/// `on_progress` receives the fraction of the samples written after each row of the grid.
+///
+fn export_projection_samples(
+    path: &str,
+    projection: Projection,
+    lon_range: [f64; 2],
+    lat_range: [f64; 2],
+    grid_step: cgmath::Deg<f64>,
+    on_progress: impl Fn(f32)
+) -> Result<(), std::io::Error> {
+    let invalid_input = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+    if grid_step.0 <= 0.0 {
+        return Err(invalid_input("grid step must be positive".into()));
+    }
+    if !(lon_range[0] <= lon_range[1] && lat_range[0] <= lat_range[1])
+        || lon_range[0] < -180.0 || lon_range[1] > 180.0 || lat_range[0] < -90.0 || lat_range[1] > 90.0
+    {
+        return Err(invalid_input("invalid range of longitude or latitude".into()));
+    }
+    let num_steps = |range: [f64; 2]| ((range[1] - range[0]) / grid_step.0 + 1.0e-9).floor();
+    let num_samples = (num_steps(lon_range) + 1.0) * (num_steps(lat_range) + 1.0);
+    if num_samples > MAX_PROJECTION_SAMPLES as f64 {
+        return Err(invalid_input(format!(
+            "grid step too small: {:.0} samples exceed the limit of {}", num_samples, MAX_PROJECTION_SAMPLES
+        )));
+    }
+    let samples = |range: [f64; 2]| (0..=num_steps(range) as usize).map(move |i| range[0] + i as f64 * grid_step.0);
+    let num_rows = num_steps(lat_range) as usize + 1;
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    writeln!(file, "projection,longitude,latitude,x,y,inverse_longitude,inverse_latitude")?;
+
+    let name = csv::quote(projection.name());
+    for (row, latitude) in samples(lat_range).enumerate() {
+        on_progress(row as f32 / num_rows as f32);
+        for longitude in samples(lon_range) {
+            let position = projection::lonlat_to_xyz(cgmath::Deg(longitude).into(), cgmath::Deg(latitude).into());
+            let p = match projection.project(position) {
+                Some(p) => p,
+                None => continue
+            };
+            let inverse = match projection.unproject(p) {
+                Some((lon, lat)) => format!("{:.9},{:.9}", cgmath::Deg::from(lon).0, cgmath::Deg::from(lat).0),
+                None => ",".to_string()
+            };
+            writeln!(file, "{},{:.6},{:.6},{:.9},{:.9},{}", name, longitude, latitude, p.x, p.y, inverse)?;
+        }
+    }
+
+    file.flush()
+}
+
+/// Exports of projected lines need the projection on the CPU (see `Projection::has_cpu_projection`).
+fn require_cpu_projection(view: &ViewBase) -> Result<(), String> {
+    if view.projection().has_cpu_projection() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is a GLSL-only projection, without CPU formulas for projecting lines",
+            view.projection().name()
+        ))
+    }
+}
+
+/// Saves the image of `view` as currently shown (without the GUI's labels and markers) as PNG or JPEG
+/// (as indicated by the extension of `path`; JPEG without alpha).
+pub fn save_view_image(path: &str, view: &ViewBase) -> Result<(), String> {
+    save_image(path, view.image())
+}
+
+/// Renders `view` offscreen at `width`×`height` pixels (regardless of the view's size; see
+/// `ViewBase::render_offscreen_tiled`) and saves it as PNG or JPEG (as `save_view_image`).
+pub fn export_view_image(path: &str, view: &ViewBase, width: u32, height: u32) -> Result<(), String> {
+    save_image(path, view.render_offscreen_tiled(width, height, Layers::All)?)
+}
+
+/// Exports `view` (only its raster layers, unless `lines` is set) as a GeoTIFF image in the view's coordinate
+/// reference system (see `wkt::view_crs`), so that it can be used in GIS software.
+pub fn export_view_geotiff(path: &str, view: &ViewBase, width: u32, height: u32, lines: bool) -> Result<(), String> {
+    if view.stereo_mode() != StereoMode::Off {
+        return Err("the view must not be in a stereo mode".into());
+    }
+    let crs = wkt::view_crs(view)?;
+
+    // the image spans 2 / zoom map units vertically (see `ViewBase::grid_coordinates`), with square pixels;
+    // `view_crs` ensures the view is centered on its grid's origin (if any), up to a shift along the central meridian
+    let grid = view.grid().unwrap_or(Grid::DEFAULT);
+    let pixel_size = 2.0 * grid.scale_factor * view.body().semi_major_axis / (view.zoom() * height as f64);
+    let center = view.grid_coordinates(cgmath::Point2{ x: 0.0, y: 0.0 })
+        .unwrap_or(cgmath::Point2{ x: grid.false_easting, y: grid.false_northing });
+    let top_left = [
+        center.x - pixel_size * width as f64 / 2.0,
+        center.y + pixel_size * height as f64 / 2.0
+    ];
+
+    let image = view.render_offscreen_tiled(width, height, if lines { Layers::All } else { Layers::RasterOnly })?;
+    geotiff::save(path, &image, top_left, pixel_size, &crs)
+}
+
+/// Exports `layers` projected to the grid coordinates of `view` (see `ViewBase::project_polylines`) as a GeoJSON
+/// or shapefile (as indicated by the extension of `path`: ".shp" or else GeoJSON).
+///
+/// Each layer becomes a single multi-part line feature, with the layer's name as its "layer" attribute. If the view
+/// has a standard coordinate reference system (see `wkt::view_crs`), it is written to a ".prj" file next to
+/// a shapefile; GeoJSON has no means of specifying it (and other programs may assume longitude and latitude).
+///
+pub fn export_vector_layers(path: &str, view: &ViewBase, layers: &[&VectorLayer]) -> Result<(), String> {
+    require_cpu_projection(view)?;
+    let projected: Vec<(&str, Vec<Vec<cgmath::Point2<f64>>>)> = layers.iter()
+        .map(|layer| (layer.name.as_str(), view.project_polylines(&layer.polylines)))
+        .filter(|(_, lines)| !lines.is_empty())
+        .collect();
+    if projected.is_empty() {
+        return Err("no lines to export".into());
+    }
+
+    if path.to_lowercase().ends_with(".shp") {
+        write_shapefile(path, &projected)?;
+        if let Ok(crs) = wkt::view_crs(view) {
+            std::fs::write(std::path::Path::new(path).with_extension("prj"), crs).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    } else {
+        let features: Vec<serde_json::Value> = projected.iter().map(|(name, lines)| serde_json::json!({
+            "type": "Feature",
+            "properties": { "layer": name },
+            "geometry": {
+                "type": "MultiLineString",
+                "coordinates": lines.iter()
+                    .map(|line| line.iter().map(|p| [p.x, p.y]).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            }
+        })).collect();
+        let collection = serde_json::json!({ "type": "FeatureCollection", "features": features });
+
+        std::fs::write(path, collection.to_string()).map_err(|e| e.to_string())
+    }
+}
+
+fn write_shapefile(path: &str, layers: &[(&str, Vec<Vec<cgmath::Point2<f64>>>)]) -> Result<(), String> {
+    use shapefile::dbase::{FieldName, FieldValue, Record, TableWriterBuilder};
+
+    let field_name = FieldName::try_from(SHAPEFILE_LAYER_FIELD).map_err(|_| "invalid field name".to_string())?;
+    let table_builder = TableWriterBuilder::new().add_character_field(field_name, SHAPEFILE_MAX_FIELD_LENGTH);
+    let mut writer = shapefile::Writer::from_path(path, table_builder).map_err(|e| e.to_string())?;
+
+    for (name, lines) in layers {
+        let parts = lines.iter()
+            .map(|line| line.iter().map(|p| shapefile::Point::new(p.x, p.y)).collect())
+            .collect();
+        let mut name = name.to_string();
+        while name.len() > SHAPEFILE_MAX_FIELD_LENGTH as usize { name.pop(); }
+        let mut record = Record::default();
+        record.insert(SHAPEFILE_LAYER_FIELD.to_string(), FieldValue::Character(Some(name)));
+        writer.write_shape_and_record(&shapefile::Polyline::with_parts(parts), &record).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn save_image(path: &str, image: image::RgbaImage) -> Result<(), String> {
+    let lower_case_path = path.to_lowercase();
+    let result = if lower_case_path.ends_with(".jpg") || lower_case_path.ends_with(".jpeg") {
+        image::DynamicImage::ImageRgba8(image).into_rgb8().save(path)
+    } else {
+        image.save(path)
+    };
+
+    result.map_err(|e| e.to_string())
+}
+
+/// Encoder of a video (H.264, e.g. MP4) by an `ffmpeg` process (which has to be in PATH), fed raw RGBA frames
+/// through its standard input.
+pub struct VideoEncoder {
+    ffmpeg: std::process::Child,
+    width: u32,
+    height: u32
+}
+
+impl VideoEncoder {
+    /// Starts encoding a video of the specified frame size (both have to be even) to `path`; the container format
+    /// is chosen by ffmpeg from the extension.
+    pub fn start(path: &str, width: u32, height: u32, frame_rate: u32) -> Result<VideoEncoder, String> {
+        let ffmpeg = std::process::Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", width, height), "-r", &frame_rate.to_string(), "-i", "-"])
+            .args(["-c:v", "libx264", "-pix_fmt", "yuv420p", path])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to start ffmpeg: {}", e))?;
+
+        Ok(VideoEncoder{ ffmpeg, width, height })
+    }
+
+    pub fn add_frame(&mut self, image: &image::RgbaImage) -> Result<(), String> {
+        if image.dimensions() != (self.width, self.height) {
+            return Err("frame size has changed".into());
+        }
+        let stdin = self.ffmpeg.stdin.as_mut().ok_or_else(|| "encoding already finished".to_string())?;
+        stdin.write_all(image.as_raw()).map_err(|e| format!("ffmpeg stopped accepting frames: {}", e))
+    }
+
+    /// Completes the video (also if not all intended frames have been added) and waits for ffmpeg to exit.
+    pub fn finish(mut self) -> Result<(), String> {
+        drop(self.ffmpeg.stdin.take());
+        let output = self.ffmpeg.wait_with_output().map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!("ffmpeg failed: {}", String::from_utf8_lossy(&output.stderr).trim()))
+        }
+    }
+}
+
+/// Encoder of a looped animated GIF; each frame is quantized to its own palette of 256 colors. The file is completed
+/// when the encoder is dropped.
+pub struct AnimatedGifEncoder {
+    encoder: image::codecs::gif::GifEncoder<std::io::BufWriter<std::fs::File>>,
+    delay: image::Delay
+}
+
+impl AnimatedGifEncoder {
+    /// Creates the file at `path`; GIF frame durations are multiples of 1/100 s, so `frame_rate` is best chosen
+    /// as a divisor of 100.
+    pub fn create(path: &str, frame_rate: u32) -> Result<AnimatedGifEncoder, String> {
+        let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        let mut encoder = image::codecs::gif::GifEncoder::new_with_speed(std::io::BufWriter::new(file), GIF_SPEED);
+        encoder.set_repeat(image::codecs::gif::Repeat::Infinite).map_err(|e| e.to_string())?;
+
+        Ok(AnimatedGifEncoder{ encoder, delay: image::Delay::from_numer_denom_ms(1000, frame_rate) })
+    }
+
+    pub fn add_frame(&mut self, image: image::RgbaImage) -> Result<(), String> {
+        self.encoder.encode_frame(image::Frame::from_parts(image, 0, 0, self.delay)).map_err(|e| e.to_string())
+    }
+}
+
+/// Exports `view` as a single-page PDF document.
+///
+/// The map fills the page within the margins specified in `settings`. The globe texture is embedded as an image
+/// rendered at the specified resolution, and all lines (coastlines, graticule, vector layers, script overlay)
+/// are projected on the CPU and written as vector paths.
+///
+pub fn export_view_pdf(path: &str, view: &ViewBase, settings: &PrintSettings) -> Result<(), String> {
+    settings.validate()?;
+    require_cpu_projection(view)?;
+
+    let to_points = |mm: f64| mm / printing::MM_PER_INCH * pdf::POINTS_PER_INCH;
+    let (width_mm, height_mm) = settings.page_size_mm();
+    let mut page = pdf::Page::new(to_points(width_mm), to_points(height_mm));
+
+    let (map_w_mm, map_h_mm) = settings.map_size_mm();
+    let [left, bottom, map_w, map_h] = [settings.margin_mm, settings.margin_mm, map_w_mm, map_h_mm].map(to_points);
+    page.set_content_rect([left, bottom, map_w, map_h]);
+
+    let (width, height) = settings.map_size();
+    let raster = view.render_offscreen_tiled(width, height, Layers::RasterOnly)?;
+    page.set_background(&image::DynamicImage::ImageRgba8(raster).into_rgb8()).map_err(|e| e.to_string())?;
+
+    let to_page = |p: cgmath::Point2<f64>| [left + (p.x + 1.0) / 2.0 * map_w, bottom + (p.y + 1.0) / 2.0 * map_h];
+
+    for lines in view.projected_lines((map_w / map_h) as f32) {
+        page.begin_path(lines.color, PDF_LINE_WIDTH);
+        for segment in &lines.segments {
+            page.add_segment(to_page(segment[0]), to_page(segment[1]));
+        }
+        page.end_path();
+    }
+
+    page.save(path).map_err(|e| e.to_string())
+}
+
+/// Exports lines of `view` (coastlines, graticule, vector layers, overlays, outline) as an SVG document
+/// of `width`×`height` pixels.
+///
+/// Lines are projected on the CPU (as by the shaders), clipped to the view's rectangle and written as paths,
+/// one group per layer; consecutive connected segments form a single path. Raster layers (e.g. the globe texture)
+/// are not included.
+///
+pub fn export_view_svg(path: &str, view: &ViewBase, width: u32, height: u32) -> Result<(), String> {
+    if width == 0 || height == 0 {
+        return Err("image size must be positive".to_string());
+    }
+    require_cpu_projection(view)?;
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path).map_err(|e| e.to_string())?);
+    write_svg(&mut file, view, width, height).and_then(|_| file.flush()).map_err(|e| e.to_string())
+}
+
+fn write_svg<W: Write>(output: &mut W, view: &ViewBase, width: u32, height: u32) -> std::io::Result<()> {
+    let (w, h) = (width as f64, height as f64);
+    let to_svg = |p: cgmath::Point2<f64>| [(p.x + 1.0) / 2.0 * w, (1.0 - p.y) / 2.0 * h];
+
+    writeln!(output, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        output,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+        width, height
+    )?;
+    for lines in view.projected_lines((w / h) as f32) {
+        let [r, g, b, a] = lines.color;
+        let channel = |c: f32| (c.max(0.0).min(1.0) * 255.0).round() as u8;
+        writeln!(
+            output,
+            concat!(
+                r#"<g fill="none" stroke="rgb({},{},{})" stroke-opacity="{:.3}" stroke-width="{}""#,
+                r#" stroke-linejoin="round">"#
+            ),
+            channel(r), channel(g), channel(b), a, SVG_LINE_WIDTH
+        )?;
+
+        let mut path_data = String::new();
+        let mut last_point: Option<cgmath::Point2<f64>> = None;
+        for [p1, p2] in lines.segments.iter().filter_map(|segment| clip_to_view(segment[0], segment[1])) {
+            let joined = last_point.map_or(false, |last| (p1.x - last.x).hypot(p1.y - last.y) < SVG_JOIN_TOLERANCE);
+            if !joined {
+                if !path_data.is_empty() {
+                    writeln!(output, r#"<path d="{}"/>"#, path_data)?;
+                }
+                let [x, y] = to_svg(p1);
+                path_data = format!("M{:.2} {:.2}", x, y);
+            }
+            let [x, y] = to_svg(p2);
+            path_data += &format!(" L{:.2} {:.2}", x, y);
+            last_point = Some(p2);
+        }
+        if !path_data.is_empty() {
+            writeln!(output, r#"<path d="{}"/>"#, path_data)?;
+        }
+
+        writeln!(output, "</g>")?;
+    }
+    writeln!(output, "</svg>")
+}
+
+/// Clips the segment `p1`-`p2` (in normalized device coordinates) to the view's rectangle (Liang-Barsky algorithm).
+fn clip_to_view(p1: cgmath::Point2<f64>, p2: cgmath::Point2<f64>) -> Option<[cgmath::Point2<f64>; 2]> {
+    let d = p2 - p1;
+    let (mut t0, mut t1) = (0.0, 1.0);
+    for (p, q) in [(-d.x, p1.x + 1.0), (d.x, 1.0 - p1.x), (-d.y, p1.y + 1.0), (d.y, 1.0 - p1.y)] {
+        if p == 0.0 {
+            if q < 0.0 { return None; }
+        } else {
+            let t = q / p;
+            if p < 0.0 { t0 = t.max(t0); } else { t1 = t.min(t1); }
+            if t0 > t1 { return None; }
+        }
+    }
+
+    Some([p1 + d * t0, p1 + d * t1])
+}
@@ -0,0 +1,169 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Search of populated places by name: Natural Earth's places (see `data::Place`) and, optionally, those of
+// a GeoNames table (e.g. "cities15000.txt" from https://download.geonames.org/export/dump/).
+//
+// Names are matched case-insensitively and without diacritics of Latin letters (so that "sao paulo" finds
+// "São Paulo"); places whose name (or, for GeoNames, one of the alternate names) starts with the query come first,
+// then those containing it, each in the order of decreasing population rank.
+//
+
+use crate::data::Place;
+use std::collections::HashMap;
+
+/// GeoNames places closer than this (degrees) to a Natural Earth place of the same name are considered duplicates.
+const DUPLICATE_DISTANCE: f64 = 0.25;
+
+/// Min. populations of Natural Earth's population ranks (`RANK_MAX`) 1 to 14.
+const RANK_POPULATIONS: [u64; 14] = [
+    1, 200, 1_000, 2_000, 5_000, 10_000, 20_000, 50_000, 100_000, 200_000, 500_000, 1_000_000, 5_000_000, 10_000_000
+];
+
+pub struct Entry {
+    pub name: String,
+    /// Country name (Natural Earth) or ISO code (GeoNames); may be empty.
+    pub country: String,
+    /// Degrees.
+    pub longitude: f64,
+    /// Degrees.
+    pub latitude: f64,
+    /// Population rank (as `Place::rank`).
+    pub rank: u8,
+    /// Names matched by queries (see `fold`).
+    keys: Vec<String>
+}
+
+impl Entry {
+    /// Returns the name followed by the country (if known).
+    pub fn full_name(&self) -> String {
+        if self.country.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}, {}", self.name, self.country)
+        }
+    }
+}
+
+pub struct Gazetteer {
+    entries: Vec<Entry>
+}
+
+impl Gazetteer {
+    pub fn new(places: &[Place]) -> Gazetteer {
+        Gazetteer{ entries: places.iter().map(|place| Entry{
+            name: place.name.clone(),
+            country: place.country.clone(),
+            longitude: place.longitude.0,
+            latitude: place.latitude.0,
+            rank: place.rank,
+            keys: vec![fold(&place.name)]
+        }).collect() }
+    }
+
+    /// Adds places from a GeoNames table (tab-separated; see https://download.geonames.org/export/dump/readme.txt),
+    /// skipping those already present; returns the number of added places.
+    pub fn add_geonames(&mut self, path: &str) -> Result<usize, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+        let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, entry) in self.entries.iter().enumerate() {
+            by_key.entry(entry.keys[0].clone()).or_default().push(idx);
+        }
+
+        let mut added = vec![];
+        for (line_idx, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() { continue; }
+            let fields: Vec<&str> = line.split('\t').collect();
+            let invalid = || format!("invalid line {}", line_idx + 1);
+            if fields.len() < 15 { return Err(invalid()); }
+            let latitude: f64 = fields[4].parse().map_err(|_| invalid())?;
+            let longitude: f64 = fields[5].parse().map_err(|_| invalid())?;
+            let population: u64 = fields[14].parse().unwrap_or(0);
+
+            let name = fields[1].to_string();
+            let key = fold(&name);
+            let duplicate = by_key.get(&key).map_or(false, |indices| indices.iter().any(|idx| {
+                let entry = &self.entries[*idx];
+                (entry.longitude - longitude).abs() < DUPLICATE_DISTANCE
+                    && (entry.latitude - latitude).abs() < DUPLICATE_DISTANCE
+            }));
+            if duplicate { continue; }
+
+            let mut keys = vec![key];
+            for alternate in std::iter::once(fields[2]).chain(fields[3].split(',')) {
+                let alternate = fold(alternate);
+                if !alternate.is_empty() && !keys.contains(&alternate) {
+                    keys.push(alternate);
+                }
+            }
+            added.push(Entry{
+                name,
+                country: fields[8].to_string(),
+                longitude,
+                latitude,
+                rank: RANK_POPULATIONS.iter().filter(|min| population >= **min).count() as u8,
+                keys
+            });
+        }
+
+        let num_added = added.len();
+        self.entries.extend(added);
+        self.entries.sort_by(|a, b| b.rank.cmp(&a.rank));
+
+        Ok(num_added)
+    }
+
+    /// Returns at most `max_results` places matching `query`, best matches first.
+    pub fn search(&self, query: &str, max_results: usize) -> Vec<&Entry> {
+        let query = fold(query.trim());
+        if query.is_empty() { return vec![]; }
+
+        let mut prefix_matches = vec![];
+        let mut other_matches = vec![];
+        for entry in &self.entries {
+            if entry.keys.iter().any(|key| key.starts_with(&query)) {
+                prefix_matches.push(entry);
+                if prefix_matches.len() == max_results { break; }
+            } else if other_matches.len() < max_results && entry.keys.iter().any(|key| key.contains(&query)) {
+                other_matches.push(entry);
+            }
+        }
+        prefix_matches.extend(other_matches);
+        prefix_matches.truncate(max_results);
+
+        prefix_matches
+    }
+}
+
+/// Returns `text` in lower case, with diacritics removed from Latin letters.
+fn fold(text: &str) -> String {
+    text.to_lowercase().chars().map(|c| match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'ď' | 'đ' => 'd',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'ĥ' | 'ħ' => 'h',
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+        'ĵ' => 'j',
+        'ķ' => 'k',
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => 'l',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => 'o',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => 's',
+        'ţ' | 'ť' | 'ŧ' | 'ț' => 't',
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'ŵ' => 'w',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        c => c
+    }).collect()
+}
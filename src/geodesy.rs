@@ -0,0 +1,207 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Geodesic lengths and areas on a sphere or an ellipsoid of revolution.
+//
+// Ellipsoidal distances are computed with Vincenty's inverse formula (accurate to well below 1 mm); ellipsoidal
+// areas are computed on the authalic sphere (of the same surface area) from authalic latitudes, i.e. edges are
+// taken as great circles of that sphere, which differs from the ellipsoid's geodesics by a small fraction
+// (of the order of the flattening) of the edges' deviation from rhumb lines.
+//
+
+use crate::geometry;
+
+/// Max. number of iterations of Vincenty's formula (it does not converge for nearly antipodal points).
+const MAX_VINCENTY_ITERATIONS: usize = 200;
+
+/// Model of a body's figure; lengths in km.
+#[derive(Copy, Clone)]
+pub enum Figure {
+    Sphere{ radius: f64 },
+    Ellipsoid{ semi_major_axis: f64, flattening: f64 }
+}
+
+impl Figure {
+    /// Returns the radius of a sphere of the same surface area.
+    fn authalic_radius(&self) -> f64 {
+        match *self {
+            Figure::Sphere{ radius } => radius,
+            Figure::Ellipsoid{ semi_major_axis: a, flattening: f } => {
+                let e2 = f * (2.0 - f);
+                if e2 < 1.0e-12 { return a; }
+                let e = e2.sqrt();
+                a * (0.5 * (1.0 + (1.0 - e2) / e * e.atanh())).sqrt()
+            }
+        }
+    }
+
+    /// Returns the authalic latitude (degrees) corresponding to `latitude` (degrees).
+    fn authalic_latitude(&self, latitude: f64) -> f64 {
+        match *self {
+            Figure::Sphere{ .. } => latitude,
+            Figure::Ellipsoid{ flattening: f, .. } => {
+                let e2 = f * (2.0 - f);
+                if e2 < 1.0e-12 { return latitude; }
+                let e = e2.sqrt();
+                let q = |sin_lat: f64| {
+                    (1.0 - e2) * (sin_lat / (1.0 - e2 * sin_lat * sin_lat) + (e * sin_lat).atanh() / e)
+                };
+                (q(latitude.to_radians().sin()) / q(1.0)).max(-1.0).min(1.0).asin().to_degrees()
+            }
+        }
+    }
+}
+
+/// Returns the length (km) of the geodesic between points given as [longitude, latitude] in degrees.
+pub fn distance(a: [f64; 2], b: [f64; 2], figure: &Figure) -> f64 {
+    match *figure {
+        Figure::Sphere{ radius } => geometry::great_circle_distance(a, b).0 * radius,
+        Figure::Ellipsoid{ semi_major_axis, flattening } => vincenty_distance(a, b, semi_major_axis, flattening)
+            // for nearly antipodal points, use the sphere of the mean radius
+            .unwrap_or_else(|| geometry::great_circle_distance(a, b).0 * semi_major_axis * (1.0 - flattening / 3.0))
+    }
+}
+
+/// Returns the total length (km) of geodesics between consecutive `points`.
+pub fn length(points: &[[f64; 2]], figure: &Figure) -> f64 {
+    points.iter().zip(points.iter().skip(1)).map(|(a, b)| distance(*a, *b, figure)).sum()
+}
+
+/// Returns the area (km²) of the polygon with vertices `ring` (the closing edge is implied); of the two regions
+/// bounded by the polygon, the smaller one is measured.
+pub fn area(ring: &[[f64; 2]], figure: &Figure) -> f64 {
+    if ring.len() < 3 { return 0.0; }
+
+    // sum of the signed spherical excesses of triangles formed by the edges and the north pole
+    let tan_half = |p: [f64; 2]| (figure.authalic_latitude(p[1]).to_radians() / 2.0).tan();
+    let mut excess = 0.0;
+    let mut winding = 0.0;
+    for (idx, p1) in ring.iter().enumerate() {
+        let p2 = ring[(idx + 1) % ring.len()];
+        let d_lon = ((p2[0] - p1[0] + 180.0).rem_euclid(360.0) - 180.0).to_radians();
+        let (t1, t2) = (tan_half(*p1), tan_half(p2));
+        excess += 2.0 * ((d_lon / 2.0).tan() * (t1 + t2)).atan2(1.0 + t1 * t2);
+        winding += d_lon;
+    }
+    // a polygon encircling a pole winds around it once; the triangles then cover the complement of the region
+    // on the pole's side
+    let full_angle = 2.0 * std::f64::consts::PI;
+    let excess = (excess - (winding / full_angle).round() * full_angle).abs();
+    let excess = excess.min(2.0 * full_angle - excess);
+
+    excess * figure.authalic_radius().powi(2)
+}
+
+/// Returns the ellipsoidal distance (in units of `a`) between points given as [longitude, latitude] in degrees,
+/// or `None` if the computation does not converge.
+fn vincenty_distance(p1: [f64; 2], p2: [f64; 2], a: f64, f: f64) -> Option<f64> {
+    let b = a * (1.0 - f);
+    let l = (p2[0] - p1[0]).to_radians();
+    // reduced latitudes
+    let u1 = ((1.0 - f) * p1[1].to_radians().tan()).atan();
+    let u2 = ((1.0 - f) * p2[1].to_radians().tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..MAX_VINCENTY_ITERATIONS {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2)).sqrt();
+        if sin_sigma == 0.0 { return Some(0.0); }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos2_alpha = 1.0 - sin_alpha * sin_alpha;
+        // on the equator, `cos2_alpha` is 0
+        let cos_2sigma_m = if cos2_alpha != 0.0 { cos_sigma - 2.0 * sin_u1 * sin_u2 / cos2_alpha } else { 0.0 };
+        let c = f / 16.0 * cos2_alpha * (4.0 + f * (4.0 - 3.0 * cos2_alpha));
+        let prev_lambda = lambda;
+        lambda = l + (1.0 - c) * f * sin_alpha
+            * (sigma + c * sin_sigma * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+
+        if (lambda - prev_lambda).abs() < 1.0e-12 {
+            let u_sq = cos2_alpha * (a * a - b * b) / (b * b);
+            let aa = 1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let bb = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = bb * sin_sigma * (cos_2sigma_m + bb / 4.0 * (
+                cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                - bb / 6.0 * cos_2sigma_m * (-3.0 + 4.0 * sin_sigma.powi(2)) * (-3.0 + 4.0 * cos_2sigma_m.powi(2))
+            ));
+
+            return Some(b * aa * (sigma - delta_sigma));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WGS84: Figure = Figure::Ellipsoid{ semi_major_axis: 6378.137, flattening: 1.0 / 298.257223563 };
+
+    const SPHERE_RADIUS: f64 = 6371.0;
+
+    /// Converts degrees, minutes and seconds to degrees.
+    fn dms(degrees: f64, minutes: f64, seconds: f64) -> f64 {
+        degrees.signum() * (degrees.abs() + minutes / 60.0 + seconds / 3600.0)
+    }
+
+    #[test]
+    fn vincenty_distance_matches_reference() {
+        // Flinders Peak and Buninyong; the reference distance is from Vincenty's original paper (1975)
+        // and differs from the WGS84 one by much less than the tolerance
+        let flinders_peak = [dms(144.0, 25.0, 29.52440), dms(-37.0, 57.0, 3.72030)];
+        let buninyong = [dms(143.0, 55.0, 35.38390), dms(-37.0, 39.0, 10.15610)];
+        let d = distance(flinders_peak, buninyong, &WGS84);
+        assert!((d - 54.972271).abs() < 1.0e-6, "{}", d);
+        assert!((distance(buninyong, flinders_peak, &WGS84) - d).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn area_of_spherical_octant() {
+        let octant = [[0.0, 0.0], [90.0, 0.0], [0.0, 90.0]];
+        let expected = 4.0 * std::f64::consts::PI * SPHERE_RADIUS.powi(2) / 8.0;
+        let figure = Figure::Sphere{ radius: SPHERE_RADIUS };
+        assert!((area(&octant, &figure) / expected - 1.0).abs() < 1.0e-12);
+        let reversed: Vec<[f64; 2]> = octant.iter().rev().copied().collect();
+        assert!((area(&reversed, &figure) / expected - 1.0).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn area_of_ring_around_pole() {
+        // a fine polygon along a parallel approximates the spherical cap beyond it
+        const NUM_VERTICES: usize = 3600;
+        const LATITUDE: f64 = 80.0;
+        let figure = Figure::Sphere{ radius: SPHERE_RADIUS };
+        let expected = 2.0 * std::f64::consts::PI * SPHERE_RADIUS.powi(2) * (1.0 - LATITUDE.to_radians().sin());
+        for pole_lat in [LATITUDE, -LATITUDE] {
+            let ring: Vec<[f64; 2]> = (0..NUM_VERTICES)
+                .map(|i| [-180.0 + 360.0 * i as f64 / NUM_VERTICES as f64, pole_lat])
+                .collect();
+            let a = area(&ring, &figure);
+            assert!((a / expected - 1.0).abs() < 1.0e-5, "{} vs. {}", a, expected);
+        }
+    }
+
+    #[test]
+    fn nearly_antipodal_distance_falls_back_to_sphere() {
+        let (a, b) = ([0.0, 0.0], [179.7, 0.5]);
+        let (semi_major_axis, flattening) = match WGS84 {
+            Figure::Ellipsoid{ semi_major_axis, flattening } => (semi_major_axis, flattening),
+            _ => unreachable!()
+        };
+        assert!(vincenty_distance(a, b, semi_major_axis, flattening).is_none());
+        let d = distance(a, b, &WGS84);
+        let expected = geometry::great_circle_distance(a, b).0 * semi_major_axis * (1.0 - flattening / 3.0);
+        assert!((d - expected).abs() < 1.0e-9, "{} vs. {}", d, expected);
+    }
+}
@@ -0,0 +1,106 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Loading of lines from GeoJSON files (RFC 7946), shown by views as vector layers.
+//
+// Geometries of types LineString, MultiLineString, Polygon and MultiPolygon (rings are drawn as lines) are loaded,
+// also from Features, FeatureCollections and GeometryCollections; points are skipped. Coordinates are longitude
+// and latitude in degrees (WGS84); altitude (if present) is ignored.
+//
+
+use serde_json::Value;
+
+/// Polyline given by (longitude, latitude) of its vertices (degrees).
+pub type Polyline = Vec<[f64; 2]>;
+
+/// Loads all lines from the GeoJSON file at `path`.
+pub fn load(path: &str) -> Result<Vec<Polyline>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: Value = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+
+    let mut lines = vec![];
+    add_object(&value, &mut lines)?;
+    if lines.is_empty() {
+        return Err("no lines found".into());
+    }
+
+    Ok(lines)
+}
+
+/// Returns all lines of a GeoJSON object (a geometry, a feature or a collection).
+pub fn object_lines(object: &Value) -> Result<Vec<Polyline>, String> {
+    let mut lines = vec![];
+    add_object(object, &mut lines)?;
+
+    Ok(lines)
+}
+
+fn add_object(object: &Value, lines: &mut Vec<Polyline>) -> Result<(), String> {
+    let object_type = object.get("type").and_then(Value::as_str).ok_or_else(|| "missing \"type\"".to_string())?;
+
+    match object_type {
+        "FeatureCollection" => for feature in member_array(object, "features")? {
+            add_object(feature, lines)?;
+        },
+
+        "Feature" => match object.get("geometry") {
+            None | Some(Value::Null) => (),
+            Some(geometry) => add_object(geometry, lines)?
+        },
+
+        "GeometryCollection" => for geometry in member_array(object, "geometries")? {
+            add_object(geometry, lines)?;
+        },
+
+        "LineString" => lines.push(polyline(member(object, "coordinates")?)?),
+
+        "MultiLineString" | "Polygon" => for line in as_array(member(object, "coordinates")?)? {
+            lines.push(polyline(line)?);
+        },
+
+        "MultiPolygon" => for polygon in as_array(member(object, "coordinates")?)? {
+            for ring in as_array(polygon)? {
+                lines.push(polyline(ring)?);
+            }
+        },
+
+        "Point" | "MultiPoint" => (),
+
+        other => return Err(format!("unsupported object type: {}", other))
+    }
+
+    Ok(())
+}
+
+fn member<'a>(object: &'a Value, name: &str) -> Result<&'a Value, String> {
+    object.get(name).ok_or_else(|| format!("missing \"{}\"", name))
+}
+
+fn member_array<'a>(object: &'a Value, name: &str) -> Result<&'a Vec<Value>, String> {
+    as_array(member(object, name)?)
+}
+
+fn as_array(value: &Value) -> Result<&Vec<Value>, String> {
+    value.as_array().ok_or_else(|| format!("expected an array, found: {}", value))
+}
+
+fn polyline(positions: &Value) -> Result<Polyline, String> {
+    as_array(positions)?.iter().map(|position| {
+        let invalid = || format!("invalid position: {}", position);
+        let coords = as_array(position)?;
+        if coords.len() < 2 { return Err(invalid()); }
+        let (lon, lat) = match (coords[0].as_f64(), coords[1].as_f64()) {
+            (Some(lon), Some(lat)) => (lon, lat),
+            _ => return Err(invalid())
+        };
+        if lon.abs() > 180.0 || lat.abs() > 90.0 { return Err(invalid()); }
+
+        Ok([lon, lat])
+    }).collect()
+}
@@ -0,0 +1,244 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Main geomagnetic field from the World Magnetic Model (WMM), read from its coefficient file (WMM.COF, available
+// from https://www.ncei.noaa.gov/products/world-magnetic-model).
+//
+// The field is a spherical harmonic expansion (Schmidt semi-normalized) of the potential, with coefficients
+// changing linearly from the model's epoch (secular variation). It is computed at the WGS84 ellipsoid's surface
+// and rotated from geocentric to geodetic components, as described in "The US/UK World Magnetic Model for
+// 2020-2025: Technical Report" (NOAA NCEI, 2019).
+//
+
+use crate::solar::UtcDateTime;
+
+/// Geomagnetic reference radius (km).
+const REFERENCE_RADIUS: f64 = 6371.2;
+
+/// WGS84 semi-major axis (km).
+const WGS84_A: f64 = 6378.137;
+
+/// WGS84 flattening.
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// Number of years after the epoch for which a model is valid.
+pub const VALIDITY_YEARS: f64 = 5.0;
+
+/// Max. absolute latitude (degrees) at which the field is computed; the east component is undefined at the poles.
+const MAX_LATITUDE: f64 = 89.9999;
+
+/// Latitude (degrees) beyond which magnetic dip poles are searched for.
+const POLE_SEARCH_LATITUDE: f64 = 55.0;
+
+pub struct Model {
+    pub name: String,
+    /// Decimal year of the coefficients.
+    pub epoch: f64,
+    max_degree: usize,
+    /// Coefficients g, h (nT) and their secular variation (nT/year), indexed by `coefficient_index(n, m)`.
+    g: Vec<f64>,
+    h: Vec<f64>,
+    dg: Vec<f64>,
+    dh: Vec<f64>
+}
+
+/// Components (nT) of the field: north, east and down.
+#[derive(Copy, Clone)]
+pub struct FieldComponents {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64
+}
+
+impl FieldComponents {
+    /// Returns the declination (degrees, positive east).
+    pub fn declination(&self) -> f64 { self.y.atan2(self.x).to_degrees() }
+
+    /// Returns the horizontal intensity (nT).
+    pub fn horizontal_intensity(&self) -> f64 { self.x.hypot(self.y) }
+}
+
+fn coefficient_index(n: usize, m: usize) -> usize { n * (n + 1) / 2 + m }
+
+/// Returns `time` as a decimal year.
+pub fn decimal_year(time: &UtcDateTime) -> f64 {
+    let start = UtcDateTime{ year: time.year, month: 1, day: 1, hour: 0, minute: 0, second: 0 }.unix_time();
+    let end = UtcDateTime{ year: time.year + 1, month: 1, day: 1, hour: 0, minute: 0, second: 0 }.unix_time();
+
+    time.year as f64 + (time.unix_time() - start) as f64 / (end - start) as f64
+}
+
+/// Loads a model from a WMM coefficient file: a header line with the epoch and the model's name, then lines
+/// "n m g h dg dh", terminated by a line of 9s (or the end of the file).
+pub fn load(path: &str) -> Result<Model, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+
+    let header: Vec<&str> = lines.next().ok_or_else(|| "empty file".to_string())?.split_whitespace().collect();
+    let epoch: f64 = header.get(0).and_then(|epoch| epoch.parse().ok())
+        .ok_or_else(|| "invalid header (expected the epoch)".to_string())?;
+    let name = header.get(1).map_or("WMM".to_string(), |name| name.to_string());
+
+    let mut coefficients = vec![];
+    for (idx, line) in lines.enumerate() {
+        if line.trim_start().starts_with("9999") { break; }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let invalid = || format!("invalid line {}: {}", idx + 2, line);
+        if fields.len() < 6 { return Err(invalid()); }
+        let n: usize = fields[0].parse().map_err(|_| invalid())?;
+        let m: usize = fields[1].parse().map_err(|_| invalid())?;
+        let mut values = [0.0; 4];
+        for (value, field) in values.iter_mut().zip(&fields[2..6]) {
+            *value = field.parse().map_err(|_| invalid())?;
+        }
+        if n == 0 || m > n { return Err(invalid()); }
+        coefficients.push((n, m, values));
+    }
+    let max_degree = coefficients.iter().map(|(n, _, _)| *n).max().ok_or_else(|| "no coefficients".to_string())?;
+
+    let num_coefficients = coefficient_index(max_degree, max_degree) + 1;
+    let mut model = Model{
+        name,
+        epoch,
+        max_degree,
+        g: vec![0.0; num_coefficients],
+        h: vec![0.0; num_coefficients],
+        dg: vec![0.0; num_coefficients],
+        dh: vec![0.0; num_coefficients]
+    };
+    for (n, m, [g, h, dg, dh]) in coefficients {
+        let idx = coefficient_index(n, m);
+        model.g[idx] = g;
+        model.h[idx] = h;
+        model.dg[idx] = dg;
+        model.dh[idx] = dh;
+    }
+
+    Ok(model)
+}
+
+impl Model {
+    /// Returns true if `year` lies within the model's validity period.
+    pub fn is_valid_at(&self, year: f64) -> bool {
+        year >= self.epoch && year <= self.epoch + VALIDITY_YEARS
+    }
+
+    /// Returns the field at the ellipsoid's surface at the given geodetic longitude and latitude (degrees)
+    /// and decimal year.
+    pub fn field(&self, longitude: f64, latitude: f64, year: f64) -> FieldComponents {
+        let dt = year - self.epoch;
+        let (lat, lon) = (latitude.max(-MAX_LATITUDE).min(MAX_LATITUDE).to_radians(), longitude.to_radians());
+
+        // geocentric latitude and radius
+        let e2 = WGS84_F * (2.0 - WGS84_F);
+        let rc = WGS84_A / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+        let (p, z) = (rc * lat.cos(), rc * (1.0 - e2) * lat.sin());
+        let r = p.hypot(z);
+        let lat_gc = (z / r).asin();
+
+        let (sin_lat, cos_lat) = lat_gc.sin_cos();
+        let (legendre, derivatives) = self.legendre(sin_lat, cos_lat);
+
+        let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+        let mut ratio = (REFERENCE_RADIUS / r).powi(2);
+        for n in 1..=self.max_degree {
+            ratio *= REFERENCE_RADIUS / r;
+            for m in 0..=n {
+                let idx = coefficient_index(n, m);
+                let g = self.g[idx] + dt * self.dg[idx];
+                let h = self.h[idx] + dt * self.dh[idx];
+                let (sin_m, cos_m) = (m as f64 * lon).sin_cos();
+                let s = g * cos_m + h * sin_m;
+                x -= ratio * s * derivatives[idx];
+                y += ratio * m as f64 * (g * sin_m - h * cos_m) * legendre[idx];
+                z -= ratio * (n + 1) as f64 * s * legendre[idx];
+            }
+        }
+        y /= cos_lat;
+
+        // rotation to geodetic components
+        let (sin_d, cos_d) = (lat_gc - lat).sin_cos();
+
+        FieldComponents{ x: x * cos_d - z * sin_d, y, z: x * sin_d + z * cos_d }
+    }
+
+    pub fn declination(&self, longitude: f64, latitude: f64, year: f64) -> f64 {
+        self.field(longitude, latitude, year).declination()
+    }
+
+    /// Returns the positions (longitude, latitude in degrees) of the north and south dip poles (where the field
+    /// is vertical) at the decimal year.
+    pub fn dip_poles(&self, year: f64) -> [[f64; 2]; 2] {
+        let horizontal = |p: [f64; 2]| self.field(p[0], p[1], year).horizontal_intensity();
+        let find_pole = |lat_range: [f64; 2]| {
+            // coarse search, then refinement around the best position
+            let mut best = [0.0, lat_range[0]];
+            let search = |center: [f64; 2], extent: [f64; 2], step: f64, best: &mut [f64; 2]| {
+                let mut min = horizontal(*best);
+                let steps = |extent: f64| (extent / step).round() as i32;
+                for i in -steps(extent[0])..=steps(extent[0]) {
+                    for j in -steps(extent[1])..=steps(extent[1]) {
+                        let p = [center[0] + i as f64 * step, (center[1] + j as f64 * step).max(-90.0).min(90.0)];
+                        if p[1] < lat_range[0] || p[1] > lat_range[1] { continue; }
+                        let value = horizontal(p);
+                        if value < min {
+                            min = value;
+                            *best = p;
+                        }
+                    }
+                }
+            };
+            let middle = [0.0, (lat_range[0] + lat_range[1]) / 2.0];
+            search(middle, [180.0, (lat_range[1] - lat_range[0]) / 2.0], 1.0, &mut best);
+            for step in [0.1, 0.01] {
+                search(best, [20.0 * step, 20.0 * step], step, &mut best);
+            }
+            if best[0] > 180.0 { best[0] -= 360.0; } else if best[0] < -180.0 { best[0] += 360.0; }
+
+            best
+        };
+
+        [find_pole([POLE_SEARCH_LATITUDE, 90.0]), find_pole([-90.0, -POLE_SEARCH_LATITUDE])]
+    }
+
+    /// Returns the Schmidt semi-normalized associated Legendre functions of sin(latitude) and their derivatives
+    /// with respect to latitude, indexed by `coefficient_index`.
+    fn legendre(&self, sin_lat: f64, cos_lat: f64) -> (Vec<f64>, Vec<f64>) {
+        let size = coefficient_index(self.max_degree, self.max_degree) + 1;
+        let mut p = vec![0.0; size];
+        let mut dp = vec![0.0; size];
+        p[0] = 1.0;
+
+        for m in 0..=self.max_degree {
+            let mm = coefficient_index(m, m);
+            if m > 0 {
+                let prev = coefficient_index(m - 1, m - 1);
+                let k = if m == 1 { 1.0 } else { ((2 * m - 1) as f64 / (2 * m) as f64).sqrt() };
+                p[mm] = k * cos_lat * p[prev];
+                dp[mm] = k * (cos_lat * dp[prev] - sin_lat * p[prev]);
+            }
+            for n in m + 1..=self.max_degree {
+                let (idx, prev) = (coefficient_index(n, m), coefficient_index(n - 1, m));
+                let norm = ((n * n - m * m) as f64).sqrt();
+                let a = (2 * n - 1) as f64;
+                let (p2, dp2) = if n >= m + 2 {
+                    let prev2 = coefficient_index(n - 2, m);
+                    (p[prev2], dp[prev2])
+                } else {
+                    (0.0, 0.0)
+                };
+                let b = (((n - 1) * (n - 1)) as f64 - (m * m) as f64).max(0.0).sqrt();
+                p[idx] = (a * sin_lat * p[prev] - b * p2) / norm;
+                dp[idx] = (a * (cos_lat * p[prev] + sin_lat * dp[prev]) - b * dp2) / norm;
+            }
+        }
+
+        (p, dp)
+    }
+}
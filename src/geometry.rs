@@ -0,0 +1,937 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Geometric operations on the sphere.
+//
+// Buffering: the boundary of the region within a great-circle distance of the given points, lines and polygons
+// is found as the contour of the distance field sampled on a longitude/latitude grid (marching squares).
+//
+// Boolean operations on polygons: the polygons' great-circle edges are clipped against each other with
+// the Greiner-Hormann algorithm (crossings of edges are inserted into both polygons' rings, which are then followed,
+// switching between the polygons at crossings); insideness is determined by counting crossings of a path
+// to a point outside of both polygons.
+//
+// Isolines: fields given by functions of longitude and latitude are sampled on a global grid and contoured
+// (marching squares) at each level.
+//
+// Filling: polygons are rasterized (scanline fill) into equirectangular masks.
+//
+// Simplification: lines are simplified with the Douglas-Peucker algorithm, with distances measured in longitude
+// and latitude (which is adequate for tolerances of a fraction of a degree).
+//
+// Routes: great-circle arcs are interpolated spherically, rhumb lines (loxodromes) linearly in Mercator
+// coordinates; both (and small circles) are split at the antimeridian.
+//
+
+use crate::geojson::Polyline;
+use cgmath::{InnerSpace, Vector3};
+use std::collections::HashMap;
+
+/// Max. number of cells of grids on which fields are contoured.
+const MAX_GRID_CELLS: usize = 250_000;
+
+/// Max. number of distance computations (grid nodes × segments) used for buffering.
+const MAX_DISTANCE_COMPUTATIONS: usize = 200_000_000;
+
+/// Number of grid cells per buffer distance (determines the boundary's accuracy).
+const CELLS_PER_DISTANCE: f64 = 20.0;
+
+/// Min. number of vertices of small circles.
+const MIN_CIRCLE_VERTICES: usize = 36;
+
+fn to_xyz([lon, lat]: [f64; 2]) -> Vector3<f64> {
+    let (lon, lat) = (lon.to_radians(), lat.to_radians());
+    Vector3::new(lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin())
+}
+
+fn to_lonlat(v: Vector3<f64>) -> [f64; 2] {
+    [v.y.atan2(v.x).to_degrees(), v.z.max(-1.0).min(1.0).asin().to_degrees()]
+}
+
+/// Returns the angular distance between points given by unit vectors.
+fn angle(p: Vector3<f64>, q: Vector3<f64>) -> f64 {
+    p.cross(q).magnitude().atan2(p.dot(q))
+}
+
+/// Returns the angular distance of `p` from the great-circle segment `a`-`b` (shorter arc).
+fn segment_distance(p: Vector3<f64>, a: Vector3<f64>, b: Vector3<f64>) -> f64 {
+    let normal = a.cross(b);
+    if normal.magnitude2() < 1.0e-20 { return angle(p, a); }
+    let normal = normal.normalize();
+
+    let s = p.dot(normal);
+    let closest = p - normal * s;
+    if closest.magnitude2() > 1.0e-20 {
+        let closest = closest.normalize();
+        if a.cross(closest).dot(normal) >= 0.0 && closest.cross(b).dot(normal) >= 0.0 {
+            return s.abs().min(1.0).asin();
+        }
+    }
+
+    angle(p, a).min(angle(p, b))
+}
+
+/// Returns the great-circle (angular) distance between points given as [longitude, latitude] in degrees.
+pub fn great_circle_distance(a: [f64; 2], b: [f64; 2]) -> cgmath::Rad<f64> {
+    cgmath::Rad(angle(to_xyz(a), to_xyz(b)))
+}
+
+/// Returns the initial bearing (clockwise from north, 0°-360°) of the great-circle route from `a` to `b`
+/// (given as [longitude, latitude] in degrees).
+pub fn initial_bearing(a: [f64; 2], b: [f64; 2]) -> cgmath::Deg<f64> {
+    let (lon1, lat1) = (a[0].to_radians(), a[1].to_radians());
+    let (lon2, lat2) = (b[0].to_radians(), b[1].to_radians());
+    let y = (lon2 - lon1).sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * (lon2 - lon1).cos();
+
+    cgmath::Deg(y.atan2(x).to_degrees().rem_euclid(360.0))
+}
+
+/// Returns the shorter great-circle arc from `a` to `b` (given as [longitude, latitude] in degrees) as vertices
+/// at most `max_step` apart; the arc is split into separate lines where it crosses the antimeridian.
+pub fn great_circle_route(a: [f64; 2], b: [f64; 2], max_step: cgmath::Rad<f64>) -> Result<Vec<Polyline>, String> {
+    let (p, q) = (to_xyz(a), to_xyz(b));
+    let total = angle(p, q);
+    if std::f64::consts::PI - total < 1.0e-9 {
+        return Err("the points are antipodal; the route is not unique".into());
+    }
+
+    let num_steps = ((total / max_step.0).ceil() as usize).max(1);
+    let mut points: Polyline = (0..=num_steps).map(|i| {
+        let t = i as f64 / num_steps as f64;
+        if total < 1.0e-12 {
+            to_lonlat(p)
+        } else {
+            to_lonlat((p * ((1.0 - t) * total).sin() + q * (t * total).sin()) / total.sin())
+        }
+    }).collect();
+    points[0] = a;
+    *points.last_mut().unwrap() = b;
+
+    Ok(split_at_antimeridian(&points))
+}
+
+/// Returns the point at the fraction `t` (0-1) of the shorter great-circle arc from `a` to `b` (given as
+/// [longitude, latitude] in degrees); returns `b` if the points coincide or are antipodal.
+pub fn great_circle_point(a: [f64; 2], b: [f64; 2], t: f64) -> [f64; 2] {
+    let (p, q) = (to_xyz(a), to_xyz(b));
+    let total = angle(p, q);
+    if total < 1.0e-12 || std::f64::consts::PI - total < 1.0e-9 { return b; }
+
+    to_lonlat((p * ((1.0 - t) * total).sin() + q * (t * total).sin()) / total.sin())
+}
+
+/// Returns the Mercator ordinate (isometric latitude) of `latitude` (radians).
+fn isometric_latitude(latitude: f64) -> f64 {
+    (std::f64::consts::FRAC_PI_4 + latitude / 2.0).tan().ln()
+}
+
+/// Returns the differences of latitude, longitude (the shorter way around) and isometric latitude (radians)
+/// between `a` and `b` (given as [longitude, latitude] in degrees).
+fn rhumb_line_deltas(a: [f64; 2], b: [f64; 2]) -> (f64, f64, f64) {
+    let d_lat = (b[1] - a[1]).to_radians();
+    let d_lon = ((b[0] - a[0] + 180.0).rem_euclid(360.0) - 180.0).to_radians();
+    let d_psi = isometric_latitude(b[1].to_radians()) - isometric_latitude(a[1].to_radians());
+
+    (d_lat, d_lon, d_psi)
+}
+
+/// Returns the (angular) length of the shorter rhumb line (path of constant bearing) between points given as
+/// [longitude, latitude] in degrees.
+pub fn rhumb_line_distance(a: [f64; 2], b: [f64; 2]) -> cgmath::Rad<f64> {
+    let (d_lat, d_lon, d_psi) = rhumb_line_deltas(a, b);
+    // ratio of the north-south distance to the Mercator ordinate difference; on a parallel, the parallel's scale
+    let q = if d_psi.abs() > 1.0e-12 { d_lat / d_psi } else { a[1].to_radians().cos() };
+
+    cgmath::Rad((d_lat * d_lat + q * q * d_lon * d_lon).sqrt())
+}
+
+/// Returns the bearing (clockwise from north, 0°-360°) of the shorter rhumb line from `a` to `b`
+/// (given as [longitude, latitude] in degrees).
+pub fn rhumb_line_bearing(a: [f64; 2], b: [f64; 2]) -> cgmath::Deg<f64> {
+    let (_, d_lon, d_psi) = rhumb_line_deltas(a, b);
+
+    cgmath::Deg(d_lon.atan2(d_psi).to_degrees().rem_euclid(360.0))
+}
+
+/// Returns the shorter rhumb line from `a` to `b` (given as [longitude, latitude] in degrees) as vertices at most
+/// `max_step` apart; the line is split into separate lines where it crosses the antimeridian.
+pub fn rhumb_line_route(a: [f64; 2], b: [f64; 2], max_step: cgmath::Rad<f64>) -> Result<Vec<Polyline>, String> {
+    let (d_lat, d_lon, d_psi) = rhumb_line_deltas(a, b);
+    let at_pole = |p: [f64; 2]| p[1].abs() > 90.0 - 1.0e-9;
+    if d_lon.abs() > 1.0e-12 && (at_pole(a) || at_pole(b)) {
+        return Err("a rhumb line other than a meridian spirals around the pole without reaching it".into());
+    }
+
+    // the longitude difference is limited too, as it grows quickly near the poles
+    let num_steps = ((rhumb_line_distance(a, b).0.max(d_lon.abs()) / max_step.0).ceil() as usize).max(1);
+    let psi_a = isometric_latitude(a[1].to_radians());
+    let mut points: Polyline = (0..=num_steps).map(|i| {
+        let t = i as f64 / num_steps as f64;
+        let lat = a[1].to_radians() + t * d_lat;
+        // the longitude changes proportionally to the Mercator ordinate
+        let lon_fraction = if d_psi.abs() > 1.0e-12 && d_lon.abs() > 1.0e-12 {
+            (isometric_latitude(lat) - psi_a) / d_psi
+        } else {
+            t
+        };
+        let lon = (a[0] + lon_fraction * d_lon.to_degrees() + 180.0).rem_euclid(360.0) - 180.0;
+        [lon, lat.to_degrees()]
+    }).collect();
+    points[0] = a;
+    *points.last_mut().unwrap() = b;
+
+    Ok(split_at_antimeridian(&points))
+}
+
+/// Returns the small circle of points at angular `radius` from `center` (given as [longitude, latitude] in degrees)
+/// as vertices at most `max_step` apart; the circle is split into separate lines where it crosses the antimeridian.
+pub fn circle(center: [f64; 2], radius: cgmath::Rad<f64>, max_step: cgmath::Rad<f64>) -> Result<Vec<Polyline>, String> {
+    let radius = radius.0;
+    if !(radius > 0.0 && radius < std::f64::consts::PI) {
+        return Err("invalid radius".into());
+    }
+
+    let circumference = 2.0 * std::f64::consts::PI * radius.sin();
+    let num_vertices = ((circumference / max_step.0).ceil() as usize).max(MIN_CIRCLE_VERTICES);
+    let (lon, lat) = (center[0].to_radians(), center[1].to_radians());
+    let mut points: Polyline = (0..num_vertices).map(|i| {
+        let azimuth = 2.0 * std::f64::consts::PI * i as f64 / num_vertices as f64;
+        let sin_lat2 = lat.sin() * radius.cos() + lat.cos() * radius.sin() * azimuth.cos();
+        let lat2 = sin_lat2.max(-1.0).min(1.0).asin();
+        let lon2 = lon + (azimuth.sin() * radius.sin() * lat.cos()).atan2(radius.cos() - lat.sin() * sin_lat2);
+        [(lon2.to_degrees() + 180.0).rem_euclid(360.0) - 180.0, lat2.to_degrees()]
+    }).collect();
+    points.push(points[0]);
+
+    Ok(split_at_antimeridian(&points))
+}
+
+/// Splits a line (with vertices close to one another) into separate lines where it crosses the antimeridian.
+fn split_at_antimeridian(points: &[[f64; 2]]) -> Vec<Polyline> {
+    let mut lines = vec![vec![points[0]]];
+    for (prev, next) in points.iter().zip(points.iter().skip(1)) {
+        if (next[0] - prev[0]).abs() > 180.0 {
+            // the crossing's latitude, interpolated with the longitude difference taken across the antimeridian
+            let sign = prev[0].signum();
+            let to_edge = 180.0 - prev[0].abs();
+            let t = to_edge / (to_edge + 180.0 - next[0].abs());
+            let lat = prev[1] + t * (next[1] - prev[1]);
+            lines.last_mut().unwrap().push([sign * 180.0, lat]);
+            lines.push(vec![[-sign * 180.0, lat]]);
+        }
+        lines.last_mut().unwrap().push(*next);
+    }
+
+    lines
+}
+
+/// Returns true if `p` lies inside the closed `ring` (tested in longitude/latitude).
+fn in_ring(p: [f64; 2], ring: &[[f64; 2]]) -> bool {
+    let mut inside = false;
+    for (a, b) in ring.iter().zip(ring.iter().skip(1)) {
+        if (a[1] > p[1]) != (b[1] > p[1]) && p[0] < a[0] + (p[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0]) {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+fn is_closed(line: &Polyline) -> bool {
+    line.len() > 3 && line.first() == line.last()
+}
+
+/// Regular longitude/latitude grid on which fields are contoured.
+struct Grid {
+    min_lon: f64,
+    max_lon: f64,
+    min_lat: f64,
+    max_lat: f64,
+    /// Number of cells along longitude.
+    nx: usize,
+    /// Number of cells along latitude.
+    ny: usize
+}
+
+impl Grid {
+    /// Creates a grid covering the given ranges (degrees) with cells of approx. `step` (enlarged if needed to keep
+    /// the number of cells at most `MAX_GRID_CELLS`).
+    fn new([min_lon, max_lon]: [f64; 2], [min_lat, max_lat]: [f64; 2], step: f64) -> Grid {
+        let mut step = step;
+        let size = |step: f64| (
+            (((max_lon - min_lon) / step).ceil() as usize).max(1),
+            (((max_lat - min_lat) / step).ceil() as usize).max(1)
+        );
+        while size(step).0 * size(step).1 > MAX_GRID_CELLS { step *= 1.25; }
+        let (nx, ny) = size(step);
+
+        Grid{ min_lon, max_lon, min_lat, max_lat, nx, ny }
+    }
+
+    fn num_nodes(&self) -> usize { (self.nx + 1) * (self.ny + 1) }
+
+    fn node(&self, i: usize, j: usize) -> [f64; 2] {
+        [
+            self.min_lon + (self.max_lon - self.min_lon) * i as f64 / self.nx as f64,
+            self.min_lat + (self.max_lat - self.min_lat) * j as f64 / self.ny as f64
+        ]
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = [f64; 2]> + '_ {
+        (0..=self.ny).flat_map(move |j| (0..=self.nx).map(move |i| self.node(i, j)))
+    }
+
+    /// Returns the contour line(s) at value 0 of `values` given at nodes (in the order of `nodes`);
+    /// negative values are inside the contoured region.
+    fn contour(&self, values: &[f64]) -> Vec<Polyline> {
+        self.contour_cells(values, |_| true)
+    }
+
+    /// Returns the contour line(s) as `contour`, skipping cells for whose corners' values `include` returns false.
+    fn contour_cells(&self, values: &[f64], include: impl Fn([f64; 4]) -> bool) -> Vec<Polyline> {
+        let value = |i: usize, j: usize| values[j * (self.nx + 1) + i];
+
+        // marching squares; crossing points are computed per grid edge (with the edge's ends always in the same
+        // order), so segments of adjacent cells share ends exactly
+        let crossing = |e0: (usize, usize), e1: (usize, usize)| -> [f64; 2] {
+            let ((i0, j0), (i1, j1)) = if e0 < e1 { (e0, e1) } else { (e1, e0) };
+            let (v0, v1) = (value(i0, j0), value(i1, j1));
+            let t = v0 / (v0 - v1);
+            let (p0, p1) = (self.node(i0, j0), self.node(i1, j1));
+            [p0[0] + t * (p1[0] - p0[0]), p0[1] + t * (p1[1] - p0[1])]
+        };
+        let mut segments = vec![];
+        for j in 0..self.ny {
+            for i in 0..self.nx {
+                // corners in counter-clockwise order, starting at the bottom-left
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                if !include(corners.map(|(i, j)| value(i, j))) { continue; }
+                let crossings: Vec<[f64; 2]> = (0..4)
+                    .filter(|&k| (value(corners[k].0, corners[k].1) < 0.0)
+                        != (value(corners[(k + 1) % 4].0, corners[(k + 1) % 4].1) < 0.0))
+                    .map(|k| crossing(corners[k], corners[(k + 1) % 4]))
+                    .collect();
+                match crossings.len() {
+                    2 => segments.push([crossings[0], crossings[1]]),
+                    // saddle; pairing of crossings is chosen arbitrarily
+                    4 => {
+                        segments.push([crossings[0], crossings[1]]);
+                        segments.push([crossings[2], crossings[3]]);
+                    },
+                    _ => ()
+                }
+            }
+        }
+
+        join_segments(segments)
+    }
+}
+
+/// Returns isolines at each of `levels` of the field `value` (of longitude and latitude, degrees), sampled globally
+/// every `step` degrees. If `period` is given, values are cyclic (e.g. angles in degrees with a period of 360):
+/// they are compared with levels modulo the period, and cells where they wrap around are skipped.
+pub fn isolines(
+    value: impl Fn([f64; 2]) -> f64,
+    levels: &[f64],
+    step: f64,
+    period: Option<f64>
+) -> Vec<Vec<Polyline>> {
+    let grid = Grid::new([-180.0, 180.0], [-90.0, 90.0], step);
+    let values: Vec<f64> = grid.nodes().map(value).collect();
+
+    levels.iter().map(|level| match period {
+        Some(period) => {
+            // differences from the level in [-period/2, period/2)
+            let differences: Vec<f64> = values.iter()
+                .map(|v| (v - level + period / 2.0).rem_euclid(period) - period / 2.0)
+                .collect();
+            grid.contour_cells(&differences, |corners| {
+                let min = corners.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = corners.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                max - min < period / 2.0
+            })
+        },
+        None => grid.contour(&values.iter().map(|v| v - level).collect::<Vec<f64>>())
+    }).collect()
+}
+
+/// Returns great-circle segments of `lines` (a single-vertex line gives a zero-length segment).
+fn segments(lines: &[&Polyline]) -> Vec<(Vector3<f64>, Vector3<f64>)> {
+    lines.iter().flat_map(|line| {
+        let xyz: Vec<Vector3<f64>> = line.iter().map(|p| to_xyz(*p)).collect();
+        if xyz.len() == 1 {
+            vec![(xyz[0], xyz[0])]
+        } else {
+            xyz.iter().zip(xyz.iter().skip(1)).map(|(a, b)| (*a, *b)).collect()
+        }
+    }).collect()
+}
+
+/// Returns the angular distance of `p` from the nearest of `segments`.
+fn min_distance(p: [f64; 2], segments: &[(Vector3<f64>, Vector3<f64>)]) -> f64 {
+    let p = to_xyz(p);
+    segments.iter().map(|(a, b)| segment_distance(p, *a, *b)).fold(std::f64::consts::PI, f64::min)
+}
+
+/// Returns true if `p` is inside the region bounded by `rings` (i.e., inside an odd number of them).
+fn in_rings(p: [f64; 2], rings: &[&Polyline]) -> bool {
+    rings.iter().filter(|ring| in_ring(p, ring)).count() % 2 == 1
+}
+
+/// Returns the ranges of longitude and latitude (degrees) covered by `lines` extended by `extent` (degrees).
+fn bounding_box(lines: &[&Polyline], extent: f64) -> ([f64; 2], [f64; 2]) {
+    let points = || lines.iter().flat_map(|line| line.iter());
+    let min_lat = (points().map(|p| p[1]).fold(90.0, f64::min) - extent).max(-90.0);
+    let max_lat = (points().map(|p| p[1]).fold(-90.0, f64::max) + extent).min(90.0);
+    let max_abs_lat = min_lat.abs().max(max_lat.abs());
+    let lon_range = if max_abs_lat >= 89.0 {
+        [-180.0, 180.0]
+    } else {
+        let lon_extent = extent / max_abs_lat.to_radians().cos();
+        let min_lon = points().map(|p| p[0]).fold(180.0, f64::min) - lon_extent;
+        let max_lon = points().map(|p| p[0]).fold(-180.0, f64::max) + lon_extent;
+        if min_lon < -180.0 || max_lon > 180.0 { [-180.0, 180.0] } else { [min_lon, max_lon] }
+    };
+
+    (lon_range, [min_lat, max_lat])
+}
+
+/// Returns the boundary of the region within angular `distance` of `lines` (single-vertex lines are points;
+/// closed lines are polygon rings, i.e. points inside an odd number of them belong to the region).
+pub fn buffer(lines: &[Polyline], distance: cgmath::Rad<f64>) -> Result<Vec<Polyline>, String> {
+    let distance = distance.0;
+    if !(distance > 0.0 && distance < std::f64::consts::PI) {
+        return Err("invalid distance".into());
+    }
+    let lines: Vec<&Polyline> = lines.iter().filter(|line| !line.is_empty()).collect();
+    if lines.is_empty() {
+        return Err("no geometry to buffer".into());
+    }
+
+    let extent = distance.to_degrees();
+    let (lon_range, lat_range) = bounding_box(&lines, extent);
+    let grid = Grid::new(lon_range, lat_range, extent / CELLS_PER_DISTANCE);
+
+    let segments = segments(&lines);
+    if grid.num_nodes() * segments.len() > MAX_DISTANCE_COMPUTATIONS {
+        return Err(format!("too many line segments ({}); select fewer lines", segments.len()));
+    }
+    let rings: Vec<&Polyline> = lines.iter().copied().filter(|line| is_closed(line)).collect();
+
+    // distance field; values are relative to `distance`
+    let values: Vec<f64> = grid.nodes().map(|p| {
+        if in_rings(p, &rings) { -distance } else { min_distance(p, &segments) - distance }
+    }).collect();
+
+    let boundary = grid.contour(&values);
+    if boundary.is_empty() {
+        return Err("the buffer has no boundary".into());
+    }
+
+    Ok(boundary)
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum BooleanOp { Union, Intersection, Difference }
+
+impl BooleanOp {
+    pub const ALL: [BooleanOp; 3] = [BooleanOp::Union, BooleanOp::Intersection, BooleanOp::Difference];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BooleanOp::Union => "union",
+            BooleanOp::Intersection => "intersection",
+            BooleanOp::Difference => "difference"
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BooleanOp::Union => "∪",
+            BooleanOp::Intersection => "∩",
+            BooleanOp::Difference => "−"
+        }
+    }
+
+    fn apply(&self, a: bool, b: bool) -> bool {
+        match self {
+            BooleanOp::Union => a || b,
+            BooleanOp::Intersection => a && b,
+            BooleanOp::Difference => a && !b
+        }
+    }
+
+    /// Returns true if the boundary of the first region (if `of_first`) or of the second one belongs to the result's
+    /// boundary where it lies inside (if `inside_other`) or outside the other region.
+    fn keeps_boundary(&self, of_first: bool, inside_other: bool) -> bool {
+        if of_first {
+            self.apply(true, inside_other) != self.apply(false, inside_other)
+        } else {
+            self.apply(inside_other, true) != self.apply(inside_other, false)
+        }
+    }
+}
+
+/// Returns the crossing point of great-circle segments `a1`-`a2` and `b1`-`b2` (shorter arcs) together with
+/// its positions along both segments (from 0 at `a1`, `b1` to 1 at `a2`, `b2`; monotonic, but not proportional
+/// to the distance). Segments touching at a point cross if they do so after moving the point infinitesimally
+/// to the positive side of the other segment's great circle.
+fn segment_crossing(
+    a1: Vector3<f64>,
+    a2: Vector3<f64>,
+    b1: Vector3<f64>,
+    b2: Vector3<f64>
+) -> Option<(Vector3<f64>, f64, f64)> {
+    let (normal_a, normal_b) = (a1.cross(a2), b1.cross(b2));
+    let (side_a1, side_a2) = (normal_b.dot(a1), normal_b.dot(a2));
+    let (side_b1, side_b2) = (normal_a.dot(b1), normal_a.dot(b2));
+    if (side_a1 >= 0.0) == (side_a2 >= 0.0) || (side_b1 >= 0.0) == (side_b2 >= 0.0) {
+        return None;
+    }
+
+    // points where each segment crosses the other's great circle; they coincide or are antipodal
+    let on_a = (a1 * side_a2 - a2 * side_a1) * side_a2.signum();
+    let on_b = (b1 * side_b2 - b2 * side_b1) * side_b2.signum();
+    if on_a.dot(on_b) <= 0.0 {
+        return None;
+    }
+
+    Some((
+        on_a.normalize(),
+        side_a1.abs() / (side_a1.abs() + side_a2.abs()),
+        side_b1.abs() / (side_b1.abs() + side_b2.abs())
+    ))
+}
+
+/// Vertex of a polygon ring (or crossing of rings of two polygons) used by `boolean`.
+struct ClipNode {
+    lonlat: [f64; 2],
+    xyz: Vector3<f64>,
+    next: usize,
+    prev: usize,
+    /// For crossings: index of the crossing (the same for both polygons).
+    crossing: Option<usize>,
+    /// For crossings: whether the result's boundary continues along this polygon's ring after the node.
+    forward: bool,
+    visited: bool
+}
+
+/// Polygon whose rings are doubly linked lists of vertices and crossings with another polygon's rings.
+struct ClipPolygon {
+    nodes: Vec<ClipNode>,
+    /// For each ring: index of its first node and whether it is crossed by the other polygon.
+    rings: Vec<(usize, bool)>,
+    /// Index of the node of each crossing.
+    crossing_nodes: Vec<usize>
+}
+
+/// Ring of a polygon as unit vectors with [longitude, latitude] in degrees (without repeating the first vertex).
+type SphericalRing = Vec<(Vector3<f64>, [f64; 2])>;
+
+/// Crossing of an edge (between vertices `i` and `i + 1`) of ring `ring` of one polygon by another polygon's ring.
+struct EdgeCrossing {
+    ring: usize,
+    edge: usize,
+    /// Position along the edge.
+    position: f64,
+    /// Index of the crossing (the same for both polygons).
+    crossing: usize
+}
+
+impl ClipPolygon {
+    /// Creates a polygon of `rings` with the crossings of their edges by another polygon, whose points are `points`.
+    fn new(rings: &[SphericalRing], crossings: &mut [EdgeCrossing], points: &[Vector3<f64>]) -> ClipPolygon {
+        crossings.sort_by(|c1, c2| {
+            (c1.ring, c1.edge).cmp(&(c2.ring, c2.edge)).then(c1.position.partial_cmp(&c2.position).unwrap())
+        });
+
+        let mut nodes = vec![];
+        let mut ring_starts = vec![];
+        let mut crossing_nodes = vec![0; points.len()];
+        let mut crossing_iter = crossings.iter().peekable();
+        for (ring_idx, ring) in rings.iter().enumerate() {
+            let start = nodes.len();
+            let mut crossed = false;
+            for (edge_idx, (xyz, lonlat)) in ring.iter().enumerate() {
+                nodes.push(ClipNode{
+                    lonlat: *lonlat, xyz: *xyz, next: 0, prev: 0, crossing: None, forward: false, visited: false
+                });
+                while let Some(crossing) = crossing_iter.next_if(|c| c.ring == ring_idx && c.edge == edge_idx) {
+                    let xyz = points[crossing.crossing];
+                    crossing_nodes[crossing.crossing] = nodes.len();
+                    nodes.push(ClipNode{
+                        lonlat: to_lonlat(xyz),
+                        xyz,
+                        next: 0,
+                        prev: 0,
+                        crossing: Some(crossing.crossing),
+                        forward: false,
+                        visited: false
+                    });
+                    crossed = true;
+                }
+            }
+            let end = nodes.len();
+            for (idx, node) in nodes.iter_mut().enumerate().skip(start) {
+                node.next = if idx + 1 == end { start } else { idx + 1 };
+                node.prev = if idx == start { end - 1 } else { idx - 1 };
+            }
+            ring_starts.push((start, crossed));
+        }
+
+        ClipPolygon{ nodes, rings: ring_starts, crossing_nodes }
+    }
+
+    /// Marks the direction in which the result's boundary continues from each crossing.
+    fn mark_directions(&mut self, op: BooleanOp, is_first: bool, other: &[SphericalRing], outside: Vector3<f64>) {
+        for &(start, _) in &self.rings {
+            let mut inside_other = in_spherical_rings(self.nodes[start].xyz, other, outside);
+            let mut idx = start;
+            loop {
+                if self.nodes[idx].crossing.is_some() {
+                    inside_other = !inside_other;
+                    self.nodes[idx].forward = op.keeps_boundary(is_first, inside_other);
+                }
+                idx = self.nodes[idx].next;
+                if idx == start { break; }
+            }
+        }
+    }
+}
+
+/// Returns true if `p` is inside the region bounded by `rings` (i.e., inside an odd number of them), where
+/// `outside` is a point known to be outside of all rings.
+fn in_spherical_rings(p: Vector3<f64>, rings: &[SphericalRing], outside: Vector3<f64>) -> bool {
+    // the path from `p` to `outside` consists of two 90° segments, which keeps it well-defined for any `p`
+    let via = if p.cross(outside).magnitude2() > 1.0e-12 {
+        p.cross(outside).normalize()
+    } else {
+        let axis = if outside.x.abs() < 0.5 { Vector3::unit_x() } else { Vector3::unit_y() };
+        outside.cross(axis).normalize()
+    };
+
+    let mut inside = false;
+    for ring in rings {
+        for (i, (a, _)) in ring.iter().enumerate() {
+            let b = ring[(i + 1) % ring.len()].0;
+            for (p1, p2) in [(p, via), (via, outside)] {
+                if segment_crossing(p1, p2, *a, b).is_some() { inside = !inside; }
+            }
+        }
+    }
+
+    inside
+}
+
+/// Returns the boundary of the result of `op` applied to regions bounded by closed lines (polygon rings) of `a`
+/// and `b`; other lines are ignored. Edges are great-circle segments; the result consists of the polygons'
+/// vertices and the crossings of their edges (Greiner-Hormann algorithm on the sphere). Rings of each layer must not
+/// cross one another, and all rings have to lie within a hemisphere.
+pub fn boolean(op: BooleanOp, a: &[Polyline], b: &[Polyline]) -> Result<Vec<Polyline>, String> {
+    let to_spherical = |lines: &[Polyline]| -> Vec<SphericalRing> {
+        lines.iter()
+            .filter(|line| is_closed(line))
+            .map(|line| line[..line.len() - 1].iter().map(|p| (to_xyz(*p), *p)).collect())
+            .collect()
+    };
+    let (rings_a, rings_b) = (to_spherical(a), to_spherical(b));
+    if rings_a.is_empty() || rings_b.is_empty() {
+        return Err("both layers have to contain polygons (closed lines)".into());
+    }
+
+    // the point opposite to the mean of all vertices is outside of all rings if they lie within a hemisphere
+    let all_vertices = || rings_a.iter().chain(rings_b.iter()).flat_map(|ring| ring.iter().map(|(xyz, _)| *xyz));
+    let mean = all_vertices().fold(Vector3::new(0.0, 0.0, 0.0), |sum, xyz| sum + xyz);
+    if mean.magnitude2() < 1.0e-12 || all_vertices().any(|xyz| xyz.dot(mean) <= 0.0) {
+        return Err("the polygons have to lie within a hemisphere".into());
+    }
+    let outside = -mean.normalize();
+
+    let mut points = vec![];
+    let mut crossings_a = vec![];
+    let mut crossings_b = vec![];
+    for (ring_a_idx, ring_a) in rings_a.iter().enumerate() {
+        for edge_a in 0..ring_a.len() {
+            let (a1, a2) = (ring_a[edge_a].0, ring_a[(edge_a + 1) % ring_a.len()].0);
+            for (ring_b_idx, ring_b) in rings_b.iter().enumerate() {
+                for edge_b in 0..ring_b.len() {
+                    let (b1, b2) = (ring_b[edge_b].0, ring_b[(edge_b + 1) % ring_b.len()].0);
+                    if let Some((point, position_a, position_b)) = segment_crossing(a1, a2, b1, b2) {
+                        let crossing = points.len();
+                        points.push(point);
+                        crossings_a.push(
+                            EdgeCrossing{ ring: ring_a_idx, edge: edge_a, position: position_a, crossing }
+                        );
+                        crossings_b.push(
+                            EdgeCrossing{ ring: ring_b_idx, edge: edge_b, position: position_b, crossing }
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    let mut polygon_a = ClipPolygon::new(&rings_a, &mut crossings_a, &points);
+    let mut polygon_b = ClipPolygon::new(&rings_b, &mut crossings_b, &points);
+    polygon_a.mark_directions(op, true, &rings_b, outside);
+    polygon_b.mark_directions(op, false, &rings_a, outside);
+
+    let mut boundary = vec![];
+
+    // rings crossing the other polygon: follow the result's boundary, switching polygons at crossings
+    for crossing in 0..points.len() {
+        let start = polygon_a.crossing_nodes[crossing];
+        if polygon_a.nodes[start].visited { continue; }
+
+        let mut ring = vec![polygon_a.nodes[start].lonlat];
+        let (mut polygon, mut other) = (&mut polygon_a, &mut polygon_b);
+        let mut idx = start;
+        loop {
+            let neighbor = other.crossing_nodes[polygon.nodes[idx].crossing.unwrap()];
+            polygon.nodes[idx].visited = true;
+            other.nodes[neighbor].visited = true;
+
+            let forward = polygon.nodes[idx].forward;
+            loop {
+                idx = if forward { polygon.nodes[idx].next } else { polygon.nodes[idx].prev };
+                ring.push(polygon.nodes[idx].lonlat);
+                if polygon.nodes[idx].crossing.is_some() { break; }
+            }
+
+            idx = other.crossing_nodes[polygon.nodes[idx].crossing.unwrap()];
+            std::mem::swap(&mut polygon, &mut other);
+            if polygon.nodes[idx].visited { break; }
+        }
+        boundary.push(ring);
+    }
+
+    // rings not crossing the other polygon are either wholly on the result's boundary or not at all
+    let polygons = [(&polygon_a, true, &rings_a, &rings_b), (&polygon_b, false, &rings_b, &rings_a)];
+    for (polygon, is_first, rings, other) in polygons.iter().copied() {
+        for (ring, &(start, crossed)) in rings.iter().zip(polygon.rings.iter()) {
+            if !crossed && op.keeps_boundary(is_first, in_spherical_rings(polygon.nodes[start].xyz, other, outside)) {
+                let mut ring: Polyline = ring.iter().map(|(_, lonlat)| *lonlat).collect();
+                ring.push(ring[0]);
+                boundary.push(ring);
+            }
+        }
+    }
+
+    if boundary.is_empty() {
+        return Err("the result is empty".into());
+    }
+
+    Ok(boundary)
+}
+
+/// Joins segments with shared ends into polylines.
+fn join_segments(segments: Vec<[[f64; 2]; 2]>) -> Vec<Polyline> {
+    let key = |p: [f64; 2]| (p[0].to_bits(), p[1].to_bits());
+    let mut segments_at: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+    for (idx, segment) in segments.iter().enumerate() {
+        for end in segment {
+            segments_at.entry(key(*end)).or_default().push(idx);
+        }
+    }
+
+    let mut used = vec![false; segments.len()];
+    let next_segment = |p: [f64; 2], used: &mut [bool]| -> Option<usize> {
+        let idx = *segments_at.get(&key(p))?.iter().find(|idx| !used[**idx])?;
+        used[idx] = true;
+        Some(idx)
+    };
+    let other_end = |idx: usize, p: [f64; 2]| {
+        if key(segments[idx][0]) == key(p) { segments[idx][1] } else { segments[idx][0] }
+    };
+
+    let mut polylines = vec![];
+    for start in 0..segments.len() {
+        if used[start] { continue; }
+        used[start] = true;
+
+        let mut polyline: Polyline = segments[start].to_vec();
+        while let Some(idx) = next_segment(*polyline.last().unwrap(), &mut used) {
+            polyline.push(other_end(idx, *polyline.last().unwrap()));
+        }
+        polyline.reverse();
+        while let Some(idx) = next_segment(*polyline.last().unwrap(), &mut used) {
+            polyline.push(other_end(idx, *polyline.last().unwrap()));
+        }
+
+        polylines.push(polyline);
+    }
+
+    polylines
+}
+
+/// Rasterizes the region bounded by `rings` (points inside an odd number of them) into an equirectangular mask
+/// of `width`×`height` pixels (255: inside, 0: outside); pixels are tested at their centers.
+pub fn rasterize_rings(rings: &[Polyline], width: u32, height: u32) -> image::GrayImage {
+    let mut mask = image::GrayImage::new(width, height);
+    fill_rings(rings, width, height, |column, row| mask.put_pixel(column, row, image::Luma([255])));
+
+    mask
+}
+
+/// Calls `fill` with the column and row of every pixel of an equirectangular image of `width`×`height` pixels
+/// whose center lies inside the region bounded by `rings` (see `rasterize_rings`).
+pub fn fill_rings(rings: &[Polyline], width: u32, height: u32, mut fill: impl FnMut(u32, u32)) {
+    // continuous pixel coordinates, with pixel centers at integer values
+    let column = |lon: f64| (lon + 180.0) / 360.0 * width as f64 - 0.5;
+    let row = |lat: f64| (90.0 - lat) / 180.0 * height as f64 - 0.5;
+
+    // columns at which each row of pixel centers crosses the rings' edges
+    let mut crossings: Vec<Vec<f64>> = vec![vec![]; height as usize];
+    for ring in rings.iter().filter(|ring| ring.len() > 2) {
+        let closing_edge = if ring.first() != ring.last() { Some((ring.last().unwrap(), &ring[0])) } else { None };
+        for (a, b) in ring.iter().zip(ring.iter().skip(1)).chain(closing_edge) {
+            let (row_a, row_b) = (row(a[1]), row(b[1]));
+            if row_a == row_b { continue; }
+            let first_row = row_a.min(row_b).ceil().max(0.0) as usize;
+            let end_row = (row_a.max(row_b).ceil().max(0.0) as usize).min(height as usize);
+            for r in first_row..end_row {
+                let t = (r as f64 - row_a) / (row_b - row_a);
+                crossings[r].push(column(a[0] + t * (b[0] - a[0])));
+            }
+        }
+    }
+
+    for (r, row_crossings) in crossings.iter_mut().enumerate() {
+        row_crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for span in row_crossings.chunks_exact(2) {
+            let first_column = span[0].ceil().max(0.0) as u32;
+            let end_column = (span[1].ceil().max(0.0) as u32).min(width);
+            for c in first_column..end_column {
+                fill(c, r as u32);
+            }
+        }
+    }
+}
+
+/// Simplifies `line` (Douglas-Peucker): omits vertices whose distance from the simplified line is less than
+/// `tolerance` (degrees). The first and last vertices are kept, so closed lines stay closed.
+pub fn simplify(line: &Polyline, tolerance: f64) -> Polyline {
+    if line.len() < 3 { return line.clone(); }
+
+    let mut kept = vec![false; line.len()];
+    kept[0] = true;
+    kept[line.len() - 1] = true;
+    let mut ranges = vec![(0, line.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let (a, b) = (line[first], line[last]);
+        let farthest = (first + 1..last)
+            .map(|idx| (idx, planar_segment_distance(line[idx], a, b)))
+            .max_by(|x, y| x.1.partial_cmp(&y.1).unwrap());
+        if let Some((idx, distance)) = farthest {
+            if distance >= tolerance {
+                kept[idx] = true;
+                ranges.push((first, idx));
+                ranges.push((idx, last));
+            }
+        }
+    }
+
+    line.iter().zip(kept).filter(|(_, kept)| *kept).map(|(p, _)| *p).collect()
+}
+
+/// Returns the distance (in the longitude/latitude plane) of `p` from the segment `a`-`b`.
+fn planar_segment_distance(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((p[0] - a[0]) * dx + (p[1] - a[1]) * dy) / length_sq).max(0.0).min(1.0)
+    } else {
+        0.0
+    };
+
+    ((p[0] - a[0] - t * dx).powi(2) + (p[1] - a[1] - t * dy).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Max. difference (degrees) between computed and expected vertices.
+    const TOLERANCE: f64 = 1.0e-9;
+
+    fn square(min_lon: f64, min_lat: f64, size: f64) -> Polyline {
+        let (max_lon, max_lat) = (min_lon + size, min_lat + size);
+        vec![[min_lon, min_lat], [max_lon, min_lat], [max_lon, max_lat], [min_lon, max_lat], [min_lon, min_lat]]
+    }
+
+    /// Latitude at longitude `lon` of the great circle through points at latitude `lat` and longitudes
+    /// `lon` ± `half_width`.
+    fn great_circle_latitude(lat: f64, half_width: f64) -> f64 {
+        (lat.to_radians().tan() / half_width.to_radians().cos()).atan().to_degrees()
+    }
+
+    /// Checks that `ring` is closed and consists of `expected` vertices (in any order and starting point).
+    fn assert_ring(ring: &Polyline, expected: &[[f64; 2]]) {
+        assert!(is_closed(ring), "{:?}", ring);
+        assert_eq!(expected.len() + 1, ring.len(), "{:?}", ring);
+        for vertex in expected {
+            assert!(
+                ring.iter().any(|p| (p[0] - vertex[0]).abs() < TOLERANCE && (p[1] - vertex[1]).abs() < TOLERANCE),
+                "{:?} not in {:?}", vertex, ring
+            );
+        }
+    }
+
+    #[test]
+    fn boolean_of_overlapping_squares_has_exact_crossings() {
+        // the squares' edges are great-circle segments, so the northern edges bulge towards the pole
+        let a = square(0.0, 0.0, 10.0);
+        let b = square(5.0, 5.0, 10.0);
+        let crossing_east = [10.0, great_circle_latitude(5.0, 5.0)];
+        let crossing_north = [5.0, great_circle_latitude(10.0, 5.0)];
+
+        let intersection = boolean(BooleanOp::Intersection, &[a.clone()], &[b.clone()]).unwrap();
+        assert_eq!(1, intersection.len());
+        assert_ring(&intersection[0], &[[5.0, 5.0], crossing_east, [10.0, 10.0], crossing_north]);
+
+        let union = boolean(BooleanOp::Union, &[a.clone()], &[b.clone()]).unwrap();
+        assert_eq!(1, union.len());
+        assert_ring(&union[0], &[
+            [0.0, 0.0], [10.0, 0.0], crossing_east, [15.0, 5.0], [15.0, 15.0], [5.0, 15.0], crossing_north, [0.0, 10.0]
+        ]);
+
+        let difference = boolean(BooleanOp::Difference, &[a], &[b]).unwrap();
+        assert_eq!(1, difference.len());
+        assert_ring(&difference[0], &[[0.0, 0.0], [10.0, 0.0], crossing_east, [5.0, 5.0], crossing_north, [0.0, 10.0]]);
+    }
+
+    #[test]
+    fn boolean_of_nested_squares_keeps_rings() {
+        let outer = square(-20.0, 30.0, 20.0);
+        let inner = square(-15.0, 35.0, 5.0);
+
+        let intersection = boolean(BooleanOp::Intersection, &[outer.clone()], &[inner.clone()]).unwrap();
+        assert_eq!(vec![inner.clone()], intersection);
+
+        let union = boolean(BooleanOp::Union, &[outer.clone()], &[inner.clone()]).unwrap();
+        assert_eq!(vec![outer.clone()], union);
+
+        // the inner ring becomes a hole
+        let difference = boolean(BooleanOp::Difference, &[outer.clone()], &[inner.clone()]).unwrap();
+        assert_eq!(vec![outer.clone(), inner.clone()], difference);
+
+        assert!(boolean(BooleanOp::Difference, &[inner], &[outer]).is_err());
+    }
+
+    #[test]
+    fn boolean_of_disjoint_squares() {
+        let a = square(0.0, 0.0, 10.0);
+        let b = square(20.0, 0.0, 10.0);
+
+        assert!(boolean(BooleanOp::Intersection, &[a.clone()], &[b.clone()]).is_err());
+        assert_eq!(vec![a.clone(), b.clone()], boolean(BooleanOp::Union, &[a.clone()], &[b.clone()]).unwrap());
+        assert_eq!(vec![a.clone()], boolean(BooleanOp::Difference, &[a], &[b]).unwrap());
+    }
+}
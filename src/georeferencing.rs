@@ -0,0 +1,168 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Warping of georeferenced images (e.g. scanned maps, weather charts) into equirectangular images of the regions
+// they cover.
+//
+// An image is georeferenced by the longitudes and latitudes of its corners, or by an affine geotransform (as used
+// by GDAL and world files). Both are expressed as a projective transformation (homography) from pixel coordinates
+// to longitude and latitude; for corners, it is the one mapping the image's rectangle exactly onto the given
+// quadrilateral. Each pixel of the region's image is sampled (bilinearly) at the position given by the inverse
+// transformation; pixels outside of the source image are transparent.
+//
+
+use cgmath::{Matrix3, SquareMatrix, Vector3};
+
+pub enum Georeference {
+    /// Longitude and latitude (degrees) of the top-left, top-right, bottom-right and bottom-left corners.
+    Corners([[f64; 2]; 4]),
+    /// Coefficients of the affine transformation (in the order used by GDAL), giving the longitude and latitude
+    /// (degrees) of the pixel position (x, y) (with (0, 0) at the top-left corner of the image):
+    ///
+    ///     longitude = t[0] + x * t[1] + y * t[2]
+    ///     latitude  = t[3] + x * t[4] + y * t[5]
+    ///
+    GeoTransform([f64; 6])
+}
+
+/// Equirectangular image of the region covered by a georeferenced image.
+pub struct WarpedImage {
+    pub image: image::RgbaImage,
+    /// Range of longitude (degrees).
+    pub lon_range: [f64; 2],
+    /// Range of latitude (degrees).
+    pub lat_range: [f64; 2]
+}
+
+/// Reads an affine geotransform from a world file (e.g. ".jgw", ".pgw", ".tfw"): 6 lines with the pixel width,
+/// the rotation terms, the pixel height and the longitude and latitude of the center of the top-left pixel.
+pub fn load_world_file(path: &str) -> Result<[f64; 6], String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let values: Vec<f64> = contents.split_whitespace().map(|value| value.parse::<f64>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    if values.len() != 6 {
+        return Err(format!("expected 6 values, found {}", values.len()));
+    }
+    let [a, d, b, e, c, f] = [values[0], values[1], values[2], values[3], values[4], values[5]];
+
+    // the world file refers to pixel centers
+    Ok([c - 0.5 * (a + b), a, b, f - 0.5 * (d + e), d, e])
+}
+
+/// Returns the path of the world file accompanying the image at `path` (if one exists), e.g. "map.jgw" or
+/// "map.jpgw" for "map.jpg".
+pub fn world_file_path(path: &str) -> Option<String> {
+    let path = std::path::Path::new(path);
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let short = format!("{}{}w", extension.chars().next()?, extension.chars().last()?);
+    [short, format!("{}w", extension), "wld".to_string()].iter()
+        .map(|extension| path.with_extension(extension))
+        .find(|candidate| candidate.is_file())
+        .map(|candidate| candidate.to_string_lossy().into_owned())
+}
+
+/// Warps `image` into an equirectangular image of the region it covers, with the resolution of `image` (at most
+/// `max_size` pixels wide and high).
+pub fn warp(image: &image::RgbaImage, georeference: &Georeference, max_size: u32) -> Result<WarpedImage, String> {
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let image_corners = [[0.0, 0.0], [width, 0.0], [width, height], [0.0, height]];
+    let to_lonlat = match georeference {
+        Georeference::Corners(corners) => homography(image_corners, *corners)?,
+        Georeference::GeoTransform(t) => Matrix3::new(t[1], t[4], 0.0, t[2], t[5], 0.0, t[0], t[3], 1.0)
+    };
+    let to_pixel = to_lonlat.invert().ok_or_else(|| "degenerate georeference".to_string())?;
+
+    let transform = |m: &Matrix3<f64>, [x, y]: [f64; 2]| {
+        let v = m * Vector3::new(x, y, 1.0);
+        [v.x / v.z, v.y / v.z]
+    };
+    let corners: Vec<[f64; 2]> = image_corners.iter()
+        .map(|p| transform(&to_lonlat, *p))
+        .collect();
+    let min = |idx: usize| corners.iter().map(|p| p[idx]).fold(f64::INFINITY, f64::min);
+    let max = |idx: usize| corners.iter().map(|p| p[idx]).fold(f64::NEG_INFINITY, f64::max);
+    let (lon_range, lat_range) = ([min(0), max(0)], [min(1), max(1)]);
+    if lon_range[0] < -180.0 || lon_range[1] > 180.0 || lat_range[0] < -90.0 || lat_range[1] > 90.0 {
+        return Err("the image has to lie within longitudes -180..180 and latitudes -90..90".into());
+    }
+    if !(lon_range[1] > lon_range[0] && lat_range[1] > lat_range[0]) {
+        return Err("the image covers an empty region".into());
+    }
+
+    // about as many pixels per degree as in the source image
+    let pixels_per_degree = width.max(height) / (lon_range[1] - lon_range[0]).max(lat_range[1] - lat_range[0]);
+    let size = |degrees: f64| ((degrees * pixels_per_degree).ceil() as u32).max(1).min(max_size);
+    let (target_width, target_height) = (size(lon_range[1] - lon_range[0]), size(lat_range[1] - lat_range[0]));
+
+    let mut warped = image::RgbaImage::new(target_width, target_height);
+    for (column, row, pixel) in warped.enumerate_pixels_mut() {
+        let lon = lon_range[0] + (column as f64 + 0.5) / target_width as f64 * (lon_range[1] - lon_range[0]);
+        let lat = lat_range[1] - (row as f64 + 0.5) / target_height as f64 * (lat_range[1] - lat_range[0]);
+        if let Some(color) = sample(image, transform(&to_pixel, [lon, lat])) {
+            *pixel = color;
+        }
+    }
+
+    Ok(WarpedImage{ image: warped, lon_range, lat_range })
+}
+
+/// Returns the color of `image` at `position` (pixel coordinates, with pixel centers at half-integers), bilinearly
+/// interpolated; `None` outside of the image.
+fn sample(image: &image::RgbaImage, [x, y]: [f64; 2]) -> Option<image::Rgba<u8>> {
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    if !(x >= 0.0 && x < width && y >= 0.0 && y < height) { return None; }
+
+    let (x, y) = ((x - 0.5).max(0.0).min(width - 1.0), (y - 0.5).max(0.0).min(height - 1.0));
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(image.width() - 1), (y0 + 1).min(image.height() - 1));
+    let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+
+    let mut color = [0u8; 4];
+    for (channel, value) in color.iter_mut().enumerate() {
+        let c = |x: u32, y: u32| image.get_pixel(x, y)[channel] as f64;
+        let top = c(x0, y0) * (1.0 - tx) + c(x1, y0) * tx;
+        let bottom = c(x0, y1) * (1.0 - tx) + c(x1, y1) * tx;
+        *value = (top * (1.0 - ty) + bottom * ty).round() as u8;
+    }
+
+    Some(image::Rgba(color))
+}
+
+/// Returns the projective transformation (as a matrix multiplying column vectors (x, y, 1)) mapping each of
+/// `from` to the corresponding point of `to`.
+fn homography(from: [[f64; 2]; 4], to: [[f64; 2]; 4]) -> Result<Matrix3<f64>, String> {
+    // unknowns h0..h7 (h8 = 1): u = (h0 x + h1 y + h2) / (h6 x + h7 y + 1), v = (h3 x + h4 y + h5) / (...)
+    let mut system = [[0.0; 9]; 8];
+    for (idx, ([x, y], [u, v])) in from.iter().zip(to.iter()).enumerate() {
+        system[2 * idx] = [*x, *y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, *u];
+        system[2 * idx + 1] = [0.0, 0.0, 0.0, *x, *y, 1.0, -v * x, -v * y, *v];
+    }
+
+    // Gaussian elimination with partial pivoting
+    for col in 0..8 {
+        let pivot = (col..8).max_by(|a, b| system[*a][col].abs().partial_cmp(&system[*b][col].abs()).unwrap()).unwrap();
+        if system[pivot][col].abs() < 1.0e-12 {
+            return Err("the corners do not form a quadrilateral".into());
+        }
+        system.swap(col, pivot);
+        let pivot_row = system[col];
+        for (row, equation) in system.iter_mut().enumerate() {
+            if row == col { continue; }
+            let factor = equation[col] / pivot_row[col];
+            for (value, pivot_value) in equation.iter_mut().zip(pivot_row.iter()).skip(col) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+    let h: Vec<f64> = (0..8).map(|row| system[row][8] / system[row][row]).collect();
+
+    // cgmath matrices are column-major
+    Ok(Matrix3::new(h[0], h[3], h[6], h[1], h[4], h[7], h[2], h[5], 1.0))
+}
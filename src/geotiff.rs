@@ -0,0 +1,432 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Loading of GeoTIFF rasters and their reprojection onto the (equirectangular) globe texture, and saving of images
+// in projected coordinate reference systems.
+//
+// The raster's georeferencing is read from the GeoTIFF tags (ModelTransformation, or ModelTiepoint
+// and ModelPixelScale) and its coordinate reference system from the GeoKey directory. Supported are:
+// geographic (longitude/latitude in degrees), Web Mercator (EPSG:3857, 3785) and UTM zones
+// (WGS84: EPSG:326xx, 327xx; NAD83: EPSG:269xx, treated as WGS84).
+//
+// Pixels equal to the GDAL_NODATA value or with zero alpha are transparent. 16-bit samples are
+// scaled to 8 bits; floating-point samples are stretched to the range of the raster's values.
+//
+// Saved images are RGBA; their CRS is user-defined, described by ESRI WKT stored in the citation GeoKeys as
+// an "ESRI PE String" (as done by GDAL for CRSs without GeoTIFF codes).
+//
+
+use crate::projection::{
+    transverse_mercator, transverse_mercator_inverse, WGS84_ECCENTRICITY, WGS84_SEMI_MAJOR_AXIS
+};
+use std::f64::consts::PI;
+
+const TAG_MODEL_TRANSFORMATION: u16 = 34264;
+const TAG_GDAL_NODATA: u16 = 42113;
+const TAG_GEO_ASCII_PARAMS: u16 = 34737;
+
+const GEO_KEY_MODEL_TYPE: u16 = 1024;
+const GEO_KEY_RASTER_TYPE: u16 = 1025;
+const GEO_KEY_CITATION: u16 = 1026;
+const GEO_KEY_PROJECTED_CS_TYPE: u16 = 3072;
+const GEO_KEY_PCS_CITATION: u16 = 3073;
+const GEO_KEY_PROJ_LINEAR_UNITS: u16 = 3076;
+
+const MODEL_TYPE_PROJECTED: u16 = 1;
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+const RASTER_PIXEL_IS_AREA: u16 = 1;
+const RASTER_PIXEL_IS_POINT: u16 = 2;
+const USER_DEFINED: u16 = 32767;
+const LINEAR_UNIT_METER: u16 = 9001;
+
+/// Value of the TIFF ExtraSamples tag denoting unassociated alpha.
+const EXTRA_SAMPLE_UNASSOCIATED_ALPHA: u16 = 2;
+
+const UTM_SCALE_FACTOR: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500_000.0;
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+
+/// Number of points per raster edge used to find the raster's longitude/latitude extent.
+const NUM_EDGE_SAMPLES: usize = 256;
+
+#[derive(Copy, Clone)]
+enum Crs {
+    Geographic,
+    WebMercator,
+    Utm{ zone: u32, south: bool }
+}
+
+impl Crs {
+    fn from_epsg(code: u16) -> Result<Crs, String> {
+        match code {
+            3857 | 3785 => Ok(Crs::WebMercator),
+            32601..=32660 => Ok(Crs::Utm{ zone: (code - 32600) as u32, south: false }),
+            32701..=32760 => Ok(Crs::Utm{ zone: (code - 32700) as u32, south: true }),
+            26901..=26923 => Ok(Crs::Utm{ zone: (code - 26900) as u32, south: false }),
+            _ => Err(format!("unsupported projected coordinate system (EPSG:{})", code))
+        }
+    }
+
+    fn utm_central_meridian(zone: u32) -> f64 { (-183.0 + 6.0 * zone as f64).to_radians() }
+
+    /// Returns model coordinates of the given point (radians); `None` if it cannot be projected.
+    fn forward(&self, lon: f64, lat: f64) -> Option<[f64; 2]> {
+        match *self {
+            Crs::Geographic => Some([lon.to_degrees(), lat.to_degrees()]),
+
+            Crs::WebMercator => if lat.abs() < PI / 2.0 {
+                Some([WGS84_SEMI_MAJOR_AXIS * lon, WGS84_SEMI_MAJOR_AXIS * (PI / 4.0 + lat / 2.0).tan().ln()])
+            } else {
+                None
+            },
+
+            Crs::Utm{ zone, south } => {
+                let lon = wrap(lon - Crs::utm_central_meridian(zone));
+                let p = transverse_mercator(lon, lat, WGS84_ECCENTRICITY)?;
+                let scale = UTM_SCALE_FACTOR * WGS84_SEMI_MAJOR_AXIS;
+                let false_northing = if south { UTM_FALSE_NORTHING_SOUTH } else { 0.0 };
+                Some([UTM_FALSE_EASTING + scale * p.x, false_northing + scale * p.y])
+            }
+        }
+    }
+
+    /// Returns (longitude, latitude) in radians of the given model coordinates.
+    fn inverse(&self, [x, y]: [f64; 2]) -> (f64, f64) {
+        match *self {
+            Crs::Geographic => (x.to_radians(), y.to_radians()),
+
+            Crs::WebMercator => (
+                x / WGS84_SEMI_MAJOR_AXIS,
+                2.0 * (y / WGS84_SEMI_MAJOR_AXIS).exp().atan() - PI / 2.0
+            ),
+
+            Crs::Utm{ zone, south } => {
+                let scale = UTM_SCALE_FACTOR * WGS84_SEMI_MAJOR_AXIS;
+                let false_northing = if south { UTM_FALSE_NORTHING_SOUTH } else { 0.0 };
+                let (lon, lat) = transverse_mercator_inverse(
+                    (x - UTM_FALSE_EASTING) / scale, (y - false_northing) / scale, WGS84_ECCENTRICITY
+                );
+                (wrap(lon + Crs::utm_central_meridian(zone)), lat)
+            }
+        }
+    }
+}
+
+/// Wraps the longitude (radians) to [-π, π).
+fn wrap(lon: f64) -> f64 {
+    (lon + PI).rem_euclid(2.0 * PI) - PI
+}
+
+/// Affine transformation from raster coordinates (pixel corners at integer values) to model coordinates:
+/// x = t[0]·i + t[1]·j + t[2], y = t[3]·i + t[4]·j + t[5].
+#[derive(Copy, Clone)]
+struct GeoTransform([f64; 6]);
+
+impl GeoTransform {
+    fn apply(&self, i: f64, j: f64) -> [f64; 2] {
+        let t = &self.0;
+        [t[0] * i + t[1] * j + t[2], t[3] * i + t[4] * j + t[5]]
+    }
+
+    fn inverse(&self) -> Result<GeoTransform, String> {
+        let t = &self.0;
+        let det = t[0] * t[4] - t[1] * t[3];
+        if det == 0.0 || !det.is_finite() {
+            return Err("degenerate raster transformation".into());
+        }
+
+        Ok(GeoTransform([
+            t[4] / det, -t[1] / det, (t[1] * t[5] - t[4] * t[2]) / det,
+            -t[3] / det, t[0] / det, (t[3] * t[2] - t[0] * t[5]) / det
+        ]))
+    }
+}
+
+/// Georeferenced raster.
+pub struct GeoRaster {
+    image: image::RgbaImage,
+    crs: Crs,
+    /// From raster to model coordinates.
+    transform: GeoTransform,
+    /// From model to raster coordinates.
+    inv_transform: GeoTransform
+}
+
+/// Loads the GeoTIFF file at `path`.
+pub fn load(path: &str) -> Result<GeoRaster, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut decoder = tiff::decoder::Decoder::new(std::io::BufReader::new(file)).map_err(|e| e.to_string())?;
+
+    let geo_keys = decoder.find_tag(tiff::tags::Tag::GeoKeyDirectoryTag)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "not a GeoTIFF file (no GeoKey directory)".to_string())?
+        .into_u16_vec()
+        .map_err(|e| e.to_string())?;
+    let geo_key = |id: u16| -> Option<u16> {
+        // header of 4 values, then entries: key id, tag location (0 for values stored in the entry), count, value
+        geo_keys.get(4..)?.chunks_exact(4).find(|entry| entry[0] == id && entry[1] == 0).map(|entry| entry[3])
+    };
+
+    let crs = match geo_key(GEO_KEY_MODEL_TYPE) {
+        Some(MODEL_TYPE_GEOGRAPHIC) => Crs::Geographic,
+        Some(MODEL_TYPE_PROJECTED) => Crs::from_epsg(geo_key(GEO_KEY_PROJECTED_CS_TYPE)
+            .ok_or_else(|| "user-defined projections are not supported".to_string())?)?,
+        _ => return Err("unsupported model type".into())
+    };
+
+    let mut f64_tag = |tag: tiff::tags::Tag| -> Result<Option<Vec<f64>>, String> {
+        match decoder.find_tag(tag).map_err(|e| e.to_string())? {
+            Some(value) => Ok(Some(value.into_f64_vec().map_err(|e| e.to_string())?)),
+            None => Ok(None)
+        }
+    };
+    let model_transformation = f64_tag(tiff::tags::Tag::from_u16_exhaustive(TAG_MODEL_TRANSFORMATION))?;
+    let tiepoint = f64_tag(tiff::tags::Tag::ModelTiepointTag)?;
+    let pixel_scale = f64_tag(tiff::tags::Tag::ModelPixelScaleTag)?;
+    let mut transform = match (model_transformation, tiepoint, pixel_scale) {
+        (Some(m), _, _) if m.len() >= 8 => GeoTransform([m[0], m[1], m[3], m[4], m[5], m[7]]),
+
+        (_, Some(tp), Some(scale)) if tp.len() >= 6 && scale.len() >= 2 => GeoTransform([
+            scale[0], 0.0, tp[3] - tp[0] * scale[0],
+            0.0, -scale[1], tp[4] + tp[1] * scale[1]
+        ]),
+
+        _ => return Err("missing or unsupported raster georeferencing".into())
+    };
+    if geo_key(GEO_KEY_RASTER_TYPE) == Some(RASTER_PIXEL_IS_POINT) {
+        // raster coordinates refer to pixel centers
+        let t = &mut transform.0;
+        t[2] -= 0.5 * (t[0] + t[1]);
+        t[5] -= 0.5 * (t[3] + t[4]);
+    }
+
+    let no_data = match decoder.find_tag(tiff::tags::Tag::from_u16_exhaustive(TAG_GDAL_NODATA)) {
+        Ok(Some(value)) => value.into_string().ok().and_then(|s| s.trim_matches('\0').trim().parse::<f64>().ok()),
+        _ => None
+    };
+
+    let image = decode_image(&mut decoder, no_data)?;
+    let inv_transform = transform.inverse()?;
+
+    Ok(GeoRaster{ image, crs, transform, inv_transform })
+}
+
+/// Saves `image` as a GeoTIFF file in the projected CRS described by `crs_wkt` (ESRI WKT); `top_left` are
+/// the coordinates (meters) of the image's top-left corner and `pixel_size` is the size (meters) of its pixels.
+pub fn save(
+    path: &str,
+    image: &image::RgbaImage,
+    top_left: [f64; 2],
+    pixel_size: f64,
+    crs_wkt: &str
+) -> Result<(), String> {
+    let citation = format!("ESRI PE String = {}|", crs_wkt);
+    let citation_len = citation.len() as u16;
+    let ascii_params = format!("{}{}", citation, citation);
+    // header (version 1.1.0, number of keys), then entries: key id, tag location, count, value or offset
+    let geo_keys: [u16; 28] = [
+        1, 1, 0, 6,
+        GEO_KEY_MODEL_TYPE, 0, 1, MODEL_TYPE_PROJECTED,
+        GEO_KEY_RASTER_TYPE, 0, 1, RASTER_PIXEL_IS_AREA,
+        GEO_KEY_CITATION, TAG_GEO_ASCII_PARAMS, citation_len, 0,
+        GEO_KEY_PROJECTED_CS_TYPE, 0, 1, USER_DEFINED,
+        GEO_KEY_PCS_CITATION, TAG_GEO_ASCII_PARAMS, citation_len, citation_len,
+        GEO_KEY_PROJ_LINEAR_UNITS, 0, 1, LINEAR_UNIT_METER
+    ];
+
+    let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = tiff::encoder::TiffEncoder::new(std::io::BufWriter::new(file)).map_err(|e| e.to_string())?;
+    let mut image_encoder = encoder.new_image::<tiff::encoder::colortype::RGBA8>(image.width(), image.height())
+        .map_err(|e| e.to_string())?;
+    let to_string = |e: tiff::TiffError| e.to_string();
+    let directory = image_encoder.encoder();
+    directory.write_tag(tiff::tags::Tag::ExtraSamples, EXTRA_SAMPLE_UNASSOCIATED_ALPHA).map_err(to_string)?;
+    directory.write_tag(tiff::tags::Tag::ModelPixelScaleTag, &[pixel_size, pixel_size, 0.0][..]).map_err(to_string)?;
+    directory.write_tag(tiff::tags::Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, top_left[0], top_left[1], 0.0][..])
+        .map_err(to_string)?;
+    directory.write_tag(tiff::tags::Tag::GeoKeyDirectoryTag, &geo_keys[..]).map_err(to_string)?;
+    directory.write_tag(tiff::tags::Tag::from_u16_exhaustive(TAG_GEO_ASCII_PARAMS), ascii_params.as_str())
+        .map_err(to_string)?;
+
+    image_encoder.write_data(image.as_raw()).map_err(to_string)
+}
+
+fn decode_image<R: std::io::Read + std::io::Seek>(
+    decoder: &mut tiff::decoder::Decoder<R>,
+    no_data: Option<f64>
+) -> Result<image::RgbaImage, String> {
+    let (width, height) = decoder.dimensions().map_err(|e| e.to_string())?;
+    let (num_channels, has_alpha) = match decoder.colortype().map_err(|e| e.to_string())? {
+        tiff::ColorType::Gray(_) => (1, false),
+        tiff::ColorType::GrayA(_) => (2, true),
+        tiff::ColorType::RGB(_) => (3, false),
+        tiff::ColorType::RGBA(_) => (4, true),
+        other => return Err(format!("unsupported color type: {:?}", other))
+    };
+
+    // `max_value`: `None` for floating-point samples
+    let (samples, max_value): (Vec<f64>, Option<f64>) = match decoder.read_image().map_err(|e| e.to_string())? {
+        tiff::decoder::DecodingResult::U8(v) => (v.into_iter().map(f64::from).collect(), Some(255.0)),
+        tiff::decoder::DecodingResult::U16(v) => (v.into_iter().map(f64::from).collect(), Some(65535.0)),
+        tiff::decoder::DecodingResult::F32(v) => (v.into_iter().map(f64::from).collect(), None),
+        tiff::decoder::DecodingResult::F64(v) => (v, None),
+        _ => return Err("unsupported sample format".into())
+    };
+    let num_pixels = width as usize * height as usize;
+    if samples.len() < num_pixels * num_channels {
+        return Err("incomplete image data".into());
+    }
+
+    let is_valid = |value: f64| value.is_finite() && Some(value) != no_data;
+    let (min, max) = samples.iter().copied().filter(|v| is_valid(*v)).fold(
+        (f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(v), max.max(v))
+    );
+    let to_u8 = |value: f64| match max_value {
+        Some(max_value) => (value / max_value * 255.0).round() as u8,
+        None => ((value - min) / (max - min).max(f64::EPSILON) * 255.0).round() as u8
+    };
+
+    let num_color_channels = if has_alpha { num_channels - 1 } else { num_channels };
+    let mut image = image::RgbaImage::new(width, height);
+    for (pixel, values) in image.pixels_mut().zip(samples.chunks_exact(num_channels)) {
+        let opaque = values[..num_color_channels].iter().all(|v| is_valid(*v));
+        let alpha = if has_alpha { to_u8(values[num_channels - 1]) } else { 255 };
+        *pixel = if !opaque {
+            image::Rgba([0, 0, 0, 0])
+        } else if num_channels <= 2 {
+            let gray = to_u8(values[0]);
+            image::Rgba([gray, gray, gray, alpha])
+        } else {
+            image::Rgba([to_u8(values[0]), to_u8(values[1]), to_u8(values[2]), alpha])
+        };
+    }
+
+    Ok(image)
+}
+
+impl GeoRaster {
+    /// Returns the ranges of longitude and latitude (radians) covered by the raster.
+    fn extent(&self) -> ([f64; 2], [f64; 2]) {
+        let (w, h) = (self.image.width() as f64, self.image.height() as f64);
+        let edge_points = (0..=NUM_EDGE_SAMPLES).flat_map(|k| {
+            let t = k as f64 / NUM_EDGE_SAMPLES as f64;
+            vec![[t * w, 0.0], [t * w, h], [0.0, t * h], [w, t * h]]
+        });
+        let lonlat: Vec<(f64, f64)> = edge_points
+            .map(|[i, j]| self.crs.inverse(self.transform.apply(i, j)))
+            .collect();
+
+        let mut lat_range = [
+            lonlat.iter().map(|p| p.1).fold(PI / 2.0, f64::min),
+            lonlat.iter().map(|p| p.1).fold(-PI / 2.0, f64::max)
+        ];
+        let mut lon_range = [
+            lonlat.iter().map(|p| p.0).fold(f64::INFINITY, f64::min),
+            lonlat.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max)
+        ];
+        if lon_range[1] - lon_range[0] > PI || lon_range[0] < -PI || lon_range[1] > PI {
+            lon_range = [-PI, PI];
+        }
+        for pole in [-PI / 2.0, PI / 2.0] {
+            if self.raster_coords(0.0, pole * 0.999_999).is_some() {
+                lat_range = [lat_range[0].min(pole), lat_range[1].max(pole)];
+                lon_range = [-PI, PI];
+            }
+        }
+
+        (lon_range, lat_range)
+    }
+
+    /// Returns the raster coordinates of the given point (radians); `None` if it is outside the raster.
+    fn raster_coords(&self, lon: f64, lat: f64) -> Option<[f64; 2]> {
+        let lon = match self.crs {
+            // choose the longitude's representation closest to the raster's (which may use e.g. 0°-360°)
+            Crs::Geographic => {
+                let (w, h) = (self.image.width() as f64, self.image.height() as f64);
+                let center_lon = self.transform.apply(w / 2.0, h / 2.0)[0].to_radians();
+                center_lon + wrap(lon - center_lon)
+            },
+            _ => lon
+        };
+
+        let [x, y] = self.crs.forward(lon, lat)?;
+        let [i, j] = self.inv_transform.apply(x, y);
+        let (w, h) = (self.image.width() as f64, self.image.height() as f64);
+
+        if i >= 0.0 && j >= 0.0 && i <= w && j <= h { Some([i, j]) } else { None }
+    }
+
+    /// Returns the raster's color at the given point (radians), bilinearly interpolated between opaque pixels;
+    /// `None` if the point is outside the raster or in its transparent part.
+    fn sample(&self, lon: f64, lat: f64) -> Option<[u8; 3]> {
+        let [i, j] = self.raster_coords(lon, lat)?;
+        let (w, h) = (self.image.width(), self.image.height());
+
+        // coordinates relative to pixel centers
+        let (x, y) = (i - 0.5, j - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let mut color = [0.0; 3];
+        let mut total_weight = 0.0;
+        let neighbors = [
+            (0, 0, (1.0 - fx) * (1.0 - fy)), (1, 0, fx * (1.0 - fy)),
+            (0, 1, (1.0 - fx) * fy), (1, 1, fx * fy)
+        ];
+        for (dx, dy, weight) in neighbors {
+            let px = (x0 as i64 + dx).max(0).min(w as i64 - 1) as u32;
+            let py = (y0 as i64 + dy).max(0).min(h as i64 - 1) as u32;
+            let pixel = self.image.get_pixel(px, py);
+            if pixel[3] == 0 { continue; }
+            let weight = weight * pixel[3] as f64 / 255.0;
+            for (sum, value) in color.iter_mut().zip(pixel.0.iter()) { *sum += weight * *value as f64; }
+            total_weight += weight;
+        }
+        if total_weight < 0.5 { return None; }
+
+        Some([
+            (color[0] / total_weight).round() as u8,
+            (color[1] / total_weight).round() as u8,
+            (color[2] / total_weight).round() as u8
+        ])
+    }
+
+    /// Returns the approx. size (radians of latitude) of the raster's pixels.
+    pub fn pixel_size(&self) -> f64 {
+        let (lon_range, lat_range) = self.extent();
+        let lon_size = (lon_range[1] - lon_range[0]) * lat_range[0].abs().min(lat_range[1].abs()).cos();
+        (lon_size / self.image.width() as f64).min((lat_range[1] - lat_range[0]) / self.image.height() as f64)
+    }
+
+    /// Draws the raster onto the equirectangular `target` (spanning longitudes -180°..180° and latitudes
+    /// 90°..-90°, top row first). Returns the number of pixels drawn.
+    pub fn reproject(&self, target: &mut image::RgbaImage) -> usize {
+        let (width, height) = (target.width(), target.height());
+        let (lon_range, lat_range) = self.extent();
+
+        let row = |lat: f64| (PI / 2.0 - lat) / PI * height as f64;
+        let col = |lon: f64| (lon + PI) / (2.0 * PI) * width as f64;
+        let rows = (row(lat_range[1]).floor().max(0.0) as u32)..(row(lat_range[0]).ceil().min(height as f64) as u32);
+        let cols = (col(lon_range[0]).floor().max(0.0) as u32)..(col(lon_range[1]).ceil().min(width as f64) as u32);
+
+        let mut num_drawn = 0;
+        for r in rows {
+            let lat = PI / 2.0 - (r as f64 + 0.5) / height as f64 * PI;
+            for c in cols.clone() {
+                let lon = -PI + (c as f64 + 0.5) / width as f64 * 2.0 * PI;
+                if let Some([red, green, blue]) = self.sample(lon, lat) {
+                    target.put_pixel(c, r, image::Rgba([red, green, blue, 255]));
+                    num_drawn += 1;
+                }
+            }
+        }
+
+        num_drawn
+    }
+}
@@ -0,0 +1,85 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Boolean operations (union, intersection, difference) on polygons of vector layers.
+//
+
+use crate::data;
+use crate::geometry;
+use crate::kml;
+
+const BOOLEAN_OP_LINE_STYLE: data::LineStyle = data::LineStyle{ color: [0.55, 0.2, 0.8, 1.0], width: 1.0 };
+
+#[derive(Default)]
+pub struct BooleanOpState {
+    /// Indices of the operands in `ProgramData::vector_layers`.
+    layers: [usize; 2],
+    /// Index in `geometry::BooleanOp::ALL`.
+    op_idx: usize,
+    /// Result of the last operation.
+    status: Option<String>
+}
+
+/// Shows the "Boolean operation" dialog (opened if `open` is true).
+pub fn handle_boolean_op(
+    ui: &imgui::Ui,
+    state: &mut BooleanOpState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Boolean operation");
+    }
+    ui.popup_modal("Boolean operation").always_auto_resize(true).build(ui, || {
+        ui.text("Creates a vector layer with the boundary of the union, intersection or difference of regions");
+        ui.text("bounded by closed lines (polygons) of two vector layers.");
+        ui.text("Polygon edges are great-circle segments; all polygons have to lie within a hemisphere.");
+
+        let layer_names: Vec<&str> = program_data.vector_layers().iter().map(|layer| layer.name.as_str()).collect();
+        if layer_names.is_empty() {
+            ui.text_disabled("No vector layers loaded.");
+        } else {
+            let op_names: Vec<&str> = geometry::BooleanOp::ALL.iter().map(|op| op.name()).collect();
+            let _width = ui.push_item_width(300.0);
+            for layer in &mut state.layers { *layer = (*layer).min(layer_names.len() - 1); }
+            ui.combo_simple_string("first layer", &mut state.layers[0], &layer_names);
+            ui.combo_simple_string("operation", &mut state.op_idx, &op_names);
+            ui.combo_simple_string("second layer", &mut state.layers[1], &layer_names);
+        }
+
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if !layer_names.is_empty() && ui.button("Create") {
+            let op = geometry::BooleanOp::ALL[state.op_idx];
+            let [a, b] = state.layers.map(|idx| &program_data.vector_layers()[idx]);
+            let name = format!("{} {} {}", a.name, op.symbol(), b.name);
+            state.status = Some(match geometry::boolean(op, &a.polylines, &b.polylines) {
+                Ok(boundary) => {
+                    program_data.add_vector_layer(
+                        name.clone(),
+                        vec![kml::Placemark{ style: None, lines: boundary }],
+                        BOOLEAN_OP_LINE_STYLE,
+                        display
+                    );
+                    format!("Created {}.", name)
+                },
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,126 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Buffers: regions within a great-circle distance of a point or of lines.
+//
+
+use crate::data;
+use crate::geometry;
+use crate::kml;
+
+/// Default distance of the buffer tool (200 nautical miles, i.e. the extent of an exclusive economic zone) in km.
+const DEFAULT_BUFFER_DISTANCE: f64 = 370.4;
+
+const BUFFER_LINE_STYLE: data::LineStyle = data::LineStyle{ color: [0.9, 0.45, 0.0, 1.0], width: 1.0 };
+
+#[derive(Default)]
+pub struct BufferState {
+    /// 0: point, otherwise index of the vector layer plus 1.
+    source_idx: usize,
+    /// Number of the buffered line of the vector layer (starting from 1); 0: all lines.
+    line: i32,
+    /// Longitude and latitude of the buffered point (degrees).
+    point: [f64; 2],
+    /// Buffer distance (km).
+    distance: f64,
+    /// Result of the last operation.
+    status: Option<String>
+}
+
+impl BufferState {
+    pub fn new() -> BufferState {
+        BufferState{
+            distance: DEFAULT_BUFFER_DISTANCE,
+            ..Default::default()
+        }
+    }
+}
+
+/// Shows the "Buffer" dialog (opened if `open` is true).
+pub fn handle_buffer(
+    ui: &imgui::Ui,
+    state: &mut BufferState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Buffer");
+    }
+    ui.popup_modal("Buffer").always_auto_resize(true).build(ui, || {
+        let unit = program_data.distance_unit;
+
+        ui.text("Creates a vector layer with the boundary of the region within the given great-circle distance");
+        ui.text("of a point or of lines of a vector layer (closed lines are treated as polygons).");
+
+        let mut source_names = vec!["point".to_string()];
+        source_names.extend(program_data.vector_layers().iter().map(|layer| layer.name.clone()));
+        state.source_idx = state.source_idx.min(source_names.len() - 1);
+        let _width = ui.push_item_width(200.0);
+        ui.combo_simple_string("source", &mut state.source_idx, &source_names);
+        if state.source_idx == 0 {
+            imgui::Drag::new("longitude").range(-180.0, 180.0).speed(0.1).display_format("%.2f°")
+                .build(ui, &mut state.point[0]);
+            imgui::Drag::new("latitude").range(-90.0, 90.0).speed(0.1).display_format("%.2f°")
+                .build(ui, &mut state.point[1]);
+        } else {
+            let num_lines = program_data.vector_layers()[state.source_idx - 1].polylines.len();
+            if ui.input_int("line (0: all lines)", &mut state.line).build() {
+                state.line = state.line.max(0).min(num_lines as i32);
+            }
+        }
+        let mut distance = unit.from_km(state.distance);
+        if imgui::Drag::new("distance")
+            .range(unit.from_km(1.0), unit.from_km(10_000.0))
+            .speed(1.0)
+            .display_format(&format!("%.1f {}", unit.symbol()))
+            .build(ui, &mut distance) {
+            state.distance = unit.to_km(distance);
+        }
+
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Create") {
+            let (lines, source_name) = if state.source_idx == 0 {
+                (vec![vec![state.point]], format!("{:.2}°, {:.2}°", state.point[0], state.point[1]))
+            } else {
+                let layer = &program_data.vector_layers()[state.source_idx - 1];
+                if state.line == 0 {
+                    (layer.polylines.clone(), layer.name.clone())
+                } else {
+                    let line = (state.line as usize).min(layer.polylines.len()) - 1;
+                    (vec![layer.polylines[line].clone()], format!("{}, line {}", layer.name, line + 1))
+                }
+            };
+            state.status = Some(
+                match geometry::buffer(&lines, cgmath::Rad(state.distance / program_data.body().mean_radius)) {
+                    Ok(boundary) => {
+                        let name = format!("buffer {:.1} {} of {}", distance, unit.symbol(), source_name);
+                        program_data.add_vector_layer(
+                            name.clone(),
+                            vec![kml::Placemark{ style: None, lines: boundary }],
+                            BUFFER_LINE_STYLE,
+                            display
+                        );
+                        format!("Created {}.", name)
+                    },
+                    Err(e) => format!("Error: {}", e)
+                }
+            );
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,208 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Camera paths: keyframes of a view's orientation, zoom and roll at given times, recorded from the most recently
+// focused view. Between keyframes, the orientation is interpolated by spherical linear interpolation (slerp)
+// of quaternions, the zoom geometrically and the roll along the shorter way. A path can be previewed in the view
+// and exported by "File/Export frames...". Applying a path switches the view to free rotation.
+//
+
+use cgmath::{InnerSpace, Quaternion};
+use crate::data;
+use crate::views;
+
+/// Default interval (seconds) between consecutively recorded keyframes.
+const DEFAULT_KEYFRAME_INTERVAL: f32 = 2.0;
+
+#[derive(Copy, Clone)]
+pub struct Keyframe {
+    /// Seconds from the start of the path.
+    time: f64,
+    orientation: cgmath::Basis3<f64>,
+    zoom: f64,
+    roll: cgmath::Rad<f64>
+}
+
+#[derive(Default)]
+pub struct CameraPathState {
+    /// If true, the editor window is shown.
+    pub open: bool,
+    /// Sorted by time.
+    keyframes: Vec<Keyframe>,
+    /// Time (seconds) of the next recorded keyframe.
+    new_time: f32,
+    /// Unique id of the view being previewed and the current time of the preview.
+    preview: Option<(u32, f64)>
+}
+
+impl CameraPathState {
+    /// Returns the time (seconds) of the last keyframe.
+    pub fn duration(&self) -> f64 { self.keyframes.last().map_or(0.0, |keyframe| keyframe.time) }
+
+    /// Returns true if the path can be played back (has at least 2 keyframes).
+    pub fn is_playable(&self) -> bool { self.keyframes.len() >= 2 }
+
+    /// Sets the orientation, zoom and roll of `view` to those of the path at `time` (seconds; clamped to the path's
+    /// duration); does nothing if there are no keyframes.
+    pub fn apply(&self, time: f64, view: &mut views::ViewBase) {
+        let (first, last) = match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return
+        };
+
+        let keyframe = if time <= first.time {
+            *first
+        } else if time >= last.time {
+            *last
+        } else {
+            let next = self.keyframes.iter().position(|keyframe| keyframe.time > time).unwrap();
+            interpolate(&self.keyframes[next - 1], &self.keyframes[next], time)
+        };
+
+        view.set_orientation(keyframe.orientation);
+        view.zoom_by(keyframe.zoom / view.zoom());
+        view.set_roll(keyframe.roll);
+    }
+
+    /// Adds a keyframe with the current state of `view` at `time`, replacing a keyframe at the same time (if any).
+    fn record(&mut self, time: f64, view: &views::ViewBase) {
+        let keyframe = Keyframe{ time, orientation: *view.orientation(), zoom: view.zoom(), roll: view.roll() };
+        self.keyframes.retain(|k| k.time != time);
+        let idx = self.keyframes.iter().position(|k| k.time > time).unwrap_or(self.keyframes.len());
+        self.keyframes.insert(idx, keyframe);
+    }
+}
+
+fn interpolate(k1: &Keyframe, k2: &Keyframe, time: f64) -> Keyframe {
+    use std::f64::consts::PI;
+
+    let t = (time - k1.time) / (k2.time - k1.time);
+
+    let q1 = Quaternion::from(k1.orientation);
+    let mut q2 = Quaternion::from(k2.orientation);
+    // q and -q are the same rotation; choose the shorter arc
+    if q1.dot(q2) < 0.0 { q2 = -q2; }
+
+    let roll_difference = (k2.roll.0 - k1.roll.0 + PI).rem_euclid(2.0 * PI) - PI;
+
+    Keyframe{
+        time,
+        orientation: q1.slerp(q2, t).into(),
+        zoom: k1.zoom * (k2.zoom / k1.zoom).powf(t),
+        roll: cgmath::Rad(k1.roll.0 + t * roll_difference)
+    }
+}
+
+/// Shows the camera path editor (if open) and advances the preview (if running).
+pub fn handle_camera_path(
+    ui: &imgui::Ui,
+    state: &mut CameraPathState,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if let Some((view_id, time)) = state.preview {
+        let time = time + program_data.animation_clock.dt();
+        state.preview = match program_data.all_views_mut().find(|view| view.unique_id() == view_id) {
+            Some(view) => {
+                state.apply(time, view);
+                if time < state.duration() { Some((view_id, time)) } else { None }
+            },
+            None => None // closed by the user
+        };
+    }
+
+    if !state.open { return; }
+
+    let mut opened = true;
+    let mut view_request: Option<f64> = None;
+    let mut removed = None;
+    let mut record_clicked = false;
+
+    imgui::Window::new(ui, "Camera path")
+        .size([420.0, 300.0], imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            ui.text_wrapped("Keyframes of the orientation, zoom and roll of the most recently focused view. \
+Export the animation with \"File/Export frames...\" (\"camera path\").");
+            ui.separator();
+
+            let _width = ui.push_item_width(100.0);
+            imgui::Drag::new("time (s)##new_keyframe")
+                .range(0.0, 3600.0)
+                .speed(0.1)
+                .display_format("%.1f")
+                .build(ui, &mut state.new_time);
+            ui.same_line();
+            record_clicked = ui.button("Add keyframe");
+
+            for (idx, keyframe) in state.keyframes.iter().enumerate() {
+                if ui.small_button(&format!("Remove##keyframe_{}", idx)) {
+                    removed = Some(idx);
+                }
+                ui.same_line();
+                if ui.small_button(&format!("Show##keyframe_{}", idx)) {
+                    view_request = Some(keyframe.time);
+                }
+                ui.same_line();
+                ui.text(format!(
+                    "{:.1} s: zoom {:.2}, roll {:.1}°",
+                    keyframe.time, keyframe.zoom, cgmath::Deg::from(keyframe.roll).0
+                ));
+            }
+            if state.keyframes.is_empty() {
+                ui.text("No keyframes.");
+            }
+
+            ui.separator();
+            if state.is_playable() {
+                let mut time = state.preview.map_or(0.0, |(_, time)| time) as f32;
+                if imgui::Slider::new("##camera_path_time", 0.0, state.duration() as f32)
+                    .display_format("%.1f s")
+                    .build(ui, &mut time)
+                {
+                    view_request = Some(time as f64);
+                }
+                ui.same_line();
+            }
+            if state.preview.is_some() {
+                if ui.button("Stop") { state.preview = None; }
+            } else if ui.button("Preview") && state.is_playable() {
+                if let Some(view_id) = focused_view {
+                    state.preview = Some((view_id, 0.0));
+                }
+            }
+            ui.same_line();
+            if ui.button("Clear") {
+                state.keyframes.clear();
+                state.preview = None;
+                state.new_time = 0.0;
+            }
+        });
+
+    if !opened {
+        state.open = false;
+        state.preview = None;
+    }
+
+    let view = match focused_view.and_then(|id| program_data.all_views_mut().find(|view| view.unique_id() == id)) {
+        Some(view) => view,
+        None => return
+    };
+    if record_clicked {
+        state.record(state.new_time.max(0.0) as f64, view);
+        state.new_time = state.new_time.max(0.0) + DEFAULT_KEYFRAME_INTERVAL;
+    }
+    if let Some(idx) = removed {
+        state.keyframes.remove(idx);
+    }
+    if let Some(time) = view_request {
+        state.preview = None;
+        state.apply(time, view);
+    }
+}
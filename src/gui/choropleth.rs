@@ -0,0 +1,182 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Choropleth maps: polygons of a shapefile filled with colors of classes of an attribute, and their legend.
+//
+
+use crate::choropleth;
+use crate::data;
+use crate::scalar_field;
+use super::GRATICULE_LABEL_COLOR;
+
+/// Size (logical pixels) of a class's color in the legend of a choropleth map.
+const LEGEND_SWATCH_SIZE: f32 = 12.0;
+
+/// Distance (logical pixels) of the legend of a choropleth map from the view's corner, and the legend's padding.
+const LEGEND_MARGIN: f32 = 8.0;
+
+const LEGEND_BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+
+#[derive(Default)]
+pub struct ChoroplethState {
+    /// Shapefile of polygons.
+    path: String,
+    /// If true, the legend is shown in views.
+    pub legend: bool,
+    /// Result of the last load.
+    status: Option<String>
+}
+
+impl ChoroplethState {
+    pub fn new() -> ChoroplethState {
+        ChoroplethState{
+            legend: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Draws the legend of a choropleth map in the lower left corner of the view's image located at `image_pos`
+/// (in screen coordinates).
+pub fn draw_choropleth_legend(ui: &imgui::Ui, legend: &choropleth::Legend, image_pos: [f32; 2], image_size: [f32; 2]) {
+    let labels: Vec<String> = legend.classes.iter()
+        .map(|(range, _)| format!("{} - {}", format_legend_value(range[0]), format_legend_value(range[1])))
+        .collect();
+    let line_height = ui.text_line_height().max(LEGEND_SWATCH_SIZE);
+    let label_width = labels.iter().map(|label| ui.calc_text_size(label)[0]).fold(0.0, f32::max);
+    let width = (LEGEND_SWATCH_SIZE + LEGEND_MARGIN + label_width).max(ui.calc_text_size(&legend.field)[0]);
+    let size = [width + 2.0 * LEGEND_MARGIN, (labels.len() + 1) as f32 * line_height + 2.0 * LEGEND_MARGIN];
+    let origin = [image_pos[0] + LEGEND_MARGIN, image_pos[1] + image_size[1] - LEGEND_MARGIN - size[1]];
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect_intersect(
+        image_pos,
+        [image_pos[0] + image_size[0], image_pos[1] + image_size[1]],
+        || {
+            draw_list.add_rect(origin, [origin[0] + size[0], origin[1] + size[1]], LEGEND_BACKGROUND_COLOR)
+                .filled(true)
+                .build();
+            let x = origin[0] + LEGEND_MARGIN;
+            let mut y = origin[1] + LEGEND_MARGIN;
+            draw_list.add_text([x, y], GRATICULE_LABEL_COLOR, &legend.field);
+            for ((_, [r, g, b]), label) in legend.classes.iter().zip(&labels) {
+                y += line_height;
+                let color = [*r as f32 / 255.0, *g as f32 / 255.0, *b as f32 / 255.0, 1.0];
+                let swatch_y = y + (line_height - LEGEND_SWATCH_SIZE) / 2.0;
+                draw_list.add_rect([x, swatch_y], [x + LEGEND_SWATCH_SIZE, swatch_y + LEGEND_SWATCH_SIZE], color)
+                    .filled(true)
+                    .build();
+                draw_list.add_text([x + LEGEND_SWATCH_SIZE + LEGEND_MARGIN, y], GRATICULE_LABEL_COLOR, label);
+            }
+        }
+    );
+}
+
+/// Formats a class bound of a choropleth map with a precision depending on its magnitude.
+fn format_legend_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{:.0}", value)
+    } else {
+        // 3 significant digits, at most 5 decimals
+        let decimals = (2.0 - value.abs().log10().floor()).max(0.0).min(5.0) as usize;
+        format!("{:.*}", decimals, value)
+    }
+}
+
+/// Shows the "Choropleth" dialog (opened if `open` is true).
+pub fn handle_choropleth(
+    ui: &imgui::Ui,
+    state: &mut ChoroplethState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Choropleth");
+    }
+    ui.popup_modal("Choropleth").always_auto_resize(true).build(ui, || {
+        ui.text("Fills polygons of a shapefile with colors of classes of a numeric attribute (from the .dbf file)");
+        ui.text("in all views, e.g. to show population density of countries.");
+        ui.input_text("shapefile", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new().add_filter("Shapefiles", &["shp"]).pick_file() {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+
+        if let Some(choropleth) = program_data.choropleth() {
+            ui.separator();
+            let mut params = choropleth.params;
+            let mut changed = false;
+            let _width = ui.push_item_width(200.0);
+            changed |= ui.combo_simple_string("attribute", &mut params.field, program_data.choropleth_fields());
+            let classification_names: Vec<&str> =
+                choropleth::Classification::ALL.iter().map(|c| c.name()).collect();
+            let mut classification_idx =
+                choropleth::Classification::ALL.iter().position(|c| *c == params.classification).unwrap();
+            if ui.combo_simple_string("classification", &mut classification_idx, &classification_names) {
+                params.classification = choropleth::Classification::ALL[classification_idx];
+                changed = true;
+            }
+            let mut num_classes = params.num_classes as i32;
+            if imgui::Slider::new("classes", 2, choropleth::MAX_CLASSES as i32).build(ui, &mut num_classes) {
+                params.num_classes = num_classes.max(2) as usize;
+                changed = true;
+            }
+            let colormap_names: Vec<&str> = scalar_field::Colormap::ALL.iter().map(|c| c.name()).collect();
+            let mut colormap_idx = scalar_field::Colormap::ALL.iter().position(|c| *c == params.colormap).unwrap();
+            if ui.combo_simple_string("colormap", &mut colormap_idx, &colormap_names) {
+                params.colormap = scalar_field::Colormap::ALL[colormap_idx];
+                changed = true;
+            }
+            changed |= imgui::Slider::new("opacity", 0.0, 1.0).build(ui, &mut params.opacity);
+            ui.checkbox("show legend in views", &mut state.legend);
+
+            for (range, [r, g, b]) in &choropleth.legend.classes {
+                let color = [*r as f32 / 255.0, *g as f32 / 255.0, *b as f32 / 255.0, 1.0];
+                imgui::ColorButton::new(format!("##{}", range[0]), color)
+                    .size([LEGEND_SWATCH_SIZE, LEGEND_SWATCH_SIZE])
+                    .build(ui);
+                ui.same_line();
+                ui.text(format!("{} - {}", format_legend_value(range[0]), format_legend_value(range[1])));
+            }
+
+            if changed {
+                if let Err(e) = program_data.set_choropleth_params(params, display) {
+                    state.status = Some(format!("Error: {}", e));
+                }
+            }
+        }
+
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Load") {
+            state.status = Some(match program_data.load_choropleth(&state.path, display) {
+                Ok(()) => format!("Loaded {}.", state.path),
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if program_data.choropleth().is_some() {
+            if ui.button("Remove") {
+                program_data.remove_choropleth();
+                state.status = None;
+            }
+            ui.same_line();
+        }
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
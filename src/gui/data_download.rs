@@ -0,0 +1,70 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// First-run dialog downloading missing data files (see `crate::data_download`).
+//
+
+use crate::data_download::DataDownload;
+
+/// Shows the first-run dialog offering to download missing data files; returns true once the program can proceed
+/// (the files have been downloaded or the user chose to continue without them). Sets `run` to false if the user
+/// chose to quit.
+pub fn handle_data_download(ui: &imgui::Ui, download: &mut DataDownload, run: &mut bool) -> bool {
+    if download.poll() { return true; }
+
+    let mut proceed = false;
+    let display_size = ui.io().display_size;
+    imgui::Window::new(ui, "Missing data files")
+        .position([display_size[0] / 2.0, display_size[1] / 2.0], imgui::Condition::Always)
+        .position_pivot([0.5, 0.5])
+        .always_auto_resize(true)
+        .collapsible(false)
+        .movable(false)
+        .build(|| {
+            ui.text("The following data files were not found:");
+            for file in &download.files {
+                ui.bullet_text(format!("{}: {}", file.description, file.destination));
+            }
+            ui.text("They can be downloaded now (from the program's repository), or the program can");
+            ui.text("continue without them (with a placeholder texture and no coastline).");
+
+            if download.is_running() {
+                let (idx, received, total) = download.progress.unwrap_or((0, 0, None));
+                let megabytes = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+                let (fraction, text) = match total {
+                    Some(total) if total > 0 => (
+                        received as f32 / total as f32,
+                        format!("{:.1} of {:.1} MiB", megabytes(received), megabytes(total))
+                    ),
+                    _ => (0.0, format!("{:.1} MiB", megabytes(received)))
+                };
+                ui.text(format!("Downloading file {} of {}...", idx + 1, download.files.len()));
+                imgui::ProgressBar::new(fraction).size([400.0, 0.0]).overlay_text(&text).build(ui);
+            } else if let Some(Err(e)) = &download.result {
+                ui.text_wrapped(format!("Error: {}.", e));
+            }
+
+            ui.separator();
+            if !download.is_running() {
+                if ui.button(if download.result.is_some() { "Retry" } else { "Download" }) {
+                    download.start();
+                }
+                ui.same_line();
+                if ui.button("Continue without") {
+                    proceed = true;
+                }
+                ui.same_line();
+            }
+            if ui.button("Quit") {
+                *run = false;
+            }
+        });
+
+    proceed
+}
@@ -0,0 +1,122 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Shading of the night hemisphere and night lights.
+//
+
+use crate::data;
+use crate::solar;
+
+#[derive(Default)]
+pub struct DayNightState {
+    /// Year, month, day, hour, minute (UTC).
+    date_time: [i32; 5],
+    /// Error in `date_time` (if any).
+    error: Option<String>,
+    /// Image of night lights.
+    night_lights_path: String,
+    /// Result of the last load of night lights.
+    status: Option<String>
+}
+
+/// Shows the "Day and night" dialog (opened if `open` is true).
+pub fn handle_day_night(
+    ui: &imgui::Ui,
+    state: &mut DayNightState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        let t = program_data.night_time().unwrap_or_else(solar::UtcDateTime::now);
+        state.date_time = [t.year, t.month as i32, t.day as i32, t.hour as i32, t.minute as i32];
+        state.error = None;
+        state.status = None;
+        if state.night_lights_path.is_empty() {
+            state.night_lights_path = data::DEFAULT_NIGHT_LIGHTS_FILE.to_string();
+        }
+        ui.open_popup("Day and night");
+    }
+    ui.popup_modal("Day and night").always_auto_resize(true).build(ui, || {
+        ui.text("Shades the night hemisphere (with twilight) at the given date and time.");
+        let mut shown = program_data.night_time().is_some();
+        let mut changed = ui.checkbox("show night", &mut shown);
+
+        let _width = ui.push_item_width(100.0);
+        for (idx, label) in ["year", "month", "day", "hour", "minute"].iter().enumerate() {
+            if idx > 0 { ui.same_line(); }
+            changed |= ui.input_int(label, &mut state.date_time[idx]).build();
+        }
+        ui.same_line();
+        ui.text("UTC");
+        if ui.button("Now") {
+            let t = solar::UtcDateTime::now();
+            state.date_time = [t.year, t.month as i32, t.day as i32, t.hour as i32, t.minute as i32];
+            changed = true;
+        }
+
+        if changed {
+            let [year, month, day, hour, minute] = state.date_time;
+            let time = solar::UtcDateTime{
+                year,
+                month: month.max(0) as u32,
+                day: day.max(0) as u32,
+                hour: hour.max(0) as u32,
+                minute: minute.max(0) as u32,
+                second: 0
+            };
+            state.error = time.validate().err();
+            if state.error.is_none() {
+                program_data.set_night_time(if shown { Some(time) } else { None }, display);
+            } else if !shown {
+                program_data.set_night_time(None, display);
+            }
+        }
+        if let Some(time) = program_data.night_time() {
+            let (lon, lat) = solar::subsolar_point(&time);
+            ui.text(format!(
+                "Subsolar point: {:.1}° {}, {:.1}° {}",
+                lon.0.abs(), if lon.0 >= 0.0 { "E" } else { "W" }, lat.0.abs(), if lat.0 >= 0.0 { "N" } else { "S" }
+            ));
+        }
+        if let Some(error) = &state.error {
+            ui.text_wrapped(format!("Error: {}", error));
+        }
+
+        ui.separator();
+        ui.text("In the globe texture mode, an equirectangular image of night lights (e.g. NASA's Black Marble)");
+        ui.text("can be shown on the night side instead of darkening.");
+        ui.input_text("night lights", &mut state.night_lights_path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("Images", &["jpg", "jpeg", "png", "tif", "tiff", "bmp"])
+                .pick_file()
+            {
+                state.night_lights_path = path.to_string_lossy().into_owned();
+            }
+        }
+        if ui.button("Load night lights") {
+            state.status = Some(match program_data.load_night_lights(&state.night_lights_path, display) {
+                Ok(()) => format!("Loaded {}.", state.night_lights_path),
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        } else if program_data.has_night_lights() {
+            ui.text_disabled("Night lights loaded.");
+        }
+
+        ui.separator();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
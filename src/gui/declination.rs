@@ -0,0 +1,115 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Magnetic declination (isogonic lines) from the World Magnetic Model.
+//
+
+use crate::data;
+use crate::geomagnetism;
+use crate::solar;
+
+/// Default spacing (degrees of declination) of isogonic lines.
+const DEFAULT_DECLINATION_SPACING: f64 = 10.0;
+
+#[derive(Default)]
+pub struct DeclinationState {
+    /// WMM coefficient file.
+    path: String,
+    /// Decimal year.
+    year: f64,
+    /// Spacing (degrees) of isogonic lines.
+    spacing: f64,
+    /// Result of the last computation.
+    status: Option<String>
+}
+
+impl DeclinationState {
+    pub fn new() -> DeclinationState {
+        DeclinationState{
+            path: data::DEFAULT_WMM_FILE.to_string(),
+            year: geomagnetism::decimal_year(&solar::UtcDateTime::now()),
+            spacing: DEFAULT_DECLINATION_SPACING,
+            status: None
+        }
+    }
+}
+
+/// Shows the "Magnetic declination" dialog (opened if `open` is true).
+pub fn handle_declination(
+    ui: &imgui::Ui,
+    state: &mut DeclinationState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Magnetic declination");
+    }
+    ui.popup_modal("Magnetic declination").always_auto_resize(true).build(ui, || {
+        ui.text("Shows isogonic lines (of equal magnetic declination; east in red, west in blue, zero in green) and");
+        ui.text("the magnetic dip poles computed with the World Magnetic Model for the given decimal year.");
+        ui.text("The model's coefficient file (WMM.COF) can be obtained from");
+        ui.text("https://www.ncei.noaa.gov/products/world-magnetic-model.");
+        ui.input_text("WMM.COF file", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new().add_filter("WMM coefficients", &["cof", "COF"]).pick_file() {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+        let _width = ui.push_item_width(150.0);
+        imgui::Drag::new("year").range(1900.0, 2100.0).speed(0.01).display_format("%.2f").build(ui, &mut state.year);
+        imgui::Drag::new("spacing")
+            .range(1.0, 90.0)
+            .speed(0.2)
+            .display_format("%.0f°")
+            .build(ui, &mut state.spacing);
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Show") {
+            let result = geomagnetism::load(&state.path).and_then(|model| {
+                let poles = program_data.show_magnetic_declination(&model, state.year, state.spacing, display)?;
+                Ok((model, poles))
+            });
+            state.status = Some(match result {
+                Ok((model, poles)) => {
+                    let format_pole = |[lon, lat]: [f64; 2]| format!(
+                        "{:.2}° {}, {:.2}° {}",
+                        lat.abs(), if lat >= 0.0 { "N" } else { "S" }, lon.abs(), if lon >= 0.0 { "E" } else { "W" }
+                    );
+                    let mut status = format!(
+                        "{} (epoch {:.1}). North dip pole: {}; south dip pole: {}.",
+                        model.name, model.epoch, format_pole(poles[0]), format_pole(poles[1])
+                    );
+                    if !model.is_valid_at(state.year) {
+                        status += &format!(
+                            " Warning: the model is valid only from {:.1} to {:.1}.",
+                            model.epoch, model.epoch + geomagnetism::VALIDITY_YEARS
+                        );
+                    }
+                    status
+                },
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if ui.button("Remove") {
+            program_data.remove_magnetic_declination();
+            state.status = None;
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,89 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Earthquakes fetched from the USGS real-time feeds.
+//
+
+use crate::data;
+use crate::earthquakes;
+use crate::solar;
+
+#[derive(Default)]
+pub struct EarthquakesState {
+    /// Index in `earthquakes::Period::ALL`.
+    period_idx: usize,
+    /// Index in `earthquakes::MinMagnitude::ALL`.
+    min_magnitude_idx: usize,
+    /// Result of the last fetch.
+    status: Option<String>
+}
+
+impl EarthquakesState {
+    pub fn new() -> EarthquakesState {
+        EarthquakesState{
+            // M2.5+
+            min_magnitude_idx: 2,
+            ..Default::default()
+        }
+    }
+}
+
+/// Shows the result of the earthquakes fetch once it has completed.
+pub fn poll_earthquakes(state: &mut EarthquakesState, program_data: &mut data::ProgramData) {
+    if let Some(result) = program_data.poll_earthquakes() {
+        state.status = Some(match result {
+            Ok(num_earthquakes) => {
+                let t = solar::UtcDateTime::now();
+                format!("Received {} earthquakes at {:02}:{:02} UTC.", num_earthquakes, t.hour, t.minute)
+            },
+            Err(e) => format!("Failed to fetch earthquakes: {}", e)
+        });
+    }
+}
+
+/// Shows the "Earthquakes" dialog (opened if `open` is true).
+pub fn handle_earthquakes(
+    ui: &imgui::Ui,
+    state: &mut EarthquakesState,
+    open: bool,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        ui.open_popup("Earthquakes");
+    }
+    ui.popup_modal("Earthquakes").always_auto_resize(true).build(ui, || {
+        ui.text("Fetches earthquakes from the USGS real-time feeds (https://earthquake.usgs.gov) and shows their");
+        ui.text("epicenters in all views as a point layer (see \"File/Vector layers...\"), with symbols growing and");
+        ui.text("turning red with magnitude. Fetching again refreshes the layer.");
+        let _width = ui.push_item_width(150.0);
+        let period_names: Vec<&str> = earthquakes::Period::ALL.iter().map(|period| period.name()).collect();
+        ui.combo_simple_string("period", &mut state.period_idx, &period_names);
+        let magnitude_names: Vec<&str> =
+            earthquakes::MinMagnitude::ALL.iter().map(|magnitude| magnitude.name()).collect();
+        ui.combo_simple_string("magnitude", &mut state.min_magnitude_idx, &magnitude_names);
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if let Some(fetch) = program_data.earthquake_fetch() {
+            ui.text(format!("Fetching {}...", fetch.feed.url()));
+        } else if ui.button("Fetch") {
+            state.status = None;
+            program_data.fetch_earthquakes(earthquakes::Feed{
+                period: earthquakes::Period::ALL[state.period_idx],
+                min_magnitude: earthquakes::MinMagnitude::ALL[state.min_magnitude_idx]
+            });
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,366 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Export of consecutive frames of the focused view as PNG files, a video or an animated GIF.
+//
+
+use crate::data;
+use crate::export;
+use crate::geometry;
+use crate::views;
+use super::{camera_path, handle_point_input};
+
+const DEFAULT_FRAMES_DIR: &str = "frames";
+
+const DEFAULT_VIDEO_FILE: &str = "animation.mp4";
+
+const DEFAULT_GIF_FILE: &str = "animation.gif";
+
+const DEFAULT_FRAME_WIDTH: i32 = 1280;
+
+const DEFAULT_FRAME_HEIGHT: i32 = 720;
+
+const DEFAULT_FRAME_RATE: i32 = 30;
+
+/// Default duration (seconds of animation time) of exported frame sequences and videos; one revolution at
+/// `DEFAULT_FRAME_EXPORT_ROTATION`.
+const DEFAULT_FRAME_EXPORT_DURATION: f32 = 30.0;
+
+/// Default east-west rotation speed (degrees per second) of the view whose frames are exported.
+const DEFAULT_FRAME_EXPORT_ROTATION: f32 = 12.0;
+
+#[derive(Default)]
+pub struct FrameExportState {
+    format: FrameExportFormat,
+    /// Directory to save frames to (as PNG files).
+    path: String,
+    video_path: String,
+    gif_path: String,
+    width: i32,
+    height: i32,
+    /// Frames per second of animation time.
+    frame_rate: i32,
+    /// Seconds of animation time.
+    duration: f32,
+    motion: FrameMotion,
+    /// Longitude and latitude (degrees) of the center the exported view is rotated to (`FrameMotion::RotateTo`).
+    end_center: [f64; 2],
+    /// East-west rotation of the exported view (degrees per second of animation time).
+    rotation_speed: f32,
+    /// Export in progress (if any).
+    progress: Option<FrameExportProgress>,
+    /// Result of the last export.
+    status: Option<String>
+}
+
+impl FrameExportState {
+    pub fn new() -> FrameExportState {
+        FrameExportState{
+            path: DEFAULT_FRAMES_DIR.to_string(),
+            video_path: DEFAULT_VIDEO_FILE.to_string(),
+            gif_path: DEFAULT_GIF_FILE.to_string(),
+            width: DEFAULT_FRAME_WIDTH,
+            height: DEFAULT_FRAME_HEIGHT,
+            frame_rate: DEFAULT_FRAME_RATE,
+            duration: DEFAULT_FRAME_EXPORT_DURATION,
+            rotation_speed: DEFAULT_FRAME_EXPORT_ROTATION,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if frames are being exported.
+    pub fn is_exporting(&self) -> bool { self.progress.is_some() }
+
+    /// Returns the directory or file the frames are exported to.
+    fn output_path(&self) -> &str {
+        match self.format {
+            FrameExportFormat::Png => &self.path,
+            FrameExportFormat::Video => &self.video_path,
+            FrameExportFormat::Gif => &self.gif_path
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum FrameExportFormat { Png, Video, Gif }
+
+impl FrameExportFormat {
+    const ALL: [FrameExportFormat; 3] = [FrameExportFormat::Png, FrameExportFormat::Video, FrameExportFormat::Gif];
+
+    fn name(&self) -> &'static str {
+        match self {
+            FrameExportFormat::Png => "PNG files",
+            FrameExportFormat::Video => "MP4 video",
+            FrameExportFormat::Gif => "animated GIF"
+        }
+    }
+}
+
+impl Default for FrameExportFormat {
+    fn default() -> FrameExportFormat { FrameExportFormat::Png }
+}
+
+/// Motion of the exported view.
+#[derive(Copy, Clone, PartialEq)]
+enum FrameMotion {
+    /// East-west rotation at `FrameExportState::rotation_speed`.
+    Spin,
+    /// Rotation from the view's current center to `FrameExportState::end_center`.
+    RotateTo,
+    /// Playback of the camera path (see `camera_path`); the duration is that of the path.
+    CameraPath
+}
+
+impl FrameMotion {
+    const ALL: [FrameMotion; 3] = [FrameMotion::Spin, FrameMotion::RotateTo, FrameMotion::CameraPath];
+
+    fn name(&self) -> &'static str {
+        match self {
+            FrameMotion::Spin => "spin",
+            FrameMotion::RotateTo => "rotate to",
+            FrameMotion::CameraPath => "camera path"
+        }
+    }
+}
+
+impl Default for FrameMotion {
+    fn default() -> FrameMotion { FrameMotion::Spin }
+}
+
+/// Destination of exported frames.
+enum FrameSink {
+    /// PNG files in `FrameExportState::path`.
+    Files,
+    Video(export::VideoEncoder),
+    Gif(export::AnimatedGifEncoder)
+}
+
+struct FrameExportProgress {
+    view_id: u32,
+    next_frame: i32,
+    num_frames: i32,
+    /// Center (longitude and latitude in degrees) of the view when the export started.
+    start_center: [f64; 2],
+    sink: FrameSink
+}
+
+/// Saves the next frame of the frame export in progress (if any), then advances the exported view's rotation
+/// by one frame's step of animation time.
+pub fn export_frame(
+    state: &mut FrameExportState,
+    camera_path: &camera_path::CameraPathState,
+    program_data: &mut data::ProgramData
+) {
+    let progress = match &mut state.progress {
+        Some(progress) => progress,
+        None => return
+    };
+
+    let result = match program_data.all_views_mut().find(|view| view.unique_id() == progress.view_id) {
+        None => Err("the view has been closed".to_string()),
+        Some(view) => {
+            match state.motion {
+                FrameMotion::RotateTo => {
+                    // eased in and out
+                    let t = if progress.num_frames > 1 {
+                        progress.next_frame as f64 / (progress.num_frames - 1) as f64
+                    } else {
+                        1.0
+                    };
+                    let t = t * t * (3.0 - 2.0 * t);
+                    let [lon, lat] = geometry::great_circle_point(progress.start_center, state.end_center, t);
+                    view.center_on(cgmath::Deg(lon), cgmath::Deg(lat));
+                },
+                FrameMotion::CameraPath => {
+                    camera_path.apply(progress.next_frame as f64 / state.frame_rate as f64, view);
+                },
+                FrameMotion::Spin => ()
+            }
+            let image = view.render_frame(state.width as u32, state.height as u32, views::Layers::All);
+            if state.motion == FrameMotion::Spin {
+                view.rotate_ew(cgmath::Rad::from(cgmath::Deg(state.rotation_speed as f64 / state.frame_rate as f64)));
+            }
+            image.and_then(|image| match &mut progress.sink {
+                FrameSink::Files => {
+                    let path = std::path::Path::new(&state.path).join(format!("frame_{:05}.png", progress.next_frame));
+                    image.save(&path).map_err(|e| format!("{}: {}", path.to_string_lossy(), e))
+                },
+                FrameSink::Video(encoder) => encoder.add_frame(&image),
+                FrameSink::Gif(encoder) => encoder.add_frame(image)
+            })
+        }
+    };
+
+    progress.next_frame += 1;
+    let status = match result {
+        Err(e) => Some(format!("Error: {}.", e)),
+        Ok(()) if progress.next_frame >= progress.num_frames => Some(match state.format {
+            FrameExportFormat::Png => format!("Saved {} frames in {}.", progress.num_frames, state.path),
+            _ => format!("Saved {} frames to {}.", progress.num_frames, state.output_path())
+        }),
+        Ok(()) => None
+    };
+    if let Some(status) = status {
+        stop_frame_export(state, program_data, status);
+    }
+}
+
+/// Ends the frame export in progress (completing the video or GIF, if any).
+fn stop_frame_export(state: &mut FrameExportState, program_data: &mut data::ProgramData, status: String) {
+    let result = match state.progress.take().map(|progress| progress.sink) {
+        Some(FrameSink::Video(encoder)) => encoder.finish(),
+        _ => Ok(())
+    };
+    state.status = Some(match result {
+        Err(e) => format!("Error: {}.", e),
+        Ok(()) => status
+    });
+    program_data.animation_clock.set_fixed_step(None);
+}
+
+/// Shows the "Export frames" dialog (opened if `open` is true).
+pub fn handle_frame_export(
+    ui: &imgui::Ui,
+    state: &mut FrameExportState,
+    open: bool,
+    focused_view: Option<u32>,
+    camera_path: &camera_path::CameraPathState,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Export frames");
+    }
+    ui.popup_modal("Export frames").always_auto_resize(true).build(ui, || {
+        ui.text("Saves consecutive frames of the most recently focused view as PNG files, a video (encoded by ffmpeg,");
+        ui.text("which has to be installed) or an animated GIF. Animation time (of the projection tour and overlay");
+        ui.text("scripts) advances by exactly 1/(frame rate) per frame, regardless of how long rendering takes.");
+
+        if let Some(progress) = &state.progress {
+            ui.text(&format!(
+                "Exporting frame {} of {} to {}...",
+                progress.next_frame + 1,
+                progress.num_frames,
+                state.output_path()
+            ));
+            ui.separator();
+            if ui.button("Stop") {
+                stop_frame_export(state, program_data, "Stopped.".into());
+            }
+        } else {
+            for (idx, format) in FrameExportFormat::ALL.iter().enumerate() {
+                if idx > 0 { ui.same_line(); }
+                if ui.radio_button_bool(format.name(), state.format == *format) {
+                    state.format = *format;
+                }
+            }
+            match state.format {
+                FrameExportFormat::Png => ui.input_text("output directory", &mut state.path).build(),
+                FrameExportFormat::Video => ui.input_text("output file", &mut state.video_path).build(),
+                FrameExportFormat::Gif => ui.input_text("output file", &mut state.gif_path).build()
+            };
+            ui.input_int("width", &mut state.width).build();
+            ui.input_int("height", &mut state.height).build();
+            ui.input_int("frame rate (fps)", &mut state.frame_rate).build();
+            if state.format == FrameExportFormat::Gif && state.frame_rate > 0 && 100 % state.frame_rate != 0 {
+                ui.text_colored(
+                    [1.0, 0.5, 0.0, 1.0],
+                    "GIF frame durations are multiples of 1/100 s; use e.g. 10, 20, 25 or 50 fps."
+                );
+            }
+            if state.motion == FrameMotion::CameraPath {
+                state.duration = camera_path.duration() as f32;
+                ui.text(format!("duration: {:.1} s", state.duration));
+            } else {
+                imgui::Drag::new("duration (s)")
+                    .range(0.0, 3600.0)
+                    .speed(0.1)
+                    .display_format("%.1f")
+                    .build(ui, &mut state.duration);
+            }
+
+            for (idx, motion) in FrameMotion::ALL.iter().enumerate() {
+                if idx > 0 { ui.same_line(); }
+                if ui.radio_button_bool(motion.name(), state.motion == *motion) {
+                    state.motion = *motion;
+                }
+            }
+            match state.motion {
+                FrameMotion::Spin => {
+                    imgui::Drag::new("rotation (°/s)")
+                        .range(-360.0, 360.0)
+                        .speed(0.1)
+                        .display_format("%.1f")
+                        .build(ui, &mut state.rotation_speed);
+                },
+                FrameMotion::RotateTo => {
+                    ui.text("From the view's current center to:");
+                    handle_point_input(ui, "frame_export_end", &mut state.end_center, program_data.user_markers());
+                },
+                FrameMotion::CameraPath => if !camera_path.is_playable() {
+                    ui.text("Record at least 2 keyframes in \"Tools/Camera path...\".");
+                }
+            }
+            if let Some(status) = &state.status {
+                ui.text_wrapped(status);
+            }
+
+            ui.separator();
+            match focused_view.and_then(
+                |id| program_data.all_views_mut().find(|view| view.unique_id() == id)
+            ) {
+                None => ui.text("No view to export."),
+                Some(view) => if ui.button("Export") {
+                    let num_frames = (state.duration * state.frame_rate as f32).round() as i32;
+                    let valid = state.width > 0 && state.height > 0 && state.frame_rate > 0 && num_frames > 0;
+                    let started = if state.motion == FrameMotion::CameraPath && !camera_path.is_playable() {
+                        Err("the camera path needs at least 2 keyframes".to_string())
+                    } else if !valid {
+                        Err("invalid frame size, rate or duration".to_string())
+                    } else {
+                        match state.format {
+                            FrameExportFormat::Png => std::fs::create_dir_all(&state.path)
+                                .map(|_| FrameSink::Files)
+                                .map_err(|e| e.to_string()),
+                            FrameExportFormat::Video => if state.width % 2 != 0 || state.height % 2 != 0 {
+                                Err("the video's width and height have to be even".to_string())
+                            } else {
+                                export::VideoEncoder::start(
+                                    &state.video_path, state.width as u32, state.height as u32, state.frame_rate as u32
+                                ).map(FrameSink::Video)
+                            },
+                            FrameExportFormat::Gif => export::AnimatedGifEncoder::create(
+                                &state.gif_path, state.frame_rate as u32
+                            ).map(FrameSink::Gif)
+                        }
+                    };
+                    state.status = match started {
+                        Err(e) => Some(format!("Error: {}.", e)),
+                        Ok(sink) => {
+                            let (lon, lat) = view.central_lonlat();
+                            state.progress = Some(FrameExportProgress{
+                                view_id: view.unique_id(),
+                                next_frame: 0,
+                                num_frames,
+                                start_center: [lon.0, lat.0],
+                                sink
+                            });
+                            program_data.animation_clock.set_fixed_step(Some(1.0 / state.frame_rate as f64));
+                            None
+                        }
+                    };
+                }
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
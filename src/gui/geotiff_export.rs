@@ -0,0 +1,107 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Export of the focused view as a georeferenced GeoTIFF image.
+//
+
+use crate::data;
+use crate::export;
+
+const DEFAULT_GEOTIFF_FILE: &str = "map.tif";
+
+/// Default width and height of images exported by "File/Export GeoTIFF...".
+const DEFAULT_GEOTIFF_SIZE: i32 = 4000;
+
+#[derive(Default)]
+pub struct GeoTiffExportState {
+    path: String,
+    width: i32,
+    height: i32,
+    /// If true, lines (coastlines, graticule etc.) are drawn over the raster layers.
+    lines: bool,
+    /// Result of the last export.
+    status: Option<String>
+}
+
+impl GeoTiffExportState {
+    pub fn new() -> GeoTiffExportState {
+        GeoTiffExportState{
+            path: DEFAULT_GEOTIFF_FILE.to_string(),
+            width: DEFAULT_GEOTIFF_SIZE,
+            height: DEFAULT_GEOTIFF_SIZE,
+            ..Default::default()
+        }
+    }
+}
+
+/// Shows the "Export GeoTIFF" dialog (opened if `open` is true).
+pub fn handle_geotiff_export(
+    ui: &imgui::Ui,
+    state: &mut GeoTiffExportState,
+    open: bool,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Export GeoTIFF");
+    }
+    ui.popup_modal("Export GeoTIFF").always_auto_resize(true).build(ui, || {
+        let focused_view = focused_view.and_then(
+            |id| program_data.all_views_mut().find(|view| view.unique_id() == id)
+        );
+
+        ui.text("Renders the most recently focused view offscreen and saves it as a GeoTIFF image with its projection");
+        ui.text("and georeferencing, for use in GIS software (e.g. QGIS, ArcGIS). The view has to show a standard");
+        ui.text("projection in its normal aspect (azimuthal ones may be centered anywhere, others on the equator),");
+        ui.text("with north up and no stretching.");
+        ui.input_text("output file", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new().add_filter("GeoTIFF", &["tif", "tiff"]).save_file() {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+        ui.input_int("width", &mut state.width).build();
+        ui.input_int("height", &mut state.height).build();
+        if let Some(view) = &focused_view {
+            if ui.button("Match view's aspect ratio") {
+                state.height = (state.width as f32 / view.wh_ratio()).round().max(1.0) as i32;
+            }
+        }
+        ui.checkbox("include lines", &mut state.lines);
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Draw coastlines, the graticule, vector layers etc. over the raster layers");
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        match focused_view {
+            None => ui.text("No view to export."),
+            Some(view) => if ui.button("Export") {
+                state.status = Some(if state.width <= 0 || state.height <= 0 {
+                    "Error: invalid image size.".to_string()
+                } else {
+                    match export::export_view_geotiff(
+                        &state.path, view, state.width as u32, state.height as u32, state.lines
+                    ) {
+                        Ok(()) => format!("Saved {} ({}×{}).", state.path, state.width, state.height),
+                        Err(e) => format!("Error: {}.", e)
+                    }
+                });
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
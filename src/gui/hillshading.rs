@@ -0,0 +1,110 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Hillshading computed from an elevation raster.
+//
+
+use crate::data;
+
+#[derive(Default)]
+pub struct HillshadingState {
+    /// Elevation raster.
+    path: String,
+    params: data::HillshadingParams,
+    /// Result of the last load.
+    status: Option<String>
+}
+
+/// Shows the "Hillshading" dialog (opened if `open` is true).
+pub fn handle_hillshading(
+    ui: &imgui::Ui,
+    state: &mut HillshadingState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        if let Some(hillshading) = program_data.hillshading() {
+            state.params = hillshading.params;
+        }
+        state.status = None;
+        ui.open_popup("Hillshading");
+    }
+    ui.popup_modal("Hillshading").always_auto_resize(true).build(ui, || {
+        ui.text("Shades relief computed from an equirectangular elevation raster (a grayscale image, 8 or 16 bits");
+        ui.text("per pixel, e.g. ETOPO or GEBCO) over the map's texture, or instead of it.");
+        ui.input_text("elevation raster", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("Images", &["png", "tif", "tiff", "jpg", "jpeg", "bmp"])
+                .pick_file()
+            {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+
+        let params = &mut state.params;
+        let mut changed = false;
+        let _width = ui.push_item_width(200.0);
+        changed |= imgui::Drag::new("elevation of black")
+            .speed(10.0)
+            .display_format("%.0f m")
+            .build(ui, &mut params.elevation_range[0]);
+        changed |= imgui::Drag::new("elevation of white")
+            .speed(10.0)
+            .display_format("%.0f m")
+            .build(ui, &mut params.elevation_range[1]);
+        changed |= imgui::Drag::new("vertical exaggeration")
+            .range(0.1, 100.0)
+            .speed(0.1)
+            .flags(imgui::SliderFlags::LOGARITHMIC)
+            .build(ui, &mut params.exaggeration);
+        changed |= imgui::Drag::new("sun azimuth")
+            .range(0.0, 360.0)
+            .display_format("%.0f°")
+            .build(ui, &mut params.sun_azimuth.0);
+        changed |= imgui::Drag::new("sun elevation")
+            .range(1.0, 90.0)
+            .display_format("%.0f°")
+            .build(ui, &mut params.sun_elevation.0);
+        let mode_names: Vec<String> = data::HillshadingMode::ALL.iter().map(|mode| mode.name().to_string()).collect();
+        let mut mode_idx = data::HillshadingMode::ALL.iter().position(|mode| *mode == params.mode).unwrap();
+        if ui.combo_simple_string("mode", &mut mode_idx, &mode_names) {
+            params.mode = data::HillshadingMode::ALL[mode_idx];
+            changed = true;
+        }
+        if changed {
+            program_data.set_hillshading_params(*params);
+        }
+
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Load") {
+            state.status = Some(match program_data.load_elevation(&state.path, state.params, display) {
+                Ok(()) => format!("Loaded {}.", state.path),
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if program_data.hillshading().is_some() {
+            if ui.button("Remove") {
+                program_data.remove_hillshading();
+                state.status = None;
+            }
+            ui.same_line();
+        }
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
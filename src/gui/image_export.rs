@@ -0,0 +1,101 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Export of the focused view as a PNG or JPEG image of any size.
+//
+
+use crate::data;
+use crate::export;
+
+const DEFAULT_IMAGE_EXPORT_FILE: &str = "map.png";
+
+/// Default width of images exported by "File/Export image...".
+const DEFAULT_IMAGE_EXPORT_SIZE: i32 = 8000;
+
+#[derive(Default)]
+pub struct ImageExportState {
+    path: String,
+    width: i32,
+    height: i32,
+    /// Result of the last export.
+    status: Option<String>
+}
+
+impl ImageExportState {
+    pub fn new() -> ImageExportState {
+        ImageExportState{
+            path: DEFAULT_IMAGE_EXPORT_FILE.to_string(),
+            width: DEFAULT_IMAGE_EXPORT_SIZE,
+            height: DEFAULT_IMAGE_EXPORT_SIZE,
+            status: None
+        }
+    }
+}
+
+/// Shows the "Export image" dialog (opened if `open` is true).
+pub fn handle_image_export(
+    ui: &imgui::Ui,
+    state: &mut ImageExportState,
+    open: bool,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Export image");
+    }
+    ui.popup_modal("Export image").always_auto_resize(true).build(ui, || {
+        let focused_view = focused_view.and_then(
+            |id| program_data.all_views_mut().find(|view| view.unique_id() == id)
+        );
+
+        ui.text("Renders the most recently focused view offscreen at the given size (independent of the window's;");
+        ui.text("large images are rendered in tiles) and saves it as PNG or JPEG (chosen by the file extension).");
+        ui.input_text("output file", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("PNG", &["png"])
+                .add_filter("JPEG", &["jpg", "jpeg"])
+                .save_file()
+            {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+        ui.input_int("width", &mut state.width).build();
+        ui.input_int("height", &mut state.height).build();
+        if let Some(view) = &focused_view {
+            if ui.button("Match view's aspect ratio") {
+                state.height = (state.width as f32 / view.wh_ratio()).round().max(1.0) as i32;
+            }
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        match focused_view {
+            None => ui.text("No view to export."),
+            Some(view) => if ui.button("Export") {
+                state.status = Some(if state.width <= 0 || state.height <= 0 {
+                    "Error: invalid image size.".to_string()
+                } else {
+                    match export::export_view_image(&state.path, view, state.width as u32, state.height as u32) {
+                        Ok(()) => format!("Saved {} ({}×{}).", state.path, state.width, state.height),
+                        Err(e) => format!("Error: {}.", e)
+                    }
+                });
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,141 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Georeferenced images (e.g. scanned maps) shown over the base map.
+//
+
+use crate::data;
+use crate::georeferencing;
+
+#[derive(Default)]
+pub struct ImageOverlayState {
+    path: String,
+    /// If true, the image is georeferenced by `geotransform`, otherwise by `corners`.
+    use_geotransform: bool,
+    /// Longitude and latitude (degrees) of the top-left, top-right, bottom-right and bottom-left corners.
+    corners: [[f64; 2]; 4],
+    /// See `georeferencing::Georeference::GeoTransform`.
+    geotransform: [f64; 6],
+    /// Result of the last load.
+    status: Option<String>
+}
+
+impl ImageOverlayState {
+    pub fn new() -> ImageOverlayState {
+        ImageOverlayState{
+            corners: [[-10.0, 10.0], [10.0, 10.0], [10.0, -10.0], [-10.0, -10.0]],
+            geotransform: [-180.0, 0.1, 0.0, 90.0, 0.0, -0.1],
+            ..Default::default()
+        }
+    }
+}
+
+/// Shows the "Georeferenced image" dialog (opened if `open` is true).
+pub fn handle_image_overlay(
+    ui: &imgui::Ui,
+    state: &mut ImageOverlayState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Georeferenced image");
+    }
+    ui.popup_modal("Georeferenced image").always_auto_resize(true).build(ui, || {
+        ui.text("Shows an image (e.g. a scanned map) over the base map in the texture mode, placed by the longitudes");
+        ui.text("and latitudes of its corners or by an affine geotransform (e.g. from a world file).");
+        ui.input_text("image", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("Images", &["jpg", "jpeg", "png", "tif", "tiff", "bmp"])
+                .pick_file()
+            {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+
+        if ui.radio_button_bool("corners", !state.use_geotransform) {
+            state.use_geotransform = false;
+        }
+        ui.same_line();
+        if ui.radio_button_bool("geotransform", state.use_geotransform) {
+            state.use_geotransform = true;
+        }
+        if state.use_geotransform {
+            ui.same_line();
+            if ui.button("Read world file") {
+                match georeferencing::world_file_path(&state.path) {
+                    Some(path) => match georeferencing::load_world_file(&path) {
+                        Ok(geotransform) => state.geotransform = geotransform,
+                        Err(e) => state.status = Some(format!("Error reading {}: {}", path, e))
+                    },
+                    None => state.status = Some("No world file found next to the image.".into())
+                }
+            }
+        }
+
+        let _width = ui.push_item_width(100.0);
+        if state.use_geotransform {
+            const LABELS: [&str; 6] =
+                ["lon. origin", "lon./column", "lon./row", "lat. origin", "lat./column", "lat./row"];
+            for (idx, (value, label)) in state.geotransform.iter_mut().zip(LABELS.iter()).enumerate() {
+                if idx % 3 != 0 { ui.same_line(); }
+                imgui::Drag::new(label).speed(0.001).display_format("%.6f").build(ui, value);
+            }
+        } else {
+            const LABELS: [&str; 4] = ["top left", "top right", "bottom right", "bottom left"];
+            for (corner, label) in state.corners.iter_mut().zip(LABELS.iter()) {
+                imgui::Drag::new(&format!("lon.##{}", label))
+                    .range(-180.0, 180.0)
+                    .speed(0.01)
+                    .display_format("%.4f°")
+                    .build(ui, &mut corner[0]);
+                ui.same_line();
+                imgui::Drag::new(&format!("lat. ({})", label))
+                    .range(-90.0, 90.0)
+                    .speed(0.01)
+                    .display_format("%.4f°")
+                    .build(ui, &mut corner[1]);
+            }
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+        if let Some(mut opacity) = program_data.image_overlay().map(|overlay| overlay.opacity) {
+            let _width = ui.push_item_width(200.0);
+            if imgui::Slider::new("opacity", 0.0, 1.0).build(ui, &mut opacity) {
+                program_data.set_image_overlay_opacity(opacity);
+            }
+        }
+
+        ui.separator();
+        if ui.button("Load") {
+            let georeference = if state.use_geotransform {
+                georeferencing::Georeference::GeoTransform(state.geotransform)
+            } else {
+                georeferencing::Georeference::Corners(state.corners)
+            };
+            state.status = match program_data.load_image_overlay(&state.path, &georeference, display) {
+                Ok(()) => None,
+                Err(e) => Some(format!("Error: {}", e))
+            };
+        }
+        ui.same_line();
+        if ui.button("Remove") {
+            program_data.remove_image_overlay();
+            state.status = None;
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,807 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Main menu of the program's window and the dialogs opened from it.
+//
+
+use crate::config;
+use crate::data;
+use crate::projection::Projection;
+use crate::views;
+use std::cell::RefCell;
+use std::rc::Rc;
+use super::{
+    boolean_op, buffer, choropleth, day_night, declination, earthquakes, frame_export, geotiff_export, hillshading,
+    image_export, image_overlay, map_colors, netcdf, overlay_script, place_search, plate_boundaries, presets, print,
+    proj_string, projection_export, range_rings, routes, session, svg_export, texture, time_zones, tour, user_markers,
+    vector_export, vector_layers, web_tiles
+};
+use super::{DoubleClickAction, GuiState, InputBindings, MAGNIFIER_ZOOM, WheelAction};
+
+/// Items of the main menu clicked in the current frame.
+#[derive(Default)]
+struct MenuClicks {
+    orthographic: bool,
+    stereographic: bool,
+    gnomonic: bool,
+    lambert_azimuthal: bool,
+    cylindrical_lambert: bool,
+    nicolosi: bool,
+    conformal_square: Option<views::ConformalSquareKind>,
+    general_perspective: bool,
+    qsc: bool,
+    littrow: bool,
+    loximuthal: bool,
+    eckert: Option<views::EckertKind>,
+    elliptical: Option<views::EllipticalKind>,
+    mercator: Option<views::MercatorKind>,
+    transverse_mercator: bool,
+    lambert_conformal_conic: bool,
+    albers_equal_area: bool,
+    armadillo: bool,
+    plugin: Option<usize>,
+    preset: Option<usize>,
+    presets: bool,
+    about: bool,
+    instructions: bool,
+    tour: bool,
+    place_search: bool,
+    export_metrics: bool,
+    export_samples: bool,
+    overlay_script: bool,
+    buffer: bool,
+    boolean_op: bool,
+    route: bool,
+    range_rings: bool,
+    user_markers: bool,
+    print: bool,
+    export_pdf: bool,
+    export_image: bool,
+    export_geotiff: bool,
+    export_svg: bool,
+    export_vector_layers: bool,
+    export_frames: bool,
+    input_bindings: bool,
+    proj_string: bool,
+    session: bool,
+    open_texture: bool,
+    vector_layers: bool,
+    map_colors: bool,
+    day_night: bool,
+    hillshading: bool,
+    web_tiles: bool,
+    image_overlay: bool,
+    scalar_field: bool,
+    vector_field: bool,
+    choropleth: bool,
+    plate_boundaries: bool,
+    time_zones: bool,
+    earthquakes: bool,
+    declination: bool,
+    new_layer_visibility: Option<views::LayerVisibility>,
+    new_vector_layer_visibility: Option<(usize, bool)>,
+    new_distance_unit: Option<data::DistanceUnit>,
+    new_body: Option<usize>
+}
+
+pub fn handle_main_menu(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) {
+    let mut clicks = MenuClicks::default();
+
+    match ui.begin_main_menu_bar() {
+        None => (),
+        Some(token) => {
+            handle_file_menu(ui, gui_state, &mut clicks);
+            handle_view_menu(ui, gui_state, program_data, &mut clicks);
+            handle_tools_menu(ui, gui_state, program_data, &mut clicks);
+            handle_help_menu(ui, &mut clicks);
+            token.end();
+        }
+    }
+
+    if clicks.tour && gui_state.tour.is_none() {
+        gui_state.tour = Some(tour::TourState::new(program_data, renderer, display));
+    }
+
+    apply_settings(&clicks, program_data, display);
+    create_new_views(&clicks, program_data, renderer, display);
+    handle_dialogs(ui, gui_state, &clicks, program_data, renderer, display);
+    handle_input_bindings(ui, &mut gui_state.input_bindings, clicks.input_bindings);
+    handle_instructions(ui, clicks.instructions);
+    handle_about(ui, clicks.about);
+}
+
+fn handle_file_menu(ui: &imgui::Ui, gui_state: &mut GuiState, clicks: &mut MenuClicks) {
+    ui.menu("File", || {
+        if ui.menu_item("Session...") {
+            clicks.session = true;
+        }
+        if ui.menu_item("Open texture...") {
+            clicks.open_texture = true;
+        }
+        if ui.menu_item("Vector layers...") {
+            clicks.vector_layers = true;
+        }
+        ui.separator();
+        if ui.menu_item_config("Save image...").enabled(gui_state.focused_view.is_some()).build() {
+            gui_state.save_image_request = gui_state.focused_view;
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Save the most recently focused view as PNG or JPEG");
+        }
+        if ui.menu_item("Print...") {
+            clicks.print = true;
+        }
+        if ui.menu_item("Export PDF...") {
+            clicks.export_pdf = true;
+        }
+        if ui.menu_item("Export image...") {
+            clicks.export_image = true;
+        }
+        if ui.menu_item("Export GeoTIFF...") {
+            clicks.export_geotiff = true;
+        }
+        if ui.menu_item("Export SVG...") {
+            clicks.export_svg = true;
+        }
+        if ui.menu_item("Export vector layers...") {
+            clicks.export_vector_layers = true;
+        }
+        if ui.menu_item("Export frames...") {
+            clicks.export_frames = true;
+        }
+    });
+}
+
+fn handle_view_menu(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    program_data: &data::ProgramData,
+    clicks: &mut MenuClicks
+) {
+    ui.menu("View", || {
+        handle_new_view_menu(ui, program_data, clicks);
+        handle_presets_menu(ui, gui_state, clicks);
+        handle_layers_menu(ui, gui_state, program_data, clicks);
+        handle_body_menu(ui, program_data, clicks);
+        handle_units_menu(ui, program_data, clicks);
+
+        if ui.menu_item("Map colors...") {
+            clicks.map_colors = true;
+        }
+        if ui.menu_item_config("Day and night...").enabled(program_data.is_earth()).build() {
+            clicks.day_night = true;
+        }
+        if ui.menu_item("Hillshading...") {
+            clicks.hillshading = true;
+        }
+        if ui.menu_item("Web tiles...") {
+            clicks.web_tiles = true;
+        }
+        if ui.menu_item("Georeferenced image...") {
+            clicks.image_overlay = true;
+        }
+        if ui.menu_item("Scalar field...") {
+            clicks.scalar_field = true;
+        }
+        if ui.menu_item("Vector field...") {
+            clicks.vector_field = true;
+        }
+        if ui.menu_item("Choropleth...") {
+            clicks.choropleth = true;
+        }
+        if ui.menu_item_config("Plate boundaries...").enabled(program_data.is_earth()).build() {
+            clicks.plate_boundaries = true;
+        }
+        if ui.menu_item_config("Time zones...").enabled(program_data.is_earth()).build() {
+            clicks.time_zones = true;
+        }
+        if ui.menu_item_config("Earthquakes...").enabled(program_data.is_earth()).build() {
+            clicks.earthquakes = true;
+        }
+        if ui.menu_item_config("Magnetic declination...").enabled(program_data.is_earth()).build() {
+            clicks.declination = true;
+        }
+
+        ui.separator();
+        if ui.menu_item_config("Link zoom").selected(gui_state.linked_views.zoom).build() {
+            gui_state.linked_views.zoom = !gui_state.linked_views.zoom;
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Linked views share their zoom in addition to the rotation");
+        }
+        if ui.menu_item_config("Magnifier").selected(gui_state.magnifier).build() {
+            gui_state.magnifier = !gui_state.magnifier;
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(format!("Show the view around the cursor magnified {}×", MAGNIFIER_ZOOM));
+        }
+    });
+}
+
+/// Shows an entry of the "View/New" menu with a hint of the projection's properties; returns true if clicked.
+/// Only the kind of `projection` matters, not its parameters.
+fn new_view_menu_item(ui: &imgui::Ui, label: &str, projection: &Projection) -> bool {
+    ui.menu_item_config(label).shortcut(projection.properties()).build()
+}
+
+fn handle_new_view_menu(ui: &imgui::Ui, program_data: &data::ProgramData, clicks: &mut MenuClicks) {
+    ui.menu("New", || {
+        if new_view_menu_item(ui, "Orthographic", &Projection::Orthographic) {
+            clicks.orthographic = true;
+        }
+        if new_view_menu_item(
+            ui,
+            "Stereographic",
+            &Projection::Stereographic{ point_distance: 1.0, image_plane: 1.0 }
+        ) {
+            clicks.stereographic = true;
+        }
+        if new_view_menu_item(ui, "Gnomonic", &Projection::Gnomonic{ max_angle: cgmath::Rad(0.0) }) {
+            clicks.gnomonic = true;
+        }
+        if new_view_menu_item(ui, "Lambert azimuthal equal-area", &Projection::LambertAzimuthalEqualArea) {
+            clicks.lambert_azimuthal = true;
+        }
+        if new_view_menu_item(
+            ui,
+            "Cylindrical equal-area",
+            &Projection::CylindricalLambert{ standard_parallel: cgmath::Rad(0.0) }
+        ) {
+            clicks.cylindrical_lambert = true;
+        }
+        if new_view_menu_item(ui, "Nicolosi globular", &Projection::Nicolosi) {
+            clicks.nicolosi = true;
+        }
+        for kind in [
+            views::ConformalSquareKind::AdamsHemisphere,
+            views::ConformalSquareKind::Guyou,
+            views::ConformalSquareKind::PeirceQuincuncial
+        ] {
+            if new_view_menu_item(ui, kind.name(), &kind.projection()) {
+                clicks.conformal_square = Some(kind);
+            }
+        }
+        if new_view_menu_item(
+            ui,
+            "General perspective",
+            &Projection::GeneralPerspective{ distance: 2.0, tilt: cgmath::Rad(0.0) }
+        ) {
+            clicks.general_perspective = true;
+        }
+        if new_view_menu_item(
+            ui,
+            "Quadrilateralized spherical cube",
+            &Projection::QuadrilateralizedSphericalCube
+        ) {
+            clicks.qsc = true;
+        }
+        if new_view_menu_item(ui, "Littrow", &Projection::Littrow) {
+            clicks.littrow = true;
+        }
+        if new_view_menu_item(
+            ui,
+            "Loximuthal",
+            &Projection::Loximuthal{ central_latitude: cgmath::Rad(0.0) }
+        ) {
+            clicks.loximuthal = true;
+        }
+        for kind in views::EckertKind::ALL {
+            if new_view_menu_item(ui, kind.name(), &kind.projection()) {
+                clicks.eckert = Some(kind);
+            }
+        }
+        for kind in views::EllipticalKind::ALL {
+            if new_view_menu_item(ui, kind.name(), &kind.projection()) {
+                clicks.elliptical = Some(kind);
+            }
+        }
+        for kind in views::MercatorKind::ALL {
+            if new_view_menu_item(ui, kind.name(), &kind.projection()) {
+                clicks.mercator = Some(kind);
+            }
+        }
+        if new_view_menu_item(
+            ui,
+            "Transverse Mercator",
+            &Projection::TransverseMercator{ eccentricity: 0.0 }
+        ) {
+            clicks.transverse_mercator = true;
+        }
+        if new_view_menu_item(
+            ui,
+            "Lambert conformal conic",
+            &Projection::LambertConformalConic{
+                standard_parallels: [cgmath::Rad(0.0); 2],
+                eccentricity: 0.0
+            }
+        ) {
+            clicks.lambert_conformal_conic = true;
+        }
+        if new_view_menu_item(
+            ui,
+            "Albers equal-area conic",
+            &Projection::AlbersEqualArea{
+                standard_parallels: [cgmath::Rad(0.0); 2],
+                eccentricity: 0.0
+            }
+        ) {
+            clicks.albers_equal_area = true;
+        }
+        if new_view_menu_item(ui, "Armadillo", &Projection::Armadillo{ parallel: cgmath::Rad(0.0) }) {
+            clicks.armadillo = true;
+        }
+        ui.separator();
+        if ui.menu_item("From PROJ string...") {
+            clicks.proj_string = true;
+        }
+
+        if program_data.plugins.iter().any(|p| p.gl_programs.is_some() || p.error.is_some()) {
+            ui.separator();
+        }
+        for (idx, plugin_data) in program_data.plugins.iter().enumerate() {
+            if plugin_data.gl_programs.is_some() && ui.menu_item(&plugin_data.plugin.name) {
+                clicks.plugin = Some(idx);
+            }
+            if let Some(error) = &plugin_data.error {
+                ui.text_disabled(format!("{} (failed to load)", plugin_data.plugin.name));
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(error);
+                }
+            }
+        }
+    });
+}
+
+fn handle_presets_menu(ui: &imgui::Ui, gui_state: &GuiState, clicks: &mut MenuClicks) {
+    ui.menu("Presets", || {
+        for (idx, preset) in gui_state.presets.presets.iter().enumerate() {
+            if ui.menu_item(preset.name()) {
+                clicks.preset = Some(idx);
+            }
+        }
+        if gui_state.presets.presets.is_empty() {
+            ui.text_disabled("(no presets)");
+        }
+        ui.separator();
+        if ui.menu_item("Manage...") {
+            clicks.presets = true;
+        }
+    });
+}
+
+fn handle_layers_menu(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    program_data: &data::ProgramData,
+    clicks: &mut MenuClicks
+) {
+    ui.menu("Layers", || {
+        let mut layer_visibility = program_data.layer_visibility();
+        for layer in views::Layer::ALL {
+            let mut visible = layer_visibility.is_visible(layer);
+            if ui.checkbox(layer.name(), &mut visible) {
+                layer_visibility.set_visible(layer, visible);
+                clicks.new_layer_visibility = Some(layer_visibility);
+            }
+        }
+        if !program_data.vector_layers().is_empty() {
+            ui.separator();
+            ui.text_disabled("vector layers:");
+        }
+        for (idx, layer) in program_data.vector_layers().iter().enumerate() {
+            let mut visible = layer.visible;
+            if ui.checkbox(&format!("{}##vector_layer_{}", layer.name, idx), &mut visible) {
+                clicks.new_vector_layer_visibility = Some((idx, visible));
+            }
+        }
+        if !program_data.places.is_empty() {
+            ui.separator();
+            let _width = ui.push_item_width(100.0);
+            imgui::Drag::new("min. place rank")
+                .range(0, 14)
+                .speed(0.05)
+                .build(ui, &mut gui_state.min_place_rank);
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Population rank of the smallest places shown \
+                    (10: 200 000, 12: 1 million, 14: 10 million inhabitants)");
+            }
+        }
+    });
+}
+
+fn handle_body_menu(ui: &imgui::Ui, program_data: &data::ProgramData, clicks: &mut MenuClicks) {
+    ui.menu("Body", || {
+        for (idx, body) in data::BODIES.iter().enumerate() {
+            if ui.menu_item_config(body.name).selected(body == program_data.body()).build() {
+                clicks.new_body = Some(idx);
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text(format!(
+                    "Mean radius: {:.1} {}, flattening: 1/{:.0}",
+                    program_data.distance_unit.from_km(body.mean_radius),
+                    program_data.distance_unit.symbol(),
+                    1.0 / body.flattening
+                ));
+            }
+        }
+    });
+}
+
+fn handle_units_menu(ui: &imgui::Ui, program_data: &data::ProgramData, clicks: &mut MenuClicks) {
+    ui.menu("Units", || {
+        for unit in data::DistanceUnit::ALL {
+            if ui.menu_item_config(unit.name())
+                .shortcut(unit.symbol())
+                .selected(unit == program_data.distance_unit)
+                .build() {
+                clicks.new_distance_unit = Some(unit);
+            }
+        }
+    });
+}
+
+fn handle_tools_menu(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    program_data: &data::ProgramData,
+    clicks: &mut MenuClicks
+) {
+    ui.menu("Tools", || {
+        if ui.menu_item_config("Find place...").enabled(program_data.gazetteer().is_some()).build() {
+            clicks.place_search = true;
+        }
+        if ui.menu_item("Export distortion metrics...") {
+            clicks.export_metrics = true;
+        }
+        if ui.menu_item("Export projection samples...") {
+            clicks.export_samples = true;
+        }
+        if ui.menu_item("Overlay script...") {
+            clicks.overlay_script = true;
+        }
+        if ui.menu_item("Buffer...") {
+            clicks.buffer = true;
+        }
+        if ui.menu_item("Boolean operation...") {
+            clicks.boolean_op = true;
+        }
+        if ui.menu_item("Routes...") {
+            clicks.route = true;
+        }
+        if ui.menu_item("Range rings...") {
+            clicks.range_rings = true;
+        }
+        if ui.menu_item("Markers...") {
+            clicks.user_markers = true;
+        }
+        if ui.menu_item("Camera path...") {
+            gui_state.camera_path.open = true;
+        }
+        if ui.menu_item_config("Measure").selected(gui_state.measurement.active).build() {
+            gui_state.measurement.active = !gui_state.measurement.active;
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Measure lengths and areas of lines and polygons clicked in views");
+        }
+        ui.separator();
+        if ui.menu_item("Input bindings...") {
+            clicks.input_bindings = true;
+        }
+    });
+}
+
+fn handle_help_menu(ui: &imgui::Ui, clicks: &mut MenuClicks) {
+    ui.menu("Help", || {
+        if ui.menu_item("Instructions...") {
+            clicks.instructions = true;
+        }
+        if ui.menu_item("Projection tour") {
+            clicks.tour = true;
+        }
+        if ui.menu_item("About...") {
+            clicks.about = true;
+        }
+    });
+}
+
+/// Applies the layer visibility, body and unit selected in the menu.
+fn apply_settings(clicks: &MenuClicks, program_data: &mut data::ProgramData, display: &glium::Display) {
+    if let Some(layer_visibility) = clicks.new_layer_visibility {
+        program_data.set_layer_visibility(layer_visibility);
+    }
+    if let Some((idx, visible)) = clicks.new_vector_layer_visibility {
+        program_data.set_vector_layer_visible(idx, visible);
+    }
+
+    if let Some(idx) = clicks.new_body {
+        if let Err(e) = program_data.set_body(idx, display) {
+            eprintln!("Failed to load data of {}: {}", data::BODIES[idx].name, e);
+        }
+    }
+
+    if let Some(unit) = clicks.new_distance_unit {
+        program_data.distance_unit = unit;
+        let config_path = config::config_path();
+        if let Err(e) = config::store_distance_unit(&config_path, unit) {
+            eprintln!("Failed to store units in {}: {}", config_path.display(), e);
+        }
+    }
+}
+
+fn create_new_views(
+    clicks: &MenuClicks,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) {
+    if clicks.orthographic {
+        program_data.add_orthographic_view(views::OrthographicView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.stereographic {
+        program_data.add_stereographic_view(views::StereographicView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.gnomonic {
+        program_data.add_gnomonic_view(views::GnomonicView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.lambert_azimuthal {
+        program_data.add_lambert_azimuthal_view(views::LambertAzimuthalView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.cylindrical_lambert {
+        program_data.add_cylindrical_lambert_view(views::CylindricalLambertView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.nicolosi {
+        program_data.add_nicolosi_view(views::NicolosiView::new(
+            program_data, renderer, display
+        ));
+    }
+    if let Some(kind) = clicks.conformal_square {
+        program_data.add_conformal_square_view(views::ConformalSquareView::new(
+            kind, program_data, renderer, display
+        ));
+    }
+    if clicks.general_perspective {
+        program_data.add_general_perspective_view(views::GeneralPerspectiveView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.qsc {
+        program_data.add_qsc_view(views::QscView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.littrow {
+        program_data.add_littrow_view(views::LittrowView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.loximuthal {
+        program_data.add_loximuthal_view(views::LoximuthalView::new(
+            program_data, renderer, display
+        ));
+    }
+    if let Some(kind) = clicks.eckert {
+        program_data.add_eckert_view(views::EckertView::new(
+            kind, program_data, renderer, display
+        ));
+    }
+    if let Some(kind) = clicks.elliptical {
+        program_data.add_elliptical_view(views::EllipticalView::new(
+            kind, program_data, renderer, display
+        ));
+    }
+    if let Some(kind) = clicks.mercator {
+        program_data.add_mercator_view(views::MercatorView::new(
+            kind, program_data, renderer, display
+        ));
+    }
+    if clicks.transverse_mercator {
+        program_data.add_transverse_mercator_view(views::TransverseMercatorView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.lambert_conformal_conic {
+        program_data.add_lambert_conformal_conic_view(views::LambertConformalConicView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.albers_equal_area {
+        program_data.add_albers_equal_area_view(views::AlbersEqualAreaView::new(
+            program_data, renderer, display
+        ));
+    }
+    if clicks.armadillo {
+        program_data.add_armadillo_view(views::ArmadilloView::new(
+            program_data, renderer, display
+        ));
+    }
+    if let Some(idx) = clicks.plugin {
+        let plugin_data = &program_data.plugins[idx];
+        let view = views::PluginView::new(
+            plugin_data.plugin, plugin_data.gl_programs.as_ref().unwrap(), program_data, renderer, display
+        );
+        program_data.add_plugin_view(view);
+    }
+}
+
+fn handle_dialogs(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    clicks: &MenuClicks,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) {
+    projection_export::handle_metrics_export(ui, &mut gui_state.metrics_export, clicks.export_metrics, program_data);
+
+    projection_export::handle_samples_export(
+        ui, &mut gui_state.samples_export, clicks.export_samples, gui_state.focused_view, program_data
+    );
+
+    if let Some(idx) = clicks.preset {
+        if let Err(e) = gui_state.presets.presets[idx].create_view(program_data, renderer, display) {
+            eprintln!("Failed to create view from preset \"{}\": {}", gui_state.presets.presets[idx].name(), e);
+        }
+    }
+
+    presets::handle_presets(ui, &mut gui_state.presets, clicks.presets, gui_state.focused_view, program_data);
+
+    proj_string::handle_proj_string(
+        ui, &mut gui_state.proj_string, clicks.proj_string, program_data, renderer, display
+    );
+
+    session::handle_session(ui, &mut gui_state.session, clicks.session, program_data, renderer, display);
+
+    texture::handle_texture(ui, &mut gui_state.texture, clicks.open_texture, program_data, display);
+
+    vector_layers::handle_vector_layers(ui, &mut gui_state.vector_layers, clicks.vector_layers, program_data, display);
+
+    map_colors::handle_map_colors(ui, &mut gui_state.map_colors, clicks.map_colors, program_data, display);
+
+    day_night::handle_day_night(ui, &mut gui_state.day_night, clicks.day_night, program_data, display);
+
+    place_search::handle_place_search(
+        ui, &mut gui_state.place_search, clicks.place_search, gui_state.focused_view, program_data
+    );
+
+    user_markers::handle_user_markers(ui, &mut gui_state.user_markers, clicks.user_markers, program_data);
+
+    hillshading::handle_hillshading(ui, &mut gui_state.hillshading, clicks.hillshading, program_data, display);
+
+    choropleth::handle_choropleth(ui, &mut gui_state.choropleth, clicks.choropleth, program_data, display);
+
+    plate_boundaries::handle_plate_boundaries(
+        ui, &mut gui_state.plate_boundaries, clicks.plate_boundaries, program_data, display
+    );
+
+    time_zones::handle_time_zones(ui, &mut gui_state.time_zones, clicks.time_zones, program_data, display);
+
+    earthquakes::handle_earthquakes(ui, &mut gui_state.earthquakes, clicks.earthquakes, program_data);
+
+    declination::handle_declination(ui, &mut gui_state.declination, clicks.declination, program_data, display);
+
+    image_overlay::handle_image_overlay(
+        ui, &mut gui_state.image_overlay, clicks.image_overlay, program_data, display
+    );
+
+    web_tiles::handle_web_tiles(ui, &mut gui_state.web_tiles, clicks.web_tiles, gui_state.focused_view, program_data);
+
+    netcdf::handle_scalar_field(ui, &mut gui_state.scalar_field, clicks.scalar_field, program_data, display);
+
+    netcdf::handle_vector_field(ui, &mut gui_state.vector_field, clicks.vector_field, program_data);
+
+    buffer::handle_buffer(ui, &mut gui_state.buffer, clicks.buffer, program_data, display);
+
+    routes::handle_routes(ui, &mut gui_state.route, clicks.route, program_data, display);
+
+    range_rings::handle_range_rings(ui, &mut gui_state.range_rings, clicks.range_rings, program_data, display);
+
+    boolean_op::handle_boolean_op(ui, &mut gui_state.boolean_op, clicks.boolean_op, program_data, display);
+
+    overlay_script::handle_overlay_script(ui, &mut gui_state.overlay_script, clicks.overlay_script, program_data);
+
+    print::handle_print(ui, &mut gui_state.print, clicks.print, gui_state.focused_view, program_data);
+
+    print::handle_pdf_export(ui, &mut gui_state.pdf_export, clicks.export_pdf, gui_state.focused_view, program_data);
+
+    image_export::handle_image_export(
+        ui, &mut gui_state.image_export, clicks.export_image, gui_state.focused_view, program_data
+    );
+
+    geotiff_export::handle_geotiff_export(
+        ui, &mut gui_state.geotiff_export, clicks.export_geotiff, gui_state.focused_view, program_data
+    );
+
+    svg_export::handle_svg_export(
+        ui, &mut gui_state.svg_export, clicks.export_svg, gui_state.focused_view, program_data
+    );
+
+    vector_export::handle_vector_export(
+        ui, &mut gui_state.vector_export, clicks.export_vector_layers, gui_state.focused_view, program_data
+    );
+
+    frame_export::handle_frame_export(
+        ui,
+        &mut gui_state.frame_export,
+        clicks.export_frames,
+        gui_state.focused_view,
+        &gui_state.camera_path,
+        program_data
+    );
+}
+
+fn handle_input_bindings(ui: &imgui::Ui, bindings: &mut InputBindings, clicked: bool) {
+    if clicked { ui.open_popup("Input bindings"); }
+    ui.popup_modal("Input bindings").always_auto_resize(true).build(ui, || {
+        let mut double_click_idx = DoubleClickAction::ALL.iter().position(|a| *a == bindings.double_click).unwrap();
+        let double_click_names: Vec<&str> = DoubleClickAction::ALL.iter().map(|a| a.name()).collect();
+        if ui.combo_simple_string("double click", &mut double_click_idx, &double_click_names) {
+            bindings.double_click = DoubleClickAction::ALL[double_click_idx];
+        }
+
+        let mut wheel_idx = WheelAction::ALL.iter().position(|a| *a == bindings.wheel).unwrap();
+        let wheel_names: Vec<&str> = WheelAction::ALL.iter().map(|a| a.name()).collect();
+        if ui.combo_simple_string("mouse wheel", &mut wheel_idx, &wheel_names) {
+            bindings.wheel = WheelAction::ALL[wheel_idx];
+        }
+
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
+
+fn handle_instructions(ui: &imgui::Ui, clicked: bool) {
+    if clicked {
+        ui.open_popup("Instructions");
+        unsafe { imgui::sys::igSetNextWindowSize(
+            imgui::sys::ImVec2{ x: 600.0, y: 300.0},
+            imgui::sys::ImGuiCond_FirstUseEver as i32
+        ); }
+    }
+    ui.popup_modal("Instructions").build(ui, || {
+        ui.text_wrapped("Within a view window, use the left mouse button to change the orientation of the projected globe. \
+Hold Shift while dragging to roll the map about the view's center. \
+Use the mouse wheel to zoom in/out. Double-click to center the view on a point. Mouse wheel and double-click \
+actions can be changed in \"Tools/Input bindings\".\n\n");
+        ui.separator();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
+
+fn handle_about(ui: &imgui::Ui, clicked: bool) {
+    if clicked { ui.open_popup("About"); }
+    ui.popup_modal("About").build(ui, || {
+        ui.text("Map Projections\n
+Copyright (c) 2022 Filip Szczerek (ga.software@yahoo.com)\n\n\
+This program is licensed under MIT license (see LICENSE.txt for details).\n\n");
+
+        ui.separator();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,66 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Colors of land and ocean in the vector map mode.
+//
+
+use crate::data;
+
+#[derive(Default)]
+pub struct MapColorsState {
+    land_color: [f32; 3],
+    ocean_color: [f32; 3],
+    /// Result of the last change.
+    status: Option<String>
+}
+
+/// Shows the "Map colors" dialog (opened if `open` is true).
+pub fn handle_map_colors(
+    ui: &imgui::Ui,
+    state: &mut MapColorsState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        if let Some(land_fill) = program_data.land_fill() {
+            state.land_color = land_fill.land_color();
+            state.ocean_color = land_fill.ocean_color();
+        }
+        ui.open_popup("Map colors");
+    }
+    ui.popup_modal("Map colors").always_auto_resize(true).build(ui, || {
+        if program_data.land_fill().is_some() {
+            ui.text("Colors of land and ocean in the vector map mode.");
+            imgui::ColorEdit::new("land", &mut state.land_color).build(ui);
+            imgui::ColorEdit::new("ocean", &mut state.ocean_color).build(ui);
+        } else {
+            ui.text("Land polygons are not loaded; the vector map mode shows only lines.");
+            ui.text("The land shapefile can be specified with --land or in the configuration file.");
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if program_data.land_fill().is_some() {
+            if ui.button("Apply") {
+                state.status = match program_data.set_land_colors(state.land_color, state.ocean_color, display) {
+                    Ok(()) => None,
+                    Err(e) => Some(format!("Error: {}", e))
+                };
+            }
+            ui.same_line();
+        }
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
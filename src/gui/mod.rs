@@ -6,147 +6,565 @@
 // (see the LICENSE file for details).
 //
 
-use cgmath::{Rotation, One};
+mod boolean_op;
+mod buffer;
+mod camera_path;
+mod choropleth;
+mod data_download;
+mod day_night;
+mod declination;
+mod earthquakes;
+mod frame_export;
+mod geotiff_export;
+mod hillshading;
+mod image_export;
+mod image_overlay;
+mod main_menu;
+mod map_colors;
+mod netcdf;
+mod overlay_script;
+mod place_search;
+mod plate_boundaries;
+mod presets;
+mod print;
+mod proj_string;
+mod projection_export;
+mod range_rings;
+mod routes;
+mod session;
+mod svg_export;
+mod texture;
+mod time_zones;
+mod tour;
+mod user_markers;
+mod vector_export;
+mod vector_layers;
+mod web_tiles;
+
+pub use data_download::handle_data_download;
+
+use cgmath::One;
 use crate::data;
+use crate::geodesy;
+use crate::geometry;
+use crate::export;
 use crate::views;
-use crate::views::{DragRotation, ViewMode};
+use crate::views::{DragRotation, GraticuleLabels, StereoMode, ViewMode};
 use retain_mut::RetainMut;
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 const MOUSE_WHEEL_ZOOM_FACTOR: f64 = 1.2;
 
+/// Default playback speed of the timeline of time-animated layers (steps per second).
+const DEFAULT_TIMELINE_SPEED: f32 = 2.0;
+
+/// Max. angular distance between consecutive vertices of routes.
+const ROUTE_MAX_STEP: cgmath::Deg<f64> = cgmath::Deg(0.5);
+
+/// Zoom factor applied by `DoubleClickAction::ZoomIn`.
+const DOUBLE_CLICK_ZOOM_FACTOR: f64 = 2.0;
+
+/// Rotation per mouse wheel step for `WheelAction::RotateEW` (at zoom 1.0).
+const WHEEL_ROTATION_STEP: cgmath::Deg<f64> = cgmath::Deg(5.0);
+
+/// Logical size of the magnifier inset (square).
+const MAGNIFIER_SIZE: f32 = 200.0;
+
+const MAGNIFIER_ZOOM: f64 = 4.0;
+
+const MARKER_COLOR: [f32; 4] = [1.0, 0.2, 0.1, 1.0];
+
+const MARKER_RADIUS: f32 = 4.0;
+
+/// Duration (seconds) of the rotation of a view to center it on a double-clicked point.
+const DOUBLE_CLICK_RECENTERING_DURATION: f64 = 0.5;
+
+const MEASUREMENT_COLOR: [f32; 4] = [1.0, 0.85, 0.0, 1.0];
+
+const MEASUREMENT_LINE_WIDTH: f32 = 2.0;
+
+/// Max. distance (logical pixels) the mouse may move between pressing and releasing the button to add a vertex
+/// in the measurement mode.
+const MEASUREMENT_CLICK_TOLERANCE: f32 = 3.0;
+
+/// Length of the head of a vector field's arrow relative to the arrow's length.
+const ARROW_HEAD_FRACTION: f32 = 0.3;
+
+/// Max. length (logical pixels) of the head of a vector field's arrow.
+const ARROW_HEAD_MAX_LENGTH: f32 = 6.0;
+
+const PLACE_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+const PLACE_RADIUS: f32 = 2.5;
+
+/// Places of lower population rank (see `data::Place::rank`) are not shown by default.
+const DEFAULT_MIN_PLACE_RANK: i32 = 12;
+
+const GRATICULE_LABEL_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+const GRATICULE_LABEL_SHADOW_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+/// Views sharing their rotation (and optionally zoom); a change of any of them is applied to the others.
+#[derive(Default)]
+struct LinkedViewsState {
+    /// Unique ids of linked views.
+    views: HashSet<u32>,
+    /// If true, linked views also share their zoom.
+    zoom: bool,
+    /// Rotation and zoom of each linked view as of the last frame.
+    last: HashMap<u32, (views::Rotation, f64)>,
+    /// Rotation and zoom of the most recently changed linked view, taken on by views joining the linked ones.
+    shared: Option<(views::Rotation, f64)>
+}
+
+/// Rotation of a view centering it on a double-clicked point.
+struct Recentering {
+    view_id: u32,
+    /// Longitude and latitude (degrees) of the view's center before recentering.
+    start: [f64; 2],
+    /// Longitude and latitude (degrees) of the new center.
+    end: [f64; 2],
+    /// Time (seconds) since the point was double-clicked.
+    time: f64
+}
+
+#[derive(Default)]
+struct TimelineState {
+    playing: bool,
+    /// If true, playback continues from the first step after the last one.
+    looping: bool,
+    steps_per_second: f32,
+    /// Time of the animation clock at which the current step was shown during playback.
+    step_time: f64,
+    /// Result of the last change of the step.
+    status: Option<String>
+}
+
+#[derive(Default)]
+struct MeasurementState {
+    /// If true, clicks in views add vertices.
+    active: bool,
+    /// Longitudes and latitudes of the vertices (degrees).
+    points: Vec<[f64; 2]>,
+    /// If true, the vertices form a polygon (whose area is measured), otherwise a line.
+    polygon: bool,
+    /// If true, the body's ellipsoid is used, otherwise the sphere of its mean radius.
+    ellipsoidal: bool,
+    /// Position (in the application window) where the left mouse button was pressed over a view.
+    press_pos: Option<[f32; 2]>
+}
+
+/// Values being edited in the oblique aspect popup (in degrees).
+#[derive(Default)]
+struct ObliqueAspectState {
+    pole_longitude: f64,
+    pole_latitude: f64,
+    rotation: f64
+}
+
+/// Lobes being edited in the interruption popup.
+#[derive(Default)]
+struct InterruptionState {
+    lobes: Vec<views::Lobe>,
+    /// Reason why `lobes` cannot be applied (if any).
+    error: Option<String>
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum DoubleClickAction { Nothing, Center, ZoomIn, AddMarker, AddNamedMarker, RouteEndpoint }
+
+impl DoubleClickAction {
+    const ALL: &'static [DoubleClickAction] = &[
+        DoubleClickAction::Nothing,
+        DoubleClickAction::Center,
+        DoubleClickAction::ZoomIn,
+        DoubleClickAction::AddMarker,
+        DoubleClickAction::AddNamedMarker,
+        DoubleClickAction::RouteEndpoint
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            DoubleClickAction::Nothing => "nothing",
+            DoubleClickAction::Center => "center on point",
+            DoubleClickAction::ZoomIn => "zoom in",
+            DoubleClickAction::AddMarker => "add marker",
+            DoubleClickAction::AddNamedMarker => "add named marker (all views)",
+            DoubleClickAction::RouteEndpoint => "set route endpoint"
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum WheelAction { Zoom, RotateEW }
+
+impl WheelAction {
+    const ALL: &'static [WheelAction] = &[WheelAction::Zoom, WheelAction::RotateEW];
+
+    fn name(&self) -> &'static str {
+        match self {
+            WheelAction::Zoom => "zoom",
+            WheelAction::RotateEW => "rotate east-west"
+        }
+    }
+}
+
+/// Actions performed by mouse input over a view.
+struct InputBindings {
+    double_click: DoubleClickAction,
+    wheel: WheelAction
+}
+
+impl Default for InputBindings {
+    fn default() -> InputBindings {
+        InputBindings{
+            double_click: DoubleClickAction::Center,
+            wheel: WheelAction::Zoom
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct GuiState {
     hidpi_factor: f64,
-    mouse_drag_origin: [f32; 2]
+    mouse_drag_origin: [f32; 2],
+    metrics_export: projection_export::MetricsExportState,
+    samples_export: projection_export::SamplesExportState,
+    overlay_script: overlay_script::OverlayScriptState,
+    proj_string: proj_string::ProjStringState,
+    session: session::SessionState,
+    presets: presets::PresetsState,
+    texture: texture::TextureState,
+    vector_layers: vector_layers::VectorLayersState,
+    map_colors: map_colors::MapColorsState,
+    day_night: day_night::DayNightState,
+    hillshading: hillshading::HillshadingState,
+    web_tiles: web_tiles::WebTilesState,
+    image_overlay: image_overlay::ImageOverlayState,
+    scalar_field: netcdf::ScalarFieldState,
+    vector_field: netcdf::VectorFieldState,
+    choropleth: choropleth::ChoroplethState,
+    plate_boundaries: plate_boundaries::PlateBoundariesState,
+    time_zones: time_zones::TimeZonesState,
+    earthquakes: earthquakes::EarthquakesState,
+    declination: declination::DeclinationState,
+    place_search: place_search::PlaceSearchState,
+    timeline: TimelineState,
+    user_markers: user_markers::UserMarkersState,
+    buffer: buffer::BufferState,
+    route: routes::RouteState,
+    range_rings: range_rings::RangeRingsState,
+    measurement: MeasurementState,
+    boolean_op: boolean_op::BooleanOpState,
+    print: print::PrintState,
+    pdf_export: print::PdfExportState,
+    image_export: image_export::ImageExportState,
+    geotiff_export: geotiff_export::GeoTiffExportState,
+    svg_export: svg_export::SvgExportState,
+    vector_export: vector_export::VectorExportState,
+    frame_export: frame_export::FrameExportState,
+    camera_path: camera_path::CameraPathState,
+    linked_views: LinkedViewsState,
+    /// Unique id of the most recently focused view.
+    focused_view: Option<u32>,
+    /// Sizes of views' windows (as of the last frame, or restored from the workspace) by unique id.
+    view_window_sizes: HashMap<u32, [f32; 2]>,
+    input_bindings: InputBindings,
+    oblique_aspect: ObliqueAspectState,
+    interruption: InterruptionState,
+    /// If true, a magnified crop of the view around the cursor is shown.
+    magnifier: bool,
+    /// Places of lower population rank are not shown.
+    min_place_rank: i32,
+    /// Unit of distances shown in views' windows (see `ProgramData::distance_unit`).
+    distance_unit: data::DistanceUnit,
+    /// Position of a named marker to be added (from a double click in a view).
+    new_user_marker: Option<(cgmath::Deg<f64>, cgmath::Deg<f64>)>,
+    /// Recentering of a view on a double-clicked point in progress (if any).
+    recentering: Option<Recentering>,
+    /// See `ProgramData::base_map_names`.
+    base_map_names: Vec<String>,
+    /// Unique id of the view whose base map is to be changed and the new base map's index.
+    base_map_request: Option<(u32, usize)>,
+    /// Unique id of the view and error of the last change of its base map.
+    base_map_error: Option<(u32, String)>,
+    /// Unique id of the view whose image is to be saved.
+    save_image_request: Option<u32>,
+    /// Unique id of the view and error of the last saving of its image.
+    save_image_error: Option<(u32, String)>,
+    /// Running projection tour (if any).
+    tour: Option<tour::TourState>
 }
 
 impl GuiState {
-    pub fn new(hidpi_factor: f64) -> GuiState {
+    pub fn new(
+        hidpi_factor: f64,
+        globe_texture_path: &str,
+        plate_boundaries_path: &str,
+        time_zones_path: &str,
+        base_map_names: Vec<String>
+    ) -> GuiState {
         GuiState{
             hidpi_factor,
+            base_map_names,
+            metrics_export: projection_export::MetricsExportState::new(),
+            pdf_export: print::PdfExportState::new(),
+            image_export: image_export::ImageExportState::new(),
+            geotiff_export: geotiff_export::GeoTiffExportState::new(),
+            svg_export: svg_export::SvgExportState::new(),
+            vector_export: vector_export::VectorExportState::new(),
+            frame_export: frame_export::FrameExportState::new(),
+            min_place_rank: DEFAULT_MIN_PLACE_RANK,
+            choropleth: choropleth::ChoroplethState::new(),
+            plate_boundaries: plate_boundaries::PlateBoundariesState::new(plate_boundaries_path),
+            time_zones: time_zones::TimeZonesState::new(time_zones_path),
+            declination: declination::DeclinationState::new(),
+            image_overlay: image_overlay::ImageOverlayState::new(),
+            earthquakes: earthquakes::EarthquakesState::new(),
+            timeline: TimelineState{
+                steps_per_second: DEFAULT_TIMELINE_SPEED,
+                looping: true,
+                ..Default::default()
+            },
+            session: session::SessionState::new(),
+            texture: texture::TextureState::new(globe_texture_path),
+            buffer: buffer::BufferState::new(),
+            web_tiles: web_tiles::WebTilesState::new(),
+            range_rings: range_rings::RangeRingsState::new(),
+            presets: presets::PresetsState::load(),
             ..Default::default()
         }
     }
+
+    pub fn view_window_sizes(&self) -> &HashMap<u32, [f32; 2]> { &self.view_window_sizes }
+
+    /// Sets the sizes of views' windows (by unique id), used for windows not present in imgui's layout.
+    pub fn set_view_window_sizes(&mut self, sizes: HashMap<u32, [f32; 2]>) { self.view_window_sizes = sizes; }
+}
+
+/// Returns the size of the view's window, if known, or `default`.
+fn view_window_size(gui_state: &GuiState, unique_id: u32, default: [f32; 2]) -> [f32; 2] {
+    gui_state.view_window_sizes.get(&unique_id).copied().unwrap_or(default)
 }
 
-fn handle_main_menu(
+/// Returns true if any view has changed (e.g. is being animated) and the next frame should follow immediately.
+pub fn handle_gui(
     ui: &imgui::Ui,
+    gui_state: &mut GuiState,
     program_data: &mut data::ProgramData,
     renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
     display: &glium::Display
-) {
-    let mut orthographic_clicked = false;
-    let mut stereographic_clicked = false;
-    let mut gnomonic_clicked = false;
-    let mut cylindrical_lambert_clicked = false;
-    let mut about_clicked = false;
-    let mut instructions_clicked = false;
-
-    match ui.begin_main_menu_bar() {
-        None => (),
-        Some(token) => {
-            ui.menu("View", || {
-                ui.menu("New", || {
-                    if ui.menu_item("Orthographic") {
-                        orthographic_clicked = true;
-                    }
-                    if ui.menu_item("Stereographic") {
-                        stereographic_clicked = true;
-                    }
-                    if ui.menu_item("Gnomonic") {
-                        gnomonic_clicked = true;
-                    }
-                    if ui.menu_item("Lambert cylindrical equal-area") {
-                        cylindrical_lambert_clicked = true;
-                    }
+) -> bool {
+    unsafe { imgui::sys::igDockSpaceOverViewport(
+        imgui::sys::igGetMainViewport(),
+        imgui::sys::ImGuiDockNodeFlags_PassthruCentralNode as i32,
+        std::ptr::null()
+    ); }
 
-                });
-            });
+    program_data.animation_clock.tick(ui.io().delta_time as f64);
+    gui_state.distance_unit = program_data.distance_unit;
 
-            ui.menu("Help", || {
-                if ui.menu_item("Instructions...") {
-                    instructions_clicked = true;
-                }
-                if ui.menu_item("About...") {
-                    about_clicked = true;
-                }
-            });
+    main_menu::handle_main_menu(ui, gui_state, program_data, renderer, display);
+    handle_measurement(ui, &mut gui_state.measurement, program_data);
+    web_tiles::follow_view_with_web_tiles(&mut gui_state.web_tiles, gui_state.focused_view, program_data);
+    program_data.poll_web_tiles(display);
+    earthquakes::poll_earthquakes(&mut gui_state.earthquakes, program_data);
+    handle_timeline(ui, &mut gui_state.timeline, program_data, display);
 
-            token.end();
+    if let Some(tour) = &mut gui_state.tour {
+        if !tour::handle_tour(ui, tour, program_data, renderer, display) {
+            gui_state.tour = None;
         }
     }
+    camera_path::handle_camera_path(ui, &mut gui_state.camera_path, gui_state.focused_view, program_data);
+    place_search::animate_recentering(&mut gui_state.place_search, program_data.animation_clock.dt(), program_data);
+    animate_double_click_recentering(&mut gui_state.recentering, program_data.animation_clock.dt(), program_data);
 
-    if orthographic_clicked {
-        program_data.add_orthographic_view(views::OrthographicView::new(
-            program_data, renderer, display
-        ));
-    }
-    if stereographic_clicked {
-        program_data.add_stereographic_view(views::StereographicView::new(
-            program_data, renderer, display
-        ));
+    overlay_script::run_overlay_script(&mut gui_state.overlay_script, program_data, display);
+
+    let exporting_frames = gui_state.frame_export.is_exporting();
+    frame_export::export_frame(&mut gui_state.frame_export, &gui_state.camera_path, program_data);
+
+    program_data.albers_equal_area_views().retain_mut(|view| handle_albers_equal_area_view(ui, gui_state, view));
+    program_data.armadillo_views().retain_mut(|view| handle_armadillo_view(ui, gui_state, view));
+    program_data.conformal_square_views().retain_mut(|view| handle_conformal_square_view(ui, gui_state, view));
+    program_data.cylindrical_lambert_views().retain_mut(|view| handle_cylindrical_lambert_view(ui, gui_state, view));
+    program_data.eckert_views().retain_mut(|view| handle_eckert_view(ui, gui_state, view));
+    program_data.elliptical_views().retain_mut(|view| handle_elliptical_view(ui, gui_state, view));
+    program_data.general_perspective_views().retain_mut(|view| handle_general_perspective_view(ui, gui_state, view));
+    program_data.gnomonic_views().retain_mut(|view| handle_gnomonic_view(ui, gui_state, view));
+    program_data.lambert_azimuthal_views().retain_mut(|view| handle_lambert_azimuthal_view(ui, gui_state, view));
+    program_data.lambert_conformal_conic_views().retain_mut(
+        |view| handle_lambert_conformal_conic_view(ui, gui_state, view)
+    );
+    program_data.littrow_views().retain_mut(|view| handle_littrow_view(ui, gui_state, view));
+    program_data.loximuthal_views().retain_mut(|view| handle_loximuthal_view(ui, gui_state, view));
+    program_data.mercator_views().retain_mut(|view| handle_mercator_view(ui, gui_state, view));
+    program_data.nicolosi_views().retain_mut(|view| handle_nicolosi_view(ui, gui_state, view));
+    program_data.orthographic_views().retain_mut(|view| handle_orthographic_view(ui, gui_state, view));
+    program_data.plugin_views().retain_mut(|view| handle_plugin_view(ui, gui_state, view));
+    program_data.qsc_views().retain_mut(|view| handle_qsc_view(ui, gui_state, view));
+    program_data.stereographic_views().retain_mut(|view| handle_stereographic_view(ui, gui_state, view));
+    program_data.transverse_mercator_views().retain_mut(|view| handle_transverse_mercator_view(ui, gui_state, view));
+    synchronize_linked_views(&mut gui_state.linked_views, gui_state.focused_view, program_data);
+
+    if let Some((longitude, latitude)) = gui_state.new_user_marker.take() {
+        let mut markers = program_data.user_markers().to_vec();
+        markers.push(data::UserMarker{ name: format!("marker {}", markers.len() + 1), longitude, latitude });
+        program_data.set_user_markers(markers);
+        if let Err(e) = user_markers::store_user_markers(program_data) {
+            eprintln!("Failed to store markers: {}", e);
+        }
     }
-    if gnomonic_clicked {
-        program_data.add_gnomonic_view(views::GnomonicView::new(
-            program_data, renderer, display
-        ));
+
+    if let Some((view_id, base_map)) = gui_state.base_map_request.take() {
+        gui_state.base_map_error =
+            program_data.set_view_base_map(view_id, base_map, display).err().map(|e| (view_id, e));
     }
-    if cylindrical_lambert_clicked {
-        program_data.add_cylindrical_lambert_view(views::CylindricalLambertView::new(
-            program_data, renderer, display
-        ));
+
+    if let Some(view_id) = gui_state.save_image_request.take() {
+        if let Some(view) = program_data.all_views_mut().find(|view| view.unique_id() == view_id) {
+            let path = rfd::FileDialog::new()
+                .add_filter("PNG", &["png"])
+                .add_filter("JPEG", &["jpg", "jpeg"])
+                .set_file_name("view.png")
+                .save_file();
+            if let Some(path) = path {
+                let path = path.to_string_lossy().into_owned();
+                gui_state.save_image_error =
+                    export::save_view_image(&path, view).err().map(|e| (view_id, format!("{}: {}", path, e)));
+            }
+        }
     }
 
-    if instructions_clicked {
-        ui.open_popup("Instructions");
-        unsafe { imgui::sys::igSetNextWindowSize(
-            imgui::sys::ImVec2{ x: 600.0, y: 300.0},
-            imgui::sys::ImGuiCond_FirstUseEver as i32
-        ); }
+    // all views have to be queried, so that their flags are cleared
+    program_data.all_views_mut().fold(false, |any_rendered, view| view.take_rendered() || any_rendered)
+        || exporting_frames
+        || gui_state.timeline.playing
+}
+
+/// Applies the rotation (and zoom, if linked) of a linked view changed since the last frame (preferably the focused
+/// one) to all other linked views. Views of the same drag rotation mode share the whole orientation and roll; others
+/// are centered on the same point (see `ViewBase::set_rotation`).
+fn synchronize_linked_views(
+    state: &mut LinkedViewsState,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    let linked: Vec<(u32, views::Rotation, f64)> = program_data.all_views_mut()
+        .filter(|view| state.views.contains(&view.unique_id()))
+        .map(|view| (view.unique_id(), view.rotation(), view.zoom()))
+        .collect();
+    state.views = linked.iter().map(|(id, _, _)| *id).collect(); // drops closed views
+    if linked.is_empty() {
+        state.last.clear();
+        state.shared = None;
+        return;
     }
-    ui.popup_modal("Instructions").build(ui, || {
-        ui.text_wrapped("Within a view window, use the left mouse button to change the orientation of the projected globe. \
-Use the mouse wheel to zoom in/out.\n\n");
-        ui.separator();
-        if ui.button("Close") {
-            ui.close_current_popup();
-        }
-    });
 
-    if about_clicked { ui.open_popup("About"); }
-    ui.popup_modal("About").build(ui, || {
-        ui.text("Map Projections\n
-Copyright (c) 2022 Filip Szczerek (ga.software@yahoo.com)\n\n\
-This program is licensed under MIT license (see LICENSE.txt for details).\n\n");
+    let link_zoom = state.zoom;
+    let last = &state.last;
+    // a view which has just joined has already taken on the shared rotation
+    let changed = |(id, rotation, zoom): &&(u32, views::Rotation, f64)| match last.get(id) {
+        None => false,
+        Some((last_rotation, last_zoom)) => rotation != last_rotation || (link_zoom && zoom != last_zoom)
+    };
+    let source = linked.iter().filter(changed).find(|(id, _, _)| Some(*id) == focused_view)
+        .or_else(|| linked.iter().find(changed));
+
+    match source {
+        Some(&(source_id, rotation, zoom)) => {
+            for view in program_data.all_views_mut() {
+                if view.unique_id() == source_id || !state.views.contains(&view.unique_id()) { continue; }
+                view.set_rotation(&rotation);
+                if link_zoom && view.zoom() != zoom {
+                    view.set_zoom(zoom);
+                }
+            }
+            state.shared = Some((rotation, zoom));
+        },
 
-        ui.separator();
-        if ui.button("Close") {
-            ui.close_current_popup();
+        None => if state.shared.is_none() {
+            state.shared = linked.first().map(|(_, rotation, zoom)| (*rotation, *zoom));
         }
-    });
+    }
+
+    // views of another drag rotation mode than the source's now have rotations of their own
+    state.last = program_data.all_views_mut()
+        .filter(|view| state.views.contains(&view.unique_id()))
+        .map(|view| (view.unique_id(), (view.rotation(), view.zoom())))
+        .collect();
 }
 
-pub fn handle_gui(
+/// Shows the timeline of time-animated layers (if any) and advances it during playback.
+fn handle_timeline(
     ui: &imgui::Ui,
-    gui_state: &mut GuiState,
+    state: &mut TimelineState,
     program_data: &mut data::ProgramData,
-    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
     display: &glium::Display
 ) {
-    unsafe { imgui::sys::igDockSpaceOverViewport(
-        imgui::sys::igGetMainViewport(),
-        imgui::sys::ImGuiDockNodeFlags_PassthruCentralNode as i32,
-        std::ptr::null()
-    ); }
+    let num_steps = program_data.num_time_steps();
+    if num_steps <= 1 {
+        state.playing = false;
+        return;
+    }
 
-    handle_main_menu(ui, program_data, renderer, display);
+    let current_step = program_data.time_step();
+    let mut new_step = None;
+    let time = program_data.animation_clock.time();
+    if state.playing && time - state.step_time >= 1.0 / state.steps_per_second as f64 {
+        if current_step + 1 < num_steps {
+            new_step = Some(current_step + 1);
+        } else if state.looping {
+            new_step = Some(0);
+        } else {
+            state.playing = false;
+        }
+    }
 
-    program_data.cylindrical_lambert_views().retain_mut(|view| handle_cylindrical_lambert_view(ui, gui_state, view));
-    program_data.gnomonic_views().retain_mut(|view| handle_gnomonic_view(ui, gui_state, view));
-    program_data.orthographic_views().retain_mut(|view| handle_orthographic_view(ui, gui_state, view));
-    program_data.stereographic_views().retain_mut(|view| handle_stereographic_view(ui, gui_state, view));
+    imgui::Window::new(ui, "Timeline")
+        .size([420.0, 110.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            if ui.button("|<") { new_step = Some(0); }
+            ui.same_line();
+            if ui.button("<") { new_step = Some(current_step.saturating_sub(1)); }
+            ui.same_line();
+            if ui.button(if state.playing { "Pause" } else { "Play" }) {
+                state.playing = !state.playing;
+                state.step_time = time;
+            }
+            ui.same_line();
+            if ui.button(">") { new_step = Some((current_step + 1).min(num_steps - 1)); }
+            ui.same_line();
+            if ui.button(">|") { new_step = Some(num_steps - 1); }
+            ui.same_line();
+            ui.checkbox("loop", &mut state.looping);
+
+            let mut step = current_step as i32 + 1;
+            let _width = ui.push_item_width(200.0);
+            if imgui::Slider::new("step", 1, num_steps as i32).build(ui, &mut step) {
+                new_step = Some((step.max(1) - 1) as usize);
+            }
+            ui.same_line();
+            ui.text(program_data.time_step_label());
+            if imgui::Slider::new("steps per second", 0.1, 30.0).build(ui, &mut state.steps_per_second) {
+                state.steps_per_second = state.steps_per_second.max(0.1);
+            }
+            if let Some(status) = &state.status {
+                ui.text_wrapped(status);
+            }
+        });
+
+    if let Some(step) = new_step {
+        state.step_time = time;
+        state.status = None;
+        if let Err(e) = program_data.set_time_step(step, display) {
+            state.status = Some(format!("Error: {}", e));
+            state.playing = false;
+        }
+    }
 }
 
 struct AdjustedImageSize {
@@ -202,21 +620,92 @@ fn adjust_pos_for_exact_hidpi_scaling(
 }
 
 fn handle_view_common(ui: &imgui::Ui, gui_state: &mut GuiState, view: &mut views::ViewBase) {
+    if ui.is_window_focused() {
+        gui_state.focused_view = Some(view.unique_id());
+    }
+    gui_state.view_window_sizes.insert(view.unique_id(), ui.window_size());
+
+    handle_projection_params(ui, gui_state.distance_unit, view);
+    handle_stretch(ui, view);
+    handle_truncation(ui, view);
+
     ui.button("reset");
     if ui.is_item_active() {
         view.set_orientation(cgmath::Basis3::one());
+        view.set_roll(cgmath::Rad(0.0));
     }
     if ui.is_item_hovered() {
         ui.tooltip_text("Reset view to default orientation");
     }
     ui.same_line();
+    if ui.button("random") {
+        view.set_orientation(views::random_orientation());
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Set a random orientation (uniformly distributed over all orientations)");
+    }
+    ui.same_line();
+    if ui.button("aspect") {
+        let (pole_longitude, pole_latitude, rotation) = view.oblique_aspect();
+        gui_state.oblique_aspect = ObliqueAspectState{
+            pole_longitude: pole_longitude.0,
+            pole_latitude: pole_latitude.0,
+            rotation: rotation.0
+        };
+        ui.open_popup("oblique aspect");
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Set the projection's pole (oblique aspect) numerically");
+    }
+    ui.popup("oblique aspect", || handle_oblique_aspect(ui, &mut gui_state.oblique_aspect, view));
+    ui.same_line();
+    if ui.button("save image") {
+        gui_state.save_image_request = Some(view.unique_id());
+    }
+    if ui.is_item_hovered() {
+        match &gui_state.save_image_error {
+            Some((view_id, error)) if *view_id == view.unique_id() => ui.tooltip_text(format!("Error: {}", error)),
+            _ => ui.tooltip_text("Save the view (without labels and markers) as PNG or JPEG")
+        }
+    }
+    ui.same_line();
 
     unsafe { imgui::sys::igSeparatorEx(imgui::sys::ImGuiSeparatorFlags_Vertical as i32); }
     ui.same_line();
 
-    if ui.checkbox("graticule", &mut view.draw_graticule) {
-        view.refresh();
+    if ui.button("layers") {
+        ui.open_popup("layers");
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Layers shown in this view");
+    }
+    ui.popup("layers", || handle_layer_overrides(ui, view));
+    ui.same_line();
+    if let Some(lobes) = view.lobes() {
+        if ui.button("interrupt") {
+            gui_state.interruption = InterruptionState{ lobes: lobes.to_vec(), error: None };
+            ui.open_popup("interruption");
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Interrupt the map (split it into lobes)");
+        }
+        ui.popup("interruption", || handle_interruption(ui, &mut gui_state.interruption, view));
+        ui.same_line();
     }
+    {
+        const LABELS: [GraticuleLabels; 3] =
+            [GraticuleLabels::Off, GraticuleLabels::Horizontal, GraticuleLabels::AlongLines];
+        let _width = ui.push_item_width(100.0);
+        let mut labels_idx = LABELS.iter().position(|l| *l == view.graticule_labels).unwrap();
+        if ui.combo_simple_string("labels##graticule", &mut labels_idx, &["off", "horizontal", "along lines"]) {
+            view.graticule_labels = LABELS[labels_idx];
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Graticule labels");
+        }
+    }
+    ui.same_line();
+    handle_graticule_spacing(ui, view);
     ui.same_line();
 
     unsafe { imgui::sys::igSeparatorEx(imgui::sys::ImGuiSeparatorFlags_Vertical as i32); }
@@ -230,6 +719,20 @@ fn handle_view_common(ui: &imgui::Ui, gui_state: &mut GuiState, view: &mut views
         view.set_view_mode(ViewMode::VectorMap);
     }
     ui.same_line();
+    if gui_state.base_map_names.len() > 1 {
+        let _width = ui.push_item_width(120.0);
+        let mut base_map = view.base_map();
+        if ui.combo_simple_string("##base_map", &mut base_map, &gui_state.base_map_names) {
+            gui_state.base_map_request = Some((view.unique_id(), base_map));
+        }
+        if ui.is_item_hovered() {
+            match &gui_state.base_map_error {
+                Some((view_id, error)) if *view_id == view.unique_id() => ui.tooltip_text(format!("Error: {}", error)),
+                _ => ui.tooltip_text("Base map shown in the texture mode")
+            }
+        }
+        ui.same_line();
+    }
 
     unsafe { imgui::sys::igSeparatorEx(imgui::sys::ImGuiSeparatorFlags_Vertical as i32); }
     ui.same_line();
@@ -243,6 +746,42 @@ fn handle_view_common(ui: &imgui::Ui, gui_state: &mut GuiState, view: &mut views
     if ui.radio_button_bool("free##1", view.drag_rotation() == DragRotation::Free) {
         view.set_drag_rotation(DragRotation::Free);
     }
+    ui.same_line();
+    {
+        let _width = ui.push_item_width(80.0);
+        let mut roll = cgmath::Deg::from(view.roll()).0;
+        if imgui::Drag::new("roll").range(-180.0, 180.0).speed(0.5).display_format("%.1f°").build(ui, &mut roll) {
+            view.set_roll(cgmath::Deg(roll).into());
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Rotation of the map about the view's center (also: Shift + drag)");
+        }
+    }
+    ui.same_line();
+    let mut linked = gui_state.linked_views.views.contains(&view.unique_id());
+    if ui.checkbox("link", &mut linked) {
+        if linked {
+            gui_state.linked_views.views.insert(view.unique_id());
+            // join the other linked views' rotation
+            if let Some((rotation, zoom)) = gui_state.linked_views.shared {
+                view.set_rotation(&rotation);
+                if gui_state.linked_views.zoom { view.set_zoom(zoom); }
+            }
+        } else {
+            gui_state.linked_views.views.remove(&view.unique_id());
+        }
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Rotate linked views together (and zoom them, if \"View/Link zoom\" is selected)");
+    }
+    if view.has_markers() {
+        ui.same_line();
+        unsafe { imgui::sys::igSeparatorEx(imgui::sys::ImGuiSeparatorFlags_Vertical as i32); }
+        ui.same_line();
+        if ui.button("clear markers") {
+            view.clear_markers();
+        }
+    }
 
     let hidpi_f = gui_state.hidpi_factor as f32;
 
@@ -259,17 +798,65 @@ fn handle_view_common(ui: &imgui::Ui, gui_state: &mut GuiState, view: &mut views
     imgui::Image::new(view.draw_buf_id(), adjusted.logical_size).build(ui);
 
     let mouse_pos_in_app_window = ui.io().mouse_pos;
+    // mouse position in normalized device coordinates (if the mouse is over the view)
+    let mut hovered_position = None;
     if ui.is_item_clicked_with_button(imgui::MouseButton::Left) {
         gui_state.mouse_drag_origin = [
             mouse_pos_in_app_window[0] - img_pos_in_app_window[0],
             mouse_pos_in_app_window[1] - img_pos_in_app_window[1]
         ];
+        gui_state.measurement.press_pos = Some(mouse_pos_in_app_window);
     }
     if ui.is_item_hovered() {
+        let mouse_pos = [
+            mouse_pos_in_app_window[0] - img_pos_in_app_window[0],
+            mouse_pos_in_app_window[1] - img_pos_in_app_window[1]
+        ];
+        let position = cgmath::Point2{
+            x: (-1.0 + 2.0 * mouse_pos[0] / adjusted.logical_size[0]) as f64,
+            y: (1.0 - 2.0 * mouse_pos[1] / adjusted.logical_size[1]) as f64
+        };
+        hovered_position = Some(position);
+
+        if gui_state.magnifier && view.stereo_mode() != StereoMode::SideBySide {
+            let id = view.render_magnifier(position, MAGNIFIER_ZOOM, (MAGNIFIER_SIZE * hidpi_f) as u32);
+            ui.tooltip(|| imgui::Image::new(id, [MAGNIFIER_SIZE, MAGNIFIER_SIZE]).build(ui));
+        }
+
         let wheel = ui.io().mouse_wheel;
         if wheel != 0.0 {
-            let zoom_factor = MOUSE_WHEEL_ZOOM_FACTOR.powf(wheel as f64);
-            view.zoom_by(zoom_factor);
+            match gui_state.input_bindings.wheel {
+                WheelAction::Zoom => view.zoom_by(MOUSE_WHEEL_ZOOM_FACTOR.powf(wheel as f64)),
+                WheelAction::RotateEW => view.rotate_ew(
+                    cgmath::Rad::from(WHEEL_ROTATION_STEP) * wheel as f64 / view.zoom()
+                )
+            }
+        }
+
+        if ui.is_mouse_double_clicked(imgui::MouseButton::Left) {
+            on_double_click(
+                gui_state.input_bindings.double_click,
+                view,
+                position,
+                &mut gui_state.new_user_marker,
+                &mut gui_state.recentering,
+                &mut gui_state.route
+            );
+        }
+
+        if gui_state.measurement.active && ui.is_mouse_released(imgui::MouseButton::Left) {
+            let measurement = &mut gui_state.measurement;
+            // only a click adds a vertex; dragging still rotates the view
+            let is_click = measurement.press_pos.take().map_or(false, |press_pos| {
+                (press_pos[0] - mouse_pos_in_app_window[0]).hypot(press_pos[1] - mouse_pos_in_app_window[1])
+                    <= MEASUREMENT_CLICK_TOLERANCE
+            });
+            if let Some((longitude, latitude)) = view.unproject(position).filter(|_| is_click) {
+                let point = [longitude.0, latitude.0];
+                if measurement.points.last() != Some(&point) {
+                    measurement.points.push(point);
+                }
+            }
         }
 
         if ui.is_mouse_dragging(imgui::MouseButton::Left) {
@@ -285,7 +872,15 @@ fn handle_view_common(ui: &imgui::Ui, gui_state: &mut GuiState, view: &mut views
                     drag_start[1] - 2.0 * delta[1] / adjusted.logical_size[1]
                 ];
 
-                view.rotate_by_dragging(drag_start, drag_end);
+                if ui.io().key_shift {
+                    view.roll_by_dragging(drag_start, drag_end);
+                } else {
+                    view.rotate_by_dragging(drag_start, drag_end);
+                }
+                // dragging takes over from the recentering
+                if gui_state.recentering.as_ref().map_or(false, |r| r.view_id == view.unique_id()) {
+                    gui_state.recentering = None;
+                }
             }
             ui.reset_mouse_drag_delta(imgui::MouseButton::Left);
             gui_state.mouse_drag_origin = [
@@ -295,74 +890,1192 @@ fn handle_view_common(ui: &imgui::Ui, gui_state: &mut GuiState, view: &mut views
         }
     }
 
+    // labels and markers are positioned for a single image, so they are not shown in stereo side-by-side mode
+    if view.stereo_mode() != StereoMode::SideBySide {
+        if view.graticule_labels != GraticuleLabels::Off {
+            draw_graticule_labels(ui, view, img_pos_in_app_window, adjusted.logical_size);
+        }
+        if view.has_places() && view.is_layer_visible(views::Layer::Places) {
+            draw_places(ui, view, gui_state.min_place_rank, img_pos_in_app_window, adjusted.logical_size);
+        }
+        let time_zones_visible = view.is_layer_visible(views::Layer::TimeZones);
+        if gui_state.time_zones.labels && view.has_time_zone_labels() && time_zones_visible {
+            draw_time_zone_labels(ui, view, img_pos_in_app_window, adjusted.logical_size);
+        }
+        if view.has_point_layers() && view.is_layer_visible(views::Layer::VectorLayers) {
+            draw_point_layers(ui, view, img_pos_in_app_window, adjusted.logical_size);
+        }
+        if view.vector_field().is_some() {
+            draw_vector_field(ui, view, img_pos_in_app_window, adjusted.logical_size);
+        }
+        if gui_state.choropleth.legend {
+            if let Some(choropleth) = view.choropleth() {
+                choropleth::draw_choropleth_legend(
+                    ui, &choropleth.legend, img_pos_in_app_window, adjusted.logical_size
+                );
+            }
+        }
+        if (view.has_markers() || view.has_user_markers()) && view.is_layer_visible(views::Layer::Markers) {
+            draw_markers(ui, view, img_pos_in_app_window, adjusted.logical_size);
+        }
+        place_search::draw_found_place(
+            ui, view, &gui_state.place_search, img_pos_in_app_window, adjusted.logical_size
+        );
+        if !gui_state.measurement.points.is_empty() {
+            draw_measurement(ui, view, &gui_state.measurement, img_pos_in_app_window, adjusted.logical_size);
+        }
+    }
+
     ui.set_cursor_pos(image_start_pos);
     let _disabled = ui.begin_disabled(true);
     let _token1 = ui.push_style_color(imgui::StyleColor::Text, [0.0, 0.0, 0.0, 1.0]);
     let _token2 = ui.push_style_color(imgui::StyleColor::Button, [1.0, 1.0, 1.0, 0.8]);
 
-    let dir_of_lonlat00 = cgmath::Vector3{ x: 1.0, y: 0.0, z: 0.0 };
-    let reor = view.orientation().invert().as_ref() * dir_of_lonlat00;
-
-    let central_longitude = if reor.x > 0.0 {
-        cgmath::Deg::from(cgmath::Rad((reor.y / reor.z.asin().cos()).asin())).0
-    } else {
-        cgmath::Deg::from(cgmath::Rad(reor.y.signum() * std::f64::consts::PI - (reor.y / reor.z.asin().cos()).asin())).0
-    };
-    let central_latitude = cgmath::Deg::from(cgmath::Rad(reor.z.asin())).0;
+    let (central_longitude, central_latitude) = view.central_lonlat();
+    let (central_longitude, central_latitude) = (central_longitude.0, central_latitude.0);
 
     let lon_str = format!("{:.1}° {}", central_longitude.abs(), if central_longitude >= 0.0 { "E" } else { "W" });
     let lat_str = format!("{:.1}° {}", central_latitude.abs(), if central_latitude >= 0.0 { "N" } else { "S" });
     ui.small_button(&format!("{} {}", lon_str, lat_str));
+
+    if let Some((longitude, latitude)) = hovered_position.and_then(|position| view.unproject(position)) {
+        let (longitude, latitude) = (longitude.0, latitude.0);
+        ui.same_line();
+        ui.small_button(&format!(
+            "cursor: {:.2}° {} {:.2}° {}",
+            longitude.abs(), if longitude >= 0.0 { "E" } else { "W" },
+            latitude.abs(), if latitude >= 0.0 { "N" } else { "S" }
+        ));
+    }
+
+    if let Some(grid_coordinates) = hovered_position.and_then(|position| view.grid_coordinates(position)) {
+        ui.same_line();
+        ui.small_button(&format!("E {:.0} m  N {:.0} m", grid_coordinates.x, grid_coordinates.y));
+    }
 }
 
-/// Returns `false` if view should be deleted.
-fn handle_cylindrical_lambert_view(
-    ui: &imgui::Ui,
-    gui_state: &mut GuiState,
-    view: &mut views::CylindricalLambertView
-) -> bool {
-    let mut opened = true;
+/// Shows controls of the projection parameters declared by the view (if any), in a single row (grid parameters
+/// in another one).
+fn handle_projection_params(ui: &imgui::Ui, distance_unit: data::DistanceUnit, view: &mut views::ViewBase) {
+    let num_params = view.projection_params().len();
+    if num_params == 0 { return; }
 
-    imgui::Window::new(ui, &format!("Lambert cylindrical###cylindrical_lambert_{}", view.unique_id()))
-        .size([640.0, 320.0], imgui::Condition::FirstUseEver)
-        .opened(&mut opened)
-        .build(|| {
-            handle_view_common(ui, gui_state, view.base_mut());
+    let _width = ui.push_item_width(200.0);
+    for idx in 0..num_params {
+        let mut param = view.projection_params()[idx];
+        if param.unit == views::ParamUnit::Flag {
+            let mut checked = param.is_set();
+            if ui.checkbox(param.label, &mut checked) {
+                view.set_projection_param_value(idx, if checked { 1.0 } else { 0.0 });
+            }
+        } else if param.unit == views::ParamUnit::Kilometers {
+            // shown in the chosen unit, on a logarithmic scale (e.g. altitudes from low orbits to beyond the Moon)
+            let mut value = distance_unit.from_km(param.value);
+            if imgui::Drag::new(param.label)
+                .range(distance_unit.from_km(param.min), distance_unit.from_km(param.max))
+                .speed(param.speed)
+                .display_format(&format!("%.0f {}", distance_unit.symbol()))
+                .flags(imgui::SliderFlags::LOGARITHMIC)
+                .build(ui, &mut value) {
+                view.set_projection_param_value(idx, distance_unit.to_km(value));
+            }
+        } else if imgui::Drag::new(param.label)
+            .range(param.min, param.max)
+            .speed(param.speed)
+            .display_format(param.display_format())
+            .build(ui, &mut param.value) {
+            view.set_projection_param_value(idx, param.value);
         }
-    );
-
-    opened
+        if ui.is_item_hovered() && !param.tooltip.is_empty() {
+            if param.unit == views::ParamUnit::Kilometers {
+                let body = view.body();
+                ui.tooltip_text(format!(
+                    "{} (mean radius of the {}: {:.0} {})",
+                    param.tooltip, body.name, distance_unit.from_km(body.mean_radius), distance_unit.symbol()
+                ));
+            } else {
+                ui.tooltip_text(param.tooltip);
+            }
+        }
+        // grid parameters (starting with the scale factor) are shown in a separate row
+        if idx + 1 < num_params && view.projection_params()[idx + 1].unit != views::ParamUnit::ScaleFactor {
+            ui.same_line();
+        }
+    }
 }
 
-/// Returns `false` if view should be deleted.
-fn handle_gnomonic_view(
-    ui: &imgui::Ui,
-    gui_state: &mut GuiState,
-    view: &mut views::GnomonicView
-) -> bool {
-    let mut opened = true;
+fn handle_stretch(ui: &imgui::Ui, view: &mut views::ViewBase) {
+    let mut stretch = match view.stretch() {
+        Some(stretch) => stretch,
+        None => return
+    };
 
-    imgui::Window::new(ui, &format!("Gnomonic###gnomonic_{}", view.unique_id()))
-        .size([640.0, 640.0], imgui::Condition::FirstUseEver)
-        .opened(&mut opened)
-        .build(|| {
-            handle_view_common(ui, gui_state, view.base_mut());
-        }
-    );
+    let _width = ui.push_item_width(150.0);
+    let mut changed = false;
+    for (idx, label) in ["horizontal stretch", "vertical stretch"].iter().enumerate() {
+        changed |= imgui::Drag::new(*label)
+            .range(views::MIN_STRETCH, views::MAX_STRETCH)
+            .speed(0.005)
+            .display_format("%.3f")
+            .build(ui, &mut stretch[idx]);
+        ui.same_line();
+    }
+    if ui.button("1:1") {
+        stretch = [1.0, 1.0];
+        changed = true;
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Reset the stretch");
+    }
+    ui.same_line();
+    ui.text(&format!("aspect: {:.3}", stretch[0] / stretch[1]));
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Ratio of the horizontal and vertical stretch");
+    }
 
-    opened
+    if changed {
+        view.set_stretch(stretch);
+    }
 }
 
-/// Returns `false` if view should be deleted.
-fn handle_orthographic_view(
-    ui: &imgui::Ui,
-    gui_state: &mut GuiState,
-    view: &mut views::OrthographicView
-) -> bool {
-    let mut opened = true;
+fn handle_truncation(ui: &imgui::Ui, view: &mut views::ViewBase) {
+    if !view.supports_truncation() { return; }
+
+    let mut truncated = view.truncation().is_some();
+    let mut latitude = view.truncation().unwrap_or(cgmath::Deg(85.0)).0;
+
+    let mut changed = ui.checkbox("truncate", &mut truncated);
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Hide the map beyond the specified latitude north and south");
+    }
+    if truncated {
+        ui.same_line();
+        let _width = ui.push_item_width(150.0);
+        changed |= imgui::Drag::new("max. latitude")
+            .range(views::MIN_TRUNCATION.0, 90.0)
+            .speed(0.1)
+            .display_format("%.1f°")
+            .build(ui, &mut latitude);
+    }
+
+    if changed {
+        view.set_truncation(if truncated { Some(cgmath::Deg(latitude)) } else { None });
+    }
+}
+
+fn handle_oblique_aspect(ui: &imgui::Ui, state: &mut ObliqueAspectState, view: &mut views::ViewBase) {
+    ui.text("Point of the globe placed at the projection's north pole, and rotation around it:");
+
+    let _width = ui.push_item_width(150.0);
+    let mut changed = imgui::Drag::new("pole latitude")
+        .range(-90.0, 90.0)
+        .speed(0.2)
+        .display_format("%.2f°")
+        .build(ui, &mut state.pole_latitude);
+    changed |= imgui::Drag::new("pole longitude")
+        .range(-180.0, 180.0)
+        .speed(0.2)
+        .display_format("%.2f°")
+        .build(ui, &mut state.pole_longitude);
+    changed |= imgui::Drag::new("rotation")
+        .range(-180.0, 180.0)
+        .speed(0.2)
+        .display_format("%.2f°")
+        .build(ui, &mut state.rotation);
+
+    if changed {
+        view.set_oblique_aspect(
+            cgmath::Deg(state.pole_longitude),
+            cgmath::Deg(state.pole_latitude),
+            cgmath::Deg(state.rotation)
+        );
+    }
+
+    if ui.button("normal aspect") {
+        *state = ObliqueAspectState{ pole_latitude: 90.0, ..Default::default() };
+        view.set_orientation(cgmath::Basis3::one());
+    }
+}
+
+fn handle_interruption(ui: &imgui::Ui, state: &mut InterruptionState, view: &mut views::ViewBase) {
+    let mut changed = false;
+
+    if ui.button("none") {
+        state.lobes.clear();
+        changed = true;
+    }
+    for preset in views::InterruptionPreset::ALL {
+        ui.same_line();
+        if ui.button(preset.name()) {
+            state.lobes = preset.lobes();
+            changed = true;
+        }
+    }
+
+    ui.text("Lobes (longitudes in degrees):");
+    let _width = ui.push_item_width(100.0);
+    let mut removed = None;
+    for (idx, lobe) in state.lobes.iter_mut().enumerate() {
+        let _id = ui.push_id(idx as i32);
+        for (label, value, tooltip) in [
+            ("west", &mut lobe.west.0, "Western boundary"),
+            ("east", &mut lobe.east.0, "Eastern boundary"),
+            ("center", &mut lobe.central_meridian.0, "Central meridian")
+        ] {
+            changed |= imgui::Drag::new(label)
+                .range(-180.0, 180.0)
+                .speed(0.2)
+                .display_format("%.1f°")
+                .build(ui, value);
+            if ui.is_item_hovered() {
+                ui.tooltip_text(tooltip);
+            }
+            ui.same_line();
+        }
+        let mut hemisphere_idx = views::Hemisphere::ALL.iter().position(|h| *h == lobe.hemisphere).unwrap();
+        let names = views::Hemisphere::ALL.map(|h| h.name());
+        if ui.combo_simple_string("hemisphere", &mut hemisphere_idx, &names) {
+            lobe.hemisphere = views::Hemisphere::ALL[hemisphere_idx];
+            changed = true;
+        }
+        ui.same_line();
+        if ui.button("remove") {
+            removed = Some(idx);
+        }
+    }
+    if let Some(idx) = removed {
+        state.lobes.remove(idx);
+        changed = true;
+    }
+
+    if state.lobes.len() < views::MAX_LOBES && ui.button("add lobe") {
+        state.lobes.push(views::Lobe{
+            central_meridian: cgmath::Deg(0.0),
+            west: cgmath::Deg(-180.0),
+            east: cgmath::Deg(180.0),
+            hemisphere: views::Hemisphere::Both
+        });
+        changed = true;
+    }
+
+    if changed {
+        state.error = view.set_lobes(state.lobes.clone()).err();
+    }
+    if let Some(error) = &state.error {
+        ui.text_colored([1.0, 0.3, 0.3, 1.0], &format!("Not applied: {}.", error));
+    }
+}
+
+fn handle_layer_overrides(ui: &imgui::Ui, view: &mut views::ViewBase) {
+    let mut overrides = view.layer_overrides();
+    let mut changed = false;
+
+    let _width = ui.push_item_width(120.0);
+    for layer in views::Layer::ALL {
+        let default_name = if view.global_layers().is_visible(layer) { "default (on)" } else { "default (off)" };
+        let mut idx = match overrides.get(layer) {
+            None => 0,
+            Some(true) => 1,
+            Some(false) => 2
+        };
+        if ui.combo_simple_string(layer.name(), &mut idx, &[default_name, "on", "off"]) {
+            overrides.set(layer, match idx { 1 => Some(true), 2 => Some(false), _ => None });
+            changed = true;
+        }
+    }
+
+    if changed {
+        view.set_layer_overrides(overrides);
+    }
+}
+
+/// `position` is given in normalized device coordinates; a named marker to be added is stored in `new_user_marker`,
+/// the rotation centering the view on the clicked point in `recentering`.
+fn on_double_click(
+    action: DoubleClickAction,
+    view: &mut views::ViewBase,
+    position: cgmath::Point2<f64>,
+    new_user_marker: &mut Option<(cgmath::Deg<f64>, cgmath::Deg<f64>)>,
+    recentering: &mut Option<Recentering>,
+    route: &mut routes::RouteState
+) {
+    match action {
+        DoubleClickAction::Nothing => (),
+
+        DoubleClickAction::Center => if let Some((longitude, latitude)) = view.unproject(position) {
+            let (start_lon, start_lat) = view.central_lonlat();
+            *recentering = Some(Recentering{
+                view_id: view.unique_id(),
+                start: [start_lon.0, start_lat.0],
+                end: [longitude.0, latitude.0],
+                time: 0.0
+            });
+        },
+
+        DoubleClickAction::ZoomIn => view.zoom_by(DOUBLE_CLICK_ZOOM_FACTOR),
+
+        DoubleClickAction::AddMarker => if let Some((longitude, latitude)) = view.unproject(position) {
+            view.add_marker(longitude, latitude);
+        },
+
+        DoubleClickAction::AddNamedMarker => if let Some(lonlat) = view.unproject(position) {
+            *new_user_marker = Some(lonlat);
+        },
+
+        DoubleClickAction::RouteEndpoint => if let Some((longitude, latitude)) = view.unproject(position) {
+            route.set_next_endpoint([longitude.0, latitude.0]);
+        }
+    }
+}
+
+/// Converts normalized device coordinates to screen coordinates within the view's image located at `image_pos`.
+fn ndc_to_screen(p: cgmath::Point2<f64>, image_pos: [f32; 2], image_size: [f32; 2]) -> [f32; 2] {
+    [
+        image_pos[0] + (p.x as f32 + 1.0) / 2.0 * image_size[0],
+        image_pos[1] + (1.0 - p.y as f32) / 2.0 * image_size[1]
+    ]
+}
+
+fn draw_markers(ui: &imgui::Ui, view: &views::ViewBase, image_pos: [f32; 2], image_size: [f32; 2]) {
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect_intersect(
+        image_pos,
+        [image_pos[0] + image_size[0], image_pos[1] + image_size[1]],
+        || {
+            for marker in view.projected_markers() {
+                let center = ndc_to_screen(marker, image_pos, image_size);
+                draw_list.add_circle(center, MARKER_RADIUS, MARKER_COLOR).filled(true).build();
+                draw_list.add_circle(center, MARKER_RADIUS, GRATICULE_LABEL_SHADOW_COLOR).build();
+            }
+            for (position, marker) in view.projected_user_markers() {
+                let center = ndc_to_screen(position, image_pos, image_size);
+                draw_list.add_circle(center, MARKER_RADIUS, MARKER_COLOR).filled(true).build();
+                draw_list.add_circle(center, MARKER_RADIUS, GRATICULE_LABEL_SHADOW_COLOR).build();
+                let name_width = ui.calc_text_size(&marker.name)[0];
+                let name_center = [center[0] + 2.0 * MARKER_RADIUS + name_width / 2.0, center[1]];
+                draw_label_text(ui, &draw_list, &marker.name, name_center, 0.0);
+            }
+        }
+    );
+}
+
+/// Advances by `dt` seconds the rotation of a view towards a double-clicked point (see `Recentering`).
+fn animate_double_click_recentering(
+    recentering: &mut Option<Recentering>,
+    dt: f64,
+    program_data: &mut data::ProgramData
+) {
+    let state = match recentering {
+        Some(state) => state,
+        None => return
+    };
+    let view = match program_data.all_views_mut().find(|view| view.unique_id() == state.view_id) {
+        Some(view) => view,
+        None => { *recentering = None; return; } // closed by the user
+    };
+
+    state.time += dt;
+    center_between(view, state.start, state.end, state.time / DOUBLE_CLICK_RECENTERING_DURATION);
+    if state.time >= DOUBLE_CLICK_RECENTERING_DURATION {
+        *recentering = None;
+    }
+}
+
+/// Centers `view` on the point at fraction `t` (clamped to 1; eased in and out) of the great circle route
+/// from `start` to `end` (longitude and latitude in degrees).
+fn center_between(view: &mut views::ViewBase, start: [f64; 2], end: [f64; 2], t: f64) {
+    let t = t.min(1.0);
+    let t = t * t * (3.0 - 2.0 * t);
+    let [lon, lat] = geometry::great_circle_point(start, end, t);
+    view.center_on(cgmath::Deg(lon), cgmath::Deg(lat));
+}
+
+/// Shows controls of longitude and latitude (degrees) of `point`, which can also be taken from one of `markers`.
+fn handle_point_input(ui: &imgui::Ui, id: &str, point: &mut [f64; 2], markers: &[data::UserMarker]) {
+    let _width = ui.push_item_width(100.0);
+    imgui::Drag::new(&format!("##{}_lon", id)).range(-180.0, 180.0).speed(0.1).display_format("%.4f°")
+        .build(ui, &mut point[0]);
+    ui.same_line();
+    imgui::Drag::new(&format!("##{}_lat", id)).range(-90.0, 90.0).speed(0.1).display_format("%.4f°")
+        .build(ui, &mut point[1]);
+    if !markers.is_empty() {
+        let mut marker_names = vec!["(marker)"];
+        marker_names.extend(markers.iter().map(|marker| marker.name.as_str()));
+        ui.same_line();
+        let _width = ui.push_item_width(150.0);
+        let mut marker_idx = 0;
+        if ui.combo_simple_string(&format!("##{}_marker", id), &mut marker_idx, &marker_names) && marker_idx > 0 {
+            let marker = &markers[marker_idx - 1];
+            *point = [marker.longitude.0, marker.latitude.0];
+        }
+    }
+}
+
+/// Draws symbols of the point layers; labels overlapping those drawn earlier are omitted.
+fn draw_point_layers(ui: &imgui::Ui, view: &views::ViewBase, image_pos: [f32; 2], image_size: [f32; 2]) {
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect_intersect(
+        image_pos,
+        [image_pos[0] + image_size[0], image_pos[1] + image_size[1]],
+        || {
+            // screen rectangles (min. x, min. y, max. x, max. y) of the drawn labels
+            let mut labels: Vec<[f32; 4]> = vec![];
+            for (position, layer, point) in view.projected_point_layers() {
+                let center = ndc_to_screen(position, image_pos, image_size);
+                let (radius, color) = layer.symbol(point);
+                draw_list.add_circle(center, radius, color).filled(true).build();
+                draw_list.add_circle(center, radius, GRATICULE_LABEL_SHADOW_COLOR).build();
+
+                if let Some(label) = point.label.as_ref().filter(|_| layer.style.labels) {
+                    let size = ui.calc_text_size(label);
+                    let rect = [
+                        center[0] + radius + PLACE_RADIUS,
+                        center[1] - size[1] / 2.0,
+                        center[0] + radius + PLACE_RADIUS + size[0],
+                        center[1] + size[1] / 2.0
+                    ];
+                    let overlaps = |r: &[f32; 4]| {
+                        rect[0] < r[2] && r[0] < rect[2] && rect[1] < r[3] && r[1] < rect[3]
+                    };
+                    if !labels.iter().any(overlaps) {
+                        draw_label_text(ui, &draw_list, label, [(rect[0] + rect[2]) / 2.0, center[1]], 0.0);
+                        labels.push(rect);
+                    }
+                }
+            }
+        }
+    );
+}
+
+/// Draws the arrows of the vector field, centered at their positions.
+fn draw_vector_field(ui: &imgui::Ui, view: &views::ViewBase, image_pos: [f32; 2], image_size: [f32; 2]) {
+    let params = match view.vector_field() {
+        Some(layer) => layer.params,
+        None => return
+    };
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect_intersect(
+        image_pos,
+        [image_pos[0] + image_size[0], image_pos[1] + image_size[1]],
+        || {
+            for (position, ahead, magnitude) in view.projected_vector_field() {
+                let center = ndc_to_screen(position, image_pos, image_size);
+                let ahead = ndc_to_screen(ahead, image_pos, image_size);
+                let direction = [ahead[0] - center[0], ahead[1] - center[1]];
+                let norm = direction[0].hypot(direction[1]);
+                if norm == 0.0 { continue; }
+                let direction = [direction[0] / norm, direction[1] / norm];
+
+                let length = magnitude * params.scale;
+                if length < 1.0 { continue; }
+                let tail = [center[0] - direction[0] * length / 2.0, center[1] - direction[1] * length / 2.0];
+                let tip = [center[0] + direction[0] * length / 2.0, center[1] + direction[1] * length / 2.0];
+                draw_list.add_line(tail, tip, params.color).build();
+
+                let head = (length * ARROW_HEAD_FRACTION).min(ARROW_HEAD_MAX_LENGTH);
+                for side in [-1.0, 1.0] {
+                    // head lines at 30° to the shaft
+                    let (sin, cos) = (side * 0.5, 0.75f32.sqrt());
+                    let back = [
+                        -(direction[0] * cos - direction[1] * sin),
+                        -(direction[0] * sin + direction[1] * cos)
+                    ];
+                    draw_list.add_line(tip, [tip[0] + back[0] * head, tip[1] + back[1] * head], params.color).build();
+                }
+            }
+        }
+    );
+}
+
+/// Draws the measured line or polygon (with edges along great circles).
+fn draw_measurement(
+    ui: &imgui::Ui,
+    view: &views::ViewBase,
+    measurement: &MeasurementState,
+    image_pos: [f32; 2],
+    image_size: [f32; 2]
+) {
+    let mut points = measurement.points.clone();
+    if measurement.polygon && points.len() > 2 { points.push(points[0]); }
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect_intersect(
+        image_pos,
+        [image_pos[0] + image_size[0], image_pos[1] + image_size[1]],
+        || {
+            for (a, b) in points.iter().zip(points.iter().skip(1)) {
+                for line in geometry::great_circle_route(*a, *b, ROUTE_MAX_STEP.into()).unwrap_or_default() {
+                    for [p1, p2] in view.projected_polyline(&line) {
+                        draw_list.add_line(
+                            ndc_to_screen(p1, image_pos, image_size),
+                            ndc_to_screen(p2, image_pos, image_size),
+                            MEASUREMENT_COLOR
+                        ).thickness(MEASUREMENT_LINE_WIDTH).build();
+                    }
+                }
+            }
+            for point in measurement.points.iter().filter_map(|p| view.projected_point(*p)) {
+                let center = ndc_to_screen(point, image_pos, image_size);
+                draw_list.add_circle(center, MARKER_RADIUS, MEASUREMENT_COLOR).filled(true).build();
+                draw_list.add_circle(center, MARKER_RADIUS, GRATICULE_LABEL_SHADOW_COLOR).build();
+            }
+        }
+    );
+}
+
+/// Shows the results of the measurement mode (if active).
+fn handle_measurement(ui: &imgui::Ui, state: &mut MeasurementState, program_data: &data::ProgramData) {
+    if !state.active { return; }
+
+    let mut opened = true;
+    imgui::Window::new(ui, "Measurement")
+        .size([380.0, 200.0], imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            ui.text_wrapped("Click views to add vertices (dragging still rotates the view).");
+            ui.checkbox("polygon", &mut state.polygon);
+            ui.same_line();
+            ui.checkbox("ellipsoidal", &mut state.ellipsoidal);
+            let body = program_data.body();
+            if ui.is_item_hovered() {
+                ui.tooltip_text(format!(
+                    "Measure on the ellipsoid of {} (flattening 1/{:.1}) instead of the sphere of its mean radius",
+                    body.name, 1.0 / body.flattening
+                ));
+            }
+
+            let figure = if state.ellipsoidal {
+                geodesy::Figure::Ellipsoid{
+                    semi_major_axis: body.semi_major_axis / 1000.0,
+                    flattening: body.flattening
+                }
+            } else {
+                geodesy::Figure::Sphere{ radius: body.mean_radius }
+            };
+            let unit = program_data.distance_unit;
+            let mut points = state.points.clone();
+            if state.polygon && points.len() > 2 { points.push(points[0]); }
+
+            ui.text(format!("Vertices: {}", state.points.len()));
+            ui.text(format!(
+                "{}: {:.3} {}",
+                if state.polygon { "Perimeter" } else { "Length" },
+                unit.from_km(geodesy::length(&points, &figure)),
+                unit.symbol()
+            ));
+            if state.polygon {
+                let area = geodesy::area(&state.points, &figure);
+                ui.text(format!("Area: {:.3} {}²", unit.from_km(unit.from_km(area)), unit.symbol()));
+            }
+
+            ui.separator();
+            if ui.button("Remove last vertex") {
+                state.points.pop();
+            }
+            ui.same_line();
+            if ui.button("Clear") {
+                state.points.clear();
+            }
+        });
+
+    if !opened {
+        state.active = false;
+    }
+}
+
+/// Shows selection of the view's graticule spacing and of the length of lines' segments.
+fn handle_graticule_spacing(ui: &imgui::Ui, view: &mut views::ViewBase) {
+    // the view's values are selectable even if not among the predefined ones (e.g. a step from the configuration)
+    let values_with = |predefined: &[f64], current: f64| {
+        let mut values = predefined.to_vec();
+        if !values.contains(&current) { values.push(current); }
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        values
+    };
+    let labels = |values: &[f64]| values.iter().map(|v| format!("{}°", v)).collect::<Vec<String>>();
+
+    let (step, substep) = (view.graticule_step().0, view.graticule_substep().0);
+    let steps = values_with(&data::GRATICULE_STEPS, step);
+    let substeps = values_with(&data::GRATICULE_SUBSTEPS, substep);
+
+    let _width = ui.push_item_width(60.0);
+    let mut step_idx = steps.iter().position(|s| *s == step).unwrap();
+    if ui.combo_simple_string("##graticule_step", &mut step_idx, &labels(&steps)) {
+        let new_step = steps[step_idx];
+        // keep the segments not longer than the lines' spacing
+        let new_substep = substep.min(new_step);
+        if let Err(e) = view.set_graticule(cgmath::Deg(new_step), cgmath::Deg(new_substep)) {
+            eprintln!("Failed to change graticule: {}", e);
+        }
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Spacing of graticule lines");
+    }
+    ui.same_line();
+    let mut substep_idx = substeps.iter().position(|s| *s == substep).unwrap();
+    if ui.combo_simple_string("##graticule_substep", &mut substep_idx, &labels(&substeps)) {
+        let new_substep = substeps[substep_idx].min(step);
+        if let Err(e) = view.set_graticule(cgmath::Deg(step), cgmath::Deg(new_substep)) {
+            eprintln!("Failed to change graticule: {}", e);
+        }
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Length of segments of graticule lines (shorter: smoother lines)");
+    }
+}
+
+/// Draws places of at least `min_rank` with their names; names which would overlap names of larger places
+/// are omitted.
+fn draw_places(ui: &imgui::Ui, view: &views::ViewBase, min_rank: i32, image_pos: [f32; 2], image_size: [f32; 2]) {
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect_intersect(
+        image_pos,
+        [image_pos[0] + image_size[0], image_pos[1] + image_size[1]],
+        || {
+            // screen rectangles (min. x, min. y, max. x, max. y) of the drawn names
+            let mut names: Vec<[f32; 4]> = vec![];
+            for (position, place) in view.projected_places(min_rank.max(0) as u8) {
+                let center = ndc_to_screen(position, image_pos, image_size);
+                draw_list.add_circle(center, PLACE_RADIUS, PLACE_COLOR).filled(true).build();
+                draw_list.add_circle(center, PLACE_RADIUS, GRATICULE_LABEL_SHADOW_COLOR).build();
+
+                let size = ui.calc_text_size(&place.name);
+                let rect = [
+                    center[0] + 2.0 * PLACE_RADIUS,
+                    center[1] - size[1] / 2.0,
+                    center[0] + 2.0 * PLACE_RADIUS + size[0],
+                    center[1] + size[1] / 2.0
+                ];
+                let overlaps = |r: &[f32; 4]| rect[0] < r[2] && r[0] < rect[2] && rect[1] < r[3] && r[1] < rect[3];
+                if !names.iter().any(overlaps) {
+                    draw_label_text(ui, &draw_list, &place.name, [(rect[0] + rect[2]) / 2.0, center[1]], 0.0);
+                    names.push(rect);
+                }
+            }
+        }
+    );
+}
+
+/// Draws labels of time zones' UTC offsets over the view's image located at `image_pos` (in screen coordinates);
+/// labels overlapping those of larger zones are omitted.
+fn draw_time_zone_labels(ui: &imgui::Ui, view: &views::ViewBase, image_pos: [f32; 2], image_size: [f32; 2]) {
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect_intersect(
+        image_pos,
+        [image_pos[0] + image_size[0], image_pos[1] + image_size[1]],
+        || {
+            // screen rectangles (min. x, min. y, max. x, max. y) of the drawn labels
+            let mut drawn: Vec<[f32; 4]> = vec![];
+            for (position, label) in view.projected_time_zone_labels() {
+                let center = ndc_to_screen(position, image_pos, image_size);
+                let size = ui.calc_text_size(&label.text);
+                let rect = [
+                    center[0] - size[0] / 2.0,
+                    center[1] - size[1] / 2.0,
+                    center[0] + size[0] / 2.0,
+                    center[1] + size[1] / 2.0
+                ];
+                let overlaps = |r: &[f32; 4]| rect[0] < r[2] && r[0] < rect[2] && rect[1] < r[3] && r[1] < rect[3];
+                if !drawn.iter().any(overlaps) {
+                    draw_label_text(ui, &draw_list, &label.text, center, 0.0);
+                    drawn.push(rect);
+                }
+            }
+        }
+    );
+}
+
+/// Draws graticule labels over the view's image located at `image_pos` (in screen coordinates).
+fn draw_graticule_labels(ui: &imgui::Ui, view: &views::ViewBase, image_pos: [f32; 2], image_size: [f32; 2]) {
+    let to_screen = |p: cgmath::Point2<f64>| ndc_to_screen(p, image_pos, image_size);
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect_intersect(
+        image_pos,
+        [image_pos[0] + image_size[0], image_pos[1] + image_size[1]],
+        || {
+            for label in view.graticule_labels() {
+                let path: Vec<[f32; 2]> = label.path.iter().map(|p| to_screen(*p)).collect();
+                let anchor = path[label.anchor];
+
+                let on_curve = view.graticule_labels == GraticuleLabels::AlongLines &&
+                    draw_label_along_path(ui, &draw_list, &label.text, &path, label.anchor);
+
+                if !on_curve {
+                    draw_label_text(ui, &draw_list, &label.text, anchor, 0.0);
+                }
+            }
+        }
+    );
+}
+
+/// Draws `text` character by character along `path` (in screen coordinates), centered at `path[anchor]`.
+/// Returns false if the path is too short to fit the text.
+fn draw_label_along_path(
+    ui: &imgui::Ui,
+    draw_list: &imgui::DrawListMut,
+    text: &str,
+    path: &[[f32; 2]],
+    anchor: usize
+) -> bool {
+    if path.len() < 2 { return false; }
+
+    // make the text run left-to-right, so that it is never upside down
+    let mut path = path.to_vec();
+    let mut anchor = anchor;
+    if path[path.len() - 1][0] < path[0][0] {
+        path.reverse();
+        anchor = path.len() - 1 - anchor;
+    }
+
+    // cumulative arc length at each point
+    let mut arc = vec![0.0f32];
+    for segment in path.windows(2) {
+        let length = ((segment[1][0] - segment[0][0]).powi(2) + (segment[1][1] - segment[0][1]).powi(2)).sqrt();
+        arc.push(arc.last().unwrap() + length);
+    }
+
+    let char_widths: Vec<f32> = text.chars().map(|c| ui.calc_text_size(c.to_string())[0]).collect();
+    let text_width: f32 = char_widths.iter().sum();
+
+    let start = arc[anchor] - text_width / 2.0;
+    if start < 0.0 || start + text_width > *arc.last().unwrap() { return false; }
+
+    let mut char_start = start;
+    for (c, width) in text.chars().zip(char_widths.iter()) {
+        let char_center = char_start + width / 2.0;
+        let segment_idx = arc.windows(2).position(|a| char_center <= a[1]).unwrap_or(arc.len() - 2);
+        let (p1, p2) = (path[segment_idx], path[segment_idx + 1]);
+        let segment_length = arc[segment_idx + 1] - arc[segment_idx];
+        let t = if segment_length > 0.0 { (char_center - arc[segment_idx]) / segment_length } else { 0.0 };
+
+        draw_label_text(
+            ui,
+            draw_list,
+            &c.to_string(),
+            [p1[0] + t * (p2[0] - p1[0]), p1[1] + t * (p2[1] - p1[1])],
+            (p2[1] - p1[1]).atan2(p2[0] - p1[0])
+        );
+
+        char_start += width;
+    }
+
+    true
+}
+
+/// Draws `text` (with a shadow, for legibility) centered at `center` and rotated clockwise by `angle` (radians).
+fn draw_label_text(ui: &imgui::Ui, draw_list: &imgui::DrawListMut, text: &str, center: [f32; 2], angle: f32) {
+    let size = ui.calc_text_size(text);
+    let pos = [center[0] - size[0] / 2.0, center[1] - size[1] / 2.0];
+
+    // imgui cannot draw rotated text; instead, vertices of the added glyphs are rotated afterwards
+    let first_vertex = unsafe { (*imgui::sys::igGetWindowDrawList()).VtxBuffer.Size };
+
+    draw_list.add_text([pos[0] + 1.0, pos[1] + 1.0], GRATICULE_LABEL_SHADOW_COLOR, text);
+    draw_list.add_text(pos, GRATICULE_LABEL_COLOR, text);
+
+    if angle != 0.0 {
+        let (sin, cos) = angle.sin_cos();
+        unsafe {
+            let vertices = &mut (*imgui::sys::igGetWindowDrawList()).VtxBuffer;
+            for i in first_vertex..vertices.Size {
+                let vertex = &mut *vertices.Data.offset(i as isize);
+                let (dx, dy) = (vertex.pos.x - center[0], vertex.pos.y - center[1]);
+                vertex.pos.x = center[0] + dx * cos - dy * sin;
+                vertex.pos.y = center[1] + dx * sin + dy * cos;
+            }
+        }
+    }
+}
+
+fn handle_stereo_mode(ui: &imgui::Ui, view: &mut views::ViewBase) {
+    ui.text("stereo:");
+    ui.same_line();
+    if ui.radio_button_bool("off##stereo", view.stereo_mode() == StereoMode::Off) {
+        view.set_stereo_mode(StereoMode::Off);
+    }
+    ui.same_line();
+    if ui.radio_button_bool("anaglyph##stereo", view.stereo_mode() == StereoMode::Anaglyph) {
+        view.set_stereo_mode(StereoMode::Anaglyph);
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("For red (left) - cyan (right) glasses");
+    }
+    ui.same_line();
+    if ui.radio_button_bool("side-by-side##stereo", view.stereo_mode() == StereoMode::SideBySide) {
+        view.set_stereo_mode(StereoMode::SideBySide);
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("For parallel (\"wall-eyed\") viewing");
+    }
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_albers_equal_area_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::AlbersEqualAreaView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("Albers equal-area conic###albers_equal_area_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [800.0, 600.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_armadillo_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::ArmadilloView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("Armadillo###armadillo_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [800.0, 500.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_conformal_square_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::ConformalSquareView
+) -> bool {
+    let mut opened = true;
+
+    let size = match view.kind() {
+        views::ConformalSquareKind::AdamsHemisphere => [640.0, 640.0],
+        views::ConformalSquareKind::Guyou => [640.0, 320.0],
+        views::ConformalSquareKind::PeirceQuincuncial => [640.0, 640.0]
+    };
+
+    imgui::Window::new(ui, &format!("{}###conformal_square_{}", view.kind().name(), view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), size), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            if view.kind() == views::ConformalSquareKind::PeirceQuincuncial {
+                let mut tiled = view.tiled();
+                if ui.checkbox("tiled", &mut tiled) {
+                    view.set_tiled(tiled);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Repeat the map around the central square");
+                }
+            }
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_cylindrical_lambert_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::CylindricalLambertView
+) -> bool {
+    let mut opened = true;
+
+    let title = match view.preset() {
+        Some(preset) => format!("Cylindrical equal-area ({})", preset.name()),
+        None => format!("Cylindrical equal-area (standard parallel {:.1}°)", view.standard_parallel().0)
+    };
+
+    imgui::Window::new(ui, &format!("{}###cylindrical_lambert_{}", title, view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [640.0, 320.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            let presets = views::CylindricalEqualAreaPreset::ALL;
+            let mut preset_names: Vec<&str> = presets.iter().map(|p| p.name()).collect();
+            preset_names.push("custom");
+            let mut preset_idx = view.preset()
+                .and_then(|preset| presets.iter().position(|p| *p == preset))
+                .unwrap_or(presets.len());
+            let _width = ui.push_item_width(150.0);
+            if ui.combo_simple_string("preset", &mut preset_idx, &preset_names) && preset_idx < presets.len() {
+                view.set_preset(presets[preset_idx]);
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Standard parallel: 0° (Lambert), 30° (Behrmann), 45° (Gall-Peters)");
+            }
+
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_general_perspective_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::GeneralPerspectiveView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("General perspective###general_perspective_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [640.0, 640.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            if ui.small_button("GEO") {
+                view.set_altitude(views::GEOSTATIONARY_ALTITUDE);
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Set the altitude of the geostationary orbit (e.g. GOES, Meteosat)");
+            }
+            ui.same_line();
+
+            handle_stereo_mode(ui, view.base_mut());
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_gnomonic_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::GnomonicView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("Gnomonic###gnomonic_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [640.0, 640.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_lambert_azimuthal_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::LambertAzimuthalView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("Lambert azimuthal equal-area###lambert_azimuthal_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [640.0, 640.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_lambert_conformal_conic_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::LambertConformalConicView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("Lambert conformal conic###lambert_conformal_conic_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [800.0, 600.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_littrow_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::LittrowView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("Littrow###littrow_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [600.0, 600.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_eckert_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::EckertView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("{}###eckert_{}", view.kind().name(), view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [800.0, 450.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_elliptical_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::EllipticalView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("{}###elliptical_{}", view.kind().name(), view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [800.0, 450.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_loximuthal_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::LoximuthalView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("Loximuthal###loximuthal_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [800.0, 500.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_mercator_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::MercatorView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("{}###mercator_{}", view.kind().name(), view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [800.0, 600.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_nicolosi_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::NicolosiView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("Nicolosi globular###nicolosi_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [640.0, 640.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_orthographic_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::OrthographicView
+) -> bool {
+    let mut opened = true;
 
     imgui::Window::new(ui, &format!("Orthographic###orthographic_{}", view.unique_id()))
-        .size([640.0, 640.0], imgui::Condition::FirstUseEver)
+        .size(view_window_size(gui_state, view.unique_id(), [640.0, 640.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_stereo_mode(ui, view.base_mut());
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_plugin_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::PluginView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("{}###plugin_{}", view.name(), view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [640.0, 640.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            if !view.base_mut().projection().has_cpu_projection() {
+                ui.text_colored(
+                    [1.0, 0.5, 0.0, 1.0],
+                    "GLSL-only projection: coordinates under the cursor, picking, labels, markers\n\
+and exports of lines are not available (they need the CPU formulas of a library plugin)."
+                );
+            }
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_qsc_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::QscView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("Quadrilateralized Spherical Cube###qsc_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [800.0, 650.0]), imgui::Condition::FirstUseEver)
         .opened(&mut opened)
         .build(|| {
             handle_view_common(ui, gui_state, view.base_mut());
@@ -381,7 +2094,26 @@ fn handle_stereographic_view(
     let mut opened = true;
 
     imgui::Window::new(ui, &format!("Stereographic###stereographic_{}", view.unique_id()))
-        .size([640.0, 640.0], imgui::Condition::FirstUseEver)
+        .size(view_window_size(gui_state, view.unique_id(), [640.0, 640.0]), imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            handle_view_common(ui, gui_state, view.base_mut());
+        }
+    );
+
+    opened
+}
+
+/// Returns `false` if view should be deleted.
+fn handle_transverse_mercator_view(
+    ui: &imgui::Ui,
+    gui_state: &mut GuiState,
+    view: &mut views::TransverseMercatorView
+) -> bool {
+    let mut opened = true;
+
+    imgui::Window::new(ui, &format!("Transverse Mercator###transverse_mercator_{}", view.unique_id()))
+        .size(view_window_size(gui_state, view.unique_id(), [600.0, 800.0]), imgui::Condition::FirstUseEver)
         .opened(&mut opened)
         .build(|| {
             handle_view_common(ui, gui_state, view.base_mut());
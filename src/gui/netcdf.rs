@@ -0,0 +1,272 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Scalar and vector fields loaded from NetCDF files.
+//
+
+use crate::data;
+use crate::scalar_field;
+
+/// Number of segments of the color bar of a scalar field's colormap.
+const COLOR_BAR_SEGMENTS: usize = 32;
+
+#[derive(Default)]
+pub struct ScalarFieldState {
+    /// NetCDF file.
+    path: String,
+    /// Variables of the file at `path` which can be loaded.
+    variables: Vec<String>,
+    variable_idx: usize,
+    /// Index of the first leading dimension (e.g. time).
+    index: i32,
+    /// Result of the last load.
+    status: Option<String>
+}
+
+#[derive(Default)]
+pub struct VectorFieldState {
+    /// NetCDF file.
+    path: String,
+    /// Variables of the file at `path` which can be loaded.
+    variables: Vec<String>,
+    /// Indices of the variables of the eastward and northward components.
+    u_idx: usize,
+    v_idx: usize,
+    /// Index of the first leading dimension (e.g. time).
+    index: i32,
+    /// Result of the last load.
+    status: Option<String>
+}
+
+/// Shows the "Scalar field" dialog (opened if `open` is true).
+pub fn handle_scalar_field(
+    ui: &imgui::Ui,
+    state: &mut ScalarFieldState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Scalar field");
+    }
+    ui.popup_modal("Scalar field").always_auto_resize(true).build(ui, || {
+        ui.text("Shows a 2-D latitude-longitude field from a NetCDF file (e.g. sea-surface temperature) as a");
+        ui.text("color-mapped overlay; missing values are transparent.");
+        if !scalar_field::NETCDF_AVAILABLE {
+            ui.text_colored([1.0, 0.3, 0.3, 1.0], &format!("{}.", scalar_field::NOT_COMPILED_IN));
+        }
+        let _width = ui.push_item_width(400.0);
+        if ui.input_text("NetCDF file", &mut state.path).build() {
+            state.variables.clear();
+        }
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new().add_filter("NetCDF", &["nc", "nc4", "cdf"]).pick_file() {
+                state.path = path.to_string_lossy().into_owned();
+                state.variables.clear();
+            }
+        }
+        if state.variables.is_empty() {
+            if ui.button("Open") {
+                match scalar_field::variables(&state.path) {
+                    Ok(variables) => {
+                        state.variables = variables;
+                        state.variable_idx = 0;
+                        state.status = None;
+                    },
+                    Err(e) => state.status = Some(format!("Error: {}.", e))
+                }
+            }
+        } else {
+            ui.combo_simple_string("variable", &mut state.variable_idx, &state.variables);
+            if ui.input_int("time/level index", &mut state.index).build() {
+                state.index = state.index.max(0);
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Index of the variable's first dimension other than latitude and longitude (if any)");
+            }
+            if ui.button("Load") {
+                let variable = &state.variables[state.variable_idx];
+                state.status = Some(
+                    match program_data.load_scalar_field(&state.path, variable, state.index as usize, display) {
+                        Ok(()) => format!("Loaded {}.", variable),
+                        Err(e) => format!("Error: {}.", e)
+                    }
+                );
+            }
+        }
+
+        if let Some(field) = program_data.scalar_field() {
+            ui.separator();
+            let mut params = field.params;
+            let value_range = field.grid.value_range;
+            ui.text(format!("{}: values from {} to {}", field.grid.name, value_range[0], value_range[1]));
+
+            let mut changed = false;
+            let mut colormap_idx = scalar_field::Colormap::ALL.iter().position(|c| *c == params.colormap).unwrap();
+            let colormap_names: Vec<&str> = scalar_field::Colormap::ALL.iter().map(|c| c.name()).collect();
+            if ui.combo_simple_string("colormap", &mut colormap_idx, &colormap_names) {
+                params.colormap = scalar_field::Colormap::ALL[colormap_idx];
+                changed = true;
+            }
+            let speed = (value_range[1] - value_range[0]).max(f32::EPSILON) / 200.0;
+            changed |= imgui::Drag::new("min. value").speed(speed).build(ui, &mut params.value_range[0]);
+            changed |= imgui::Drag::new("max. value").speed(speed).build(ui, &mut params.value_range[1]);
+            ui.same_line();
+            if ui.button("Reset") {
+                params.value_range = value_range;
+                changed = true;
+            }
+            changed |= imgui::Slider::new("opacity", 0.0, 1.0).build(ui, &mut params.opacity);
+
+            // color bar
+            let draw_list = ui.get_window_draw_list();
+            let origin = ui.cursor_screen_pos();
+            let size = [400.0, 16.0];
+            let color = |t: f32| {
+                let [r, g, b] = params.colormap.color(t);
+                [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
+            };
+            for i in 0..COLOR_BAR_SEGMENTS {
+                let (t0, t1) = (i as f32 / COLOR_BAR_SEGMENTS as f32, (i + 1) as f32 / COLOR_BAR_SEGMENTS as f32);
+                draw_list.add_rect_filled_multicolor(
+                    [origin[0] + t0 * size[0], origin[1]],
+                    [origin[0] + t1 * size[0], origin[1] + size[1]],
+                    color(t0), color(t1), color(t1), color(t0)
+                );
+            }
+            ui.dummy(size);
+            ui.text(format!("{}", params.value_range[0]));
+            ui.same_line_with_pos(size[0] - ui.calc_text_size(format!("{}", params.value_range[1]))[0]);
+            ui.text(format!("{}", params.value_range[1]));
+
+            if changed {
+                program_data.set_scalar_field_params(params, display);
+            }
+        }
+
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if program_data.scalar_field().is_some() {
+            if ui.button("Remove") {
+                program_data.remove_scalar_field();
+                state.status = None;
+            }
+            ui.same_line();
+        }
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
+
+/// Shows the "Vector field" dialog (opened if `open` is true).
+pub fn handle_vector_field(
+    ui: &imgui::Ui,
+    state: &mut VectorFieldState,
+    open: bool,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Vector field");
+    }
+    ui.popup_modal("Vector field").always_auto_resize(true).build(ui, || {
+        ui.text("Shows a 2-D vector field (e.g. wind) from eastward (u) and northward (v) components on a latitude-");
+        ui.text("longitude grid in a NetCDF file as arrows, oriented along each projection's local meridians.");
+        if !scalar_field::NETCDF_AVAILABLE {
+            ui.text_colored([1.0, 0.3, 0.3, 1.0], &format!("{}.", scalar_field::NOT_COMPILED_IN));
+        }
+        let _width = ui.push_item_width(400.0);
+        if ui.input_text("NetCDF file", &mut state.path).build() {
+            state.variables.clear();
+        }
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new().add_filter("NetCDF", &["nc", "nc4", "cdf"]).pick_file() {
+                state.path = path.to_string_lossy().into_owned();
+                state.variables.clear();
+            }
+        }
+        if state.variables.is_empty() {
+            if ui.button("Open") {
+                match scalar_field::variables(&state.path) {
+                    Ok(variables) => {
+                        // guess the components by their names (e.g. "u10", "uwnd")
+                        let find = |prefix: char| variables.iter()
+                            .position(|name| name.to_lowercase().starts_with(prefix))
+                            .unwrap_or(0);
+                        state.u_idx = find('u');
+                        state.v_idx = find('v');
+                        state.variables = variables;
+                        state.status = None;
+                    },
+                    Err(e) => state.status = Some(format!("Error: {}.", e))
+                }
+            }
+        } else {
+            ui.combo_simple_string("eastward component (u)", &mut state.u_idx, &state.variables);
+            ui.combo_simple_string("northward component (v)", &mut state.v_idx, &state.variables);
+            if ui.input_int("time/level index", &mut state.index).build() {
+                state.index = state.index.max(0);
+            }
+            if ui.button("Load") {
+                let (u, v) = (&state.variables[state.u_idx], &state.variables[state.v_idx]);
+                state.status = Some(match program_data.load_vector_field(&state.path, u, v, state.index as usize) {
+                    Ok(()) => format!("Loaded {} and {}.", u, v),
+                    Err(e) => format!("Error: {}.", e)
+                });
+            }
+        }
+
+        if let Some(layer) = program_data.vector_field() {
+            ui.separator();
+            let mut params = layer.params;
+            ui.text(&layer.field.name);
+
+            let mut changed = false;
+            changed |= imgui::Drag::new("arrow spacing")
+                .range(1.0, 30.0)
+                .speed(0.1)
+                .display_format("%.1f°")
+                .build(ui, &mut params.spacing);
+            changed |= imgui::Drag::new("arrow length per unit")
+                .range(0.01, 1000.0)
+                .speed(0.01)
+                .flags(imgui::SliderFlags::LOGARITHMIC)
+                .display_format("%.2f px")
+                .build(ui, &mut params.scale);
+            changed |= imgui::ColorEdit::new("arrow color", &mut params.color).build(ui);
+
+            if changed {
+                program_data.set_vector_field_params(params);
+            }
+        }
+
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if program_data.vector_field().is_some() {
+            if ui.button("Remove") {
+                program_data.remove_vector_field();
+                state.status = None;
+            }
+            ui.same_line();
+        }
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,110 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Overlay scripts: Rhai scripts drawing overlays in all views.
+//
+
+use crate::data;
+use crate::scripting;
+
+#[derive(Default)]
+pub struct OverlayScriptState {
+    path: String,
+    /// Result of the last load or run of the script.
+    status: Option<String>
+}
+
+/// Runs the loaded overlay script (if any) for all views; a script which fails is unloaded.
+pub fn run_overlay_script(
+    state: &mut OverlayScriptState,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    let script = match program_data.overlay_script.take() {
+        Some(script) => script,
+        None => return
+    };
+
+    let mut error = None;
+    let mut view_ids = vec![];
+    for view in program_data.all_views_mut() {
+        view_ids.push(view.unique_id());
+        let (center_lon, center_lat) = view.central_lonlat();
+        let view_state = scripting::ViewState{ center_lon, center_lat, zoom: view.zoom() };
+        match script.run(view.unique_id(), &view_state, program_data.animation_clock.time()) {
+            Ok(None) => (), // unchanged
+            Ok(Some(vertices)) => view.set_script_overlay(if vertices.is_empty() {
+                None
+            } else {
+                Some(data::create_line_list(&vertices, display))
+            }),
+
+            Err(e) => { error = Some(e); break; }
+        }
+    }
+
+    match error {
+        None => {
+            script.retain_outputs(&view_ids);
+            program_data.overlay_script = Some(script);
+        },
+        Some(e) => {
+            // stop running a faulty script
+            state.status = Some(format!("Error in {}: {}", script.path(), e));
+            for view in program_data.all_views_mut() {
+                view.set_script_overlay(None);
+            }
+        }
+    }
+}
+
+/// Shows the "Overlay script" dialog (opened if `open` is true).
+pub fn handle_overlay_script(
+    ui: &imgui::Ui,
+    state: &mut OverlayScriptState,
+    open: bool,
+    program_data: &mut data::ProgramData
+) {
+    if open { ui.open_popup("Overlay script"); }
+    ui.popup_modal("Overlay script").build(ui, || {
+        ui.text_wrapped("Loads a Rhai script which draws an overlay in all views; it is run every frame. \
+See \"src/scripting.rs\" for the available variables and functions.");
+        ui.input_text("script file", &mut state.path).build();
+
+        match &program_data.overlay_script {
+            Some(script) => ui.text(&format!("Running: {}", script.path())),
+            None => ui.text("No script loaded.")
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Load") {
+            match scripting::OverlayScript::load(&state.path, program_data.animation_clock.time()) {
+                Ok(script) => {
+                    program_data.overlay_script = Some(script);
+                    state.status = None;
+                },
+                Err(e) => state.status = Some(format!("Error: {}", e))
+            }
+        }
+        ui.same_line();
+        if ui.button("Unload") {
+            program_data.overlay_script = None;
+            for view in program_data.all_views_mut() {
+                view.set_script_overlay(None);
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,176 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Search of populated places and centering of a view on the chosen one.
+//
+
+use crate::data;
+use crate::views;
+use super::{center_between, draw_label_text, ndc_to_screen, GRATICULE_LABEL_SHADOW_COLOR, MARKER_RADIUS};
+
+/// Color of the marker of a place found by the place search.
+const FOUND_PLACE_COLOR: [f32; 4] = [1.0, 0.85, 0.1, 1.0];
+
+/// Max. number of places listed by the place search.
+const MAX_PLACE_SEARCH_RESULTS: usize = 15;
+
+/// Duration (seconds) of the rotation of a view to center it on a found place.
+const RECENTERING_DURATION: f64 = 1.5;
+
+/// Time (seconds) for which the marker of a found place is shown after recentering.
+const FOUND_PLACE_MARKER_DURATION: f64 = 5.0;
+
+/// Place chosen in the place search, on which a view is being (or has recently been) centered.
+struct FoundPlace {
+    view_id: u32,
+    name: String,
+    /// Longitude and latitude (degrees).
+    lonlat: [f64; 2],
+    /// Longitude and latitude (degrees) of the view's center before recentering.
+    start: [f64; 2],
+    /// Time (seconds) since the place was chosen.
+    time: f64
+}
+
+#[derive(Default)]
+pub struct PlaceSearchState {
+    query: String,
+    /// Names and positions (longitude, latitude in degrees) of places matching `query`.
+    results: Vec<(String, [f64; 2])>,
+    found: Option<FoundPlace>,
+    status: Option<String>
+}
+
+/// Draws the marker and name of a place found by the place search (once `view` has been centered on it).
+pub fn draw_found_place(
+    ui: &imgui::Ui,
+    view: &views::ViewBase,
+    state: &PlaceSearchState,
+    image_pos: [f32; 2],
+    image_size: [f32; 2]
+) {
+    let found = match state.found.as_ref().filter(|found| found.view_id == view.unique_id()) {
+        Some(found) => found,
+        None => return
+    };
+    if found.time < RECENTERING_DURATION { return; }
+    let position = match view.projected_point(found.lonlat) {
+        Some(position) => position,
+        None => return
+    };
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect_intersect(
+        image_pos,
+        [image_pos[0] + image_size[0], image_pos[1] + image_size[1]],
+        || {
+            let center = ndc_to_screen(position, image_pos, image_size);
+            draw_list.add_circle(center, 2.0 * MARKER_RADIUS, FOUND_PLACE_COLOR).thickness(2.0).build();
+            draw_list.add_circle(center, MARKER_RADIUS, FOUND_PLACE_COLOR).filled(true).build();
+            draw_list.add_circle(center, MARKER_RADIUS, GRATICULE_LABEL_SHADOW_COLOR).build();
+            let name_width = ui.calc_text_size(&found.name)[0];
+            let name_center = [center[0] + 3.0 * MARKER_RADIUS + name_width / 2.0, center[1]];
+            draw_label_text(ui, &draw_list, &found.name, name_center, 0.0);
+        }
+    );
+}
+
+/// Advances by `dt` seconds the rotation of a view towards a found place (see `PlaceSearchState::found`),
+/// along the great circle between the view's initial center and the place; removes the place's marker
+/// after `FOUND_PLACE_MARKER_DURATION`.
+pub fn animate_recentering(state: &mut PlaceSearchState, dt: f64, program_data: &mut data::ProgramData) {
+    let found = match &mut state.found {
+        Some(found) => found,
+        None => return
+    };
+    let view = match program_data.all_views_mut().find(|view| view.unique_id() == found.view_id) {
+        Some(view) => view,
+        None => { state.found = None; return; } // closed by the user
+    };
+
+    let recentering = found.time < RECENTERING_DURATION;
+    found.time += dt;
+    if recentering {
+        center_between(view, found.start, found.lonlat, found.time / RECENTERING_DURATION);
+    } else if found.time > RECENTERING_DURATION + FOUND_PLACE_MARKER_DURATION {
+        state.found = None;
+    }
+}
+
+/// Shows the "Find place" dialog (opened if `open` is true).
+pub fn handle_place_search(
+    ui: &imgui::Ui,
+    state: &mut PlaceSearchState,
+    open: bool,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Find place");
+    }
+    ui.popup_modal("Find place").always_auto_resize(true).build(ui, || {
+        ui.text("Centers the most recently focused view on a populated place.");
+        let _width = ui.push_item_width(250.0);
+        if ui.input_text("name", &mut state.query).build() {
+            state.results = program_data.gazetteer()
+                .map(|gazetteer| gazetteer.search(&state.query, MAX_PLACE_SEARCH_RESULTS)
+                    .iter()
+                    .map(|entry| (entry.full_name(), [entry.longitude, entry.latitude]))
+                    .collect())
+                .unwrap_or_default();
+        }
+
+        let mut chosen = None;
+        for (idx, (name, [lon, lat])) in state.results.iter().enumerate() {
+            let label = format!(
+                "{}  ({:.2}° {}, {:.2}° {})##place{}",
+                name,
+                lat.abs(), if *lat >= 0.0 { "N" } else { "S" },
+                lon.abs(), if *lon >= 0.0 { "E" } else { "W" },
+                idx
+            );
+            if ui.selectable(&label) {
+                chosen = Some(idx);
+            }
+        }
+        if !state.query.trim().is_empty() && state.results.is_empty() {
+            ui.text("No places found.");
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        if let Some(idx) = chosen {
+            let view = focused_view.and_then(
+                |id| program_data.all_views_mut().find(|view| view.unique_id() == id)
+            );
+            match view {
+                Some(view) => {
+                    let (name, lonlat) = state.results[idx].clone();
+                    let (start_lon, start_lat) = view.central_lonlat();
+                    state.found = Some(FoundPlace{
+                        view_id: view.unique_id(),
+                        name,
+                        lonlat,
+                        start: [start_lon.0, start_lat.0],
+                        time: 0.0
+                    });
+                    ui.close_current_popup();
+                },
+                None => state.status = Some("No view to center.".into())
+            }
+        }
+
+        ui.separator();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,72 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Tectonic plate boundaries.
+//
+
+use crate::data;
+
+#[derive(Default)]
+pub struct PlateBoundariesState {
+    /// GeoJSON file.
+    path: String,
+    /// Result of the last load.
+    status: Option<String>
+}
+
+impl PlateBoundariesState {
+    pub fn new(path: &str) -> PlateBoundariesState {
+        PlateBoundariesState{
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Shows the "Plate boundaries" dialog (opened if `open` is true).
+pub fn handle_plate_boundaries(
+    ui: &imgui::Ui,
+    state: &mut PlateBoundariesState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Plate boundaries");
+    }
+    ui.popup_modal("Plate boundaries").always_auto_resize(true).build(ui, || {
+        ui.text("Loads tectonic plate boundaries from a GeoJSON file (e.g. the PB2002 model) and shows them in all");
+        ui.text("views as the \"plate boundaries\" layer: ridges in red, trenches (convergent boundaries) in blue,");
+        ui.text("transform faults dashed in green.");
+        ui.input_text("GeoJSON file", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new().add_filter("GeoJSON", &["json", "geojson"]).pick_file() {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Load") {
+            state.status = Some(match program_data.load_plate_boundaries(&state.path, display) {
+                Ok(()) => format!("Loaded {}.", state.path),
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,134 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Presets: named settings of views, from which new views can be created.
+//
+
+use crate::data;
+use crate::session;
+
+/// Default file for importing and exporting presets.
+const DEFAULT_PRESETS_EXCHANGE_FILE: &str = "presets.json";
+
+#[derive(Default)]
+pub struct PresetsState {
+    pub presets: Vec<session::Preset>,
+    /// Name under which the focused view is to be saved.
+    name: String,
+    /// File to import presets from or export them to.
+    path: String,
+    /// Result of the last operation.
+    status: Option<String>
+}
+
+impl PresetsState {
+    /// Loads presets from the presets file.
+    pub fn load() -> PresetsState {
+        PresetsState{
+            presets: session::load_presets(&session::presets_path()).unwrap_or_else(|e| {
+                eprintln!("Failed to load presets: {}", e);
+                vec![]
+            }),
+            path: DEFAULT_PRESETS_EXCHANGE_FILE.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Stores presets in the presets file; returns the status to show.
+    fn store(&self) -> Option<String> {
+        match session::save_presets(&session::presets_path(), &self.presets) {
+            Ok(()) => None,
+            Err(e) => Some(format!("Error saving presets: {}.", e))
+        }
+    }
+}
+
+/// Shows the "Presets" dialog (opened if `open` is true).
+pub fn handle_presets(
+    ui: &imgui::Ui,
+    state: &mut PresetsState,
+    open: bool,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Presets");
+    }
+    ui.popup_modal("Presets").always_auto_resize(true).build(ui, || {
+        ui.text_wrapped("Saves the most recently focused view (projection, parameters, orientation, zoom, layers) \
+            under a name; new views can be created from presets via \"View/Presets\".");
+        ui.input_text("name", &mut state.name).build();
+        ui.same_line();
+        if ui.button("Save focused view") {
+            let name = state.name.trim().to_string();
+            let preset = focused_view.and_then(|id| session::Preset::from_view(&name, id, program_data));
+            state.status = if name.is_empty() {
+                Some("Error: no name specified.".to_string())
+            } else if let Some(preset) = preset {
+                match state.presets.iter_mut().find(|p| p.name() == name) {
+                    Some(existing) => *existing = preset,
+                    None => state.presets.push(preset)
+                }
+                state.store()
+            } else {
+                Some("Error: no view to save.".to_string())
+            };
+        }
+
+        ui.separator();
+        let mut to_delete = None;
+        for (idx, preset) in state.presets.iter().enumerate() {
+            if ui.small_button(&format!("delete##preset{}", idx)) {
+                to_delete = Some(idx);
+            }
+            ui.same_line();
+            ui.text(preset.name());
+        }
+        if let Some(idx) = to_delete {
+            state.presets.remove(idx);
+            state.status = state.store();
+        }
+        ui.text_disabled(format!("Stored in {}", session::presets_path().display()));
+
+        ui.separator();
+        ui.input_text("file", &mut state.path).build();
+        if ui.is_item_hovered() {
+            ui.tooltip_text("File for sharing presets; importing replaces presets with the same names");
+        }
+        if ui.button("Import") {
+            state.status = match session::load_presets(std::path::Path::new(&state.path)) {
+                Ok(imported) => {
+                    let num_imported = imported.len();
+                    for preset in imported {
+                        state.presets.retain(|p| p.name() != preset.name());
+                        state.presets.push(preset);
+                    }
+                    state.store().or_else(|| Some(format!("Imported {} preset(s).", num_imported)))
+                },
+                Err(e) => Some(format!("Error: {}.", e))
+            };
+        }
+        ui.same_line();
+        if ui.button("Export") {
+            state.status = Some(match session::save_presets(std::path::Path::new(&state.path), &state.presets) {
+                Ok(()) => format!("Exported {} preset(s).", state.presets.len()),
+                Err(e) => format!("Error: {}.", e)
+            });
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,210 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Printing and PDF export of the focused view.
+//
+
+use crate::data;
+use crate::export;
+use crate::printing;
+
+const DEFAULT_PRINT_DPI: i32 = 300;
+
+/// Default margin (millimeters) of printed and exported pages.
+const DEFAULT_PAGE_MARGIN: f64 = 10.0;
+
+const DEFAULT_PDF_FILE: &str = "view.pdf";
+
+struct PageSetupState {
+    paper: printing::Paper,
+    landscape: bool,
+    dpi: i32,
+    /// Millimeters.
+    margin: f64
+}
+
+impl Default for PageSetupState {
+    fn default() -> PageSetupState {
+        PageSetupState{
+            paper: printing::Paper::A4,
+            landscape: true,
+            dpi: DEFAULT_PRINT_DPI,
+            margin: DEFAULT_PAGE_MARGIN
+        }
+    }
+}
+
+impl PageSetupState {
+    fn settings(&self) -> printing::PrintSettings {
+        printing::PrintSettings{
+            paper: self.paper,
+            landscape: self.landscape,
+            dpi: self.dpi.max(0) as u32,
+            margin_mm: self.margin
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PrintState {
+    page_setup: PageSetupState,
+    /// Available printers (listed when the dialog is opened; see `printing::printers`).
+    printers: Option<Result<Vec<String>, String>>,
+    /// Index of the chosen printer in `printers` plus 1 (0: the default printer).
+    printer_idx: usize,
+    /// Printing in progress.
+    job: Option<printing::PrintJob>,
+    /// Result of the last print.
+    status: Option<String>
+}
+
+#[derive(Default)]
+pub struct PdfExportState {
+    page_setup: PageSetupState,
+    path: String,
+    /// Result of the last export.
+    status: Option<String>
+}
+
+impl PdfExportState {
+    pub fn new() -> PdfExportState {
+        PdfExportState{
+            path: DEFAULT_PDF_FILE.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+fn handle_page_setup(ui: &imgui::Ui, state: &mut PageSetupState) {
+    let mut paper_idx = printing::Paper::ALL.iter().position(|p| *p == state.paper).unwrap();
+    let paper_names: Vec<&str> = printing::Paper::ALL.iter().map(|p| p.name()).collect();
+    if ui.combo_simple_string("paper", &mut paper_idx, &paper_names) {
+        state.paper = printing::Paper::ALL[paper_idx];
+    }
+    ui.checkbox("landscape", &mut state.landscape);
+    ui.input_int("resolution (DPI)", &mut state.dpi).build();
+    imgui::Drag::new("margins (mm)").range(0.0, 100.0).speed(0.5).display_format("%.1f").build(ui, &mut state.margin);
+}
+
+/// Shows the "Print" dialog (opened if `open` is true).
+pub fn handle_print(
+    ui: &imgui::Ui,
+    state: &mut PrintState,
+    open: bool,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        let printers = printing::printers();
+        if printers.as_ref().map_or(0, |printers| printers.len()) < state.printer_idx {
+            state.printer_idx = 0;
+        }
+        state.printers = Some(printers);
+        ui.open_popup("Print");
+    }
+    ui.popup_modal("Print").build(ui, || {
+        let focused_view = focused_view.and_then(
+            |id| program_data.all_views_mut().find(|view| view.unique_id() == id)
+        );
+
+        ui.text_wrapped("Renders the most recently focused view to fill a page and sends it to the system's printer.");
+
+        let printer = match &state.printers {
+            Some(Ok(printers)) if !printers.is_empty() => {
+                let names: Vec<&str> = std::iter::once("(default)")
+                    .chain(printers.iter().map(|printer| printer.as_str()))
+                    .collect();
+                ui.combo_simple_string("printer", &mut state.printer_idx, &names);
+                state.printer_idx.checked_sub(1).map(|idx| printers[idx].clone())
+            },
+            Some(Err(e)) => {
+                ui.text_wrapped(&format!("Cannot list printers ({}); the default one will be used.", e));
+                None
+            },
+            // the printer and the paper are chosen in the system's print dialog
+            _ => None
+        };
+
+        handle_page_setup(ui, &mut state.page_setup);
+
+        if let Some(result) = state.job.as_ref().and_then(|job| job.poll()) {
+            state.job = None;
+            state.status = Some(match result {
+                Ok(()) => "Sent to printer.".to_string(),
+                Err(e) => format!("Error: {}.", e)
+            });
+        }
+        if state.job.is_some() {
+            ui.text("Printing...");
+        } else if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        match focused_view {
+            None => ui.text("No view to print."),
+            Some(view) => if state.job.is_none() && ui.button("Print") {
+                state.status = None;
+                match printing::PrintJob::start(view, &state.page_setup.settings(), printer) {
+                    Ok(job) => state.job = Some(job),
+                    Err(e) => state.status = Some(format!("Error: {}.", e))
+                }
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
+
+/// Shows the "Export PDF" dialog (opened if `open` is true).
+pub fn handle_pdf_export(
+    ui: &imgui::Ui,
+    state: &mut PdfExportState,
+    open: bool,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Export PDF");
+    }
+    ui.popup_modal("Export PDF").build(ui, || {
+        let focused_view = focused_view.and_then(
+            |id| program_data.all_views_mut().find(|view| view.unique_id() == id)
+        );
+
+        ui.text_wrapped("Saves the most recently focused view as a single-page PDF. Lines are stored as vector paths; \
+the globe texture is embedded as an image of the specified resolution.");
+
+        handle_page_setup(ui, &mut state.page_setup);
+        ui.input_text("output file", &mut state.path).build();
+
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        match focused_view {
+            None => ui.text("No view to export."),
+            Some(view) => if ui.button("Export") {
+                state.status = Some(match export::export_view_pdf(&state.path, view, &state.page_setup.settings()) {
+                    Ok(()) => format!("Saved {}.", state.path),
+                    Err(e) => format!("Error: {}.", e)
+                });
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
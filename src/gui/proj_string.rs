@@ -0,0 +1,66 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Creation of views from PROJ strings.
+//
+
+use crate::data;
+use crate::proj_string;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Default)]
+pub struct ProjStringState {
+    definition: String,
+    /// Result of the last attempt to create a view.
+    status: Option<String>
+}
+
+/// Shows the "New view from PROJ string" dialog (opened if `open` is true).
+pub fn handle_proj_string(
+    ui: &imgui::Ui,
+    state: &mut ProjStringState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) {
+    if open { ui.open_popup("New view from PROJ string"); }
+    ui.popup_modal("New view from PROJ string").always_auto_resize(true).build(ui, || {
+        ui.text("Supported projections: aea, adams_hemi, cea, eck4, eck6, gall, gnom, guyou, hammer, laea, lcc,");
+        ui.text("loxim, merc, mill, moll, nicol, nsper, ortho, peirce_q, qsc, stere, tmerc, utm.");
+        ui.text("Angles have to be specified in decimal degrees.");
+        {
+            let _width = ui.push_item_width(500.0);
+            ui.input_text("definition", &mut state.definition)
+                .hint("+proj=lcc +lat_1=33 +lat_2=45 +lon_0=-96")
+                .build();
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Create") {
+            match proj_string::parse(&state.definition)
+                .and_then(|definition| proj_string::create_view(&definition, program_data, renderer, display))
+            {
+                Ok(_) => {
+                    state.status = None;
+                    ui.close_current_popup();
+                },
+                Err(e) => state.status = Some(format!("Error: {}.", e))
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
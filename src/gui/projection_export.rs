@@ -0,0 +1,185 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Export of distortion metrics of all projections and of samples of a single projection.
+//
+
+use crate::data;
+use crate::export;
+use crate::projection::Projection;
+
+const DEFAULT_METRICS_GRID_STEP: f32 = 10.0;
+
+const DEFAULT_METRICS_FILE: &str = "distortion_metrics.csv";
+
+const DEFAULT_SAMPLES_GRID_STEP: f32 = 15.0;
+
+const DEFAULT_SAMPLES_FILE: &str = "projection_samples.csv";
+
+pub struct SamplesExportState {
+    lon_range: [f32; 2],
+    lat_range: [f32; 2],
+    grid_step: f32,
+    path: String,
+    /// Export in progress.
+    export: Option<export::SamplesExport>,
+    /// Result of the last export.
+    status: Option<String>
+}
+
+impl Default for SamplesExportState {
+    fn default() -> SamplesExportState {
+        SamplesExportState{
+            lon_range: [-180.0, 180.0],
+            lat_range: [-90.0, 90.0],
+            grid_step: DEFAULT_SAMPLES_GRID_STEP,
+            path: DEFAULT_SAMPLES_FILE.to_string(),
+            export: None,
+            status: None
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MetricsExportState {
+    grid_step: f32,
+    path: String,
+    /// Export in progress.
+    export: Option<export::SamplesExport>,
+    /// Result of the last export.
+    status: Option<String>
+}
+
+impl MetricsExportState {
+    pub fn new() -> MetricsExportState {
+        MetricsExportState{
+            grid_step: DEFAULT_METRICS_GRID_STEP,
+            path: DEFAULT_METRICS_FILE.to_string(),
+            export: None,
+            status: None
+        }
+    }
+}
+
+/// Shows the "Export distortion metrics" dialog (opened if `open` is true).
+pub fn handle_metrics_export(
+    ui: &imgui::Ui,
+    state: &mut MetricsExportState,
+    open: bool,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Export distortion metrics");
+    }
+    ui.popup_modal("Export distortion metrics").build(ui, || {
+        ui.text_wrapped("Samples all projections (in their normal aspect) on a longitude/latitude grid and saves \
+scale factors h, k, areal scale and maximum angular deformation (in degrees) as CSV.");
+        ui.input_float("grid step (°)", &mut state.grid_step).build();
+        ui.input_text("output file", &mut state.path).build();
+
+        if let Some(result) = state.export.as_mut().and_then(|export| export.poll()) {
+            state.export = None;
+            state.status = Some(match result {
+                Ok(()) => format!("Saved {}.", state.path),
+                Err(e) => format!("Error: {}.", e)
+            });
+        }
+        if let Some(export) = &state.export {
+            imgui::ProgressBar::new(export.progress).overlay_text("Exporting...").build(ui);
+        } else if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if state.export.is_none() && ui.button("Export") {
+            let projections: Vec<Projection> = Projection::ALL.iter().copied()
+                .chain(program_data.plugins.iter()
+                    .filter(|p| p.plugin.has_cpu_projection())
+                    .map(|p| Projection::Plugin(p.plugin)))
+                .collect();
+
+            state.status = None;
+            state.export = Some(export::SamplesExport::start_distortion_metrics(
+                state.path.clone(),
+                projections,
+                cgmath::Deg(state.grid_step as f64)
+            ));
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
+
+/// Shows the "Export projection samples" dialog (opened if `open` is true).
+pub fn handle_samples_export(
+    ui: &imgui::Ui,
+    state: &mut SamplesExportState,
+    open: bool,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Export projection samples");
+    }
+    ui.popup_modal("Export projection samples").always_auto_resize(true).build(ui, || {
+        let projection = focused_view.and_then(
+            |id| program_data.all_views_mut().find(|view| view.unique_id() == id)
+        ).map(|view| view.projection());
+
+        ui.text("Samples the projection of the most recently focused view (with its parameters, in the normal");
+        ui.text("aspect) on a longitude/latitude grid and saves the map coordinates of the points (in multiples");
+        ui.text("of the globe's radius) and their inverse projections as CSV, e.g. for comparison with PROJ");
+        ui.text("or published tables.");
+        ui.input_float("min. longitude (°)", &mut state.lon_range[0]).build();
+        ui.input_float("max. longitude (°)", &mut state.lon_range[1]).build();
+        ui.input_float("min. latitude (°)", &mut state.lat_range[0]).build();
+        ui.input_float("max. latitude (°)", &mut state.lat_range[1]).build();
+        ui.input_float("grid step (°)", &mut state.grid_step).build();
+        ui.input_text("output file", &mut state.path).build();
+
+        if let Some(result) = state.export.as_mut().and_then(|export| export.poll()) {
+            state.export = None;
+            state.status = Some(match result {
+                Ok(()) => format!("Saved {}.", state.path),
+                Err(e) => format!("Error: {}.", e)
+            });
+        }
+        if let Some(export) = &state.export {
+            imgui::ProgressBar::new(export.progress).size([400.0, 0.0]).overlay_text("Exporting...").build(ui);
+        } else if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if let Some(projection) = projection {
+            ui.text(format!("Projection: {}", projection.name()));
+        }
+        match projection {
+            None => ui.text("No view to sample."),
+            Some(projection) => if state.export.is_none() && ui.button("Export") {
+                state.status = None;
+                state.export = Some(export::SamplesExport::start(
+                    state.path.clone(),
+                    projection,
+                    state.lon_range.map(|value| value as f64),
+                    state.lat_range.map(|value| value as f64),
+                    cgmath::Deg(state.grid_step as f64)
+                ));
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,114 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Range rings: concentric circles of equal great-circle distance from a point.
+//
+
+use crate::data;
+use crate::geometry;
+use crate::kml;
+use super::{handle_point_input, ROUTE_MAX_STEP};
+
+/// Default spacing of range rings (km).
+const DEFAULT_RANGE_RING_SPACING: f64 = 1000.0;
+
+const DEFAULT_NUM_RANGE_RINGS: i32 = 5;
+
+const RANGE_RING_LINE_STYLE: data::LineStyle = data::LineStyle{ color: [0.95, 0.75, 0.1, 1.0], width: 1.0 };
+
+#[derive(Default)]
+pub struct RangeRingsState {
+    /// Longitude and latitude of the rings' center (degrees).
+    center: [f64; 2],
+    /// Distance between consecutive rings (km).
+    spacing: f64,
+    num_rings: i32,
+    /// Result of the last operation.
+    status: Option<String>
+}
+
+impl RangeRingsState {
+    pub fn new() -> RangeRingsState {
+        RangeRingsState{
+            spacing: DEFAULT_RANGE_RING_SPACING,
+            num_rings: DEFAULT_NUM_RANGE_RINGS,
+            ..Default::default()
+        }
+    }
+}
+
+/// Shows the "Range rings" dialog (opened if `open` is true).
+pub fn handle_range_rings(
+    ui: &imgui::Ui,
+    state: &mut RangeRingsState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Range rings");
+    }
+    ui.popup_modal("Range rings").always_auto_resize(true).build(ui, || {
+        let unit = program_data.distance_unit;
+
+        ui.text("Creates a vector layer with concentric circles of equal great-circle distance from a point.");
+
+        handle_point_input(ui, "range_rings_center", &mut state.center, program_data.user_markers());
+        ui.same_line();
+        ui.text("center");
+        let _width = ui.push_item_width(200.0);
+        let mut spacing = unit.from_km(state.spacing);
+        if imgui::Drag::new("spacing")
+            .range(unit.from_km(1.0), unit.from_km(10_000.0))
+            .speed(1.0)
+            .display_format(&format!("%.1f {}", unit.symbol()))
+            .build(ui, &mut spacing) {
+            state.spacing = unit.to_km(spacing);
+        }
+        if ui.input_int("number of rings", &mut state.num_rings).build() {
+            state.num_rings = state.num_rings.max(1).min(100);
+        }
+
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Create") {
+            let radius = program_data.body().mean_radius;
+            // rings are not possible beyond the antipode
+            let rings: Vec<_> = (1..=state.num_rings)
+                .map(|i| cgmath::Rad(i as f64 * state.spacing / radius))
+                .take_while(|distance| distance.0 < std::f64::consts::PI)
+                .filter_map(|distance| geometry::circle(state.center, distance, ROUTE_MAX_STEP.into()).ok())
+                .flatten()
+                .collect();
+            state.status = Some(if rings.is_empty() {
+                "Error: the spacing exceeds the distance to the antipode.".to_string()
+            } else {
+                let name = format!(
+                    "range rings every {:.1} {} around {:.2}°, {:.2}°",
+                    spacing, unit.symbol(), state.center[0], state.center[1]
+                );
+                program_data.add_vector_layer(
+                    name.clone(),
+                    vec![kml::Placemark{ style: None, lines: rings }],
+                    RANGE_RING_LINE_STYLE,
+                    display
+                );
+                format!("Created {}.", name)
+            });
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
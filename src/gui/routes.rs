@@ -0,0 +1,117 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Great-circle routes and rhumb lines between two points.
+//
+
+use crate::data;
+use crate::geometry;
+use crate::kml;
+use super::{handle_point_input, ROUTE_MAX_STEP};
+
+const GREAT_CIRCLE_LINE_STYLE: data::LineStyle = data::LineStyle{ color: [0.85, 0.1, 0.1, 1.0], width: 1.5 };
+
+const RHUMB_LINE_STYLE: data::LineStyle = data::LineStyle{ color: [0.1, 0.6, 0.2, 1.0], width: 1.5 };
+
+#[derive(Default)]
+pub struct RouteState {
+    /// Longitudes and latitudes of the route's start and end (degrees).
+    points: [[f64; 2]; 2],
+    /// Endpoint (0: start, 1: end) set by the next double click with `DoubleClickAction::RouteEndpoint`.
+    next_endpoint: usize,
+    /// Result of the last operation.
+    status: Option<String>
+}
+
+impl RouteState {
+    /// Sets the next endpoint (see `next_endpoint`) to `lonlat` (degrees).
+    pub fn set_next_endpoint(&mut self, lonlat: [f64; 2]) {
+        self.points[self.next_endpoint] = lonlat;
+        self.next_endpoint = 1 - self.next_endpoint;
+    }
+}
+
+/// Shows the "Routes" dialog (opened if `open` is true).
+pub fn handle_routes(
+    ui: &imgui::Ui,
+    state: &mut RouteState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Routes");
+    }
+    ui.popup_modal("Routes").always_auto_resize(true).build(ui, || {
+        let unit = program_data.distance_unit;
+
+        ui.text("Creates a vector layer with the great-circle route (the shortest path) or the rhumb line");
+        ui.text("(the path of constant bearing) between two points.");
+        ui.text("The points can also be set by double-clicking a view (see \"Tools/Input bindings...\").");
+
+        for (idx, label) in ["start", "end"].iter().enumerate() {
+            handle_point_input(ui, label, &mut state.points[idx], program_data.user_markers());
+            ui.same_line();
+            ui.text(label);
+        }
+
+        let [start, end] = state.points;
+        let radius = program_data.body().mean_radius;
+        ui.text(format!(
+            "Great circle: {:.1} {}, initial bearing {:.1}°",
+            unit.from_km(geometry::great_circle_distance(start, end).0 * radius),
+            unit.symbol(),
+            geometry::initial_bearing(start, end).0
+        ));
+        ui.text(format!(
+            "Rhumb line: {:.1} {}, bearing {:.1}°",
+            unit.from_km(geometry::rhumb_line_distance(start, end).0 * radius),
+            unit.symbol(),
+            geometry::rhumb_line_bearing(start, end).0
+        ));
+
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        let mut route = None;
+        if ui.button("Create great circle") {
+            let lines = geometry::great_circle_route(start, end, ROUTE_MAX_STEP.into());
+            route = Some(("great circle", lines, GREAT_CIRCLE_LINE_STYLE));
+        }
+        ui.same_line();
+        if ui.button("Create rhumb line") {
+            let lines = geometry::rhumb_line_route(start, end, ROUTE_MAX_STEP.into());
+            route = Some(("rhumb line", lines, RHUMB_LINE_STYLE));
+        }
+        if let Some((kind, result, style)) = route {
+            state.status = Some(match result {
+                Ok(lines) => {
+                    let name = format!(
+                        "{} {:.2}°, {:.2}° - {:.2}°, {:.2}°", kind, start[0], start[1], end[0], end[1]
+                    );
+                    program_data.add_vector_layer(
+                        name.clone(),
+                        vec![kml::Placemark{ style: None, lines }],
+                        style,
+                        display
+                    );
+                    format!("Created {}.", name)
+                },
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,75 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Saving and loading of sessions (all views and layer settings).
+//
+
+use crate::data;
+use crate::session;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const DEFAULT_SESSION_FILE: &str = "session.json";
+
+#[derive(Default)]
+pub struct SessionState {
+    path: String,
+    /// Result of the last save or load.
+    status: Option<String>
+}
+
+impl SessionState {
+    pub fn new() -> SessionState {
+        SessionState{
+            path: DEFAULT_SESSION_FILE.to_string(),
+            status: None
+        }
+    }
+}
+
+/// Shows the "Session" dialog (opened if `open` is true).
+pub fn handle_session(
+    ui: &imgui::Ui,
+    state: &mut SessionState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Session");
+    }
+    ui.popup_modal("Session").always_auto_resize(true).build(ui, || {
+        ui.text("Saves or loads all views and layer settings. Loading replaces the current views.");
+        ui.input_text("session file", &mut state.path).build();
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Save") {
+            state.status = Some(match session::save(&state.path, program_data) {
+                Ok(()) => format!("Saved {}.", state.path),
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if ui.button("Load") {
+            state.status = Some(match session::load(&state.path, program_data, renderer, display) {
+                Ok(()) => format!("Loaded {}.", state.path),
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,89 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Export of the lines of the focused view as SVG.
+//
+
+use crate::data;
+use crate::export;
+
+const DEFAULT_SVG_FILE: &str = "view.svg";
+
+/// Default width (pixels) of SVG documents exported by "File/Export SVG...".
+const DEFAULT_SVG_WIDTH: i32 = 1200;
+
+#[derive(Default)]
+pub struct SvgExportState {
+    path: String,
+    /// Width of the document (pixels); the height follows from the view's aspect ratio.
+    width: i32,
+    /// Result of the last export.
+    status: Option<String>
+}
+
+impl SvgExportState {
+    pub fn new() -> SvgExportState {
+        SvgExportState{
+            path: DEFAULT_SVG_FILE.to_string(),
+            width: DEFAULT_SVG_WIDTH,
+            status: None
+        }
+    }
+}
+
+/// Shows the "Export SVG" dialog (opened if `open` is true).
+pub fn handle_svg_export(
+    ui: &imgui::Ui,
+    state: &mut SvgExportState,
+    open: bool,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Export SVG");
+    }
+    ui.popup_modal("Export SVG").always_auto_resize(true).build(ui, || {
+        let focused_view = focused_view.and_then(
+            |id| program_data.all_views_mut().find(|view| view.unique_id() == id)
+        );
+
+        ui.text("Saves the lines of the most recently focused view (coastlines, graticule, vector layers, overlays)");
+        ui.text("as editable SVG paths, clipped to the view. Raster layers (e.g. the globe texture) are not included,");
+        ui.text("so the export is best suited to the vector map (\"lines\") mode.");
+        ui.input_text("output file", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new().add_filter("SVG", &["svg"]).save_file() {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+        ui.input_int("width", &mut state.width).build();
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        match focused_view {
+            None => ui.text("No view to export."),
+            Some(view) => if ui.button("Export") {
+                let width = state.width.max(0) as u32;
+                let height = (width as f32 / view.wh_ratio()).round() as u32;
+                state.status = Some(match export::export_view_svg(&state.path, view, width, height) {
+                    Ok(()) => format!("Saved {} ({}×{}).", state.path, width, height),
+                    Err(e) => format!("Error: {}.", e)
+                });
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
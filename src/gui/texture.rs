@@ -0,0 +1,114 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Loading of the globe texture, GeoTIFF rasters and texture overlays.
+//
+
+use crate::data;
+
+#[derive(Default)]
+pub struct TextureState {
+    path: String,
+    /// Result of the last load.
+    status: Option<String>
+}
+
+impl TextureState {
+    pub fn new(path: &str) -> TextureState {
+        TextureState{
+            path: path.to_string(),
+            status: None
+        }
+    }
+}
+
+/// Shows the "Open texture" dialog (opened if `open` is true).
+pub fn handle_texture(
+    ui: &imgui::Ui,
+    state: &mut TextureState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Open texture");
+    }
+    ui.popup_modal("Open texture").always_auto_resize(true).build(ui, || {
+        ui.text("Loads an equirectangular image (covering 360° × 180°) shown by views in the globe texture mode.");
+        ui.text("A georeferenced GeoTIFF raster (geographic, Web Mercator or UTM coordinates) can instead be added");
+        ui.text("onto the current texture, or an equirectangular image of e.g. cloud cover can be blended over it");
+        ui.text("as an overlay (dark areas of the overlay are transparent). A sequence of overlay images (e.g. hourly");
+        ui.text("cloud cover) is shown one at a time, as chosen with the timeline.");
+        ui.input_text("image file", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("Images", &["jpg", "jpeg", "png", "tif", "tiff", "bmp"])
+                .pick_file()
+            {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+        if let Some(mut opacity) = program_data.overlay().map(|overlay| overlay.opacity) {
+            let _width = ui.push_item_width(200.0);
+            if imgui::Slider::new("overlay opacity", 0.0, 1.0).build(ui, &mut opacity) {
+                program_data.set_overlay_opacity(opacity);
+            }
+            ui.same_line();
+            if ui.button("Remove overlay") {
+                program_data.remove_overlay();
+            }
+        }
+
+        ui.separator();
+        if ui.button("Load") {
+            state.status = Some(match program_data.load_globe_texture(&state.path, display) {
+                Ok(()) => format!("Loaded {}.", state.path),
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if ui.button("Add GeoTIFF") {
+            state.status = Some(match program_data.load_geotiff(&state.path, display) {
+                Ok(()) => format!("Added {}.", state.path),
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if ui.button("Load overlay") {
+            state.status = Some(match program_data.load_overlay(&state.path, display) {
+                Ok(()) => format!("Loaded overlay {}.", state.path),
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if ui.button("Load overlay sequence...") {
+            if let Some(paths) = rfd::FileDialog::new()
+                .add_filter("Images", &["jpg", "jpeg", "png", "tif", "tiff", "bmp"])
+                .pick_files()
+            {
+                let mut paths: Vec<String> = paths.iter().map(|path| path.to_string_lossy().into_owned()).collect();
+                paths.sort();
+                let num_frames = paths.len();
+                state.status = Some(match program_data.load_overlay_sequence(paths, display) {
+                    Ok(()) => format!("Loaded a sequence of {} overlay images.", num_frames),
+                    Err(e) => format!("Error: {}", e)
+                });
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
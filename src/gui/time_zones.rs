@@ -0,0 +1,75 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Time-zone boundaries.
+//
+
+use crate::data;
+
+#[derive(Default)]
+pub struct TimeZonesState {
+    /// GeoJSON file (may be zipped).
+    path: String,
+    /// If true, UTC offsets of time zones are shown (with the "time zones" layer).
+    pub labels: bool,
+    /// Result of the last load.
+    status: Option<String>
+}
+
+impl TimeZonesState {
+    pub fn new(path: &str) -> TimeZonesState {
+        TimeZonesState{
+            path: path.to_string(),
+            labels: true,
+            status: None
+        }
+    }
+}
+
+/// Shows the "Time zones" dialog (opened if `open` is true).
+pub fn handle_time_zones(
+    ui: &imgui::Ui,
+    state: &mut TimeZonesState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Time zones");
+    }
+    ui.popup_modal("Time zones").always_auto_resize(true).build(ui, || {
+        ui.text("Loads time-zone boundaries from the timezone-boundary-builder dataset (GeoJSON, may be zipped;");
+        ui.text("https://github.com/evansiroky/timezone-boundary-builder) and shows them in all views as the");
+        ui.text("\"time zones\" layer, optionally with the zones' current offsets from UTC.");
+        ui.input_text("GeoJSON file", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new().add_filter("GeoJSON", &["json", "geojson", "zip"]).pick_file() {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+        ui.checkbox("UTC offset labels", &mut state.labels);
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Load") {
+            state.status = Some(match program_data.load_time_zones(&state.path, display) {
+                Ok(()) => format!("Loaded {}.", state.path),
+                Err(e) => format!("Error: {}", e)
+            });
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,386 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Guided tour of the available projections.
+//
+
+use crate::data;
+use crate::views;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Speed of the globe's rotation during demonstration.
+const ROTATION_SPEED: cgmath::Deg<f64> = cgmath::Deg(15.0); // per second
+
+/// During demonstration, zoom oscillates between initial / `ZOOM_AMPLITUDE` and initial * `ZOOM_AMPLITUDE`.
+const ZOOM_AMPLITUDE: f64 = 1.3;
+
+/// Period (in seconds) of the zoom oscillation.
+const ZOOM_PERIOD: f64 = 12.0;
+
+/// Period (in seconds) of the sweep of the step's projection parameter (if any).
+const PARAM_SWEEP_PERIOD: f64 = 10.0;
+
+struct TourStep {
+    /// One of `views::PROJECTION_NAMES`.
+    projection: &'static str,
+    title: &'static str,
+    description: &'static str,
+    /// Uniform name of the projection parameter swept during demonstration (if any).
+    sweep: Option<&'static str>
+}
+
+const STEPS: &[TourStep] = &[
+    TourStep{
+        projection: "orthographic",
+        title: "Orthographic",
+        description: "The globe as seen from an infinite distance. Exactly one hemisphere is shown; shapes and areas \
+            are increasingly compressed towards its edge. Neither conformal nor equal-area, but the most \
+            natural-looking.",
+        sweep: None
+    },
+    TourStep{
+        projection: "general_perspective",
+        title: "General perspective",
+        description: "The globe as seen from a finite distance, e.g. by a geostationary satellite. Less than \
+            a hemisphere is visible; the observer's line of sight can also be tilted.",
+        sweep: Some("tilt")
+    },
+    TourStep{
+        projection: "stereographic",
+        title: "Stereographic",
+        description: "Projection onto a plane from the point opposite to the center of the view. It is conformal \
+            (preserves angles) and maps circles on the globe to circles. Moving the projection point shows the whole \
+            family of azimuthal perspective projections, from near-gnomonic to near-orthographic.",
+        sweep: Some("point_distance")
+    },
+    TourStep{
+        projection: "lambert_azimuthal",
+        title: "Lambert azimuthal equal-area",
+        description: "The equal-area counterpart of the stereographic projection: areas are preserved, while shapes \
+            are increasingly sheared towards the edge. The whole globe fits in a circle, with the point opposite \
+            to the center of the view stretched along its boundary.",
+        sweep: None
+    },
+    TourStep{
+        projection: "gnomonic",
+        title: "Gnomonic",
+        description: "Projection from the globe's center onto a tangent plane. Every great circle is a straight line, \
+            so shortest routes are straight; distortion grows without bound towards 90° from the center.",
+        sweep: None
+    },
+    TourStep{
+        projection: "mercator",
+        title: "Mercator",
+        description: "Conformal cylindrical projection: rhumb lines (lines of constant bearing) are straight, which \
+            made it the standard for navigation. Areas grow without bound towards the poles.",
+        sweep: None
+    },
+    TourStep{
+        projection: "transverse_mercator",
+        title: "Transverse Mercator",
+        description: "The Mercator projection with the cylinder touching a meridian instead of the equator. Distortion \
+            is small near the central meridian, which makes it the basis of UTM and many national grids.",
+        sweep: None
+    },
+    TourStep{
+        projection: "miller",
+        title: "Miller cylindrical",
+        description: "A compromise between the Mercator and equirectangular projections: parallels are spaced less \
+            widely towards the poles, so that the poles can be shown.",
+        sweep: None
+    },
+    TourStep{
+        projection: "gall_stereographic",
+        title: "Gall stereographic",
+        description: "Cylindrical projection onto a cylinder cutting the globe at 45°; a compromise which moderates \
+            the Mercator's exaggeration of high latitudes.",
+        sweep: None
+    },
+    TourStep{
+        projection: "cylindrical_lambert",
+        title: "Lambert cylindrical equal-area",
+        description: "Projection onto a cylinder wrapped around the equator, along lines perpendicular to the axis. \
+            Areas are preserved, but shapes are strongly compressed north-south towards the poles. Moving the standard \
+            parallel away from the equator (e.g. to 30° for Behrmann, 45° for Gall-Peters) moves the undistorted \
+            region with it.",
+        sweep: Some("standard_parallel")
+    },
+    TourStep{
+        projection: "lambert_conformal_conic",
+        title: "Lambert conformal conic",
+        description: "Conformal projection onto a cone; the scale is true along two standard parallels. Well suited \
+            to regions extending east-west in the middle latitudes, e.g. for aeronautical charts.",
+        sweep: Some("standard_parallel_1")
+    },
+    TourStep{
+        projection: "albers_equal_area",
+        title: "Albers equal-area conic",
+        description: "The equal-area counterpart of the Lambert conformal conic projection, with two standard \
+            parallels; commonly used for maps of the United States.",
+        sweep: Some("standard_parallel_1")
+    },
+    TourStep{
+        projection: "mollweide",
+        title: "Mollweide",
+        description: "Equal-area projection of the whole globe onto an ellipse with a 2:1 axis ratio. Parallels are \
+            straight; shapes are distorted towards the edges. The map can be interrupted into lobes.",
+        sweep: None
+    },
+    TourStep{
+        projection: "eckert_iv",
+        title: "Eckert IV",
+        description: "Equal-area pseudocylindrical projection with the poles shown as lines half as long as the \
+            equator and meridians drawn as elliptical arcs.",
+        sweep: None
+    },
+    TourStep{
+        projection: "eckert_vi",
+        title: "Eckert VI",
+        description: "Equal-area pseudocylindrical projection with pole lines half as long as the equator and \
+            meridians drawn as sinusoids.",
+        sweep: None
+    },
+    TourStep{
+        projection: "hammer",
+        title: "Hammer",
+        description: "Equal-area projection of the whole globe onto an ellipse, obtained by stretching a hemisphere \
+            of the Lambert azimuthal projection. Parallels are curved, which reduces shear near the edges compared \
+            to Mollweide. Changing the axis ratio keeps the map equal-area.",
+        sweep: Some("axis_ratio")
+    },
+    TourStep{
+        projection: "briesemeister",
+        title: "Briesemeister",
+        description: "Oblique Hammer variant with a 1.75:1 axis ratio, centered so that the continents are shown \
+            with little interruption.",
+        sweep: None
+    },
+    TourStep{
+        projection: "atlantis",
+        title: "Atlantis",
+        description: "Transverse Mollweide projection centered on the Atlantic Ocean, showing it as one uninterrupted \
+            body of water surrounded by the continents.",
+        sweep: None
+    },
+    TourStep{
+        projection: "loximuthal",
+        title: "Loximuthal",
+        description: "Rhumb lines (lines of constant bearing) from the central point are straight and true to scale. \
+            The central latitude can be changed.",
+        sweep: Some("central_latitude")
+    },
+    TourStep{
+        projection: "nicolosi",
+        title: "Nicolosi globular",
+        description: "A hemisphere within a circle, with meridians and parallels drawn as circular arcs. \
+            Neither conformal nor equal-area; historically used for maps of the hemispheres.",
+        sweep: None
+    },
+    TourStep{
+        projection: "armadillo",
+        title: "Armadillo",
+        description: "Orthographic view of the globe's surface wrapped on a torus, showing most of the world \
+            in a single, continuous image. The parallel at the torus' rim can be changed.",
+        sweep: Some("parallel")
+    },
+    TourStep{
+        projection: "adams_hemisphere",
+        title: "Adams hemisphere-in-a-square",
+        description: "Conformal projection of a hemisphere onto a square; the scale is infinite only \
+            at the square's corners.",
+        sweep: None
+    },
+    TourStep{
+        projection: "guyou",
+        title: "Guyou",
+        description: "Conformal projection of the whole globe onto a rectangle consisting of two squares, \
+            each containing a hemisphere.",
+        sweep: None
+    },
+    TourStep{
+        projection: "peirce_quincuncial",
+        title: "Peirce quincuncial",
+        description: "Conformal projection of the whole globe onto a square, with one hemisphere in the inner \
+            square and the other split among the corners. Copies of the map can be tiled without gaps.",
+        sweep: None
+    },
+    TourStep{
+        projection: "littrow",
+        title: "Littrow",
+        description: "Conformal retroazimuthal projection: the direction from any point towards the center of the view \
+            is shown correctly relative to the local meridian.",
+        sweep: None
+    },
+    TourStep{
+        projection: "qsc",
+        title: "Quadrilateralized spherical cube",
+        description: "The globe is mapped onto the six faces of a cube, with areas preserved within each face. \
+            Used for storing whole-sky data (e.g. by the COBE satellite).",
+        sweep: None
+    }
+];
+
+pub struct TourState {
+    step: usize,
+    /// Unique id of the view created for the current step, or the error which prevented its creation.
+    view_id: Result<u32, String>,
+    /// Time (in seconds) since the current step started.
+    time: f64,
+    /// Zoom of the view at the start of the step.
+    initial_zoom: Option<f64>,
+    /// Value of the swept projection parameter (if any) at the start of the step.
+    initial_param_value: Option<f64>,
+    animate: bool
+}
+
+impl TourState {
+    /// Starts the tour from the first step.
+    pub fn new(
+        program_data: &mut data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> TourState {
+        TourState{
+            step: 0,
+            view_id: create_view(&STEPS[0], program_data, renderer, display),
+            time: 0.0,
+            initial_zoom: None,
+            initial_param_value: None,
+            animate: true
+        }
+    }
+
+    fn go_to_step(
+        &mut self,
+        step: usize,
+        program_data: &mut data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) {
+        if let Ok(view_id) = self.view_id {
+            program_data.remove_view(view_id);
+        }
+        self.step = step;
+        self.view_id = create_view(&STEPS[step], program_data, renderer, display);
+        self.time = 0.0;
+        self.initial_zoom = None;
+        self.initial_param_value = None;
+    }
+
+    /// Advances the demonstration animation of the current step's view by `dt` seconds.
+    fn animate(&mut self, dt: f64, program_data: &mut data::ProgramData) {
+        let view_id = match self.view_id {
+            Ok(view_id) => view_id,
+            Err(_) => return
+        };
+        let view = match program_data.all_views_mut().find(|v| v.unique_id() == view_id) {
+            Some(view) => view,
+            None => return // closed by the user
+        };
+
+        let initial_zoom = *self.initial_zoom.get_or_insert(view.zoom());
+        self.time += dt;
+        let time = self.time;
+        let phase = |period: f64| (2.0 * std::f64::consts::PI * time / period).sin();
+
+        view.rotate_ew(cgmath::Rad::from(ROTATION_SPEED) * dt);
+
+        let zoom = initial_zoom * ZOOM_AMPLITUDE.powf(phase(ZOOM_PERIOD));
+        view.zoom_by(zoom / view.zoom());
+
+        let swept_param = STEPS[self.step].sweep
+            .and_then(|name| view.projection_params().iter().position(|param| param.uniform_name == name));
+        if let Some(idx) = swept_param {
+            let param = view.projection_params()[idx];
+            let initial_value = *self.initial_param_value.get_or_insert(param.value);
+            let amplitude = (param.max - param.min) / 2.0;
+            view.set_projection_param_value(idx, initial_value + amplitude * phase(PARAM_SWEEP_PERIOD));
+        }
+    }
+}
+
+/// Creates the view of a tour step; returns its unique id.
+fn create_view(
+    step: &TourStep,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) -> Result<u32, String> {
+    views::create_view(step.projection, None, program_data, renderer, display)
+        .map_err(|e| format!("Failed to create the view of \"{}\": {}", step.title, e))
+}
+
+/// Returns `false` if the tour has been closed.
+pub fn handle_tour(
+    ui: &imgui::Ui,
+    state: &mut TourState,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) -> bool {
+    let mut opened = true;
+    let mut new_step = None;
+
+    imgui::Window::new(ui, "Projection tour")
+        .size([400.0, 260.0], imgui::Condition::FirstUseEver)
+        .opened(&mut opened)
+        .build(|| {
+            let step = &STEPS[state.step];
+            ui.text(format!("{} / {}", state.step + 1, STEPS.len()));
+            ui.same_line();
+            ui.text(step.title);
+            ui.separator();
+            ui.text_wrapped(step.description);
+            if let Err(error) = &state.view_id {
+                ui.text_colored([1.0, 0.3, 0.3, 1.0], error);
+            }
+            ui.separator();
+
+            if state.step > 0 && ui.button("< previous") {
+                new_step = Some(state.step - 1);
+            }
+            if state.step + 1 < STEPS.len() {
+                if state.step > 0 { ui.same_line(); }
+                if ui.button("next >") {
+                    new_step = Some(state.step + 1);
+                }
+            }
+            ui.same_line();
+            ui.checkbox("animate", &mut state.animate);
+        });
+
+    if !opened {
+        if let Ok(view_id) = state.view_id {
+            program_data.remove_view(view_id);
+        }
+        return false;
+    }
+
+    if let Some(step) = new_step {
+        state.go_to_step(step, program_data, renderer, display);
+    } else if state.animate {
+        state.animate(program_data.animation_clock.dt(), program_data);
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tour_shows_every_projection_once() {
+        for name in views::PROJECTION_NAMES {
+            assert_eq!(1, STEPS.iter().filter(|step| step.projection == name).count(), "{}", name);
+        }
+        assert_eq!(views::PROJECTION_NAMES.len(), STEPS.len());
+    }
+}
@@ -0,0 +1,116 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Named markers shown in all views and kept between runs.
+//
+
+use crate::data;
+use crate::session;
+
+#[derive(Default)]
+pub struct UserMarkersState {
+    /// Name of a marker to be added.
+    name: String,
+    /// Longitude and latitude of a marker to be added (degrees).
+    lonlat: [f64; 2],
+    /// Result of the last save.
+    status: Option<String>
+}
+
+/// Saves the user markers to the markers file.
+pub fn store_user_markers(program_data: &data::ProgramData) -> Result<(), String> {
+    session::save_user_markers(&session::user_markers_path(), program_data.user_markers())
+}
+
+/// Shows the "Markers" dialog (opened if `open` is true).
+pub fn handle_user_markers(
+    ui: &imgui::Ui,
+    state: &mut UserMarkersState,
+    open: bool,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Markers");
+    }
+    ui.popup_modal("Markers").always_auto_resize(true).build(ui, || {
+        ui.text("Named markers are shown in all views (with the \"markers\" layer) and kept between runs.");
+        ui.text("They can also be added by double-clicking a view (see \"Tools/Input bindings...\").");
+
+        let mut markers = program_data.user_markers().to_vec();
+        let mut changed = false;
+        let mut removed = None;
+        for (idx, marker) in markers.iter_mut().enumerate() {
+            let _width = ui.push_item_width(150.0);
+            changed |= ui.input_text(&format!("##marker_name{}", idx), &mut marker.name).build();
+            ui.same_line();
+            let _width = ui.push_item_width(80.0);
+            changed |= imgui::Drag::new(&format!("##marker_lon{}", idx))
+                .range(-180.0, 180.0)
+                .speed(0.01)
+                .display_format("%.4f°")
+                .build(ui, &mut marker.longitude.0);
+            ui.same_line();
+            changed |= imgui::Drag::new(&format!("##marker_lat{}", idx))
+                .range(-90.0, 90.0)
+                .speed(0.01)
+                .display_format("%.4f°")
+                .build(ui, &mut marker.latitude.0);
+            ui.same_line();
+            if ui.small_button(&format!("delete##marker{}", idx)) {
+                removed = Some(idx);
+            }
+        }
+        if let Some(idx) = removed {
+            markers.remove(idx);
+            changed = true;
+        }
+
+        ui.separator();
+        {
+            let _width = ui.push_item_width(150.0);
+            ui.input_text("##new_marker_name", &mut state.name).build();
+            ui.same_line();
+            let _width = ui.push_item_width(80.0);
+            imgui::Drag::new("##new_marker_lon").range(-180.0, 180.0).speed(0.01).display_format("%.4f°")
+                .build(ui, &mut state.lonlat[0]);
+            ui.same_line();
+            imgui::Drag::new("##new_marker_lat").range(-90.0, 90.0).speed(0.01).display_format("%.4f°")
+                .build(ui, &mut state.lonlat[1]);
+            ui.same_line();
+            if ui.button("Add") {
+                let name = if state.name.is_empty() {
+                    format!("marker {}", markers.len() + 1)
+                } else {
+                    state.name.clone()
+                };
+                markers.push(data::UserMarker{
+                    name,
+                    longitude: cgmath::Deg(state.lonlat[0]),
+                    latitude: cgmath::Deg(state.lonlat[1])
+                });
+                state.name.clear();
+                changed = true;
+            }
+        }
+
+        if changed {
+            program_data.set_user_markers(markers);
+            state.status = store_user_markers(program_data).err().map(|e| format!("Error: {}", e));
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
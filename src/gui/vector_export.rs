@@ -0,0 +1,97 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Export of vector layers projected as in the focused view (GeoJSON or shapefiles).
+//
+
+use crate::data;
+
+const DEFAULT_VECTOR_EXPORT_FILE: &str = "layers.geojson";
+
+#[derive(Default)]
+pub struct VectorExportState {
+    path: String,
+    /// Layers to export, indexed as `ProgramData::vector_layers`.
+    selected: Vec<bool>,
+    /// Result of the last export.
+    status: Option<String>
+}
+
+impl VectorExportState {
+    pub fn new() -> VectorExportState {
+        VectorExportState{
+            path: DEFAULT_VECTOR_EXPORT_FILE.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Shows the "Export vector layers" dialog (opened if `open` is true).
+pub fn handle_vector_export(
+    ui: &imgui::Ui,
+    state: &mut VectorExportState,
+    open: bool,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        state.selected = program_data.vector_layers().iter().map(|layer| layer.visible).collect();
+        ui.open_popup("Export vector layers");
+    }
+    ui.popup_modal("Export vector layers").always_auto_resize(true).build(ui, || {
+        ui.text("Saves the chosen vector layers projected as in the most recently focused view (without its zoom,");
+        ui.text("stretch and roll), in grid coordinates (meters), as GeoJSON or a shapefile (\".shp\"). If the view");
+        ui.text("has a standard coordinate reference system (see \"File/Export GeoTIFF...\"), it is saved next to");
+        ui.text("a shapefile as a \".prj\" file.");
+        ui.input_text("output file", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("GeoJSON", &["geojson", "json"])
+                .add_filter("Shapefile", &["shp"])
+                .save_file()
+            {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+
+        ui.separator();
+        state.selected.resize(program_data.vector_layers().len(), false);
+        for (idx, layer) in program_data.vector_layers().iter().enumerate() {
+            ui.checkbox(&format!("{}##export_vector_layer_{}", layer.name, idx), &mut state.selected[idx]);
+        }
+        if program_data.vector_layers().is_empty() {
+            ui.text("No vector layers loaded.");
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        match focused_view {
+            None => ui.text("No view to export."),
+            Some(view_id) => if ui.button("Export") {
+                let indices: Vec<usize> = (0..state.selected.len()).filter(|idx| state.selected[*idx]).collect();
+                state.status = Some(if indices.is_empty() {
+                    "Error: no layers chosen.".to_string()
+                } else {
+                    match program_data.export_vector_layers(view_id, &indices, &state.path) {
+                        Ok(()) => format!("Saved {}.", state.path),
+                        Err(e) => format!("Error: {}.", e)
+                    }
+                });
+            }
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,368 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Vector layers (GeoJSON, KML, shapefiles) and point layers (CSV): loading, styles and attribute filters.
+//
+
+use crate::csv;
+use crate::data;
+use crate::shape_filter;
+
+/// Max. tolerance (arcminutes) of simplification of vector layers.
+const MAX_LINE_SIMPLIFICATION: f32 = 30.0;
+
+#[derive(Default)]
+pub struct VectorLayersState {
+    path: String,
+    /// Result of the last load.
+    status: Option<String>,
+    /// Loaded CSV file (to be added as a point layer) and its columns chosen for the points' data.
+    csv: Option<(csv::Table, csv::PointColumns)>,
+    /// Attribute filter being edited.
+    filter: Option<FilterState>,
+    /// Tolerance (arcminutes) of simplification of lines.
+    simplification: f32
+}
+
+struct FilterState {
+    /// Index of the filtered layer in `ProgramData::vector_layers`.
+    layer: usize,
+    /// Attributes of the layer's features.
+    attributes: Vec<String>,
+    attribute_idx: usize,
+    comparison_idx: usize,
+    value: String,
+    /// Result of the last filtering.
+    status: Option<String>
+}
+
+impl FilterState {
+    fn new(layer: usize, attributes: Vec<String>, filter: Option<&shape_filter::AttributeFilter>) -> FilterState {
+        let find = |attribute: &str| attributes.iter().position(|name| name == attribute);
+        FilterState{
+            layer,
+            attribute_idx: filter.and_then(|filter| find(&filter.attribute))
+                .or_else(|| find("scalerank"))
+                .unwrap_or(0),
+            comparison_idx: filter
+                .and_then(|filter| shape_filter::Comparison::ALL.iter().position(|c| *c == filter.comparison))
+                .unwrap_or(1),
+            value: filter.map(|filter| filter.value.clone()).unwrap_or_default(),
+            attributes,
+            status: None
+        }
+    }
+}
+
+/// Shows controls of visibility and symbols of the point layers.
+fn handle_point_layers(ui: &imgui::Ui, program_data: &mut data::ProgramData) {
+    let mut removed = None;
+    let mut new_visibility = None;
+    let mut new_style = None;
+    for (idx, layer) in program_data.point_layers().iter().enumerate() {
+        if ui.small_button(&format!("Remove##point_layer_{}", idx)) {
+            removed = Some(idx);
+        }
+        ui.same_line();
+        let mut visible = layer.visible;
+        if ui.checkbox(&format!("##point_layer_visible_{}", idx), &mut visible) {
+            new_visibility = Some((idx, visible));
+        }
+        ui.same_line();
+        let mut style = layer.style;
+        let mut style_changed = imgui::ColorEdit::new(&format!("##point_layer_color_{}", idx), &mut style.color)
+            .inputs(false)
+            .build(ui);
+        ui.same_line();
+        let _width = ui.push_item_width(60.0);
+        style_changed |= imgui::Drag::new(&format!("##point_layer_radius_{}", idx))
+            .range(1.0, 50.0)
+            .speed(0.05)
+            .display_format("%.1f px")
+            .build(ui, &mut style.radius);
+        ui.same_line();
+        ui.text(format!("{} ({} points)", layer.name, layer.points.len()));
+
+        ui.indent();
+        style_changed |= ui.checkbox(&format!("labels##point_layer_labels_{}", idx), &mut style.labels);
+        if let Some([min, max]) = layer.value_range {
+            ui.same_line();
+            style_changed |= ui.checkbox(&format!("size by value##point_layer_size_{}", idx), &mut style.size_by_value);
+            if style.size_by_value {
+                ui.same_line();
+                style_changed |= imgui::Drag::new(&format!("##point_layer_max_radius_{}", idx))
+                    .range(1.0, 50.0)
+                    .speed(0.05)
+                    .display_format("max. %.1f px")
+                    .build(ui, &mut style.max_radius);
+            }
+            ui.same_line();
+            style_changed |= ui.checkbox(
+                &format!("color by value##point_layer_color_by_value_{}", idx),
+                &mut style.color_by_value
+            );
+            if style.color_by_value {
+                ui.same_line();
+                style_changed |= imgui::ColorEdit::new(
+                    &format!("##point_layer_max_color_{}", idx),
+                    &mut style.max_color
+                ).inputs(false).build(ui);
+            }
+            ui.same_line();
+            ui.text(format!("(values from {} to {})", min, max));
+        }
+        ui.unindent();
+
+        if style_changed {
+            new_style = Some((idx, style));
+        }
+    }
+    if let Some((idx, visible)) = new_visibility {
+        program_data.set_point_layer_visible(idx, visible);
+    }
+    if let Some((idx, style)) = new_style {
+        program_data.set_point_layer_style(idx, style);
+    }
+    if let Some(idx) = removed {
+        program_data.remove_point_layer(idx);
+    }
+}
+
+/// Shows the "Vector layers" dialog (opened if `open` is true).
+pub fn handle_vector_layers(
+    ui: &imgui::Ui,
+    state: &mut VectorLayersState,
+    open: bool,
+    program_data: &mut data::ProgramData,
+    display: &glium::Display
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Vector layers");
+    }
+    ui.popup_modal("Vector layers").always_auto_resize(true).build(ui, || {
+        ui.text("Loads lines (LineString, MultiLineString, Polygon, MultiPolygon) from a GeoJSON file,");
+        ui.text("or placemarks (with their line styles) from a KML/KMZ file;");
+        ui.text("they are shown in all views as the \"vector layers\" layer.");
+        ui.text("Visibility, color and line width can be set for each layer; the coastline is shown only in");
+        ui.text("the vector map mode.");
+        ui.text("Points (with optional labels and values) can be loaded from a CSV file with a header row;");
+        ui.text("their symbols' size and color can depend on the values.");
+        ui.input_text("GeoJSON/KML/CSV file", &mut state.path).build();
+        ui.same_line();
+        if ui.button("Browse...") {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("GeoJSON", &["geojson", "json"])
+                .add_filter("KML", &["kml", "kmz"])
+                .add_filter("CSV", &["csv", "txt"])
+                .pick_file() {
+                state.path = path.to_string_lossy().into_owned();
+            }
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.separator();
+        let mut removed = None;
+        let mut new_visibility = None;
+        let mut new_style = None;
+        let mut filter_clicked = None;
+        for (idx, layer) in program_data.vector_layers().iter().enumerate() {
+            if ui.small_button(&format!("Remove##vector_layer_{}", idx)) {
+                removed = Some(idx);
+            }
+            ui.same_line();
+            let mut visible = layer.visible;
+            if ui.checkbox(&format!("##vector_layer_visible_{}", idx), &mut visible) {
+                new_visibility = Some((idx, visible));
+            }
+            ui.same_line();
+            let mut style = layer.style;
+            let mut style_changed = imgui::ColorEdit::new(&format!("##vector_layer_color_{}", idx), &mut style.color)
+                .inputs(false)
+                .build(ui);
+            ui.same_line();
+            let _width = ui.push_item_width(60.0);
+            style_changed |= imgui::Drag::new(&format!("##vector_layer_width_{}", idx))
+                .range(0.5, 10.0)
+                .speed(0.05)
+                .display_format("%.1f px")
+                .build(ui, &mut style.width);
+            if style_changed {
+                new_style = Some((idx, style));
+            }
+            if layer.shapefile.is_some() {
+                ui.same_line();
+                if ui.small_button(&format!("Filter...##vector_layer_{}", idx)) {
+                    filter_clicked = Some(idx);
+                }
+            }
+            ui.same_line();
+            match &layer.filter {
+                Some(filter) => ui.text(format!("{} ({} lines; {})", layer.name, layer.polylines.len(), filter)),
+                None => ui.text(format!("{} ({} lines)", layer.name, layer.polylines.len()))
+            }
+        }
+        if program_data.vector_layers().is_empty() {
+            ui.text("No vector layers loaded.");
+        }
+        if let Some(idx) = filter_clicked {
+            match program_data.vector_layer_attributes(idx) {
+                Ok(attributes) => {
+                    let filter = program_data.vector_layers()[idx].filter.as_ref();
+                    state.filter = Some(FilterState::new(idx, attributes, filter));
+                },
+                Err(e) => state.status = Some(format!("Failed to read attributes: {}", e))
+            }
+        }
+        if let Some((idx, visible)) = new_visibility {
+            program_data.set_vector_layer_visible(idx, visible);
+        }
+        if let Some((idx, style)) = new_style {
+            program_data.set_vector_layer_style(idx, style);
+        }
+        if let Some(idx) = removed {
+            program_data.remove_vector_layer(idx);
+            // indices of the following layers have changed
+            state.filter = None;
+        }
+
+        ui.separator();
+        ui.text("Lines can be simplified (Douglas-Peucker) for faster drawing and cleaner small-scale maps:");
+        {
+            let _width = ui.push_item_width(200.0);
+            imgui::Slider::new("tolerance##line_simplification", 0.0, MAX_LINE_SIMPLIFICATION)
+                .display_format("%.1f arcmin")
+                .build(ui, &mut state.simplification);
+        }
+        if ui.is_item_deactivated_after_edit() {
+            program_data.set_line_simplification(state.simplification as f64 / 60.0, display);
+        }
+        let (num_drawn, num_vertices) = program_data.vector_layer_vertices();
+        ui.text(format!("{} of {} vertices drawn", num_drawn, num_vertices));
+
+        let mut filter_closed = false;
+        if let Some(filter) = &mut state.filter {
+            ui.separator();
+            ui.text(format!("Show features of {} with:", program_data.vector_layers()[filter.layer].name));
+            {
+                let _width = ui.push_item_width(200.0);
+                ui.combo_simple_string("##filter_attribute", &mut filter.attribute_idx, &filter.attributes);
+            }
+            ui.same_line();
+            {
+                let _width = ui.push_item_width(50.0);
+                let symbols: Vec<&str> = shape_filter::Comparison::ALL.iter().map(|c| c.symbol()).collect();
+                ui.combo_simple_string("##filter_comparison", &mut filter.comparison_idx, &symbols);
+            }
+            ui.same_line();
+            {
+                let _width = ui.push_item_width(120.0);
+                ui.input_text("##filter_value", &mut filter.value).build();
+            }
+            ui.text("(numbers are compared numerically, other values as text)");
+
+            let mut new_filter = None;
+            if ui.button("Apply##filter") {
+                new_filter = Some(filter.attributes.get(filter.attribute_idx).map(|attribute| {
+                    shape_filter::AttributeFilter{
+                        attribute: attribute.clone(),
+                        comparison: shape_filter::Comparison::ALL[filter.comparison_idx],
+                        value: filter.value.trim().to_string()
+                    }
+                }));
+            }
+            ui.same_line();
+            if ui.button("Show all##filter") {
+                new_filter = Some(None);
+            }
+            ui.same_line();
+            if ui.button("Close##filter") {
+                filter_closed = true;
+            }
+            if let Some(new_filter) = new_filter {
+                filter.status = Some(match program_data.set_vector_layer_filter(filter.layer, new_filter, display) {
+                    Ok((num_shown, num_features)) => format!("{} of {} features shown.", num_shown, num_features),
+                    Err(e) => format!("Failed to filter features: {}", e)
+                });
+            }
+            if let Some(status) = &filter.status {
+                ui.text(status);
+            }
+        }
+        if filter_closed {
+            state.filter = None;
+        }
+
+        handle_point_layers(ui, program_data);
+
+        let mut point_layer_added = false;
+        if let Some((table, columns)) = &mut state.csv {
+            ui.separator();
+            ui.text(format!("Columns of {} ({} rows):", state.path, table.rows.len()));
+            let mut column_names = vec!["(none)"];
+            column_names.extend(table.columns.iter().map(|column| column.as_str()));
+            let _width = ui.push_item_width(200.0);
+            for (label, column) in [("longitude", &mut columns.longitude), ("latitude", &mut columns.latitude)] {
+                let mut idx = *column + 1;
+                if ui.combo_simple_string(label, &mut idx, &column_names) && idx > 0 {
+                    *column = idx - 1;
+                }
+            }
+            for (label, column) in [("label", &mut columns.label), ("value", &mut columns.value)] {
+                let mut idx = column.map_or(0, |column| column + 1);
+                if ui.combo_simple_string(label, &mut idx, &column_names) {
+                    *column = if idx > 0 { Some(idx - 1) } else { None };
+                }
+            }
+            if ui.button("Add point layer") {
+                state.status = Some(match table.points(*columns) {
+                    Ok((points, num_skipped)) => {
+                        let num_points = points.len();
+                        program_data.add_point_layer(state.path.clone(), points);
+                        point_layer_added = true;
+                        format!(
+                            "Added {} points (skipped rows without valid coordinates: {}).", num_points, num_skipped
+                        )
+                    },
+                    Err(e) => format!("Error: {}", e)
+                });
+            }
+        }
+        if point_layer_added {
+            state.csv = None;
+        }
+
+        ui.separator();
+        if ui.button("Load") {
+            state.csv = None;
+            let lower_case_path = state.path.to_lowercase();
+            state.status = if lower_case_path.ends_with(".csv") || lower_case_path.ends_with(".txt") {
+                match csv::load(&state.path) {
+                    Ok(table) => {
+                        let columns = table.guess_columns();
+                        state.csv = Some((table, columns));
+                        None
+                    },
+                    Err(e) => Some(format!("Error: {}", e))
+                }
+            } else {
+                Some(match program_data.load_vector_layer(&state.path, display) {
+                    Ok(()) => format!("Loaded {}.", state.path),
+                    Err(e) => format!("Error: {}", e)
+                })
+            };
+        }
+        ui.same_line();
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
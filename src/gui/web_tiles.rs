@@ -0,0 +1,188 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Base maps from XYZ web tile sources or MBTiles files, covering the region of the focused view.
+//
+
+use crate::data;
+use crate::views;
+use crate::web_tiles;
+
+/// Time (seconds) the focused view has to stay unchanged before web tiles are fetched again for it.
+const WEB_TILES_REFETCH_DELAY: f64 = 0.5;
+
+#[derive(Default)]
+pub struct WebTilesState {
+    /// If true, tiles are read from `mbtiles_path` instead of the web tile source.
+    use_mbtiles: bool,
+    mbtiles_path: String,
+    max_zoom: i32,
+    /// If true, tiles are fetched again whenever the focused view has been changed.
+    follow_view: bool,
+    /// Position (see `view_position`) of the focused view when last checked, and the time it was reached.
+    view_position: Option<([f64; 5], f64)>,
+    /// Position of the view the tiles have been fetched for.
+    fetched_position: Option<[f64; 5]>,
+    status: Option<String>
+}
+
+impl WebTilesState {
+    pub fn new() -> WebTilesState {
+        WebTilesState{
+            max_zoom: web_tiles::MAX_ZOOM as i32,
+            ..Default::default()
+        }
+    }
+}
+
+/// Saves the named markers in the user's configuration directory.
+/// Returns the view's center, zoom, roll and width-to-height ratio, which determine the region it shows.
+fn view_position(view: &views::ViewBase) -> [f64; 5] {
+    let (longitude, latitude) = view.central_lonlat();
+    [longitude.0, latitude.0, view.zoom(), view.roll().0, view.wh_ratio() as f64]
+}
+
+/// Starts fetching web tiles (from the source selected in the "Web tiles" dialog) covering the region shown
+/// by the view `view_id`.
+fn fetch_web_tiles_for_view(state: &mut WebTilesState, view_id: u32, program_data: &mut data::ProgramData) {
+    let (region, position) = match program_data.all_views_mut().find(|view| view.unique_id() == view_id) {
+        Some(view) => (view.visible_region(), view_position(view)),
+        None => return
+    };
+    state.fetched_position = Some(position);
+    match region {
+        Some((lon_range, lat_range, pixels_per_degree)) => {
+            let zoom = web_tiles::zoom_for_resolution(pixels_per_degree).min(state.max_zoom as u32);
+            let source = if state.use_mbtiles {
+                web_tiles::TileSource::MbTiles(state.mbtiles_path.clone())
+            } else {
+                web_tiles::TileSource::Web(program_data.tile_url.clone())
+            };
+            state.status = program_data.fetch_web_tiles(&source, lon_range, lat_range, zoom)
+                .err().map(|e| format!("Error: {}.", e));
+        },
+        None => state.status = Some("Error: the view does not show any region.".into())
+    }
+}
+
+/// If following the focused view is enabled, fetches web tiles for it once it has stayed unchanged
+/// for `WEB_TILES_REFETCH_DELAY`.
+pub fn follow_view_with_web_tiles(
+    state: &mut WebTilesState,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if !state.follow_view { return; }
+    let view_id = match focused_view { Some(id) => id, None => return };
+    let position = match program_data.all_views_mut().find(|view| view.unique_id() == view_id) {
+        Some(view) => view_position(view),
+        None => return
+    };
+
+    let now = program_data.animation_clock.time();
+    let since = match state.view_position {
+        Some((last_position, since)) if last_position == position => since,
+        _ => {
+            state.view_position = Some((position, now));
+            return;
+        }
+    };
+    if state.fetched_position != Some(position) && now - since >= WEB_TILES_REFETCH_DELAY {
+        fetch_web_tiles_for_view(state, view_id, program_data);
+    }
+}
+
+/// Shows the "Web tiles" dialog (opened if `open` is true).
+pub fn handle_web_tiles(
+    ui: &imgui::Ui,
+    state: &mut WebTilesState,
+    open: bool,
+    focused_view: Option<u32>,
+    program_data: &mut data::ProgramData
+) {
+    if open {
+        state.status = None;
+        ui.open_popup("Web tiles");
+    }
+    ui.popup_modal("Web tiles").always_auto_resize(true).build(ui, || {
+        ui.text("Shows a base map from an XYZ web tile source (e.g. OpenStreetMap) over the map's texture, covering");
+        ui.text("the region shown by the most recently focused view. Tiles are cached on disk for as long as the");
+        ui.text("server allows (Cache-Control, Expires; 7 days by default) and revalidated afterwards; observe the");
+        ui.text("tile source's usage policy (e.g. https://operations.osmfoundation.org/policies/tiles/).");
+        ui.text("Alternatively, tiles can be read from an MBTiles file (without network access).");
+        if ui.radio_button_bool("web", !state.use_mbtiles) {
+            state.use_mbtiles = false;
+        }
+        ui.same_line();
+        if ui.radio_button_bool("MBTiles file", state.use_mbtiles) {
+            state.use_mbtiles = true;
+        }
+        let _width = ui.push_item_width(400.0);
+        if state.use_mbtiles {
+            ui.input_text("MBTiles file", &mut state.mbtiles_path).build();
+            ui.same_line();
+            if ui.button("Browse...") {
+                if let Some(path) = rfd::FileDialog::new().add_filter("MBTiles", &["mbtiles"]).pick_file() {
+                    state.mbtiles_path = path.to_string_lossy().into_owned();
+                }
+            }
+        } else {
+            ui.input_text("tile URL", &mut program_data.tile_url).build();
+        }
+        if ui.input_int("max. zoom", &mut state.max_zoom).build() {
+            state.max_zoom = state.max_zoom.max(0).min(web_tiles::MAX_ZOOM as i32);
+        }
+
+        if let Some(fetch) = program_data.tile_fetch() {
+            let range = fetch.range;
+            if fetch.is_complete() {
+                ui.text(format!("Zoom {}: {} tiles received.", range.zoom, range.num_tiles()));
+            } else {
+                ui.text(format!(
+                    "Zoom {}: received {} of {} tiles...", range.zoom, fetch.num_received, range.num_tiles()
+                ));
+            }
+            if fetch.num_failed > 0 {
+                ui.text_wrapped(format!(
+                    "Failed to read {} tiles (last error: {}).",
+                    fetch.num_failed,
+                    fetch.last_error.as_deref().unwrap_or("")
+                ));
+            }
+        }
+        if let Some(status) = &state.status {
+            ui.text_wrapped(status);
+        }
+
+        ui.checkbox("follow the focused view", &mut state.follow_view);
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Fetch tiles again whenever the view has been panned, zoomed or resized.");
+        }
+
+        ui.separator();
+        match focused_view {
+            None => ui.text("No view to cover."),
+            Some(view_id) => if ui.button("Fetch for focused view") {
+                fetch_web_tiles_for_view(state, view_id, program_data);
+            }
+        }
+        ui.same_line();
+        if program_data.web_tiles().is_some() || program_data.tile_fetch().is_some() {
+            if ui.button("Remove") {
+                program_data.remove_web_tiles();
+                state.follow_view = false;
+                state.status = None;
+            }
+            ui.same_line();
+        }
+        if ui.button("Close") {
+            ui.close_current_popup();
+        }
+    });
+}
@@ -0,0 +1,176 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Loading of placemarks from KML files (and KMZ archives, i.e. zipped KML), shown by views as vector layers.
+//
+// Geometries of types LineString, LinearRing, Polygon (boundaries are drawn as lines), Point (drawn as a small
+// cross) and MultiGeometry are loaded from all Placemarks. Line color and width are taken from the placemark's
+// inline `Style` or the shared style referenced by its `styleUrl` (for a `StyleMap`, the "normal" style is used);
+// styles in external files are not supported.
+//
+
+use crate::data::LineStyle;
+use crate::geojson::Polyline;
+use std::collections::HashMap;
+use std::io::Read;
+
+/// Half-length of the arms of crosses drawn for points (degrees of latitude).
+const POINT_CROSS_SIZE: f64 = 0.2;
+
+/// Lines of a single placemark.
+pub struct Placemark {
+    /// `None` if the placemark does not specify line style.
+    pub style: Option<LineStyle>,
+    pub lines: Vec<Polyline>
+}
+
+/// Loads all placemarks from the KML or KMZ file at `path`.
+pub fn load(path: &str) -> Result<Vec<Placemark>, String> {
+    let contents = if path.to_lowercase().ends_with(".kmz") {
+        read_kmz(path)?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| e.to_string())?
+    };
+    let document = roxmltree::Document::parse(&contents).map_err(|e| e.to_string())?;
+
+    let styles = shared_styles(&document);
+    let style_maps = style_maps(&document);
+
+    let mut placemarks = vec![];
+    for node in document.descendants().filter(|n| n.has_tag_name("Placemark")) {
+        let mut lines = vec![];
+        for geometry in node.children().filter(|n| n.is_element()) {
+            add_geometry(geometry, &mut lines)?;
+        }
+        if lines.is_empty() { continue; }
+
+        let style = match child(node, "Style") {
+            Some(style) => line_style(style),
+            None => child_text(node, "styleUrl").and_then(|url| {
+                let id = url.trim().trim_start_matches('#');
+                let id = style_maps.get(id).map(String::as_str).unwrap_or(id);
+                styles.get(id).copied().flatten()
+            })
+        };
+
+        placemarks.push(Placemark{ style, lines });
+    }
+    if placemarks.is_empty() {
+        return Err("no placemarks found".into());
+    }
+
+    Ok(placemarks)
+}
+
+/// Returns the contents of the KML document in a KMZ archive (by convention, the first `.kml` file).
+fn read_kmz(path: &str) -> Result<String, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let name = archive.file_names()
+        .find(|name| name.to_lowercase().ends_with(".kml"))
+        .map(str::to_string)
+        .ok_or_else(|| "no KML file in the archive".to_string())?;
+
+    let mut contents = String::new();
+    archive.by_name(&name).map_err(|e| e.to_string())?.read_to_string(&mut contents).map_err(|e| e.to_string())?;
+
+    Ok(contents)
+}
+
+fn child<'a, 'input>(node: roxmltree::Node<'a, 'input>, name: &str) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name).and_then(|n| n.text())
+}
+
+/// Returns line styles (if specified) of all shared styles, by id.
+fn shared_styles(document: &roxmltree::Document) -> HashMap<String, Option<LineStyle>> {
+    document.descendants()
+        .filter(|n| n.has_tag_name("Style"))
+        .filter_map(|n| n.attribute("id").map(|id| (id.to_string(), line_style(n))))
+        .collect()
+}
+
+/// Returns ids of the "normal" styles of all style maps, by the maps' ids.
+fn style_maps(document: &roxmltree::Document) -> HashMap<String, String> {
+    document.descendants()
+        .filter(|n| n.has_tag_name("StyleMap"))
+        .filter_map(|n| {
+            let id = n.attribute("id")?;
+            let normal = n.children()
+                .filter(|pair| pair.has_tag_name("Pair"))
+                .find(|pair| child_text(*pair, "key").map(str::trim) == Some("normal"))?;
+            let url = child_text(normal, "styleUrl")?;
+
+            Some((id.to_string(), url.trim().trim_start_matches('#').to_string()))
+        })
+        .collect()
+}
+
+fn line_style(style: roxmltree::Node) -> Option<LineStyle> {
+    let line_style = child(style, "LineStyle")?;
+    let color = child_text(line_style, "color").and_then(parse_color).unwrap_or([1.0, 1.0, 1.0, 1.0]);
+    let width = child_text(line_style, "width").and_then(|w| w.trim().parse::<f32>().ok()).unwrap_or(1.0);
+
+    Some(LineStyle{ color, width })
+}
+
+/// Parses a KML color ("aabbggrr", hexadecimal).
+fn parse_color(text: &str) -> Option<[f32; 4]> {
+    let value = u32::from_str_radix(text.trim(), 16).ok()?;
+    let channel = |shift: u32| ((value >> shift) & 0xFF) as f32 / 255.0;
+
+    Some([channel(0), channel(8), channel(16), channel(24)])
+}
+
+fn add_geometry(geometry: roxmltree::Node, lines: &mut Vec<Polyline>) -> Result<(), String> {
+    match geometry.tag_name().name() {
+        "LineString" | "LinearRing" => lines.push(coordinates(geometry)?),
+
+        "Polygon" => for boundary in geometry.children()
+            .filter(|n| n.has_tag_name("outerBoundaryIs") || n.has_tag_name("innerBoundaryIs")) {
+            if let Some(ring) = child(boundary, "LinearRing") {
+                lines.push(coordinates(ring)?);
+            }
+        },
+
+        "Point" => if let Some(&[lon, lat]) = coordinates(geometry)?.first() {
+            let dlon = (POINT_CROSS_SIZE / lat.to_radians().cos().max(0.01)).min(180.0);
+            lines.push(vec![[(lon - dlon).max(-180.0), lat], [(lon + dlon).min(180.0), lat]]);
+            lines.push(vec![[lon, (lat - POINT_CROSS_SIZE).max(-90.0)], [lon, (lat + POINT_CROSS_SIZE).min(90.0)]]);
+        },
+
+        "MultiGeometry" => for child in geometry.children().filter(|n| n.is_element()) {
+            add_geometry(child, lines)?;
+        },
+
+        _ => ()
+    }
+
+    Ok(())
+}
+
+/// Parses the geometry's coordinates ("lon,lat[,alt]" tuples separated by whitespace).
+fn coordinates(geometry: roxmltree::Node) -> Result<Polyline, String> {
+    let text = child_text(geometry, "coordinates").ok_or_else(|| "missing coordinates".to_string())?;
+
+    text.split_whitespace().map(|tuple| {
+        let invalid = || format!("invalid coordinates: {}", tuple);
+        let mut values = tuple.split(',').map(|v| v.parse::<f64>());
+        let (lon, lat) = match (values.next(), values.next()) {
+            (Some(Ok(lon)), Some(Ok(lat))) => (lon, lat),
+            _ => return Err(invalid())
+        };
+        if lon.abs() > 180.0 || lat.abs() > 90.0 { return Err(invalid()); }
+
+        Ok([lon, lat])
+    }).collect()
+}
@@ -9,22 +9,256 @@
 #[macro_use]
 extern crate imgui_glium_renderer;
 
+mod batch;
+mod choropleth;
+mod config;
+mod csv;
 mod data;
+mod data_download;
 mod draw_buffer;
+mod earthquakes;
+mod export;
+mod gazetteer;
+mod geodesy;
+mod geojson;
+mod geomagnetism;
+mod georeferencing;
+mod geometry;
+mod geotiff;
 mod gui;
+mod kml;
+mod pdf;
+mod plate_boundaries;
+mod plugins;
+mod printing;
+mod proj_string;
+mod projection;
 mod runner;
+mod scalar_field;
+mod scripting;
+mod session;
+mod shape_cache;
+mod shape_filter;
+mod solar;
+mod time_zones;
+mod ui_test;
+mod vector_field;
 mod views;
+mod web_tiles;
+mod wkt;
 
-use std::{rc::Rc, io::Write};
+use std::{cell::RefCell, rc::Rc, io::Write};
+
+const USAGE: &str = "\
+Usage: projections [options]
+
+Options:
+    --texture PATH      globe texture image (overrides the configuration file)
+    --coastline PATH    coastline shapefile (*.shp; overrides the configuration file)
+    --rivers PATH       rivers shapefile (*.shp; overrides the configuration file)
+    --lakes PATH        lakes shapefile (*.shp; overrides the configuration file)
+    --places PATH       populated places shapefile (*.shp; overrides the configuration file)
+    --land PATH         land polygons shapefile filled in the vector map mode (*.shp; overrides
+                        the configuration file)
+    --view NAME         open a view at startup (may be repeated); NAME is a projection name as used
+                        in session files (e.g. mercator, orthographic) or the name of a plugin projection
+    --ui-test PATH      run the UI test script (see src/ui_test.rs)
+    --batch PATH        render the views described by the TOML job file to image files without
+                        starting the GUI (see src/batch.rs); needs a display server (X11 or Wayland),
+                        e.g. Xvfb on a headless machine
+    --help              print this message";
+
+/// Command-line arguments.
+struct Args {
+    texture: Option<String>,
+    coastline: Option<String>,
+    rivers: Option<String>,
+    lakes: Option<String>,
+    places: Option<String>,
+    land: Option<String>,
+    views: Vec<String>,
+    ui_test: Option<String>,
+    batch: Option<String>
+}
 
 fn main() {
-    let runner = runner::create_runner(18.0);
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // UI tests use the default configuration, so that positions and assertions in test files do not depend on it
+    let mut config = if args.ui_test.is_some() {
+        config::Config::default()
+    } else {
+        let config_path = config::config_path();
+        config::load(&config_path).unwrap_or_else(|e| {
+            eprintln!("Failed to load configuration from {}: {}", config_path.display(), e);
+            Default::default()
+        })
+    };
+    if let Some(texture) = args.texture { config.globe_texture = texture; }
+    if let Some(coastline) = args.coastline { config.coastline = coastline; }
+    if let Some(rivers) = args.rivers { config.rivers = rivers; }
+    if let Some(lakes) = args.lakes { config.lakes = lakes; }
+    if let Some(places) = args.places { config.places = places; }
+    if let Some(land) = args.land { config.vector_map.land = land; }
+    config.resolve_data_paths();
+
+    if let Some(path) = &args.batch {
+        std::process::exit(batch::run(path, &config));
+    }
 
-    let mut data = data::ProgramData::new(runner.display());
+    // UI tests start with the default layout, input bindings and no views (except those from "--view") or user markers
+    let restore_workspace = args.ui_test.is_none();
+    let mut runner = runner::create_runner(
+        18.0,
+        [config.window.width, config.window.height],
+        Some(session::layout_path()).filter(|_| restore_workspace)
+    );
 
-    let mut gui_state = gui::GuiState::new(runner.platform().hidpi_factor());
+    let ui_test = match &args.ui_test {
+        None => None,
+        Some(path) => match ui_test::UiTest::load(path) {
+            Ok(ui_test) => Some(Rc::new(RefCell::new(ui_test))),
+            Err(e) => {
+                eprintln!("Failed to load UI test {}: {}", path, e);
+                std::process::exit(2);
+            }
+        }
+    };
+    if let Some(ui_test) = &ui_test {
+        let ui_test = Rc::clone(ui_test);
+        runner.set_input_source(Box::new(move |io| ui_test.borrow_mut().apply_input(io)));
+    }
 
-    runner.main_loop(move |_, ui, display, renderer| {
-        gui::handle_gui(ui, &mut gui_state, &mut data, renderer, display);
+    // on the first run, missing data files can be downloaded before the program starts (not during UI tests)
+    let mut data_download = Some(data_download::missing_files(&config))
+        .filter(|files| !files.is_empty() && ui_test.is_none())
+        .map(data_download::DataDownload::new);
+
+    let hidpi_factor = runner.platform().hidpi_factor();
+    let views = args.views;
+    let program: Rc<RefCell<Option<(data::ProgramData, gui::GuiState)>>> = Rc::new(RefCell::new(None));
+
+    if restore_workspace {
+        let program = Rc::clone(&program);
+        runner.set_exit_handler(Box::new(move || {
+            if let Some((data, gui_state)) = program.borrow_mut().as_mut() {
+                let path = session::workspace_path();
+                if let Err(e) = session::save_workspace(&path, data, gui_state.view_window_sizes()) {
+                    eprintln!("Failed to save workspace to {}: {}", path.display(), e);
+                }
+            }
+        }));
+    }
+
+    runner.main_loop(move |run, ui, display, renderer| {
+        if let Some(download) = &mut data_download {
+            if !gui::handle_data_download(ui, download, run) { return true; }
+            data_download = None;
+            // downloaded files are in the user's data directory
+            config.resolve_data_paths();
+        }
+
+        let mut program = program.borrow_mut();
+        let (data, gui_state) = program.get_or_insert_with(
+            || start(&config, &views, restore_workspace, hidpi_factor, display, renderer)
+        );
+        let active = gui::handle_gui(ui, gui_state, data, renderer, display);
+        if let Some(ui_test) = &ui_test {
+            let mut ui_test = ui_test.borrow_mut();
+            if !ui_test.after_frame(data) {
+                ui_test.finish();
+            }
+        }
+        active
     });
 }
+
+/// Creates the program's data (with views of projections named in `views`; if there are none, with the views
+/// of the saved workspace if `restore_workspace` is set) and GUI state. User markers are loaded only
+/// if `restore_workspace` is set.
+fn start(
+    config: &config::Config,
+    views: &[String],
+    restore_workspace: bool,
+    hidpi_factor: f64,
+    display: &glium::Display,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>
+) -> (data::ProgramData, gui::GuiState) {
+    let mut data = data::ProgramData::new(config, display);
+
+    if restore_workspace {
+        let user_markers_path = session::user_markers_path();
+        match session::load_user_markers(&user_markers_path) {
+            Ok(user_markers) => data.set_user_markers(user_markers),
+            Err(e) => eprintln!("Failed to load markers from {}: {}", user_markers_path.display(), e)
+        }
+    }
+
+    for name in views {
+        if let Err(e) = session::create_default_view(name, &mut data, renderer, display) {
+            eprintln!("Failed to open view: {}", e);
+        }
+    }
+
+    let mut window_sizes = Default::default();
+    if views.is_empty() && restore_workspace {
+        let workspace_path = session::workspace_path();
+        match session::load_workspace(&workspace_path, &mut data, renderer, display) {
+            Ok(sizes) => window_sizes = sizes,
+            Err(e) => eprintln!("Failed to restore workspace from {}: {}", workspace_path.display(), e)
+        }
+    }
+
+    let mut gui_state = gui::GuiState::new(
+        hidpi_factor,
+        &config.globe_texture,
+        &config.plate_boundaries,
+        &config.time_zones,
+        data.base_map_names()
+    );
+    gui_state.set_view_window_sizes(window_sizes);
+
+    (data, gui_state)
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut result = Args{
+        texture: None,
+        coastline: None,
+        rivers: None,
+        lakes: None,
+        places: None,
+        land: None,
+        views: vec![],
+        ui_test: None,
+        batch: None
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value of {}", arg));
+        match arg.as_str() {
+            "--texture" => result.texture = Some(value()?),
+            "--coastline" => result.coastline = Some(value()?),
+            "--rivers" => result.rivers = Some(value()?),
+            "--lakes" => result.lakes = Some(value()?),
+            "--places" => result.places = Some(value()?),
+            "--land" => result.land = Some(value()?),
+            "--view" => result.views.push(value()?),
+            "--ui-test" => result.ui_test = Some(value()?),
+            "--batch" => result.batch = Some(value()?),
+            "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            },
+            _ => return Err(format!("unknown argument: {}", arg))
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,175 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Minimal writer of single-page PDF documents containing stroked paths and a background image.
+//
+
+use std::fmt::Write;
+
+pub const POINTS_PER_INCH: f64 = 72.0;
+
+const JPEG_QUALITY: u8 = 90;
+
+struct Image {
+    width: u32,
+    height: u32,
+    /// JPEG-encoded RGB data.
+    data: Vec<u8>
+}
+
+pub struct Page {
+    /// Width in points.
+    width: f64,
+    /// Height in points.
+    height: f64,
+    /// Rectangle (left, bottom, width, height in points) covered by the background image, to which paths
+    /// are clipped.
+    content_rect: [f64; 4],
+    /// Content stream (drawing operators).
+    content: String,
+    background: Option<Image>,
+    /// End of the current path (if any), used to join adjacent segments.
+    last_point: Option<[f64; 2]>
+}
+
+impl Page {
+    /// Creates an empty page; dimensions are in points; the origin is the bottom-left corner.
+    pub fn new(width: f64, height: f64) -> Page {
+        Page{
+            width,
+            height,
+            content_rect: [0.0, 0.0, width, height],
+            content: String::new(),
+            background: None,
+            last_point: None
+        }
+    }
+
+    pub fn width(&self) -> f64 { self.width }
+
+    pub fn height(&self) -> f64 { self.height }
+
+    /// Restricts the background image and paths to a rectangle (left, bottom, width, height in points;
+    /// by default, the whole page).
+    pub fn set_content_rect(&mut self, rect: [f64; 4]) {
+        self.content_rect = rect;
+    }
+
+    /// Stretches `image` over the content rectangle, below everything drawn so far.
+    pub fn set_background(&mut self, image: &image::RgbImage) -> Result<(), image::ImageError> {
+        let mut data = vec![];
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY).encode(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            image::ColorType::Rgb8
+        )?;
+
+        self.background = Some(Image{ width: image.width(), height: image.height(), data });
+
+        Ok(())
+    }
+
+    /// Starts a new path whose segments will be stroked with the specified color and line width (in points).
+    pub fn begin_path(&mut self, color: [f32; 4], line_width: f64) {
+        writeln!(
+            self.content,
+            "{:.3} {:.3} {:.3} RG {:.2} w 1 J 1 j",
+            color[0], color[1], color[2], line_width
+        ).unwrap();
+        self.last_point = None;
+    }
+
+    /// Adds a line segment to the current path; consecutive segments sharing an end are joined.
+    pub fn add_segment(&mut self, start: [f64; 2], end: [f64; 2]) {
+        if self.last_point != Some(start) {
+            writeln!(self.content, "{:.2} {:.2} m", start[0], start[1]).unwrap();
+        }
+        writeln!(self.content, "{:.2} {:.2} l", end[0], end[1]).unwrap();
+        self.last_point = Some(end);
+    }
+
+    /// Strokes the current path.
+    pub fn end_path(&mut self) {
+        writeln!(self.content, "S").unwrap();
+        self.last_point = None;
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), std::io::Error> {
+        const CONTENT_OBJ: usize = 4;
+        const IMAGE_OBJ: usize = 5;
+
+        // page contents are clipped to the content rectangle; the background image (if any) is drawn first
+        let [left, bottom, width, height] = self.content_rect;
+        let mut content = format!("{:.2} {:.2} {:.2} {:.2} re W n\n", left, bottom, width, height);
+        if self.background.is_some() {
+            writeln!(content, "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im0 Do Q", width, height, left, bottom).unwrap();
+        }
+        content += &self.content;
+
+        let resources = if self.background.is_some() {
+            format!("<< /XObject << /Im0 {} 0 R >> >>", IMAGE_OBJ)
+        } else {
+            "<< >>".to_string()
+        };
+
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources {} /Contents {} 0 R >>",
+                self.width, self.height, resources, CONTENT_OBJ
+            ).into_bytes(),
+            stream_object("", content.as_bytes())
+        ];
+        if let Some(image) = &self.background {
+            objects.push(stream_object(
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+                     /BitsPerComponent 8 /Filter /DCTDecode",
+                    image.width, image.height
+                ),
+                &image.data
+            ));
+        }
+
+        // the second line contains binary characters, as recommended for files with binary content
+        let mut output = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+
+        let mut offsets = vec![];
+        for (idx, object) in objects.iter().enumerate() {
+            offsets.push(output.len());
+            output.extend_from_slice(format!("{} 0 obj\n", idx + 1).as_bytes());
+            output.extend_from_slice(object);
+            output.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref_offset = output.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in &offsets {
+            writeln!(xref, "{:010} 00000 n ", offset).unwrap();
+        }
+        write!(
+            xref,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1, xref_offset
+        ).unwrap();
+        output.extend_from_slice(xref.as_bytes());
+
+        std::fs::write(path, output)
+    }
+}
+
+fn stream_object(dictionary_entries: &str, data: &[u8]) -> Vec<u8> {
+    let mut object = format!("<< {} /Length {} >>\nstream\n", dictionary_entries, data.len()).into_bytes();
+    object.extend_from_slice(data);
+    object.extend_from_slice(b"\nendstream");
+
+    object
+}
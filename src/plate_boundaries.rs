@@ -0,0 +1,166 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Tectonic plate boundaries loaded from a GeoJSON file and drawn with a line style per type of boundary.
+//
+// The type of a feature's boundary is taken from its property "STEPCLASS", "Type", "type", "boundary_type"
+// or "class": either a class code of the PB2002 model (Bird, 2003) or a description. PB2002 classes:
+//
+//     OSR (oceanic spreading ridge), CRB (continental rift boundary)           -> ridge
+//     SUB (subduction zone), OCB, CCB (oceanic/continental convergent boundary) -> trench
+//     OTF, CTF (oceanic/continental transform fault)                          -> transform
+//
+// Descriptions containing "ridge", "rift", "spreading" or "divergent" denote ridges; "subduction", "trench"
+// or "convergent" trenches; "transform" transform faults. Other boundaries are drawn with a neutral style.
+//
+
+use crate::data::LineStyle;
+use crate::geojson::{self, Polyline};
+use serde_json::Value;
+
+/// Properties of a feature which may specify the type of its boundary.
+const TYPE_PROPERTIES: [&str; 5] = ["STEPCLASS", "Type", "type", "boundary_type", "class"];
+
+/// Length and spacing (degrees) of dashes of transform faults.
+const TRANSFORM_DASH: [f64; 2] = [1.0, 0.6];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BoundaryType {
+    /// Divergent boundary (spreading ridge, rift).
+    Ridge,
+    /// Convergent boundary (subduction zone, collision zone).
+    Trench,
+    Transform,
+    Other
+}
+
+impl BoundaryType {
+    pub const ALL: [BoundaryType; 4] = [
+        BoundaryType::Ridge,
+        BoundaryType::Trench,
+        BoundaryType::Transform,
+        BoundaryType::Other
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoundaryType::Ridge => "ridge",
+            BoundaryType::Trench => "trench",
+            BoundaryType::Transform => "transform",
+            BoundaryType::Other => "other"
+        }
+    }
+
+    pub fn style(&self) -> LineStyle {
+        match self {
+            BoundaryType::Ridge => LineStyle{ color: [0.85, 0.15, 0.1, 1.0], width: 2.0 },
+            BoundaryType::Trench => LineStyle{ color: [0.15, 0.2, 0.75, 1.0], width: 3.0 },
+            BoundaryType::Transform => LineStyle{ color: [0.1, 0.6, 0.2, 1.0], width: 1.5 },
+            BoundaryType::Other => LineStyle{ color: [0.5, 0.5, 0.5, 1.0], width: 1.0 }
+        }
+    }
+
+    fn classify(value: &str) -> BoundaryType {
+        match value.trim().to_uppercase().as_str() {
+            "OSR" | "CRB" => return BoundaryType::Ridge,
+            "SUB" | "OCB" | "CCB" => return BoundaryType::Trench,
+            "OTF" | "CTF" => return BoundaryType::Transform,
+            _ => ()
+        }
+        let value = value.to_lowercase();
+        let contains_any = |words: &[&str]| words.iter().any(|word| value.contains(word));
+        if contains_any(&["ridge", "rift", "spreading", "divergent"]) {
+            BoundaryType::Ridge
+        } else if contains_any(&["subduction", "trench", "convergent"]) {
+            BoundaryType::Trench
+        } else if contains_any(&["transform"]) {
+            BoundaryType::Transform
+        } else {
+            BoundaryType::Other
+        }
+    }
+}
+
+/// Loads plate boundaries from the GeoJSON file at `path`; returns lines of each type (transform faults dashed).
+pub fn load(path: &str) -> Result<Vec<(BoundaryType, Vec<Polyline>)>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: Value = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+    let features = match value.get("features").and_then(Value::as_array) {
+        Some(features) => features.as_slice(),
+        None => std::slice::from_ref(&value)
+    };
+
+    let mut lines: Vec<(BoundaryType, Vec<Polyline>)> = BoundaryType::ALL.iter().map(|t| (*t, vec![])).collect();
+    for feature in features {
+        let boundary_type = feature.get("properties")
+            .and_then(|properties| TYPE_PROPERTIES.iter().find_map(|name| properties.get(*name)?.as_str()))
+            .map_or(BoundaryType::Other, BoundaryType::classify);
+        let feature_lines = geojson::object_lines(feature)?;
+        let type_lines = &mut lines.iter_mut().find(|(t, _)| *t == boundary_type).unwrap().1;
+        if boundary_type == BoundaryType::Transform {
+            let [dash, gap] = TRANSFORM_DASH;
+            type_lines.extend(feature_lines.iter().flat_map(|line| dashed(line, dash, gap)));
+        } else {
+            type_lines.extend(feature_lines);
+        }
+    }
+    lines.retain(|(_, lines)| !lines.is_empty());
+    if lines.is_empty() {
+        return Err("no lines found".into());
+    }
+
+    Ok(lines)
+}
+
+/// Splits `line` into dashes of length `dash` separated by gaps of length `gap` (degrees of longitude and latitude).
+/// A segment crossing the antimeridian is skipped.
+fn dashed(line: &Polyline, dash: f64, gap: f64) -> Vec<Polyline> {
+    let mut dashes = vec![];
+    let mut current: Polyline = line.first().into_iter().copied().collect();
+    // distance along the line from the start of the current dash
+    let mut phase = 0.0;
+
+    for segment in line.windows(2) {
+        let (p0, p1) = (segment[0], segment[1]);
+        if (p1[0] - p0[0]).abs() > 180.0 {
+            if current.len() > 1 { dashes.push(std::mem::take(&mut current)); }
+            current.clear();
+            if phase < dash { current.push(p1); }
+            continue;
+        }
+
+        let length = ((p1[0] - p0[0]).powi(2) + (p1[1] - p0[1]).powi(2)).sqrt();
+        let point_at = |dist: f64| {
+            let t = dist / length;
+            [p0[0] + t * (p1[0] - p0[0]), p0[1] + t * (p1[1] - p0[1])]
+        };
+        let mut dist = 0.0;
+        loop {
+            let in_dash = phase < dash;
+            let step = if in_dash { dash - phase } else { dash + gap - phase };
+            if dist + step > length {
+                phase += length - dist;
+                break;
+            }
+            dist += step;
+            current.push(point_at(dist));
+            if in_dash {
+                if current.len() > 1 { dashes.push(std::mem::take(&mut current)); }
+                current.clear();
+                phase = dash;
+            } else {
+                phase = 0.0;
+            }
+        }
+        if phase < dash { current.push(p1); }
+    }
+    if current.len() > 1 { dashes.push(current); }
+
+    dashes
+}
@@ -0,0 +1,199 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Loading of user projections from dynamic libraries.
+//
+// A plugin is a dynamic library exporting the C function
+//
+//     const ProjectionPluginInfo* projections_plugin_info(void);
+//
+// which returns a pointer to a `ProjectionPluginInfo` structure (see below) with static lifetime.
+//
+// Alternatively, a plugin can be a GLSL file (with extension `vert`) defining the function
+//
+//     vec2 project(vec2 lonlat);
+//
+// which receives (longitude, latitude) in radians of a point of the rotated globe (the view's center is at (0, 0))
+// and returns its map coordinates (for globe radius 1), or the predefined constant `HIDDEN` if the point is not shown.
+// The plugin is named after its file (without extension). As it has no CPU-side projection, it is available only
+// for display (see `Projection::has_cpu_projection`).
+//
+
+use cgmath::Point2;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+
+pub const PLUGINS_DIR: &str = "plugins";
+
+/// Version of the plugin interface; bumped on every incompatible change of `ProjectionPluginInfo`.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+const ENTRY_POINT: &[u8] = b"projections_plugin_info\0";
+
+const SHADER_PLUGIN_EXTENSION: &str = "vert";
+
+/// Line of the GLSL plugin wrapper replaced with the plugin's source.
+const SHADER_PLUGIN_SOURCE_PLACEHOLDER: &str = "PLUGIN_SOURCE";
+
+/// Converts (longitude, latitude) to (x, y) or vice versa; angles are in radians.
+///
+/// Returns non-zero on success, or 0 if the input has no counterpart in the projection
+/// (e.g., the point lies on the hidden hemisphere).
+///
+pub type ConversionFn = extern "C" fn(f64, f64, *mut f64, *mut f64) -> c_int;
+
+/// Plugin description as laid out in memory by the plugin.
+#[repr(C)]
+pub struct ProjectionPluginInfo {
+    /// Must equal `PLUGIN_ABI_VERSION`.
+    pub abi_version: u32,
+    /// Null-terminated UTF-8 name shown in the GUI.
+    pub name: *const c_char,
+    /// Forward projection (longitude, latitude) -> (x, y) of the normal aspect, with globe radius 1; must not be
+    /// null.
+    pub forward: Option<ConversionFn>,
+    /// Inverse projection (x, y) -> (longitude, latitude); may be null.
+    pub inverse: Option<ConversionFn>,
+    /// Null-terminated GLSL source of a vertex shader (may be null). It must follow the conventions of the built-in
+    /// vertex shaders (same uniforms, input and output); without it the projection is available only to CPU-side
+    /// tools (e.g. distortion metrics export).
+    pub vertex_shader: *const c_char
+}
+
+/// A loaded plugin. The underlying library stays loaded for the lifetime of the program.
+#[derive(Debug)]
+pub struct Plugin {
+    pub name: String,
+    pub vertex_shader: Option<String>,
+    /// Absent for GLSL plugins.
+    forward: Option<ConversionFn>,
+    inverse: Option<ConversionFn>
+}
+
+impl PartialEq for Plugin {
+    fn eq(&self, other: &Plugin) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Plugin {
+    /// Returns true if the plugin provides a CPU-side projection (i.e., it is not a GLSL plugin).
+    pub fn has_cpu_projection(&self) -> bool { self.forward.is_some() }
+
+    pub fn has_cpu_inverse(&self) -> bool { self.inverse.is_some() }
+
+    pub fn forward(&self, longitude: cgmath::Rad<f64>, latitude: cgmath::Rad<f64>) -> Option<Point2<f64>> {
+        let forward = self.forward?;
+        let (mut x, mut y) = (0.0, 0.0);
+        if forward(longitude.0, latitude.0, &mut x, &mut y) != 0 {
+            Some(Point2{ x, y })
+        } else {
+            None
+        }
+    }
+
+    /// Returns (longitude, latitude).
+    pub fn inverse(&self, point: Point2<f64>) -> Option<(cgmath::Rad<f64>, cgmath::Rad<f64>)> {
+        let inverse = self.inverse?;
+        let (mut longitude, mut latitude) = (0.0, 0.0);
+        if inverse(point.x, point.y, &mut longitude, &mut latitude) != 0 {
+            Some((cgmath::Rad(longitude), cgmath::Rad(latitude)))
+        } else {
+            None
+        }
+    }
+}
+
+/// Loads all plugins from `PLUGINS_DIR`; failures are reported and skipped.
+pub fn load_plugins() -> Vec<&'static Plugin> {
+    let mut plugins = vec![];
+
+    let entries = match std::fs::read_dir(PLUGINS_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return plugins
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        let result = if extension == Some(std::env::consts::DLL_EXTENSION) {
+            load_plugin(&path)
+        } else if extension == Some(SHADER_PLUGIN_EXTENSION) {
+            load_shader_plugin(&path)
+        } else {
+            continue;
+        };
+
+        match result {
+            Ok(plugin) => plugins.push(plugin),
+            Err(e) => eprintln!("Failed to load plugin {}: {}", path.display(), e)
+        }
+    }
+
+    plugins
+}
+
+fn load_plugin(path: &std::path::Path) -> Result<&'static Plugin, String> {
+    let library = unsafe { libloading::Library::new(path) }.map_err(|e| e.to_string())?;
+
+    let plugin = unsafe {
+        let entry_point = library
+            .get::<unsafe extern "C" fn() -> *const ProjectionPluginInfo>(ENTRY_POINT)
+            .map_err(|e| e.to_string())?;
+
+        let info = entry_point();
+        if info.is_null() {
+            return Err("plugin info is null".to_string());
+        }
+        let info = &*info;
+
+        if info.abi_version != PLUGIN_ABI_VERSION {
+            return Err(format!(
+                "unsupported plugin interface version {} (expected {})", info.abi_version, PLUGIN_ABI_VERSION
+            ));
+        }
+        if info.name.is_null() {
+            return Err("plugin name is null".to_string());
+        }
+        let forward = info.forward.ok_or_else(|| "forward projection function is null".to_string())?;
+
+        Plugin{
+            name: CStr::from_ptr(info.name).to_string_lossy().into_owned(),
+            vertex_shader: if info.vertex_shader.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(info.vertex_shader).to_string_lossy().into_owned())
+            },
+            forward: Some(forward),
+            inverse: info.inverse
+        }
+    };
+
+    // function pointers of `plugin` refer to the library's code, so it must never be unloaded
+    std::mem::forget(library);
+
+    Ok(Box::leak(Box::new(plugin)))
+}
+
+fn load_shader_plugin(path: &std::path::Path) -> Result<&'static Plugin, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let vertex_shader: String = include_str!("resources/shaders/plugin.vert").lines().enumerate()
+        .map(|(idx, line)| if line.trim() == SHADER_PLUGIN_SOURCE_PLACEHOLDER {
+            // make compiler messages refer to lines of the plugin's file (as source string 1), then again to lines
+            // of the wrapper (source string 0) after it; `#line` sets the number of the following line
+            format!("#line 1 1\n{}\n#line {} 0\n", source, idx + 2)
+        } else {
+            format!("{}\n", line)
+        })
+        .collect();
+
+    Ok(Box::leak(Box::new(Plugin{ name, vertex_shader: Some(vertex_shader), forward: None, inverse: None })))
+}
@@ -0,0 +1,203 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Printing of views via the system's print facility: on Windows, the standard print dialog (where the printer and
+// the paper are chosen) is shown; elsewhere, the page is sent with `lp` to the printer and on the paper chosen in
+// the program (the printers are listed with `lpstat`).
+//
+
+use crate::views::{Layers, ViewBase};
+use std::sync::mpsc;
+
+pub const MM_PER_INCH: f64 = 25.4;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Paper { A3, A4, A5, Letter, Legal }
+
+impl Paper {
+    pub const ALL: &'static [Paper] = &[Paper::A3, Paper::A4, Paper::A5, Paper::Letter, Paper::Legal];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Paper::A3 => "A3",
+            Paper::A4 => "A4",
+            Paper::A5 => "A5",
+            Paper::Letter => "Letter",
+            Paper::Legal => "Legal"
+        }
+    }
+
+    /// Returns (width, height) in millimeters in portrait orientation.
+    fn size_mm(&self) -> (f64, f64) {
+        match self {
+            Paper::A3 => (297.0, 420.0),
+            Paper::A4 => (210.0, 297.0),
+            Paper::A5 => (148.0, 210.0),
+            Paper::Letter => (215.9, 279.4),
+            Paper::Legal => (215.9, 355.6)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PrintSettings {
+    pub paper: Paper,
+    pub landscape: bool,
+    pub dpi: u32,
+    /// Blank margin on each side of the page (millimeters).
+    pub margin_mm: f64
+}
+
+impl PrintSettings {
+    /// Returns page size (width, height) in millimeters.
+    pub fn page_size_mm(&self) -> (f64, f64) {
+        let (w_mm, h_mm) = self.paper.size_mm();
+        if self.landscape { (h_mm, w_mm) } else { (w_mm, h_mm) }
+    }
+
+    /// Returns page size in pixels at the configured resolution.
+    pub fn page_size(&self) -> (u32, u32) {
+        let (w_mm, h_mm) = self.page_size_mm();
+        let to_pixels = |mm: f64| (mm / MM_PER_INCH * self.dpi as f64).round() as u32;
+
+        (to_pixels(w_mm), to_pixels(h_mm))
+    }
+
+    /// Returns size (width, height) in millimeters of the map, which fills the page within the margins.
+    pub fn map_size_mm(&self) -> (f64, f64) {
+        let (w_mm, h_mm) = self.page_size_mm();
+
+        (w_mm - 2.0 * self.margin_mm, h_mm - 2.0 * self.margin_mm)
+    }
+
+    /// Returns size of the map in pixels at the configured resolution.
+    pub fn map_size(&self) -> (u32, u32) {
+        let (w_mm, h_mm) = self.map_size_mm();
+        let to_pixels = |mm: f64| (mm / MM_PER_INCH * self.dpi as f64).round() as u32;
+
+        (to_pixels(w_mm), to_pixels(h_mm))
+    }
+
+    /// Returns an error if the settings do not allow rendering a map.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.dpi == 0 {
+            return Err("resolution must be positive".to_string());
+        }
+        let (w_mm, h_mm) = self.map_size_mm();
+        if !(self.margin_mm >= 0.0 && w_mm > 0.0 && h_mm > 0.0) {
+            return Err("margins must be non-negative and leave room for the map".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns names of the available printers, or an empty list if the printer is chosen in the system's print dialog.
+#[cfg(target_os = "windows")]
+pub fn printers() -> Result<Vec<String>, String> {
+    Ok(vec![])
+}
+
+/// Returns names of the available printers, or an empty list if the printer is chosen in the system's print dialog.
+#[cfg(not(target_os = "windows"))]
+pub fn printers() -> Result<Vec<String>, String> {
+    let output = std::process::Command::new("lpstat").arg("-e").output()
+        .map_err(|e| format!("cannot run lpstat: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("lpstat failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+/// Printing of a view; the page is sent to the system's print facility in a background thread.
+pub struct PrintJob {
+    receiver: mpsc::Receiver<Result<(), String>>
+}
+
+impl PrintJob {
+    /// Renders `view` to fill a page (within the margins) and starts sending it to the system's print facility;
+    /// `printer` (one of `printers`) is the default printer if not specified.
+    pub fn start(view: &ViewBase, settings: &PrintSettings, printer: Option<String>) -> Result<PrintJob, String> {
+        settings.validate()?;
+
+        let (map_width, map_height) = settings.map_size();
+        let map = view.render_offscreen_tiled(map_width, map_height, Layers::All)?;
+        let path = std::env::temp_dir().join(format!("projections_print_{}.png", view.unique_id()));
+        let settings = *settings;
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            // fails if the job has been dropped
+            let _ = sender.send(print_page(&map, &path, &settings, printer.as_deref()));
+        });
+
+        Ok(PrintJob{ receiver })
+    }
+
+    /// Returns the result of printing once the page has been sent.
+    pub fn poll(&self) -> Option<Result<(), String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err("printing stopped unexpectedly".into()))
+        }
+    }
+}
+
+/// Places `map` in the middle of a page, saves it as `path` and sends it to the printer.
+fn print_page(
+    map: &image::RgbaImage,
+    path: &std::path::Path,
+    settings: &PrintSettings,
+    printer: Option<&str>
+) -> Result<(), String> {
+    let (width, height) = settings.page_size();
+    let mut image = image::RgbaImage::from_pixel(width, height, image::Rgba([255, 255, 255, 255]));
+    image::imageops::replace(&mut image, map, (width - map.width()) / 2, (height - map.height()) / 2);
+    image.save(path).map_err(|e| e.to_string())?;
+
+    // the print command returns once the page has been spooled (or printed), so the file is no longer needed
+    let result = send_to_printer(path, settings.paper, printer);
+    let _ = std::fs::remove_file(path);
+
+    result
+}
+
+#[cfg(target_os = "windows")]
+fn send_to_printer(path: &std::path::Path, _paper: Paper, _printer: Option<&str>) -> Result<(), String> {
+    // shows the standard print dialog
+    run(std::process::Command::new("mspaint").arg("/p").arg(path))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn send_to_printer(path: &std::path::Path, paper: Paper, printer: Option<&str>) -> Result<(), String> {
+    let mut command = std::process::Command::new("lp");
+    if let Some(printer) = printer {
+        command.arg("-d").arg(printer);
+    }
+    // CUPS rotates the image to the page's orientation
+    command.arg("-o").arg(format!("media={}", paper.name())).arg("-o").arg("fit-to-page").arg(path);
+
+    run(&mut command)
+}
+
+/// Runs the print command and waits for it to finish; returns an error (with the command's error output)
+/// if it fails, e.g. because of an unknown printer or paper size.
+fn run(command: &mut std::process::Command) -> Result<(), String> {
+    let output = command.output().map_err(|e| format!("cannot run the print command: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("print command failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(())
+}
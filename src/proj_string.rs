@@ -0,0 +1,405 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Creation of views from PROJ-style definition strings, e.g.
+//
+//     +proj=lcc +lat_1=33 +lat_2=45 +lon_0=-96
+//
+// Supported values of `proj`: aea, adams_hemi, cea, eck4, eck6, gall, gnom, guyou, hammer, laea, lcc, loxim, merc,
+// mill, moll, nicol, nsper, ortho, peirce_q, qsc, stere, tmerc, utm. Parameters: lon_0, lat_0 (the center
+// of azimuthal projections, the grid's origin of lcc and tmerc), lat_1, lat_2, lat_ts (cea), h (nsper), zone and south
+// (utm), k_0/k, x_0, y_0 (scale factor and false easting/northing in meters; lcc, stere, tmerc, utm), ellps/datum
+// (WGS84 or GRS80; used by aea, lcc, merc, tmerc, utm), R.
+// Other parameters which do not affect the shape of the map (units, lat_ts of merc and stere etc.) are ignored. Angles
+// are in decimal degrees.
+//
+
+use crate::data;
+use crate::views;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Parameters which do not affect the shape of the map.
+const IGNORED_PARAMS: &[&str] = &[
+    "x_0", "y_0", "k", "k_0", "units", "to_meter", "no_defs", "type", "wktext", "towgs84", "south", "over", "axis"
+];
+
+/// Ellipsoids treated as WGS84 (they differ by less than 0.1 mm in the semi-minor axis).
+const WGS84_ELLIPSOIDS: &[&str] = &["WGS84", "GRS80", "NAD83"];
+
+/// Max. absolute value of a standard parallel.
+const MAX_STANDARD_PARALLEL: f64 = 89.0;
+
+/// Projection (with its shape-affecting parameters) specified by a PROJ string.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ProjKind {
+    AdamsHemisphere,
+    AlbersEqualArea{ standard_parallels: [cgmath::Deg<f64>; 2] },
+    CylindricalEqualArea{ standard_parallel: cgmath::Deg<f64> },
+    EckertIV,
+    EckertVI,
+    GallStereographic,
+    /// `altitude`: observer's altitude above the surface (km).
+    GeneralPerspective{ altitude: f64 },
+    Gnomonic,
+    Guyou,
+    Hammer,
+    LambertAzimuthal,
+    LambertConformalConic{ standard_parallels: [cgmath::Deg<f64>; 2] },
+    Loximuthal{ central_latitude: cgmath::Deg<f64> },
+    Mercator,
+    Miller,
+    Mollweide,
+    Nicolosi,
+    Orthographic,
+    PeirceQuincuncial,
+    Qsc,
+    Stereographic,
+    TransverseMercator
+}
+
+/// View definition parsed from a PROJ string.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ProjDefinition {
+    pub kind: ProjKind,
+    /// Point shown in the center of the view.
+    pub center_lon: cgmath::Deg<f64>,
+    pub center_lat: cgmath::Deg<f64>,
+    /// True if the WGS84 ellipsoid was requested; ignored by projections supporting only a sphere.
+    pub ellipsoidal: bool,
+    /// Ignored by projections without grid parameters.
+    pub grid: views::Grid
+}
+
+pub fn parse(definition: &str) -> Result<ProjDefinition, String> {
+    let mut params: Vec<(&str, Option<&str>)> = vec![];
+    for token in definition.split_whitespace() {
+        let token = token.strip_prefix('+').unwrap_or(token);
+        let (key, value) = match token.split_once('=') {
+            Some((key, value)) => (key, Some(value)),
+            None => (token, None)
+        };
+        if params.iter().any(|(k, _)| *k == key) {
+            return Err(format!("parameter \"{}\" specified more than once", key));
+        }
+        params.push((key, value));
+    }
+
+    let get = |key: &str| params.iter().find(|(k, _)| *k == key).map(|(_, value)| *value);
+
+    let get_number = |key: &str| -> Result<Option<f64>, String> {
+        match get(key) {
+            None => Ok(None),
+            Some(None) => Err(format!("parameter \"{}\" requires a value", key)),
+            Some(Some(value)) => match value.parse::<f64>() {
+                Ok(number) if number.is_finite() => Ok(Some(number)),
+                _ => Err(format!("invalid value of \"{}\": {}", key, value))
+            }
+        }
+    };
+
+    let get_angle = |key: &str, max_abs: f64| -> Result<Option<cgmath::Deg<f64>>, String> {
+        match get_number(key)? {
+            Some(value) if value.abs() > max_abs => Err(format!("value of \"{}\" out of range: {}", key, value)),
+            value => Ok(value.map(cgmath::Deg))
+        }
+    };
+
+    let get_standard_parallels = || -> Result<[cgmath::Deg<f64>; 2], String> {
+        let lat_1 = get_angle("lat_1", MAX_STANDARD_PARALLEL)?
+            .ok_or_else(|| "missing standard parallel (\"lat_1\")".to_string())?;
+        let lat_2 = get_angle("lat_2", MAX_STANDARD_PARALLEL)?.unwrap_or(lat_1);
+        Ok([lat_1, lat_2])
+    };
+
+    let proj = match get("proj") {
+        Some(Some(proj)) => proj,
+        Some(None) => return Err("parameter \"proj\" requires a value".to_string()),
+        None => return Err("missing parameter \"proj\"".to_string())
+    };
+
+    let mut used_params = vec!["proj", "lon_0", "ellps", "datum", "R"];
+    let kind = match proj {
+        "adams_hemi" => ProjKind::AdamsHemisphere,
+
+        "aea" => {
+            used_params.extend(&["lat_1", "lat_2"]);
+            ProjKind::AlbersEqualArea{ standard_parallels: get_standard_parallels()? }
+        },
+
+        "cea" => {
+            used_params.push("lat_ts");
+            ProjKind::CylindricalEqualArea{
+                standard_parallel: get_angle("lat_ts", MAX_STANDARD_PARALLEL)?.unwrap_or(cgmath::Deg(0.0))
+            }
+        },
+
+        "eck4" => ProjKind::EckertIV,
+
+        "eck6" => ProjKind::EckertVI,
+
+        "gall" => ProjKind::GallStereographic,
+
+        "gnom" => { used_params.push("lat_0"); ProjKind::Gnomonic },
+
+        "guyou" => ProjKind::Guyou,
+
+        "hammer" => ProjKind::Hammer,
+
+        "laea" => { used_params.push("lat_0"); ProjKind::LambertAzimuthal },
+
+        "lcc" => {
+            used_params.extend(&["lat_1", "lat_2", "lat_0"]);
+            ProjKind::LambertConformalConic{ standard_parallels: get_standard_parallels()? }
+        },
+
+        "loxim" => {
+            used_params.push("lat_1");
+            ProjKind::Loximuthal{
+                central_latitude: get_angle("lat_1", MAX_STANDARD_PARALLEL)?.unwrap_or(cgmath::Deg(45.0))
+            }
+        },
+
+        "merc" => { used_params.push("lat_ts"); ProjKind::Mercator },
+
+        "mill" => ProjKind::Miller,
+
+        "moll" => ProjKind::Mollweide,
+
+        "nicol" => ProjKind::Nicolosi,
+
+        "nsper" => {
+            used_params.extend(&["lat_0", "h"]);
+            let height = get_number("h")?.ok_or_else(|| "missing observer's height (\"h\")".to_string())?;
+            if height <= 0.0 {
+                return Err(format!("observer's height (\"h\") must be positive: {}", height));
+            }
+            ProjKind::GeneralPerspective{ altitude: height / 1000.0 }
+        },
+
+        "ortho" => { used_params.push("lat_0"); ProjKind::Orthographic },
+
+        "peirce_q" => ProjKind::PeirceQuincuncial,
+
+        "qsc" => { used_params.push("lat_0"); ProjKind::Qsc },
+
+        "stere" => { used_params.extend(&["lat_0", "lat_ts"]); ProjKind::Stereographic },
+
+        "tmerc" => { used_params.push("lat_0"); ProjKind::TransverseMercator },
+
+        "utm" => { used_params.push("zone"); ProjKind::TransverseMercator },
+
+        _ => return Err(format!("unsupported projection: {}", proj))
+    };
+
+    if let Some((key, _)) = params.iter().find(|(k, _)| !used_params.contains(k) && !IGNORED_PARAMS.contains(k)) {
+        return Err(format!("unsupported parameter: {}", key));
+    }
+
+    let center_lon = if proj == "utm" {
+        let zone = get_number("zone")?.ok_or_else(|| "missing UTM zone (\"zone\")".to_string())?;
+        if zone.fract() != 0.0 || !(1.0..=60.0).contains(&zone) {
+            return Err(format!("invalid UTM zone: {}", zone));
+        }
+        cgmath::Deg(6.0 * zone - 183.0)
+    } else {
+        get_angle("lon_0", 180.0)?.unwrap_or(cgmath::Deg(0.0))
+    };
+
+    // only azimuthal projections are centered on `lat_0`; for lcc and tmerc it is the grid's origin
+    let is_azimuthal = matches!(proj, "gnom" | "laea" | "nsper" | "ortho" | "qsc" | "stere");
+    let center_lat = if proj == "peirce_q" {
+        // the northern hemisphere in the middle
+        cgmath::Deg(90.0)
+    } else if is_azimuthal {
+        get_angle("lat_0", 90.0)?.unwrap_or(cgmath::Deg(0.0))
+    } else {
+        cgmath::Deg(0.0)
+    };
+
+    let mut ellipsoidal = false;
+    for key in ["ellps", "datum"] {
+        match get(key) {
+            None => (),
+            Some(Some(value)) if WGS84_ELLIPSOIDS.contains(&value) => ellipsoidal = true,
+            Some(Some(value)) => return Err(format!("unsupported {}: {} (only WGS84 is available)", key, value)),
+            Some(None) => return Err(format!("parameter \"{}\" requires a value", key))
+        }
+    }
+    // with the sphere radius given explicitly, PROJ ignores the ellipsoid
+    if get("R").is_some() { ellipsoidal = false; }
+
+    let mut grid = if proj == "utm" {
+        views::Grid{ central_meridian: center_lon.0, ..views::Grid::utm(get("south").is_some()) }
+    } else {
+        // the grid origin of lcc and tmerc may lie off the equator (without moving the map)
+        let origin_latitude = if matches!(proj, "lcc" | "tmerc") {
+            get_angle("lat_0", 90.0)?.unwrap_or(center_lat)
+        } else {
+            center_lat
+        };
+        views::Grid{ central_meridian: center_lon.0, origin_latitude: origin_latitude.0, ..views::Grid::DEFAULT }
+    };
+    if get("k_0").is_some() && get("k").is_some() {
+        return Err("only one of \"k_0\" and \"k\" can be specified".to_string());
+    }
+    if let Some(scale_factor) = get_number("k_0")?.or(get_number("k")?) {
+        if scale_factor <= 0.0 {
+            return Err(format!("scale factor must be positive: {}", scale_factor));
+        }
+        grid.scale_factor = scale_factor;
+    }
+    if let Some(false_easting) = get_number("x_0")? { grid.false_easting = false_easting; }
+    if let Some(false_northing) = get_number("y_0")? { grid.false_northing = false_northing; }
+
+    Ok(ProjDefinition{ kind, center_lon, center_lat, ellipsoidal, grid })
+}
+
+/// Creates a view according to `definition`; returns its unique id.
+pub fn create_view(
+    definition: &ProjDefinition,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) -> Result<u32, String> {
+    let ellipsoidal = if definition.ellipsoidal { 1.0 } else { 0.0 };
+    // projection name as used in session files and parameters by uniform name
+    let (projection, params): (&str, Vec<(&str, f64)>) = match definition.kind {
+        ProjKind::AdamsHemisphere => ("adams_hemisphere", vec![]),
+        ProjKind::AlbersEqualArea{ standard_parallels } => ("albers_equal_area", vec![
+            ("ellipsoidal", ellipsoidal),
+            ("standard_parallel_1", standard_parallels[0].0),
+            ("standard_parallel_2", standard_parallels[1].0)
+        ]),
+        ProjKind::CylindricalEqualArea{ standard_parallel } =>
+            ("cylindrical_lambert", vec![("standard_parallel", standard_parallel.0.abs())]),
+        ProjKind::EckertIV => ("eckert_iv", vec![]),
+        ProjKind::EckertVI => ("eckert_vi", vec![]),
+        ProjKind::GallStereographic => ("gall_stereographic", vec![]),
+        ProjKind::GeneralPerspective{ altitude } => ("general_perspective", vec![("altitude", altitude)]),
+        ProjKind::Gnomonic => ("gnomonic", vec![]),
+        ProjKind::Guyou => ("guyou", vec![]),
+        ProjKind::Hammer => ("hammer", vec![]),
+        ProjKind::LambertAzimuthal => ("lambert_azimuthal", vec![]),
+        ProjKind::LambertConformalConic{ standard_parallels } => ("lambert_conformal_conic", vec![
+            ("ellipsoidal", ellipsoidal),
+            ("standard_parallel_1", standard_parallels[0].0),
+            ("standard_parallel_2", standard_parallels[1].0)
+        ]),
+        ProjKind::Loximuthal{ central_latitude } => ("loximuthal", vec![("central_latitude", central_latitude.0)]),
+        ProjKind::Mercator => ("mercator", vec![("ellipsoidal", ellipsoidal)]),
+        ProjKind::Miller => ("miller", vec![]),
+        ProjKind::Mollweide => ("mollweide", vec![]),
+        ProjKind::Nicolosi => ("nicolosi", vec![]),
+        ProjKind::Orthographic => ("orthographic", vec![]),
+        ProjKind::PeirceQuincuncial => ("peirce_quincuncial", vec![]),
+        ProjKind::Qsc => ("qsc", vec![]),
+        ProjKind::Stereographic => ("stereographic", vec![]),
+        ProjKind::TransverseMercator => ("transverse_mercator", vec![("ellipsoidal", ellipsoidal)])
+    };
+
+    let unique_id = views::create_view(projection, None, program_data, renderer, display)?;
+    let view = program_data.all_views_mut().find(|view| view.unique_id() == unique_id).unwrap();
+    for (name, value) in params {
+        if let Some(idx) = view.projection_params().iter().position(|param| param.uniform_name == name) {
+            view.set_projection_param_value(idx, value);
+        }
+    }
+    view.center_on(definition.center_lon, definition.center_lat);
+    view.set_grid(definition.grid);
+
+    Ok(unique_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lambert_conformal_conic() {
+        let definition = parse("+proj=lcc +lat_1=33 +lat_2=45 +lon_0=-96 +ellps=GRS80 +units=m").unwrap();
+        assert_eq!(
+            definition.kind,
+            ProjKind::LambertConformalConic{ standard_parallels: [cgmath::Deg(33.0), cgmath::Deg(45.0)] }
+        );
+        assert_eq!(definition.center_lon, cgmath::Deg(-96.0));
+        assert_eq!(definition.center_lat, cgmath::Deg(0.0));
+        assert!(definition.ellipsoidal);
+    }
+
+    #[test]
+    fn utm_zone_determines_central_meridian() {
+        let definition = parse("+proj=utm +zone=33 +south +datum=WGS84").unwrap();
+        assert_eq!(definition.kind, ProjKind::TransverseMercator);
+        assert_eq!(definition.center_lon, cgmath::Deg(15.0));
+        assert_eq!(definition.grid, views::Grid{ central_meridian: 15.0, ..views::Grid::utm(true) });
+    }
+
+    #[test]
+    fn transverse_mercator_origin_latitude_does_not_move_map() {
+        let definition = parse("+proj=tmerc +lat_0=49 +lon_0=-2 +k=0.9996012717 +x_0=400000 +y_0=-100000").unwrap();
+        assert_eq!(definition.center_lat, cgmath::Deg(0.0));
+        assert_eq!(definition.grid, views::Grid{
+            scale_factor: 0.9996012717,
+            false_easting: 400_000.0,
+            false_northing: -100_000.0,
+            central_meridian: -2.0,
+            origin_latitude: 49.0
+        });
+    }
+
+    #[test]
+    fn lambert_conformal_conic_origin_latitude_sets_grid_origin() {
+        let definition = parse(
+            "+proj=lcc +lat_0=46.5 +lon_0=3 +lat_1=49 +lat_2=44 +x_0=700000 +y_0=6600000 +ellps=GRS80"
+        ).unwrap();
+        assert_eq!(definition.center_lat, cgmath::Deg(0.0));
+        assert_eq!(definition.grid, views::Grid{
+            scale_factor: 1.0,
+            false_easting: 700_000.0,
+            false_northing: 6_600_000.0,
+            central_meridian: 3.0,
+            origin_latitude: 46.5
+        });
+    }
+
+    #[test]
+    fn sphere_radius_overrides_ellipsoid() {
+        assert!(!parse("+proj=merc +ellps=WGS84 +R=6371000").unwrap().ellipsoidal);
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        for definition in [
+            "",
+            "+lon_0=10",
+            "+proj=xyz",
+            "+proj=merc +lon_0=10 +lon_0=20",
+            "+proj=merc +lon_0=abc",
+            "+proj=merc +lon_0=nan",
+            "+proj=merc +lon_0=inf",
+            "+proj=merc +x_0=-inf",
+            "+proj=merc +y_0=NaN",
+            "+proj=tmerc +k=infinity",
+            "+proj=nsper +h=inf",
+            "+proj=utm +zone=nan",
+            "+proj=merc +lon_0=190",
+            "+proj=merc +foo=1",
+            "+proj=lcc +lon_0=10",
+            "+proj=utm",
+            "+proj=utm +zone=61",
+            "+proj=utm +zone=10.5",
+            "+proj=nsper +h=-1",
+            "+proj=tmerc +k=1 +k_0=1",
+            "+proj=merc +ellps=clrk66"
+        ] {
+            assert!(parse(definition).is_err(), "accepted \"{}\"", definition);
+        }
+    }
+}
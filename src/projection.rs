@@ -0,0 +1,1456 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// CPU-side projection formulas. They mirror the vertex shaders in "resources/shaders"
+// and are used wherever projected coordinates are needed outside of rendering.
+//
+
+use cgmath::{InnerSpace, Point2, Vector3};
+use crate::plugins::Plugin;
+
+/// Step used for numerical differentiation of projection formulas.
+const DIFF_STEP: f64 = 1.0e-6;
+
+const BOUNDARY_NUM_SEGMENTS: usize = 180;
+
+/// Spacing (in degrees) of the grid searched for the starting point in `invert_numerically`.
+const INVERSE_GRID_STEP: f64 = 2.0;
+
+const INVERSE_MAX_ITERATIONS: usize = 20;
+
+/// Max. distance (in the units of the inverted function) between the requested and the found point
+/// in `invert_numerically`.
+const INVERSE_TOLERANCE: f64 = 1.0e-6;
+
+/// Eccentricity of the WGS84 ellipsoid.
+pub const WGS84_ECCENTRICITY: f64 = 0.08181919084262149;
+
+/// Semi-major axis of the WGS84 ellipsoid (meters); map coordinates are expressed in its multiples.
+pub const WGS84_SEMI_MAJOR_AXIS: f64 = 6_378_137.0;
+
+/// Max. absolute latitude shown by the Mercator projection (the map is infinite at the poles; Mercator views
+/// are normally truncated at a lower latitude).
+const MERCATOR_MAX_LATITUDE: f64 = 1.562069680534925; // 89.5°
+
+/// Max. absolute longitude (from the central meridian) shown by the transverse Mercator projection.
+const TRANSVERSE_MERCATOR_MAX_LONGITUDE: f64 = 1.3962634015954636; // 80°
+
+/// Max. latitude (towards the pole opposite to the cone's apex) shown by the Lambert conformal conic projection.
+const LAMBERT_CONFORMAL_CONIC_MAX_LATITUDE: f64 = 1.3962634015954636; // 80°
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    AdamsHemisphere,
+    /// `standard_parallels`: parallels along which the scale is true;
+    /// `eccentricity`: 0 for a sphere, otherwise that of an ellipsoid with the semi-major axis equal to 1.
+    AlbersEqualArea{ standard_parallels: [cgmath::Rad<f64>; 2], eccentricity: f64 },
+    /// `parallel`: parallel shown as the torus' outer rim.
+    Armadillo{ parallel: cgmath::Rad<f64> },
+    /// `standard_parallel`: parallel (and its opposite) along which the scale is true.
+    CylindricalLambert{ standard_parallel: cgmath::Rad<f64> },
+    EckertIV,
+    EckertVI,
+    GallStereographic,
+    /// `distance`: observer's distance from the globe's center (in globe radii);
+    /// `tilt`: tilt of the line of sight towards the top of the view.
+    GeneralPerspective{ distance: f64, tilt: cgmath::Rad<f64> },
+    /// `max_angle`: max. angular distance of the shown points from the center of the view.
+    Gnomonic{ max_angle: cgmath::Rad<f64> },
+    Guyou,
+    /// `axis_ratio`: ratio of the bounding ellipse's axes (2 for the Hammer projection, 1.75 for Briesemeister).
+    Hammer{ axis_ratio: f64 },
+    LambertAzimuthalEqualArea,
+    /// `standard_parallels`: parallels along which the scale is true;
+    /// `eccentricity`: 0 for a sphere, otherwise that of an ellipsoid with the semi-major axis equal to 1.
+    LambertConformalConic{ standard_parallels: [cgmath::Rad<f64>; 2], eccentricity: f64 },
+    Littrow,
+    /// `central_latitude`: latitude of the central point (from which rhumb lines are straight and true to scale).
+    Loximuthal{ central_latitude: cgmath::Rad<f64> },
+    /// `eccentricity`: 0 for a sphere, otherwise that of an ellipsoid with the semi-major axis equal to 1.
+    Mercator{ eccentricity: f64 },
+    Miller,
+    Mollweide,
+    Nicolosi,
+    Orthographic,
+    /// Centered on the view's center; the rear hemisphere is split into the map's corners.
+    PeirceQuincuncial,
+    QuadrilateralizedSphericalCube,
+    /// Azimuthal perspective projection from the point (-`point_distance`, 0, 0) onto the plane x = `image_plane`;
+    /// `point_distance` = 1 and `image_plane` = 1 give the stereographic projection.
+    Stereographic{ point_distance: f64, image_plane: f64 },
+    /// `eccentricity`: 0 for a sphere, otherwise that of an ellipsoid with the semi-major axis equal to 1.
+    TransverseMercator{ eccentricity: f64 },
+    Plugin(&'static Plugin)
+}
+
+impl Projection {
+    pub const ALL: &'static [Projection] = &[
+        Projection::AdamsHemisphere,
+        Projection::AlbersEqualArea{
+            standard_parallels: [cgmath::Rad(0.5759586531581288), cgmath::Rad(0.7853981633974483)], // 33°, 45°
+            eccentricity: 0.0
+        },
+        Projection::Armadillo{ parallel: cgmath::Rad(0.3490658503988659) }, // 20°
+        Projection::CylindricalLambert{ standard_parallel: cgmath::Rad(0.0) },
+        Projection::EckertIV,
+        Projection::EckertVI,
+        Projection::GallStereographic,
+        Projection::GeneralPerspective{ distance: 1.0 + 35786.0 / 6371.0, tilt: cgmath::Rad(0.0) },
+        Projection::Gnomonic{ max_angle: cgmath::Rad(1.3962634015954636) }, // 80°
+        Projection::Guyou,
+        Projection::Hammer{ axis_ratio: 2.0 },
+        Projection::LambertAzimuthalEqualArea,
+        Projection::LambertConformalConic{
+            standard_parallels: [cgmath::Rad(0.5759586531581288), cgmath::Rad(0.7853981633974483)], // 33°, 45°
+            eccentricity: 0.0
+        },
+        Projection::Littrow,
+        Projection::Loximuthal{ central_latitude: cgmath::Rad(0.6981317007977318) }, // 40°
+        Projection::Mercator{ eccentricity: 0.0 },
+        Projection::Miller,
+        Projection::Mollweide,
+        Projection::Nicolosi,
+        Projection::Orthographic,
+        Projection::PeirceQuincuncial,
+        Projection::QuadrilateralizedSphericalCube,
+        Projection::Stereographic{ point_distance: 1.0, image_plane: 1.0 },
+        Projection::TransverseMercator{ eccentricity: 0.0 }
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Projection::AdamsHemisphere => "Adams hemisphere-in-a-square",
+            Projection::AlbersEqualArea{ .. } => "Albers equal-area conic",
+            Projection::Armadillo{ .. } => "Armadillo",
+            Projection::CylindricalLambert{ standard_parallel } => if standard_parallel.0 == 0.0 {
+                "Lambert cylindrical equal-area"
+            } else {
+                "Cylindrical equal-area"
+            },
+            Projection::EckertIV => "Eckert IV",
+            Projection::EckertVI => "Eckert VI",
+            Projection::GallStereographic => "Gall stereographic",
+            Projection::GeneralPerspective{ .. } => "General perspective",
+            Projection::Gnomonic{ .. } => "Gnomonic",
+            Projection::Guyou => "Guyou",
+            Projection::Hammer{ .. } => "Hammer",
+            Projection::LambertAzimuthalEqualArea => "Lambert azimuthal equal-area",
+            Projection::LambertConformalConic{ .. } => "Lambert conformal conic",
+            Projection::Littrow => "Littrow",
+            Projection::Loximuthal{ .. } => "Loximuthal",
+            Projection::Mercator{ .. } => "Mercator",
+            Projection::Miller => "Miller cylindrical",
+            Projection::Mollweide => "Mollweide",
+            Projection::Nicolosi => "Nicolosi globular",
+            Projection::Orthographic => "Orthographic",
+            Projection::PeirceQuincuncial => "Peirce quincuncial",
+            Projection::QuadrilateralizedSphericalCube => "Quadrilateralized spherical cube",
+            Projection::Stereographic{ .. } => "Stereographic",
+            Projection::TransverseMercator{ .. } => "Transverse Mercator",
+            Projection::Plugin(plugin) => &plugin.name
+        }
+    }
+
+    /// Returns true if the projection can be calculated on the CPU (i.e., it is not a GLSL plugin). Without it,
+    /// the projection can only be displayed: unprojection (the cursor readout, picking), labels, markers and exports
+    /// of projected lines are not available.
+    pub fn has_cpu_projection(&self) -> bool {
+        match self {
+            Projection::Plugin(plugin) => plugin.has_cpu_projection(),
+            _ => true
+        }
+    }
+
+    /// Returns true for azimuthal projections, which are centered on the point of tangency (e.g. their grid's origin)
+    /// rather than on the equator.
+    pub fn is_azimuthal(&self) -> bool {
+        matches!(
+            self,
+            Projection::GeneralPerspective{ .. }
+                | Projection::Gnomonic{ .. }
+                | Projection::LambertAzimuthalEqualArea
+                | Projection::Orthographic
+                | Projection::Stereographic{ .. }
+        )
+    }
+
+    /// Returns a short description of what the projection preserves (or what it is best suited for).
+    pub fn properties(&self) -> &'static str {
+        match *self {
+            Projection::AdamsHemisphere => "conformal (except corners)",
+            Projection::AlbersEqualArea{ .. } => "equal-area; mid-latitude regions",
+            Projection::Armadillo{ .. } => "compromise; artistic",
+            Projection::CylindricalLambert{ .. } => "equal-area",
+            Projection::EckertIV => "equal-area; world maps",
+            Projection::EckertVI => "equal-area; world maps",
+            Projection::GallStereographic => "compromise; world maps",
+            Projection::GeneralPerspective{ .. } => "view from space",
+            Projection::Gnomonic{ .. } => "great circles are straight",
+            Projection::Guyou => "conformal (except corners)",
+            Projection::Hammer{ .. } => "equal-area; world maps",
+            Projection::LambertAzimuthalEqualArea => "equal-area; directions from center",
+            Projection::LambertConformalConic{ .. } => "conformal; mid-latitude regions",
+            Projection::Littrow => "conformal; retroazimuthal",
+            Projection::Loximuthal{ .. } => "rhumb lines from center are true",
+            Projection::Mercator{ .. } => "conformal; navigation",
+            Projection::Miller => "compromise; world maps",
+            Projection::Mollweide => "equal-area; world maps",
+            Projection::Nicolosi => "compromise; hemispheres",
+            Projection::Orthographic => "perspective; globe-like",
+            Projection::PeirceQuincuncial => "conformal (except 4 points)",
+            Projection::QuadrilateralizedSphericalCube => "equal-area; data storage",
+            Projection::Stereographic{ .. } => "conformal; directions from center",
+            Projection::TransverseMercator{ .. } => "conformal; north-south regions",
+            Projection::Plugin(_) => ""
+        }
+    }
+
+    /// Projects a point of the unit globe onto the map plane.
+    ///
+    /// `position` is the point's position after applying the view's orientation (i.e., the observer is looking
+    /// at (1, 0, 0)). The result is expressed in the same units as the vertex shader's output before applying
+    /// zoom and aspect ratio. Returns `None` if the point is not rendered by the projection.
+    ///
+    pub fn project(&self, position: Vector3<f64>) -> Option<Point2<f64>> {
+        match self {
+            Projection::AdamsHemisphere => {
+                if position.x < 0.0 {
+                    None
+                } else {
+                    let (longitude, latitude) = xyz_to_lonlat(position);
+                    Some(adams_hemisphere(longitude.0, latitude.0))
+                }
+            },
+
+            Projection::Armadillo{ parallel } => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                if armadillo_visible(longitude.0, latitude.0, parallel.0) {
+                    Some(armadillo(longitude.0, latitude.0, parallel.0))
+                } else {
+                    None
+                }
+            },
+
+            Projection::CylindricalLambert{ standard_parallel } => {
+                let angle = position.y.atan2(position.x);
+                let cos_sp = standard_parallel.0.cos();
+                Some(Point2{ x: angle * cos_sp, y: position.z / cos_sp })
+            },
+
+            Projection::EckertIV => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                Some(eckert_iv(longitude.0, latitude.0))
+            },
+
+            Projection::EckertVI => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                Some(eckert_vi(longitude.0, latitude.0))
+            },
+
+            Projection::GallStereographic => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                Some(Point2{
+                    x: longitude.0 * std::f64::consts::FRAC_1_SQRT_2,
+                    y: (1.0 + std::f64::consts::FRAC_1_SQRT_2) * (latitude.0 / 2.0).tan()
+                })
+            },
+
+            Projection::GeneralPerspective{ distance, tilt } => {
+                // near-side visibility test
+                if position.x < 1.0 / distance { return None; }
+
+                let k = (distance - 1.0) / (distance - position.x);
+                let (x, y) = (k * position.y, k * position.z);
+
+                let a = y * tilt.0.sin() / (distance - 1.0) + tilt.0.cos();
+                if a <= 0.0 { return None; }
+
+                Some(Point2{ x: x * tilt.0.cos() / a, y: y / a })
+            },
+
+            Projection::Gnomonic{ max_angle } => {
+                if position.x < max_angle.0.cos() {
+                    None
+                } else {
+                    Some(Point2{ x: position.y / position.x, y: position.z / position.x })
+                }
+            },
+
+            Projection::Guyou => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                if position.x >= 0.0 {
+                    Some(guyou_hemisphere(longitude.0, latitude.0))
+                } else {
+                    // the rear hemisphere is the mirror image of the front one, reflected across the square's side
+                    let sign = longitude.0.signum();
+                    let mirrored = guyou_hemisphere(sign * std::f64::consts::PI - longitude.0, latitude.0);
+                    Some(Point2{ x: sign * 2.0 * ELL_K - mirrored.x, y: mirrored.y })
+                }
+            },
+
+            Projection::Hammer{ axis_ratio } => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                Some(hammer(longitude.0, latitude.0, axis_ratio))
+            },
+
+            Projection::LambertAzimuthalEqualArea => {
+                // the point opposite to the center of the view is projected onto the whole boundary circle
+                if 1.0 + position.x < 1.0e-12 {
+                    None
+                } else {
+                    let k = (2.0 / (1.0 + position.x)).sqrt();
+                    Some(Point2{ x: k * position.y, y: k * position.z })
+                }
+            },
+
+            Projection::Littrow => {
+                // the map is infinite towards the hemisphere's edge; show the same extent as the gnomonic projection
+                if position.x < cgmath::Rad::from(cgmath::Deg(80.0)).0.cos() {
+                    None
+                } else {
+                    let cos2_lat = position.x.powi(2) + position.y.powi(2);
+                    Some(Point2{ x: position.y / cos2_lat, y: position.x * position.z / cos2_lat })
+                }
+            },
+
+            Projection::Loximuthal{ central_latitude } => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                Some(loximuthal(longitude.0, latitude.0, central_latitude.0))
+            },
+
+            Projection::Mollweide => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                Some(mollweide(longitude.0, latitude.0))
+            },
+
+            Projection::Nicolosi => {
+                if position.x < 0.0 {
+                    None
+                } else {
+                    let (longitude, latitude) = xyz_to_lonlat(position);
+                    Some(nicolosi(longitude.0, latitude.0))
+                }
+            },
+
+            Projection::Orthographic => {
+                if position.x < 0.0 {
+                    None
+                } else {
+                    Some(Point2{ x: position.y, y: position.z })
+                }
+            },
+
+            Projection::PeirceQuincuncial => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                Some(peirce_quincuncial(longitude.0, latitude.0))
+            },
+
+            Projection::QuadrilateralizedSphericalCube => Some(qsc(position)),
+
+            Projection::Stereographic{ point_distance, image_plane } => {
+                if position.x < 0.0 {
+                    None
+                } else {
+                    let k = (image_plane + point_distance) / (position.x + point_distance);
+                    Some(Point2{ x: k * position.y, y: k * position.z })
+                }
+            },
+
+            Projection::AlbersEqualArea{ standard_parallels, eccentricity } => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                albers_equal_area(
+                    longitude.0,
+                    latitude.0,
+                    [standard_parallels[0].0, standard_parallels[1].0],
+                    eccentricity
+                )
+            },
+
+            Projection::LambertConformalConic{ standard_parallels, eccentricity } => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                lambert_conformal_conic(
+                    longitude.0,
+                    latitude.0,
+                    [standard_parallels[0].0, standard_parallels[1].0],
+                    eccentricity
+                )
+            },
+
+            Projection::Mercator{ eccentricity } => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                if latitude.0.abs() > MERCATOR_MAX_LATITUDE {
+                    None
+                } else {
+                    Some(Point2{ x: longitude.0, y: -conformal_t(latitude.0, eccentricity).ln() })
+                }
+            },
+
+            Projection::Miller => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                Some(Point2{
+                    x: longitude.0,
+                    y: 1.25 * (std::f64::consts::FRAC_PI_4 + 0.4 * latitude.0).tan().ln()
+                })
+            },
+
+            Projection::TransverseMercator{ eccentricity } => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                transverse_mercator(longitude.0, latitude.0, eccentricity)
+            },
+
+            Projection::Plugin(plugin) => {
+                let (longitude, latitude) = xyz_to_lonlat(position);
+                plugin.forward(longitude, latitude)
+            }
+        }
+    }
+
+    /// Returns (longitude, latitude) of the point of the normal aspect's map at `point` (map coordinates), or `None`
+    /// if there is no such point. Closed-form inverse formulas are used if available (see `inverse`); other
+    /// projections are inverted numerically (see `invert_numerically`).
+    pub fn unproject(&self, point: Point2<f64>) -> Option<(cgmath::Rad<f64>, cgmath::Rad<f64>)> {
+        if self.has_inverse() { return self.inverse(point).map(xyz_to_lonlat); }
+
+        let project = |longitude: f64, latitude: f64| {
+            self.project(lonlat_to_xyz(cgmath::Deg(longitude).into(), cgmath::Deg(latitude).into()))
+        };
+        invert_numerically(project, point)
+            .map(|(longitude, latitude)| (cgmath::Deg(longitude).into(), cgmath::Deg(latitude).into()))
+    }
+
+    /// Returns true if the projection has closed-form inverse formulas (see `inverse`).
+    pub fn has_inverse(&self) -> bool {
+        match self {
+            Projection::AlbersEqualArea{ .. }
+            | Projection::CylindricalLambert{ .. }
+            | Projection::EckertIV
+            | Projection::EckertVI
+            | Projection::GallStereographic
+            | Projection::Gnomonic{ .. }
+            | Projection::Hammer{ .. }
+            | Projection::LambertAzimuthalEqualArea
+            | Projection::LambertConformalConic{ .. }
+            | Projection::Littrow
+            | Projection::Loximuthal{ .. }
+            | Projection::Mercator{ .. }
+            | Projection::Miller
+            | Projection::Mollweide
+            | Projection::Orthographic
+            | Projection::QuadrilateralizedSphericalCube
+            | Projection::Stereographic{ .. }
+            | Projection::TransverseMercator{ .. } => true,
+
+            Projection::Plugin(plugin) => plugin.has_cpu_inverse(),
+
+            _ => false
+        }
+    }
+
+    /// Inverse of `project` by closed-form formulas: returns the point of the unit globe projected to `point`
+    /// (map coordinates), or `None` if there is no such point or the projection has no closed-form inverse
+    /// (see `has_inverse`).
+    pub fn inverse(&self, point: Point2<f64>) -> Option<Vector3<f64>> {
+        use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_4};
+
+        let (x, y) = (point.x, point.y);
+        let from_lonlat = |longitude: f64, latitude: f64| lonlat_to_xyz(cgmath::Rad(longitude), cgmath::Rad(latitude));
+
+        let position = match *self {
+            Projection::AlbersEqualArea{ standard_parallels, eccentricity } => {
+                let (longitude, latitude) = albers_equal_area_inverse(
+                    x,
+                    y,
+                    [standard_parallels[0].0, standard_parallels[1].0],
+                    eccentricity
+                )?;
+                from_lonlat(longitude, latitude)
+            },
+
+            Projection::CylindricalLambert{ standard_parallel } => {
+                let cos_sp = standard_parallel.0.cos();
+                let z = y * cos_sp;
+                if z.abs() > 1.0 { return None; }
+                from_lonlat(x / cos_sp, z.asin())
+            },
+
+            Projection::EckertIV => {
+                let (longitude, latitude) = eckert_iv_inverse(x, y)?;
+                from_lonlat(longitude, latitude)
+            },
+
+            Projection::EckertVI => {
+                let (longitude, latitude) = eckert_vi_inverse(x, y)?;
+                from_lonlat(longitude, latitude)
+            },
+
+            Projection::GallStereographic => from_lonlat(x / FRAC_1_SQRT_2, 2.0 * (y / (1.0 + FRAC_1_SQRT_2)).atan()),
+
+            Projection::Gnomonic{ .. } => Vector3::new(1.0, x, y).normalize(),
+
+            Projection::Hammer{ axis_ratio } => {
+                let (longitude, latitude) = hammer_inverse(x, y, axis_ratio)?;
+                from_lonlat(longitude, latitude)
+            },
+
+            Projection::LambertAzimuthalEqualArea => {
+                let rho2 = x * x + y * y;
+                if rho2 >= 4.0 { return None; }
+                let position_x = 1.0 - rho2 / 2.0;
+                let k = (2.0 / (1.0 + position_x)).sqrt();
+                Vector3::new(position_x, x / k, y / k)
+            },
+
+            Projection::LambertConformalConic{ standard_parallels, eccentricity } => {
+                let (longitude, latitude) = lambert_conformal_conic_inverse(
+                    x,
+                    y,
+                    [standard_parallels[0].0, standard_parallels[1].0],
+                    eccentricity
+                )?;
+                from_lonlat(longitude, latitude)
+            },
+
+            Projection::Littrow => {
+                let (longitude, latitude) = littrow_inverse(x, y)?;
+                from_lonlat(longitude, latitude)
+            },
+
+            Projection::Loximuthal{ central_latitude } => {
+                let (longitude, latitude) = loximuthal_inverse(x, y, central_latitude.0)?;
+                from_lonlat(longitude, latitude)
+            },
+
+            Projection::Mercator{ eccentricity } => from_lonlat(x, latitude_from_conformal_t((-y).exp(), eccentricity)),
+
+            Projection::Miller => from_lonlat(x, 2.5 * (0.8 * y).exp().atan() - 2.5 * FRAC_PI_4),
+
+            Projection::Mollweide => {
+                let (longitude, latitude) = mollweide_inverse(x, y)?;
+                from_lonlat(longitude, latitude)
+            },
+
+            Projection::Orthographic => {
+                let rho2 = x * x + y * y;
+                if rho2 > 1.0 { return None; }
+                Vector3::new((1.0 - rho2).sqrt(), x, y)
+            },
+
+            Projection::QuadrilateralizedSphericalCube => qsc_inverse(point)?,
+
+            Projection::Stereographic{ point_distance, image_plane } => {
+                // solves rho^2 (p_x + d)^2 = c^2 (1 - p_x^2) (with c = image plane + d) for the near-side p_x
+                let (d, c) = (point_distance, image_plane + point_distance);
+                let rho2 = x * x + y * y;
+                let discriminant = c * c + rho2 * (1.0 - d * d);
+                if discriminant < 0.0 || rho2 + c * c == 0.0 { return None; }
+                let position_x = (-rho2 * d + c * discriminant.sqrt()) / (rho2 + c * c);
+                let k = c / (position_x + d);
+                Vector3::new(position_x, x / k, y / k)
+            },
+
+            Projection::TransverseMercator{ eccentricity } => {
+                let (longitude, latitude) = transverse_mercator_inverse(x, y, eccentricity);
+                from_lonlat(longitude, latitude)
+            },
+
+            Projection::Plugin(plugin) => {
+                return plugin.inverse(point).map(|(longitude, latitude)| lonlat_to_xyz(longitude, latitude));
+            },
+
+            _ => return None
+        };
+
+        // rejects points outside of the map (e.g. beyond the longitude range or the max. latitude)
+        match self.project(position) {
+            Some(p) if (p - point).magnitude() < INVERSE_TOLERANCE => Some(position),
+            _ => None
+        }
+    }
+
+    /// Returns the boundary (as line segments in map coordinates) of the projection's extent, if the projection
+    /// is limited to a part of the globe whose edge is not a natural map edge (e.g. where the map is cut off).
+    pub fn boundary(&self) -> Option<Vec<[Point2<f64>; 2]>> {
+        match *self {
+            Projection::Armadillo{ parallel } => Some(armadillo_horizon(parallel.0)),
+            Projection::Gnomonic{ max_angle } => Some(circle(max_angle.0.tan(), BOUNDARY_NUM_SEGMENTS)),
+            _ => None
+        }
+    }
+}
+
+//
+// Ellipsoidal formulas below are from J. P. Snyder, "Map Projections - A Working Manual" (1987), and (transverse
+// Mercator) C. F. F. Karney, "Transverse Mercator with an accuracy of a few nanometers" (2011). The ellipsoid's
+// semi-major axis equals 1; with eccentricity 0 the formulas reduce to the spherical ones.
+//
+
+/// Returns the parallel's radius divided by the radius of curvature in the prime vertical (Snyder's m).
+fn parallel_factor(latitude: f64, eccentricity: f64) -> f64 {
+    let e_sin = eccentricity * latitude.sin();
+    latitude.cos() / (1.0 - e_sin * e_sin).sqrt()
+}
+
+/// Returns tan(π/4 - χ/2), where χ is the conformal latitude (Snyder's t).
+fn conformal_t(latitude: f64, eccentricity: f64) -> f64 {
+    let e_sin = eccentricity * latitude.sin();
+    (std::f64::consts::FRAC_PI_4 - latitude / 2.0).tan() / ((1.0 - e_sin) / (1.0 + e_sin)).powf(eccentricity / 2.0)
+}
+
+/// Inverse of `conformal_t`: returns the latitude (Snyder's (7-9), iterated).
+fn latitude_from_conformal_t(t: f64, eccentricity: f64) -> f64 {
+    use std::f64::consts::FRAC_PI_2;
+
+    let mut latitude = FRAC_PI_2 - 2.0 * t.atan();
+    if eccentricity == 0.0 { return latitude; }
+    for _ in 0..INVERSE_MAX_ITERATIONS {
+        let e_sin = eccentricity * latitude.sin();
+        latitude = FRAC_PI_2 - 2.0 * (t * ((1.0 - e_sin) / (1.0 + e_sin)).powf(eccentricity / 2.0)).atan();
+    }
+
+    latitude
+}
+
+/// Returns Snyder's q (proportional to the area between the equator and the parallel).
+fn authalic_q(latitude: f64, eccentricity: f64) -> f64 {
+    let sin_lat = latitude.sin();
+    if eccentricity == 0.0 { return 2.0 * sin_lat; }
+
+    let e_sin = eccentricity * sin_lat;
+    (1.0 - eccentricity.powi(2)) * (
+        sin_lat / (1.0 - e_sin * e_sin) - 1.0 / (2.0 * eccentricity) * ((1.0 - e_sin) / (1.0 + e_sin)).ln()
+    )
+}
+
+/// Inverse of `authalic_q`: returns the latitude (Snyder's (3-16), iterated), or `None` if `q` exceeds its value
+/// at the poles.
+fn latitude_from_authalic_q(q: f64, eccentricity: f64) -> Option<f64> {
+    use std::f64::consts::FRAC_PI_2;
+    const EPS: f64 = 1.0e-12;
+
+    let q_pole = authalic_q(FRAC_PI_2, eccentricity);
+    if q.abs() > q_pole + EPS { return None; }
+    if q.abs() >= q_pole - EPS { return Some(FRAC_PI_2.copysign(q)); }
+
+    let mut latitude = (q / 2.0).max(-1.0).min(1.0).asin();
+    if eccentricity == 0.0 { return Some(latitude); }
+    let e2 = eccentricity.powi(2);
+    for _ in 0..INVERSE_MAX_ITERATIONS {
+        let sin_lat = latitude.sin();
+        let e_sin = eccentricity * sin_lat;
+        let one_minus_e2_sin2 = 1.0 - e_sin * e_sin;
+        let delta = one_minus_e2_sin2.powi(2) / (2.0 * latitude.cos()) * (
+            q / (1.0 - e2) - sin_lat / one_minus_e2_sin2
+                + 1.0 / (2.0 * eccentricity) * ((1.0 - e_sin) / (1.0 + e_sin)).ln()
+        );
+        latitude += delta;
+        if delta.abs() < EPS { break; }
+    }
+
+    Some(latitude)
+}
+
+/// Returns the third flattening and the rectifying radius (in multiples of the semi-major axis) of an ellipsoid.
+fn third_flattening_and_rectifying_radius(eccentricity: f64) -> (f64, f64) {
+    let flattening = 1.0 - (1.0 - eccentricity.powi(2)).sqrt();
+    let n = flattening / (2.0 - flattening);
+
+    (n, (1.0 + n.powi(2) / 4.0 + n.powi(4) / 64.0) / (1.0 + n))
+}
+
+/// Transverse Mercator projection (Krüger series) with the central meridian at longitude 0;
+/// the result is expressed in multiples of the semi-major axis.
+pub fn transverse_mercator(longitude: f64, latitude: f64, eccentricity: f64) -> Option<Point2<f64>> {
+    if longitude.abs() > TRANSVERSE_MERCATOR_MAX_LONGITUDE { return None; }
+
+    let (n, rectifying_radius) = third_flattening_and_rectifying_radius(eccentricity);
+    let alpha = [
+        n / 2.0 - 2.0 / 3.0 * n.powi(2) + 5.0 / 16.0 * n.powi(3),
+        13.0 / 48.0 * n.powi(2) - 3.0 / 5.0 * n.powi(3),
+        61.0 / 240.0 * n.powi(3)
+    ];
+
+    let sin_lat = latitude.sin().max(-1.0 + 1.0e-12).min(1.0 - 1.0e-12);
+    let t = (sin_lat.atanh() - eccentricity * (eccentricity * sin_lat).atanh()).sinh();
+    let xi_prime = t.atan2(longitude.cos());
+    let eta_prime = (longitude.sin() / (1.0 + t * t).sqrt()).atanh();
+
+    let (mut xi, mut eta) = (xi_prime, eta_prime);
+    for (j, alpha_j) in alpha.iter().enumerate() {
+        let k = 2.0 * (j + 1) as f64;
+        xi += alpha_j * (k * xi_prime).sin() * (k * eta_prime).cosh();
+        eta += alpha_j * (k * xi_prime).cos() * (k * eta_prime).sinh();
+    }
+
+    Some(Point2{ x: rectifying_radius * eta, y: rectifying_radius * xi })
+}
+
+/// Inverse of `transverse_mercator`; returns (longitude, latitude).
+pub fn transverse_mercator_inverse(x: f64, y: f64, eccentricity: f64) -> (f64, f64) {
+    let (n, rectifying_radius) = third_flattening_and_rectifying_radius(eccentricity);
+    let beta = [
+        n / 2.0 - 2.0 / 3.0 * n.powi(2) + 37.0 / 96.0 * n.powi(3),
+        1.0 / 48.0 * n.powi(2) + 1.0 / 15.0 * n.powi(3),
+        17.0 / 480.0 * n.powi(3)
+    ];
+
+    let (xi, eta) = (y / rectifying_radius, x / rectifying_radius);
+    let (mut xi_prime, mut eta_prime) = (xi, eta);
+    for (j, beta_j) in beta.iter().enumerate() {
+        let k = 2.0 * (j + 1) as f64;
+        xi_prime -= beta_j * (k * xi).sin() * (k * eta).cosh();
+        eta_prime -= beta_j * (k * xi).cos() * (k * eta).sinh();
+    }
+
+    let longitude = eta_prime.sinh().atan2(xi_prime.cos());
+    // tangent of the conformal latitude
+    let t_prime = xi_prime.sin() / (eta_prime.sinh().powi(2) + xi_prime.cos().powi(2)).sqrt();
+
+    // Newton's iteration for the tangent of the geodetic latitude
+    let e2 = eccentricity.powi(2);
+    let mut t = t_prime;
+    for _ in 0..5 {
+        let sigma = (eccentricity * (eccentricity * t / (1.0 + t * t).sqrt()).atanh()).sinh();
+        let t_prime_i = t * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + t * t).sqrt();
+        t += (t_prime - t_prime_i) * (1.0 + (1.0 - e2) * t * t)
+            / ((1.0 - e2) * (1.0 + t * t).sqrt() * (1.0 + t_prime_i * t_prime_i).sqrt());
+    }
+
+    (longitude, t.atan())
+}
+
+/// Returns the cone constant n, Snyder's F and the radius of the origin's parallel of the Lambert conformal conic
+/// projection, or `None` if the cone degenerates to a cylinder.
+fn lambert_conformal_conic_constants(standard_parallels: [f64; 2], eccentricity: f64) -> Option<(f64, f64, f64)> {
+    let [lat1, lat2] = standard_parallels;
+    let (m1, m2) = (parallel_factor(lat1, eccentricity), parallel_factor(lat2, eccentricity));
+    let (t1, t2) = (conformal_t(lat1, eccentricity), conformal_t(lat2, eccentricity));
+
+    let n = if (lat1 - lat2).abs() < 1.0e-9 { lat1.sin() } else { (m1.ln() - m2.ln()) / (t1.ln() - t2.ln()) };
+    if n.abs() < 1.0e-9 { return None; }
+
+    let f = m1 / (n * t1.powf(n));
+    let rho0 = f * conformal_t((lat1 + lat2) / 2.0, eccentricity).powf(n);
+
+    Some((n, f, rho0))
+}
+
+/// The map's origin is at the latitude halfway between the standard parallels.
+fn lambert_conformal_conic(
+    longitude: f64,
+    latitude: f64,
+    standard_parallels: [f64; 2],
+    eccentricity: f64
+) -> Option<Point2<f64>> {
+    let (n, f, rho0) = lambert_conformal_conic_constants(standard_parallels, eccentricity)?;
+    if latitude * n.signum() < -LAMBERT_CONFORMAL_CONIC_MAX_LATITUDE { return None; }
+
+    let rho = f * conformal_t(latitude, eccentricity).powf(n);
+    let theta = n * longitude;
+
+    Some(Point2{ x: rho * theta.sin(), y: rho0 - rho * theta.cos() })
+}
+
+/// Inverse of `lambert_conformal_conic` (Snyder's (15-9) - (15-11)); returns (longitude, latitude).
+fn lambert_conformal_conic_inverse(
+    x: f64,
+    y: f64,
+    standard_parallels: [f64; 2],
+    eccentricity: f64
+) -> Option<(f64, f64)> {
+    let (n, f, rho0) = lambert_conformal_conic_constants(standard_parallels, eccentricity)?;
+
+    let sign = n.signum();
+    let rho = sign * (x * x + (rho0 - y).powi(2)).sqrt();
+    let theta = (sign * x).atan2(sign * (rho0 - y));
+    let t = (rho / f).powf(1.0 / n);
+
+    Some((theta / n, latitude_from_conformal_t(t, eccentricity)))
+}
+
+/// Returns the cone constant n, Snyder's C and the radius of the origin's parallel of the Albers equal-area conic
+/// projection, or `None` if the cone degenerates to a cylinder.
+fn albers_equal_area_constants(standard_parallels: [f64; 2], eccentricity: f64) -> Option<(f64, f64, f64)> {
+    let [lat1, lat2] = standard_parallels;
+    let (m1, m2) = (parallel_factor(lat1, eccentricity), parallel_factor(lat2, eccentricity));
+    let (q1, q2) = (authalic_q(lat1, eccentricity), authalic_q(lat2, eccentricity));
+
+    let n = if (lat1 - lat2).abs() < 1.0e-9 { lat1.sin() } else { (m1 * m1 - m2 * m2) / (q2 - q1) };
+    if n.abs() < 1.0e-9 { return None; }
+
+    let c = m1 * m1 + n * q1;
+    let rho0 = (c - n * authalic_q((lat1 + lat2) / 2.0, eccentricity)).max(0.0).sqrt() / n;
+
+    Some((n, c, rho0))
+}
+
+/// The map's origin is at the latitude halfway between the standard parallels.
+fn albers_equal_area(
+    longitude: f64,
+    latitude: f64,
+    standard_parallels: [f64; 2],
+    eccentricity: f64
+) -> Option<Point2<f64>> {
+    let (n, c, rho0) = albers_equal_area_constants(standard_parallels, eccentricity)?;
+
+    let rho = (c - n * authalic_q(latitude, eccentricity)).max(0.0).sqrt() / n;
+    let theta = n * longitude;
+
+    Some(Point2{ x: rho * theta.sin(), y: rho0 - rho * theta.cos() })
+}
+
+/// Inverse of `albers_equal_area` (Snyder's (14-8) - (14-11)); returns (longitude, latitude).
+fn albers_equal_area_inverse(
+    x: f64,
+    y: f64,
+    standard_parallels: [f64; 2],
+    eccentricity: f64
+) -> Option<(f64, f64)> {
+    let (n, c, rho0) = albers_equal_area_constants(standard_parallels, eccentricity)?;
+
+    let sign = n.signum();
+    let rho = sign * (x * x + (rho0 - y).powi(2)).sqrt();
+    let theta = (sign * x).atan2(sign * (rho0 - y));
+    let q = (c - (rho * n).powi(2)) / n;
+
+    Some((theta / n, latitude_from_authalic_q(q, eccentricity)?))
+}
+
+fn circle(radius: f64, num_segments: usize) -> Vec<[Point2<f64>; 2]> {
+    let point = |i: usize| {
+        let angle = 2.0 * std::f64::consts::PI * i as f64 / num_segments as f64;
+        Point2{ x: radius * angle.cos(), y: radius * angle.sin() }
+    };
+
+    (0..num_segments).map(|i| [point(i), point(i + 1)]).collect()
+}
+
+/// Half of the side of a face of the quadrilateralized spherical cube (in map coordinates).
+pub const QSC_FACE_HALF_SIZE: f64 = std::f64::consts::FRAC_PI_4;
+
+/// Centers of the quadrilateralized spherical cube's faces (in units of `QSC_FACE_HALF_SIZE`) laid out as a cross:
+/// front, right, back, left, top, bottom.
+pub const QSC_FACE_CENTERS: [[f64; 2]; 6] = [[0.0, 0.0], [2.0, 0.0], [4.0, 0.0], [-2.0, 0.0], [0.0, 2.0], [0.0, -2.0]];
+
+/// Complete elliptic integral of the first kind for k² = 1/2.
+const ELL_K: f64 = 1.854074677301372;
+
+/// Side of the Peirce quincuncial projection's square (in map coordinates).
+pub const PEIRCE_QUINCUNCIAL_SIDE: f64 = 2.0 * std::f64::consts::SQRT_2 * ELL_K;
+
+/// Elliptic integral of the first kind for k² = 1/2 (even Chebyshev series approximation).
+fn ell_int_5(phi: f64) -> f64 {
+    const C0: f64 = 2.19174570831038;
+    const C: [f64; 7] = [
+        -8.58691003636495e-07,
+        2.02692115653689e-07,
+        3.12960480765314e-05,
+        5.30394739921063e-05,
+        -0.0012804644680613,
+        -0.00575574836830288,
+        0.0914203033408211
+    ];
+
+    let y = phi * std::f64::consts::FRAC_2_PI;
+    let y = 2.0 * y * y - 1.0;
+    let y2 = 2.0 * y;
+    let mut d1 = 0.0;
+    let mut d2 = 0.0;
+    for c in C.iter() {
+        let temp = d1;
+        d1 = y2 * d1 - d2 + c;
+        d2 = temp;
+    }
+
+    phi * (y * d1 - d2 + 0.5 * C0)
+}
+
+fn aacos(x: f64) -> f64 { x.max(-1.0).min(1.0).acos() }
+
+fn aasin(x: f64) -> f64 { x.max(-1.0).min(1.0).asin() }
+
+fn conformal_square(a: f64, b: f64, sm: bool, sn: bool) -> Point2<f64> {
+    let m = aasin((1.0 + (a + b).cos().min(0.0)).sqrt());
+    let n = aasin((1.0 - (a - b).cos().max(0.0)).abs().sqrt());
+
+    Point2{
+        x: ell_int_5(if sm { -m } else { m }),
+        y: ell_int_5(if sn { -n } else { n })
+    }
+}
+
+/// Adams hemisphere-in-a-square projection; expects |lon| ⩽ π/2.
+fn adams_hemisphere(lon: f64, lat: f64) -> Point2<f64> {
+    let a = lat.cos() * lon.sin();
+    let xy = conformal_square(aacos(a), std::f64::consts::FRAC_PI_2 - lat, lat.sin() + a < 0.0, lat.sin() - a < 0.0);
+
+    // rotate by 45°
+    Point2{
+        x: std::f64::consts::FRAC_1_SQRT_2 * (xy.x - xy.y),
+        y: std::f64::consts::FRAC_1_SQRT_2 * (xy.x + xy.y)
+    }
+}
+
+/// Guyou projection of a hemisphere; expects |lon| ⩽ π/2.
+fn guyou_hemisphere(lon: f64, lat: f64) -> Point2<f64> {
+    if (lat.abs() - std::f64::consts::FRAC_PI_2).abs() < 1.0e-10 {
+        return Point2{ x: 0.0, y: lat.signum() * ELL_K };
+    }
+
+    let (sin_lon, sin_lat, cos_lat) = (lon.sin(), lat.sin(), lat.cos());
+
+    conformal_square(
+        aacos((cos_lat * sin_lon - sin_lat) * std::f64::consts::FRAC_1_SQRT_2),
+        aacos((cos_lat * sin_lon + sin_lat) * std::f64::consts::FRAC_1_SQRT_2),
+        lon < 0.0,
+        lat < 0.0
+    )
+}
+
+/// Peirce quincuncial projection: the front hemisphere is projected onto a square as by `guyou_hemisphere`;
+/// the rear one is reflected across the square's sides (each quarter across the nearest side), which is
+/// the conformal continuation of the map. The result is rotated by 45°, so that the map is an upright square.
+fn peirce_quincuncial(lon: f64, lat: f64) -> Point2<f64> {
+    use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, PI};
+
+    let xy = if lon.abs() <= FRAC_PI_2 {
+        guyou_hemisphere(lon, lat)
+    } else {
+        let mut mirrored = guyou_hemisphere(lon.signum() * PI - lon, lat);
+        if mirrored.x.abs() >= mirrored.y.abs() {
+            mirrored.x = mirrored.x.signum() * 2.0 * ELL_K - mirrored.x;
+        } else {
+            mirrored.y = mirrored.y.signum() * 2.0 * ELL_K - mirrored.y;
+        }
+        mirrored
+    };
+
+    Point2{ x: FRAC_1_SQRT_2 * (xy.x - xy.y), y: FRAC_1_SQRT_2 * (xy.x + xy.y) }
+}
+
+/// Loximuthal projection with central point at lon. 0°, lat. `lat1`.
+fn loximuthal(lon: f64, lat: f64, lat1: f64) -> Point2<f64> {
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+    const EPS: f64 = 1.0e-10;
+
+    let x = if (lat - lat1).abs() < EPS {
+        lon * lat1.cos()
+    } else {
+        // `x` tends to 0 at the poles, where the logarithm diverges
+        let lat = lat.max(-FRAC_PI_2 + EPS).min(FRAC_PI_2 - EPS);
+        lon * (lat - lat1) / ((FRAC_PI_4 + lat / 2.0).tan() / (FRAC_PI_4 + lat1 / 2.0).tan()).ln()
+    };
+
+    Point2{ x, y: lat - lat1 }
+}
+
+/// Inverse of `loximuthal`; returns (longitude, latitude).
+fn loximuthal_inverse(x: f64, y: f64, lat1: f64) -> Option<(f64, f64)> {
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+    const EPS: f64 = 1.0e-10;
+
+    let lat = y + lat1;
+    if lat.abs() > FRAC_PI_2 { return None; }
+
+    let lon = if y.abs() < EPS {
+        x / lat1.cos()
+    } else {
+        let lat = lat.max(-FRAC_PI_2 + EPS).min(FRAC_PI_2 - EPS);
+        x * ((FRAC_PI_4 + lat / 2.0).tan() / (FRAC_PI_4 + lat1 / 2.0).tan()).ln() / (lat - lat1)
+    };
+
+    Some((lon, lat))
+}
+
+/// Raisz armadillo projection (formulas of the "d3-geo-projection" library); does not check visibility.
+fn armadillo(lon: f64, lat: f64, parallel: f64) -> Point2<f64> {
+    let k = (1.0 + parallel.sin() - parallel.cos()) / 2.0;
+    let half_lon = lon / 2.0;
+
+    Point2{
+        x: (1.0 + lat.cos()) * half_lon.sin(),
+        y: k + lat.sin() * parallel.cos() - (1.0 + lat.cos()) * parallel.sin() * half_lon.cos()
+    }
+}
+
+/// Returns the latitude of the horizon of the armadillo projection's torus at the given longitude (towards the pole
+/// opposite to `parallel`'s hemisphere).
+fn armadillo_horizon_latitude(lon: f64, parallel: f64) -> f64 {
+    let s = if parallel >= 0.0 { 1.0 } else { -1.0 };
+    -s * (lon / 2.0).cos().atan2((s * parallel).tan())
+}
+
+fn armadillo_visible(lon: f64, lat: f64, parallel: f64) -> bool {
+    let s = if parallel >= 0.0 { 1.0 } else { -1.0 };
+    s * lat >= s * armadillo_horizon_latitude(lon, parallel)
+}
+
+/// Returns the armadillo projection's horizon as line segments.
+fn armadillo_horizon(parallel: f64) -> Vec<[Point2<f64>; 2]> {
+    use std::f64::consts::PI;
+
+    let point = |i: usize| {
+        let lon = -PI + 2.0 * PI * i as f64 / BOUNDARY_NUM_SEGMENTS as f64;
+        armadillo(lon, armadillo_horizon_latitude(lon, parallel), parallel)
+    };
+
+    (0..BOUNDARY_NUM_SEGMENTS).map(|i| [point(i), point(i + 1)]).collect()
+}
+
+/// Max. number of Newton's method iterations used to find the auxiliary angle of the Eckert projections.
+const ECKERT_MAX_ITERATIONS: usize = 20;
+
+/// Eckert IV projection (see J. P. Snyder, "Map Projections - A Working Manual" (1987)).
+fn eckert_iv(lon: f64, lat: f64) -> Point2<f64> {
+    use std::f64::consts::{FRAC_PI_2, PI};
+    const EPS: f64 = 1.0e-12;
+
+    // solve: θ + sin θ cos θ + 2 sin θ = (2 + π/2) sin φ
+    let target = (2.0 + FRAC_PI_2) * lat.sin();
+    let lat2 = lat * lat;
+    let mut theta = lat * (0.895168 + lat2 * (0.0218849 + lat2 * 0.00826809));
+    for _ in 0..ECKERT_MAX_ITERATIONS {
+        let cos_theta = theta.cos();
+        let derivative = 2.0 * cos_theta * (1.0 + cos_theta);
+        // the derivative vanishes at the poles
+        if derivative < EPS { break; }
+        let delta = (theta + theta.sin() * cos_theta + 2.0 * theta.sin() - target) / derivative;
+        theta = (theta - delta).max(-FRAC_PI_2).min(FRAC_PI_2);
+        if delta.abs() < EPS { break; }
+    }
+
+    Point2{
+        x: 2.0 / (PI * (4.0 + PI)).sqrt() * lon * (1.0 + theta.cos()),
+        y: 2.0 * (PI / (4.0 + PI)).sqrt() * theta.sin()
+    }
+}
+
+/// Inverse of `eckert_iv`; returns (longitude, latitude).
+fn eckert_iv_inverse(x: f64, y: f64) -> Option<(f64, f64)> {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    let sin_theta = y / (2.0 * (PI / (4.0 + PI)).sqrt());
+    if sin_theta.abs() > 1.0 { return None; }
+    let theta = sin_theta.asin();
+    let cos_theta = theta.cos();
+
+    Some((
+        x * (PI * (4.0 + PI)).sqrt() / (2.0 * (1.0 + cos_theta)),
+        aasin((theta + sin_theta * cos_theta + 2.0 * sin_theta) / (2.0 + FRAC_PI_2))
+    ))
+}
+
+/// Eckert VI projection (see J. P. Snyder, "Map Projections - A Working Manual" (1987)).
+fn eckert_vi(lon: f64, lat: f64) -> Point2<f64> {
+    use std::f64::consts::{FRAC_PI_2, PI};
+    const EPS: f64 = 1.0e-12;
+
+    // solve: θ + sin θ = (1 + π/2) sin φ
+    let target = (1.0 + FRAC_PI_2) * lat.sin();
+    let mut theta = lat;
+    for _ in 0..ECKERT_MAX_ITERATIONS {
+        let delta = (theta + theta.sin() - target) / (1.0 + theta.cos());
+        theta -= delta;
+        if delta.abs() < EPS { break; }
+    }
+
+    let k = 1.0 / (2.0 + PI).sqrt();
+    Point2{ x: k * lon * (1.0 + theta.cos()), y: 2.0 * k * theta }
+}
+
+/// Inverse of `eckert_vi`; returns (longitude, latitude).
+fn eckert_vi_inverse(x: f64, y: f64) -> Option<(f64, f64)> {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    let k = 1.0 / (2.0 + PI).sqrt();
+    let theta = y / (2.0 * k);
+    if theta.abs() > FRAC_PI_2 { return None; }
+
+    Some((x / (k * (1.0 + theta.cos())), aasin((theta + theta.sin()) / (1.0 + FRAC_PI_2))))
+}
+
+/// Hammer projection with the bounding ellipse's axes' ratio `axis_ratio` (equal-area for any ratio).
+fn hammer(lon: f64, lat: f64, axis_ratio: f64) -> Point2<f64> {
+    let d = (2.0 / (1.0 + lat.cos() * (lon / 2.0).cos())).sqrt();
+    let k = (axis_ratio / 2.0).sqrt();
+
+    Point2{ x: k * 2.0 * d * lat.cos() * (lon / 2.0).sin(), y: d * lat.sin() / k }
+}
+
+/// Inverse of `hammer` (Snyder's formulas for the Hammer-Aitoff projection, with the coordinates rescaled to the axis
+/// ratio of 2); returns (longitude, latitude).
+fn hammer_inverse(x: f64, y: f64, axis_ratio: f64) -> Option<(f64, f64)> {
+    let k = (axis_ratio / 2.0).sqrt();
+    let (x, y) = (x / k, y * k);
+
+    // outside of the bounding ellipse
+    let z2 = 1.0 - (x / 4.0).powi(2) - (y / 2.0).powi(2);
+    if z2 < 0.5 { return None; }
+    let z = z2.sqrt();
+
+    Some((2.0 * (z * x).atan2(2.0 * (2.0 * z2 - 1.0)), aasin(z * y)))
+}
+
+/// Mollweide projection.
+fn mollweide(lon: f64, lat: f64) -> Point2<f64> {
+    use std::f64::consts::{PI, SQRT_2};
+    const MAX_ITERATIONS: usize = 30;
+    const EPS: f64 = 1.0e-12;
+
+    // solve: 2θ + sin 2θ = π sin φ (for 2θ)
+    let target = PI * lat.sin();
+    let mut theta2 = lat;
+    for _ in 0..MAX_ITERATIONS {
+        let derivative = 1.0 + theta2.cos();
+        // the derivative vanishes at the poles
+        if derivative < EPS { break; }
+        let delta = (theta2 + theta2.sin() - target) / derivative;
+        theta2 = (theta2 - delta).max(-PI).min(PI);
+        if delta.abs() < EPS { break; }
+    }
+    let theta = theta2 / 2.0;
+
+    Point2{ x: 2.0 * SQRT_2 / PI * lon * theta.cos(), y: SQRT_2 * theta.sin() }
+}
+
+/// Inverse of the Littrow projection (see `Projection::project`; x = sin λ / cos φ, y = tan φ cos λ);
+/// returns (longitude, latitude) in the hemisphere centered at lon. 0°, lat. 0°.
+fn littrow_inverse(x: f64, y: f64) -> Option<(f64, f64)> {
+    // sin² φ is the positive root of x² u² + (1 + y² - x²) u - y² = 0
+    let b = 1.0 + y * y - x * x;
+    let denominator = b + (b * b + 4.0 * x * x * y * y).sqrt();
+    if denominator <= 0.0 { return None; }
+    let latitude = aasin((2.0 * y * y / denominator).sqrt()).copysign(y);
+    let sin_lon = x * latitude.cos();
+    if sin_lon.abs() > 1.0 { return None; }
+
+    Some((sin_lon.asin(), latitude))
+}
+
+/// Inverse of `mollweide`; returns (longitude, latitude).
+fn mollweide_inverse(x: f64, y: f64) -> Option<(f64, f64)> {
+    use std::f64::consts::{PI, SQRT_2};
+
+    let sin_theta = y / SQRT_2;
+    if sin_theta.abs() > 1.0 { return None; }
+    let theta = sin_theta.asin();
+    let cos_theta = theta.cos();
+    let lon = if cos_theta < 1.0e-12 { 0.0 } else { PI * x / (2.0 * SQRT_2 * cos_theta) };
+
+    Some((lon, aasin((2.0 * theta + (2.0 * theta).sin()) / PI)))
+}
+
+/// Nicolosi globular projection of the hemisphere centered at lon. 0°, lat. 0°.
+fn nicolosi(lon: f64, lat: f64) -> Point2<f64> {
+    const EPS: f64 = 1.0e-10;
+    use std::f64::consts::FRAC_PI_2;
+
+    if lon.abs() < EPS {
+        return Point2{ x: 0.0, y: lat };
+    } else if lat.abs() < EPS {
+        return Point2{ x: lon, y: 0.0 };
+    } else if (lon.abs() - FRAC_PI_2).abs() < EPS {
+        return Point2{ x: lon * lat.cos(), y: FRAC_PI_2 * lat.sin() };
+    } else if (lat.abs() - FRAC_PI_2).abs() < EPS {
+        return Point2{ x: 0.0, y: lat };
+    }
+
+    let b = FRAC_PI_2 / lon - lon / FRAC_PI_2;
+    let c = lat / FRAC_PI_2;
+    let d = (1.0 - c * c) / (lat.sin() - c);
+    let b2_d2 = (b / d).powi(2);
+    let m = (b * lat.sin() / d - 0.5 * b) / (1.0 + b2_d2);
+    let n = (lat.sin() / b2_d2 + 0.5 * d) / (1.0 + 1.0 / b2_d2);
+
+    let x = (m * m + lat.cos().powi(2) / (1.0 + b2_d2)).sqrt();
+    let y = (n * n - (lat.sin().powi(2) / b2_d2 + d * lat.sin() - 1.0) / (1.0 + 1.0 / b2_d2)).max(0.0).sqrt();
+
+    Point2{
+        x: FRAC_PI_2 * (m + lon.signum() * x),
+        y: FRAC_PI_2 * (n - lat.signum() * y)
+    }
+}
+
+/// Quadrilateralized spherical cube with faces laid out as a cross (see `QSC_FACE_CENTERS`).
+fn qsc(position: Vector3<f64>) -> Point2<f64> {
+    let p = position;
+    let (ax, ay, az) = (p.x.abs(), p.y.abs(), p.z.abs());
+
+    // arguments of `qsc_face`: coordinates along the face's horizontal and vertical axes and along its normal
+    let (face, xy) = if az >= ax && az >= ay {
+        if p.z > 0.0 { (4, qsc_face(p.y, -p.x, p.z)) } else { (5, qsc_face(p.y, p.x, -p.z)) }
+    } else if ax >= ay {
+        if p.x > 0.0 { (0, qsc_face(p.y, p.z, p.x)) } else { (2, qsc_face(-p.y, p.z, -p.x)) }
+    } else if p.y > 0.0 {
+        (1, qsc_face(-p.x, p.z, p.y))
+    } else {
+        (3, qsc_face(p.x, p.z, -p.y))
+    };
+
+    Point2{
+        x: QSC_FACE_HALF_SIZE * (QSC_FACE_CENTERS[face][0] + xy.x),
+        y: QSC_FACE_HALF_SIZE * (QSC_FACE_CENTERS[face][1] + xy.y)
+    }
+}
+
+/// Projects a point of the unit globe onto a face of the quadrilateralized spherical cube (COBE variant, see
+/// F. K. Chan, E. M. O'Neill, "Feasibility Study of a Quadrilateralized Spherical Cube Earth Data Base", 1975).
+///
+/// `a`, `b`: coordinates along the face's horizontal and vertical axes, `c`: coordinate along the face's normal.
+/// Returns coordinates within [-1, 1] × [-1, 1].
+///
+fn qsc_face(a: f64, b: f64, c: f64) -> Point2<f64> {
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    let phi = aacos(c);
+    if phi < 1.0e-10 { return Point2{ x: 0.0, y: 0.0 }; }
+
+    // by symmetry, reduce the azimuth to the area |theta| ⩽ π/4
+    let mut theta = b.atan2(a);
+    let area_rotation = if theta.abs() <= FRAC_PI_4 {
+        0.0
+    } else if theta > FRAC_PI_4 && theta <= 3.0 * FRAC_PI_4 {
+        FRAC_PI_2
+    } else if theta > 3.0 * FRAC_PI_4 || theta <= -3.0 * FRAC_PI_4 {
+        PI
+    } else {
+        -FRAC_PI_2
+    };
+    theta -= area_rotation;
+    if theta < -PI { theta += 2.0 * PI; }
+
+    let mu = ((12.0 / PI) * (theta + aacos(theta.sin() * FRAC_PI_4.cos()) - FRAC_PI_2)).atan();
+    let t = ((1.0 - phi.cos()) / mu.cos().powi(2) / (1.0 - (1.0 / theta.cos()).atan().cos())).sqrt();
+
+    let mu = mu + area_rotation;
+
+    Point2{ x: t * mu.cos(), y: t * mu.sin() }
+}
+
+/// Inverse of `qsc`; returns `None` if `point` lies outside of the cube's faces.
+fn qsc_inverse(point: Point2<f64>) -> Option<Vector3<f64>> {
+    let (x, y) = (point.x / QSC_FACE_HALF_SIZE, point.y / QSC_FACE_HALF_SIZE);
+    let face = QSC_FACE_CENTERS.iter().position(|center| (x - center[0]).abs() <= 1.0 && (y - center[1]).abs() <= 1.0)?;
+    let [a, b, c] = qsc_face_inverse(x - QSC_FACE_CENTERS[face][0], y - QSC_FACE_CENTERS[face][1]);
+
+    // inverse of the face's axes chosen by `qsc`
+    Some(match face {
+        0 => Vector3::new(c, a, b),
+        1 => Vector3::new(-a, c, b),
+        2 => Vector3::new(-c, -a, b),
+        3 => Vector3::new(a, -c, b),
+        4 => Vector3::new(-b, a, c),
+        _ => Vector3::new(b, a, -c)
+    })
+}
+
+/// Inverse of `qsc_face` (formulas of PROJ's "qsc" projection); returns [a, b, c].
+fn qsc_face_inverse(x: f64, y: f64) -> [f64; 3] {
+    use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, PI};
+
+    let t = (x * x + y * y).sqrt();
+    if t < 1.0e-12 { return [0.0, 0.0, 1.0]; }
+
+    // by symmetry, reduce the azimuth to the area |mu| ⩽ π/4
+    let mu = y.atan2(x);
+    let area_rotation = if x >= y.abs() {
+        0.0
+    } else if y >= x.abs() {
+        FRAC_PI_2
+    } else if -x >= y.abs() {
+        PI
+    } else {
+        -FRAC_PI_2
+    };
+    let mut mu = mu - area_rotation;
+    if mu < -PI { mu += 2.0 * PI; } else if mu > PI { mu -= 2.0 * PI; }
+
+    let s = (PI / 12.0) * mu.tan();
+    let theta = (s.sin() / (s.cos() - FRAC_1_SQRT_2)).atan();
+    let cos_phi = (1.0 - (mu.cos() * t).powi(2) * (1.0 - (1.0 / theta.cos()).atan().cos())).max(-1.0).min(1.0);
+    let sin_phi = (1.0 - cos_phi * cos_phi).sqrt();
+
+    let theta = theta + area_rotation;
+
+    [sin_phi * theta.cos(), sin_phi * theta.sin(), cos_phi]
+}
+
+/// Returns position on the unit globe corresponding to the given longitude and latitude.
+pub fn lonlat_to_xyz(longitude: cgmath::Rad<f64>, latitude: cgmath::Rad<f64>) -> Vector3<f64> {
+    Vector3{
+        x: longitude.0.cos() * latitude.0.cos(),
+        y: longitude.0.sin() * latitude.0.cos(),
+        z: latitude.0.sin()
+    }
+}
+
+/// Returns (longitude, latitude) of a point on the unit globe.
+pub fn xyz_to_lonlat(position: Vector3<f64>) -> (cgmath::Rad<f64>, cgmath::Rad<f64>) {
+    (cgmath::Rad(position.y.atan2(position.x)), cgmath::Rad(position.z.max(-1.0).min(1.0).asin()))
+}
+
+/// Distortion characteristics at a point of the map (see Tissot's indicatrix).
+#[derive(Copy, Clone, Debug)]
+pub struct Distortion {
+    /// Scale factor along the meridian.
+    pub h: f64,
+    /// Scale factor along the parallel.
+    pub k: f64,
+    /// Areal scale factor.
+    pub area_scale: f64,
+    /// Maximum angular deformation.
+    pub angular_deformation: cgmath::Rad<f64>
+}
+
+/// Returns (longitude, latitude) in degrees for which `project` (taking longitude and latitude in degrees) gives
+/// `position`, or `None` if there is no such point.
+///
+/// The closest projected point of a coarse grid is refined with Newton's method.
+///
+pub fn invert_numerically<F: Fn(f64, f64) -> Option<Point2<f64>>>(
+    project: F,
+    position: Point2<f64>
+) -> Option<(f64, f64)> {
+    let mut closest: Option<(f64, f64, f64)> = None;
+    let mut latitude = -90.0 + INVERSE_GRID_STEP / 2.0;
+    while latitude < 90.0 {
+        let mut longitude = -180.0 + INVERSE_GRID_STEP / 2.0;
+        while longitude < 180.0 {
+            if let Some(p) = project(longitude, latitude) {
+                let distance = (p - position).magnitude();
+                if closest.map_or(true, |(_, _, min_distance)| distance < min_distance) {
+                    closest = Some((longitude, latitude, distance));
+                }
+            }
+            longitude += INVERSE_GRID_STEP;
+        }
+        latitude += INVERSE_GRID_STEP;
+    }
+    let (mut longitude, mut latitude, _) = closest?;
+
+    const H: f64 = 1.0e-5;
+    for _ in 0..INVERSE_MAX_ITERATIONS {
+        let p = project(longitude, latitude)?;
+        let error = position - p;
+        if error.magnitude() < INVERSE_TOLERANCE {
+            let longitude = (longitude + 180.0).rem_euclid(360.0) - 180.0;
+            return Some((longitude, latitude));
+        }
+
+        // numerical Jacobian
+        let d_lon = (project(longitude + H, latitude)? - p) / H;
+        let d_lat = (project(longitude, latitude + H)? - p) / H;
+        let determinant = d_lon.x * d_lat.y - d_lat.x * d_lon.y;
+        if determinant.abs() < 1.0e-12 { return None; }
+
+        // steps are limited to the grid spacing to stay in the vicinity of the starting point
+        let step_lon = (error.x * d_lat.y - d_lat.x * error.y) / determinant;
+        let step_lat = (d_lon.x * error.y - error.x * d_lon.y) / determinant;
+        let max_step = INVERSE_GRID_STEP;
+        longitude += step_lon.max(-max_step).min(max_step);
+        latitude = (latitude + step_lat.max(-max_step).min(max_step)).max(-90.0).min(90.0);
+    }
+
+    None
+}
+
+/// Calculates distortion of the normal aspect of `projection` at the given point.
+///
+/// Returns `None` if the point or its immediate neighborhood is not rendered by the projection.
+///
+pub fn distortion(
+    projection: Projection,
+    longitude: cgmath::Rad<f64>,
+    latitude: cgmath::Rad<f64>
+) -> Option<Distortion> {
+    let project = |lon: f64, lat: f64| projection.project(lonlat_to_xyz(cgmath::Rad(lon), cgmath::Rad(lat)));
+
+    let (lon, lat) = (longitude.0, latitude.0);
+    let cos_lat = lat.cos();
+    if cos_lat <= DIFF_STEP { return None; }
+
+    let n = project(lon, lat + DIFF_STEP)?;
+    let s = project(lon, lat - DIFF_STEP)?;
+    let e = project(lon + DIFF_STEP, lat)?;
+    let w = project(lon - DIFF_STEP, lat)?;
+
+    let dx_dlat = (n.x - s.x) / (2.0 * DIFF_STEP);
+    let dy_dlat = (n.y - s.y) / (2.0 * DIFF_STEP);
+    let dx_dlon = (e.x - w.x) / (2.0 * DIFF_STEP);
+    let dy_dlon = (e.y - w.y) / (2.0 * DIFF_STEP);
+
+    let h = (dx_dlat.powi(2) + dy_dlat.powi(2)).sqrt();
+    let k = (dx_dlon.powi(2) + dy_dlon.powi(2)).sqrt() / cos_lat;
+    let area_scale = (dx_dlat * dy_dlon - dx_dlon * dy_dlat).abs() / cos_lat;
+
+    // semi-axes `a` and `b` of Tissot's indicatrix
+    let a_plus_b = (h.powi(2) + k.powi(2) + 2.0 * area_scale).max(0.0).sqrt();
+    let a_minus_b = (h.powi(2) + k.powi(2) - 2.0 * area_scale).max(0.0).sqrt();
+    let angular_deformation = if a_plus_b > 0.0 {
+        cgmath::Rad(2.0 * (a_minus_b / a_plus_b).min(1.0).asin())
+    } else {
+        cgmath::Rad(0.0)
+    };
+
+    Some(Distortion{ h, k, area_scale, angular_deformation })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Max. distance (on the unit globe) between a point and its inverse projection; the ellipsoidal transverse
+    /// Mercator series are accurate to about 1e-8 at 60° from the central meridian.
+    const ROUND_TRIP_TOLERANCE: f64 = 1.0e-7;
+
+    #[test]
+    fn inverse_reverses_project() {
+        let ellipsoidal = [
+            Projection::AlbersEqualArea{
+                standard_parallels: [cgmath::Rad(0.5759586531581288), cgmath::Rad(0.7853981633974483)],
+                eccentricity: WGS84_ECCENTRICITY
+            },
+            Projection::LambertConformalConic{
+                standard_parallels: [cgmath::Rad(0.5759586531581288), cgmath::Rad(0.7853981633974483)],
+                eccentricity: WGS84_ECCENTRICITY
+            },
+            Projection::Mercator{ eccentricity: WGS84_ECCENTRICITY },
+            Projection::TransverseMercator{ eccentricity: WGS84_ECCENTRICITY }
+        ];
+
+        for projection in Projection::ALL.iter().chain(ellipsoidal.iter()).filter(|p| p.has_inverse()) {
+            // points of the front hemisphere, so that they are shown by all projections
+            for longitude in (-60..=60).step_by(15) {
+                for latitude in (-60..=60).step_by(15) {
+                    let position = lonlat_to_xyz(
+                        cgmath::Deg(longitude as f64).into(),
+                        cgmath::Deg(latitude as f64).into()
+                    );
+                    let point = match projection.project(position) {
+                        Some(point) => point,
+                        None => continue
+                    };
+                    let inverse = projection.inverse(point).unwrap_or_else(
+                        || panic!("{:?}: no inverse of ({}°, {}°)", projection, longitude, latitude)
+                    );
+                    assert!(
+                        (inverse - position).magnitude() < ROUND_TRIP_TOLERANCE,
+                        "{:?}: ({}°, {}°) mapped back to {:?}",
+                        projection, longitude, latitude, xyz_to_lonlat(inverse)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unproject_outside_of_map_fails() {
+        assert!(Projection::Orthographic.inverse(Point2{ x: 1.5, y: 0.0 }).is_none());
+        assert!(Projection::Mollweide.inverse(Point2{ x: 0.0, y: 2.0 }).is_none());
+    }
+}
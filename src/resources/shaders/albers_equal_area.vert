@@ -0,0 +1,101 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+// 1 to use the WGS84 ellipsoid, 0 to use a sphere
+uniform float ellipsoidal;
+
+// parallels along which the scale is true (radians)
+uniform float standard_parallel_1;
+uniform float standard_parallel_2;
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+} vs_out;
+
+// has to equal `DISCARD` in "*.geom"
+const vec4 DISCARD = vec4(1.0e+9, 1.0e+9, 1.0e+9, 1.0e+9);
+
+const float WGS84_ECCENTRICITY = 0.0818191908426215;
+
+const float EPS = 1.0e-6;
+
+float parallel_factor(float lat, float e)
+{
+    float e_sin = e * sin(lat);
+    return cos(lat) / sqrt(1 - e_sin * e_sin);
+}
+
+float authalic_q(float lat, float e)
+{
+    float sin_lat = sin(lat);
+    if (e == 0)
+    {
+        return 2 * sin_lat;
+    }
+
+    float e_sin = e * sin_lat;
+    return (1 - e * e) * (sin_lat / (1 - e_sin * e_sin) - 1 / (2 * e) * log((1 - e_sin) / (1 + e_sin)));
+}
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    float lon = atan(position.y, position.x);
+    float lat = asin(clamp(position.z, -1.0, 1.0));
+
+    float e = ellipsoidal * WGS84_ECCENTRICITY;
+    float m1 = parallel_factor(standard_parallel_1, e);
+    float m2 = parallel_factor(standard_parallel_2, e);
+    float q1 = authalic_q(standard_parallel_1, e);
+    float q2 = authalic_q(standard_parallel_2, e);
+
+    float n;
+    if (abs(standard_parallel_1 - standard_parallel_2) < EPS)
+    {
+        n = sin(standard_parallel_1);
+    }
+    else
+    {
+        n = (m1 * m1 - m2 * m2) / (q2 - q1);
+    }
+
+    if (abs(n) < EPS)
+    {
+        gl_Position = DISCARD;
+        return;
+    }
+
+    float c = m1 * m1 + n * q1;
+    float rho = sqrt(max(c - n * authalic_q(lat, e), 0)) / n;
+    // the map's origin is at the latitude halfway between the standard parallels
+    float rho0 = sqrt(max(c - n * authalic_q((standard_parallel_1 + standard_parallel_2) / 2, e), 0)) / n;
+    float theta = n * lon;
+
+    vec2 projected = vec2(rho * sin(theta), rho0 - rho * cos(theta));
+
+    gl_Position = vec4(zoom / wh_ratio * projected.x, zoom * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+}
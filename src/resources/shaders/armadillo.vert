@@ -0,0 +1,66 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Raisz armadillo (orthoapsidal) projection: the globe is mapped onto a torus, which is then projected
+// orthographically while tilted towards the viewer. Points on the far side of the torus are hidden.
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+// parallel shown as the torus' outer rim (radians)
+uniform float parallel;
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+} vs_out;
+
+// has to equal `DISCARD` in "*.geom"
+const vec4 DISCARD = vec4(1.0e+9, 1.0e+9, 1.0e+9, 1.0e+9);
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    float lon = atan(position.y, position.x);
+    float lat = asin(clamp(position.z, -1.0, 1.0));
+
+    float s = parallel >= 0.0 ? 1.0 : -1.0;
+    float half_lon = lon / 2;
+
+    // horizon of the tilted torus
+    if (s * lat < -atan(cos(half_lon), tan(s * parallel)))
+    {
+        gl_Position = DISCARD;
+        return;
+    }
+
+    float k = (1 + sin(parallel) - cos(parallel)) / 2;
+    vec2 projected = vec2(
+        (1 + cos(lat)) * sin(half_lon),
+        k + sin(lat) * cos(parallel) - (1 + cos(lat)) * sin(parallel) * cos(half_lon)
+    );
+
+    gl_Position = vec4(zoom / wh_ratio * projected.x, zoom * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+}
@@ -0,0 +1,172 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Conformal projections onto a square. One of the following is defined when creating the program:
+// ADAMS_HEMISPHERE, GUYOU, PEIRCE_QUINCUNCIAL.
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+} vs_out;
+
+// has to equal `DISCARD` in "*.geom"
+const vec4 DISCARD = vec4(1.0e+9, 1.0e+9, 1.0e+9, 1.0e+9);
+
+const float PI = 3.14159265;
+const float HALF_PI = 1.57079633;
+const float RSQRT2 = 0.70710678;
+const float EPS = 1.0e-5;
+
+// complete elliptic integral of the first kind for k² = 1/2; equals half the side of the hemisphere's square
+const float K = 1.85407468;
+
+float aacos(float x) { return acos(clamp(x, -1.0, 1.0)); }
+
+float aasin(float x) { return asin(clamp(x, -1.0, 1.0)); }
+
+// Elliptic integral of the first kind for k² = 1/2 (even Chebyshev series approximation).
+float ell_int_5(float phi)
+{
+    const float C0 = 2.19174570831038;
+    const float C[7] = float[7](
+        -8.58691003636495e-07,
+        2.02692115653689e-07,
+        3.12960480765314e-05,
+        5.30394739921063e-05,
+        -0.0012804644680613,
+        -0.00575574836830288,
+        0.0914203033408211
+    );
+
+    float y = phi / HALF_PI;
+    y = 2.0 * y * y - 1.0;
+    float y2 = 2.0 * y;
+    float d1 = 0.0;
+    float d2 = 0.0;
+    for (int i = 0; i < 7; ++i)
+    {
+        float temp = d1;
+        d1 = y2 * d1 - d2 + C[i];
+        d2 = temp;
+    }
+
+    return phi * (y * d1 - d2 + 0.5 * C0);
+}
+
+vec2 conformal_square(float a, float b, bool sm, bool sn)
+{
+    float m = aasin(sqrt(1.0 + min(0.0, cos(a + b))));
+    if (sm) { m = -m; }
+
+    float n = aasin(sqrt(abs(1.0 - max(0.0, cos(a - b)))));
+    if (sn) { n = -n; }
+
+    return vec2(ell_int_5(m), ell_int_5(n));
+}
+
+// Expects |lon| ⩽ π/2.
+vec2 adams_hemisphere(float lon, float lat)
+{
+    float sin_lat = sin(lat);
+    float a = cos(lat) * sin(lon);
+    vec2 xy = conformal_square(aacos(a), HALF_PI - lat, (sin_lat + a) < 0.0, (sin_lat - a) < 0.0);
+
+    // rotate by 45°
+    return RSQRT2 * vec2(xy.x - xy.y, xy.x + xy.y);
+}
+
+// Expects |lon| ⩽ π/2.
+vec2 guyou_hemisphere(float lon, float lat)
+{
+    if (abs(abs(lat) - HALF_PI) < EPS)
+    {
+        return vec2(0.0, sign(lat) * K);
+    }
+
+    float sin_lon = sin(lon);
+    float sin_lat = sin(lat);
+    float cos_lat = cos(lat);
+
+    return conformal_square(
+        aacos((cos_lat * sin_lon - sin_lat) * RSQRT2),
+        aacos((cos_lat * sin_lon + sin_lat) * RSQRT2),
+        lon < 0.0,
+        lat < 0.0
+    );
+}
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    float lon = atan(position.y, position.x);
+    float lat = asin(clamp(position.z, -1.0, 1.0));
+
+#if defined(ADAMS_HEMISPHERE)
+    if (position.x < 0)
+    {
+        gl_Position = DISCARD;
+        return;
+    }
+    vec2 projected = adams_hemisphere(lon, lat);
+#elif defined(GUYOU)
+    vec2 projected;
+    if (position.x >= 0)
+    {
+        projected = guyou_hemisphere(lon, lat);
+    }
+    else
+    {
+        // the rear hemisphere is the mirror image of the front one, reflected across the square's side
+        vec2 mirrored = guyou_hemisphere(sign(lon) * PI - lon, lat);
+        projected = vec2(sign(lon) * 2.0 * K - mirrored.x, mirrored.y);
+    }
+#elif defined(PEIRCE_QUINCUNCIAL)
+    vec2 xy;
+    if (position.x >= 0)
+    {
+        xy = guyou_hemisphere(lon, lat);
+    }
+    else
+    {
+        // each quarter of the rear hemisphere is reflected across the nearest side of the front hemisphere's square
+        xy = guyou_hemisphere(sign(lon) * PI - lon, lat);
+        if (abs(xy.x) >= abs(xy.y))
+        {
+            xy.x = sign(xy.x) * 2.0 * K - xy.x;
+        }
+        else
+        {
+            xy.y = sign(xy.y) * 2.0 * K - xy.y;
+        }
+    }
+    // rotate by 45°
+    vec2 projected = RSQRT2 * vec2(xy.x - xy.y, xy.x + xy.y);
+#endif
+
+    gl_Position = vec4(zoom / wh_ratio * projected.x, zoom * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+}
@@ -11,11 +11,22 @@
 uniform mat3 globe_orientation;
 uniform float zoom;
 uniform float wh_ratio;
+// horizontal and vertical stretch of the map
+uniform vec2 stretch;
+
+// parallel (and its opposite) along which the scale is true (radians)
+uniform float standard_parallel;
 
 in vec2 lonlat_position;
 out VS_OUT
 {
     vec2 tex_coord;
+#if defined(LOBES)
+    // rotated longitude and latitude (radians)
+    vec2 lonlat;
+    // map's x coordinate divided by longitude (depends only on latitude)
+    float x_per_lon;
+#endif
 } vs_out;
 
 // has to equal `DISCARD` in "*.geom"
@@ -45,7 +56,14 @@ void main()
             sign(position.y) * PI - asin(position.y / r);
     }
 
-    gl_Position = vec4(zoom / wh_ratio * angle, zoom * position.z, 0, 1);
+    float cos_sp = cos(standard_parallel);
+    vec2 projected = vec2(angle * cos_sp, position.z / cos_sp);
+
+    gl_Position = vec4(zoom / wh_ratio * stretch.x * projected.x, zoom * stretch.y * projected.y, 0, 1);
 
     vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+#if defined(LOBES)
+    vs_out.lonlat = vec2(angle, asin(clamp(position.z, -1.0, 1.0)));
+    vs_out.x_per_lon = cos_sp;
+#endif
 }
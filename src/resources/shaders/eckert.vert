@@ -0,0 +1,113 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Eckert IV projection, or Eckert VI if ECKERT_VI is defined when creating the program. The auxiliary angle theta
+// is found with Newton's method (see J. P. Snyder, "Map Projections - A Working Manual" (1987)).
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+#if defined(LOBES)
+    // rotated longitude and latitude (radians)
+    vec2 lonlat;
+    // map's x coordinate divided by longitude (depends only on latitude)
+    float x_per_lon;
+#endif
+} vs_out;
+
+const float HALF_PI = 1.570796;
+
+const int MAX_ITERATIONS = 8;
+
+const float EPS = 1.0e-6;
+
+#if defined(ECKERT_VI)
+// 1 / sqrt(2 + pi)
+const float X_FACTOR = 0.441013;
+// 2 / sqrt(2 + pi)
+const float Y_FACTOR = 0.882026;
+#else
+// 2 / sqrt(pi * (4 + pi))
+const float X_FACTOR = 0.422238;
+// 2 * sqrt(pi / (4 + pi))
+const float Y_FACTOR = 1.326500;
+#endif
+
+// Returns the auxiliary angle for the given latitude.
+float theta(float lat)
+{
+#if defined(ECKERT_VI)
+    // solve: theta + sin(theta) = (1 + pi/2) * sin(lat)
+    float target = (1 + HALF_PI) * sin(lat);
+    float th = lat;
+    for (int i = 0; i < MAX_ITERATIONS; i++)
+    {
+        float delta = (th + sin(th) - target) / (1 + cos(th));
+        th -= delta;
+        if (abs(delta) < EPS) { break; }
+    }
+    return th;
+#else
+    // solve: theta + sin(theta) * cos(theta) + 2 * sin(theta) = (2 + pi/2) * sin(lat)
+    float target = (2 + HALF_PI) * sin(lat);
+    float lat2 = lat * lat;
+    float th = lat * (0.895168 + lat2 * (0.0218849 + lat2 * 0.00826809));
+    for (int i = 0; i < MAX_ITERATIONS; i++)
+    {
+        float cos_th = cos(th);
+        float derivative = 2 * cos_th * (1 + cos_th);
+        // the derivative vanishes at the poles
+        if (derivative < EPS) { break; }
+        float delta = (th + sin(th) * cos_th + 2 * sin(th) - target) / derivative;
+        th = clamp(th - delta, -HALF_PI, HALF_PI);
+        if (abs(delta) < EPS) { break; }
+    }
+    return th;
+#endif
+}
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    float lon = atan(position.y, position.x);
+    float lat = asin(clamp(position.z, -1.0, 1.0));
+
+    float th = theta(lat);
+    float x_per_lon = X_FACTOR * (1 + cos(th));
+#if defined(ECKERT_VI)
+    vec2 projected = vec2(lon * x_per_lon, Y_FACTOR * th);
+#else
+    vec2 projected = vec2(lon * x_per_lon, Y_FACTOR * sin(th));
+#endif
+
+    gl_Position = vec4(zoom / wh_ratio * projected.x, zoom * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+#if defined(LOBES)
+    vs_out.lonlat = vec2(lon, lat);
+    vs_out.x_per_lon = x_per_lon;
+#endif
+}
@@ -0,0 +1,97 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Mollweide projection, or Hammer if HAMMER is defined when creating the program. The globe is rotated (oblique
+// aspect) before projecting.
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+#if defined(HAMMER)
+// ratio of the bounding ellipse's axes (2 for the Hammer projection)
+uniform float axis_ratio;
+#endif
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+#if defined(LOBES)
+    // rotated longitude and latitude (radians)
+    vec2 lonlat;
+    // map's x coordinate divided by longitude (depends only on latitude)
+    float x_per_lon;
+#endif
+} vs_out;
+
+const float PI = 3.141593;
+
+const float SQRT_2 = 1.414214;
+
+#if !defined(HAMMER)
+const int MAX_ITERATIONS = 10;
+
+const float EPS = 1.0e-6;
+
+// Returns the auxiliary angle theta for the given latitude (solves: 2 theta + sin(2 theta) = pi * sin(lat)).
+float theta(float lat)
+{
+    float target = PI * sin(lat);
+    float th2 = lat;
+    for (int i = 0; i < MAX_ITERATIONS; i++)
+    {
+        float derivative = 1 + cos(th2);
+        // the derivative vanishes at the poles
+        if (derivative < EPS) { break; }
+        float delta = (th2 + sin(th2) - target) / derivative;
+        th2 = clamp(th2 - delta, -PI, PI);
+        if (abs(delta) < EPS) { break; }
+    }
+    return th2 / 2;
+}
+#endif
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    float lon = atan(position.y, position.x);
+    float lat = asin(clamp(position.z, -1.0, 1.0));
+
+#if defined(HAMMER)
+    float d = sqrt(2 / (1 + cos(lat) * cos(lon / 2)));
+    // keeps the map equal-area for any `axis_ratio`
+    float k = sqrt(axis_ratio / 2);
+    vec2 projected = vec2(k * 2 * d * cos(lat) * sin(lon / 2), d * sin(lat) / k);
+#else
+    float th = theta(lat);
+    float x_per_lon = 2 * SQRT_2 / PI * cos(th);
+    vec2 projected = vec2(lon * x_per_lon, SQRT_2 * sin(th));
+#endif
+
+    gl_Position = vec4(zoom / wh_ratio * projected.x, zoom * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+#if defined(LOBES)
+    vs_out.lonlat = vec2(lon, lat);
+    vs_out.x_per_lon = x_per_lon;
+#endif
+}
@@ -0,0 +1,69 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+// observer's altitude above the surface and the globe's radius (km)
+uniform float altitude;
+uniform float mean_radius;
+// tilt of the observer's line of sight towards the top of the view (radians)
+uniform float tilt;
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+} vs_out;
+
+// has to equal `DISCARD` in "*.geom"
+const vec4 DISCARD = vec4(1.0e+9, 1.0e+9, 1.0e+9, 1.0e+9);
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    // observer's distance from the globe's center (in globe radii)
+    float observer_distance = 1.0 + altitude / mean_radius;
+
+    // near-side visibility test: point is beyond the horizon
+    if (position.x < 1.0 / observer_distance)
+    {
+        gl_Position = DISCARD;
+        return;
+    }
+
+    float k = (observer_distance - 1.0) / (observer_distance - position.x);
+    vec2 vertical = k * position.yz;
+
+    float height = observer_distance - 1.0;
+    float a = vertical.y * sin(tilt) / height + cos(tilt);
+    if (a <= 0)
+    {
+        // behind the observer's image plane
+        gl_Position = DISCARD;
+        return;
+    }
+
+    vec2 projected = vec2(vertical.x * cos(tilt) / a, vertical.y / a);
+
+    gl_Position = vec4(zoom / wh_ratio * projected.x, zoom * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+}
@@ -16,7 +16,134 @@ out vec4 output_color;
 
 uniform sampler2D source_texture;
 
+// if true, `night_texture` (night lights) is shown instead of `source_texture` on the night side,
+// blended across the terminator
+uniform bool night_blending;
+uniform sampler2D night_texture;
+// unit vector pointing towards the subsolar point
+uniform vec3 sun_direction;
+// sine of the Sun's altitude below which only `night_texture` is shown
+uniform float night_sun_altitude_sin;
+
+// if true, `web_tiles_texture` (with alpha) is blended over `source_texture` in the region `web_tiles_bounds`
+// (min. x, min. y, max. x, max. y in texture coordinates; max. x exceeds 1 if the region crosses the antimeridian)
+uniform bool web_tiles;
+uniform sampler2D web_tiles_texture;
+uniform vec4 web_tiles_bounds;
+
+// color-mapped scalar field (with alpha: 0 where values are missing) blended over the result
+uniform sampler2D field_texture;
+// 0: no field
+uniform float field_opacity;
+
+// georeferenced image (with alpha: 0 outside of the image) blended over the result in the region `image_bounds`
+// (as `web_tiles_bounds`)
+uniform sampler2D image_texture;
+uniform vec4 image_bounds;
+// 0: no image
+uniform float image_opacity;
+
+// blended over the result (e.g. clouds); treated as premultiplied over black, i.e. dark areas are transparent
+uniform sampler2D overlay_texture;
+// 0: no overlay
+uniform float overlay_opacity;
+
+// 0: no hillshading, 1: modulates `source_texture`, 2: replaces `source_texture`
+uniform int hillshading;
+uniform sampler2D elevation_texture;
+// elevations (meters) corresponding to 0 and 1 of `elevation_texture`
+uniform vec2 elevation_range;
+uniform float elevation_exaggeration;
+// unit vector (east, north, up) pointing towards the light source
+uniform vec3 hillshading_sun;
+// radius of the mapped body (meters)
+uniform float body_radius;
+
+const float PI = 3.14159265;
+
+/// Returns the illumination (0-1) of the relief at `tex_coord`.
+float hillshade(vec2 tex_coord)
+{
+    vec2 texel = 1.0 / vec2(textureSize(elevation_texture, 0));
+    float elevation_scale = (elevation_range.y - elevation_range.x) * elevation_exaggeration;
+
+    float d_east = texture(elevation_texture, tex_coord + vec2(texel.x, 0.0)).r
+        - texture(elevation_texture, tex_coord - vec2(texel.x, 0.0)).r;
+    float d_north = texture(elevation_texture, tex_coord - vec2(0.0, texel.y)).r
+        - texture(elevation_texture, tex_coord + vec2(0.0, texel.y)).r;
+
+    // ground distances (meters) between the samples; texture coordinates are equirectangular
+    float latitude = (0.5 - tex_coord.y) * PI;
+    float dist_east = 4.0 * PI * texel.x * body_radius * max(cos(latitude), 0.01);
+    float dist_north = 2.0 * PI * texel.y * body_radius;
+
+    vec3 normal = normalize(vec3(
+        -d_east * elevation_scale / dist_east,
+        -d_north * elevation_scale / dist_north,
+        1.0
+    ));
+
+    return max(dot(normal, hillshading_sun), 0.0);
+}
+
 void main()
 {
     output_color = texture(source_texture, fs_in.tex_coord);
+
+    if (web_tiles)
+    {
+        vec2 tex_coord = fs_in.tex_coord;
+        if (tex_coord.x < web_tiles_bounds.x) { tex_coord.x += 1.0; }
+        vec2 tiles_coord = (tex_coord - web_tiles_bounds.xy) / (web_tiles_bounds.zw - web_tiles_bounds.xy);
+        if (all(greaterThanEqual(tiles_coord, vec2(0.0))) && all(lessThanEqual(tiles_coord, vec2(1.0))))
+        {
+            vec4 tile = texture(web_tiles_texture, tiles_coord);
+            output_color.rgb = mix(output_color.rgb, tile.rgb, tile.a);
+        }
+    }
+
+    if (hillshading == 1)
+    {
+        // flat areas are unchanged
+        float shade = hillshade(fs_in.tex_coord) / max(hillshading_sun.z, 0.01);
+        output_color.rgb = min(output_color.rgb * shade, vec3(1.0));
+    }
+    else if (hillshading == 2)
+    {
+        output_color = vec4(vec3(hillshade(fs_in.tex_coord)), 1.0);
+    }
+
+    if (night_blending)
+    {
+        // texture coordinates are equirectangular (see vertex shaders)
+        float longitude = (fs_in.tex_coord.x - 0.5) * 2.0 * PI;
+        float latitude = (0.5 - fs_in.tex_coord.y) * PI;
+        vec3 position = vec3(cos(latitude) * cos(longitude), cos(latitude) * sin(longitude), sin(latitude));
+
+        float night = clamp(dot(position, sun_direction) / night_sun_altitude_sin, 0.0, 1.0);
+        output_color = mix(output_color, texture(night_texture, fs_in.tex_coord), night);
+    }
+
+    if (field_opacity > 0.0)
+    {
+        vec4 field = texture(field_texture, fs_in.tex_coord);
+        output_color.rgb = mix(output_color.rgb, field.rgb, field.a * field_opacity);
+    }
+
+    if (image_opacity > 0.0)
+    {
+        vec2 image_coord = (fs_in.tex_coord - image_bounds.xy) / (image_bounds.zw - image_bounds.xy);
+        if (all(greaterThanEqual(image_coord, vec2(0.0))) && all(lessThanEqual(image_coord, vec2(1.0))))
+        {
+            vec4 image = texture(image_texture, image_coord);
+            output_color.rgb = mix(output_color.rgb, image.rgb, image.a * image_opacity);
+        }
+    }
+
+    if (overlay_opacity > 0.0)
+    {
+        vec3 overlay = texture(overlay_texture, fs_in.tex_coord).rgb;
+        float coverage = max(overlay.r, max(overlay.g, overlay.b));
+        output_color.rgb = output_color.rgb * (1.0 - overlay_opacity * coverage) + overlay_opacity * overlay;
+    }
 }
@@ -12,6 +12,9 @@ uniform mat3 globe_orientation;
 uniform float zoom;
 uniform float wh_ratio;
 
+// max. angular distance of the shown points from the center of the view (radians)
+uniform float max_angle;
+
 in vec2 lonlat_position;
 out VS_OUT
 {
@@ -47,7 +50,7 @@ void main()
     }
 
     float cos_side_angle = dot(to_position, to_tangent) / (length(to_position) * length(to_tangent));
-    if (cos_side_angle < cos(radians(80)))
+    if (cos_side_angle < cos(max_angle))
     {
         gl_Position = DISCARD;
         return;
@@ -0,0 +1,51 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+} vs_out;
+
+// has to equal `DISCARD` in "*.geom"
+const vec4 DISCARD = vec4(1.0e+9, 1.0e+9, 1.0e+9, 1.0e+9);
+
+// the point opposite to the center of the view is projected onto the whole boundary circle
+const float MIN_ANTIPODE_DISTANCE = 1.0e-6;
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    if (1.0 + position.x < MIN_ANTIPODE_DISTANCE)
+    {
+        gl_Position = DISCARD;
+        return;
+    }
+
+    // the distance from the center equals the chord between the point and the center of the view
+    float k = sqrt(2.0 / (1.0 + position.x));
+
+    gl_Position = vec4(zoom / wh_ratio * k * position.y, zoom * k * position.z, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+}
@@ -0,0 +1,70 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+// latitude of the central point (radians)
+uniform float central_latitude;
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+#if defined(LOBES)
+    // rotated longitude and latitude (radians)
+    vec2 lonlat;
+    // map's x coordinate divided by longitude (depends only on latitude)
+    float x_per_lon;
+#endif
+} vs_out;
+
+const float QUARTER_PI = 0.785398;
+
+const float EPS = 1.0e-4;
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    float lon = atan(position.y, position.x);
+    float lat = asin(clamp(position.z, -1.0, 1.0));
+
+    float x_per_lon;
+    if (abs(lat - central_latitude) < EPS)
+    {
+        x_per_lon = cos(central_latitude);
+    }
+    else
+    {
+        // keep away from the poles, where the logarithm diverges (and `x` tends to 0)
+        float lat_clamped = clamp(lat, -QUARTER_PI * 2 + EPS, QUARTER_PI * 2 - EPS);
+        x_per_lon = (lat - central_latitude)
+            / log(tan(QUARTER_PI + lat_clamped / 2) / tan(QUARTER_PI + central_latitude / 2));
+    }
+    vec2 projected = vec2(lon * x_per_lon, lat - central_latitude);
+
+    gl_Position = vec4(zoom / wh_ratio * projected.x, zoom * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+#if defined(LOBES)
+    vs_out.lonlat = vec2(lon, lat);
+    vs_out.x_per_lon = x_per_lon;
+#endif
+}
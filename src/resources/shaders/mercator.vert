@@ -0,0 +1,98 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Mercator projection and cylindrical projections differing from it only in the spacing of parallels.
+// One of the following may be defined when creating the program (otherwise the projection is Mercator):
+// MILLER, GALL_STEREOGRAPHIC.
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+// horizontal and vertical stretch of the map
+uniform vec2 stretch;
+
+#if !defined(MILLER) && !defined(GALL_STEREOGRAPHIC)
+// 1 to use the WGS84 ellipsoid, 0 to use a sphere
+uniform float ellipsoidal;
+#endif
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+#if defined(LOBES)
+    // rotated longitude and latitude (radians)
+    vec2 lonlat;
+    // map's x coordinate divided by longitude (depends only on latitude)
+    float x_per_lon;
+#endif
+} vs_out;
+
+// has to equal `DISCARD` in "*.geom"
+const vec4 DISCARD = vec4(1.0e+9, 1.0e+9, 1.0e+9, 1.0e+9);
+
+const float QUARTER_PI = 0.785398;
+
+#if defined(GALL_STEREOGRAPHIC)
+// cosine of the standard parallels (±45°)
+const float COS_STANDARD_PARALLEL = 0.707107;
+#elif !defined(MILLER)
+const float WGS84_ECCENTRICITY = 0.0818191908426215;
+
+// the map is infinite at the poles (the view is normally truncated at a lower latitude)
+const float MAX_LATITUDE = 1.56207; // 89.5°
+#endif
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    float lon = atan(position.y, position.x);
+    float lat = asin(clamp(position.z, -1.0, 1.0));
+
+#if defined(MILLER)
+    float x_per_lon = 1.0;
+    vec2 projected = vec2(lon, 1.25 * log(tan(QUARTER_PI + 0.4 * lat)));
+#elif defined(GALL_STEREOGRAPHIC)
+    float x_per_lon = COS_STANDARD_PARALLEL;
+    vec2 projected = vec2(x_per_lon * lon, (1 + COS_STANDARD_PARALLEL) * tan(lat / 2));
+#else
+    if (abs(lat) > MAX_LATITUDE)
+    {
+        gl_Position = DISCARD;
+        return;
+    }
+
+    float e = ellipsoidal * WGS84_ECCENTRICITY;
+    float e_sin = e * sin(lat);
+    float t = tan(QUARTER_PI - lat / 2) / pow((1 - e_sin) / (1 + e_sin), e / 2);
+
+    float x_per_lon = 1.0;
+    vec2 projected = vec2(lon, -log(t));
+#endif
+
+    gl_Position = vec4(zoom / wh_ratio * stretch.x * projected.x, zoom * stretch.y * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+#if defined(LOBES)
+    vs_out.lonlat = vec2(lon, lat);
+    vs_out.x_per_lon = x_per_lon;
+#endif
+}
@@ -0,0 +1,86 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+} vs_out;
+
+// has to equal `DISCARD` in "*.geom"
+const vec4 DISCARD = vec4(1.0e+9, 1.0e+9, 1.0e+9, 1.0e+9);
+
+const float HALF_PI = 1.570796;
+
+const float EPS = 1.0e-5;
+
+// Nicolosi globular projection of the hemisphere centered at lon. 0°, lat. 0°.
+vec2 nicolosi(float lon, float lat)
+{
+    if (abs(lon) < EPS)
+    {
+        return vec2(0, lat);
+    }
+    else if (abs(lat) < EPS)
+    {
+        return vec2(lon, 0);
+    }
+    else if (abs(abs(lon) - HALF_PI) < EPS)
+    {
+        return vec2(lon * cos(lat), HALF_PI * sin(lat));
+    }
+    else if (abs(abs(lat) - HALF_PI) < EPS)
+    {
+        return vec2(0, lat);
+    }
+
+    float b = HALF_PI / lon - lon / HALF_PI;
+    float c = lat / HALF_PI;
+    float sin_lat = sin(lat);
+    float d = (1 - c * c) / (sin_lat - c);
+    float b2_d2 = (b / d) * (b / d);
+    float m = (b * sin_lat / d - 0.5 * b) / (1 + b2_d2);
+    float n = (sin_lat / b2_d2 + 0.5 * d) / (1 + 1 / b2_d2);
+
+    float cos_lat = cos(lat);
+    float x = sqrt(m * m + cos_lat * cos_lat / (1 + b2_d2));
+    float y = sqrt(max(0.0, n * n - (sin_lat * sin_lat / b2_d2 + d * sin_lat - 1) / (1 + 1 / b2_d2)));
+
+    return HALF_PI * vec2(m + sign(lon) * x, n - sign(lat) * y);
+}
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    if (position.x < 0)
+    {
+        gl_Position = DISCARD;
+        return;
+    }
+
+    vec2 projected = nicolosi(atan(position.y, position.x), asin(clamp(position.z, -1.0, 1.0)));
+
+    gl_Position = vec4(zoom / wh_ratio * projected.x, zoom * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+}
@@ -0,0 +1,30 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Renders lines specified directly in map coordinates.
+//
+
+#version 330 core
+
+uniform float zoom;
+uniform float wh_ratio;
+// horizontal and vertical stretch of the map
+uniform vec2 stretch;
+// rotation of the map about the view's center (radians, counterclockwise)
+uniform float roll;
+// see "lines.geom"
+uniform vec4 crop;
+
+in vec2 position;
+
+void main()
+{
+    vec2 p = mat2(cos(roll), sin(roll), -sin(roll), cos(roll)) * (zoom * stretch * position);
+    gl_Position = vec4((vec2(p.x / wh_ratio, p.y) - crop.xy) * crop.zw, 0, 1);
+}
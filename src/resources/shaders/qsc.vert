@@ -0,0 +1,131 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+} vs_out;
+
+const float PI = 3.141593;
+const float HALF_PI = 1.570796;
+const float QUARTER_PI = 0.785398;
+
+const float EPS = 1.0e-5;
+
+// half of the side of a cube face in map coordinates
+const float FACE_HALF_SIZE = QUARTER_PI;
+
+// Projects a point of the unit globe onto a cube face. `a`, `b`: coordinates along the face's horizontal
+// and vertical axes, `c`: coordinate along the face's normal. Returns coordinates within [-1, 1]².
+vec2 qsc_face(float a, float b, float c)
+{
+    float phi = acos(clamp(c, -1.0, 1.0));
+    if (phi < EPS)
+    {
+        return vec2(0, 0);
+    }
+
+    // by symmetry, reduce the azimuth to the area |theta| <= π/4
+    float theta = atan(b, a);
+    float area_rotation;
+    if (abs(theta) <= QUARTER_PI)
+    {
+        area_rotation = 0.0;
+    }
+    else if (theta > QUARTER_PI && theta <= 3 * QUARTER_PI)
+    {
+        area_rotation = HALF_PI;
+    }
+    else if (theta > 3 * QUARTER_PI || theta <= -3 * QUARTER_PI)
+    {
+        area_rotation = PI;
+    }
+    else
+    {
+        area_rotation = -HALF_PI;
+    }
+    theta -= area_rotation;
+    if (theta < -PI)
+    {
+        theta += 2 * PI;
+    }
+
+    float mu = atan((12.0 / PI) * (theta + acos(clamp(sin(theta) * cos(QUARTER_PI), -1.0, 1.0)) - HALF_PI));
+    float cos_mu = cos(mu);
+    float t = sqrt((1.0 - cos(phi)) / (cos_mu * cos_mu) / (1.0 - cos(atan(1.0 / cos(theta)))));
+
+    mu += area_rotation;
+
+    return t * vec2(cos(mu), sin(mu));
+}
+
+// Faces are laid out as a cross: left, front, right, back in the middle row, top and bottom above and below front.
+vec2 qsc(vec3 p)
+{
+    vec3 ap = abs(p);
+
+    if (ap.z >= ap.x && ap.z >= ap.y)
+    {
+        if (p.z > 0)
+        {
+            return FACE_HALF_SIZE * (vec2(0, 2) + qsc_face(p.y, -p.x, p.z));
+        }
+        else
+        {
+            return FACE_HALF_SIZE * (vec2(0, -2) + qsc_face(p.y, p.x, -p.z));
+        }
+    }
+    else if (ap.x >= ap.y)
+    {
+        if (p.x > 0)
+        {
+            return FACE_HALF_SIZE * qsc_face(p.y, p.z, p.x);
+        }
+        else
+        {
+            return FACE_HALF_SIZE * (vec2(4, 0) + qsc_face(-p.y, p.z, -p.x));
+        }
+    }
+    else
+    {
+        if (p.y > 0)
+        {
+            return FACE_HALF_SIZE * (vec2(2, 0) + qsc_face(-p.x, p.z, p.y));
+        }
+        else
+        {
+            return FACE_HALF_SIZE * (vec2(-2, 0) + qsc_face(p.x, p.z, -p.y));
+        }
+    }
+}
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    vec2 projected = qsc(position);
+
+    gl_Position = vec4(zoom / wh_ratio * projected.x, zoom * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+}
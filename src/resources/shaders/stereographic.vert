@@ -12,6 +12,12 @@ uniform mat3 globe_orientation;
 uniform float zoom;
 uniform float wh_ratio;
 
+// distance of the projection point (on the far side of the globe) from the globe's center;
+// 1 = stereographic, 0 = gnomonic, infinity = orthographic
+uniform float point_distance;
+// position of the image plane along the X axis; 1 = tangent plane, less than 1 = secant plane
+uniform float image_plane;
+
 in vec2 lonlat_position;
 out VS_OUT
 {
@@ -21,9 +27,6 @@ out VS_OUT
 // has to equal `DISCARD` in "*.geom"
 const vec4 DISCARD = vec4(1.0e+9, 1.0e+9, 1.0e+9, 1.0e+9);
 
-const vec3 GLOBE_TANGENT = vec3(1, 0, 0);
-const vec3 PROJECTION_CENTER = vec3(-1, 0, 0);
-
 void main()
 {
     float longitude = radians(lonlat_position.x);
@@ -37,24 +40,16 @@ void main()
 
     vec3 position = globe_orientation * original_position;
 
-    vec3 to_position = position - PROJECTION_CENTER;
-    const vec3 to_tangent = GLOBE_TANGENT - PROJECTION_CENTER;
-
-    if (to_position == vec3(0, 0, 0))
-    {
-        gl_Position = DISCARD;
-        return;
-    }
-
-    float cos_side_angle = dot(to_position, to_tangent) / (length(to_position) * length(to_tangent));
-    if (cos_side_angle < cos(radians(45)))
+    // only the near hemisphere is shown
+    if (position.x < 0)
     {
         gl_Position = DISCARD;
         return;
     }
 
-    float k = dot(to_tangent, to_tangent) / dot(to_position, to_tangent);
-    vec3 projected = PROJECTION_CENTER + k * to_position;
+    vec3 projection_point = vec3(-point_distance, 0, 0);
+    float k = (image_plane + point_distance) / (position.x + point_distance);
+    vec3 projected = projection_point + k * (position - projection_point);
 
     gl_Position = vec4(zoom / wh_ratio * projected.y, zoom * projected.z, 0, 1);
     vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
@@ -12,6 +12,7 @@ in vec2 tex_coord;
 out vec4 output_color;
 
 uniform sampler2DMS source_texture;
+uniform int num_samples;
 
 void main()
 {
@@ -20,11 +21,11 @@ void main()
     ivec2 texel = ivec2(tex_coord * textureSize(source_texture)); //TODO: provide texture size as a uniform for better speed?
 
     //TODO: provide additional input with sample mask, sum only edge samples?
-    for (int i = 0; i < 8; ++i) //TODO: provide sample count as uniform
+    for (int i = 0; i < num_samples; ++i)
     {
         color += texelFetch(source_texture, texel, i);
     }
-    color /= 8.0;
+    color /= float(num_samples);
 
     output_color = color;
 }
@@ -0,0 +1,92 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Uses the series of Krüger (see C. F. F. Karney, "Transverse Mercator with an accuracy of a few nanometers", 2011),
+// truncated at the 3rd order of the third flattening.
+//
+
+#version 330 core
+
+uniform mat3 globe_orientation;
+uniform float zoom;
+uniform float wh_ratio;
+
+// 1 to use the WGS84 ellipsoid, 0 to use a sphere
+uniform float ellipsoidal;
+
+in vec2 lonlat_position;
+out VS_OUT
+{
+    vec2 tex_coord;
+} vs_out;
+
+// has to equal `DISCARD` in "*.geom"
+const vec4 DISCARD = vec4(1.0e+9, 1.0e+9, 1.0e+9, 1.0e+9);
+
+const float WGS84_ECCENTRICITY = 0.0818191908426215;
+
+// max. distance from the central meridian; the map is infinite at ±90°
+const float MAX_LONGITUDE = 1.39626; // 80°
+
+float atanh_(float x)
+{
+    return 0.5 * log((1 + x) / (1 - x));
+}
+
+void main()
+{
+    float longitude = radians(lonlat_position.x);
+    float latitude = radians(lonlat_position.y);
+
+    vec3 original_position = vec3(
+        cos(longitude) * cos(latitude),
+        sin(longitude) * cos(latitude),
+        sin(latitude)
+    );
+
+    vec3 position = globe_orientation * original_position;
+
+    float lon = atan(position.y, position.x);
+    float lat = asin(clamp(position.z, -1.0, 1.0));
+
+    if (abs(lon) > MAX_LONGITUDE)
+    {
+        gl_Position = DISCARD;
+        return;
+    }
+
+    float e = ellipsoidal * WGS84_ECCENTRICITY;
+    float f = 1 - sqrt(1 - e * e);
+    float n = f / (2 - f);
+    float rectifying_radius = (1 + n * n / 4 + n * n * n * n / 64) / (1 + n);
+    float alpha[3] = float[3](
+        n / 2 - 2.0 / 3.0 * n * n + 5.0 / 16.0 * n * n * n,
+        13.0 / 48.0 * n * n - 3.0 / 5.0 * n * n * n,
+        61.0 / 240.0 * n * n * n
+    );
+
+    float sin_lat = clamp(sin(lat), -1 + 1.0e-6, 1 - 1.0e-6);
+    float t = sinh(atanh_(sin_lat) - e * atanh_(e * sin_lat));
+    float xi_prime = atan(t, cos(lon));
+    float eta_prime = atanh_(sin(lon) / sqrt(1 + t * t));
+
+    float xi = xi_prime;
+    float eta = eta_prime;
+    for (int j = 1; j <= 3; j++)
+    {
+        float k = 2 * j;
+        xi += alpha[j - 1] * sin(k * xi_prime) * cosh(k * eta_prime);
+        eta += alpha[j - 1] * cos(k * xi_prime) * sinh(k * eta_prime);
+    }
+
+    vec2 projected = rectifying_radius * vec2(eta, xi);
+
+    gl_Position = vec4(zoom / wh_ratio * projected.x, zoom * projected.y, 0, 1);
+    vs_out.tex_coord = vec2(0.5 + lonlat_position.x / 360.0, 0.5 - lonlat_position.y / 180.0);
+}
@@ -12,25 +12,47 @@ use std::rc::Rc;
 
 mod clipboard_support;
 
+/// Number of consecutive frames without input or activity after which the event loop enters the idle mode.
+const FRAMES_BEFORE_IDLE: u32 = 3;
+
+/// In the idle mode, the UI is redrawn only on input or after this interval.
+const IDLE_REDRAW_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
 pub struct Runner {
     event_loop: glium::glutin::event_loop::EventLoop<()>,
     display: glium::Display,
     imgui: imgui::Context,
     platform: imgui_winit_support::WinitPlatform,
-    renderer: Rc<RefCell<imgui_glium_renderer::Renderer>>
+    renderer: Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    /// If set, replaces user input (see `Runner::set_input_source`).
+    input_source: Option<Box<dyn FnMut(&mut imgui::Io)>>,
+    /// Called when the main loop ends (see `Runner::set_exit_handler`).
+    exit_handler: Option<Box<dyn FnMut()>>
 }
 
-pub fn create_runner(logical_font_size: f64) -> Runner {
+/// Creates the main window; imgui's settings (window layout) are loaded from and saved to `layout_path` (if any).
+pub fn create_runner(
+    logical_font_size: f64,
+    logical_window_size: [f64; 2],
+    layout_path: Option<std::path::PathBuf>
+) -> Runner {
     let event_loop = glium::glutin::event_loop::EventLoop::new();
     let context = glium::glutin::ContextBuilder::new().with_vsync(true);
     let builder = glium::glutin::window::WindowBuilder::new()
         .with_title("Projections".to_owned())
-        .with_inner_size(glium::glutin::dpi::LogicalSize::new(1280f64, 768f64));
+        .with_inner_size(glium::glutin::dpi::LogicalSize::new(logical_window_size[0], logical_window_size[1]));
     let display =
         glium::Display::new(builder, context, &event_loop).expect("Failed to initialize display.");
 
     let mut imgui = imgui::Context::create();
-    imgui.set_ini_filename(None);
+    if let Some(dir) = layout_path.as_ref().and_then(|path| path.parent()) {
+        if !dir.as_os_str().is_empty() {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                eprintln!("Failed to create directory {}: {}", dir.display(), e);
+            }
+        }
+    }
+    imgui.set_ini_filename(layout_path);
 
     if let Some(backend) = clipboard_support::init() {
         imgui.set_clipboard_backend(backend);
@@ -75,10 +97,42 @@ pub fn create_runner(logical_font_size: f64) -> Runner {
         display,
         imgui,
         platform,
-        renderer: Rc::new(RefCell::new(renderer))
+        renderer: Rc::new(RefCell::new(renderer)),
+        input_source: None,
+        exit_handler: None
     }
 }
 
+/// Display of a hidden window, for rendering without the GUI (e.g. in the batch mode).
+pub struct HiddenDisplay {
+    pub display: glium::Display,
+    pub renderer: Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    /// Has to outlive `display`.
+    _event_loop: glium::glutin::event_loop::EventLoop<()>
+}
+
+pub fn create_hidden_display() -> Result<HiddenDisplay, String> {
+    // winit panics (instead of returning an error) if there is no display server to connect to
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return Err("no display server (neither DISPLAY nor WAYLAND_DISPLAY is set)".into());
+    }
+
+    let event_loop = glium::glutin::event_loop::EventLoop::new();
+    let context = glium::glutin::ContextBuilder::new();
+    let builder = glium::glutin::window::WindowBuilder::new()
+        .with_title("Projections".to_owned())
+        .with_visible(false);
+    let display = glium::Display::new(builder, context, &event_loop).map_err(|e| e.to_string())?;
+
+    // views register their textures with the renderer, which needs a (temporary) imgui context
+    let mut imgui = imgui::Context::create();
+    imgui.set_ini_filename(None);
+    let renderer = imgui_glium_renderer::Renderer::init(&mut imgui, &display).map_err(|e| e.to_string())?;
+
+    Ok(HiddenDisplay{ display, renderer: Rc::new(RefCell::new(renderer)), _event_loop: event_loop })
+}
+
 impl Runner {
     pub fn renderer(&self) -> &Rc<RefCell<imgui_glium_renderer::Renderer>> {
         &self.renderer
@@ -92,8 +146,22 @@ impl Runner {
         &self.display
     }
 
+    /// Makes the main loop ignore user input and call `input_source` before every frame instead
+    /// (frames are then drawn continuously).
+    pub fn set_input_source(&mut self, input_source: Box<dyn FnMut(&mut imgui::Io)>) {
+        self.input_source = Some(input_source);
+    }
+
+    /// Makes the main loop call `exit_handler` when it ends (e.g. when the window is closed).
+    pub fn set_exit_handler(&mut self, exit_handler: Box<dyn FnMut()>) {
+        self.exit_handler = Some(exit_handler);
+    }
+
+    /// Runs the main loop; `run_ui` returns true if anything changed or is being animated (i.e., the next frame
+    /// should be drawn without waiting for input).
     pub fn main_loop<F>(self, mut run_ui: F)
-        where F: FnMut(&mut bool, &mut imgui::Ui, &glium::Display, &Rc<RefCell<imgui_glium_renderer::Renderer>>) + 'static
+        where F: FnMut(&mut bool, &mut imgui::Ui, &glium::Display, &Rc<RefCell<imgui_glium_renderer::Renderer>>) -> bool
+            + 'static
     {
         let Runner {
             event_loop,
@@ -101,33 +169,55 @@ impl Runner {
             mut imgui,
             mut platform,
             renderer,
-            ..
+            mut input_source,
+            mut exit_handler
         } = self;
 
         let mut last_frame = std::time::Instant::now();
+        // number of consecutive frames without input or activity
+        let mut idle_frames = 0;
+        let mut redraw_interval_elapsed = false;
 
         event_loop.run(move |event, _, control_flow| match event {
-            glium::glutin::event::Event::NewEvents(_) => {
+            glium::glutin::event::Event::NewEvents(cause) => {
                 let now = std::time::Instant::now();
                 imgui.io_mut().update_delta_time(now - last_frame);
                 last_frame = now;
+                if let glium::glutin::event::StartCause::ResumeTimeReached{ .. } = cause {
+                    redraw_interval_elapsed = true;
+                }
             },
 
             glium::glutin::event::Event::MainEventsCleared => {
-                let gl_window = display.gl_window();
-                platform
-                    .prepare_frame(imgui.io_mut(), &gl_window.window())
-                    .expect("Failed to prepare frame");
-                gl_window.window().request_redraw();
+                // in the idle mode, ignore events which do not concern the window (e.g. raw mouse motion)
+                if idle_frames < FRAMES_BEFORE_IDLE || redraw_interval_elapsed || input_source.is_some() {
+                    let gl_window = display.gl_window();
+                    platform
+                        .prepare_frame(imgui.io_mut(), &gl_window.window())
+                        .expect("Failed to prepare frame");
+                    gl_window.window().request_redraw();
+                }
             },
 
             glium::glutin::event::Event::RedrawRequested(_) => {
+                if let Some(input_source) = &mut input_source {
+                    input_source(imgui.io_mut());
+                }
                 let mut ui = imgui.frame();
 
                 let mut run = true;
-                run_ui(&mut run, &mut ui, &display, &renderer);
-                if !run {
+                let active = run_ui(&mut run, &mut ui, &display, &renderer);
+                idle_frames = if active { 0 } else { idle_frames.saturating_add(1) };
+                redraw_interval_elapsed = false;
+
+                if !run || *control_flow == glium::glutin::event_loop::ControlFlow::Exit {
                     *control_flow = glium::glutin::event_loop::ControlFlow::Exit;
+                } else if idle_frames >= FRAMES_BEFORE_IDLE && input_source.is_none() {
+                    *control_flow = glium::glutin::event_loop::ControlFlow::WaitUntil(
+                        std::time::Instant::now() + IDLE_REDRAW_INTERVAL
+                    );
+                } else {
+                    *control_flow = glium::glutin::event_loop::ControlFlow::Poll;
                 }
 
                 let gl_window = display.gl_window();
@@ -146,7 +236,15 @@ impl Runner {
                 ..
             } => *control_flow = glium::glutin::event_loop::ControlFlow::Exit,
 
+            glium::glutin::event::Event::LoopDestroyed => if let Some(exit_handler) = &mut exit_handler {
+                exit_handler();
+            },
+
             event => {
+                if input_source.is_some() && is_input_event(&event) { return; }
+
+                if let glium::glutin::event::Event::WindowEvent{ .. } = event { idle_frames = 0; }
+
                 let converted_event = convert_touch_to_mouse(event);
 
                 let gl_window = display.gl_window();
@@ -156,6 +254,21 @@ impl Runner {
     }
 }
 
+fn is_input_event<T>(event: &glium::glutin::event::Event<T>) -> bool {
+    use glium::glutin::event::{Event, WindowEvent};
+
+    matches!(event, Event::WindowEvent{ event:
+        WindowEvent::CursorMoved{ .. }
+        | WindowEvent::MouseInput{ .. }
+        | WindowEvent::MouseWheel{ .. }
+        | WindowEvent::KeyboardInput{ .. }
+        | WindowEvent::ReceivedCharacter(_)
+        | WindowEvent::ModifiersChanged(_)
+        | WindowEvent::Touch(_),
+        ..
+    })
+}
+
 fn convert_touch_to_mouse<'a, T>(event: glium::glutin::event::Event<'a, T>) -> glium::glutin::event::Event<'a, T> {
     use glium::glutin::event;
 
@@ -0,0 +1,383 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Gridded scalar fields (e.g. sea-surface temperature) loaded from NetCDF files and shown as color-mapped overlays.
+//
+// A field is a variable whose last two dimensions are latitude and longitude (in this order, as in CF-compliant
+// files), each with a coordinate variable of the same name; leading dimensions (e.g. time, depth) are fixed
+// at a chosen index of the first one and 0 of the others. `scale_factor` and `add_offset` are applied; values equal
+// to `_FillValue` or `missing_value` are missing (transparent).
+//
+// Indices of the first leading dimension are labeled with dates if its coordinate variable has CF time units
+// (e.g. "days since 1800-01-01 00:00:00", assuming the Gregorian calendar).
+//
+// Reading NetCDF files requires the "netcdf" feature (and the netCDF-C library); without it, the loaders report
+// an error.
+//
+
+#[cfg(feature = "netcdf")]
+use crate::solar::UtcDateTime;
+
+/// Values of larger magnitude are treated as missing (common default fill values are ~1e36).
+#[cfg(feature = "netcdf")]
+const MAX_VALID_MAGNITUDE: f32 = 1.0e30;
+
+/// Whether NetCDF files can be read (see the module comment).
+pub const NETCDF_AVAILABLE: bool = cfg!(feature = "netcdf");
+
+pub const NOT_COMPILED_IN: &str = "NetCDF support is not compiled in (build with \"--features netcdf\")";
+
+/// Max. width of the equirectangular image of a field.
+const MAX_IMAGE_WIDTH: u32 = 8192;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Colormap {
+    Viridis,
+    Inferno,
+    /// Diverging (blue - white - red).
+    Coolwarm,
+    Grayscale
+}
+
+impl Colormap {
+    pub const ALL: [Colormap; 4] = [Colormap::Viridis, Colormap::Inferno, Colormap::Coolwarm, Colormap::Grayscale];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colormap::Viridis => "viridis",
+            Colormap::Inferno => "inferno",
+            Colormap::Coolwarm => "coolwarm",
+            Colormap::Grayscale => "grayscale"
+        }
+    }
+
+    /// Equally spaced colors interpolated linearly.
+    fn stops(&self) -> &'static [[u8; 3]] {
+        match self {
+            Colormap::Viridis => &[
+                [68, 1, 84], [72, 40, 120], [62, 73, 137], [49, 104, 142], [38, 130, 142],
+                [31, 158, 137], [53, 183, 121], [110, 206, 88], [181, 222, 43], [253, 231, 37]
+            ],
+            Colormap::Inferno => &[
+                [0, 0, 4], [40, 11, 84], [101, 21, 110], [159, 42, 99], [212, 72, 66],
+                [245, 125, 21], [250, 193, 39], [252, 255, 164]
+            ],
+            Colormap::Coolwarm => &[[59, 76, 192], [141, 176, 254], [221, 220, 220], [244, 154, 123], [180, 4, 38]],
+            Colormap::Grayscale => &[[0, 0, 0], [255, 255, 255]]
+        }
+    }
+
+    /// Returns the color of `t` (from 0 to 1; clamped).
+    pub fn color(&self, t: f32) -> [u8; 3] {
+        let stops = self.stops();
+        let pos = t.max(0.0).min(1.0) * (stops.len() - 1) as f32;
+        let idx = (pos as usize).min(stops.len() - 2);
+        let frac = pos - idx as f32;
+        let mix = |i: usize| (stops[idx][i] as f32 * (1.0 - frac) + stops[idx + 1][i] as f32 * frac).round() as u8;
+
+        [mix(0), mix(1), mix(2)]
+    }
+}
+
+/// Values of a field on a latitude-longitude grid.
+pub struct Grid {
+    /// Name of the variable (with units, if specified).
+    pub name: String,
+    /// Ascending longitudes (degrees, from -180 to 180).
+    longitudes: Vec<f64>,
+    /// Ascending latitudes (degrees).
+    latitudes: Vec<f64>,
+    /// Row-major (row 0: first latitude); missing values are NaN.
+    values: Vec<f32>,
+    /// Min. and max. value.
+    pub value_range: [f32; 2]
+}
+
+/// Returns names of variables with at least two dimensions (candidates for `load`).
+#[cfg(feature = "netcdf")]
+pub fn variables(path: &str) -> Result<Vec<String>, String> {
+    let file = netcdf::open(path).map_err(|e| format!("cannot open {}: {}", path, e))?;
+    let names: Vec<String> = file.variables()
+        .filter(|variable| variable.dimensions().len() >= 2)
+        .map(|variable| variable.name())
+        .collect();
+    if names.is_empty() {
+        return Err("no variables with at least two dimensions".into());
+    }
+
+    Ok(names)
+}
+
+/// Loads the field `variable`; `index` is the index of the first leading dimension (if any).
+#[cfg(feature = "netcdf")]
+pub fn load(path: &str, variable: &str, index: usize) -> Result<Grid, String> {
+    let file = netcdf::open(path).map_err(|e| format!("cannot open {}: {}", path, e))?;
+    let var = file.variable(variable).ok_or_else(|| format!("no variable {}", variable))?;
+    let dims = var.dimensions();
+    if dims.len() < 2 {
+        return Err(format!("{} has fewer than two dimensions", variable));
+    }
+    let num_leading = dims.len() - 2;
+    if num_leading > 0 && index >= dims[0].len() {
+        return Err(format!("index {} exceeds the dimension {} (length {})", index, dims[0].name(), dims[0].len()));
+    }
+
+    let coordinates = |dim: &netcdf::Dimension| -> Result<Vec<f64>, String> {
+        let coord_var = file.variable(&dim.name())
+            .ok_or_else(|| format!("no coordinate variable of dimension {}", dim.name()))?;
+        let mut values = vec![0.0; dim.len()];
+        coord_var.values_to(&mut values, None, None).map_err(|e| e.to_string())?;
+        Ok(values)
+    };
+    let latitudes = coordinates(&dims[num_leading])?;
+    let longitudes = coordinates(&dims[num_leading + 1])?;
+    if latitudes.is_empty() || longitudes.is_empty() {
+        return Err(format!("{} is empty", variable));
+    }
+    // fill values (or NaNs) in a coordinate variable would leave grid points without a position
+    if latitudes.iter().any(|lat| !lat.is_finite()) || longitudes.iter().any(|lon| !lon.is_finite()) {
+        return Err(format!("{} has missing (non-finite) coordinates", variable));
+    }
+    if latitudes.iter().any(|lat| lat.abs() > 90.0) {
+        return Err(format!("{} does not look like latitude", dims[num_leading].name()));
+    }
+
+    let mut start = vec![0; dims.len()];
+    if num_leading > 0 { start[0] = index; }
+    let mut count = vec![1; dims.len()];
+    count[num_leading] = latitudes.len();
+    count[num_leading + 1] = longitudes.len();
+    let mut values = vec![0.0f32; latitudes.len() * longitudes.len()];
+    var.values_to(&mut values, Some(&start), Some(&count)).map_err(|e| e.to_string())?;
+
+    let attribute = |name: &str| -> Option<f64> {
+        use netcdf::AttrValue;
+        match var.attribute(name)?.value().ok()? {
+            AttrValue::Uchar(v) => Some(v as f64),
+            AttrValue::Schar(v) => Some(v as f64),
+            AttrValue::Ushort(v) => Some(v as f64),
+            AttrValue::Short(v) => Some(v as f64),
+            AttrValue::Uint(v) => Some(v as f64),
+            AttrValue::Int(v) => Some(v as f64),
+            AttrValue::Float(v) => Some(v as f64),
+            AttrValue::Double(v) => Some(v),
+            _ => None
+        }
+    };
+    let missing: Vec<f32> = ["_FillValue", "missing_value"].iter()
+        .filter_map(|name| attribute(name))
+        .map(|value| value as f32)
+        .collect();
+    let scale = attribute("scale_factor").unwrap_or(1.0) as f32;
+    let offset = attribute("add_offset").unwrap_or(0.0) as f32;
+    for value in &mut values {
+        *value = if missing.contains(value) || !value.is_finite() || value.abs() > MAX_VALID_MAGNITUDE {
+            f32::NAN
+        } else {
+            *value * scale + offset
+        };
+    }
+
+    let value_range = values.iter().filter(|v| !v.is_nan()).fold(
+        [f32::INFINITY, f32::NEG_INFINITY],
+        |range, v| [range[0].min(*v), range[1].max(*v)]
+    );
+    if value_range[0] > value_range[1] {
+        return Err(format!("{} has no valid values", variable));
+    }
+
+    let name = match var.attribute("units").and_then(|units| units.value().ok()) {
+        Some(netcdf::AttrValue::Str(units)) => format!("{} [{}]", variable, units),
+        _ => variable.to_string()
+    };
+
+    Ok(Grid::new(name, longitudes, latitudes, values, value_range))
+}
+
+/// Returns labels of the indices of the first leading dimension of `variable` (dates, coordinates or indices),
+/// or a single empty label if there are no leading dimensions.
+#[cfg(feature = "netcdf")]
+pub fn step_labels(path: &str, variable: &str) -> Result<Vec<String>, String> {
+    let file = netcdf::open(path).map_err(|e| format!("cannot open {}: {}", path, e))?;
+    let var = file.variable(variable).ok_or_else(|| format!("no variable {}", variable))?;
+    let dims = var.dimensions();
+    if dims.len() <= 2 { return Ok(vec![String::new()]); }
+
+    let dim = &dims[0];
+    let coord_var = match file.variable(&dim.name()) {
+        Some(coord_var) => coord_var,
+        None => return Ok((0..dim.len()).map(|idx| format!("{} {}", dim.name(), idx)).collect())
+    };
+    let mut values = vec![0.0; dim.len()];
+    coord_var.values_to(&mut values, None, None).map_err(|e| e.to_string())?;
+    let units = match coord_var.attribute("units").and_then(|units| units.value().ok()) {
+        Some(netcdf::AttrValue::Str(units)) => units,
+        _ => String::new()
+    };
+
+    Ok(match parse_time_units(&units) {
+        Some((unit_seconds, epoch)) => values.iter().map(|value| {
+            let t = UtcDateTime::from_unix_time(epoch + (value * unit_seconds).round() as i64);
+            if t.hour == 0 && t.minute == 0 {
+                format!("{:04}-{:02}-{:02}", t.year, t.month, t.day)
+            } else {
+                format!("{:04}-{:02}-{:02} {:02}:{:02}", t.year, t.month, t.day, t.hour, t.minute)
+            }
+        }).collect(),
+        None => values.iter().map(|value| format!("{} = {} {}", dim.name(), value, units).trim().to_string()).collect()
+    })
+}
+
+#[cfg(not(feature = "netcdf"))]
+pub fn variables(_path: &str) -> Result<Vec<String>, String> { Err(NOT_COMPILED_IN.into()) }
+
+#[cfg(not(feature = "netcdf"))]
+pub fn load(_path: &str, _variable: &str, _index: usize) -> Result<Grid, String> { Err(NOT_COMPILED_IN.into()) }
+
+#[cfg(not(feature = "netcdf"))]
+pub fn step_labels(_path: &str, _variable: &str) -> Result<Vec<String>, String> { Err(NOT_COMPILED_IN.into()) }
+
+/// Parses CF time units ("<unit> since <date> [<time>]"); returns the unit's length in seconds and the epoch
+/// (seconds since 1970-01-01 00:00 UTC).
+#[cfg(feature = "netcdf")]
+fn parse_time_units(units: &str) -> Option<(f64, i64)> {
+    let (unit, since) = units.split_once(" since ")?;
+    let unit_seconds = match unit.trim().to_lowercase().as_str() {
+        "days" | "day" | "d" => 86400.0,
+        "hours" | "hour" | "hr" | "h" => 3600.0,
+        "minutes" | "minute" | "min" => 60.0,
+        "seconds" | "second" | "sec" | "s" => 1.0,
+        _ => return None
+    };
+
+    let since = since.trim().replace('T', " ");
+    let mut parts = since.split_whitespace();
+    let date: Vec<i64> = parts.next()?.split('-').map(|field| field.parse().ok()).collect::<Option<_>>()?;
+    let time: Vec<f64> = match parts.next() {
+        Some(time) => time.trim_end_matches('Z').split(':').map(|field| field.parse().ok()).collect::<Option<_>>()?,
+        None => vec![]
+    };
+    if date.len() != 3 { return None; }
+    let epoch = UtcDateTime{
+        year: date[0] as i32,
+        month: date[1] as u32,
+        day: date[2] as u32,
+        hour: time.get(0).copied().unwrap_or(0.0) as u32,
+        minute: time.get(1).copied().unwrap_or(0.0) as u32,
+        second: time.get(2).copied().unwrap_or(0.0) as u32
+    };
+    epoch.validate().ok()?;
+
+    Some((unit_seconds, epoch.unix_time()))
+}
+
+impl Grid {
+    /// Sorts the grid's columns and rows by ascending longitude (normalized to [-180, 180)) and latitude.
+    #[cfg_attr(not(feature = "netcdf"), allow(dead_code))]
+    fn new(name: String, longitudes: Vec<f64>, latitudes: Vec<f64>, values: Vec<f32>, value_range: [f32; 2]) -> Grid {
+        let width = longitudes.len();
+        let normalized: Vec<f64> = longitudes.iter().map(|lon| (lon + 180.0).rem_euclid(360.0) - 180.0).collect();
+        let mut column_order: Vec<usize> = (0..width).collect();
+        column_order.sort_by(|a, b| normalized[*a].total_cmp(&normalized[*b]));
+        let mut row_order: Vec<usize> = (0..latitudes.len()).collect();
+        row_order.sort_by(|a, b| latitudes[*a].total_cmp(&latitudes[*b]));
+
+        Grid{
+            name,
+            longitudes: column_order.iter().map(|idx| normalized[*idx]).collect(),
+            latitudes: row_order.iter().map(|idx| latitudes[*idx]).collect(),
+            values: row_order.iter()
+                .flat_map(|row| column_order.iter().map(move |column| (*row, *column)))
+                .map(|(row, column)| values[row * width + column])
+                .collect(),
+            value_range
+        }
+    }
+
+    /// Returns the mean spacing of the grid's longitudes (degrees).
+    fn longitude_step(&self) -> f64 {
+        let num_lon = self.longitudes.len();
+        if num_lon > 1 { (self.longitudes[num_lon - 1] - self.longitudes[0]) / (num_lon - 1) as f64 } else { 360.0 }
+    }
+
+    /// Returns 360 if the grid covers all longitudes (i.e., wraps around the antimeridian).
+    fn longitude_period(&self) -> Option<f64> {
+        let lon_step = self.longitude_step();
+        let lon_span = self.longitudes[self.longitudes.len() - 1] - self.longitudes[0];
+        if (lon_span + lon_step - 360.0).abs() < lon_step / 2.0 { Some(360.0) } else { None }
+    }
+
+    /// Returns true if `other` has the same grid points.
+    pub fn has_same_points(&self, other: &Grid) -> bool {
+        self.longitudes == other.longitudes && self.latitudes == other.latitudes
+    }
+
+    /// Returns the value at the grid point nearest to (`longitude`, `latitude`) (degrees), or NaN if the point
+    /// is outside the grid or the value is missing.
+    pub fn value(&self, longitude: f64, latitude: f64) -> f32 {
+        let column = nearest(&self.longitudes, longitude, self.longitude_period());
+        let row = nearest(&self.latitudes, latitude, None);
+        match (column, row) {
+            (Some(column), Some(row)) => self.values[row * self.longitudes.len() + column],
+            _ => f32::NAN
+        }
+    }
+
+    /// Returns a global equirectangular image of the field, with `value_range` mapped to `colormap`; missing values
+    /// are transparent.
+    pub fn to_image(&self, colormap: Colormap, value_range: [f32; 2]) -> image::RgbaImage {
+        let num_lon = self.longitudes.len();
+        let period = self.longitude_period();
+
+        let width = ((360.0 / self.longitude_step()).round() as u32).max(2).min(MAX_IMAGE_WIDTH);
+        let height = width / 2;
+        // grid indices nearest to the image's columns and rows
+        let columns: Vec<Option<usize>> = (0..width)
+            .map(|column| nearest(&self.longitudes, -180.0 + (column as f64 + 0.5) / width as f64 * 360.0, period))
+            .collect();
+        let rows: Vec<Option<usize>> = (0..height)
+            .map(|row| nearest(&self.latitudes, 90.0 - (row as f64 + 0.5) / height as f64 * 180.0, None))
+            .collect();
+        let scale = 1.0 / (value_range[1] - value_range[0]).max(f32::EPSILON);
+
+        let mut image = image::RgbaImage::new(width, height);
+        for (row, grid_row) in rows.iter().enumerate() {
+            let grid_row = match grid_row { Some(grid_row) => *grid_row, None => continue };
+            for (column, grid_column) in columns.iter().enumerate() {
+                let grid_column = match grid_column { Some(grid_column) => *grid_column, None => continue };
+                let value = self.values[grid_row * num_lon + grid_column];
+                if !value.is_nan() {
+                    let [r, g, b] = colormap.color((value - value_range[0]) * scale);
+                    image.put_pixel(column as u32, row as u32, image::Rgba([r, g, b, 255]));
+                }
+            }
+        }
+
+        image
+    }
+}
+
+/// Returns the index of the element of ascending `coords` nearest to `x`, or `None` if `x` is farther
+/// than half a grid step outside. If `period` is given, coordinates wrap around.
+fn nearest(coords: &[f64], x: f64, period: Option<f64>) -> Option<usize> {
+    let n = coords.len();
+    if n == 1 { return Some(0); }
+    let step = (coords[n - 1] - coords[0]) / (n - 1) as f64;
+    let distance = |idx: usize| {
+        let d = (coords[idx] - x).abs();
+        match period { Some(period) => d.min(period - d), None => d }
+    };
+
+    let next = coords.partition_point(|c| *c < x);
+    // the first and last elements are the neighbors across the period
+    let candidates = [next.min(n - 1), next.saturating_sub(1), 0, n - 1];
+    let best = candidates.iter().copied().min_by(|a, b| distance(*a).total_cmp(&distance(*b)))?;
+
+    if distance(best) <= step / 2.0 * 1.001 { Some(best) } else { None }
+}
@@ -0,0 +1,145 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Overlays generated procedurally by Rhai scripts (https://rhai.rs).
+//
+// The script is run every frame, separately for each view. The following variables are available:
+//
+//   time        - seconds of animation time elapsed since the script was loaded (see `data::AnimationClock`)
+//   center_lon  - longitude of the view's center (degrees)
+//   center_lat  - latitude of the view's center (degrees)
+//   zoom        - view's zoom factor
+//
+// and the following functions (all arguments are floating-point values, angles are in degrees):
+//
+//   line(lon1, lat1, lon2, lat2) - draws a line segment
+//
+// A run may generate at most `MAX_VERTICES` vertices. Line buffers of a view are recreated only when the script's
+// output for it changes; outputs of closed views are dropped.
+//
+
+use crate::data::LonLatVertex;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Max. number of operations performed by a single run of a script (protects against endless loops).
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Max. length (in degrees of longitude and latitude) of a line's individual segments.
+const MAX_SEGMENT_LENGTH: f64 = 1.0;
+
+/// Max. number of vertices generated by a single run of a script (2 per line segment).
+const MAX_VERTICES: usize = 1_000_000;
+
+/// State of a view passed to the script.
+pub struct ViewState {
+    pub center_lon: cgmath::Deg<f64>,
+    pub center_lat: cgmath::Deg<f64>,
+    pub zoom: f64
+}
+
+pub struct OverlayScript {
+    path: String,
+    engine: rhai::Engine,
+    ast: rhai::AST,
+    /// Vertices (as pairs denoting line segments) generated by the current run of the script.
+    vertices: Rc<RefCell<Vec<LonLatVertex>>>,
+    /// Vertices generated by the last run for each view (by unique id).
+    outputs: RefCell<HashMap<u32, Vec<LonLatVertex>>>,
+    /// Animation time at which the script was loaded.
+    start_time: f64
+}
+
+impl OverlayScript {
+    /// Loads the script at `path`; `time`: current animation time.
+    pub fn load(path: &str, time: f64) -> Result<OverlayScript, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+        let vertices = Rc::new(RefCell::new(vec![]));
+
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let line_vertices = Rc::clone(&vertices);
+        engine.register_result_fn("line", move |lon1: f64, lat1: f64, lon2: f64, lat2: f64| {
+            add_line(&mut line_vertices.borrow_mut(), [lon1, lat1], [lon2, lat2])
+                .map_err(|e| -> Box<rhai::EvalAltResult> { e.into() })
+        });
+
+        let ast = engine.compile(&source).map_err(|e| e.to_string())?;
+
+        Ok(OverlayScript{
+            path: path.to_string(),
+            engine,
+            ast,
+            vertices,
+            outputs: RefCell::new(HashMap::new()),
+            start_time: time
+        })
+    }
+
+    pub fn path(&self) -> &str { &self.path }
+
+    /// Runs the script for the view with `view_id` at animation time `time`; returns vertices of line segments
+    /// (as pairs), or `None` if they are the same as in the previous run for this view.
+    pub fn run(&self, view_id: u32, view_state: &ViewState, time: f64) -> Result<Option<Vec<LonLatVertex>>, String> {
+        self.vertices.borrow_mut().clear();
+
+        let mut scope = rhai::Scope::new();
+        scope.push("time", time - self.start_time);
+        scope.push("center_lon", view_state.center_lon.0);
+        scope.push("center_lat", view_state.center_lat.0);
+        scope.push("zoom", view_state.zoom);
+
+        self.engine.run_ast_with_scope(&mut scope, &self.ast).map_err(|e| e.to_string())?;
+
+        let vertices = self.vertices.replace(vec![]);
+        let mut outputs = self.outputs.borrow_mut();
+        if outputs.get(&view_id) == Some(&vertices) {
+            Ok(None)
+        } else {
+            outputs.insert(view_id, vertices.clone());
+            Ok(Some(vertices))
+        }
+    }
+
+    /// Drops the outputs of views other than `view_ids` (e.g. of closed views).
+    pub fn retain_outputs(&self, view_ids: &[u32]) {
+        self.outputs.borrow_mut().retain(|view_id, _| view_ids.contains(view_id));
+    }
+}
+
+/// Adds line from `start` to `end` (lon., lat. in degrees), subdivided into short segments,
+/// so that it follows the projection's curvature. Fails if `MAX_VERTICES` would be exceeded.
+fn add_line(vertices: &mut Vec<LonLatVertex>, start: [f64; 2], end: [f64; 2]) -> Result<(), String> {
+    let length = (end[0] - start[0]).abs().max((end[1] - start[1]).abs());
+    if !length.is_finite() {
+        return Err("line coordinates must be finite".into());
+    }
+    let num_segments = ((length / MAX_SEGMENT_LENGTH).ceil() as usize).max(1);
+    if vertices.len() + 2 * num_segments > MAX_VERTICES {
+        return Err(format!("too many line segments (max. {})", MAX_VERTICES / 2));
+    }
+
+    let point = |i: usize| {
+        let t = i as f64 / num_segments as f64;
+        LonLatVertex::new(
+            cgmath::Deg(start[0] + t * (end[0] - start[0])),
+            cgmath::Deg((start[1] + t * (end[1] - start[1])).max(-90.0).min(90.0))
+        )
+    };
+
+    for i in 0..num_segments {
+        vertices.push(point(i));
+        vertices.push(point(i + 1));
+    }
+
+    Ok(())
+}
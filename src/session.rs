@@ -0,0 +1,965 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Saving and loading of sessions (global settings and all views) as JSON files.
+//
+// Format (version 2):
+//
+//     {
+//         "version": 2,
+//         "body": "earth",                              // optional; or "moon", "mars", "mercury"
+//         "layers": {
+//             "graticule": true, "script_overlay": true, "vector_layers": true, "rivers_and_lakes": true,
+//             "plate_boundaries": true, "time_zones": true, "places": true, "outline": true, "markers": true
+//         },
+//         "views": [
+//             {
+//                 "projection": "lambert_conformal_conic",
+//                 "plugin": "Sinusoidal",                   // only if "projection" is "plugin"
+//                 "orientation": { "mode": "nsew", "center_lon": -96.0, "center_lat": 0.0 },
+//                 //          or { "mode": "free", "matrix": [[1, 0, 0], [0, 1, 0], [0, 0, 1]] } (column-major)
+//                 "zoom": 0.5,
+//                 "roll": 0.0,                              // optional; degrees
+//                 "stretch": [1.0, 1.0],                    // optional; only for cylindrical projections
+//                 "truncation": 85.0,                       // optional; only for cylindrical projections
+//                 "view_mode": "globe_texture",             // or "vector_map"
+//                 "stereo_mode": "off",                     // or "anaglyph", "side_by_side"
+//                 "graticule_labels": "off",                // or "horizontal", "along_lines"
+//                 "graticule_step": 10.0,                   // optional; degrees
+//                 "graticule_substep": 1.0,                 // optional; degrees (length of lines' segments)
+//                 "layer_overrides": { "graticule": false }, // optional; omitted layers use global settings
+//                 "params": { "ellipsoidal": 1.0, "standard_parallel_1": 33.0, "standard_parallel_2": 45.0 },
+//                 "markers": [[-96.0, 40.0]],
+//                 "lobes": [                                 // optional; only for interrupted maps
+//                     { "central_meridian": -100.0, "west": -180.0, "east": -40.0, "hemisphere": "north" }
+//                 ],
+//                 "tiled": true                              // optional; only for "peirce_quincuncial"
+//             }
+//         ]
+//     }
+//
+// "projection" is one of: albers_equal_area, adams_hemisphere, armadillo, atlantis, briesemeister, cylindrical_lambert,
+// eckert_iv, eckert_vi, gall_stereographic, general_perspective, gnomonic, guyou, hammer, lambert_azimuthal,
+// lambert_conformal_conic, littrow, loximuthal, mercator, miller, mollweide, nicolosi, orthographic,
+// peirce_quincuncial, plugin, qsc, stereographic, transverse_mercator. "params" are keyed by the names of the
+// projection's parameters (as passed to shaders); angles are in degrees, distances (e.g. "altitude" of the general
+// perspective) in kilometers.
+//
+// Every incompatible change of the format has to increment `SESSION_VERSION` and add a migration from
+// the previous version to `SESSION_FORMAT`, so that older files keep loading. The presets, markers and workspace
+// files below have their own versions and migrations; the first two include view descriptions, so a change of those
+// has to be migrated in the presets (and, as a part of the session, in the workspace) too.
+//
+// Version 1 stored the altitude and tilt of the general perspective as a separate "general_perspective" object.
+//
+// View presets (named view configurations) are stored in "presets.json" in the user's configuration directory;
+// the file can be copied between machines. Its format uses the same view description:
+//
+//     { "version": 2, "presets": [{ "name": "USA (LCC)", "view": { "projection": "lambert_conformal_conic", ... } }] }
+//
+// Named markers shown in all views are stored in "markers.json" in the user's configuration directory:
+//
+//     { "version": 1, "markers": [{ "name": "Home", "longitude": 21.0, "latitude": 52.2 }] }
+//
+// The workspace (the session on exit, with unique ids and sizes of the views' windows) is stored in "workspace.json"
+// in the user's configuration directory and restored at startup:
+//
+//     { "version": 1, "session": { "version": 2, ... }, "windows": [{ "unique_id": 3, "size": [800.0, 600.0] }] }
+//
+// Restored views get their previous unique ids, which are a part of their windows' names, so that imgui's window
+// layout (positions, sizes and docking of all windows, stored in "layout.ini") applies to them again.
+//
+
+use crate::config;
+use crate::data;
+use crate::views;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+pub const SESSION_VERSION: u32 = 2;
+
+const PRESETS_VERSION: u32 = 2;
+
+const USER_MARKERS_VERSION: u32 = 1;
+
+const WORKSPACE_VERSION: u32 = 1;
+
+/// Named presets, in the user's configuration directory (see `config::config_dir`).
+const PRESETS_FILE: &str = "presets.json";
+
+const USER_MARKERS_FILE: &str = "markers.json";
+
+const WORKSPACE_FILE: &str = "workspace.json";
+
+/// imgui's settings file (window layout).
+const LAYOUT_FILE: &str = "layout.ini";
+
+/// Converts the contents of a file to the next version of its format (except for the "version" field).
+type Migration = fn(serde_json::Value) -> Result<serde_json::Value, String>;
+
+/// Versioned JSON file format.
+struct Format {
+    /// Used in error messages.
+    name: &'static str,
+    version: u32,
+    /// `migrations[i]` converts a file of version `i + 1` to version `i + 2`.
+    migrations: &'static [Migration]
+}
+
+const SESSION_FORMAT: Format = Format{
+    name: "session",
+    version: SESSION_VERSION,
+    migrations: &[session_general_perspective_params]
+};
+
+const PRESETS_FORMAT: Format = Format{
+    name: "presets",
+    version: PRESETS_VERSION,
+    migrations: &[presets_general_perspective_params]
+};
+
+const USER_MARKERS_FORMAT: Format = Format{ name: "markers", version: USER_MARKERS_VERSION, migrations: &[] };
+
+const WORKSPACE_FORMAT: Format = Format{ name: "workspace", version: WORKSPACE_VERSION, migrations: &[] };
+
+#[derive(Serialize, Deserialize)]
+struct Session {
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    layers: LayersState,
+    views: Vec<ViewState>
+}
+
+#[derive(Serialize, Deserialize)]
+struct LayersState {
+    graticule: bool,
+    script_overlay: bool,
+    #[serde(default = "default_visible")]
+    vector_layers: bool,
+    #[serde(default = "default_visible")]
+    rivers_and_lakes: bool,
+    #[serde(default = "default_visible")]
+    plate_boundaries: bool,
+    #[serde(default = "default_visible")]
+    time_zones: bool,
+    #[serde(default = "default_visible")]
+    places: bool,
+    outline: bool,
+    markers: bool
+}
+
+fn default_visible() -> bool { true }
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct LayerOverridesState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    graticule: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    script_overlay: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vector_layers: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rivers_and_lakes: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    plate_boundaries: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_zones: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    places: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    outline: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    markers: Option<bool>
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+enum OrientationState {
+    Nsew{ center_lon: f64, center_lat: f64 },
+    Free{ matrix: [[f64; 3]; 3] }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ViewModeState { GlobeTexture, VectorMap }
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StereoModeState { Off, Anaglyph, SideBySide }
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum GraticuleLabelsState { Off, Horizontal, AlongLines }
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum HemisphereState { Both, North, South }
+
+#[derive(Clone, Serialize, Deserialize)]
+struct LobeState {
+    central_meridian: f64,
+    west: f64,
+    east: f64,
+    hemisphere: HemisphereState
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct ViewState {
+    projection: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    plugin: Option<String>,
+    orientation: OrientationState,
+    zoom: f64,
+    #[serde(default)]
+    roll: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stretch: Option<[f64; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    truncation: Option<f64>,
+    view_mode: ViewModeState,
+    stereo_mode: StereoModeState,
+    graticule_labels: GraticuleLabelsState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    graticule_step: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    graticule_substep: Option<f64>,
+    #[serde(default)]
+    layer_overrides: LayerOverridesState,
+    #[serde(default)]
+    params: BTreeMap<String, f64>,
+    #[serde(default)]
+    markers: Vec<[f64; 2]>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    lobes: Vec<LobeState>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    tiled: bool
+}
+
+/// Returns states of all views with their unique ids.
+fn all_view_states(program_data: &mut data::ProgramData) -> Vec<(u32, ViewState)> {
+    let mut view_states = vec![];
+
+    macro_rules! save_views {
+        ($views:ident, $projection:expr) => {
+            for view in program_data.$views().iter_mut() {
+                view_states.push((view.unique_id(), view_state($projection, view.base_mut())));
+            }
+        }
+    }
+
+    save_views!(albers_equal_area_views, "albers_equal_area");
+    save_views!(armadillo_views, "armadillo");
+    for view in program_data.conformal_square_views().iter_mut() {
+        let projection = match view.kind() {
+            views::ConformalSquareKind::AdamsHemisphere => "adams_hemisphere",
+            views::ConformalSquareKind::Guyou => "guyou",
+            views::ConformalSquareKind::PeirceQuincuncial => "peirce_quincuncial"
+        };
+        let mut state = view_state(projection, view.base_mut());
+        state.tiled = view.tiled();
+        view_states.push((view.unique_id(), state));
+    }
+    save_views!(cylindrical_lambert_views, "cylindrical_lambert");
+    for view in program_data.eckert_views().iter_mut() {
+        let projection = match view.kind() {
+            views::EckertKind::IV => "eckert_iv",
+            views::EckertKind::VI => "eckert_vi"
+        };
+        view_states.push((view.unique_id(), view_state(projection, view.base_mut())));
+    }
+    for view in program_data.elliptical_views().iter_mut() {
+        let projection = match view.kind() {
+            views::EllipticalKind::Mollweide => "mollweide",
+            views::EllipticalKind::Hammer => "hammer",
+            views::EllipticalKind::Briesemeister => "briesemeister",
+            views::EllipticalKind::Atlantis => "atlantis"
+        };
+        view_states.push((view.unique_id(), view_state(projection, view.base_mut())));
+    }
+    save_views!(general_perspective_views, "general_perspective");
+    save_views!(gnomonic_views, "gnomonic");
+    save_views!(lambert_azimuthal_views, "lambert_azimuthal");
+    save_views!(lambert_conformal_conic_views, "lambert_conformal_conic");
+    save_views!(littrow_views, "littrow");
+    save_views!(loximuthal_views, "loximuthal");
+    for view in program_data.mercator_views().iter_mut() {
+        let projection = match view.kind() {
+            views::MercatorKind::Mercator => "mercator",
+            views::MercatorKind::Miller => "miller",
+            views::MercatorKind::GallStereographic => "gall_stereographic"
+        };
+        view_states.push((view.unique_id(), view_state(projection, view.base_mut())));
+    }
+    save_views!(nicolosi_views, "nicolosi");
+    save_views!(orthographic_views, "orthographic");
+    for view in program_data.plugin_views().iter_mut() {
+        let mut state = view_state("plugin", view.base_mut());
+        state.plugin = Some(view.name().to_string());
+        view_states.push((view.unique_id(), state));
+    }
+    save_views!(qsc_views, "qsc");
+    save_views!(stereographic_views, "stereographic");
+    save_views!(transverse_mercator_views, "transverse_mercator");
+
+    view_states
+}
+
+pub fn save(path: &str, program_data: &mut data::ProgramData) -> Result<(), String> {
+    let (session, _) = current_session(program_data);
+    let contents = serde_json::to_string_pretty(&session).map_err(|e| e.to_string())?;
+    std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+/// Returns the current session and the unique ids of its views.
+fn current_session(program_data: &mut data::ProgramData) -> (Session, Vec<u32>) {
+    let layers = program_data.layer_visibility();
+    let (unique_ids, view_states) = all_view_states(program_data).into_iter().unzip();
+
+    let session = Session{
+        version: SESSION_VERSION,
+        body: Some(program_data.body().key.to_string()),
+        layers: LayersState{
+            graticule: layers.is_visible(views::Layer::Graticule),
+            script_overlay: layers.is_visible(views::Layer::ScriptOverlay),
+            vector_layers: layers.is_visible(views::Layer::VectorLayers),
+            rivers_and_lakes: layers.is_visible(views::Layer::RiversAndLakes),
+            plate_boundaries: layers.is_visible(views::Layer::PlateBoundaries),
+            time_zones: layers.is_visible(views::Layer::TimeZones),
+            places: layers.is_visible(views::Layer::Places),
+            outline: layers.is_visible(views::Layer::Outline),
+            markers: layers.is_visible(views::Layer::Markers)
+        },
+        views: view_states
+    };
+
+    (session, unique_ids)
+}
+
+/// Replaces all views with those of the session loaded from `path`.
+pub fn load(
+    path: &str,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) -> Result<(), String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+    let session: Session = serde_json::from_value(migrate(&SESSION_FORMAT, value)?).map_err(|e| e.to_string())?;
+
+    apply_session(&session, None, program_data, renderer, display)
+}
+
+/// Replaces all views with those of `session`; if `unique_ids` are specified, views get the corresponding ones.
+/// On error, the current views and settings are left unchanged.
+fn apply_session(
+    session: &Session,
+    unique_ids: Option<&[u32]>,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) -> Result<(), String> {
+    let body = match &session.body {
+        None => 0,
+        Some(key) => data::BODIES.iter().position(|body| body.key == key)
+            .ok_or_else(|| format!("unknown body: {}", key))?
+    };
+
+    // the new views are created next to the current ones, which are removed only once all have been created
+    let old_ids: Vec<u32> = program_data.all_views_mut().map(|view| view.unique_id()).collect();
+    let mut new_ids = vec![];
+    let mut result = Ok(());
+    for (idx, state) in session.views.iter().enumerate() {
+        match create_view(state, program_data, renderer, display) {
+            Ok(unique_id) => new_ids.push(unique_id),
+            Err(e) => {
+                result = Err(format!("view {}: {}", idx + 1, e));
+                break;
+            }
+        }
+    }
+    if result.is_ok() && data::BODIES[body] != *program_data.body() {
+        result = program_data.set_body(body, display);
+    }
+    if let Err(e) = result {
+        for id in new_ids {
+            program_data.remove_view(id);
+        }
+        return Err(e);
+    }
+
+    for id in old_ids {
+        program_data.remove_view(id);
+    }
+
+    let mut layers = views::LayerVisibility::default();
+    layers.set_visible(views::Layer::Graticule, session.layers.graticule);
+    layers.set_visible(views::Layer::ScriptOverlay, session.layers.script_overlay);
+    layers.set_visible(views::Layer::VectorLayers, session.layers.vector_layers);
+    layers.set_visible(views::Layer::RiversAndLakes, session.layers.rivers_and_lakes);
+    layers.set_visible(views::Layer::PlateBoundaries, session.layers.plate_boundaries);
+    layers.set_visible(views::Layer::TimeZones, session.layers.time_zones);
+    layers.set_visible(views::Layer::Places, session.layers.places);
+    layers.set_visible(views::Layer::Outline, session.layers.outline);
+    layers.set_visible(views::Layer::Markers, session.layers.markers);
+    program_data.set_layer_visibility(layers);
+
+    if let Some(ids) = unique_ids {
+        // the previous views are gone, so their ids can be reused
+        for (new_id, id) in new_ids.iter().zip(ids) {
+            if let Some(view) = program_data.all_views_mut().find(|view| view.unique_id() == *new_id) {
+                view.set_unique_id(*id);
+            }
+        }
+        let max_id = ids.iter().max().map_or(0, |id| id + 1);
+        program_data.set_next_unique_id(max_id.max(program_data.next_unique_id()));
+    }
+
+    Ok(())
+}
+
+/// Creates a view with default settings; `name` is a projection name as used in session files (e.g. "mercator")
+/// or the name of a plugin projection. Returns the view's unique id.
+pub fn create_default_view(
+    name: &str,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) -> Result<u32, String> {
+    if find_plugin(name, program_data).is_some() {
+        views::create_view("plugin", Some(name), program_data, renderer, display)
+    } else {
+        views::create_view(name, None, program_data, renderer, display)
+    }
+}
+
+/// Named view configuration which can be saved and used to create new views.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Preset {
+    name: String,
+    view: ViewState
+}
+
+impl Preset {
+    /// Returns the preset of the view with the specified unique id (if any).
+    pub fn from_view(name: &str, unique_id: u32, program_data: &mut data::ProgramData) -> Option<Preset> {
+        all_view_states(program_data).into_iter()
+            .find(|(id, _)| *id == unique_id)
+            .map(|(_, view)| Preset{ name: name.to_string(), view })
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+
+    /// Creates a new view as specified by the preset.
+    pub fn create_view(
+        &self,
+        program_data: &mut data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> Result<(), String> {
+        create_view(&self.view, program_data, renderer, display).map(|_| ())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PresetsFile {
+    version: u32,
+    presets: Vec<Preset>
+}
+
+/// Returns the path of the presets file in the user's configuration directory (or in the current directory
+/// if the configuration directory is unknown).
+pub fn presets_path() -> std::path::PathBuf {
+    match config::config_dir() {
+        Some(dir) => dir.join(PRESETS_FILE),
+        None => std::path::PathBuf::from(PRESETS_FILE)
+    }
+}
+
+/// Loads presets from `path`; a nonexistent file contains no presets.
+pub fn load_presets(path: &std::path::Path) -> Result<Vec<Preset>, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.to_string())
+    };
+    let value: serde_json::Value = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+    let file: PresetsFile = serde_json::from_value(migrate(&PRESETS_FORMAT, value)?).map_err(|e| e.to_string())?;
+
+    Ok(file.presets)
+}
+
+/// Saves `presets` to `path`, creating its directory if needed.
+pub fn save_presets(path: &std::path::Path, presets: &[Preset]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let file = PresetsFile{ version: PRESETS_VERSION, presets: presets.to_vec() };
+    let contents = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+#[derive(Serialize, Deserialize)]
+struct UserMarkerState {
+    name: String,
+    /// Degrees.
+    longitude: f64,
+    /// Degrees.
+    latitude: f64
+}
+
+#[derive(Serialize, Deserialize)]
+struct UserMarkersFile {
+    version: u32,
+    markers: Vec<UserMarkerState>
+}
+
+/// Returns the path of the named markers file in the user's configuration directory (or in the current directory
+/// if the configuration directory is unknown).
+pub fn user_markers_path() -> std::path::PathBuf {
+    match config::config_dir() {
+        Some(dir) => dir.join(USER_MARKERS_FILE),
+        None => std::path::PathBuf::from(USER_MARKERS_FILE)
+    }
+}
+
+/// Loads named markers from `path`; a nonexistent file contains no markers.
+pub fn load_user_markers(path: &std::path::Path) -> Result<Vec<data::UserMarker>, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.to_string())
+    };
+    let value: serde_json::Value = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+    let file: UserMarkersFile =
+        serde_json::from_value(migrate(&USER_MARKERS_FORMAT, value)?).map_err(|e| e.to_string())?;
+
+    Ok(file.markers.into_iter().map(|m| data::UserMarker{
+        name: m.name,
+        longitude: cgmath::Deg(m.longitude),
+        latitude: cgmath::Deg(m.latitude)
+    }).collect())
+}
+
+/// Saves `markers` to `path`, creating its directory if needed.
+pub fn save_user_markers(path: &std::path::Path, markers: &[data::UserMarker]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let file = UserMarkersFile{
+        version: USER_MARKERS_VERSION,
+        markers: markers.iter().map(|m| UserMarkerState{
+            name: m.name.clone(),
+            longitude: m.longitude.0,
+            latitude: m.latitude.0
+        }).collect()
+    };
+    let contents = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+#[derive(Serialize, Deserialize)]
+struct WindowState {
+    unique_id: u32,
+    /// Logical pixels.
+    size: Option<[f32; 2]>
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorkspaceFile {
+    version: u32,
+    /// Migrated as a session.
+    session: serde_json::Value,
+    /// Windows of the session's views, in the same order.
+    windows: Vec<WindowState>
+}
+
+/// Returns the path of the workspace file in the user's configuration directory (or in the current directory
+/// if the configuration directory is unknown).
+pub fn workspace_path() -> std::path::PathBuf {
+    match config::config_dir() {
+        Some(dir) => dir.join(WORKSPACE_FILE),
+        None => std::path::PathBuf::from(WORKSPACE_FILE)
+    }
+}
+
+/// Returns the path of imgui's window layout file in the user's configuration directory (or in the current
+/// directory if the configuration directory is unknown).
+pub fn layout_path() -> std::path::PathBuf {
+    match config::config_dir() {
+        Some(dir) => dir.join(LAYOUT_FILE),
+        None => std::path::PathBuf::from(LAYOUT_FILE)
+    }
+}
+
+/// Saves the current session with the views' unique ids and window sizes (`window_sizes`, by unique id) to `path`,
+/// creating its directory if needed.
+pub fn save_workspace(
+    path: &std::path::Path,
+    program_data: &mut data::ProgramData,
+    window_sizes: &HashMap<u32, [f32; 2]>
+) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let (session, unique_ids) = current_session(program_data);
+    let file = WorkspaceFile{
+        version: WORKSPACE_VERSION,
+        session: serde_json::to_value(&session).map_err(|e| e.to_string())?,
+        windows: unique_ids.into_iter()
+            .map(|unique_id| WindowState{ unique_id, size: window_sizes.get(&unique_id).copied() })
+            .collect()
+    };
+    let contents = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+/// Replaces all views with those of the workspace loaded from `path` (a nonexistent file leaves them unchanged);
+/// returns the sizes of the views' windows by unique id.
+pub fn load_workspace(
+    path: &std::path::Path,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) -> Result<HashMap<u32, [f32; 2]>, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e.to_string())
+    };
+    let value: serde_json::Value = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+    let file: WorkspaceFile = serde_json::from_value(migrate(&WORKSPACE_FORMAT, value)?).map_err(|e| e.to_string())?;
+    let session: Session =
+        serde_json::from_value(migrate(&SESSION_FORMAT, file.session)?).map_err(|e| e.to_string())?;
+    if file.windows.len() != session.views.len() {
+        return Err("numbers of windows and views differ".into());
+    }
+
+    let unique_ids: Vec<u32> = file.windows.iter().map(|window| window.unique_id).collect();
+    apply_session(&session, Some(&unique_ids), program_data, renderer, display)?;
+
+    Ok(file.windows.iter()
+        .filter_map(|window| window.size.map(|size| (window.unique_id, size)))
+        .collect())
+}
+
+/// Moves the altitude and tilt of a general perspective view (version 1) to its parameters.
+fn general_perspective_params(view: &mut serde_json::Value) {
+    let general_perspective = match view.as_object_mut().and_then(|view| view.remove("general_perspective")) {
+        Some(general_perspective) => general_perspective,
+        None => return
+    };
+    if view.get("params").map_or(true, |params| !params.is_object()) {
+        view["params"] = serde_json::json!({});
+    }
+    for name in ["altitude", "tilt"] {
+        if let Some(value) = general_perspective.get(name) {
+            view["params"][name] = value.clone();
+        }
+    }
+}
+
+fn session_general_perspective_params(mut value: serde_json::Value) -> Result<serde_json::Value, String> {
+    if let Some(views) = value.get_mut("views").and_then(|views| views.as_array_mut()) {
+        views.iter_mut().for_each(general_perspective_params);
+    }
+    Ok(value)
+}
+
+fn presets_general_perspective_params(mut value: serde_json::Value) -> Result<serde_json::Value, String> {
+    if let Some(presets) = value.get_mut("presets").and_then(|presets| presets.as_array_mut()) {
+        for preset in presets {
+            if let Some(view) = preset.get_mut("view") {
+                general_perspective_params(view);
+            }
+        }
+    }
+    Ok(value)
+}
+
+/// Converts a file of `format` of any supported version to the current version.
+fn migrate(format: &Format, mut value: serde_json::Value) -> Result<serde_json::Value, String> {
+    debug_assert_eq!(format.migrations.len() + 1, format.version as usize);
+
+    let version = value.get("version")
+        .and_then(|version| version.as_u64())
+        .ok_or_else(|| format!("missing {} version", format.name))?;
+
+    if version == 0 || version > format.version as u64 {
+        return Err(format!(
+            "unsupported {} version {} (this program supports versions up to {})", format.name, version, format.version
+        ));
+    }
+
+    for (idx, migration) in format.migrations.iter().enumerate().skip((version - 1) as usize) {
+        value = migration(value)?;
+        value["version"] = serde_json::Value::from(idx as u32 + 2);
+    }
+
+    Ok(value)
+}
+
+fn view_state(projection: &str, view: &views::ViewBase) -> ViewState {
+    let orientation = match view.drag_rotation() {
+        views::DragRotation::NSEW => {
+            let (center_lon, center_lat) = view.central_lonlat();
+            OrientationState::Nsew{ center_lon: center_lon.0, center_lat: center_lat.0 }
+        },
+        views::DragRotation::Free => OrientationState::Free{ matrix: cgmath::Matrix3::from(*view.orientation()).into() }
+    };
+
+    let layer_overrides = view.layer_overrides();
+
+    ViewState{
+        projection: projection.to_string(),
+        plugin: None,
+        orientation,
+        zoom: view.zoom(),
+        roll: cgmath::Deg::from(view.roll()).0,
+        stretch: view.stretch(),
+        truncation: view.truncation().map(|latitude| latitude.0),
+        view_mode: match view.view_mode() {
+            views::ViewMode::GlobeTexture => ViewModeState::GlobeTexture,
+            views::ViewMode::VectorMap => ViewModeState::VectorMap
+        },
+        stereo_mode: match view.stereo_mode() {
+            views::StereoMode::Off => StereoModeState::Off,
+            views::StereoMode::Anaglyph => StereoModeState::Anaglyph,
+            views::StereoMode::SideBySide => StereoModeState::SideBySide
+        },
+        graticule_labels: match view.graticule_labels {
+            views::GraticuleLabels::Off => GraticuleLabelsState::Off,
+            views::GraticuleLabels::Horizontal => GraticuleLabelsState::Horizontal,
+            views::GraticuleLabels::AlongLines => GraticuleLabelsState::AlongLines
+        },
+        graticule_step: Some(view.graticule_step().0),
+        graticule_substep: Some(view.graticule_substep().0),
+        layer_overrides: LayerOverridesState{
+            graticule: layer_overrides.get(views::Layer::Graticule),
+            script_overlay: layer_overrides.get(views::Layer::ScriptOverlay),
+            vector_layers: layer_overrides.get(views::Layer::VectorLayers),
+            rivers_and_lakes: layer_overrides.get(views::Layer::RiversAndLakes),
+            plate_boundaries: layer_overrides.get(views::Layer::PlateBoundaries),
+            time_zones: layer_overrides.get(views::Layer::TimeZones),
+            places: layer_overrides.get(views::Layer::Places),
+            outline: layer_overrides.get(views::Layer::Outline),
+            markers: layer_overrides.get(views::Layer::Markers)
+        },
+        params: view.projection_params().iter().map(|p| (p.uniform_name.to_string(), p.value)).collect(),
+        markers: view.markers().iter().map(|(lon, lat)| [lon.0, lat.0]).collect(),
+        lobes: view.lobes().unwrap_or(&[]).iter().map(|lobe| LobeState{
+            central_meridian: lobe.central_meridian.0,
+            west: lobe.west.0,
+            east: lobe.east.0,
+            hemisphere: match lobe.hemisphere {
+                views::Hemisphere::Both => HemisphereState::Both,
+                views::Hemisphere::North => HemisphereState::North,
+                views::Hemisphere::South => HemisphereState::South
+            }
+        }).collect(),
+        tiled: false
+    }
+}
+
+fn apply_view_state(state: &ViewState, view: &mut views::ViewBase) -> Result<(), String> {
+    match state.orientation {
+        OrientationState::Nsew{ center_lon, center_lat } => {
+            view.set_drag_rotation(views::DragRotation::NSEW);
+            view.center_on(cgmath::Deg(center_lon), cgmath::Deg(center_lat));
+        },
+        OrientationState::Free{ matrix } => {
+            view.set_drag_rotation(views::DragRotation::Free);
+            view.set_orientation(cgmath::Basis3::from(cgmath::Quaternion::from(cgmath::Matrix3::from(matrix))));
+        }
+    }
+
+    if state.zoom > 0.0 {
+        view.zoom_by(state.zoom / view.zoom());
+    }
+    view.set_roll(cgmath::Deg(state.roll).into());
+    if let Some(stretch) = state.stretch {
+        view.set_stretch(stretch);
+    }
+    view.set_truncation(state.truncation.map(cgmath::Deg));
+
+    view.set_view_mode(match state.view_mode {
+        ViewModeState::GlobeTexture => views::ViewMode::GlobeTexture,
+        ViewModeState::VectorMap => views::ViewMode::VectorMap
+    });
+    view.set_stereo_mode(match state.stereo_mode {
+        StereoModeState::Off => views::StereoMode::Off,
+        StereoModeState::Anaglyph => views::StereoMode::Anaglyph,
+        StereoModeState::SideBySide => views::StereoMode::SideBySide
+    });
+    view.graticule_labels = match state.graticule_labels {
+        GraticuleLabelsState::Off => views::GraticuleLabels::Off,
+        GraticuleLabelsState::Horizontal => views::GraticuleLabels::Horizontal,
+        GraticuleLabelsState::AlongLines => views::GraticuleLabels::AlongLines
+    };
+    if state.graticule_step.is_some() || state.graticule_substep.is_some() {
+        view.set_graticule(
+            state.graticule_step.map(cgmath::Deg).unwrap_or_else(|| view.graticule_step()),
+            state.graticule_substep.map(cgmath::Deg).unwrap_or_else(|| view.graticule_substep())
+        )?;
+    }
+
+    let mut layer_overrides = views::LayerOverrides::default();
+    layer_overrides.set(views::Layer::Graticule, state.layer_overrides.graticule);
+    layer_overrides.set(views::Layer::ScriptOverlay, state.layer_overrides.script_overlay);
+    layer_overrides.set(views::Layer::VectorLayers, state.layer_overrides.vector_layers);
+    layer_overrides.set(views::Layer::RiversAndLakes, state.layer_overrides.rivers_and_lakes);
+    layer_overrides.set(views::Layer::PlateBoundaries, state.layer_overrides.plate_boundaries);
+    layer_overrides.set(views::Layer::TimeZones, state.layer_overrides.time_zones);
+    layer_overrides.set(views::Layer::Places, state.layer_overrides.places);
+    layer_overrides.set(views::Layer::Outline, state.layer_overrides.outline);
+    layer_overrides.set(views::Layer::Markers, state.layer_overrides.markers);
+    view.set_layer_overrides(layer_overrides);
+
+    for idx in 0..view.projection_params().len() {
+        if let Some(value) = state.params.get(view.projection_params()[idx].uniform_name) {
+            view.set_projection_param_value(idx, *value);
+        }
+    }
+
+    for [lon, lat] in &state.markers {
+        view.add_marker(cgmath::Deg(*lon), cgmath::Deg(*lat));
+    }
+
+    if !state.lobes.is_empty() {
+        view.set_lobes(state.lobes.iter().map(|lobe| views::Lobe{
+            central_meridian: cgmath::Deg(lobe.central_meridian),
+            west: cgmath::Deg(lobe.west),
+            east: cgmath::Deg(lobe.east),
+            hemisphere: match lobe.hemisphere {
+                HemisphereState::Both => views::Hemisphere::Both,
+                HemisphereState::North => views::Hemisphere::North,
+                HemisphereState::South => views::Hemisphere::South
+            }
+        }).collect())?;
+    }
+
+    Ok(())
+}
+
+fn find_plugin<'a>(name: &str, program_data: &'a data::ProgramData) -> Option<&'a data::PluginData> {
+    program_data.plugins.iter().find(|p| p.plugin.name == name && p.gl_programs.is_some())
+}
+
+/// Creates a view as specified by `state`; returns its unique id. On error, no view is created.
+fn create_view(
+    state: &ViewState,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) -> Result<u32, String> {
+    let unique_id = views::create_view(&state.projection, state.plugin.as_deref(), program_data, renderer, display)?;
+
+    if let Some(view) = program_data.conformal_square_views().iter_mut().find(|view| view.unique_id() == unique_id) {
+        view.set_tiled(state.tiled);
+    }
+    let view = program_data.all_views_mut().find(|view| view.unique_id() == unique_id).unwrap();
+    if let Err(e) = apply_view_state(state, view) {
+        program_data.remove_view(unique_id);
+        return Err(e);
+    }
+
+    Ok(unique_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FORMAT: Format = Format{ name: "test", version: 3, migrations: &[add_a, add_b] };
+
+    fn add_a(mut value: serde_json::Value) -> Result<serde_json::Value, String> {
+        value["a"] = serde_json::Value::from(1);
+        Ok(value)
+    }
+
+    fn add_b(mut value: serde_json::Value) -> Result<serde_json::Value, String> {
+        value["b"] = serde_json::Value::from(2);
+        Ok(value)
+    }
+
+    #[test]
+    fn migrate_accepts_current_version() {
+        for format in [&SESSION_FORMAT, &PRESETS_FORMAT, &USER_MARKERS_FORMAT, &WORKSPACE_FORMAT] {
+            let file = serde_json::json!({ "version": format.version, "contents": [] });
+            assert_eq!(migrate(format, file.clone()).unwrap(), file);
+        }
+    }
+
+    #[test]
+    fn migrate_applies_subsequent_migrations() {
+        assert_eq!(
+            migrate(&TEST_FORMAT, serde_json::json!({ "version": 1 })).unwrap(),
+            serde_json::json!({ "version": 3, "a": 1, "b": 2 })
+        );
+        assert_eq!(
+            migrate(&TEST_FORMAT, serde_json::json!({ "version": 2 })).unwrap(),
+            serde_json::json!({ "version": 3, "b": 2 })
+        );
+    }
+
+    #[test]
+    fn migrate_moves_general_perspective_settings_to_params() {
+        let session = serde_json::json!({
+            "version": 1,
+            "views": [
+                { "projection": "general_perspective", "general_perspective": { "altitude": 500.0, "tilt": 30.0 } },
+                { "projection": "mercator", "params": { "ellipsoidal": 1.0 } }
+            ]
+        });
+        assert_eq!(
+            migrate(&SESSION_FORMAT, session).unwrap(),
+            serde_json::json!({
+                "version": 2,
+                "views": [
+                    { "projection": "general_perspective", "params": { "altitude": 500.0, "tilt": 30.0 } },
+                    { "projection": "mercator", "params": { "ellipsoidal": 1.0 } }
+                ]
+            })
+        );
+
+        let presets = serde_json::json!({
+            "version": 1,
+            "presets": [{
+                "name": "GOES",
+                "view": {
+                    "projection": "general_perspective",
+                    "general_perspective": { "altitude": 35786.0, "tilt": 0.0 }
+                }
+            }]
+        });
+        assert_eq!(
+            migrate(&PRESETS_FORMAT, presets).unwrap()["presets"][0]["view"]["params"],
+            serde_json::json!({ "altitude": 35786.0, "tilt": 0.0 })
+        );
+    }
+
+    #[test]
+    fn migrate_rejects_unsupported_versions() {
+        assert!(migrate(&SESSION_FORMAT, serde_json::json!({ "views": [] })).is_err());
+        assert!(migrate(&SESSION_FORMAT, serde_json::json!({ "version": 0 })).is_err());
+        assert!(migrate(&SESSION_FORMAT, serde_json::json!({ "version": SESSION_VERSION + 1 })).is_err());
+        assert!(migrate(&TEST_FORMAT, serde_json::json!({ "version": 4 })).is_err());
+    }
+}
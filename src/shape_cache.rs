@@ -0,0 +1,323 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Binary cache of polylines read from shapefiles.
+//
+// Parsing a large shapefile (e.g. the 1:10m coastline) takes much longer than reading its polylines back from
+// a flat binary file. After the first parse, polylines are written to `shape_cache` in the program's configuration
+// directory, in a file named after the shapefile's path; the cache is used as long as the SHA-256 digest of
+// the shapefile's `.shp`, `.shx` and `.dbf` files matches the one stored in it (hashing them is much faster than
+// parsing, and unlike their lengths and modification times, also detects files replaced with preserved timestamps).
+//
+// The cache also contains vertices and indices of the polylines' line segments (see `data::polyline_segments`).
+// The file is memory-mapped, and they are uploaded to GL buffers straight from the mapping.
+//
+// Cache file format (little-endian; every section starts at a multiple of 4 bytes, so that the mapped vertices and
+// indices can be used in place):
+//
+//     magic: [u8; 8]              "PRJSHPC3"
+//     digest: [u8; 32]            SHA-256 digest of the shapefile (see `SourceDigest`)
+//     num_polylines: u64
+//     num_vertices: u64
+//     num_indices: u64
+//     polyline_lengths: [u64; num_polylines]
+//     points: [f64; 2 * num_vertices]        longitude, latitude (degrees)
+//     vertices: [f32; 2 * num_vertices]      `data::LonLatVertex`
+//     indices: [u32; num_indices]
+//
+
+use crate::config;
+use crate::data::{self, LonLatVertex};
+use crate::geojson::Polyline;
+use sha2::Digest;
+use std::convert::TryInto;
+use std::ops::Range;
+
+const MAGIC: &[u8; 8] = b"PRJSHPC3";
+
+const CACHE_SUBDIR: &str = "shape_cache";
+
+/// Files of a shapefile which determine its cached contents (the `.shx` and `.dbf` ones are optional).
+const HASHED_EXTENSIONS: [&str; 3] = ["shp", "shx", "dbf"];
+
+/// Polylines of a shapefile with the vertices and indices of their line segments.
+pub struct Shapes {
+    pub polylines: Vec<Polyline>,
+    segments: Segments
+}
+
+enum Segments {
+    /// Stored in a memory-mapped cache file (at the specified byte ranges).
+    Mapped{ map: memmap2::Mmap, vertices: Range<usize>, indices: Range<usize> },
+    Owned{ vertices: Vec<LonLatVertex>, indices: Vec<u32> }
+}
+
+impl Shapes {
+    pub fn vertices(&self) -> &[LonLatVertex] {
+        match &self.segments {
+            // checked by `load`
+            Segments::Mapped{ map, vertices, .. } => cast(&map[vertices.clone()]).unwrap(),
+            Segments::Owned{ vertices, .. } => vertices
+        }
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        match &self.segments {
+            Segments::Mapped{ map, indices, .. } => cast(&map[indices.clone()]).unwrap(),
+            Segments::Owned{ indices, .. } => indices
+        }
+    }
+}
+
+/// SHA-256 digest of the `.shp`, `.shx` and `.dbf` files of a shapefile (and of whether the latter two exist).
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct SourceDigest([u8; 32]);
+
+impl SourceDigest {
+    fn of(path: &str) -> Result<SourceDigest, String> {
+        let path = std::path::Path::new(path);
+        let mut hasher = sha2::Sha256::new();
+        for (idx, extension) in HASHED_EXTENSIONS.iter().enumerate() {
+            let file_path = if idx == 0 { path.to_path_buf() } else { path.with_extension(extension) };
+            match std::fs::File::open(&file_path) {
+                Ok(mut file) => {
+                    hasher.update([1]);
+                    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("{}: {}", file_path.display(), e))?;
+                },
+                Err(e) if idx == 0 => return Err(format!("{}: {}", file_path.display(), e)),
+                Err(_) => hasher.update([0])
+            }
+        }
+
+        let mut digest = [0; 32];
+        digest.copy_from_slice(&hasher.finalize());
+
+        Ok(SourceDigest(digest))
+    }
+}
+
+/// Returns shapes of the shapefile at `path` from the cache, or reads their polylines with `read` (and caches them).
+pub fn cached<F>(path: &str, read: F) -> Result<Shapes, String>
+    where F: FnOnce(&str) -> Result<Vec<Polyline>, String>
+{
+    let digest = SourceDigest::of(path)?;
+    let cache_path = cache_path(path);
+
+    if let Some(shapes) = cache_path.as_ref().and_then(|cache_path| load(cache_path, digest)) {
+        return Ok(shapes);
+    }
+
+    let polylines = read(path)?;
+    let (vertices, indices) = data::polyline_segments(&polylines);
+
+    if let Some(cache_path) = &cache_path {
+        // the old cache file may still be mapped (also by another instance of the program), so it is replaced
+        // rather than overwritten
+        let temp_path = cache_path.with_extension("tmp");
+        let result = std::fs::create_dir_all(cache_path.parent().unwrap())
+            .and_then(|_| std::fs::write(&temp_path, encode(&polylines, &vertices, &indices, digest)))
+            .and_then(|_| std::fs::rename(&temp_path, cache_path));
+        if let Err(e) = result {
+            eprintln!("Failed to cache {} in {}: {}", path, cache_path.display(), e);
+        }
+    }
+
+    Ok(Shapes{ polylines, segments: Segments::Owned{ vertices, indices } })
+}
+
+/// Returns the path of the cache file of the shapefile at `path` (if the configuration directory is known).
+fn cache_path(path: &str) -> Option<std::path::PathBuf> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.into());
+    let path_hash = data::fnv1a(path.to_string_lossy().as_bytes());
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+
+    config::config_dir().map(|dir| dir.join(CACHE_SUBDIR).join(format!("{}_{:016x}.bin", stem, path_hash)))
+}
+
+/// Returns shapes from the cache file at `cache_path`, if it is valid for a shapefile with `digest`.
+fn load(cache_path: &std::path::Path, digest: SourceDigest) -> Option<Shapes> {
+    let file = std::fs::File::open(cache_path).ok()?;
+    // cache files are never modified in place (see `cached`)
+    let map = unsafe { memmap2::Mmap::map(&file) }.ok()?;
+    let Decoded{ polylines, vertices, indices } = decode(&map, digest)?;
+    // a mapping is page-aligned, so this fails only on big-endian platforms
+    cast::<LonLatVertex>(&map[vertices.clone()])?;
+    cast::<u32>(&map[indices.clone()])?;
+
+    Some(Shapes{ polylines, segments: Segments::Mapped{ map, vertices, indices } })
+}
+
+fn encode(polylines: &[Polyline], vertices: &[LonLatVertex], indices: &[u32], digest: SourceDigest) -> Vec<u8> {
+    let mut data = Vec::with_capacity(64 + 8 * polylines.len() + 24 * vertices.len() + 4 * indices.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&digest.0);
+    for value in [polylines.len() as u64, vertices.len() as u64, indices.len() as u64] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    for polyline in polylines {
+        data.extend_from_slice(&(polyline.len() as u64).to_le_bytes());
+    }
+    for point in polylines.iter().flatten() {
+        data.extend_from_slice(&point[0].to_le_bytes());
+        data.extend_from_slice(&point[1].to_le_bytes());
+    }
+    for vertex in vertices {
+        data.extend_from_slice(&vertex.lonlat_position[0].to_le_bytes());
+        data.extend_from_slice(&vertex.lonlat_position[1].to_le_bytes());
+    }
+    for index in indices {
+        data.extend_from_slice(&index.to_le_bytes());
+    }
+
+    data
+}
+
+/// Contents of a cache file.
+struct Decoded {
+    polylines: Vec<Polyline>,
+    /// Byte range of the vertices.
+    vertices: Range<usize>,
+    /// Byte range of the indices.
+    indices: Range<usize>
+}
+
+/// Returns `None` if `data` is not a valid cache of a shapefile with `digest`.
+fn decode(data: &[u8], digest: SourceDigest) -> Option<Decoded> {
+    let mut offset = 0;
+    let read_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+    let read_f64 = |bytes: &[u8]| f64::from_le_bytes(bytes.try_into().unwrap());
+
+    if take(data, &mut offset, MAGIC.len())? != MAGIC {
+        return None;
+    }
+    if take(data, &mut offset, digest.0.len())? != digest.0 {
+        return None;
+    }
+    let mut header = [0u64; 3];
+    for value in &mut header {
+        *value = read_u64(take(data, &mut offset, 8)?);
+    }
+    let [num_polylines, num_vertices, num_indices] = header;
+    let (num_polylines, num_vertices, num_indices) =
+        (num_polylines as usize, num_vertices as usize, num_indices as usize);
+
+    let lengths = take(data, &mut offset, num_polylines.checked_mul(8)?)?;
+    let points = take(data, &mut offset, num_vertices.checked_mul(16)?)?;
+    let vertices = offset..offset.checked_add(num_vertices.checked_mul(8)?)?;
+    take(data, &mut offset, vertices.len())?;
+    let indices = offset..offset.checked_add(num_indices.checked_mul(4)?)?;
+    let index_data = take(data, &mut offset, indices.len())?;
+    if offset != data.len() { return None; }
+
+    // indices of a damaged file must not make GL read outside of the vertex buffer
+    let read_u32 = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+    if index_data.chunks_exact(4).any(|index| read_u32(index) as usize >= num_vertices) {
+        return None;
+    }
+
+    let mut points = points.chunks_exact(16).map(|p| [read_f64(&p[..8]), read_f64(&p[8..])]);
+    let mut polylines = Vec::with_capacity(num_polylines);
+    for length in lengths.chunks_exact(8) {
+        let length = read_u64(length) as usize;
+        let polyline: Polyline = points.by_ref().take(length).collect();
+        if polyline.len() != length { return None; }
+        polylines.push(polyline);
+    }
+    if points.next().is_some() { return None; }
+
+    Some(Decoded{ polylines, vertices, indices })
+}
+
+/// Returns `len` bytes of `data` starting at `offset` (advanced past them).
+fn take<'a>(data: &'a [u8], offset: &mut usize, len: usize) -> Option<&'a [u8]> {
+    let bytes = data.get(*offset..offset.checked_add(len)?)?;
+    *offset += len;
+
+    Some(bytes)
+}
+
+/// Reinterprets little-endian `bytes` as values of `T` (`LonLatVertex` or `u32`, for which every bit pattern is valid);
+/// returns `None` if they are not aligned for `T` or the platform is big-endian.
+fn cast<T: Copy>(bytes: &[u8]) -> Option<&[T]> {
+    if cfg!(target_endian = "big") { return None; }
+
+    let (prefix, values, suffix) = unsafe { bytes.align_to::<T>() };
+    if prefix.is_empty() && suffix.is_empty() { Some(values) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: SourceDigest = SourceDigest([7; 32]);
+
+    fn polylines() -> Vec<Polyline> {
+        vec![vec![[-10.5, 20.25], [30.0, -40.0], [179.9, 89.9]], vec![], vec![[0.0, 0.0]]]
+    }
+
+    fn encoded() -> Vec<u8> {
+        let (vertices, indices) = data::polyline_segments(&polylines());
+        encode(&polylines(), &vertices, &indices, DIGEST)
+    }
+
+    #[test]
+    fn decode_reverses_encode() {
+        let data = encoded();
+        let decoded = decode(&data, DIGEST).unwrap();
+        assert_eq!(decoded.polylines, polylines());
+        assert_eq!(decoded.vertices.len(), 4 * 8);
+        let indices: Vec<u32> = data[decoded.indices].chunks_exact(4)
+            .map(|index| u32::from_le_bytes(index.try_into().unwrap()))
+            .collect();
+        assert_eq!(indices, vec![0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn decode_rejects_stale_or_damaged_cache() {
+        let data = encoded();
+        let mut other_digest = DIGEST;
+        other_digest.0[31] = 8;
+        assert!(decode(&data, other_digest).is_none());
+        assert!(decode(&data[..data.len() - 1], DIGEST).is_none());
+        let mut extended = data.clone();
+        extended.push(0);
+        assert!(decode(&extended, DIGEST).is_none());
+        let mut bad_magic = data.clone();
+        bad_magic[0] = b'X';
+        assert!(decode(&bad_magic, DIGEST).is_none());
+        let mut bad_index = data;
+        let last = bad_index.len() - 4;
+        bad_index[last..].copy_from_slice(&4u32.to_le_bytes());
+        assert!(decode(&bad_index, DIGEST).is_none());
+    }
+
+    #[test]
+    fn digest_covers_all_files_of_shapefile() {
+        let dir = std::env::temp_dir().join(format!("projections_shape_cache_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let shp = dir.join("test.shp");
+        let shp_path = shp.to_str().unwrap();
+
+        std::fs::write(&shp, b"shapes 1").unwrap();
+        let digest = SourceDigest::of(shp_path).unwrap();
+        // same length (and possibly the same modification time)
+        std::fs::write(&shp, b"shapes 2").unwrap();
+        let replaced = SourceDigest::of(shp_path).unwrap();
+        std::fs::write(shp.with_extension("dbf"), b"").unwrap();
+        let with_dbf = SourceDigest::of(shp_path).unwrap();
+        std::fs::write(shp.with_extension("dbf"), b"records").unwrap();
+        let changed_dbf = SourceDigest::of(shp_path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_ne!(digest, replaced);
+        assert_ne!(replaced, with_dbf);
+        assert_ne!(with_dbf, changed_dbf);
+        assert!(SourceDigest::of(shp_path).is_err());
+    }
+}
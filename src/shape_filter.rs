@@ -0,0 +1,163 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Filtering of shapefile features by their attributes (DBF records), e.g. `scalerank <= 4`, which thins out dense
+// datasets. An attribute is compared numerically if both it and the filter's value are numbers, otherwise as text;
+// features without the attribute are filtered out.
+//
+
+use crate::geojson::Polyline;
+use shapefile::dbase::FieldValue;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater
+}
+
+impl Comparison {
+    pub const ALL: [Comparison; 6] = [
+        Comparison::Less,
+        Comparison::LessOrEqual,
+        Comparison::Equal,
+        Comparison::NotEqual,
+        Comparison::GreaterOrEqual,
+        Comparison::Greater
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Greater => ">"
+        }
+    }
+
+    fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Less => ordering == Ordering::Less,
+            Comparison::LessOrEqual => ordering != Ordering::Greater,
+            Comparison::Equal => ordering == Ordering::Equal,
+            Comparison::NotEqual => ordering != Ordering::Equal,
+            Comparison::GreaterOrEqual => ordering != Ordering::Less,
+            Comparison::Greater => ordering == Ordering::Greater
+        }
+    }
+}
+
+/// Predicate on an attribute of features, e.g. `scalerank <= 4`.
+#[derive(Clone, PartialEq)]
+pub struct AttributeFilter {
+    pub attribute: String,
+    pub comparison: Comparison,
+    pub value: String
+}
+
+impl std::fmt::Display for AttributeFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.attribute, self.comparison.symbol(), self.value)
+    }
+}
+
+impl AttributeFilter {
+    fn matches(&self, feature: &Feature) -> bool {
+        let value = self.value.trim();
+        let ordering = match feature.attributes.get(&self.attribute) {
+            Some(AttributeValue::Number(number)) => match value.parse::<f64>() {
+                Ok(value) => number.partial_cmp(&value),
+                Err(_) => Some(number.to_string().as_str().cmp(value))
+            },
+            Some(AttributeValue::Text(text)) => Some(text.trim().cmp(value)),
+            None => None
+        };
+
+        ordering.map_or(false, |ordering| self.comparison.holds(ordering))
+    }
+}
+
+enum AttributeValue {
+    Number(f64),
+    Text(String)
+}
+
+/// Lines of a shapefile's feature (polylines or polygon rings) and its attributes.
+struct Feature {
+    lines: Vec<Polyline>,
+    attributes: HashMap<String, AttributeValue>
+}
+
+/// Features of a shapefile.
+pub struct Features {
+    features: Vec<Feature>,
+    /// Names of attributes of the features (sorted).
+    pub attributes: Vec<String>
+}
+
+/// Loads features (polylines and polygons) of a shapefile with their attributes.
+pub fn load(path: &str) -> Result<Features, String> {
+    let mut reader = shapefile::Reader::from_path(path).map_err(|e| e.to_string())?;
+
+    let mut features = vec![];
+    for shape_record in reader.iter_shapes_and_records() {
+        let (shape, record) = shape_record.map_err(|e| e.to_string())?;
+        let lines: Vec<Polyline> = match shape {
+            shapefile::Shape::Polyline(polyline) => polyline.parts().iter()
+                .map(|part| part.iter().map(|point| [point.x, point.y]).collect())
+                .collect(),
+            shapefile::Shape::Polygon(polygon) => polygon.rings().iter()
+                .map(|ring| ring.points().iter().map(|point| [point.x, point.y]).collect())
+                .collect(),
+            _ => continue
+        };
+        let attributes = record.into_iter()
+            .filter_map(|(name, value)| attribute_value(value).map(|value| (name, value)))
+            .collect();
+        features.push(Feature{ lines, attributes });
+    }
+
+    let mut attributes: Vec<String> = features.iter().flat_map(|feature| feature.attributes.keys().cloned()).collect();
+    attributes.sort();
+    attributes.dedup();
+
+    Ok(Features{ features, attributes })
+}
+
+fn attribute_value(value: FieldValue) -> Option<AttributeValue> {
+    match value {
+        FieldValue::Character(text) => text.map(AttributeValue::Text),
+        FieldValue::Numeric(number) => number.map(AttributeValue::Number),
+        FieldValue::Float(number) => number.map(|number| AttributeValue::Number(number as f64)),
+        FieldValue::Integer(number) => Some(AttributeValue::Number(number as f64)),
+        FieldValue::Double(number) | FieldValue::Currency(number) => Some(AttributeValue::Number(number)),
+        FieldValue::Logical(value) => value.map(|value| AttributeValue::Text(if value { "T" } else { "F" }.into())),
+        _ => None
+    }
+}
+
+impl Features {
+    pub fn len(&self) -> usize { self.features.len() }
+
+    /// Returns lines of the features matching `filter` (all if `None`) and the number of such features.
+    pub fn lines(&self, filter: Option<&AttributeFilter>) -> (Vec<Polyline>, usize) {
+        let matching: Vec<&Feature> = self.features.iter()
+            .filter(|feature| filter.map_or(true, |filter| filter.matches(feature)))
+            .collect();
+
+        (matching.iter().flat_map(|feature| feature.lines.iter().cloned()).collect(), matching.len())
+    }
+}
@@ -0,0 +1,128 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Position of the Sun and UTC date/time conversions.
+//
+// The subsolar point is computed with the low-precision formulas of the Astronomical Almanac (accurate to about
+// 0.01° between 1950 and 2050), which is plenty for showing the day/night terminator.
+//
+
+/// Date and time (UTC) in the proleptic Gregorian calendar.
+#[derive(Copy, Clone, PartialEq)]
+pub struct UtcDateTime {
+    pub year: i32,
+    /// From 1 to 12.
+    pub month: u32,
+    /// From 1 to 31.
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32
+}
+
+/// Returns the number of days since 1970-01-01 of the specified date.
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    // after H. Hinnant, "chrono-Compatible Low-Level Date Algorithms"
+    let year = if month <= 2 { year as i64 - 1 } else { year as i64 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+/// Returns the date (year, month, day) of the specified number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year as i32, month, day)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 => if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 { 29 } else { 28 },
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    }
+}
+
+impl UtcDateTime {
+    /// Returns the current date and time.
+    pub fn now() -> UtcDateTime {
+        let since_epoch = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+        UtcDateTime::from_unix_time(since_epoch.as_secs() as i64)
+    }
+
+    /// Creates the date and time from the number of seconds since 1970-01-01 00:00 UTC.
+    pub fn from_unix_time(time: i64) -> UtcDateTime {
+        let (year, month, day) = civil_from_days(time.div_euclid(86400));
+        let seconds = time.rem_euclid(86400) as u32;
+
+        UtcDateTime{ year, month, day, hour: seconds / 3600, minute: seconds / 60 % 60, second: seconds % 60 }
+    }
+
+    /// Returns the number of seconds since 1970-01-01 00:00 UTC.
+    pub fn unix_time(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * 86400
+            + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.month < 1 || self.month > 12 {
+            return Err("month has to be between 1 and 12".into());
+        }
+        if self.day < 1 || self.day > days_in_month(self.year, self.month) {
+            return Err(format!("day has to be between 1 and {}", days_in_month(self.year, self.month)));
+        }
+        if self.hour > 23 || self.minute > 59 || self.second > 59 {
+            return Err("invalid time".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the longitude and latitude of the point where the Sun is at the zenith at `time`.
+pub fn subsolar_point(time: &UtcDateTime) -> (cgmath::Deg<f64>, cgmath::Deg<f64>) {
+    // days since J2000.0 (2000-01-01 12:00 UT)
+    let d = time.unix_time() as f64 / 86400.0 - 10957.5;
+
+    let mean_anomaly = (357.529 + 0.98560028 * d).to_radians();
+    let mean_longitude = 280.459 + 0.98564736 * d;
+    let ecliptic_longitude =
+        (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin()).to_radians();
+    let obliquity = (23.439 - 0.00000036 * d).to_radians();
+
+    let right_ascension = (obliquity.cos() * ecliptic_longitude.sin()).atan2(ecliptic_longitude.cos());
+    let declination = (obliquity.sin() * ecliptic_longitude.sin()).asin();
+
+    // Greenwich mean sidereal time (degrees)
+    let gmst = 280.46061837 + 360.98564736629 * d;
+
+    let longitude = (right_ascension.to_degrees() - gmst + 180.0).rem_euclid(360.0) - 180.0;
+
+    (cgmath::Deg(longitude), cgmath::Deg(declination.to_degrees()))
+}
+
+/// Returns the altitude of the Sun above the horizon at (`longitude`, `latitude`) (all in radians),
+/// given the subsolar point (neglecting refraction and the Sun's radius).
+pub fn sun_altitude(subsolar_lon: f64, subsolar_lat: f64, longitude: f64, latitude: f64) -> f64 {
+    (latitude.sin() * subsolar_lat.sin() + latitude.cos() * subsolar_lat.cos() * (longitude - subsolar_lon).cos())
+        .max(-1.0).min(1.0).asin()
+}
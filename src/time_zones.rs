@@ -0,0 +1,123 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Time-zone boundaries loaded from the timezone-boundary-builder dataset
+// (https://github.com/evansiroky/timezone-boundary-builder): a GeoJSON FeatureCollection of (multi)polygons
+// with the IANA time zone identifier in the property "tzid", either as is or zipped (as released, e.g.
+// "timezones-with-oceans.geojson.zip").
+//
+// UTC offsets of zones are determined from the IANA time zone database (via `chrono-tz`) for a given time,
+// so they include daylight saving time in effect then.
+//
+
+use crate::geojson::{self, Polyline};
+use chrono::{Offset, TimeZone as _};
+use serde_json::Value;
+use std::io::Read;
+
+pub struct TimeZone {
+    /// IANA identifier (e.g. "Europe/Warsaw").
+    pub tzid: String,
+    /// Boundary rings of the zone's polygons.
+    pub boundary: Vec<Polyline>,
+    /// Position (longitude, latitude in degrees) of the zone's label: the centroid of its largest ring.
+    pub label_position: [f64; 2],
+    /// Area (in square degrees) of the zone's largest ring.
+    pub label_area: f64
+}
+
+impl TimeZone {
+    /// Returns the zone's offset from UTC (in minutes) at `unix_time`, or `None` if the zone is unknown.
+    pub fn utc_offset(&self, unix_time: i64) -> Option<i32> {
+        let tz: chrono_tz::Tz = self.tzid.parse().ok()?;
+        let utc = chrono::NaiveDateTime::from_timestamp(unix_time, 0);
+
+        Some(tz.offset_from_utc_datetime(&utc).fix().local_minus_utc() / 60)
+    }
+}
+
+/// Returns a label of a UTC offset (in minutes), e.g. "UTC+5:30" or "UTC-3".
+pub fn offset_label(offset: i32) -> String {
+    if offset == 0 { return "UTC".into(); }
+    let sign = if offset > 0 { "+" } else { "-" };
+    let (hours, minutes) = (offset.abs() / 60, offset.abs() % 60);
+    if minutes == 0 {
+        format!("UTC{}{}", sign, hours)
+    } else {
+        format!("UTC{}{}:{:02}", sign, hours, minutes)
+    }
+}
+
+/// Loads time zones from a GeoJSON file or a zip archive containing one; zones are sorted by decreasing
+/// `label_area`.
+pub fn load(path: &str) -> Result<Vec<TimeZone>, String> {
+    let contents = if path.to_lowercase().ends_with(".zip") {
+        let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+        let idx = (0..archive.len())
+            .find(|idx| archive.by_index(*idx).map_or(false, |entry| {
+                let name = entry.name().to_lowercase();
+                name.ends_with(".json") || name.ends_with(".geojson")
+            }))
+            .ok_or_else(|| "no GeoJSON file in the archive".to_string())?;
+        let mut contents = String::new();
+        archive.by_index(idx).map_err(|e| e.to_string())?
+            .read_to_string(&mut contents).map_err(|e| e.to_string())?;
+        contents
+    } else {
+        std::fs::read_to_string(path).map_err(|e| e.to_string())?
+    };
+    let value: Value = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+    let features = value.get("features").and_then(Value::as_array)
+        .ok_or_else(|| "expected a FeatureCollection".to_string())?;
+
+    let mut zones = vec![];
+    for feature in features {
+        let tzid = match feature.get("properties").and_then(|properties| properties.get("tzid")?.as_str()) {
+            Some(tzid) => tzid.to_string(),
+            None => continue
+        };
+        let boundary = geojson::object_lines(feature)?;
+        let (label_position, label_area) = match boundary.iter().map(|ring| (centroid(ring), area(ring)))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        {
+            Some(largest) => largest,
+            None => continue
+        };
+        zones.push(TimeZone{ tzid, boundary, label_position, label_area });
+    }
+    if zones.is_empty() {
+        return Err("no time zones found (features with the property \"tzid\")".into());
+    }
+    zones.sort_by(|a, b| b.label_area.partial_cmp(&a.label_area).unwrap());
+
+    Ok(zones)
+}
+
+/// Returns the area (in square degrees) of a ring.
+fn area(ring: &Polyline) -> f64 {
+    ring.windows(2).map(|s| s[0][0] * s[1][1] - s[1][0] * s[0][1]).sum::<f64>().abs() / 2.0
+}
+
+/// Returns the centroid of a ring (the mean of its vertices if it is degenerate).
+fn centroid(ring: &Polyline) -> [f64; 2] {
+    let mut sum = [0.0; 3];
+    for s in ring.windows(2) {
+        let cross = s[0][0] * s[1][1] - s[1][0] * s[0][1];
+        sum[0] += (s[0][0] + s[1][0]) * cross;
+        sum[1] += (s[0][1] + s[1][1]) * cross;
+        sum[2] += cross;
+    }
+    if sum[2].abs() > 1.0e-12 {
+        [sum[0] / (3.0 * sum[2]), sum[1] / (3.0 * sum[2])]
+    } else {
+        let n = ring.len().max(1) as f64;
+        [ring.iter().map(|p| p[0]).sum::<f64>() / n, ring.iter().map(|p| p[1]).sum::<f64>() / n]
+    }
+}
@@ -0,0 +1,315 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Automated UI tests: synthetic mouse and keyboard input is fed to the GUI frame by frame (real input is ignored),
+// and assertions about the resulting views are checked. Run with:
+//
+//     $ cargo run --release -- --ui-test <test file>
+//
+// The program exits after the last step, with code 0 if all assertions passed and 1 otherwise. Tests run with
+// the default configuration, layout and input bindings (the user's configuration file, workspace and markers are not
+// loaded), so that positions in test files refer to the default window layout (e.g. the initial orthographic view
+// is a 640×640 window, whose image is 624 pixels wide; dragging across it by 100 pixels rotates it by 18.4°).
+//
+// A test file is a JSON array of steps, executed in order:
+//
+//     { "action": "move", "pos": [640, 400] }                  move the mouse (logical pixels of the main window)
+//     { "action": "press", "button": "left" }                  press a mouse button ("left", "right", "middle";
+//     { "action": "release", "button": "left" }                   "left" if not specified)
+//     { "action": "click", "pos": [30, 10] }                   move, press and release the left button
+//     { "action": "double_click", "pos": [640, 400] }
+//     { "action": "drag", "from": [600, 400], "to": [700, 400], "frames": 10 }
+//     { "action": "wheel", "delta": 1.0 }                      scroll (positive: up)
+//     { "action": "key", "key": "enter" }                      press and release a key (enter, escape, tab,
+//                                                                 backspace, delete, left, right, up, down)
+//     { "action": "text", "text": "+proj=merc" }               type characters
+//     { "action": "wait", "frames": 10 }
+//     { "action": "assert_num_views", "count": 2 }
+//     { "action": "assert_view", "view": 0, "projection": "Mercator", "center": [0.0, 0.0], "zoom": 0.5 }
+//     { "action": "assert_render_hash", "view": 0, "size": [400, 300], "hash": "9c2f0e4b7a1d3e58" }
+//
+// Every step takes at least one frame. `view` is the view's index in the order of `ProgramData::all_views_mut`.
+// In "assert_view", all checks except "view" are optional; "tolerance" (default: 0.01) applies to the center
+// (in degrees) and zoom. "assert_render_hash" renders the view offscreen and compares the FNV-1a hash of its pixels;
+// if "hash" is omitted, the computed hash is printed instead (to record it). Hashes depend on the GPU and driver.
+//
+
+use crate::data;
+use serde::Deserialize;
+use std::collections::VecDeque;
+
+/// Duration of a frame reported to the GUI, so that animations are reproducible.
+const FRAME_DURATION: f32 = 1.0 / 60.0;
+
+const DEFAULT_TOLERANCE: f64 = 0.01;
+
+#[derive(Copy, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Button { Left, Right, Middle }
+
+impl Default for Button {
+    fn default() -> Button { Button::Left }
+}
+
+impl Button {
+    fn imgui_button(&self) -> imgui::MouseButton {
+        match self {
+            Button::Left => imgui::MouseButton::Left,
+            Button::Right => imgui::MouseButton::Right,
+            Button::Middle => imgui::MouseButton::Middle
+        }
+    }
+}
+
+#[derive(Copy, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Key { Enter, Escape, Tab, Backspace, Delete, Left, Right, Up, Down }
+
+impl Key {
+    fn imgui_key(&self) -> imgui::Key {
+        match self {
+            Key::Enter => imgui::Key::Enter,
+            Key::Escape => imgui::Key::Escape,
+            Key::Tab => imgui::Key::Tab,
+            Key::Backspace => imgui::Key::Backspace,
+            Key::Delete => imgui::Key::Delete,
+            Key::Left => imgui::Key::LeftArrow,
+            Key::Right => imgui::Key::RightArrow,
+            Key::Up => imgui::Key::UpArrow,
+            Key::Down => imgui::Key::DownArrow
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum Step {
+    Move{ pos: [f32; 2] },
+    Press{ #[serde(default)] button: Button },
+    Release{ #[serde(default)] button: Button },
+    Click{ pos: [f32; 2] },
+    DoubleClick{ pos: [f32; 2] },
+    Drag{ from: [f32; 2], to: [f32; 2], frames: usize },
+    Wheel{ delta: f32 },
+    Key{ key: Key },
+    Text{ text: String },
+    Wait{ frames: usize },
+    AssertNumViews{ count: usize },
+    AssertView{
+        view: usize,
+        projection: Option<String>,
+        center: Option<[f64; 2]>,
+        zoom: Option<f64>,
+        tolerance: Option<f64>
+    },
+    AssertRenderHash{ view: usize, size: [u32; 2], hash: Option<String> }
+}
+
+/// Input applied in a single frame.
+#[derive(Default)]
+struct FrameInput {
+    mouse_pos: Option<[f32; 2]>,
+    button: Option<(Button, bool)>,
+    wheel: f32,
+    key: Option<Key>,
+    text: String
+}
+
+/// Everything done in a single frame; `step` is the index of the step it belongs to.
+struct Frame {
+    step: usize,
+    input: FrameInput,
+    /// Checked after the frame's GUI has been handled.
+    assertion: Option<Step>
+}
+
+pub struct UiTest {
+    frames: VecDeque<Frame>,
+    /// Key pressed in the previous frame (released in the current one).
+    pressed_key: Option<Key>,
+    /// Frame whose input has been applied most recently.
+    current: Option<Frame>,
+    num_assertions: usize,
+    failures: Vec<String>
+}
+
+impl UiTest {
+    pub fn load(path: &str) -> Result<UiTest, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let steps: Vec<Step> = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+
+        let mut frames = VecDeque::new();
+        for (idx, step) in steps.into_iter().enumerate() {
+            let input = |input: FrameInput| Frame{ step: idx, input, assertion: None };
+            let click = |pos: [f32; 2]| vec![
+                input(FrameInput{ mouse_pos: Some(pos), ..Default::default() }),
+                input(FrameInput{ button: Some((Button::Left, true)), ..Default::default() }),
+                input(FrameInput{ button: Some((Button::Left, false)), ..Default::default() })
+            ];
+
+            match step {
+                Step::Move{ pos } => frames.push_back(input(FrameInput{ mouse_pos: Some(pos), ..Default::default() })),
+
+                Step::Press{ button } => frames.push_back(
+                    input(FrameInput{ button: Some((button, true)), ..Default::default() })
+                ),
+
+                Step::Release{ button } => frames.push_back(
+                    input(FrameInput{ button: Some((button, false)), ..Default::default() })
+                ),
+
+                Step::Click{ pos } => frames.extend(click(pos)),
+
+                Step::DoubleClick{ pos } => {
+                    frames.extend(click(pos));
+                    frames.extend(click(pos));
+                },
+
+                Step::Drag{ from, to, frames: num_frames } => {
+                    frames.push_back(input(FrameInput{ mouse_pos: Some(from), ..Default::default() }));
+                    frames.push_back(input(FrameInput{ button: Some((Button::Left, true)), ..Default::default() }));
+                    let num_frames = num_frames.max(1);
+                    for i in 1..=num_frames {
+                        let t = i as f32 / num_frames as f32;
+                        let pos = [from[0] + t * (to[0] - from[0]), from[1] + t * (to[1] - from[1])];
+                        frames.push_back(input(FrameInput{ mouse_pos: Some(pos), ..Default::default() }));
+                    }
+                    frames.push_back(input(FrameInput{ button: Some((Button::Left, false)), ..Default::default() }));
+                },
+
+                Step::Wheel{ delta } => frames.push_back(input(FrameInput{ wheel: delta, ..Default::default() })),
+
+                Step::Key{ key } => frames.push_back(input(FrameInput{ key: Some(key), ..Default::default() })),
+
+                Step::Text{ text } => frames.push_back(input(FrameInput{ text, ..Default::default() })),
+
+                Step::Wait{ frames: num_frames } => {
+                    for _ in 0..num_frames.max(1) {
+                        frames.push_back(input(FrameInput::default()));
+                    }
+                },
+
+                assertion => frames.push_back(
+                    Frame{ step: idx, input: FrameInput::default(), assertion: Some(assertion) }
+                )
+            }
+        }
+
+        Ok(UiTest{ frames, pressed_key: None, current: None, num_assertions: 0, failures: vec![] })
+    }
+
+    /// Applies input of the next frame; to be called before the frame is started.
+    pub fn apply_input(&mut self, io: &mut imgui::Io) {
+        io.delta_time = FRAME_DURATION;
+
+        if let Some(key) = self.pressed_key.take() {
+            io[key.imgui_key()] = false;
+        }
+
+        self.current = self.frames.pop_front();
+        let input = match &self.current {
+            Some(frame) => &frame.input,
+            None => return
+        };
+
+        if let Some(pos) = input.mouse_pos {
+            io.mouse_pos = pos;
+        }
+        if let Some((button, down)) = input.button {
+            io[button.imgui_button()] = down;
+        }
+        io.mouse_wheel = input.wheel;
+        if let Some(key) = input.key {
+            io[key.imgui_key()] = true;
+            self.pressed_key = Some(key);
+        }
+        for c in input.text.chars() {
+            io.add_input_character(c);
+        }
+    }
+
+    /// Checks the current frame's assertion (if any); returns false when the test is finished.
+    pub fn after_frame(&mut self, program_data: &mut data::ProgramData) -> bool {
+        let frame = match self.current.take() {
+            Some(frame) => frame,
+            None => return false
+        };
+
+        if let Some(assertion) = &frame.assertion {
+            self.num_assertions += 1;
+            if let Err(e) = check(assertion, program_data) {
+                let message = format!("step {}: {}", frame.step + 1, e);
+                eprintln!("UI test failure: {}", message);
+                self.failures.push(message);
+            }
+        }
+
+        !self.frames.is_empty()
+    }
+
+    /// Prints the summary and exits the program.
+    pub fn finish(&self) -> ! {
+        println!("UI test: {} assertion(s), {} failure(s).", self.num_assertions, self.failures.len());
+        std::process::exit(if self.failures.is_empty() { 0 } else { 1 });
+    }
+}
+
+fn check(assertion: &Step, program_data: &mut data::ProgramData) -> Result<(), String> {
+    match assertion {
+        Step::AssertNumViews{ count } => {
+            let num_views = program_data.all_views_mut().count();
+            if num_views != *count {
+                return Err(format!("expected {} view(s), found {}", count, num_views));
+            }
+        },
+
+        Step::AssertView{ view: view_idx, projection, center, zoom, tolerance } => {
+            let tolerance = tolerance.unwrap_or(DEFAULT_TOLERANCE);
+            let view = program_data.all_views_mut().nth(*view_idx).ok_or_else(|| format!("no view {}", view_idx))?;
+
+            if let Some(projection) = projection {
+                let actual = view.projection().name();
+                if actual != projection {
+                    return Err(format!("expected projection \"{}\", found \"{}\"", projection, actual));
+                }
+            }
+            if let Some([lon, lat]) = center {
+                let (central_lon, central_lat) = view.central_lonlat();
+                let lon_error = (central_lon.0 - lon + 180.0).rem_euclid(360.0) - 180.0;
+                if lon_error.abs() > tolerance || (central_lat.0 - lat).abs() > tolerance {
+                    return Err(format!(
+                        "expected center ({:.3}°, {:.3}°), found ({:.3}°, {:.3}°)",
+                        lon, lat, central_lon.0, central_lat.0
+                    ));
+                }
+            }
+            if let Some(zoom) = zoom {
+                if (view.zoom() - zoom).abs() > tolerance {
+                    return Err(format!("expected zoom {:.3}, found {:.3}", zoom, view.zoom()));
+                }
+            }
+        },
+
+        Step::AssertRenderHash{ view: view_idx, size, hash } => {
+            let view = program_data.all_views_mut().nth(*view_idx).ok_or_else(|| format!("no view {}", view_idx))?;
+            let image = view.render_offscreen(size[0], size[1], crate::views::Layers::All);
+            let actual = format!("{:016x}", data::fnv1a(image.as_raw()));
+            match hash {
+                None => println!("UI test: render hash of view {}: {}", view_idx, actual),
+                Some(hash) => if *hash != actual {
+                    return Err(format!("expected render hash {}, found {}", hash, actual));
+                }
+            }
+        },
+
+        _ => ()
+    }
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// 2-D vector fields (e.g. wind) given by eastward (u) and northward (v) components on a latitude-longitude grid,
+// loaded from two variables of a NetCDF file (see `scalar_field`) and shown by views as arrows.
+//
+// The direction of an arrow in a view is that of the image of the local east-north frame, obtained by projecting
+// points displaced slightly eastward and northward, so it accounts for the projection's meridian convergence
+// (and, for non-conformal projections, for its local shear).
+//
+
+use crate::scalar_field;
+
+pub struct VectorField {
+    /// Names of the components' variables (with units, if specified).
+    pub name: String,
+    /// Eastward component.
+    u: scalar_field::Grid,
+    /// Northward component.
+    v: scalar_field::Grid,
+    /// Upper bound of the magnitude (from the ranges of the components).
+    pub max_speed: f32
+}
+
+/// Loads the components `u_variable` and `v_variable` (at `index` of their first leading dimension)
+/// from a NetCDF file.
+pub fn load(path: &str, u_variable: &str, v_variable: &str, index: usize) -> Result<VectorField, String> {
+    let u = scalar_field::load(path, u_variable, index)?;
+    let v = scalar_field::load(path, v_variable, index)?;
+    if !u.has_same_points(&v) {
+        return Err(format!("{} and {} are defined on different grids", u_variable, v_variable));
+    }
+    let max_speed = u.value_range[0].abs().max(u.value_range[1].abs())
+        .hypot(v.value_range[0].abs().max(v.value_range[1].abs()));
+
+    Ok(VectorField{ name: format!("{}, {}", u.name, v.name), u, v, max_speed })
+}
+
+impl VectorField {
+    /// Returns the [u, v] components at the grid point nearest to (`longitude`, `latitude`) (degrees), or `None`
+    /// if there is no value.
+    pub fn sample(&self, longitude: f64, latitude: f64) -> Option<[f32; 2]> {
+        let (u, v) = (self.u.value(longitude, latitude), self.v.value(longitude, latitude));
+        if u.is_nan() || v.is_nan() { None } else { Some([u, v]) }
+    }
+}
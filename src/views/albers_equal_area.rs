@@ -0,0 +1,101 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::One;
+use std::rc::Rc;
+use crate::data;
+use crate::projection::{Projection, WGS84_ECCENTRICITY};
+use crate::views::{base::ViewBase, ParamUnit, ProjectionParam};
+use std::cell::RefCell;
+
+mod uniform_names {
+    pub const ELLIPSOIDAL: &str = "ellipsoidal";
+    pub const STANDARD_PARALLEL_1: &str = "standard_parallel_1";
+    pub const STANDARD_PARALLEL_2: &str = "standard_parallel_2";
+}
+
+const DEFAULT_STANDARD_PARALLELS: [cgmath::Deg<f64>; 2] = [cgmath::Deg(33.0), cgmath::Deg(45.0)];
+
+/// Max. absolute value of a standard parallel.
+const MAX_STANDARD_PARALLEL: cgmath::Deg<f64> = cgmath::Deg(89.0);
+
+/// Initial zoom; shows most of the globe.
+const INITIAL_ZOOM: f64 = 0.5;
+
+/// Albers equal-area conic projection: equal-area, true to scale along the two standard parallels.
+pub struct AlbersEqualAreaView {
+    base: ViewBase
+}
+
+impl AlbersEqualAreaView {
+    pub fn new(
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> AlbersEqualAreaView {
+        let mut view = AlbersEqualAreaView{
+            base: ViewBase::new(
+                AlbersEqualAreaView::initial_orientation(),
+                Projection::AlbersEqualArea{
+                    standard_parallels: [DEFAULT_STANDARD_PARALLELS[0].into(), DEFAULT_STANDARD_PARALLELS[1].into()],
+                    eccentricity: 0.0
+                },
+                program_data,
+                Rc::clone(&program_data.gl_programs.albers_equal_area.lines),
+                Rc::clone(&program_data.gl_programs.albers_equal_area.triangles),
+                display,
+                renderer
+            )
+        };
+        view.base.set_projection_params(
+            vec![
+                ProjectionParam{
+                    uniform_name: uniform_names::ELLIPSOIDAL,
+                    label: "WGS84 ellipsoid",
+                    tooltip: "Use the WGS84 ellipsoid instead of a sphere",
+                    unit: ParamUnit::Flag,
+                    min: 0.0,
+                    max: 1.0,
+                    speed: 1.0,
+                    value: 0.0
+                },
+                standard_parallel_param(uniform_names::STANDARD_PARALLEL_1, "standard parallel 1", 0),
+                standard_parallel_param(uniform_names::STANDARD_PARALLEL_2, "standard parallel 2", 1)
+            ],
+            |values, _| Projection::AlbersEqualArea{
+                standard_parallels: [cgmath::Deg(values[1]).into(), cgmath::Deg(values[2]).into()],
+                eccentricity: if values[0] != 0.0 { WGS84_ECCENTRICITY } else { 0.0 }
+            }
+        );
+        view.base.zoom_by(INITIAL_ZOOM);
+
+        view
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
+
+fn standard_parallel_param(uniform_name: &'static str, label: &'static str, index: usize) -> ProjectionParam {
+    ProjectionParam{
+        uniform_name,
+        label,
+        tooltip: "Parallel along which the scale is true",
+        unit: ParamUnit::Degrees,
+        min: -MAX_STANDARD_PARALLEL.0,
+        max: MAX_STANDARD_PARALLEL.0,
+        speed: 0.2,
+        value: DEFAULT_STANDARD_PARALLELS[index].0
+    }
+}
@@ -0,0 +1,78 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::One;
+use std::rc::Rc;
+use crate::data;
+use crate::projection::Projection;
+use crate::views::{base::ViewBase, ParamUnit, ProjectionParam};
+use std::cell::RefCell;
+
+mod uniform_names {
+    pub const PARALLEL: &str = "parallel";
+}
+
+/// Raisz's choice of the parallel.
+const DEFAULT_PARALLEL: cgmath::Deg<f64> = cgmath::Deg(20.0);
+
+/// Max. absolute value of the parallel.
+const MAX_PARALLEL: cgmath::Deg<f64> = cgmath::Deg(60.0);
+
+/// Initial zoom; shows the whole map.
+const INITIAL_ZOOM: f64 = 0.45;
+
+/// Raisz armadillo: orthoapsidal projection, i.e., orthographic projection of the globe mapped onto a torus
+/// (an "armadillo's shell") viewed obliquely.
+pub struct ArmadilloView {
+    base: ViewBase
+}
+
+impl ArmadilloView {
+    pub fn new(
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> ArmadilloView {
+        let mut view = ArmadilloView{
+            base: ViewBase::new(
+                ArmadilloView::initial_orientation(),
+                Projection::Armadillo{ parallel: DEFAULT_PARALLEL.into() },
+                program_data,
+                Rc::clone(&program_data.gl_programs.armadillo.lines),
+                Rc::clone(&program_data.gl_programs.armadillo.triangles),
+                display,
+                renderer
+            )
+        };
+        view.base.set_projection_params(
+            vec![ProjectionParam{
+                uniform_name: uniform_names::PARALLEL,
+                label: "parallel",
+                tooltip: "Parallel shown as the torus' outer rim, i.e., the tilt of the torus towards the viewer",
+                unit: ParamUnit::Degrees,
+                min: -MAX_PARALLEL.0,
+                max: MAX_PARALLEL.0,
+                speed: 0.2,
+                value: DEFAULT_PARALLEL.0
+            }],
+            |values, _| Projection::Armadillo{ parallel: cgmath::Deg(values[0]).into() }
+        );
+        view.base.zoom_by(INITIAL_ZOOM);
+
+        view
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
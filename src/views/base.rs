@@ -6,12 +6,40 @@
 // (see the LICENSE file for details).
 //
 
-use crate::draw_buffer::{Sampling, DrawBuffer};
-use crate::data::{LonLatGlBuffers, ProgramData, ToArray};
-use cgmath::{Basis3, Vector3, InnerSpace, Rotation3, One, Matrix3};
+use crate::csv;
+use crate::draw_buffer::DrawBuffer;
+use crate::geojson;
+use crate::data::{
+    self,
+    Choropleth,
+    DrawnLines,
+    Hillshading,
+    HillshadingMode,
+    ImageOverlay,
+    LonLatGlBuffers,
+    LonLatVertex,
+    NightShading,
+    Overlay,
+    Place,
+    PointLayer,
+    ProgramData,
+    ScalarField,
+    TimeZoneLabel,
+    UserMarker,
+    ToArray,
+    VectorFieldLayer,
+    WebTiles,
+    XyVertex
+};
+use crate::projection;
+use crate::views::interruption::{self, Lobe};
+use crate::views::{Grid, Layer, LayerOverrides, LayerVisibility, ProjectionParam};
+use crate::views::params::grid_param_names;
+use cgmath::{Basis3, Point2, Vector3, InnerSpace, Rotation, Rotation3, One, Matrix3};
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use glium::Surface;
+use glium::uniforms::{Uniforms, UniformValue};
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum DragRotation {
@@ -25,10 +53,184 @@ pub enum ViewMode {
     GlobeTexture
 }
 
+/// Determines how a view is rendered for stereoscopic viewing.
+#[derive(Copy, Clone, PartialEq)]
+pub enum StereoMode {
+    Off,
+    /// Left eye's image in red, right eye's in cyan.
+    Anaglyph,
+    /// Left eye's image on the left, right eye's on the right (for parallel viewing).
+    SideBySide
+}
+
+#[derive(Copy, Clone)]
+enum Eye { Left, Right }
+
+/// Rotation of a view's globe and map (see `ViewBase::rotation`), as shared by linked views.
+#[derive(Copy, Clone, PartialEq)]
+pub struct Rotation {
+    drag_rotation: DragRotation,
+    orientation: Basis3<f64>,
+    angle_ns: cgmath::Rad<f64>,
+    angle_ew: cgmath::Rad<f64>,
+    roll: cgmath::Rad<f64>
+}
+
+/// Rotation of the globe (around the view's vertical axis) as seen by each eye in stereo mode.
+const STEREO_EYE_ANGLE: cgmath::Deg<f64> = cgmath::Deg(2.0);
+
+/// Min. zoom of a view.
+const MIN_ZOOM: f64 = 0.5;
+
+/// Min. latitude at which a map can be truncated (see `ViewBase::set_truncation`).
+pub const MIN_TRUNCATION: cgmath::Deg<f64> = cgmath::Deg(10.0);
+
+/// Range of the horizontal and vertical stretch of the map (see `ViewBase::set_stretch`).
+pub const MIN_STRETCH: f64 = 0.2;
+pub const MAX_STRETCH: f64 = 5.0;
+
 mod uniform_names {
     pub const UNIFORM_COLOR: &str = "uniform_color";
 }
 
+const GRATICULE_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+
+const SCRIPT_OVERLAY_COLOR: [f32; 4] = [0.85, 0.1, 0.6, 1.0];
+
+const OUTLINE_COLOR: [f32; 4] = [0.2, 0.4, 0.9, 1.0];
+
+/// Value of the `crop` uniform (see "lines.geom") which renders the whole view: center of the rendered region
+/// (in normalized device coordinates) and magnification along X and Y.
+const NO_CROP: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+/// Max. width and height of tiles rendered by `ViewBase::render_offscreen_tiled` (limits the memory used
+/// by multisampled draw buffers).
+const MAX_OFFSCREEN_TILE_SIZE: u32 = 2048;
+
+/// Max. width and height of images rendered by `ViewBase::render_offscreen_tiled` (an image of this size takes 1 GiB).
+pub const MAX_OFFSCREEN_SIZE: u32 = 16384;
+
+/// Max. length (in normalized device coordinates) of a projected line segment; longer ones are omitted
+/// (must equal the value used in "lines.geom").
+const MAX_PROJECTED_SEGMENT_LENGTH: f64 = 0.2;
+
+/// Max. length (in map units) of a segment of lines projected by `ViewBase::project_polylines`; lines are split
+/// at longer ones (as by `MAX_PROJECTED_SEGMENT_LENGTH` at zoom 1).
+const MAX_MAP_SEGMENT_LENGTH: f64 = 0.2;
+
+/// Min. spacing (degrees) of the arrows of a vector field.
+const MIN_VECTOR_FIELD_SPACING: f64 = 1.0;
+
+/// Every n-th graticule line (counting from the equator and the prime meridian) is labeled.
+const GRATICULE_LABEL_INTERVAL: f64 = 3.0;
+
+/// Length (in degrees) of a labeled line's part on either side of the label's anchor.
+const GRATICULE_LABEL_PATH_HALF_LENGTH: u32 = 15;
+
+/// Placement of graticule labels.
+#[derive(Copy, Clone, PartialEq)]
+pub enum GraticuleLabels {
+    Off,
+    /// Horizontal text centered on the labeled line.
+    Horizontal,
+    /// Text following the projected labeled line, character by character.
+    AlongLines
+}
+
+/// Label of a graticule line, together with the line's projected part around the label.
+pub struct GraticuleLabel {
+    pub text: String,
+    /// Labeled line's part in normalized device coordinates; consecutive points are 1° apart.
+    pub path: Vec<Point2<f64>>,
+    /// Index of the label's center in `path`.
+    pub anchor: usize
+}
+
+/// Selects what is drawn by `ViewBase::render_offscreen`.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Layers {
+    All,
+    /// Only the globe texture (if in `ViewMode::GlobeTexture`), without any lines.
+    RasterOnly
+}
+
+/// Line segments of a view, projected on the CPU.
+pub struct ProjectedLines {
+    pub color: [f32; 4],
+    /// Segments' ends in normalized device coordinates, i.e. [-1, 1] along each axis (bottom-left is [-1, -1]).
+    pub segments: Vec<[Point2<f64>; 2]>
+}
+
+/// Extends uniforms with projection-specific float uniforms, the mapped body's mean radius and lobes
+/// of the interrupted map.
+struct WithProjectionUniforms<'a, U: Uniforms> {
+    uniforms: &'a U,
+    projection_uniforms: &'a [(&'static str, f32)],
+    projection_params: &'a [ProjectionParam],
+    /// Kilometers.
+    mean_radius: f32,
+    lobe_uniforms: &'a [([f32; 4], [f32; 2])]
+}
+
+impl<'a, U: Uniforms> Uniforms for WithProjectionUniforms<'a, U> {
+    fn visit_values<'b, F: FnMut(&str, UniformValue<'b>)>(&'b self, mut output: F) {
+        self.uniforms.visit_values(&mut output);
+        for (name, value) in self.projection_uniforms {
+            output(name, UniformValue::Float(*value));
+        }
+        for param in self.projection_params {
+            output(param.uniform_name, UniformValue::Float(param.uniform_value()));
+        }
+        output("mean_radius", UniformValue::Float(self.mean_radius));
+        output("num_lobes", UniformValue::SignedInt(self.lobe_uniforms.len() as i32));
+        for (i, (bounds, center)) in self.lobe_uniforms.iter().enumerate() {
+            output(&format!("lobe_bounds[{}]", i), UniformValue::Vec4(*bounds));
+            output(&format!("lobe_centers[{}]", i), UniformValue::Vec2(*center));
+        }
+    }
+}
+
+/// Returns an orientation drawn uniformly from all possible orientations.
+///
+/// Uses a uniformly distributed unit quaternion (K. Shoemake, "Uniform random rotations", Graphics Gems III, 1992);
+/// note that uniformly distributed Euler angles would not result in uniformly distributed orientations.
+///
+pub fn random_orientation() -> Basis3<f64> {
+    use std::f64::consts::PI;
+
+    let (u1, u2, u3): (f64, f64, f64) = (rand::random(), rand::random(), rand::random());
+    let (r1, r2) = ((1.0 - u1).sqrt(), u1.sqrt());
+
+    let quaternion = cgmath::Quaternion::new(
+        r2 * (2.0 * PI * u3).cos(),
+        r1 * (2.0 * PI * u2).sin(),
+        r1 * (2.0 * PI * u2).cos(),
+        r2 * (2.0 * PI * u3).sin()
+    );
+
+    Basis3::from(quaternion)
+}
+
+/// Returns the globe orientation corresponding to an oblique aspect (see `ViewBase::set_oblique_aspect`).
+fn oblique_aspect_orientation(
+    pole_longitude: cgmath::Deg<f64>,
+    pole_latitude: cgmath::Deg<f64>,
+    rotation: cgmath::Deg<f64>
+) -> Basis3<f64> {
+    // moves the pole to long. 0°, then along the meridian to (0, 0, 1), then rotates around it
+    Basis3::from_angle_z(cgmath::Rad::from(rotation))
+        * Basis3::from_angle_y(cgmath::Rad::from(pole_latitude - cgmath::Deg(90.0)))
+        * Basis3::from_angle_z(-cgmath::Rad::from(pole_longitude))
+}
+
+/// Returns the longitude and latitude of the point in the center of a view with the globe's `orientation`.
+fn central_lonlat(orientation: &Basis3<f64>) -> (cgmath::Deg<f64>, cgmath::Deg<f64>) {
+    let dir_of_lonlat00 = Vector3{ x: 1.0, y: 0.0, z: 0.0 };
+    let (longitude, latitude) = projection::xyz_to_lonlat(orientation.invert().as_ref() * dir_of_lonlat00);
+
+    (longitude.into(), latitude.into())
+}
+
 /// Base struct representing a view.
 ///
 /// The underlying globe being projected is oriented as per `orientation`. The globe is centered
@@ -43,7 +245,15 @@ pub struct ViewBase {
 
     orientation: Basis3<f64>,
 
-    pub draw_graticule: bool,
+    /// CPU-side counterpart of the view's vertex shader.
+    projection: projection::Projection,
+
+    /// Global layer visibility (see `ProgramData::set_layer_visibility`).
+    global_layers: LayerVisibility,
+
+    layer_overrides: LayerOverrides,
+
+    pub graticule_labels: GraticuleLabels,
 
     wh_ratio: f32,
 
@@ -51,25 +261,129 @@ pub struct ViewBase {
 
     angle_ew: cgmath::Rad<f64>,
 
+    /// Rotation of the map about the view's center (counterclockwise); independent of the globe's orientation.
+    roll: cgmath::Rad<f64>,
+
     view_mode: ViewMode,
 
     zoom: f64,
 
     drag_rotation: DragRotation,
 
+    stereo_mode: StereoMode,
+
     draw_buf: DrawBuffer,
 
+    /// Draw buffer of the magnifier inset (see `render_magnifier`); created on first use.
+    magnifier_buf: Option<DrawBuffer>,
+    /// Draw buffer of frames rendered by `render_frame`; created on first use.
+    frame_buf: Option<DrawBuffer>,
+
     globe_gl_buf: LonLatGlBuffers,
 
     graticule_gl_buf: LonLatGlBuffers,
 
-    map_gl_buf: LonLatGlBuffers,
+    /// Spacing of lines in `graticule_gl_buf`.
+    graticule_step: cgmath::Deg<f64>,
+
+    /// Approx. length of segments of lines in `graticule_gl_buf`.
+    graticule_substep: cgmath::Deg<f64>,
+
+    /// Projection-specific values passed to the vertex shader as float uniforms.
+    projection_uniforms: Vec<(&'static str, f32)>,
+
+    /// Adjustable projection parameters; also passed to the vertex shader as float uniforms.
+    projection_params: Vec<ProjectionParam>,
+
+    /// Creates the CPU-side projection from values of `projection_params` (for the mapped body).
+    projection_from_params: Option<fn(&[f64], &data::Body) -> projection::Projection>,
+
+    /// Geometry generated by the overlay script (if any).
+    script_overlay_gl_buf: Option<LonLatGlBuffers>,
+
+    /// Lines of the visible vector layers (see `ProgramData::vector_layers`), including the coastline.
+    vector_layer_lines: Vec<DrawnLines>,
+
+    /// Horizontal and vertical stretch of the map (applied in addition to zoom); `None` if not supported by the view.
+    stretch: Option<[f64; 2]>,
+
+    /// Lobes of the interrupted map (empty if not interrupted); `None` if interruption is not supported by the view.
+    lobes: Option<Vec<Lobe>>,
+
+    /// Latitude (north and south) beyond which the map is not shown; supported together with interruption.
+    truncation: Option<cgmath::Deg<f64>>,
+
+    /// Values of the geometry shaders' lobe uniforms (see `interruption::shader_uniforms`).
+    lobe_uniforms: Vec<([f32; 4], [f32; 2])>,
+
+    /// Points (longitude, latitude) marked by the user.
+    markers: Vec<(cgmath::Deg<f64>, cgmath::Deg<f64>)>,
+
+    /// Named markers (see `ProgramData::user_markers`).
+    user_markers: Rc<Vec<UserMarker>>,
+
+    /// See `ProgramData::point_layers`.
+    point_layers: Rc<Vec<PointLayer>>,
+
+    /// Populated places (see `ProgramData::places`).
+    places: Rc<Vec<Place>>,
+
+    /// See `ProgramData::time_zone_labels`.
+    time_zone_labels: Rc<Vec<TimeZoneLabel>>,
+
+    /// Mapped body (see `ProgramData::body`).
+    body: data::Body,
+
+    /// Line segments specified directly in map coordinates (e.g. boundaries of an interrupted map's parts).
+    outline: Vec<[Point2<f64>; 2]>,
+
+    outline_gl_buf: Option<glium::VertexBuffer<XyVertex>>,
+
+    /// Boundary of the projection's extent (see `Projection::boundary`).
+    boundary_gl_buf: Option<glium::VertexBuffer<XyVertex>>,
+
+    outline_gl_prog: Rc<glium::Program>,
+
+    /// Index of the base map shown in the globe texture mode (see `ProgramData::base_map_names`).
+    base_map: usize,
 
     globe_texture: Rc<glium::texture::texture2d::Texture2d>,
 
+    /// Filled land and ocean drawn in the vector map mode (see `ProgramData::land_texture`).
+    land_texture: Option<Rc<glium::texture::texture2d::Texture2d>>,
+
+    /// Shading of the night hemisphere drawn over the map (see `ProgramData::set_night_time`).
+    night_shading: Option<NightShading>,
+
+    /// Texture blended over the globe texture (see `ProgramData::overlay`).
+    overlay: Option<Overlay>,
+
+    /// Shading of relief of the map's texture (see `ProgramData::hillshading`).
+    hillshading: Option<Hillshading>,
+
+    /// Region of web tiles shown over the base map (see `ProgramData::web_tiles`).
+    web_tiles: Option<WebTiles>,
+
+    /// Georeferenced image shown over the base map (see `ProgramData::image_overlay`).
+    image_overlay: Option<ImageOverlay>,
+
+    /// Color-mapped scalar field blended over the map (see `ProgramData::scalar_field`).
+    scalar_field: Option<ScalarField>,
+
+    /// See `ProgramData::vector_field`.
+    vector_field: Option<VectorFieldLayer>,
+
+    /// Polygons colored by an attribute, drawn over the map (see `ProgramData::choropleth`).
+    choropleth: Option<Choropleth>,
+
     lines_gl_prog: Rc<glium::Program>,
 
-    tris_gl_prog: Rc<glium::Program>
+    tris_gl_prog: Rc<glium::Program>,
+
+    display: glium::Display,
+
+    /// True if the view has been rendered since the last call to `take_rendered`.
+    rendered: Cell<bool>
 }
 
 impl ViewBase {
@@ -84,18 +398,297 @@ impl ViewBase {
         self.render();
     }
 
+    /// Returns the index of the shown base map (see `ProgramData::base_map_names`).
+    pub fn base_map(&self) -> usize { self.base_map }
+
+    /// Shows `texture` (the `index`-th base map) in the globe texture mode.
+    pub fn set_base_map(&mut self, index: usize, texture: Rc<glium::texture::texture2d::Texture2d>) {
+        self.base_map = index;
+        self.globe_texture = texture;
+        self.render();
+    }
+
+    pub fn set_land_texture(&mut self, land_texture: Option<Rc<glium::texture::texture2d::Texture2d>>) {
+        self.land_texture = land_texture;
+        self.render();
+    }
+
+    pub fn set_places(&mut self, places: Rc<Vec<Place>>) {
+        self.places = places;
+        self.render();
+    }
+
+    pub fn set_time_zone_labels(&mut self, time_zone_labels: Rc<Vec<TimeZoneLabel>>) {
+        self.time_zone_labels = time_zone_labels;
+        self.render();
+    }
+
+    pub fn body(&self) -> &data::Body { &self.body }
+
+    pub fn set_body(&mut self, body: data::Body) {
+        self.body = body;
+        // the projection may depend on the body's radius
+        self.on_projection_params_changed();
+    }
+
+    pub fn set_night_shading(&mut self, night_shading: Option<NightShading>) {
+        self.night_shading = night_shading;
+        self.render();
+    }
+
+    pub fn set_overlay(&mut self, overlay: Option<Overlay>) {
+        self.overlay = overlay;
+        self.render();
+    }
+
+    pub fn set_hillshading(&mut self, hillshading: Option<Hillshading>) {
+        self.hillshading = hillshading;
+        self.render();
+    }
+
+    pub fn set_web_tiles(&mut self, web_tiles: Option<WebTiles>) {
+        self.web_tiles = web_tiles;
+        self.render();
+    }
+
+    pub fn set_image_overlay(&mut self, image_overlay: Option<ImageOverlay>) {
+        self.image_overlay = image_overlay;
+        self.render();
+    }
+
+    pub fn set_scalar_field(&mut self, scalar_field: Option<ScalarField>) {
+        self.scalar_field = scalar_field;
+        self.render();
+    }
+
+    pub fn set_choropleth(&mut self, choropleth: Option<Choropleth>) {
+        self.choropleth = choropleth;
+        self.render();
+    }
+
+    pub fn choropleth(&self) -> Option<&Choropleth> { self.choropleth.as_ref() }
+
     pub fn draw_buf_id(&self) -> imgui::TextureId { self.draw_buf.id() }
 
+    /// Returns the width/height ratio of the view's image.
+    pub fn wh_ratio(&self) -> f32 { self.wh_ratio }
+
+    /// Returns the view's image as last rendered.
+    pub fn image(&self) -> image::RgbaImage { self.draw_buf.read_image() }
+
     pub fn drag_rotation(&self) -> DragRotation { self.drag_rotation }
 
+    pub fn stereo_mode(&self) -> StereoMode { self.stereo_mode }
+
+    pub fn is_layer_visible(&self, layer: Layer) -> bool {
+        self.layer_overrides.is_visible(layer, &self.global_layers)
+    }
+
+    fn visible_script_overlay(&self) -> Option<&LonLatGlBuffers> {
+        self.script_overlay_gl_buf.as_ref().filter(|_| self.is_layer_visible(Layer::ScriptOverlay))
+    }
+
+    /// Returns lines of vector layers which are (if `base_map` is true) or are not a part of the base map,
+    /// if shown by the view.
+    fn visible_vector_layer_lines(&self, base_map: bool) -> impl Iterator<Item = &DrawnLines> {
+        self.vector_layer_lines.iter().filter(move |lines| lines.base_map == base_map && if base_map {
+            self.view_mode == ViewMode::VectorMap
+        } else {
+            self.is_layer_visible(lines.layer)
+        })
+    }
+
+    pub fn layer_overrides(&self) -> LayerOverrides { self.layer_overrides }
+
+    pub fn set_layer_overrides(&mut self, layer_overrides: LayerOverrides) {
+        self.layer_overrides = layer_overrides;
+        self.render();
+    }
+
+    pub fn global_layers(&self) -> &LayerVisibility { &self.global_layers }
+
+    pub fn set_global_layers(&mut self, global_layers: LayerVisibility) {
+        self.global_layers = global_layers;
+        self.render();
+    }
+
+    pub fn set_stereo_mode(&mut self, stereo_mode: StereoMode) {
+        self.stereo_mode = stereo_mode;
+        self.render();
+    }
+
+    pub fn zoom(&self) -> f64 { self.zoom }
+
+    /// Returns horizontal and vertical stretch of the map, or `None` if the view does not support stretching.
+    pub fn stretch(&self) -> Option<[f64; 2]> { self.stretch }
+
+    /// Sets horizontal and vertical stretch of the map (changes its aspect ratio); does nothing if the view
+    /// does not support stretching.
+    pub fn set_stretch(&mut self, stretch: [f64; 2]) {
+        if self.stretch.is_none() { return; }
+
+        self.stretch = Some([
+            stretch[0].max(MIN_STRETCH).min(MAX_STRETCH),
+            stretch[1].max(MIN_STRETCH).min(MAX_STRETCH)
+        ]);
+        self.render();
+    }
+
+    /// Enables stretching of the map; the view's shaders have to apply the `stretch` uniform.
+    pub(in crate::views) fn enable_stretch(&mut self) {
+        self.stretch = Some([1.0, 1.0]);
+    }
+
+    /// Returns lobes of the interrupted map (empty if not interrupted), or `None` if the view does not support
+    /// interruption.
+    pub fn lobes(&self) -> Option<&[Lobe]> { self.lobes.as_deref() }
+
+    pub fn graticule_step(&self) -> cgmath::Deg<f64> { self.graticule_step }
+
+    pub fn graticule_substep(&self) -> cgmath::Deg<f64> { self.graticule_substep }
+
+    /// Sets the spacing of graticule lines (between 1° and 90°) and the approx. length of their segments.
+    pub fn set_graticule(&mut self, step: cgmath::Deg<f64>, substep: cgmath::Deg<f64>) -> Result<(), String> {
+        if !(step.0 >= 1.0 && step.0 <= 90.0) {
+            return Err("graticule step has to be between 1° and 90°".into());
+        }
+        if !(substep.0 > 0.0 && substep <= step) {
+            return Err("graticule substep has to be positive and not greater than the step".into());
+        }
+        if step == self.graticule_step && substep == self.graticule_substep { return Ok(()); }
+
+        self.graticule_gl_buf = data::create_graticule(step, substep, &self.display);
+        self.graticule_step = step;
+        self.graticule_substep = substep;
+        self.render();
+
+        Ok(())
+    }
+
+    /// Interrupts the map (or makes it uninterrupted if `lobes` is empty). Points not covered by any lobe
+    /// are not shown.
+    pub fn set_lobes(&mut self, lobes: Vec<Lobe>) -> Result<(), String> {
+        if self.lobes.is_none() { return Err("interruption is not supported by this view".to_string()); }
+        interruption::validate(&lobes)?;
+
+        self.lobes = Some(lobes);
+        self.update_lobes();
+        self.render();
+
+        Ok(())
+    }
+
+    /// Returns the latitude at which the map is truncated (if any).
+    pub fn truncation(&self) -> Option<cgmath::Deg<f64>> { self.truncation }
+
+    /// Returns true if the map can be truncated (see `set_truncation`).
+    pub fn supports_truncation(&self) -> bool { self.lobes.is_some() }
+
+    /// Hides the map beyond the specified latitude north and south (at least `MIN_TRUNCATION`); `None` shows
+    /// the whole map. Does nothing if the view does not support truncation.
+    pub fn set_truncation(&mut self, truncation: Option<cgmath::Deg<f64>>) {
+        if !self.supports_truncation() { return; }
+
+        self.truncation = truncation.map(|latitude| cgmath::Deg(latitude.0.max(MIN_TRUNCATION.0).min(90.0)));
+        self.update_lobes();
+        self.render();
+    }
+
+    /// Enables interruption of the map; the view has to use programs created with `LOBES` defined
+    /// (see `data::create_pseudocylindrical_gl_program_pair`).
+    pub(in crate::views) fn enable_interruption(&mut self) {
+        self.lobes = Some(vec![]);
+    }
+
+    fn update_lobes(&mut self) {
+        let lobes = match &self.lobes {
+            Some(lobes) => lobes,
+            None => return
+        };
+
+        self.lobe_uniforms = interruption::shader_uniforms(&self.projection, lobes, self.truncation);
+        let outline = interruption::outline(&self.projection, lobes, self.truncation);
+        self.outline_gl_buf = if outline.is_empty() { None } else { Some(self.create_segments_gl_buf(&outline)) };
+        self.outline = outline;
+    }
+
     pub fn zoom_by(&mut self, relative_zoom: f64) {
         self.zoom *= relative_zoom;
-        if self.zoom < 0.5 { self.zoom = 0.5; }
+        if self.zoom < MIN_ZOOM { self.zoom = MIN_ZOOM; }
+        self.render();
+    }
+
+    /// Sets the zoom (clamped to the min. zoom).
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom.max(MIN_ZOOM);
         self.render();
     }
 
     pub fn orientation(&self) -> &cgmath::Basis3<f64> { &self.orientation }
 
+    /// Returns the globe's orientation, the roll and the drag rotation mode (with its state).
+    pub fn rotation(&self) -> Rotation {
+        Rotation{
+            drag_rotation: self.drag_rotation,
+            orientation: self.orientation,
+            angle_ns: self.angle_ns,
+            angle_ew: self.angle_ew,
+            roll: self.roll
+        }
+    }
+
+    /// Sets the rotation (as returned by `rotation` of this or another view). If `rotation` is of another drag
+    /// rotation mode, the view keeps its own: it is then centered on the same point (see `center_on`) and rolled.
+    pub fn set_rotation(&mut self, rotation: &Rotation) {
+        if rotation.drag_rotation == self.drag_rotation {
+            self.orientation = rotation.orientation;
+            self.angle_ns = rotation.angle_ns;
+            self.angle_ew = rotation.angle_ew;
+            self.roll = rotation.roll;
+            self.render();
+        } else {
+            let (longitude, latitude) = central_lonlat(&rotation.orientation);
+            self.center_on(longitude, latitude);
+            self.set_roll(rotation.roll);
+        }
+    }
+
+    pub fn roll(&self) -> cgmath::Rad<f64> { self.roll }
+
+    /// Rotates the map about the view's center (counterclockwise for positive `roll`), so that any direction
+    /// of the map can point up.
+    pub fn set_roll(&mut self, roll: cgmath::Rad<f64>) {
+        use std::f64::consts::PI;
+
+        self.roll = cgmath::Rad((roll.0 + PI).rem_euclid(2.0 * PI) - PI);
+        self.render();
+    }
+
+    /// Rotates `position` (in normalized device coordinates) by `angle` about the view's center.
+    fn rotate_ndc(&self, position: Point2<f64>, angle: cgmath::Rad<f64>, wh_ratio: f32) -> Point2<f64> {
+        let (sin, cos) = angle.0.sin_cos();
+        let (x, y) = (position.x * wh_ratio as f64, position.y);
+
+        Point2{ x: (x * cos - y * sin) / wh_ratio as f64, y: x * sin + y * cos }
+    }
+
+    /// Returns (longitude, latitude) of the point in the center of the view.
+    pub fn central_lonlat(&self) -> (cgmath::Deg<f64>, cgmath::Deg<f64>) {
+        central_lonlat(&self.orientation)
+    }
+
+    pub fn set_script_overlay(&mut self, gl_buf: Option<LonLatGlBuffers>) {
+        if gl_buf.is_none() && self.script_overlay_gl_buf.is_none() { return; }
+
+        self.script_overlay_gl_buf = gl_buf;
+        self.render();
+    }
+
+    pub fn set_vector_layers(&mut self, lines: Vec<DrawnLines>) {
+        self.vector_layer_lines = lines;
+        self.render();
+    }
+
     pub fn set_orientation(&mut self, orientation: cgmath::Basis3<f64>) {
         if orientation != Basis3::one() {
             self.drag_rotation = DragRotation::Free;
@@ -107,6 +700,227 @@ impl ViewBase {
         self.render();
     }
 
+    /// Sets an oblique aspect: the point (`pole_longitude`, `pole_latitude`) of the globe becomes the projection's
+    /// north pole (i.e. the top of the view in the projection's normal aspect), and the globe is then rotated
+    /// by `rotation` around it.
+    pub fn set_oblique_aspect(
+        &mut self,
+        pole_longitude: cgmath::Deg<f64>,
+        pole_latitude: cgmath::Deg<f64>,
+        rotation: cgmath::Deg<f64>
+    ) {
+        self.set_orientation(oblique_aspect_orientation(pole_longitude, pole_latitude, rotation));
+    }
+
+    /// Returns the current aspect as (pole longitude, pole latitude, rotation around the pole);
+    /// see `set_oblique_aspect`.
+    pub fn oblique_aspect(&self) -> (cgmath::Deg<f64>, cgmath::Deg<f64>, cgmath::Deg<f64>) {
+        let pole = self.orientation.invert().rotate_vector(Vector3::unit_z());
+        let (pole_longitude, pole_latitude) = projection::xyz_to_lonlat(pole);
+        let (pole_longitude, pole_latitude) = (pole_longitude.into(), pole_latitude.into());
+
+        // the remaining rotation is around the Z axis
+        let without_rotation = oblique_aspect_orientation(pole_longitude, pole_latitude, cgmath::Deg(0.0));
+        let remainder = self.orientation * without_rotation.invert();
+        let x_axis = remainder.rotate_vector(Vector3::unit_x());
+        let rotation = cgmath::Rad(x_axis.y.atan2(x_axis.x)).into();
+
+        (pole_longitude, pole_latitude, rotation)
+    }
+
+    /// Rotates the globe so that the specified point is in the center of the view.
+    pub fn center_on(&mut self, longitude: cgmath::Deg<f64>, latitude: cgmath::Deg<f64>) {
+        match self.drag_rotation {
+            DragRotation::NSEW => {
+                self.angle_ew = -cgmath::Rad::from(longitude);
+                self.angle_ns = cgmath::Rad::from(latitude);
+                self.orientation = self.nsew_orientation();
+            },
+
+            DragRotation::Free => {
+                let position = self.orientation.rotate_vector(
+                    projection::lonlat_to_xyz(longitude.into(), latitude.into())
+                );
+                self.orientation = Basis3::between_vectors(position, Vector3::unit_x()) * self.orientation;
+            }
+        }
+
+        self.render();
+    }
+
+    /// Rotates the globe around the view's vertical axis (eastwards for positive `angle`).
+    pub fn rotate_ew(&mut self, angle: cgmath::Rad<f64>) {
+        match self.drag_rotation {
+            DragRotation::NSEW => {
+                self.angle_ew += angle;
+                self.orientation = self.nsew_orientation();
+            },
+
+            DragRotation::Free => self.orientation = Basis3::from_angle_z(angle) * self.orientation
+        }
+
+        self.render();
+    }
+
+    fn nsew_orientation(&self) -> Basis3<f64> {
+        Basis3::from_angle_y(self.angle_ns) * Basis3::from_angle_z(self.angle_ew)
+    }
+
+    pub fn add_marker(&mut self, longitude: cgmath::Deg<f64>, latitude: cgmath::Deg<f64>) {
+        self.markers.push((longitude, latitude));
+    }
+
+    pub fn clear_markers(&mut self) {
+        self.markers.clear();
+    }
+
+    pub fn has_markers(&self) -> bool { !self.markers.is_empty() }
+
+    /// Returns (longitude, latitude) of the markers.
+    pub fn markers(&self) -> &[(cgmath::Deg<f64>, cgmath::Deg<f64>)] { &self.markers }
+
+    /// Returns positions (in normalized device coordinates) of the visible places of at least `min_rank`,
+    /// in the order of decreasing rank.
+    pub fn projected_places(&self, min_rank: u8) -> Vec<(Point2<f64>, &Place)> {
+        self.places.iter()
+            .take_while(|place| place.rank >= min_rank)
+            .filter_map(|place| {
+                self.project_to_ndc(place.longitude, place.latitude, self.wh_ratio)
+                    .filter(|p| p.x.abs() <= 1.0 && p.y.abs() <= 1.0)
+                    .map(|p| (p, place))
+            })
+            .collect()
+    }
+
+    pub fn has_places(&self) -> bool { !self.places.is_empty() }
+
+    /// Returns positions (in normalized device coordinates) of the visible time zone labels.
+    pub fn projected_time_zone_labels(&self) -> Vec<(Point2<f64>, &TimeZoneLabel)> {
+        self.time_zone_labels.iter()
+            .filter_map(|label| {
+                self.project_to_ndc(label.longitude, label.latitude, self.wh_ratio)
+                    .filter(|p| p.x.abs() <= 1.0 && p.y.abs() <= 1.0)
+                    .map(|p| (p, label))
+            })
+            .collect()
+    }
+
+    pub fn has_time_zone_labels(&self) -> bool { !self.time_zone_labels.is_empty() }
+
+    /// Returns positions (in normalized device coordinates) of the visible markers.
+    pub fn projected_markers(&self) -> Vec<Point2<f64>> {
+        self.markers.iter().filter_map(|(lon, lat)| self.project_to_ndc(*lon, *lat, self.wh_ratio)).collect()
+    }
+
+    pub fn set_user_markers(&mut self, user_markers: Rc<Vec<UserMarker>>) {
+        self.user_markers = user_markers;
+    }
+
+    pub fn has_user_markers(&self) -> bool { !self.user_markers.is_empty() }
+
+    /// Returns positions (in normalized device coordinates) of the visible named markers.
+    pub fn projected_user_markers(&self) -> Vec<(Point2<f64>, &UserMarker)> {
+        self.user_markers.iter()
+            .filter_map(|m| self.project_to_ndc(m.longitude, m.latitude, self.wh_ratio).map(|p| (p, m)))
+            .collect()
+    }
+
+    pub fn set_point_layers(&mut self, point_layers: Rc<Vec<PointLayer>>) {
+        self.point_layers = point_layers;
+    }
+
+    /// Returns true if any point layer is shown (also if the "vector layers" layer is hidden).
+    pub fn has_point_layers(&self) -> bool { self.point_layers.iter().any(|layer| layer.visible) }
+
+    pub fn set_vector_field(&mut self, vector_field: Option<VectorFieldLayer>) {
+        self.vector_field = vector_field;
+    }
+
+    pub fn vector_field(&self) -> Option<&VectorFieldLayer> { self.vector_field.as_ref() }
+
+    /// Returns the visible arrows of the vector field (if any), sampled every `VectorFieldParams::spacing` degrees:
+    /// positions (in normalized device coordinates), points close to them in the direction of the field's vectors,
+    /// and the vectors' magnitudes.
+    pub fn projected_vector_field(&self) -> Vec<(Point2<f64>, Point2<f64>, f32)> {
+        // angular distance of the points used to find the image of the local east-north frame
+        const D: f64 = 0.01;
+
+        let layer = match &self.vector_field {
+            Some(layer) => layer,
+            None => return vec![]
+        };
+        let spacing = (layer.params.spacing as f64).max(MIN_VECTOR_FIELD_SPACING);
+        let project = |longitude: f64, latitude: f64| {
+            self.project_to_ndc(cgmath::Deg(longitude), cgmath::Deg(latitude), self.wh_ratio)
+        };
+
+        let mut arrows = vec![];
+        let mut latitude = -90.0 + spacing / 2.0;
+        while latitude < 90.0 {
+            // keep arrows evenly spaced on the sphere
+            let cos_lat = latitude.to_radians().cos();
+            let num_arrows = (360.0 * cos_lat / spacing).round().max(1.0) as usize;
+            for i in 0..num_arrows {
+                let longitude = -180.0 + (i as f64 + 0.5) / num_arrows as f64 * 360.0;
+                let [u, v] = match layer.field.sample(longitude, latitude) {
+                    Some(uv) => uv,
+                    None => continue
+                };
+                let p0 = match project(longitude, latitude) {
+                    Some(p) if p.x.abs() <= 1.0 && p.y.abs() <= 1.0 => p,
+                    _ => continue
+                };
+                let p_east = project(longitude + D / cos_lat, latitude);
+                let p_north = project(longitude, latitude + D);
+                let (p_east, p_north) = match (p_east, p_north) {
+                    (Some(p_east), Some(p_north)) => (p_east, p_north),
+                    _ => continue
+                };
+                // skip points at interruptions or the antimeridian
+                if (p_east - p0).magnitude() > MAX_PROJECTED_SEGMENT_LENGTH
+                    || (p_north - p0).magnitude() > MAX_PROJECTED_SEGMENT_LENGTH
+                {
+                    continue;
+                }
+                let direction = (p_east - p0) * u as f64 + (p_north - p0) * v as f64;
+                if direction.magnitude2() == 0.0 { continue; }
+
+                arrows.push((p0, p0 + direction.normalize() * D, u.hypot(v)));
+            }
+            latitude += spacing;
+        }
+
+        arrows
+    }
+
+    /// Returns positions (in normalized device coordinates) of the visible points of the shown point layers.
+    pub fn projected_point_layers(&self) -> Vec<(Point2<f64>, &PointLayer, &csv::Point)> {
+        self.point_layers.iter().filter(|layer| layer.visible).flat_map(|layer| {
+            layer.points.iter().filter_map(move |point| {
+                self.project_to_ndc(cgmath::Deg(point.longitude), cgmath::Deg(point.latitude), self.wh_ratio)
+                    .filter(|p| p.x.abs() <= 1.0 && p.y.abs() <= 1.0)
+                    .map(|p| (p, layer, point))
+            })
+        }).collect()
+    }
+
+    /// Returns the position (in normalized device coordinates) of the point given as [longitude, latitude]
+    /// in degrees, or `None` if not visible.
+    pub fn projected_point(&self, lonlat: [f64; 2]) -> Option<Point2<f64>> {
+        self.project_to_ndc(cgmath::Deg(lonlat[0]), cgmath::Deg(lonlat[1]), self.wh_ratio)
+    }
+
+    /// Returns the visible segments (in normalized device coordinates) of `line` given as [longitude, latitude]
+    /// in degrees; its vertices have to be close to one another.
+    pub fn projected_polyline(&self, line: &[[f64; 2]]) -> Vec<[Point2<f64>; 2]> {
+        let projected: Vec<Option<Point2<f64>>> = line.iter().map(|p| self.projected_point(*p)).collect();
+
+        projected.iter().zip(projected.iter().skip(1)).filter_map(|(p1, p2)| {
+            let (p1, p2) = ((*p1)?, (*p2)?);
+            if (p2 - p1).magnitude() > MAX_PROJECTED_SEGMENT_LENGTH { None } else { Some([p1, p2]) }
+        }).collect()
+    }
+
     pub fn set_drag_rotation(&mut self, drag_rotation: DragRotation) {
         self.drag_rotation = drag_rotation;
         if drag_rotation == DragRotation::NSEW {
@@ -117,63 +931,792 @@ impl ViewBase {
         }
     }
 
-    pub(in crate::views) fn render(
+    pub(in crate::views) fn render(&self) {
+        self.render_to(&self.draw_buf, self.wh_ratio, Layers::All, NO_CROP);
+        self.rendered.set(true);
+    }
+
+    /// Returns true if the view has been rendered (i.e., its contents may have changed) since the previous call.
+    pub fn take_rendered(&self) -> bool {
+        self.rendered.replace(false)
+    }
+
+    /// Renders the view offscreen at the specified size; the view's own draw buffer is not affected.
+    pub fn render_offscreen(&self, width: u32, height: u32, layers: Layers) -> image::RgbaImage {
+        let draw_buf = self.draw_buf.new_similar(self.draw_buf.sampling(), width, height);
+        self.render_to(&draw_buf, width as f32 / height as f32, layers, NO_CROP);
+
+        draw_buf.read_image()
+    }
+
+    /// Renders the view offscreen at the specified size, which may exceed the max. texture size: the image is then
+    /// rendered in tiles (cropped regions of the view) which are assembled.
+    pub fn render_offscreen_tiled(&self, width: u32, height: u32, layers: Layers) -> Result<image::RgbaImage, String> {
+        self.render_tiles(&mut None, width, height, layers)
+    }
+
+    /// Renders the view offscreen as `render_offscreen_tiled`, reusing the view's frame draw buffer between calls
+    /// (e.g. for successive frames of an animation of the same size).
+    pub fn render_frame(&mut self, width: u32, height: u32, layers: Layers) -> Result<image::RgbaImage, String> {
+        let mut draw_buf = self.frame_buf.take();
+        let result = self.render_tiles(&mut draw_buf, width, height, layers);
+        self.frame_buf = draw_buf;
+
+        result
+    }
+
+    /// Renders the view offscreen at the specified size in tiles of equal size (fitting in the max. texture size)
+    /// using `draw_buf` (created if `None`); tiles at the right and bottom edges may extend beyond the image.
+    fn render_tiles(
         &self,
-        //view_specific_uniforms: glium::uniforms::UniformsStorage<'_, T, R>
-    ) {
+        draw_buf: &mut Option<DrawBuffer>,
+        width: u32,
+        height: u32,
+        layers: Layers
+    ) -> Result<image::RgbaImage, String> {
+        if width > MAX_OFFSCREEN_SIZE || height > MAX_OFFSCREEN_SIZE {
+            return Err(format!("image size is limited to {}×{} pixels", MAX_OFFSCREEN_SIZE, MAX_OFFSCREEN_SIZE));
+        }
+        let max_tile_size = (self.display.get_capabilities().max_texture_size as u32).min(MAX_OFFSCREEN_TILE_SIZE);
+        let (tile_width, tile_height) = (width.min(max_tile_size), height.min(max_tile_size));
+        let tiled = tile_width < width || tile_height < height;
+        if tiled && self.stereo_mode == StereoMode::SideBySide {
+            return Err(format!(
+                "views in side-by-side stereo mode can be exported at most {} pixels wide", max_tile_size
+            ));
+        }
+
+        let draw_buf = draw_buf.get_or_insert_with(
+            || self.draw_buf.new_similar(self.draw_buf.sampling(), tile_width, tile_height)
+        );
+        draw_buf.update_size(tile_width, tile_height);
+        if !tiled {
+            self.render_to(draw_buf, width as f32 / height as f32, layers, NO_CROP);
+            return Ok(draw_buf.read_image());
+        }
+
+        let mut image = image::RgbaImage::new(width, height);
+        for top in (0..height).step_by(tile_height as usize) {
+            for left in (0..width).step_by(tile_width as usize) {
+                // the tile's region in normalized device coordinates of the whole image
+                let x = [left, left + tile_width].map(|x| -1.0 + 2.0 * x as f32 / width as f32);
+                let y = [top + tile_height, top].map(|y| 1.0 - 2.0 * y as f32 / height as f32);
+                let crop = [(x[0] + x[1]) / 2.0, (y[0] + y[1]) / 2.0, 2.0 / (x[1] - x[0]), 2.0 / (y[1] - y[0])];
+                self.render_to(draw_buf, width as f32 / height as f32, layers, crop);
+
+                // clipped to the image
+                image::imageops::replace(&mut image, &draw_buf.read_image(), left, top);
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Renders a square crop of the view centered at `center` (in normalized device coordinates), magnified
+    /// `magnification` times, into the magnifier's draw buffer of `size`×`size` pixels; returns the buffer's id.
+    pub fn render_magnifier(&mut self, center: Point2<f64>, magnification: f64, size: u32) -> imgui::TextureId {
+        let mut draw_buf = self.magnifier_buf.take()
+            .unwrap_or_else(|| self.draw_buf.new_similar(self.draw_buf.sampling(), size, size));
+        draw_buf.update_size(size, size);
+
+        let scale = magnification as f32 / size as f32;
+        let crop = [
+            center.x as f32,
+            center.y as f32,
+            self.draw_buf.width() as f32 * scale,
+            self.draw_buf.height() as f32 * scale
+        ];
+        self.render_to(&draw_buf, self.wh_ratio, Layers::All, crop);
+
+        let id = draw_buf.id();
+        self.magnifier_buf = Some(draw_buf);
+
+        id
+    }
+
+    /// Projects all lines drawn by the view (as they would appear with the specified width/height ratio).
+    pub fn projected_lines(&self, wh_ratio: f32) -> Vec<ProjectedLines> {
+        let mut result = vec![];
+
+        for lines in self.visible_vector_layer_lines(true) {
+            result.push(ProjectedLines{
+                color: lines.style.color,
+                segments: self.project_lines(&lines.gl_buf, wh_ratio)
+            });
+        }
+        if self.is_layer_visible(Layer::Graticule) {
+            result.push(ProjectedLines{
+                color: GRATICULE_COLOR,
+                segments: self.project_lines(&self.graticule_gl_buf, wh_ratio)
+            });
+        }
+        for lines in self.visible_vector_layer_lines(false) {
+            result.push(ProjectedLines{
+                color: lines.style.color,
+                segments: self.project_lines(&lines.gl_buf, wh_ratio)
+            });
+        }
+        if let Some(script_overlay_gl_buf) = self.visible_script_overlay() {
+            result.push(ProjectedLines{
+                color: SCRIPT_OVERLAY_COLOR,
+                segments: self.project_lines(script_overlay_gl_buf, wh_ratio)
+            });
+        }
+        if self.is_layer_visible(Layer::Outline) {
+            let to_ndc = |p: Point2<f64>| self.map_to_ndc(p, wh_ratio);
+            if !self.outline.is_empty() {
+                result.push(ProjectedLines{
+                    color: OUTLINE_COLOR,
+                    segments: self.outline.iter().map(|s| [to_ndc(s[0]), to_ndc(s[1])]).collect()
+                });
+            }
+            if let Some(boundary) = self.projection.boundary() {
+                result.push(ProjectedLines{
+                    color: OUTLINE_COLOR,
+                    segments: boundary.iter().map(|s| [to_ndc(s[0]), to_ndc(s[1])]).collect()
+                });
+            }
+        }
+
+        result
+    }
+
+    /// Projects the line list stored in `gl_buf` the same way as the vertex and geometry shaders do.
+    fn project_lines(&self, gl_buf: &LonLatGlBuffers, wh_ratio: f32) -> Vec<[Point2<f64>; 2]> {
+        let vertices = gl_buf.vertices.read().unwrap();
+        let indices = gl_buf.indices.read().unwrap();
+
+        let project = |vertex: &LonLatVertex| self.project_to_ndc(
+            cgmath::Deg(vertex.lonlat_position[0] as f64),
+            cgmath::Deg(vertex.lonlat_position[1] as f64),
+            wh_ratio
+        );
+
+        indices.chunks_exact(2).filter_map(|segment| {
+            let p1 = project(&vertices[segment[0] as usize])?;
+            let p2 = project(&vertices[segment[1] as usize])?;
+            if (p2 - p1).magnitude() > MAX_PROJECTED_SEGMENT_LENGTH {
+                None
+            } else {
+                Some([p1, p2])
+            }
+        }).collect()
+    }
+
+    /// Returns the position (in normalized device coordinates) of the specified point, or `None` if not visible.
+    fn project_to_ndc(
+        &self,
+        longitude: cgmath::Deg<f64>,
+        latitude: cgmath::Deg<f64>,
+        wh_ratio: f32
+    ) -> Option<Point2<f64>> {
+        self.project_to_map(longitude, latitude).map(|p| self.map_to_ndc(p, wh_ratio))
+    }
+
+    /// Projects `polylines` (longitude, latitude in degrees) to grid coordinates (meters; see `grid_coordinates`).
+    /// If the view does not declare grid parameters, map coordinates of the view's rotation and interruption
+    /// (but not its zoom, stretch and roll) are scaled to meters. Lines are split where they leave the map or jump
+    /// between its distant parts (e.g. at the antimeridian); parts of a single point are omitted.
+    pub fn project_polylines(&self, polylines: &[geojson::Polyline]) -> Vec<Vec<Point2<f64>>> {
+        let declared_grid = self.grid();
+        let grid = declared_grid.unwrap_or(Grid::DEFAULT);
+        let meters_per_map_unit = grid.scale_factor * self.body.semi_major_axis;
+
+        let mut result = vec![];
+        for polyline in polylines {
+            let mut part: Vec<Point2<f64>> = vec![];
+            let mut last_point: Option<Point2<f64>> = None;
+            for [lon, lat] in polyline {
+                let point = match &declared_grid {
+                    Some(grid) => grid.map_position(&self.projection, cgmath::Deg(*lon), cgmath::Deg(*lat)),
+                    None => self.project_to_map(cgmath::Deg(*lon), cgmath::Deg(*lat))
+                };
+                let continued = match (last_point, point) {
+                    (Some(last), Some(p)) => (p - last).magnitude() <= MAX_MAP_SEGMENT_LENGTH,
+                    _ => false
+                };
+                if !continued {
+                    if part.len() > 1 { result.push(std::mem::take(&mut part)); } else { part.clear(); }
+                }
+                if let Some(p) = point {
+                    part.push(Point2{
+                        x: grid.false_easting + meters_per_map_unit * p.x,
+                        y: grid.false_northing + meters_per_map_unit * p.y
+                    });
+                }
+                last_point = point;
+            }
+            if part.len() > 1 {
+                result.push(part);
+            }
+        }
+
+        result
+    }
+
+    /// Returns the position (in map coordinates) of the specified point, or `None` if not visible.
+    fn project_to_map(&self, longitude: cgmath::Deg<f64>, latitude: cgmath::Deg<f64>) -> Option<Point2<f64>> {
+        let position = self.orientation.rotate_vector(projection::lonlat_to_xyz(longitude.into(), latitude.into()));
+        let (rotated_longitude, rotated_latitude) = projection::xyz_to_lonlat(position);
+
+        if let Some(truncation) = self.truncation {
+            if cgmath::Deg::from(rotated_latitude).0.abs() > truncation.0 { return None; }
+        }
+
+        match self.lobes.as_deref() {
+            Some(lobes) if !lobes.is_empty() => {
+                interruption::project(&self.projection, lobes, rotated_longitude, rotated_latitude)
+            },
+            _ => self.projection.project(position)
+        }
+    }
+
+    /// Converts map coordinates to normalized device coordinates.
+    fn map_to_ndc(&self, position: Point2<f64>, wh_ratio: f32) -> Point2<f64> {
+        let [stretch_x, stretch_y] = self.stretch.unwrap_or([1.0, 1.0]);
+        let position = Point2{
+            x: position.x * stretch_x * self.zoom / wh_ratio as f64,
+            y: position.y * stretch_y * self.zoom
+        };
+
+        self.rotate_ndc(position, self.roll, wh_ratio)
+    }
+
+    /// Converts normalized device coordinates to map coordinates (inverse of `map_to_ndc`).
+    fn ndc_to_map(&self, position: Point2<f64>, wh_ratio: f32) -> Point2<f64> {
+        let [stretch_x, stretch_y] = self.stretch.unwrap_or([1.0, 1.0]);
+        let position = self.rotate_ndc(position, -self.roll, wh_ratio);
+
+        Point2{
+            x: position.x * wh_ratio as f64 / (stretch_x * self.zoom),
+            y: position.y / (stretch_y * self.zoom)
+        }
+    }
+
+    /// Returns the ranges of longitude and latitude (degrees) shown by the view (approximately, from a grid
+    /// of unprojected points) and the view's resolution (pixels per degree of longitude), or `None` if nothing
+    /// is shown. A range of longitude crossing the antimeridian ends beyond 180° (e.g. [170, 200]); if a pole
+    /// is shown, the range is [-180, 180].
+    pub fn visible_region(&self) -> Option<([f64; 2], [f64; 2], f64)> {
+        const NUM_STEPS: usize = 10;
+        let points: Vec<[f64; 2]> = (0..=NUM_STEPS).flat_map(|i| (0..=NUM_STEPS).map(move |j| Point2{
+            x: -1.0 + 2.0 * i as f64 / NUM_STEPS as f64,
+            y: -1.0 + 2.0 * j as f64 / NUM_STEPS as f64
+        })).filter_map(|position| self.unproject(position).map(|(lon, lat)| [lon.0, lat.0])).collect();
+        if points.is_empty() { return None; }
+
+        let is_visible = |p: Option<Point2<f64>>| p.map_or(false, |p| p.x.abs() <= 1.0 && p.y.abs() <= 1.0);
+        let north_pole = is_visible(self.project_to_ndc(cgmath::Deg(0.0), cgmath::Deg(90.0), self.wh_ratio));
+        let south_pole = is_visible(self.project_to_ndc(cgmath::Deg(0.0), cgmath::Deg(-90.0), self.wh_ratio));
+        let min_lat = if south_pole { -90.0 } else { points.iter().map(|p| p[1]).fold(90.0, f64::min) };
+        let max_lat = if north_pole { 90.0 } else { points.iter().map(|p| p[1]).fold(-90.0, f64::max) };
+
+        let lon_range = if north_pole || south_pole {
+            [-180.0, 180.0]
+        } else {
+            // the shown longitudes are those outside the largest gap between the sampled ones
+            let mut longitudes: Vec<f64> = points.iter().map(|p| p[0]).collect();
+            longitudes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let wrap_gap = longitudes[0] + 360.0 - longitudes[longitudes.len() - 1];
+            let (gap, gap_end) = longitudes.windows(2)
+                .map(|w| (w[1] - w[0], w[1]))
+                .fold((wrap_gap, longitudes[0]), |largest, gap| if gap.0 > largest.0 { gap } else { largest });
+            [gap_end, gap_end + 360.0 - gap]
+        };
+        let lon_span = (lon_range[1] - lon_range[0]).max(1.0e-6);
+
+        Some((lon_range, [min_lat, max_lat], self.draw_buf.width() as f64 / lon_span))
+    }
+
+    /// Returns (longitude, latitude) of the point shown at `position` (in normalized device coordinates),
+    /// or `None` if there is no such point.
+    ///
+    /// Closed-form inverse formulas are used if the projection has them (see `Projection::inverse`) and the map
+    /// is not interrupted; otherwise the projection is inverted numerically (see `projection::invert_numerically`).
+    ///
+    pub fn unproject(&self, position: Point2<f64>) -> Option<(cgmath::Deg<f64>, cgmath::Deg<f64>)> {
+        if self.projection.has_inverse() && self.lobes.as_deref().map_or(true, |lobes| lobes.is_empty()) {
+            let rotated = self.projection.inverse(self.ndc_to_map(position, self.wh_ratio))?;
+            if let Some(truncation) = self.truncation {
+                if cgmath::Deg::from(projection::xyz_to_lonlat(rotated).1).0.abs() > truncation.0 { return None; }
+            }
+            let (longitude, latitude) = projection::xyz_to_lonlat(self.orientation.invert().rotate_vector(rotated));
+            return Some((longitude.into(), latitude.into()));
+        }
+
+        projection::invert_numerically(
+            |longitude, latitude| self.project_to_ndc(cgmath::Deg(longitude), cgmath::Deg(latitude), self.wh_ratio),
+            position
+        ).map(|(longitude, latitude)| (cgmath::Deg(longitude), cgmath::Deg(latitude)))
+    }
+
+    /// Returns labels of the graticule lines visible in the view.
+    ///
+    /// Meridians are labeled at a latitude close to the view's center, parallels at a close longitude;
+    /// in both cases halfway between graticule lines, so that labels do not cover the intersections.
+    ///
+    pub fn graticule_labels(&self) -> Vec<GraticuleLabel> {
+        let step = self.graticule_step.0;
+        let label_step = GRATICULE_LABEL_INTERVAL * step;
+        let max_label_latitude = 90.0 - GRATICULE_LABEL_PATH_HALF_LENGTH as f64;
+
+        let (central_longitude, central_latitude) = self.central_lonlat();
+        let halfway = |value: f64| (value / step).floor() * step + step / 2.0;
+        let label_longitude = halfway(central_longitude.0);
+        let label_latitude = halfway(central_latitude.0).max(-max_label_latitude).min(max_label_latitude);
+
+        let mut labels = vec![];
+
+        let mut longitude = -180.0 + label_step;
+        while longitude <= 180.0 {
+            let label = self.graticule_label(
+                format_longitude(longitude),
+                |offset| (longitude, label_latitude + offset)
+            );
+            labels.extend(label);
+            longitude += label_step;
+        }
+
+        let mut latitude = -90.0 + label_step;
+        while latitude < 90.0 {
+            let label = self.graticule_label(
+                format_latitude(latitude),
+                |offset| (label_longitude + offset, latitude)
+            );
+            labels.extend(label);
+            latitude += label_step;
+        }
+
+        labels
+    }
+
+    /// Creates a label of the line whose points are given by `point_at` (returning longitude and latitude
+    /// in degrees of the line's point at `offset` degrees from the label's center).
+    fn graticule_label<F: Fn(f64) -> (f64, f64)>(&self, text: String, point_at: F) -> Option<GraticuleLabel> {
+        let project = |offset: f64| {
+            let (longitude, latitude) = point_at(offset);
+            self.project_to_ndc(cgmath::Deg(longitude), cgmath::Deg(latitude), self.wh_ratio)
+        };
+
+        let anchor = project(0.0)?;
+        if anchor.x.abs() > 1.0 || anchor.y.abs() > 1.0 { return None; }
+
+        // follows the line from the anchor in the specified direction as long as it is visible and continuous
+        let follow_line = |direction: f64| {
+            let mut points = vec![];
+            let mut previous = anchor;
+            for i in 1..=GRATICULE_LABEL_PATH_HALF_LENGTH {
+                match project(direction * i as f64) {
+                    Some(p) if (p - previous).magnitude() <= MAX_PROJECTED_SEGMENT_LENGTH => {
+                        points.push(p);
+                        previous = p;
+                    },
+                    _ => break
+                }
+            }
+            points
+        };
+
+        let mut path = follow_line(-1.0);
+        path.reverse();
+        let anchor_idx = path.len();
+        path.push(anchor);
+        path.extend(follow_line(1.0));
+
+        Some(GraticuleLabel{ text, path, anchor: anchor_idx })
+    }
+
+    /// Renders the view's region selected by `crop` (see `NO_CROP`) into `draw_buf`.
+    fn render_to(&self, draw_buf: &DrawBuffer, wh_ratio: f32, layers: Layers, crop: [f32; 4]) {
+        let mut target = draw_buf.frame_buf();
+
+        match self.view_mode {
+            ViewMode::GlobeTexture => target.clear_color(0.5, 0.5, 0.5, 1.0),
+            ViewMode::VectorMap => target.clear_color(0.87, 0.87, 0.87, 1.0)
+        }
+
         // no need for a depth test; depending on particular view, either the projection clips the rear hemisphere,
         // or the vertex shader outputs vertices on a plane
-        let draw_params = glium::DrawParameters::default();
+        match self.stereo_mode {
+            StereoMode::Off => self.draw(&mut target, &self.orientation, wh_ratio, layers, crop, &Default::default()),
+
+            StereoMode::Anaglyph => {
+                // color masks select the red channel for the left eye and green & blue for the right one
+                let eyes = [(Eye::Left, (true, false, false, true)), (Eye::Right, (false, true, true, true))];
+                for (eye, color_mask) in eyes {
+                    self.draw(
+                        &mut target,
+                        &self.eye_orientation(eye),
+                        wh_ratio,
+                        layers,
+                        crop,
+                        &glium::DrawParameters{ color_mask, ..Default::default() }
+                    );
+                }
+            },
+
+            StereoMode::SideBySide => {
+                let (width, height) = (draw_buf.width() / 2, draw_buf.height());
+                for (eye, left) in [(Eye::Left, 0), (Eye::Right, width)] {
+                    self.draw(
+                        &mut target,
+                        &self.eye_orientation(eye),
+                        wh_ratio / 2.0,
+                        layers,
+                        crop,
+                        &glium::DrawParameters{
+                            viewport: Some(glium::Rect{ left, bottom: 0, width, height }),
+                            ..Default::default()
+                        }
+                    );
+                }
+            }
+        }
+
+        draw_buf.update_storage_buf();
+    }
+
+    /// Returns orientation of the globe as seen by one of the eyes in stereo mode.
+    fn eye_orientation(&self, eye: Eye) -> Basis3<f64> {
+        let angle = match eye {
+            Eye::Left => STEREO_EYE_ANGLE,
+            Eye::Right => -STEREO_EYE_ANGLE
+        };
+
+        // rotating the globe eastwards is equivalent to moving the observer westwards
+        Basis3::from_angle_z(cgmath::Rad::from(angle)) * self.orientation
+    }
 
+    fn draw<S: Surface>(
+        &self,
+        target: &mut S,
+        orientation: &Basis3<f64>,
+        wh_ratio: f32,
+        layers: Layers,
+        crop: [f32; 4],
+        draw_params: &glium::DrawParameters
+    ) {
+        let [stretch_x, stretch_y] = self.stretch.unwrap_or([1.0, 1.0]);
+        // in the vector map mode, the globe mesh (if drawn) shows filled land and ocean
+        let map_texture = match (self.view_mode, &self.land_texture) {
+            (ViewMode::VectorMap, Some(land_texture)) => Some(&**land_texture),
+            (ViewMode::VectorMap, None) => None,
+            (ViewMode::GlobeTexture, _) => Some(&*self.globe_texture)
+        };
         let uniforms = uniform! {
-            globe_orientation: Matrix3::from(self.orientation).cast::<f32>().unwrap().to_array(),
+            globe_orientation: Matrix3::from(*orientation).cast::<f32>().unwrap().to_array(),
             zoom: self.zoom as f32,
-            wh_ratio : self.wh_ratio,
-            source_texture: glium::uniforms::Sampler::new(&*self.globe_texture)
-                .wrap_function(glium::uniforms::SamplerWrapFunction::Clamp)
+            stretch: [stretch_x as f32, stretch_y as f32],
+            wh_ratio : wh_ratio,
+            roll: self.roll.0 as f32,
+            crop: crop
+        };
+        let sampler = |texture| {
+            glium::uniforms::Sampler::new(texture).wrap_function(glium::uniforms::SamplerWrapFunction::Clamp)
+        };
+        let with_texture = |texture| {
+            uniforms.clone()
+                .add("source_texture", sampler(texture))
+                .add("night_blending", false)
+                .add("web_tiles", false)
+                .add("field_opacity", 0.0f32)
+                .add("image_opacity", 0.0f32)
+                .add("overlay_opacity", 0.0f32)
+                .add("hillshading", 0i32)
         };
 
-        let mut target = self.draw_buf.frame_buf();
+        // parts of an interrupted or truncated map are clipped to their lobes (see "tris.geom")
+        let map_draw_params = glium::DrawParameters{
+            clip_planes_bitmask: if self.lobe_uniforms.is_empty() { 0 } else { 0b1111 },
+            ..draw_params.clone()
+        };
 
-        match self.view_mode {
-            ViewMode::GlobeTexture => {
-                target.clear_color(0.5, 0.5, 0.5, 1.0);
+        // in the globe texture mode, night lights (if available) replace darkening of the night side
+        let night_lights = match (&self.night_shading, self.view_mode) {
+            (Some(NightShading{ night_lights: Some(night_lights), sun_direction, .. }), ViewMode::GlobeTexture) => {
+                Some((&**night_lights, *sun_direction))
+            },
+            _ => None
+        };
 
-                target.draw(
+        if let Some(map_texture) = map_texture {
+            // in the globe texture mode, the overlay (if any) is blended over the map in "globe_texturing.frag"
+            let (overlay_texture, overlay_opacity) = match (&self.overlay, self.view_mode) {
+                (Some(overlay), ViewMode::GlobeTexture) => (&*overlay.texture, overlay.opacity),
+                _ => (map_texture, 0.0)
+            };
+            // web tiles (if any) are shown over the base map in the globe texture mode
+            let (web_tiles, web_tiles_texture, web_tiles_bounds) = match (&self.web_tiles, self.view_mode) {
+                (Some(web_tiles), ViewMode::GlobeTexture) => (true, &*web_tiles.texture, [
+                    ((web_tiles.lon_range[0] + 180.0) / 360.0) as f32,
+                    ((90.0 - web_tiles.lat_range[1]) / 180.0) as f32,
+                    ((web_tiles.lon_range[1] + 180.0) / 360.0) as f32,
+                    ((90.0 - web_tiles.lat_range[0]) / 180.0) as f32
+                ]),
+                _ => (false, map_texture, [0.0; 4])
+            };
+            let (field_texture, field_opacity) = match (&self.scalar_field, self.view_mode) {
+                (Some(field), ViewMode::GlobeTexture) => (&*field.texture, field.params.opacity),
+                _ => (map_texture, 0.0)
+            };
+            // a georeferenced image (if any) is blended over the scalar field in the globe texture mode
+            let (image_texture, image_bounds, image_opacity) = match (&self.image_overlay, self.view_mode) {
+                (Some(image), ViewMode::GlobeTexture) => (&*image.texture, [
+                    ((image.lon_range[0] + 180.0) / 360.0) as f32,
+                    ((90.0 - image.lat_range[1]) / 180.0) as f32,
+                    ((image.lon_range[1] + 180.0) / 360.0) as f32,
+                    ((90.0 - image.lat_range[0]) / 180.0) as f32
+                ], image.opacity),
+                _ => (map_texture, [0.0; 4], 0.0)
+            };
+            // hillshading (if any) is computed in "globe_texturing.frag"
+            let (hillshading, elevation_texture, params) = match &self.hillshading {
+                Some(Hillshading{ elevation, params }) => (
+                    match params.mode { HillshadingMode::Modulate => 1, HillshadingMode::Standalone => 2 },
+                    &**elevation,
+                    *params
+                ),
+                None => (0i32, map_texture, Default::default())
+            };
+            let sun_azimuth = cgmath::Rad::from(params.sun_azimuth).0;
+            let sun_elevation = cgmath::Rad::from(params.sun_elevation).0;
+            let map_uniforms = uniforms.clone()
+                .add("source_texture", sampler(map_texture))
+                .add("web_tiles", web_tiles)
+                .add("web_tiles_texture", sampler(web_tiles_texture)
+                    .minify_filter(glium::uniforms::MinifySamplerFilter::LinearMipmapLinear))
+                .add("web_tiles_bounds", web_tiles_bounds)
+                .add("field_texture", sampler(field_texture))
+                .add("field_opacity", field_opacity)
+                .add("image_texture", sampler(image_texture)
+                    .minify_filter(glium::uniforms::MinifySamplerFilter::Linear))
+                .add("image_bounds", image_bounds)
+                .add("image_opacity", image_opacity)
+                .add("overlay_texture", sampler(overlay_texture))
+                .add("overlay_opacity", overlay_opacity)
+                .add("hillshading", hillshading)
+                .add("elevation_texture", sampler(elevation_texture)
+                    .minify_filter(glium::uniforms::MinifySamplerFilter::Linear))
+                .add("elevation_range", params.elevation_range)
+                .add("elevation_exaggeration", params.exaggeration)
+                .add("hillshading_sun", [
+                    sun_azimuth.sin() * sun_elevation.cos(),
+                    sun_azimuth.cos() * sun_elevation.cos(),
+                    sun_elevation.sin()
+                ])
+                .add("body_radius", (1000.0 * self.body.mean_radius) as f32);
+
+            match night_lights {
+                // blended across the terminator in "globe_texturing.frag"
+                Some((night_lights, sun_direction)) => target.draw(
                     &*self.globe_gl_buf.vertices,
                     &*self.globe_gl_buf.indices,
                     &*self.tris_gl_prog,
-                    &uniforms,
-                    &draw_params
-                ).unwrap();
-            },
+                    &self.with_projection_uniforms(&map_uniforms
+                        .add("night_blending", true)
+                        .add("night_texture", sampler(night_lights))
+                        .add("sun_direction", sun_direction)
+                        .add("night_sun_altitude_sin", data::NIGHT_SUN_ALTITUDE.0.to_radians().sin() as f32)
+                    ),
+                    &map_draw_params
+                ),
 
-            ViewMode::VectorMap => {
-                target.clear_color(0.87, 0.87, 0.87, 1.0);
+                None => target.draw(
+                    &*self.globe_gl_buf.vertices,
+                    &*self.globe_gl_buf.indices,
+                    &*self.tris_gl_prog,
+                    &self.with_projection_uniforms(&map_uniforms.add("night_blending", false)),
+                    &map_draw_params
+                )
+            }.unwrap();
+        }
+
+        if let Some(choropleth) = &self.choropleth {
+            target.draw(
+                &*self.globe_gl_buf.vertices,
+                &*self.globe_gl_buf.indices,
+                &*self.tris_gl_prog,
+                &self.with_projection_uniforms(&with_texture(&*choropleth.texture)),
+                &glium::DrawParameters{ blend: glium::Blend::alpha_blending(), ..map_draw_params.clone() }
+            ).unwrap();
+        }
 
-                let uniforms = uniforms.clone().add(uniform_names::UNIFORM_COLOR, [0f32, 0f32, 0f32, 1f32]);
+        if let Some(night_shading) = self.night_shading.as_ref().filter(|_| night_lights.is_none()) {
+            target.draw(
+                &*self.globe_gl_buf.vertices,
+                &*self.globe_gl_buf.indices,
+                &*self.tris_gl_prog,
+                &self.with_projection_uniforms(&with_texture(&*night_shading.texture)),
+                &glium::DrawParameters{ blend: glium::Blend::alpha_blending(), ..map_draw_params.clone() }
+            ).unwrap();
+        }
+
+        if layers == Layers::RasterOnly { return; }
+
+        let draw_vector_layer_lines = |target: &mut S, base_map: bool| {
+            for lines in self.visible_vector_layer_lines(base_map) {
+                let uniforms = uniforms.clone().add(uniform_names::UNIFORM_COLOR, lines.style.color);
                 target.draw(
-                    &*self.map_gl_buf.vertices,
-                    &*self.map_gl_buf.indices,
+                    &*lines.gl_buf.vertices,
+                    &*lines.gl_buf.indices,
                     &self.lines_gl_prog,
-                    &uniforms,
-                    &draw_params
+                    &self.with_projection_uniforms(&uniforms),
+                    &glium::DrawParameters{ line_width: Some(lines.style.width), ..map_draw_params.clone() }
                 ).unwrap();
             }
-        }
+        };
 
-        if self.draw_graticule {
-            let uniforms = uniforms.add(uniform_names::UNIFORM_COLOR, [0.6f32, 0.6f32, 0.6f32, 1f32]);
+        draw_vector_layer_lines(target, true);
+
+        if self.is_layer_visible(Layer::Graticule) {
+            let uniforms = uniforms.clone().add(uniform_names::UNIFORM_COLOR, GRATICULE_COLOR);
             target.draw(
                 &*self.graticule_gl_buf.vertices,
                 &*self.graticule_gl_buf.indices,
                 &self.lines_gl_prog,
+                &self.with_projection_uniforms(&uniforms),
+                &map_draw_params
+            ).unwrap();
+        }
+
+        draw_vector_layer_lines(target, false);
+
+        if let Some(script_overlay_gl_buf) = self.visible_script_overlay() {
+            let uniforms = uniforms.clone().add(uniform_names::UNIFORM_COLOR, SCRIPT_OVERLAY_COLOR);
+            target.draw(
+                &*script_overlay_gl_buf.vertices,
+                &*script_overlay_gl_buf.indices,
+                &self.lines_gl_prog,
+                &self.with_projection_uniforms(&uniforms),
+                &map_draw_params
+            ).unwrap();
+        }
+
+        if !self.is_layer_visible(Layer::Outline) { return; }
+
+        let uniforms = uniforms.add(uniform_names::UNIFORM_COLOR, OUTLINE_COLOR);
+        for gl_buf in self.outline_gl_buf.iter().chain(self.boundary_gl_buf.iter()) {
+            target.draw(
+                gl_buf,
+                &glium::index::NoIndices(glium::index::PrimitiveType::LinesList),
+                &self.outline_gl_prog,
                 &uniforms,
-                &draw_params
+                draw_params
             ).unwrap();
         }
+    }
+
+    pub(in crate::views) fn set_outline(&mut self, outline: Vec<[Point2<f64>; 2]>) {
+        self.outline_gl_buf = Some(self.create_segments_gl_buf(&outline));
+        self.outline = outline;
+        self.render();
+    }
+
+    fn update_boundary(&mut self) {
+        self.boundary_gl_buf = self.projection.boundary().map(|boundary| self.create_segments_gl_buf(&boundary));
+    }
+
+    fn create_segments_gl_buf(&self, segments: &[[Point2<f64>; 2]]) -> glium::VertexBuffer<XyVertex> {
+        let vertex_data: Vec<XyVertex> = segments.iter()
+            .flat_map(|segment| segment.iter().map(|p| XyVertex{ position: [p.x as f32, p.y as f32] }))
+            .collect();
+
+        glium::VertexBuffer::new(&self.display, &vertex_data).unwrap()
+    }
+
+    pub fn projection(&self) -> projection::Projection { self.projection }
+
+    pub(in crate::views) fn set_projection(&mut self, projection: projection::Projection) {
+        self.projection = projection;
+        self.update_boundary();
+        self.update_lobes();
+    }
+
+    pub(in crate::views) fn set_projection_uniforms(&mut self, projection_uniforms: Vec<(&'static str, f32)>) {
+        self.projection_uniforms = projection_uniforms;
+        self.render();
+    }
+
+    pub fn projection_params(&self) -> &[ProjectionParam] { &self.projection_params }
+
+    /// Declares adjustable parameters of the view's projection; `projection_from_params` creates
+    /// the CPU-side projection from the parameters' values (given in the same order) for the mapped body.
+    pub(in crate::views) fn set_projection_params(
+        &mut self,
+        projection_params: Vec<ProjectionParam>,
+        projection_from_params: fn(&[f64], &data::Body) -> projection::Projection
+    ) {
+        self.projection_params = projection_params;
+        self.projection_from_params = Some(projection_from_params);
+        self.on_projection_params_changed();
+    }
+
+    /// Sets value of the `idx`-th projection parameter (clamped to the parameter's range).
+    pub fn set_projection_param_value(&mut self, idx: usize, value: f64) {
+        let param = &mut self.projection_params[idx];
+        param.value = value.max(param.min).min(param.max);
+        self.on_projection_params_changed();
+    }
+
+    fn projection_param_index(&self, uniform_name: &str) -> Option<usize> {
+        self.projection_params.iter().position(|p| p.uniform_name == uniform_name)
+    }
+
+    /// Returns the view's grid; `None` if the view does not declare grid parameters (see `Grid::params`).
+    pub fn grid(&self) -> Option<Grid> {
+        let value = |name| self.projection_param_index(name).map(|idx| self.projection_params[idx].value);
+
+        Some(Grid{
+            scale_factor: value(grid_param_names::SCALE_FACTOR)?,
+            false_easting: value(grid_param_names::FALSE_EASTING)?,
+            false_northing: value(grid_param_names::FALSE_NORTHING)?,
+            central_meridian: value(grid_param_names::CENTRAL_MERIDIAN)?,
+            origin_latitude: value(grid_param_names::ORIGIN_LATITUDE)?
+        })
+    }
+
+    /// Sets the view's grid; does nothing if the view does not declare grid parameters.
+    pub fn set_grid(&mut self, grid: Grid) {
+        if self.grid().is_none() { return; }
+
+        for (name, value) in [
+            (grid_param_names::SCALE_FACTOR, grid.scale_factor),
+            (grid_param_names::FALSE_EASTING, grid.false_easting),
+            (grid_param_names::FALSE_NORTHING, grid.false_northing),
+            (grid_param_names::CENTRAL_MERIDIAN, grid.central_meridian),
+            (grid_param_names::ORIGIN_LATITUDE, grid.origin_latitude)
+        ] {
+            let idx = self.projection_param_index(name).unwrap();
+            self.set_projection_param_value(idx, value);
+        }
+    }
+
+    /// Returns grid coordinates (easting, northing in meters) of the point shown at `position` (in normalized
+    /// device coordinates): its map coordinates relative to the grid's origin (see `Grid::map_position`), regardless
+    /// of the view's current rotation. `None` if the view does not declare grid parameters or shows no point there.
+    pub fn grid_coordinates(&self, position: Point2<f64>) -> Option<Point2<f64>> {
+        let grid = self.grid()?;
+        let (longitude, latitude) = self.unproject(position)?;
+
+        grid.project(&self.projection, self.body.semi_major_axis, longitude, latitude)
+    }
+
+    fn on_projection_params_changed(&mut self) {
+        if let Some(projection_from_params) = self.projection_from_params {
+            let values: Vec<f64> = self.projection_params.iter().map(|p| p.value).collect();
+            self.set_projection(projection_from_params(&values, &self.body));
+        }
+        self.render();
+    }
 
-        self.draw_buf.update_storage_buf();
+    fn with_projection_uniforms<'a, U: Uniforms>(&'a self, uniforms: &'a U) -> WithProjectionUniforms<'a, U> {
+        WithProjectionUniforms{
+            uniforms,
+            projection_uniforms: &self.projection_uniforms,
+            projection_params: &self.projection_params,
+            mean_radius: self.body.mean_radius as f32,
+            lobe_uniforms: &self.lobe_uniforms
+        }
     }
 
     pub fn update_size(&mut self, width: u32, height: u32) {
@@ -185,10 +1728,14 @@ impl ViewBase {
         }
     }
 
-    pub (in crate::views) fn unique_id(&self) -> u32 { self.unique_id }
+    pub fn unique_id(&self) -> u32 { self.unique_id }
+
+    /// Changes the view's unique id (e.g. to that of a restored view); it must not be the id of an existing object.
+    pub fn set_unique_id(&mut self, unique_id: u32) { self.unique_id = unique_id; }
 
     pub(in crate::views) fn new(
         orientation: Basis3<f64>,
+        projection: projection::Projection,
         program_data: &ProgramData,
         lines_gl_prog: Rc<glium::Program>,
         tris_gl_prog: Rc<glium::Program>,
@@ -201,36 +1748,92 @@ impl ViewBase {
             DragRotation::NSEW
         };
 
-        ViewBase{
+        let mut view_base = ViewBase{
             unique_id: program_data.new_unique_id(),
             orientation,
-            draw_graticule: true,
+            projection,
+            global_layers: program_data.layer_visibility(),
+            layer_overrides: Default::default(),
+            graticule_labels: program_data.default_graticule_labels,
             wh_ratio: 1.0,
             view_mode: ViewMode::GlobeTexture,
             angle_ns: cgmath::Rad(0.0),
             angle_ew: cgmath::Rad(0.0),
+            roll: cgmath::Rad(0.0),
             drag_rotation,
+            stereo_mode: StereoMode::Off,
             zoom: 1.0,
             draw_buf: DrawBuffer::new(
-                Sampling::Multi,
+                program_data.sampling,
                 &program_data.gl_programs.texture_copy_single,
                 &program_data.gl_programs.texture_copy_multi,
                 &program_data.unit_quad,
                 display,
                 &renderer
             ),
+            magnifier_buf: None,
+            frame_buf: None,
             globe_gl_buf: program_data.globe_gl_buf.clone(),
             graticule_gl_buf: program_data.graticule_gl_buf.clone(),
-            map_gl_buf: program_data.map_gl_buf.clone(),
+            graticule_step: program_data.graticule_step,
+            graticule_substep: data::DEFAULT_GRATICULE_SUBSTEP,
+            projection_uniforms: vec![],
+            projection_params: vec![],
+            projection_from_params: None,
+            script_overlay_gl_buf: None,
+            vector_layer_lines: program_data.vector_layer_lines(),
+            stretch: None,
+            lobes: None,
+            truncation: None,
+            lobe_uniforms: vec![],
+            markers: vec![],
+            user_markers: Rc::clone(program_data.user_markers()),
+            point_layers: Rc::clone(program_data.point_layers()),
+            places: Rc::clone(&program_data.places),
+            time_zone_labels: Rc::clone(&program_data.time_zone_labels),
+            body: *program_data.body(),
+            outline: vec![],
+            outline_gl_buf: None,
+            boundary_gl_buf: None,
+            outline_gl_prog: Rc::clone(&program_data.gl_programs.outline),
+            base_map: 0,
             globe_texture: program_data.globe_texture.clone(),
+            land_texture: program_data.land_texture(),
+            night_shading: program_data.night_shading(),
+            overlay: program_data.overlay().cloned(),
+            hillshading: program_data.hillshading().cloned(),
+            web_tiles: program_data.web_tiles().cloned(),
+            image_overlay: program_data.image_overlay().cloned(),
+            scalar_field: program_data.scalar_field().cloned(),
+            vector_field: program_data.vector_field().cloned(),
+            choropleth: program_data.choropleth().cloned(),
             lines_gl_prog,
-            tris_gl_prog
-        }
+            tris_gl_prog,
+            display: display.clone(),
+            rendered: Cell::new(false)
+        };
+        view_base.update_boundary();
+
+        view_base
+    }
+
+    /// Rolls the map by the change of the mouse position's angle around the view's center; `start` and `end`
+    /// as in `rotate_by_dragging`.
+    pub fn roll_by_dragging(&mut self, start: [f32; 2], end: [f32; 2]) {
+        let angle = |p: [f32; 2]| (p[1] as f64).atan2(p[0] as f64 * self.wh_ratio as f64);
+        self.set_roll(self.roll + cgmath::Rad(angle(end) - angle(start)));
     }
 
     /// Elements of `start` and `end` denote normalized mouse position within the view,
     /// with values from [-1, 1] (i.e., bottom-left is [-1, -1], and top-right is [1, 1]).
     pub fn rotate_by_dragging(&mut self, start: [f32; 2], end: [f32; 2]) {
+        // the globe is rotated in the map's (not rolled) frame
+        let unroll = |p: [f32; 2]| {
+            let p = self.rotate_ndc(Point2{ x: p[0] as f64, y: p[1] as f64 }, -self.roll, self.wh_ratio);
+            [p.x as f32, p.y as f32]
+        };
+        let (start, end) = (unroll(start), unroll(end));
+
         match self.drag_rotation {
             // simulates "space ball" rotation
             DragRotation::Free => {
@@ -255,13 +1858,26 @@ impl ViewBase {
 
                 self.angle_ew += cgmath::Rad(1.0 / self.zoom * (end[0] - start[0]) as f64);
 
-                let rotation_ns = cgmath::Basis3::from_angle_y(self.angle_ns);
-                let rotation_ew = cgmath::Basis3::from_angle_z(self.angle_ew);
-
-                self.orientation = rotation_ns * rotation_ew;
+                self.orientation = self.nsew_orientation();
             }
         }
 
         self.render();
     }
 }
+
+fn format_longitude(longitude: f64) -> String {
+    if longitude == 0.0 || longitude.abs() == 180.0 {
+        format!("{}°", longitude.abs())
+    } else {
+        format!("{}°{}", longitude.abs(), if longitude > 0.0 { "E" } else { "W" })
+    }
+}
+
+fn format_latitude(latitude: f64) -> String {
+    if latitude == 0.0 {
+        "0°".to_string()
+    } else {
+        format!("{}°{}", latitude.abs(), if latitude > 0.0 { "N" } else { "S" })
+    }
+}
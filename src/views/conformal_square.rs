@@ -0,0 +1,114 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::{Deg, One};
+use std::rc::Rc;
+use crate::data;
+use crate::projection::{self, Projection};
+use crate::views::{base::ViewBase};
+use std::cell::RefCell;
+
+const PEIRCE_QUINCUNCIAL_INITIAL_ZOOM: f64 = 0.35;
+
+/// Conformal projections onto a square, based on elliptic integrals.
+#[derive(Copy, Clone, PartialEq)]
+pub enum ConformalSquareKind {
+    /// Adams hemisphere-in-a-square; shows one hemisphere.
+    AdamsHemisphere,
+    /// Guyou; shows the world as two hemisphere squares side by side.
+    Guyou,
+    /// Peirce quincuncial; the northern hemisphere is a square in the middle of the map, the southern one is split
+    /// into four triangles at the corners.
+    PeirceQuincuncial
+}
+
+impl ConformalSquareKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConformalSquareKind::AdamsHemisphere => "Adams hemisphere-in-a-square",
+            ConformalSquareKind::Guyou => "Guyou",
+            ConformalSquareKind::PeirceQuincuncial => "Peirce quincuncial"
+        }
+    }
+
+    pub fn projection(&self) -> Projection {
+        match self {
+            ConformalSquareKind::AdamsHemisphere => Projection::AdamsHemisphere,
+            ConformalSquareKind::Guyou => Projection::Guyou,
+            ConformalSquareKind::PeirceQuincuncial => Projection::PeirceQuincuncial
+        }
+    }
+}
+
+pub struct ConformalSquareView {
+    base: ViewBase,
+    kind: ConformalSquareKind,
+    /// If true, the map is repeated around the central square (only for `ConformalSquareKind::PeirceQuincuncial`).
+    tiled: bool
+}
+
+impl ConformalSquareView {
+    pub fn new(
+        kind: ConformalSquareKind,
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> ConformalSquareView {
+        let gl_programs = match kind {
+            ConformalSquareKind::AdamsHemisphere => &program_data.gl_programs.adams_hemisphere,
+            ConformalSquareKind::Guyou => &program_data.gl_programs.guyou,
+            ConformalSquareKind::PeirceQuincuncial => &program_data.gl_programs.peirce_quincuncial
+        };
+
+        let mut view = ConformalSquareView{
+            base: ViewBase::new(
+                ConformalSquareView::initial_orientation(),
+                kind.projection(),
+                program_data,
+                Rc::clone(&gl_programs.lines),
+                Rc::clone(&gl_programs.triangles),
+                display,
+                renderer
+            ),
+            kind,
+            tiled: false
+        };
+        if kind == ConformalSquareKind::PeirceQuincuncial {
+            // the northern hemisphere in the middle
+            view.base.center_on(Deg(0.0), Deg(90.0));
+            view.base.zoom_by(PEIRCE_QUINCUNCIAL_INITIAL_ZOOM);
+            view.set_tiled(false);
+        }
+
+        view
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn kind(&self) -> ConformalSquareKind { self.kind }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    pub fn tiled(&self) -> bool { self.tiled }
+
+    /// Enables repeating the map in a 3×3 grid of tiles; every other tile is rotated by 180°, so that
+    /// the map continues seamlessly across the tiles' sides. Only for `ConformalSquareKind::PeirceQuincuncial`.
+    pub fn set_tiled(&mut self, tiled: bool) {
+        if self.kind != ConformalSquareKind::PeirceQuincuncial { return; }
+
+        self.tiled = tiled;
+        let tile_size = if tiled { projection::PEIRCE_QUINCUNCIAL_SIDE as f32 } else { 0.0 };
+        self.base.set_projection_uniforms(vec![("tile_size", tile_size)]);
+        self.base.render();
+    }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
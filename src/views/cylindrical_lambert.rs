@@ -9,9 +9,50 @@
 use cgmath::One;
 use std::rc::Rc;
 use crate::data;
-use crate::views::{base::ViewBase};
+use crate::projection::Projection;
+use crate::views::{base::ViewBase, ParamUnit, ProjectionParam};
 use std::cell::RefCell;
 
+mod uniform_names {
+    pub const STANDARD_PARALLEL: &str = "standard_parallel";
+}
+
+/// Max. standard parallel; the map's height grows without bound as the standard parallel approaches a pole.
+const MAX_STANDARD_PARALLEL: cgmath::Deg<f64> = cgmath::Deg(85.0);
+
+/// Named variants of the cylindrical equal-area projection, differing in the standard parallel.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CylindricalEqualAreaPreset {
+    Lambert,
+    Behrmann,
+    GallPeters
+}
+
+impl CylindricalEqualAreaPreset {
+    pub const ALL: [CylindricalEqualAreaPreset; 3] = [
+        CylindricalEqualAreaPreset::Lambert,
+        CylindricalEqualAreaPreset::Behrmann,
+        CylindricalEqualAreaPreset::GallPeters
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CylindricalEqualAreaPreset::Lambert => "Lambert",
+            CylindricalEqualAreaPreset::Behrmann => "Behrmann",
+            CylindricalEqualAreaPreset::GallPeters => "Gall-Peters"
+        }
+    }
+
+    pub fn standard_parallel(&self) -> cgmath::Deg<f64> {
+        match self {
+            CylindricalEqualAreaPreset::Lambert => cgmath::Deg(0.0),
+            CylindricalEqualAreaPreset::Behrmann => cgmath::Deg(30.0),
+            CylindricalEqualAreaPreset::GallPeters => cgmath::Deg(45.0)
+        }
+    }
+}
+
+/// Cylindrical equal-area projection with an adjustable standard parallel (0° for the Lambert variant).
 pub struct CylindricalLambertView {
     base: ViewBase,
 }
@@ -22,22 +63,60 @@ impl CylindricalLambertView {
         renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
         display: &glium::Display
     ) -> CylindricalLambertView {
-        CylindricalLambertView{
+        let mut view = CylindricalLambertView{
             base: ViewBase::new(
                 CylindricalLambertView::initial_orientation(),
+                Projection::CylindricalLambert{ standard_parallel: cgmath::Rad(0.0) },
                 program_data,
                 Rc::clone(&program_data.gl_programs.cylindrical_lambert.lines),
                 Rc::clone(&program_data.gl_programs.cylindrical_lambert.triangles),
                 display,
                 renderer
             ),
-        }
+        };
+        view.base.set_projection_params(
+            vec![ProjectionParam{
+                uniform_name: uniform_names::STANDARD_PARALLEL,
+                label: "standard parallel",
+                tooltip: "Parallel (and its opposite) along which the scale is true",
+                unit: ParamUnit::Degrees,
+                min: 0.0,
+                max: MAX_STANDARD_PARALLEL.0,
+                speed: 0.2,
+                value: 0.0
+            }],
+            |values, _| Projection::CylindricalLambert{ standard_parallel: cgmath::Deg(values[0]).into() }
+        );
+        view.base.enable_stretch();
+        view.base.enable_interruption();
+
+        view
     }
 
     pub fn unique_id(&self) -> u32 { self.base.unique_id() }
 
     pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
 
+    pub fn standard_parallel(&self) -> cgmath::Deg<f64> {
+        // the only parameter
+        cgmath::Deg(self.base.projection_params()[0].value)
+    }
+
+    pub fn set_standard_parallel(&mut self, standard_parallel: cgmath::Deg<f64>) {
+        self.base.set_projection_param_value(0, standard_parallel.0.abs());
+    }
+
+    /// Returns the preset matching the current standard parallel (if any).
+    pub fn preset(&self) -> Option<CylindricalEqualAreaPreset> {
+        let standard_parallel = self.standard_parallel();
+        CylindricalEqualAreaPreset::ALL.iter().copied()
+            .find(|preset| (preset.standard_parallel().0 - standard_parallel.0).abs() < 1.0e-6)
+    }
+
+    pub fn set_preset(&mut self, preset: CylindricalEqualAreaPreset) {
+        self.set_standard_parallel(preset.standard_parallel());
+    }
+
     /// Returns identity matrix: observer facing long. 0°, lat. °.
     fn initial_orientation() -> cgmath::Basis3<f64> {
         cgmath::Basis3::one()
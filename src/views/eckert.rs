@@ -0,0 +1,91 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::One;
+use std::rc::Rc;
+use crate::data;
+use crate::projection::Projection;
+use crate::views::base::ViewBase;
+use std::cell::RefCell;
+
+/// Initial zoom; shows the whole map.
+const INITIAL_ZOOM: f64 = 0.35;
+
+/// Eckert's pseudocylindrical equal-area projections with the poles shown as lines half as long as the equator.
+#[derive(Copy, Clone, PartialEq)]
+pub enum EckertKind {
+    /// Parallels spaced as on Mollweide's ellipse; meridians are semicircles.
+    IV,
+    /// Meridians are sinusoids.
+    VI
+}
+
+impl EckertKind {
+    pub const ALL: [EckertKind; 2] = [EckertKind::IV, EckertKind::VI];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EckertKind::IV => "Eckert IV",
+            EckertKind::VI => "Eckert VI"
+        }
+    }
+
+    pub fn projection(&self) -> Projection {
+        match self {
+            EckertKind::IV => Projection::EckertIV,
+            EckertKind::VI => Projection::EckertVI
+        }
+    }
+}
+
+pub struct EckertView {
+    base: ViewBase,
+    kind: EckertKind
+}
+
+impl EckertView {
+    pub fn new(
+        kind: EckertKind,
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> EckertView {
+        let gl_programs = match kind {
+            EckertKind::IV => &program_data.gl_programs.eckert_iv,
+            EckertKind::VI => &program_data.gl_programs.eckert_vi
+        };
+
+        let mut view = EckertView{
+            base: ViewBase::new(
+                EckertView::initial_orientation(),
+                kind.projection(),
+                program_data,
+                Rc::clone(&gl_programs.lines),
+                Rc::clone(&gl_programs.triangles),
+                display,
+                renderer
+            ),
+            kind
+        };
+        view.base.enable_interruption();
+        view.base.zoom_by(INITIAL_ZOOM);
+
+        view
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    pub fn kind(&self) -> EckertKind { self.kind }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
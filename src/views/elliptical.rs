@@ -0,0 +1,161 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::{One, Rotation3};
+use std::rc::Rc;
+use crate::data;
+use crate::projection::Projection;
+use crate::views::{base::ViewBase, ParamUnit, ProjectionParam};
+use std::cell::RefCell;
+
+mod uniform_names {
+    pub const AXIS_RATIO: &str = "axis_ratio";
+}
+
+/// Ratio of the Hammer map's axes.
+const HAMMER_AXIS_RATIO: f64 = 2.0;
+
+/// Ratio of the Briesemeister map's axes.
+const BRIESEMEISTER_AXIS_RATIO: f64 = 1.75;
+
+/// Center of the Briesemeister map (longitude, latitude).
+const BRIESEMEISTER_CENTER: (cgmath::Deg<f64>, cgmath::Deg<f64>) = (cgmath::Deg(10.0), cgmath::Deg(45.0));
+
+/// Center of Bartholomew's Atlantis map (longitude, latitude).
+const ATLANTIS_CENTER: (cgmath::Deg<f64>, cgmath::Deg<f64>) = (cgmath::Deg(-30.0), cgmath::Deg(45.0));
+
+/// Initial zoom; shows the whole map.
+const INITIAL_ZOOM: f64 = 0.33;
+
+/// Equal-area projections of the world onto an ellipse, and their oblique aspects. The aspect is applied
+/// by rotating the globe before projecting it, so parallels and meridians of an oblique aspect are curved.
+#[derive(Copy, Clone, PartialEq)]
+pub enum EllipticalKind {
+    /// Pseudocylindrical; parallels are straight lines.
+    Mollweide,
+    /// Modified azimuthal equal-area; parallels are curved.
+    Hammer,
+    /// Oblique Hammer centered on 10°E, 45°N, with axes' ratio 1.75; shows the continents with little distortion.
+    Briesemeister,
+    /// Bartholomew's Atlantis: transverse Mollweide centered on 30°W, 45°N; shows the Atlantic Ocean as a whole.
+    Atlantis
+}
+
+impl EllipticalKind {
+    pub const ALL: [EllipticalKind; 4] = [
+        EllipticalKind::Mollweide,
+        EllipticalKind::Hammer,
+        EllipticalKind::Briesemeister,
+        EllipticalKind::Atlantis
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EllipticalKind::Mollweide => "Mollweide",
+            EllipticalKind::Hammer => "Hammer",
+            EllipticalKind::Briesemeister => "Briesemeister",
+            EllipticalKind::Atlantis => "Atlantis (oblique Mollweide)"
+        }
+    }
+
+    pub fn projection(&self) -> Projection {
+        if self.is_hammer() { Projection::Hammer{ axis_ratio: self.axis_ratio() } } else { Projection::Mollweide }
+    }
+
+    fn axis_ratio(&self) -> f64 {
+        match self {
+            EllipticalKind::Briesemeister => BRIESEMEISTER_AXIS_RATIO,
+            _ => HAMMER_AXIS_RATIO
+        }
+    }
+
+    fn is_hammer(&self) -> bool {
+        match self {
+            EllipticalKind::Hammer | EllipticalKind::Briesemeister => true,
+            EllipticalKind::Mollweide | EllipticalKind::Atlantis => false
+        }
+    }
+}
+
+pub struct EllipticalView {
+    base: ViewBase,
+    kind: EllipticalKind
+}
+
+impl EllipticalView {
+    pub fn new(
+        kind: EllipticalKind,
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> EllipticalView {
+        let gl_programs = if kind.is_hammer() {
+            &program_data.gl_programs.hammer
+        } else {
+            &program_data.gl_programs.mollweide
+        };
+        let axis_ratio = kind.axis_ratio();
+
+        let mut view = EllipticalView{
+            base: ViewBase::new(
+                EllipticalView::initial_orientation(),
+                kind.projection(),
+                program_data,
+                Rc::clone(&gl_programs.lines),
+                Rc::clone(&gl_programs.triangles),
+                display,
+                renderer
+            ),
+            kind
+        };
+
+        if kind.is_hammer() {
+            view.base.set_projection_params(
+                vec![ProjectionParam{
+                    uniform_name: uniform_names::AXIS_RATIO,
+                    label: "axis ratio",
+                    tooltip: "Ratio of the bounding ellipse's axes (2 for Hammer, 1.75 for Briesemeister); \
+                        the map remains equal-area",
+                    unit: ParamUnit::Plain,
+                    min: 1.0,
+                    max: 3.0,
+                    speed: 0.005,
+                    value: axis_ratio
+                }],
+                |values, _| Projection::Hammer{ axis_ratio: values[0] }
+            );
+        } else {
+            view.base.enable_interruption();
+        }
+
+        match kind {
+            EllipticalKind::Briesemeister => view.base.center_on(BRIESEMEISTER_CENTER.0, BRIESEMEISTER_CENTER.1),
+            EllipticalKind::Atlantis => {
+                view.base.center_on(ATLANTIS_CENTER.0, ATLANTIS_CENTER.1);
+                // transverse aspect: the central meridian becomes horizontal
+                let orientation = cgmath::Basis3::from_angle_x(cgmath::Deg(90.0)) * *view.base.orientation();
+                view.base.set_orientation(orientation);
+            },
+            EllipticalKind::Mollweide | EllipticalKind::Hammer => ()
+        }
+        view.base.zoom_by(INITIAL_ZOOM);
+
+        view
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    pub fn kind(&self) -> EllipticalKind { self.kind }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
@@ -0,0 +1,168 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Creation of views by projection name, as used in session files, batch jobs and on the command line.
+//
+
+use crate::data;
+use crate::views::{
+    AlbersEqualAreaView,
+    ArmadilloView,
+    ConformalSquareKind,
+    ConformalSquareView,
+    CylindricalLambertView,
+    EckertKind,
+    EckertView,
+    EllipticalKind,
+    EllipticalView,
+    GeneralPerspectiveView,
+    GnomonicView,
+    LambertAzimuthalView,
+    LambertConformalConicView,
+    LittrowView,
+    LoximuthalView,
+    MercatorKind,
+    MercatorView,
+    NicolosiView,
+    OrthographicView,
+    PluginView,
+    QscView,
+    StereographicView,
+    TransverseMercatorView
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Names of the built-in projections (plugin projections are created as "plugin" with the plugin's name).
+pub const PROJECTION_NAMES: [&str; 26] = [
+    "albers_equal_area", "adams_hemisphere", "armadillo", "atlantis", "briesemeister", "cylindrical_lambert",
+    "eckert_iv", "eckert_vi", "gall_stereographic", "general_perspective", "gnomonic", "guyou", "hammer",
+    "lambert_azimuthal", "lambert_conformal_conic", "littrow", "loximuthal", "mercator", "miller", "mollweide",
+    "nicolosi", "orthographic", "peirce_quincuncial", "qsc", "stereographic", "transverse_mercator"
+];
+
+/// Creates a view with default settings and adds it to `program_data`; returns its unique id. `projection` is one
+/// of `PROJECTION_NAMES`, or "plugin" with the name of a loaded plugin projection in `plugin`.
+pub fn create_view(
+    projection: &str,
+    plugin: Option<&str>,
+    program_data: &mut data::ProgramData,
+    renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+    display: &glium::Display
+) -> Result<u32, String> {
+    macro_rules! add_view {
+        ($view:expr, $add_fn:ident) => {{
+            let view = $view;
+            let unique_id = view.unique_id();
+            program_data.$add_fn(view);
+            unique_id
+        }}
+    }
+
+    let unique_id = match projection {
+        "albers_equal_area" => add_view!(
+            AlbersEqualAreaView::new(program_data, renderer, display), add_albers_equal_area_view
+        ),
+        "adams_hemisphere" => add_view!(
+            ConformalSquareView::new(ConformalSquareKind::AdamsHemisphere, program_data, renderer, display),
+            add_conformal_square_view
+        ),
+        "armadillo" => add_view!(
+            ArmadilloView::new(program_data, renderer, display), add_armadillo_view
+        ),
+        "atlantis" => add_view!(
+            EllipticalView::new(EllipticalKind::Atlantis, program_data, renderer, display), add_elliptical_view
+        ),
+        "briesemeister" => add_view!(
+            EllipticalView::new(EllipticalKind::Briesemeister, program_data, renderer, display), add_elliptical_view
+        ),
+        "cylindrical_lambert" => add_view!(
+            CylindricalLambertView::new(program_data, renderer, display), add_cylindrical_lambert_view
+        ),
+        "eckert_iv" => add_view!(
+            EckertView::new(EckertKind::IV, program_data, renderer, display), add_eckert_view
+        ),
+        "eckert_vi" => add_view!(
+            EckertView::new(EckertKind::VI, program_data, renderer, display), add_eckert_view
+        ),
+        "gall_stereographic" => add_view!(
+            MercatorView::new(MercatorKind::GallStereographic, program_data, renderer, display), add_mercator_view
+        ),
+        "general_perspective" => add_view!(
+            GeneralPerspectiveView::new(program_data, renderer, display), add_general_perspective_view
+        ),
+        "gnomonic" => add_view!(
+            GnomonicView::new(program_data, renderer, display), add_gnomonic_view
+        ),
+        "guyou" => add_view!(
+            ConformalSquareView::new(ConformalSquareKind::Guyou, program_data, renderer, display),
+            add_conformal_square_view
+        ),
+        "hammer" => add_view!(
+            EllipticalView::new(EllipticalKind::Hammer, program_data, renderer, display), add_elliptical_view
+        ),
+        "lambert_azimuthal" => add_view!(
+            LambertAzimuthalView::new(program_data, renderer, display), add_lambert_azimuthal_view
+        ),
+        "lambert_conformal_conic" => add_view!(
+            LambertConformalConicView::new(program_data, renderer, display), add_lambert_conformal_conic_view
+        ),
+        "littrow" => add_view!(
+            LittrowView::new(program_data, renderer, display), add_littrow_view
+        ),
+        "loximuthal" => add_view!(
+            LoximuthalView::new(program_data, renderer, display), add_loximuthal_view
+        ),
+        "mercator" => add_view!(
+            MercatorView::new(MercatorKind::Mercator, program_data, renderer, display), add_mercator_view
+        ),
+        "miller" => add_view!(
+            MercatorView::new(MercatorKind::Miller, program_data, renderer, display), add_mercator_view
+        ),
+        "mollweide" => add_view!(
+            EllipticalView::new(EllipticalKind::Mollweide, program_data, renderer, display), add_elliptical_view
+        ),
+        "nicolosi" => add_view!(
+            NicolosiView::new(program_data, renderer, display), add_nicolosi_view
+        ),
+        "orthographic" => add_view!(
+            OrthographicView::new(program_data, renderer, display), add_orthographic_view
+        ),
+        "peirce_quincuncial" => add_view!(
+            ConformalSquareView::new(ConformalSquareKind::PeirceQuincuncial, program_data, renderer, display),
+            add_conformal_square_view
+        ),
+        "plugin" => {
+            let name = plugin.ok_or("missing name of the plugin projection")?;
+            let plugin_data = program_data.plugins.iter()
+                .find(|p| p.plugin.name == name && p.gl_programs.is_some())
+                .ok_or_else(|| format!("unknown projection: {}", name))?;
+            add_view!(
+                PluginView::new(
+                    plugin_data.plugin, plugin_data.gl_programs.as_ref().unwrap(), program_data, renderer, display
+                ),
+                add_plugin_view
+            )
+        },
+        "qsc" => add_view!(
+            QscView::new(program_data, renderer, display), add_qsc_view
+        ),
+        "stereographic" => add_view!(
+            StereographicView::new(program_data, renderer, display), add_stereographic_view
+        ),
+        "transverse_mercator" => add_view!(
+            TransverseMercatorView::new(program_data, renderer, display), add_transverse_mercator_view
+        ),
+        projection => return Err(format!(
+            "unknown projection: {} (expected one of: {})", projection, PROJECTION_NAMES.join(", ")
+        ))
+    };
+
+    Ok(unique_id)
+}
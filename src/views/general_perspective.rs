@@ -0,0 +1,98 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::One;
+use std::rc::Rc;
+use crate::data;
+use crate::projection::Projection;
+use crate::views::{base::ViewBase, ParamUnit, ProjectionParam};
+use std::cell::RefCell;
+
+/// Altitude of geostationary satellites (e.g. GOES) in km.
+pub const GEOSTATIONARY_ALTITUDE: f64 = 35786.0;
+
+mod uniform_names {
+    pub const ALTITUDE: &str = "altitude";
+    pub const TILT: &str = "tilt";
+}
+
+mod param_indices {
+    pub const ALTITUDE: usize = 0;
+}
+
+/// Vertical perspective as seen by an observer (e.g. a satellite) at finite altitude, optionally tilted.
+pub struct GeneralPerspectiveView {
+    base: ViewBase
+}
+
+impl GeneralPerspectiveView {
+    pub fn new(
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> GeneralPerspectiveView {
+        let mut view = GeneralPerspectiveView{
+            base: ViewBase::new(
+                GeneralPerspectiveView::initial_orientation(),
+                Projection::GeneralPerspective{
+                    distance: 1.0 + GEOSTATIONARY_ALTITUDE / program_data.body().mean_radius,
+                    tilt: cgmath::Rad(0.0)
+                },
+                program_data,
+                Rc::clone(&program_data.gl_programs.general_perspective.lines),
+                Rc::clone(&program_data.gl_programs.general_perspective.triangles),
+                display,
+                renderer
+            )
+        };
+        view.base.set_projection_params(
+            vec![
+                ProjectionParam{
+                    uniform_name: uniform_names::ALTITUDE,
+                    label: "altitude",
+                    tooltip: "Observer's altitude above the surface",
+                    unit: ParamUnit::Kilometers,
+                    min: 100.0,
+                    max: 1_000_000.0,
+                    speed: 10.0,
+                    value: GEOSTATIONARY_ALTITUDE
+                },
+                ProjectionParam{
+                    uniform_name: uniform_names::TILT,
+                    label: "tilt",
+                    tooltip: "Tilt of the line of sight towards the top of the view",
+                    unit: ParamUnit::Degrees,
+                    min: 0.0,
+                    max: 89.0,
+                    speed: 0.2,
+                    value: 0.0
+                }
+            ],
+            |values, body| Projection::GeneralPerspective{
+                distance: 1.0 + values[0] / body.mean_radius,
+                tilt: cgmath::Deg(values[1]).into()
+            }
+        );
+
+        view
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    /// Sets the observer's altitude above the surface (km).
+    pub fn set_altitude(&mut self, altitude: f64) {
+        self.base.set_projection_param_value(param_indices::ALTITUDE, altitude);
+    }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
@@ -9,9 +9,20 @@
 use cgmath::One;
 use std::rc::Rc;
 use crate::data;
-use crate::views::{base::ViewBase};
+use crate::projection::Projection;
+use crate::views::{base::ViewBase, ParamUnit, ProjectionParam};
 use std::cell::RefCell;
 
+mod uniform_names {
+    pub const MAX_ANGLE: &str = "max_angle";
+}
+
+const DEFAULT_FIELD_OF_VIEW: cgmath::Deg<f64> = cgmath::Deg(80.0);
+
+/// Range of the field of view (its angular radius); at 90° the projection extends to infinity.
+const MIN_FIELD_OF_VIEW: cgmath::Deg<f64> = cgmath::Deg(60.0);
+const MAX_FIELD_OF_VIEW: cgmath::Deg<f64> = cgmath::Deg(85.0);
+
 pub struct GnomonicView {
     base: ViewBase,
 }
@@ -22,16 +33,32 @@ impl GnomonicView {
         renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
         display: &glium::Display
     ) -> GnomonicView {
-        GnomonicView{
+        let mut view = GnomonicView{
             base: ViewBase::new(
                 GnomonicView::initial_orientation(),
+                Projection::Gnomonic{ max_angle: DEFAULT_FIELD_OF_VIEW.into() },
                 program_data,
                 Rc::clone(&program_data.gl_programs.gnomonic.lines),
                 Rc::clone(&program_data.gl_programs.gnomonic.triangles),
                 display,
                 renderer
             ),
-        }
+        };
+        view.base.set_projection_params(
+            vec![ProjectionParam{
+                uniform_name: uniform_names::MAX_ANGLE,
+                label: "field of view",
+                tooltip: "Max. angular distance of the shown points from the center of the view",
+                unit: ParamUnit::Degrees,
+                min: MIN_FIELD_OF_VIEW.0,
+                max: MAX_FIELD_OF_VIEW.0,
+                speed: 0.1,
+                value: DEFAULT_FIELD_OF_VIEW.0
+            }],
+            |values, _| Projection::Gnomonic{ max_angle: cgmath::Deg(values[0]).into() }
+        );
+
+        view
     }
 
     pub fn unique_id(&self) -> u32 { self.base.unique_id() }
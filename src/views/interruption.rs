@@ -0,0 +1,211 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Interrupted maps of pseudocylindrical projections.
+//
+// An interrupted map consists of lobes; each one covers a range of longitudes of one or both hemispheres
+// and is projected around its own central meridian. Lobes of one hemisphere are joined along the equator.
+//
+// A map can also be truncated at a latitude (e.g. Mercator, which is infinite at the poles); the geometry shaders
+// clip it like a single lobe covering all longitudes.
+//
+
+use cgmath::{Deg, Point2, Rad};
+use crate::projection::{self, Projection};
+
+/// Max. number of lobes; has to equal `MAX_LOBES` in "tris.geom" and "lines.geom".
+pub const MAX_LOBES: usize = 8;
+
+/// Number of segments of a lobe's boundary meridian in the map's outline.
+const BOUNDARY_NUM_SEGMENTS: usize = 90;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Hemisphere { Both, North, South }
+
+impl Hemisphere {
+    pub const ALL: [Hemisphere; 3] = [Hemisphere::Both, Hemisphere::North, Hemisphere::South];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hemisphere::Both => "both",
+            Hemisphere::North => "northern",
+            Hemisphere::South => "southern"
+        }
+    }
+
+    /// Returns the range of latitudes (south, north), limited to `truncation` (if specified).
+    fn latitudes(&self, truncation: Option<Deg<f64>>) -> (Deg<f64>, Deg<f64>) {
+        let max_latitude = truncation.unwrap_or(Deg(90.0));
+        match self {
+            Hemisphere::Both => (-max_latitude, max_latitude),
+            Hemisphere::North => (Deg(0.0), max_latitude),
+            Hemisphere::South => (-max_latitude, Deg(0.0))
+        }
+    }
+}
+
+/// Part of an interrupted map.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Lobe {
+    /// Meridian around which the lobe is projected.
+    pub central_meridian: Deg<f64>,
+    /// Western boundary (-180° to 180°).
+    pub west: Deg<f64>,
+    /// Eastern boundary (-180° to 180°).
+    pub east: Deg<f64>,
+    pub hemisphere: Hemisphere
+}
+
+impl Lobe {
+    fn contains(&self, longitude: Deg<f64>, latitude: Deg<f64>) -> bool {
+        let (south, north) = self.hemisphere.latitudes(None);
+        longitude >= self.west && longitude <= self.east && latitude >= south && latitude <= north
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InterruptionPreset {
+    /// Goode's interruption of the homolosine projection (lobes centered on continents).
+    GoodeLand,
+    /// Eastern and western hemisphere.
+    Hemispheres
+}
+
+impl InterruptionPreset {
+    pub const ALL: [InterruptionPreset; 2] = [InterruptionPreset::GoodeLand, InterruptionPreset::Hemispheres];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InterruptionPreset::GoodeLand => "Goode (land)",
+            InterruptionPreset::Hemispheres => "two hemispheres"
+        }
+    }
+
+    pub fn lobes(&self) -> Vec<Lobe> {
+        let lobe = |central_meridian: f64, west: f64, east: f64, hemisphere: Hemisphere| Lobe{
+            central_meridian: Deg(central_meridian), west: Deg(west), east: Deg(east), hemisphere
+        };
+
+        match self {
+            InterruptionPreset::GoodeLand => vec![
+                lobe(-100.0, -180.0, -40.0, Hemisphere::North),
+                lobe(30.0, -40.0, 180.0, Hemisphere::North),
+                lobe(-160.0, -180.0, -100.0, Hemisphere::South),
+                lobe(-60.0, -100.0, -20.0, Hemisphere::South),
+                lobe(20.0, -20.0, 80.0, Hemisphere::South),
+                lobe(140.0, 80.0, 180.0, Hemisphere::South)
+            ],
+
+            InterruptionPreset::Hemispheres => vec![
+                lobe(-90.0, -180.0, 0.0, Hemisphere::Both),
+                lobe(90.0, 0.0, 180.0, Hemisphere::Both)
+            ]
+        }
+    }
+}
+
+/// Checks if `lobes` can be used to interrupt a map.
+pub fn validate(lobes: &[Lobe]) -> Result<(), String> {
+    if lobes.len() > MAX_LOBES {
+        return Err(format!("too many lobes ({}; max. {})", lobes.len(), MAX_LOBES));
+    }
+
+    for (i, lobe) in lobes.iter().enumerate() {
+        if lobe.west.0 < -180.0 || lobe.east.0 > 180.0 || lobe.west >= lobe.east {
+            return Err(format!("lobe {}: invalid longitude range", i + 1));
+        }
+        if lobe.central_meridian < lobe.west || lobe.central_meridian > lobe.east {
+            return Err(format!("lobe {}: central meridian outside the lobe", i + 1));
+        }
+        if (lobe.central_meridian - lobe.west).0 > 180.0 || (lobe.east - lobe.central_meridian).0 > 180.0 {
+            return Err(format!("lobe {}: boundary farther than 180° from the central meridian", i + 1));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the lobe containing the specified point (the first one if lobes overlap).
+pub fn find_lobe(lobes: &[Lobe], longitude: Rad<f64>, latitude: Rad<f64>) -> Option<&Lobe> {
+    let (longitude, latitude) = (Deg::from(longitude), Deg::from(latitude));
+    lobes.iter().find(|lobe| lobe.contains(longitude, latitude))
+}
+
+/// Returns the x coordinate (in map coordinates) of the point where the lobe's central meridian crosses
+/// the equator.
+pub fn central_meridian_x(projection: &Projection, lobe: &Lobe) -> f64 {
+    projection.project(projection::lonlat_to_xyz(lobe.central_meridian.into(), Rad(0.0)))
+        .map_or(0.0, |p| p.x)
+}
+
+/// Projects (`longitude`, `latitude`) (already rotated by the view's orientation) onto the interrupted map.
+pub fn project(
+    projection: &Projection,
+    lobes: &[Lobe],
+    longitude: Rad<f64>,
+    latitude: Rad<f64>
+) -> Option<Point2<f64>> {
+    let lobe = find_lobe(lobes, longitude, latitude)?;
+    let central_meridian: Rad<f64> = lobe.central_meridian.into();
+
+    projection.project(projection::lonlat_to_xyz(longitude - central_meridian, latitude))
+        .map(|p| Point2{ x: p.x + central_meridian_x(projection, lobe), y: p.y })
+}
+
+/// Returns the lobes to which the map is clipped: `lobes`, or a single lobe covering the whole map if the map
+/// is only truncated.
+fn clipped_lobes(lobes: &[Lobe], truncation: Option<Deg<f64>>) -> Vec<Lobe> {
+    if lobes.is_empty() && truncation.is_some() {
+        vec![Lobe{ central_meridian: Deg(0.0), west: Deg(-180.0), east: Deg(180.0), hemisphere: Hemisphere::Both }]
+    } else {
+        lobes.to_vec()
+    }
+}
+
+/// Returns values of the `lobe_bounds` and `lobe_centers` uniforms of the geometry shaders (see "tris.geom").
+pub fn shader_uniforms(
+    projection: &Projection,
+    lobes: &[Lobe],
+    truncation: Option<Deg<f64>>
+) -> Vec<([f32; 4], [f32; 2])> {
+    clipped_lobes(lobes, truncation).iter().map(|lobe| {
+        let (south, north) = lobe.hemisphere.latitudes(truncation);
+        let bounds = [lobe.west, lobe.east, south, north].map(|angle| Rad::from(angle).0 as f32);
+        let center = [Rad::from(lobe.central_meridian).0 as f32, central_meridian_x(projection, lobe) as f32];
+        (bounds, center)
+    }).collect()
+}
+
+/// Returns boundaries of all lobes (in map coordinates).
+pub fn outline(projection: &Projection, lobes: &[Lobe], truncation: Option<Deg<f64>>) -> Vec<[Point2<f64>; 2]> {
+    let mut segments = vec![];
+
+    for lobe in &clipped_lobes(lobes, truncation) {
+        let (south, north) = lobe.hemisphere.latitudes(truncation);
+        let x0 = central_meridian_x(projection, lobe);
+
+        for boundary in [lobe.west, lobe.east] {
+            let point_at = |i: usize| {
+                let latitude = south + (north - south) * (i as f64 / BOUNDARY_NUM_SEGMENTS as f64);
+                projection.project(projection::lonlat_to_xyz(
+                    (boundary - lobe.central_meridian).into(),
+                    latitude.into()
+                )).map(|p| Point2{ x: p.x + x0, y: p.y })
+            };
+
+            for i in 0..BOUNDARY_NUM_SEGMENTS {
+                if let (Some(p1), Some(p2)) = (point_at(i), point_at(i + 1)) {
+                    segments.push([p1, p2]);
+                }
+            }
+        }
+    }
+
+    segments
+}
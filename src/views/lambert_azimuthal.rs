@@ -0,0 +1,53 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::One;
+use std::rc::Rc;
+use crate::data;
+use crate::projection::Projection;
+use crate::views::{base::ViewBase};
+use std::cell::RefCell;
+
+/// Initial zoom; shows the whole globe (a circle of radius 2).
+const INITIAL_ZOOM: f64 = 0.5;
+
+/// Lambert azimuthal equal-area projection; shows the whole globe, with the point opposite to the center of the view
+/// stretched along the boundary circle.
+pub struct LambertAzimuthalView {
+    base: ViewBase,
+}
+
+impl LambertAzimuthalView {
+    pub fn new(
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> LambertAzimuthalView {
+        let mut base = ViewBase::new(
+            LambertAzimuthalView::initial_orientation(),
+            Projection::LambertAzimuthalEqualArea,
+            program_data,
+            Rc::clone(&program_data.gl_programs.lambert_azimuthal.lines),
+            Rc::clone(&program_data.gl_programs.lambert_azimuthal.triangles),
+            display,
+            renderer
+        );
+        base.zoom_by(INITIAL_ZOOM);
+
+        LambertAzimuthalView{ base }
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
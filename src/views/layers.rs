@@ -0,0 +1,97 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Optional layers drawn in views.
+//
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Layer {
+    Graticule,
+    /// Geometry generated by the overlay script.
+    ScriptOverlay,
+    /// Vector layers other than the base map (see `data::VectorLayer`).
+    VectorLayers,
+    /// Rivers and lakes loaded at startup (see `data::VectorLayer`).
+    RiversAndLakes,
+    /// Tectonic plate boundaries (see `plate_boundaries`).
+    PlateBoundaries,
+    /// Time-zone boundaries and UTC offsets (see `time_zones`).
+    TimeZones,
+    /// Populated places (see `data::Place`).
+    Places,
+    /// Boundaries of interrupted maps' parts and of projections' extents.
+    Outline,
+    /// Points marked by the user.
+    Markers
+}
+
+const NUM_LAYERS: usize = 9;
+
+impl Layer {
+    pub const ALL: [Layer; NUM_LAYERS] = [
+        Layer::Graticule,
+        Layer::ScriptOverlay,
+        Layer::VectorLayers,
+        Layer::RiversAndLakes,
+        Layer::PlateBoundaries,
+        Layer::TimeZones,
+        Layer::Places,
+        Layer::Outline,
+        Layer::Markers
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layer::Graticule => "graticule",
+            Layer::ScriptOverlay => "script overlay",
+            Layer::VectorLayers => "vector layers",
+            Layer::RiversAndLakes => "rivers and lakes",
+            Layer::PlateBoundaries => "plate boundaries",
+            Layer::TimeZones => "time zones",
+            Layer::Places => "places",
+            Layer::Outline => "outline",
+            Layer::Markers => "markers"
+        }
+    }
+}
+
+/// Visibility of every layer.
+#[derive(Copy, Clone, PartialEq)]
+pub struct LayerVisibility([bool; NUM_LAYERS]);
+
+impl Default for LayerVisibility {
+    fn default() -> LayerVisibility {
+        LayerVisibility([true; NUM_LAYERS])
+    }
+}
+
+impl LayerVisibility {
+    pub fn is_visible(&self, layer: Layer) -> bool { self.0[layer as usize] }
+
+    pub fn set_visible(&mut self, layer: Layer, visible: bool) {
+        self.0[layer as usize] = visible;
+    }
+}
+
+/// Per-view overrides of the global layer visibility (`None`: use the global setting).
+#[derive(Copy, Clone, Default, PartialEq)]
+pub struct LayerOverrides([Option<bool>; NUM_LAYERS]);
+
+impl LayerOverrides {
+    pub fn get(&self, layer: Layer) -> Option<bool> { self.0[layer as usize] }
+
+    pub fn set(&mut self, layer: Layer, visible: Option<bool>) {
+        self.0[layer as usize] = visible;
+    }
+
+    /// Returns visibility of `layer` taking into account the global setting.
+    pub fn is_visible(&self, layer: Layer, global: &LayerVisibility) -> bool {
+        self.get(layer).unwrap_or_else(|| global.is_visible(layer))
+    }
+}
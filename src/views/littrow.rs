@@ -0,0 +1,48 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::One;
+use std::rc::Rc;
+use crate::data;
+use crate::projection::Projection;
+use crate::views::{base::ViewBase};
+use std::cell::RefCell;
+
+/// Littrow projection (the only conformal retroazimuthal projection) of the hemisphere centered at the view's center.
+pub struct LittrowView {
+    base: ViewBase,
+}
+
+impl LittrowView {
+    pub fn new(
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> LittrowView {
+        LittrowView{
+            base: ViewBase::new(
+                LittrowView::initial_orientation(),
+                Projection::Littrow,
+                program_data,
+                Rc::clone(&program_data.gl_programs.littrow.lines),
+                Rc::clone(&program_data.gl_programs.littrow.triangles),
+                display,
+                renderer
+            ),
+        }
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
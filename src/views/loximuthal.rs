@@ -0,0 +1,73 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::One;
+use std::rc::Rc;
+use crate::data;
+use crate::projection::Projection;
+use crate::views::{base::ViewBase, ParamUnit, ProjectionParam};
+use std::cell::RefCell;
+
+mod uniform_names {
+    pub const CENTRAL_LATITUDE: &str = "central_latitude";
+}
+
+const DEFAULT_CENTRAL_LATITUDE: cgmath::Deg<f64> = cgmath::Deg(40.0);
+
+/// Max. absolute value of the central latitude (the projection is undefined for the poles).
+const MAX_CENTRAL_LATITUDE: cgmath::Deg<f64> = cgmath::Deg(89.0);
+
+/// Loximuthal projection: rhumb lines from the central point are straight and true to scale.
+pub struct LoximuthalView {
+    base: ViewBase
+}
+
+impl LoximuthalView {
+    pub fn new(
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> LoximuthalView {
+        let mut view = LoximuthalView{
+            base: ViewBase::new(
+                LoximuthalView::initial_orientation(),
+                Projection::Loximuthal{ central_latitude: DEFAULT_CENTRAL_LATITUDE.into() },
+                program_data,
+                Rc::clone(&program_data.gl_programs.loximuthal.lines),
+                Rc::clone(&program_data.gl_programs.loximuthal.triangles),
+                display,
+                renderer
+            )
+        };
+        view.base.set_projection_params(
+            vec![ProjectionParam{
+                uniform_name: uniform_names::CENTRAL_LATITUDE,
+                label: "central latitude",
+                tooltip: "Latitude of the central point; rhumb lines from it are straight and true to scale",
+                unit: ParamUnit::Degrees,
+                min: -MAX_CENTRAL_LATITUDE.0,
+                max: MAX_CENTRAL_LATITUDE.0,
+                speed: 0.2,
+                value: DEFAULT_CENTRAL_LATITUDE.0
+            }],
+            |values, _| Projection::Loximuthal{ central_latitude: cgmath::Deg(values[0]).into() }
+        );
+        view.base.enable_interruption();
+
+        view
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
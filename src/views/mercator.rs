@@ -0,0 +1,122 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::One;
+use std::rc::Rc;
+use crate::data;
+use crate::projection::{Projection, WGS84_ECCENTRICITY};
+use crate::views::{base::ViewBase, ParamUnit, ProjectionParam};
+use std::cell::RefCell;
+
+mod uniform_names {
+    pub const ELLIPSOIDAL: &str = "ellipsoidal";
+}
+
+/// Initial zoom; shows the Mercator map up to ±85° of latitude.
+const INITIAL_ZOOM: f64 = 0.3;
+
+/// Latitude at which the Mercator map is initially truncated.
+const INITIAL_TRUNCATION: cgmath::Deg<f64> = cgmath::Deg(85.0);
+
+/// Mercator projection and cylindrical compromise projections differing from it only in the spacing of parallels.
+#[derive(Copy, Clone, PartialEq)]
+pub enum MercatorKind {
+    /// Conformal, with rhumb lines shown as straight lines; infinite at the poles.
+    Mercator,
+    /// Miller cylindrical: Mercator with the latitude scaled by 4/5 (and the result by 5/4); shows the poles.
+    Miller,
+    /// Gall stereographic: projection from the equator's antipode onto a cylinder secant at ±45°.
+    GallStereographic
+}
+
+impl MercatorKind {
+    pub const ALL: [MercatorKind; 3] = [MercatorKind::Mercator, MercatorKind::Miller, MercatorKind::GallStereographic];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MercatorKind::Mercator => "Mercator",
+            MercatorKind::Miller => "Miller cylindrical",
+            MercatorKind::GallStereographic => "Gall stereographic"
+        }
+    }
+
+    pub fn projection(&self) -> Projection {
+        match self {
+            MercatorKind::Mercator => Projection::Mercator{ eccentricity: 0.0 },
+            MercatorKind::Miller => Projection::Miller,
+            MercatorKind::GallStereographic => Projection::GallStereographic
+        }
+    }
+}
+
+pub struct MercatorView {
+    base: ViewBase,
+    kind: MercatorKind
+}
+
+impl MercatorView {
+    pub fn new(
+        kind: MercatorKind,
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> MercatorView {
+        let gl_programs = match kind {
+            MercatorKind::Mercator => &program_data.gl_programs.mercator,
+            MercatorKind::Miller => &program_data.gl_programs.miller,
+            MercatorKind::GallStereographic => &program_data.gl_programs.gall_stereographic
+        };
+
+        let mut view = MercatorView{
+            base: ViewBase::new(
+                MercatorView::initial_orientation(),
+                kind.projection(),
+                program_data,
+                Rc::clone(&gl_programs.lines),
+                Rc::clone(&gl_programs.triangles),
+                display,
+                renderer
+            ),
+            kind
+        };
+        view.base.enable_stretch();
+        view.base.enable_interruption();
+        if kind == MercatorKind::Mercator {
+            view.base.set_projection_params(
+                vec![ProjectionParam{
+                    uniform_name: uniform_names::ELLIPSOIDAL,
+                    label: "WGS84 ellipsoid",
+                    tooltip: "Use the WGS84 ellipsoid instead of a sphere",
+                    unit: ParamUnit::Flag,
+                    min: 0.0,
+                    max: 1.0,
+                    speed: 1.0,
+                    value: 0.0
+                }],
+                |values, _| Projection::Mercator{
+                    eccentricity: if values[0] != 0.0 { WGS84_ECCENTRICITY } else { 0.0 }
+                }
+            );
+            view.base.set_truncation(Some(INITIAL_TRUNCATION));
+        }
+        view.base.zoom_by(INITIAL_ZOOM);
+
+        view
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    pub fn kind(&self) -> MercatorKind { self.kind }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
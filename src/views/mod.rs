@@ -7,13 +7,66 @@
 //
 
 mod base;
+mod albers_equal_area;
+mod armadillo;
+mod conformal_square;
 mod cylindrical_lambert;
+mod eckert;
+mod elliptical;
+mod factory;
+mod general_perspective;
 mod gnomonic;
+mod interruption;
+mod lambert_azimuthal;
+mod lambert_conformal_conic;
+mod layers;
+mod littrow;
+mod loximuthal;
+mod mercator;
+mod nicolosi;
 mod orthographic;
+mod params;
+mod plugin;
+mod qsc;
 mod stereographic;
+mod transverse_mercator;
 
-pub use base::{ViewBase, DragRotation, ViewMode};
-pub use cylindrical_lambert::CylindricalLambertView;
+pub use base::{
+    random_orientation,
+    ViewBase,
+    MAX_OFFSCREEN_SIZE,
+    MAX_STRETCH,
+    MIN_STRETCH,
+    MIN_TRUNCATION,
+    DragRotation,
+    GraticuleLabel,
+    GraticuleLabels,
+    Layers,
+    ProjectedLines,
+    Rotation,
+    StereoMode,
+    ViewMode
+};
+pub use albers_equal_area::AlbersEqualAreaView;
+pub use armadillo::ArmadilloView;
+pub use conformal_square::{ConformalSquareKind, ConformalSquareView};
+pub use cylindrical_lambert::{CylindricalEqualAreaPreset, CylindricalLambertView};
+pub use eckert::{EckertKind, EckertView};
+pub use elliptical::{EllipticalKind, EllipticalView};
+pub use factory::{create_view, PROJECTION_NAMES};
+pub use general_perspective::{GeneralPerspectiveView, GEOSTATIONARY_ALTITUDE};
 pub use gnomonic::GnomonicView;
+pub use interruption::{Hemisphere, InterruptionPreset, Lobe, MAX_LOBES};
+pub use lambert_azimuthal::LambertAzimuthalView;
+pub use lambert_conformal_conic::LambertConformalConicView;
+pub use layers::{Layer, LayerOverrides, LayerVisibility};
+pub use littrow::LittrowView;
+pub use loximuthal::LoximuthalView;
+pub use mercator::{MercatorKind, MercatorView};
+pub use nicolosi::NicolosiView;
 pub use orthographic::OrthographicView;
+pub use params::{Grid, ParamUnit, ProjectionParam};
+pub use plugin::PluginView;
+pub use qsc::QscView;
 pub use stereographic::StereographicView;
+pub use transverse_mercator::TransverseMercatorView;
@@ -9,6 +9,7 @@
 use cgmath::One;
 use std::rc::Rc;
 use crate::data;
+use crate::projection::Projection;
 use crate::views::{base::ViewBase};
 use std::cell::RefCell;
 
@@ -25,6 +26,7 @@ impl OrthographicView {
         OrthographicView{
             base: ViewBase::new(
                 OrthographicView::initial_orientation(),
+                Projection::Orthographic,
                 program_data,
                 Rc::clone(&program_data.gl_programs.orthographic.lines),
                 Rc::clone(&program_data.gl_programs.orthographic.triangles),
@@ -0,0 +1,276 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Adjustable projection parameters declared by views.
+//
+
+use crate::projection::{self, Projection};
+use cgmath::{Basis3, Point2, Rotation, Rotation3};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ParamUnit {
+    /// Shown in degrees, passed to shaders in radians.
+    Degrees,
+    /// Shown and passed to shaders as is.
+    Plain,
+    /// Shown as a checkbox; passed to shaders as 0 or 1.
+    Flag,
+    /// Shown with 5 decimal places; passed to shaders as is.
+    ScaleFactor,
+    /// Shown in meters; passed to shaders as is.
+    Meters,
+    /// Shown in the chosen distance unit; passed to shaders in kilometers.
+    Kilometers
+}
+
+/// Parameter of a view's projection (e.g. central meridian, standard parallel); shown in the view's window
+/// and passed to its vertex shader as a float uniform.
+#[derive(Copy, Clone)]
+pub struct ProjectionParam {
+    /// Name of the corresponding uniform in the view's vertex shader.
+    pub uniform_name: &'static str,
+    pub label: &'static str,
+    pub tooltip: &'static str,
+    pub unit: ParamUnit,
+    pub min: f64,
+    pub max: f64,
+    /// Value change per pixel of mouse dragging.
+    pub speed: f32,
+    /// Value in `unit`s.
+    pub value: f64
+}
+
+impl ProjectionParam {
+    /// Returns the value to be passed to shaders.
+    pub fn uniform_value(&self) -> f32 {
+        match self.unit {
+            ParamUnit::Degrees => self.value.to_radians() as f32,
+            ParamUnit::Plain | ParamUnit::Flag | ParamUnit::ScaleFactor | ParamUnit::Meters
+                | ParamUnit::Kilometers => self.value as f32
+        }
+    }
+
+    pub fn is_set(&self) -> bool { self.value != 0.0 }
+
+    pub fn display_format(&self) -> &'static str {
+        match self.unit {
+            ParamUnit::Degrees => "%.1f°",
+            ParamUnit::Plain | ParamUnit::Flag => "%.3f",
+            ParamUnit::ScaleFactor => "%.5f",
+            ParamUnit::Meters => "%.0f m",
+            ParamUnit::Kilometers => "%.0f km"
+        }
+    }
+}
+
+/// Names of the grid parameters (see `Grid::params`).
+pub mod grid_param_names {
+    pub const SCALE_FACTOR: &str = "scale_factor";
+    pub const FALSE_EASTING: &str = "false_easting";
+    pub const FALSE_NORTHING: &str = "false_northing";
+    pub const CENTRAL_MERIDIAN: &str = "grid_central_meridian";
+    pub const ORIGIN_LATITUDE: &str = "grid_origin_latitude";
+}
+
+/// Range of the scale factor at the projection's origin.
+const MIN_SCALE_FACTOR: f64 = 0.9;
+const MAX_SCALE_FACTOR: f64 = 1.1;
+
+/// Max. absolute value of false easting and northing (in meters).
+const MAX_FALSE_OFFSET: f64 = 20_000_000.0;
+
+/// Origin of the projection, scale factor at the origin and false easting/northing, relating map coordinates
+/// to a grid system (e.g. UTM). They do not change the shape of the map, only the grid coordinates of its points
+/// (see `ViewBase::grid_coordinates`), which do not depend on where the view is currently centered.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Grid {
+    pub scale_factor: f64,
+    /// Meters.
+    pub false_easting: f64,
+    /// Meters.
+    pub false_northing: f64,
+    /// Degrees.
+    pub central_meridian: f64,
+    /// Degrees.
+    pub origin_latitude: f64
+}
+
+impl Grid {
+    /// Map coordinates scaled to meters.
+    pub const DEFAULT: Grid = Grid{
+        scale_factor: 1.0,
+        false_easting: 0.0,
+        false_northing: 0.0,
+        central_meridian: 0.0,
+        origin_latitude: 0.0
+    };
+
+    /// Universal Transverse Mercator grid of the northern or southern hemisphere (with the central meridian
+    /// of the zone to be set).
+    pub fn utm(south: bool) -> Grid {
+        Grid{
+            scale_factor: 0.9996,
+            false_easting: 500_000.0,
+            false_northing: if south { 10_000_000.0 } else { 0.0 },
+            central_meridian: 0.0,
+            origin_latitude: 0.0
+        }
+    }
+
+    /// Returns grid coordinates (easting, northing in meters) of the specified point projected with `projection`
+    /// on a globe with the specified semi-major axis (meters; see `map_position`).
+    pub fn project(
+        &self,
+        projection: &Projection,
+        semi_major_axis: f64,
+        longitude: cgmath::Deg<f64>,
+        latitude: cgmath::Deg<f64>
+    ) -> Option<Point2<f64>> {
+        let map = self.map_position(projection, longitude, latitude)?;
+        let meters_per_map_unit = self.scale_factor * semi_major_axis;
+
+        Some(Point2{
+            x: self.false_easting + meters_per_map_unit * map.x,
+            y: self.false_northing + meters_per_map_unit * map.y
+        })
+    }
+
+    /// Returns the position (in map coordinates, relative to the grid's origin) of the specified point projected
+    /// with `projection`. Azimuthal projections are centered on the origin. Others are used in their normal aspect
+    /// with the grid's central meridian, so that e.g. northings of the ellipsoidal transverse Mercator are measured
+    /// along the meridian from the origin's latitude.
+    pub fn map_position(
+        &self,
+        projection: &Projection,
+        longitude: cgmath::Deg<f64>,
+        latitude: cgmath::Deg<f64>
+    ) -> Option<Point2<f64>> {
+        let to_xyz = |longitude: f64, latitude: f64| {
+            projection::lonlat_to_xyz(cgmath::Deg(longitude).into(), cgmath::Deg(latitude).into())
+        };
+
+        if projection.is_azimuthal() {
+            // as set by `ViewBase::center_on` in the N-S-E-W rotation mode
+            let origin_orientation = Basis3::from_angle_y(cgmath::Rad::from(cgmath::Deg(self.origin_latitude)))
+                * Basis3::from_angle_z(-cgmath::Rad::from(cgmath::Deg(self.central_meridian)));
+
+            projection.project(origin_orientation.rotate_vector(to_xyz(longitude.0, latitude.0)))
+        } else {
+            let origin = projection.project(to_xyz(0.0, self.origin_latitude))?;
+            let p = projection.project(to_xyz(longitude.0 - self.central_meridian, latitude.0))?;
+
+            Some(Point2{ x: p.x, y: p.y - origin.y })
+        }
+    }
+
+    /// Returns the parameters to be declared by a view supporting the grid (after its projection's own parameters).
+    pub fn params(&self) -> Vec<ProjectionParam> {
+        vec![
+            ProjectionParam{
+                uniform_name: grid_param_names::SCALE_FACTOR,
+                label: "scale factor",
+                tooltip: "Scale factor at the projection's origin (k0); affects only the grid coordinates",
+                unit: ParamUnit::ScaleFactor,
+                min: MIN_SCALE_FACTOR,
+                max: MAX_SCALE_FACTOR,
+                speed: 0.0001,
+                value: self.scale_factor
+            },
+            ProjectionParam{
+                uniform_name: grid_param_names::FALSE_EASTING,
+                label: "false easting",
+                tooltip: "Added to the easting of every point",
+                unit: ParamUnit::Meters,
+                min: -MAX_FALSE_OFFSET,
+                max: MAX_FALSE_OFFSET,
+                speed: 1000.0,
+                value: self.false_easting
+            },
+            ProjectionParam{
+                uniform_name: grid_param_names::FALSE_NORTHING,
+                label: "false northing",
+                tooltip: "Added to the northing of every point",
+                unit: ParamUnit::Meters,
+                min: -MAX_FALSE_OFFSET,
+                max: MAX_FALSE_OFFSET,
+                speed: 1000.0,
+                value: self.false_northing
+            },
+            ProjectionParam{
+                uniform_name: grid_param_names::CENTRAL_MERIDIAN,
+                label: "central meridian",
+                tooltip: "Longitude of the grid's origin (where the false easting applies)",
+                unit: ParamUnit::Degrees,
+                min: -180.0,
+                max: 180.0,
+                speed: 0.1,
+                value: self.central_meridian
+            },
+            ProjectionParam{
+                uniform_name: grid_param_names::ORIGIN_LATITUDE,
+                label: "latitude of origin",
+                tooltip: "Latitude of the grid's origin (where the false northing applies)",
+                unit: ParamUnit::Degrees,
+                min: -90.0,
+                max: 90.0,
+                speed: 0.1,
+                value: self.origin_latitude
+            }
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projection::WGS84_ECCENTRICITY;
+
+    const WGS84_SEMI_MAJOR_AXIS: f64 = 6_378_137.0;
+
+    /// Lengths (meters) of the WGS84 meridian arc from the equator to 45° and 49°.
+    const MERIDIAN_ARC_45: f64 = 4_984_944.378;
+    const MERIDIAN_ARC_49: f64 = 5_429_627.632;
+
+    #[test]
+    fn ellipsoidal_transverse_mercator_northing_follows_meridian_arc_from_origin() {
+        let grid = Grid{
+            scale_factor: 0.9996,
+            false_easting: 500_000.0,
+            false_northing: 200_000.0,
+            central_meridian: 15.0,
+            origin_latitude: 45.0
+        };
+        let projection = Projection::TransverseMercator{ eccentricity: WGS84_ECCENTRICITY };
+        let project = |lon, lat| grid.project(&projection, WGS84_SEMI_MAJOR_AXIS, cgmath::Deg(lon), cgmath::Deg(lat))
+            .unwrap();
+
+        let origin = project(15.0, 45.0);
+        assert!((origin.x - 500_000.0).abs() < 1.0e-6 && (origin.y - 200_000.0).abs() < 1.0e-6);
+
+        let p = project(15.0, 49.0);
+        assert!((p.x - 500_000.0).abs() < 1.0e-6);
+        assert!((p.y - (200_000.0 + 0.9996 * (MERIDIAN_ARC_49 - MERIDIAN_ARC_45))).abs() < 0.01, "{}", p.y);
+    }
+
+    #[test]
+    fn grid_origin_is_at_false_easting_and_northing() {
+        let grid = Grid{ central_meridian: -96.0, origin_latitude: 23.0, false_easting: 1000.0, ..Grid::DEFAULT };
+        for projection in [
+            Projection::LambertConformalConic{
+                standard_parallels: [cgmath::Deg(29.5).into(), cgmath::Deg(45.5).into()],
+                eccentricity: WGS84_ECCENTRICITY
+            },
+            Projection::Stereographic{ point_distance: 1.0, image_plane: 1.0 }
+        ] {
+            let origin = grid.project(&projection, WGS84_SEMI_MAJOR_AXIS, cgmath::Deg(-96.0), cgmath::Deg(23.0))
+                .unwrap();
+            assert!((origin.x - 1000.0).abs() < 1.0e-6 && origin.y.abs() < 1.0e-6, "{:?}", origin);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::One;
+use std::rc::Rc;
+use crate::data;
+use crate::projection::Projection;
+use crate::plugins::Plugin;
+use crate::views::{base::ViewBase};
+use std::cell::RefCell;
+
+/// View of a projection provided by a plugin.
+pub struct PluginView {
+    base: ViewBase,
+    plugin: &'static Plugin
+}
+
+impl PluginView {
+    pub fn new(
+        plugin: &'static Plugin,
+        gl_programs: &data::GlProgramPair,
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> PluginView {
+        PluginView{
+            base: ViewBase::new(
+                PluginView::initial_orientation(),
+                Projection::Plugin(plugin),
+                program_data,
+                Rc::clone(&gl_programs.lines),
+                Rc::clone(&gl_programs.triangles),
+                display,
+                renderer
+            ),
+            plugin
+        }
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn name(&self) -> &str { &self.plugin.name }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
@@ -0,0 +1,68 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::{One, Point2};
+use std::rc::Rc;
+use crate::data;
+use crate::projection::{self, Projection};
+use crate::views::{base::ViewBase};
+use std::cell::RefCell;
+
+/// Initial zoom; shows the whole cross of cube faces.
+const INITIAL_ZOOM: f64 = 0.3;
+
+/// Quadrilateralized spherical cube; the cube's faces are laid out as a cross, with their boundaries drawn.
+pub struct QscView {
+    base: ViewBase,
+}
+
+impl QscView {
+    pub fn new(
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> QscView {
+        let mut base = ViewBase::new(
+            QscView::initial_orientation(),
+            Projection::QuadrilateralizedSphericalCube,
+            program_data,
+            Rc::clone(&program_data.gl_programs.qsc.lines),
+            Rc::clone(&program_data.gl_programs.qsc.triangles),
+            display,
+            renderer
+        );
+        base.zoom_by(INITIAL_ZOOM);
+        base.set_outline(face_boundaries());
+
+        QscView{ base }
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
+
+/// Returns edges of all cube faces in map coordinates.
+fn face_boundaries() -> Vec<[Point2<f64>; 2]> {
+    let h = projection::QSC_FACE_HALF_SIZE;
+    let mut segments = vec![];
+    for center in &projection::QSC_FACE_CENTERS {
+        let corner = |dx: f64, dy: f64| Point2{ x: h * (center[0] + dx), y: h * (center[1] + dy) };
+        segments.push([corner(-1.0, -1.0), corner(1.0, -1.0)]);
+        segments.push([corner(1.0, -1.0), corner(1.0, 1.0)]);
+        segments.push([corner(1.0, 1.0), corner(-1.0, 1.0)]);
+        segments.push([corner(-1.0, 1.0), corner(-1.0, -1.0)]);
+    }
+
+    segments
+}
@@ -9,9 +9,22 @@
 use cgmath::One;
 use std::rc::Rc;
 use crate::data;
-use crate::views::{base::ViewBase};
+use crate::projection::Projection;
+use crate::views::{base::ViewBase, Grid, ParamUnit, ProjectionParam};
 use std::cell::RefCell;
 
+mod uniform_names {
+    pub const POINT_DISTANCE: &str = "point_distance";
+    pub const IMAGE_PLANE: &str = "image_plane";
+}
+
+/// Min. distance of the projection point from the globe's center; at 0 the projection becomes gnomonic,
+/// which cannot show the whole hemisphere.
+const MIN_POINT_DISTANCE: f64 = 0.1;
+
+const MAX_POINT_DISTANCE: f64 = 3.0;
+
+/// Azimuthal perspective projection from a point on the far side of the globe, stereographic by default.
 pub struct StereographicView {
     base: ViewBase,
 }
@@ -22,16 +35,51 @@ impl StereographicView {
         renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
         display: &glium::Display
     ) -> StereographicView {
-        StereographicView{
+        let mut view = StereographicView{
             base: ViewBase::new(
                 StereographicView::initial_orientation(),
+                Projection::Stereographic{ point_distance: 1.0, image_plane: 1.0 },
                 program_data,
                 Rc::clone(&program_data.gl_programs.stereographic.lines),
                 Rc::clone(&program_data.gl_programs.stereographic.triangles),
                 display,
                 renderer
             ),
-        }
+        };
+        let mut params = vec![
+            ProjectionParam{
+                uniform_name: uniform_names::POINT_DISTANCE,
+                label: "projection point",
+                tooltip: "Distance of the projection point from the globe's center (in globe radii)\n\
+                    1: stereographic\n\
+                    towards 0: gnomonic\n\
+                    towards infinity: orthographic",
+                unit: ParamUnit::Plain,
+                min: MIN_POINT_DISTANCE,
+                max: MAX_POINT_DISTANCE,
+                speed: 0.005,
+                value: 1.0
+            },
+            ProjectionParam{
+                uniform_name: uniform_names::IMAGE_PLANE,
+                label: "image plane",
+                tooltip: "Position of the image plane relative to the globe's center\n\
+                    1: tangent plane\n\
+                    less than 1: secant plane",
+                unit: ParamUnit::Plain,
+                min: 0.0,
+                max: 1.0,
+                speed: 0.005,
+                value: 1.0
+            }
+        ];
+        params.extend(Grid::DEFAULT.params());
+        view.base.set_projection_params(
+            params,
+            |values, _| Projection::Stereographic{ point_distance: values[0], image_plane: values[1] }
+        );
+
+        view
     }
 
     pub fn unique_id(&self) -> u32 { self.base.unique_id() }
@@ -0,0 +1,75 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+use cgmath::One;
+use std::rc::Rc;
+use crate::data;
+use crate::projection::{Projection, WGS84_ECCENTRICITY};
+use crate::views::{base::ViewBase, Grid, ParamUnit, ProjectionParam};
+use std::cell::RefCell;
+
+mod uniform_names {
+    pub const ELLIPSOIDAL: &str = "ellipsoidal";
+}
+
+/// Initial zoom; shows the map up to ±80° from the central meridian.
+const INITIAL_ZOOM: f64 = 0.3;
+
+/// Transverse Mercator projection: conformal, true to scale along the central meridian.
+pub struct TransverseMercatorView {
+    base: ViewBase
+}
+
+impl TransverseMercatorView {
+    pub fn new(
+        program_data: &data::ProgramData,
+        renderer: &Rc<RefCell<imgui_glium_renderer::Renderer>>,
+        display: &glium::Display
+    ) -> TransverseMercatorView {
+        let mut view = TransverseMercatorView{
+            base: ViewBase::new(
+                TransverseMercatorView::initial_orientation(),
+                Projection::TransverseMercator{ eccentricity: 0.0 },
+                program_data,
+                Rc::clone(&program_data.gl_programs.transverse_mercator.lines),
+                Rc::clone(&program_data.gl_programs.transverse_mercator.triangles),
+                display,
+                renderer
+            )
+        };
+        let mut params = vec![ProjectionParam{
+            uniform_name: uniform_names::ELLIPSOIDAL,
+            label: "WGS84 ellipsoid",
+            tooltip: "Use the WGS84 ellipsoid instead of a sphere",
+            unit: ParamUnit::Flag,
+            min: 0.0,
+            max: 1.0,
+            speed: 1.0,
+            value: 0.0
+        }];
+        params.extend(Grid::DEFAULT.params());
+        view.base.set_projection_params(
+            params,
+            |values, _| Projection::TransverseMercator{
+                eccentricity: if values[0] != 0.0 { WGS84_ECCENTRICITY } else { 0.0 }
+            }
+        );
+        view.base.zoom_by(INITIAL_ZOOM);
+
+        view
+    }
+
+    pub fn unique_id(&self) -> u32 { self.base.unique_id() }
+
+    pub fn base_mut(&mut self) -> &mut ViewBase { &mut self.base }
+
+    /// Returns identity matrix: observer facing long. 0°, lat. 0°.
+    fn initial_orientation() -> cgmath::Basis3<f64> {
+        cgmath::Basis3::one()
+    }
+}
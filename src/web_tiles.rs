@@ -0,0 +1,482 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Base map from an XYZ web tile source (e.g. OpenStreetMap) or an MBTiles file.
+//
+// Tiles are 256×256 images in the Web Mercator projection, numbered from the north-west corner
+// (see https://wiki.openstreetmap.org/wiki/Slippy_map_tilenames). Tiles covering a region at a zoom level are read
+// from the disk cache or fetched over HTTP (or read from the MBTiles file) in a background thread; once all have
+// been received, their mosaic is resampled into an equirectangular image of the region, which views show over
+// the base map. A region crossing the antimeridian has a range of longitude extending beyond 180°; its columns
+// of tiles continue from the westernmost ones.
+//
+// Each cached tile has a file with the time of fetching, the expiry time (from the response's `Cache-Control: max-age`
+// or `Expires` header; `DEFAULT_MAX_AGE` if there are none) and validators (`ETag`, `Last-Modified`). An expired tile
+// is revalidated with a conditional request; if that fails, the expired tile is used.
+//
+// An MBTiles file (https://github.com/mapbox/mbtiles-spec) is an SQLite database with raster tiles in the table
+// `tiles(zoom_level, tile_column, tile_row, tile_data)`, whose rows are numbered from the south (TMS scheme).
+// Tiles missing from the file are left transparent.
+//
+
+use crate::config;
+use crate::data;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::sync::mpsc;
+
+pub const DEFAULT_TILE_URL: &str = "https://tile.openstreetmap.org/{z}/{x}/{y}.png";
+
+pub const MAX_ZOOM: u32 = 19;
+
+/// Max. number of tiles fetched for a region.
+pub const MAX_TILES: usize = 256;
+
+const TILE_SIZE: u32 = 256;
+
+/// Max. size of a tile's file; larger responses are rejected.
+const MAX_TILE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Max. latitude (degrees) covered by Web Mercator tiles.
+const MAX_LATITUDE: f64 = 85.051129;
+
+/// Directory (in the program's configuration directory) with cached tiles; each tile source has a subdirectory.
+const TILE_CACHE_SUBDIR: &str = "tile_cache";
+
+/// Max. length of the readable part of a tile source's cache subdirectory name (followed by the URL's hash).
+const MAX_CACHE_NAME_LEN: usize = 48;
+
+/// Time (seconds) for which tiles received without caching headers are used without revalidation.
+const DEFAULT_MAX_AGE: u64 = 7 * 24 * 3600;
+
+/// Extension of the files with cache information of tiles (see `CachedTileInfo`).
+const CACHE_INFO_EXTENSION: &str = "json";
+
+/// Identifies the program to tile servers (required e.g. by the OpenStreetMap tile usage policy).
+pub const USER_AGENT: &str = concat!("projections/", env!("CARGO_PKG_VERSION"));
+
+/// Source of tiles.
+#[derive(Clone, PartialEq)]
+pub enum TileSource {
+    /// URL template with `{z}`, `{x}`, `{y}` replaced by the tile's zoom level, column and row.
+    Web(String),
+    /// Path of an MBTiles file.
+    MbTiles(String)
+}
+
+impl TileSource {
+    /// Returns the highest zoom level the source provides.
+    pub fn max_zoom(&self) -> Result<u32, String> {
+        match self {
+            TileSource::Web(_) => Ok(MAX_ZOOM),
+            TileSource::MbTiles(path) => {
+                let max_zoom: Option<u32> = open_mbtiles(path)?
+                    .query_row("SELECT MAX(zoom_level) FROM tiles", [], |row| row.get(0))
+                    .map_err(|e| format!("cannot read {}: {}", path, e))?;
+                max_zoom.map(|zoom| zoom.min(MAX_ZOOM)).ok_or_else(|| format!("{} contains no tiles", path))
+            }
+        }
+    }
+}
+
+/// Reader of tiles from a source (used by the fetching thread).
+enum TileReader {
+    Web(String),
+    MbTiles(rusqlite::Connection)
+}
+
+impl TileReader {
+    fn new(source: &TileSource) -> Result<TileReader, String> {
+        Ok(match source {
+            TileSource::Web(url_template) => TileReader::Web(url_template.clone()),
+            TileSource::MbTiles(path) => TileReader::MbTiles(open_mbtiles(path)?)
+        })
+    }
+
+    /// Returns `None` if the source has no such tile; `x` must be less than the number of columns at `zoom`.
+    fn read(&self, zoom: u32, x: u32, y: u32) -> Result<Option<image::RgbaImage>, String> {
+        match self {
+            TileReader::Web(url_template) => fetch_tile(url_template, zoom, x, y).map(Some),
+            TileReader::MbTiles(connection) => {
+                let bytes: Option<Vec<u8>> = match connection.query_row(
+                    "SELECT tile_data FROM tiles WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                    [zoom, x, (1u32 << zoom) - 1 - y],
+                    |row| row.get(0)
+                ) {
+                    Ok(bytes) => Some(bytes),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                    Err(e) => return Err(e.to_string())
+                };
+
+                match bytes {
+                    Some(bytes) => Ok(Some(image::load_from_memory(&bytes).map_err(|e| e.to_string())?.into_rgba8())),
+                    None => Ok(None)
+                }
+            }
+        }
+    }
+}
+
+fn open_mbtiles(path: &str) -> Result<rusqlite::Connection, String> {
+    rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("cannot open {}: {}", path, e))
+}
+
+/// Range of tiles at a zoom level.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TileRange {
+    pub zoom: u32,
+    /// First and last column; the last one exceeds the number of columns at `zoom` if the range crosses
+    /// the antimeridian (columns wrap around).
+    pub x: [u32; 2],
+    /// First and last row.
+    pub y: [u32; 2]
+}
+
+fn tile_x(longitude: f64, zoom: u32) -> f64 {
+    (longitude + 180.0) / 360.0 * (1u32 << zoom) as f64
+}
+
+fn tile_y(latitude: f64, zoom: u32) -> f64 {
+    let latitude = latitude.max(-MAX_LATITUDE).min(MAX_LATITUDE).to_radians();
+    (1.0 - latitude.tan().asinh() / std::f64::consts::PI) / 2.0 * (1u32 << zoom) as f64
+}
+
+fn tile_longitude(x: f64, zoom: u32) -> f64 {
+    x / (1u32 << zoom) as f64 * 360.0 - 180.0
+}
+
+fn tile_latitude(y: f64, zoom: u32) -> f64 {
+    (std::f64::consts::PI * (1.0 - 2.0 * y / (1u32 << zoom) as f64)).sinh().atan().to_degrees()
+}
+
+impl TileRange {
+    /// Returns the tiles covering the region (degrees; `lon_range` starts within [-180°, 180°] and extends
+    /// beyond 180° if it crosses the antimeridian) at the highest zoom level not exceeding `max_zoom` which needs
+    /// at most `MAX_TILES` tiles.
+    pub fn covering(lon_range: [f64; 2], lat_range: [f64; 2], max_zoom: u32) -> TileRange {
+        let mut zoom = max_zoom.min(MAX_ZOOM);
+        loop {
+            let last = (1u32 << zoom) - 1;
+            let first_x = (tile_x(lon_range[0], zoom) as u32).min(last);
+            let range = TileRange{
+                zoom,
+                // at most all columns
+                x: [first_x, (tile_x(lon_range[1], zoom) as u32).min(first_x + last)],
+                // rows are numbered from the north
+                y: [
+                    (tile_y(lat_range[1], zoom) as u32).min(last),
+                    (tile_y(lat_range[0], zoom) as u32).min(last)
+                ]
+            };
+            if range.num_tiles() <= MAX_TILES || zoom == 0 { return range; }
+            zoom -= 1;
+        }
+    }
+
+    pub fn num_tiles(&self) -> usize {
+        (self.x[1] - self.x[0] + 1) as usize * (self.y[1] - self.y[0] + 1) as usize
+    }
+
+    /// Returns the ranges of longitude and latitude (degrees) covered by the tiles.
+    pub fn bounds(&self) -> ([f64; 2], [f64; 2]) {
+        (
+            [tile_longitude(self.x[0] as f64, self.zoom), tile_longitude((self.x[1] + 1) as f64, self.zoom)],
+            [tile_latitude((self.y[1] + 1) as f64, self.zoom), tile_latitude(self.y[0] as f64, self.zoom)]
+        )
+    }
+
+    /// Returns columns (not wrapped around) and rows of the tiles.
+    fn tiles(&self) -> impl Iterator<Item = (u32, u32)> {
+        let (x, y) = (self.x, self.y);
+        (y[0]..=y[1]).flat_map(move |ty| (x[0]..=x[1]).map(move |tx| (tx, ty)))
+    }
+}
+
+/// Returns the zoom level at which tiles have at least `pixels_per_degree` (of longitude).
+pub fn zoom_for_resolution(pixels_per_degree: f64) -> u32 {
+    let zoom = (pixels_per_degree * 360.0 / TILE_SIZE as f64).log2().ceil();
+    if zoom > 0.0 { (zoom as u32).min(MAX_ZOOM) } else { 0 }
+}
+
+/// Returns the directory of cached tiles of the source with `url_template` (if the configuration directory
+/// is known).
+fn cache_dir(url_template: &str) -> Option<std::path::PathBuf> {
+    // the readable part alone is ambiguous (and may be truncated); the hash tells sources apart
+    let readable: String = url_template
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .chars()
+        .take(MAX_CACHE_NAME_LEN)
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let source = format!("{}_{:016x}", readable, data::fnv1a(url_template.as_bytes()));
+
+    config::config_dir().map(|dir| dir.join(TILE_CACHE_SUBDIR).join(source))
+}
+
+/// Cache information of a tile (stored next to the tile's file).
+#[derive(Serialize, Deserialize)]
+struct CachedTileInfo {
+    /// Time of the last fetch or revalidation (seconds since the Unix epoch).
+    fetched: u64,
+    /// Time (seconds since the Unix epoch) after which the tile has to be revalidated.
+    expires: u64,
+    etag: Option<String>,
+    last_modified: Option<String>
+}
+
+fn unix_time() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Returns the time (seconds since the Unix epoch) until which a response received at `now` may be used without
+/// revalidation, as given by its `Cache-Control` and `Expires` headers, or `None` if it must not be stored.
+fn cache_expiry(cache_control: Option<&str>, expires: Option<&str>, now: u64) -> Option<u64> {
+    let directives: Vec<String> = cache_control.unwrap_or("").split(',').map(|d| d.trim().to_lowercase()).collect();
+    if directives.iter().any(|d| d == "no-store") {
+        return None;
+    }
+    if directives.iter().any(|d| d == "no-cache") {
+        return Some(now);
+    }
+    if let Some(max_age) = directives.iter().find_map(|d| d.strip_prefix("max-age=")) {
+        // an invalid value means the response is stale
+        return Some(now + max_age.trim_matches('"').parse::<u64>().unwrap_or(0));
+    }
+    if let Some(expires) = expires {
+        // an invalid date (e.g. "0") means the response is stale
+        return Some(chrono::DateTime::parse_from_rfc2822(expires).map_or(now, |t| t.timestamp().max(0) as u64));
+    }
+
+    Some(now + DEFAULT_MAX_AGE)
+}
+
+/// Returns a cached tile and its cache information (if it is cached and valid).
+fn read_cached_tile(path: &std::path::Path) -> Option<(image::RgbaImage, CachedTileInfo)> {
+    let tile = image::load_from_memory(&std::fs::read(path).ok()?).ok()?.into_rgba8();
+    let info = std::fs::read_to_string(path.with_extension(CACHE_INFO_EXTENSION)).ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        // tiles cached without information are revalidated
+        .unwrap_or(CachedTileInfo{ fetched: 0, expires: 0, etag: None, last_modified: None });
+
+    Some((tile, info))
+}
+
+fn write_cache_info(path: &std::path::Path, info: &CachedTileInfo) -> Result<(), String> {
+    let contents = serde_json::to_string(info).map_err(|e| e.to_string())?;
+    std::fs::write(path.with_extension(CACHE_INFO_EXTENSION), contents).map_err(|e| e.to_string())
+}
+
+fn fetch_tile(url_template: &str, zoom: u32, x: u32, y: u32) -> Result<image::RgbaImage, String> {
+    let cache_path = cache_dir(url_template)
+        .map(|dir| dir.join(zoom.to_string()).join(x.to_string()).join(y.to_string()));
+
+    // a damaged cached tile is fetched again
+    let cached = cache_path.as_deref().and_then(read_cached_tile);
+    let now = unix_time();
+    if let Some((tile, info)) = &cached {
+        if now < info.expires { return Ok(tile.clone()); }
+    }
+
+    let url = url_template
+        .replace("{z}", &zoom.to_string())
+        .replace("{x}", &x.to_string())
+        .replace("{y}", &y.to_string());
+    let mut request = ureq::get(&url).set("User-Agent", USER_AGENT);
+    if let Some((_, info)) = &cached {
+        if let Some(etag) = &info.etag { request = request.set("If-None-Match", etag); }
+        if let Some(last_modified) = &info.last_modified { request = request.set("If-Modified-Since", last_modified); }
+    }
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(e) => return match cached {
+            // better an outdated tile than none
+            Some((tile, _)) => Ok(tile),
+            None => Err(e.to_string())
+        }
+    };
+
+    let expires = cache_expiry(response.header("Cache-Control"), response.header("Expires"), now);
+    let header = |name: &str| response.header(name).map(|value| value.to_string());
+    let (etag, last_modified) = (header("ETag"), header("Last-Modified"));
+
+    if response.status() == 304 {
+        if let (Some((tile, info)), Some(path)) = (cached, &cache_path) {
+            let info = CachedTileInfo{
+                fetched: now,
+                expires: expires.unwrap_or(now),
+                etag: etag.or(info.etag),
+                last_modified: last_modified.or(info.last_modified)
+            };
+            if let Err(e) = write_cache_info(path, &info) {
+                eprintln!("Failed to update cache information of tile {}/{}/{}: {}", zoom, x, y, e);
+            }
+            return Ok(tile);
+        }
+        return Err("unexpected response: not modified".into());
+    }
+
+    let mut bytes = vec![];
+    // one byte more than allowed tells a truncated response from a complete one
+    response.into_reader().take(MAX_TILE_BYTES + 1).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    if bytes.len() as u64 > MAX_TILE_BYTES {
+        return Err(format!("tile larger than {} bytes", MAX_TILE_BYTES));
+    }
+    // only valid images are cached
+    let tile = image::load_from_memory(&bytes).map_err(|e| e.to_string())?.into_rgba8();
+
+    if let (Some(path), Some(expires)) = (&cache_path, expires) {
+        let info = CachedTileInfo{ fetched: now, expires, etag, last_modified };
+        let result = std::fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| std::fs::write(path, &bytes))
+            .map_err(|e| e.to_string())
+            .and_then(|_| write_cache_info(path, &info));
+        if let Err(e) = result {
+            eprintln!("Failed to cache tile {}/{}/{}: {}", zoom, x, y, e);
+        }
+    }
+
+    Ok(tile)
+}
+
+/// Fetching of a region's tiles (in a background thread, stopped when dropped).
+pub struct TileFetch {
+    pub source: TileSource,
+    pub range: TileRange,
+    receiver: mpsc::Receiver<(u32, u32, Result<Option<image::RgbaImage>, String>)>,
+    mosaic: image::RgbaImage,
+    pub num_received: usize,
+    pub num_failed: usize,
+    /// Error of the most recent failed tile.
+    pub last_error: Option<String>
+}
+
+impl TileFetch {
+    pub fn start(source: &TileSource, range: TileRange) -> Result<TileFetch, String> {
+        let reader = TileReader::new(source)?;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let num_columns = 1u32 << range.zoom;
+            for (x, y) in range.tiles() {
+                // fails once the fetch has been dropped
+                if sender.send((x, y, reader.read(range.zoom, x % num_columns, y))).is_err() { return; }
+            }
+        });
+
+        Ok(TileFetch{
+            source: source.clone(),
+            range,
+            receiver,
+            mosaic: image::RgbaImage::new(
+                (range.x[1] - range.x[0] + 1) * TILE_SIZE,
+                (range.y[1] - range.y[0] + 1) * TILE_SIZE
+            ),
+            num_received: 0,
+            num_failed: 0,
+            last_error: None
+        })
+    }
+
+    /// Adds the tiles received so far to the mosaic; returns true if the last tile has been received
+    /// during this call.
+    pub fn poll(&mut self) -> bool {
+        let mut any_received = false;
+        while let Ok((x, y, result)) = self.receiver.try_recv() {
+            any_received = true;
+            self.num_received += 1;
+            match result {
+                Ok(None) => (),
+                Ok(Some(tile)) => {
+                    let tile = if tile.dimensions() == (TILE_SIZE, TILE_SIZE) {
+                        tile
+                    } else {
+                        image::imageops::resize(&tile, TILE_SIZE, TILE_SIZE, image::imageops::FilterType::Triangle)
+                    };
+                    image::imageops::replace(
+                        &mut self.mosaic,
+                        &tile,
+                        (x - self.range.x[0]) * TILE_SIZE,
+                        (y - self.range.y[0]) * TILE_SIZE
+                    );
+                },
+                Err(e) => {
+                    self.num_failed += 1;
+                    let x = x % (1u32 << self.range.zoom);
+                    self.last_error = Some(format!("tile {}/{}/{}: {}", self.range.zoom, x, y, e));
+                }
+            }
+        }
+
+        any_received && self.is_complete()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.num_received == self.range.num_tiles()
+    }
+
+    /// Returns the mosaic resampled into an equirectangular image of the region given by `range.bounds()`
+    /// (with the resolution of the mosaic at the region's center); missing tiles are transparent.
+    pub fn resample(&self, max_size: u32) -> image::RgbaImage {
+        let (lon_range, lat_range) = self.range.bounds();
+        let zoom = self.range.zoom;
+        let width = self.mosaic.width();
+        let center_latitude = (lat_range[0] + lat_range[1]) / 2.0;
+        let height = (width as f64 * (lat_range[1] - lat_range[0]) / (lon_range[1] - lon_range[0])
+            / center_latitude.to_radians().cos()).round().max(1.0) as u32;
+        let scale = (max_size as f64 / width.max(height) as f64).min(1.0);
+        let (width, height) = (((width as f64 * scale) as u32).max(1), ((height as f64 * scale) as u32).max(1));
+
+        let mut image = image::RgbaImage::new(width, height);
+        for row in 0..height {
+            let latitude = lat_range[1] - (row as f64 + 0.5) / height as f64 * (lat_range[1] - lat_range[0]);
+            let mosaic_y = ((tile_y(latitude, zoom) - self.range.y[0] as f64) * TILE_SIZE as f64) as u32;
+            let mosaic_y = mosaic_y.min(self.mosaic.height() - 1);
+            for column in 0..width {
+                let longitude = lon_range[0] + (column as f64 + 0.5) / width as f64 * (lon_range[1] - lon_range[0]);
+                let mosaic_x = ((tile_x(longitude, zoom) - self.range.x[0] as f64) * TILE_SIZE as f64) as u32;
+                let mosaic_x = mosaic_x.min(self.mosaic.width() - 1);
+                image.put_pixel(column, row, *self.mosaic.get_pixel(mosaic_x, mosaic_y));
+            }
+        }
+
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_crossing_antimeridian_continues_from_the_westernmost_columns() {
+        let range = TileRange::covering([170.0, 200.0], [-10.0, 10.0], 3);
+        assert_eq!(TileRange{ zoom: 3, x: [7, 8], y: [3, 4] }, range);
+        assert_eq!([7, 0], [range.x[0] % 8, range.x[1] % 8]);
+
+        let (lon_range, _) = range.bounds();
+        assert_eq!([135.0, 225.0], lon_range);
+
+        // the whole globe needs each column once
+        let range = TileRange::covering([-180.0, 180.0], [-80.0, 80.0], 2);
+        assert_eq!([0, 3], range.x);
+    }
+
+    #[test]
+    fn cache_expiry_follows_headers() {
+        let now = 1_000_000;
+        assert_eq!(Some(now + 3600), cache_expiry(Some("public, max-age=3600"), None, now));
+        assert_eq!(Some(now + 60), cache_expiry(Some("Max-Age=60"), Some("Thu, 01 Jan 1970 00:00:00 GMT"), now));
+        assert_eq!(Some(now), cache_expiry(Some("no-cache"), None, now));
+        assert_eq!(None, cache_expiry(Some("no-store, max-age=60"), None, now));
+        assert_eq!(Some(784111777), cache_expiry(None, Some("Sun, 06 Nov 1994 08:49:37 GMT"), now));
+        assert_eq!(Some(now), cache_expiry(None, Some("0"), now));
+        assert_eq!(Some(now + DEFAULT_MAX_AGE), cache_expiry(None, None, now));
+    }
+}
@@ -0,0 +1,165 @@
+//
+// Map Projections
+// Copyright (c) 2022 Filip Szczerek <ga.software@yahoo.com>
+//
+// This project is licensed under the terms of the MIT license
+// (see the LICENSE file for details).
+//
+
+//
+// Coordinate reference systems of views, described as ESRI well-known text (as in ".prj" files; understood by GDAL,
+// QGIS and ArcGIS).
+//
+// A view has a standard CRS if its projection has a standard counterpart and the view shows it in the normal aspect:
+// azimuthal projections may be centered anywhere (with north up), others have to be centered on the equator; the view
+// must not be rolled or stretched. The CRS's projected coordinates are those of `ViewBase::grid_coordinates` (meters,
+// with the view's grid parameters), so a view declaring them has to be centered on its grid's origin (the origin
+// of a non-azimuthal projection may lie north or south of the center). Without a grid, the origin is in the center
+// of the view; for the other conic projections and the loximuthal, it lies on the central meridian at the latitude
+// given by their parameters.
+//
+
+use crate::projection::Projection;
+use crate::views::{DragRotation, Grid, ViewBase};
+
+/// Max. distance (degrees) of the center of a view of a non-azimuthal projection from the equator
+/// (and of the center of a view declaring a grid from the grid's origin).
+const MAX_CENTER_LATITUDE: f64 = 1.0e-6;
+
+const WGS84_INVERSE_FLATTENING: f64 = 298.257_223_563;
+
+/// Returns the ESRI WKT of the view's projected coordinate reference system.
+pub fn view_crs(view: &ViewBase) -> Result<String, String> {
+    if view.drag_rotation() != DragRotation::NSEW {
+        return Err("the view has to use the N-S-E-W rotation mode".into());
+    }
+    if view.roll().0 != 0.0 {
+        return Err("the view must not be rolled".into());
+    }
+    if view.stretch().map_or(false, |stretch| stretch != [1.0, 1.0]) {
+        return Err("the view must not be stretched".into());
+    }
+
+    let (center_lon, center_lat) = view.central_lonlat();
+    let (center_lon, center_lat) = (center_lon.0, center_lat.0);
+    let grid = view.grid().unwrap_or(Grid::DEFAULT);
+    let deg = |angle: cgmath::Rad<f64>| cgmath::Deg::from(angle).0;
+
+    let mut azimuthal = false;
+    let mut eccentricity = 0.0;
+    let (name, params): (&str, Vec<(&str, f64)>) = match view.projection() {
+        Projection::AlbersEqualArea{ standard_parallels: [p1, p2], eccentricity: e } => {
+            eccentricity = e;
+            ("Albers", vec![
+                ("Central_Meridian", center_lon),
+                ("Standard_Parallel_1", deg(p1)),
+                ("Standard_Parallel_2", deg(p2)),
+                ("Latitude_Of_Origin", deg((p1 + p2) / 2.0))
+            ])
+        },
+        Projection::CylindricalLambert{ standard_parallel } => ("Cylindrical_Equal_Area", vec![
+            ("Central_Meridian", center_lon),
+            ("Standard_Parallel_1", deg(standard_parallel))
+        ]),
+        Projection::EckertIV => ("Eckert_IV", vec![("Central_Meridian", center_lon)]),
+        Projection::EckertVI => ("Eckert_VI", vec![("Central_Meridian", center_lon)]),
+        Projection::GallStereographic => ("Gall_Stereographic", vec![("Central_Meridian", center_lon)]),
+        Projection::Gnomonic{ .. } => {
+            azimuthal = true;
+            ("Gnomonic", vec![("Longitude_Of_Center", center_lon), ("Latitude_Of_Center", center_lat)])
+        },
+        Projection::Hammer{ axis_ratio } if axis_ratio == 2.0 => {
+            ("Hammer_Aitoff", vec![("Central_Meridian", center_lon)])
+        },
+        Projection::LambertAzimuthalEqualArea => {
+            azimuthal = true;
+            ("Lambert_Azimuthal_Equal_Area", vec![
+                ("Central_Meridian", center_lon),
+                ("Latitude_Of_Origin", center_lat)
+            ])
+        },
+        Projection::LambertConformalConic{ standard_parallels: [p1, p2], eccentricity: e } => {
+            eccentricity = e;
+            ("Lambert_Conformal_Conic", vec![
+                ("Central_Meridian", center_lon),
+                ("Standard_Parallel_1", deg(p1)),
+                ("Standard_Parallel_2", deg(p2)),
+                ("Scale_Factor", grid.scale_factor),
+                ("Latitude_Of_Origin", grid.origin_latitude)
+            ])
+        },
+        Projection::Loximuthal{ central_latitude } => (
+            "Loximuthal", vec![("Central_Meridian", center_lon), ("Central_Parallel", deg(central_latitude))]
+        ),
+        Projection::Mercator{ eccentricity: e } => {
+            eccentricity = e;
+            ("Mercator", vec![("Central_Meridian", center_lon), ("Standard_Parallel_1", 0.0)])
+        },
+        Projection::Miller => ("Miller_Cylindrical", vec![("Central_Meridian", center_lon)]),
+        Projection::Mollweide => ("Mollweide", vec![("Central_Meridian", center_lon)]),
+        Projection::Orthographic => {
+            azimuthal = true;
+            ("Orthographic", vec![("Longitude_Of_Center", center_lon), ("Latitude_Of_Center", center_lat)])
+        },
+        Projection::Stereographic{ point_distance, image_plane } if point_distance == 1.0 && image_plane == 1.0 => {
+            azimuthal = true;
+            ("Stereographic", vec![
+                ("Central_Meridian", center_lon),
+                ("Scale_Factor", grid.scale_factor),
+                ("Latitude_Of_Origin", center_lat)
+            ])
+        },
+        Projection::TransverseMercator{ eccentricity: e } => {
+            eccentricity = e;
+            ("Transverse_Mercator", vec![
+                ("Central_Meridian", center_lon),
+                ("Scale_Factor", grid.scale_factor),
+                ("Latitude_Of_Origin", grid.origin_latitude)
+            ])
+        },
+        projection => return Err(format!("{} has no standard coordinate reference system", projection.name()))
+    };
+    if !azimuthal && center_lat.abs() > MAX_CENTER_LATITUDE {
+        return Err("the view has to be centered on the equator".into());
+    }
+    if let Some(grid) = view.grid() {
+        let lon_offset = (center_lon - grid.central_meridian + 540.0).rem_euclid(360.0) - 180.0;
+        let lat_offset = if azimuthal { center_lat - grid.origin_latitude } else { 0.0 };
+        if lon_offset.abs() > MAX_CENTER_LATITUDE || lat_offset.abs() > MAX_CENTER_LATITUDE {
+            return Err("the view has to be centered on its grid's origin (see the grid parameters)".into());
+        }
+    }
+    if grid.scale_factor != 1.0 && !params.iter().any(|(name, _)| *name == "Scale_Factor") {
+        return Err("the projection does not support a scale factor".into());
+    }
+
+    let body = view.body();
+    let (spheroid, inverse_flattening) = if eccentricity == 0.0 {
+        (format!("{}_Sphere", body.name), 0.0)
+    } else if body.key == "earth" {
+        ("WGS_1984".to_string(), WGS84_INVERSE_FLATTENING)
+    } else {
+        (body.name.to_string(), 1.0 / body.flattening)
+    };
+
+    let mut wkt = format!(
+        concat!(
+            "PROJCS[\"{name}_{spheroid}\",GEOGCS[\"GCS_{spheroid}\",DATUM[\"D_{spheroid}\",",
+            "SPHEROID[\"{spheroid}\",{a},{inverse_flattening}]],PRIMEM[\"Greenwich\",0.0],",
+            "UNIT[\"Degree\",0.0174532925199433]],PROJECTION[\"{name}\"],",
+            "PARAMETER[\"False_Easting\",{false_easting}],PARAMETER[\"False_Northing\",{false_northing}]"
+        ),
+        name = name,
+        spheroid = spheroid,
+        a = body.semi_major_axis,
+        inverse_flattening = inverse_flattening,
+        false_easting = grid.false_easting,
+        false_northing = grid.false_northing
+    );
+    for (name, value) in params {
+        wkt += &format!(",PARAMETER[\"{}\",{}]", name, value);
+    }
+    wkt += ",UNIT[\"Meter\",1.0]]";
+
+    Ok(wkt)
+}